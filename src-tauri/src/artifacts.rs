@@ -0,0 +1,83 @@
+use anyhow::Result;
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// Root directory for files the app produces as a side effect of running
+/// (crash reports, screenshots, generated media). Lives under the app data dir.
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new(app_data_dir: &Path) -> Self {
+        Self {
+            root: app_data_dir.join("artifacts"),
+        }
+    }
+
+    /// Get (and create) a named sub-directory, e.g. `watermarked`.
+    pub fn dir(&self, kind: &str) -> Result<PathBuf> {
+        let dir = self.root.join(kind);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    pub fn crash_dir(&self) -> PathBuf {
+        self.root.join("crash")
+    }
+
+    /// Delete what a publish task left behind: its screenshot directory,
+    /// watermarked copies and captions. Returns the bytes freed.
+    pub fn remove_task_files(&self, task_id: i64) -> u64 {
+        let mut freed = remove_path(
+            &self
+                .root
+                .join("screenshots")
+                .join(format!("task-{}", task_id)),
+        );
+        // `task12-account3.mp4`, `task12.srt`; must not match task120
+        let stem = format!("task{}", task_id);
+        for kind in ["watermarked", "captions"] {
+            let Ok(entries) = std::fs::read_dir(self.root.join(kind)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.split(['.', '-']).next() == Some(stem.as_str()) {
+                    freed += remove_path(&entry.path());
+                }
+            }
+        }
+        freed
+    }
+}
+
+fn path_size(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.is_dir() => std::fs::read_dir(path)
+            .map(|entries| entries.flatten().map(|e| path_size(&e.path())).sum())
+            .unwrap_or(0),
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Remove a file or directory tree; a missing path frees nothing
+fn remove_path(path: &Path) -> u64 {
+    if !path.exists() {
+        return 0;
+    }
+    let size = path_size(path);
+    let removed = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+    match removed {
+        Ok(()) => size,
+        Err(e) => {
+            warn!("Failed to remove artifact {}: {}", path.display(), e);
+            0
+        }
+    }
+}
@@ -1,17 +1,23 @@
+use super::page_ops::{FileChooserOpened, FileInputNode, PageOps, UploadRequestSeen};
+use super::signals::{
+    PageSnapshot, SignalConfirmation, SignalHit, SignalMatcher, UploadSignals, NETWORK_WEIGHT,
+};
+use crate::tasks;
 use anyhow::{bail, Context, Result};
 use chromiumoxide::browser::Browser;
 use chromiumoxide::cdp::browser_protocol::dom::{
     BackendNodeId, GetDocumentParams, QuerySelectorParams, SetFileInputFilesParams,
 };
 use chromiumoxide::cdp::browser_protocol::input::{
-    DispatchDragEventParams, DispatchDragEventType, DispatchMouseEventParams,
-    DispatchMouseEventType, DragData, MouseButton,
+    DispatchDragEventParams, DispatchDragEventType, DispatchKeyEventParams, DispatchKeyEventType,
+    DispatchMouseEventParams, DispatchMouseEventType, DragData, InsertTextParams, MouseButton,
 };
 use chromiumoxide::cdp::browser_protocol::page::{
     EventFileChooserOpened, SetInterceptFileChooserDialogParams,
 };
 use chromiumoxide::page::Page;
-use futures::StreamExt;
+use futures::stream::BoxStream;
+use futures::{FutureExt, StreamExt};
 use log::{info, warn};
 use std::time::{Duration, Instant};
 
@@ -21,6 +27,7 @@ const CDP_TARGET_RETRY_WAIT_SECS: u64 = 3;
 
 pub struct UploadOptions {
     pub platform: &'static str,
+    pub signals: &'static UploadSignals,
     pub candidate_selectors: Vec<&'static str>,
     pub success_timeout_secs: u64,
     pub attempt_timeout_secs: u64,
@@ -47,6 +54,24 @@ pub struct ClickChooserUploadResult {
     pub file_set: bool,
 }
 
+/// 连接到 Chrome 并新开一个标签页打开指定 URL，不复用、不改动用户已有的页面。
+/// 用于巡检类任务，调用方用完后应关闭该页面。
+pub async fn open_probe_page(port: u16, url: &str) -> Result<(Browser, Page)> {
+    let debug_url = format!("http://127.0.0.1:{}", port);
+    let (browser, mut handler) = Browser::connect(&debug_url)
+        .await
+        .context(format!("连接 Chrome 端口 {} 失败", port))?;
+    tasks::global().spawn(format!("cdp_handler:probe:{}", port), async move {
+        while let Some(_event) = handler.next().await {}
+    });
+
+    let page = browser
+        .new_page(url.to_string())
+        .await
+        .context(format!("在端口 {} 新建页面失败：{}", port, url))?;
+    Ok((browser, page))
+}
+
 /// 连接到已运行的 Chrome 实例（通过 CDP）
 pub async fn connect_to_chrome(port: u16, expected_url: &str) -> Result<(Browser, Page)> {
     let debug_url = format!("http://127.0.0.1:{}", port);
@@ -56,7 +81,9 @@ pub async fn connect_to_chrome(port: u16, expected_url: &str) -> Result<(Browser
         .context(format!("连接 Chrome 端口 {} 失败", port))?;
 
     // 启动事件处理器
-    tokio::spawn(async move { while let Some(_event) = handler.next().await {} });
+    tasks::global().spawn(format!("cdp_handler:automation:{}", port), async move {
+        while let Some(_event) = handler.next().await {}
+    });
 
     let expected_host = extract_host(expected_url);
     let strict_target = !expected_host.is_empty();
@@ -300,6 +327,31 @@ async fn redirect_page_to_expected_url(page: &Page, expected_url: &str) -> Resul
     Ok(result)
 }
 
+/// 整页跳转导致原页面会话失效后，按目标 host 重新选择页面。
+/// 跳转后的地址往往已不是上传页（例如 /publish/success），因此只要求 host 匹配。
+pub async fn reselect_target_page(browser: &Browser, expected_url: &str) -> Result<Page> {
+    let expected_host = extract_host(expected_url);
+    let pages =
+        wait_for_pages_or_target(browser, &expected_host, true, CDP_TARGET_RETRY_WAIT_SECS).await?;
+    let selection = select_best_page(&pages, expected_url, &expected_host).await;
+    if selection.score < STRICT_TARGET_SCORE {
+        bail!(
+            "CDP_NO_PAGE: 页面跳转后未找到 {} 的页面。best_url={} tie_break={}",
+            expected_host,
+            selection.url,
+            selection.tie_break
+        );
+    }
+    info!(
+        "[CDP] 重新选择页面 idx={} url={}",
+        selection.idx, selection.url
+    );
+    pages
+        .into_iter()
+        .nth(selection.idx)
+        .context("CDP_NO_PAGE: 重新选择的页面已不存在")
+}
+
 struct PageSelection {
     idx: usize,
     score: i32,
@@ -544,6 +596,11 @@ async fn probe_page(page: &Page, idx: usize, expected_url: &str, expected_host:
 pub async fn upload_file(page: &Page, file_path: &str) -> Result<()> {
     let opts = UploadOptions {
         platform: "generic",
+        signals: &UploadSignals {
+            matchers: &[SignalMatcher::FileInputNonempty],
+            veto_text_markers: &[],
+            deep_scan: false,
+        },
         candidate_selectors: vec!["input[type='file']"],
         success_timeout_secs: 8,
         attempt_timeout_secs: 3,
@@ -583,7 +640,7 @@ pub async fn upload_file_with_strategies(
             continue;
         }
 
-        match set_file_input(page, selector, file_path).await {
+        match set_file_input(page, selector, &[file_path]).await {
             Ok(()) => {
                 info!(
                     "[{}-upload] attempt={} selector={} set_file_ok",
@@ -602,9 +659,10 @@ pub async fn upload_file_with_strategies(
 
         let signal = wait_for_upload_start_signal(
             page,
-            opts.platform,
+            opts.signals,
             opts.attempt_timeout_secs,
             Duration::from_millis(500),
+            false,
         )
         .await;
 
@@ -646,306 +704,78 @@ pub async fn upload_file_with_strategies(
     );
 }
 
-pub async fn wait_for_upload_start_signal(
-    page: &Page,
-    platform: &str,
+/// Poll until corroborating signals confirm the upload started. When time runs
+/// out, a borderline case (e.g. only the file input filled) is still returned,
+/// marked as such, so it stays visible in reports; weaker evidence is dropped.
+///
+/// With `watch_network`, outgoing upload requests count as a conclusive signal
+/// and are checked first; on slow uplinks they show up long before the page
+/// renders any progress.
+pub async fn wait_for_upload_start_signal<P: PageOps>(
+    page: &P,
+    signals: &UploadSignals,
     timeout_secs: u64,
     poll_every: Duration,
+    watch_network: bool,
 ) -> Option<String> {
     let start = Instant::now();
+    let mut confirmation = SignalConfirmation::default();
+    let mut requests = if watch_network {
+        match page.upload_request_listener().await {
+            Ok(requests) => Some(requests),
+            Err(e) => {
+                warn!("[upload-signal] 网络请求监听不可用，仅检测页面信号：{}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
     while start.elapsed() <= Duration::from_secs(timeout_secs) {
-        if let Some(signal) = detect_upload_start_signal(page, platform).await {
-            return Some(signal);
+        let mut hits = requests
+            .as_mut()
+            .map(drain_upload_requests)
+            .unwrap_or_default();
+        hits.extend(detect_upload_start_signals(page, signals).await);
+        let at_ms = start.elapsed().as_millis() as u64;
+        if let Some(confirmed) = confirmation.observe(at_ms, hits) {
+            return Some(confirmed.describe());
         }
         tokio::time::sleep(poll_every).await;
     }
-    None
+    let borderline = confirmation.best_effort()?;
+    warn!(
+        "[upload-signal] 未达到确认分数，按边界信号放行：{}",
+        borderline.describe()
+    );
+    Some(borderline.describe())
 }
 
-async fn detect_upload_start_signal(page: &Page, platform: &str) -> Option<String> {
-    let js = match platform {
-        "douyin" => {
-            r#"
-            (function() {
-                const href = window.location.href || '';
-                if (href.includes('/creator-micro/content/post/video')) {
-                    return 'url:post_video';
-                }
-
-                const fileInputs = Array.from(document.querySelectorAll('input[type="file"]'));
-                for (const input of fileInputs) {
-                    if (input && input.files && input.files.length > 0) {
-                        return 'file:selected:' + input.files.length;
-                    }
-                }
-
-                const progress = document.querySelector('[class*="progress"], .progress-div, [class*="upload-progress"], [class*="percent"]');
-                if (progress) {
-                    const text = ((progress.textContent || '').trim().replace(/\s+/g, ' ')).slice(0, 60);
-                    return text ? ('progress:' + text) : 'progress:visible';
-                }
-
-                const pageText = (document.body && document.body.innerText) ? document.body.innerText : '';
-                if (pageText.includes('上传中') || pageText.includes('处理中') || pageText.includes('转码中') || pageText.includes('校验中')) {
-                    return 'text:uploading';
-                }
-                if (pageText.includes('重新上传') || pageText.includes('更换视频')) {
-                    return 'text:replace-video';
-                }
-                return '';
-            })()
-            "#
-        }
-        "xiaohongshu" => {
-            r#"
-            (function() {
-                const href = window.location.href || '';
-                if (href.includes('/publish/post') || href.includes('/publish/edit') || href.includes('/publish/success')) {
-                    return 'url:post';
-                }
-
-                const fileInputs = Array.from(document.querySelectorAll('input[type="file"]'));
-                for (const input of fileInputs) {
-                    if (input && input.files && input.files.length > 0) {
-                        return 'file:selected:' + input.files.length;
-                    }
-                }
-
-                const progress = document.querySelector('[class*="progress"], [class*="upload-progress"], [class*="percent"], [class*="loading"]');
-                if (progress) {
-                    const text = ((progress.textContent || '').trim().replace(/\s+/g, ' ')).slice(0, 60);
-                    return text ? ('progress:' + text) : 'progress:visible';
-                }
-
-                const pageText = (document.body && document.body.innerText) ? document.body.innerText : '';
-                if (pageText.includes('上传中') || pageText.includes('处理中') || pageText.includes('发布中') || pageText.includes('正在上传')) {
-                    return 'text:uploading';
-                }
-                if (pageText.includes('重新上传') || pageText.includes('更换视频')) {
-                    return 'text:replace-video';
-                }
-                return '';
-            })()
-            "#
-        }
-        "bilibili" => {
-            r#"
-            (function() {
-                const href = window.location.href || '';
-                if (href.includes('/upload-manager') || href.includes('/video/edit') || href.includes('/archive')) {
-                    return 'url:post';
-                }
-
-                const fileInputs = Array.from(document.querySelectorAll('input[type="file"]'));
-                for (const input of fileInputs) {
-                    if (input && input.files && input.files.length > 0) {
-                        return 'file:selected:' + input.files.length;
-                    }
-                }
-
-                const progress = document.querySelector('[class*="progress"], [class*="upload-progress"], [class*="percent"], [class*="uploading"]');
-                if (progress) {
-                    const text = ((progress.textContent || '').trim().replace(/\s+/g, ' ')).slice(0, 60);
-                    return text ? ('progress:' + text) : 'progress:visible';
-                }
-
-                const pageText = (document.body && document.body.innerText) ? document.body.innerText : '';
-                if (pageText.includes('上传中') || pageText.includes('处理中') || pageText.includes('转码中') || pageText.includes('投稿')) {
-                    return 'text:uploading';
-                }
-                return '';
-            })()
-            "#
-        }
-        "wechat" => {
-            r#"
-            (function() {
-                const normalize = (value) => (value || '').replace(/\s+/g, ' ').trim();
-                const progressSelectors = [
-                    "[class*='upload-progress']",
-                    "[class*='uploader']",
-                    "[class*='uploading']",
-                    "[class*='progress']",
-                    "[class*='percent']",
-                    "[class*='loading']"
-                ];
-                const uploadTextMarkers = ['上传中', '处理中', '校验中', '转码中', '发布中', '正在上传', '重新上传', '更换视频'];
-                const initTextMarkers = ['页面初始化中', '初始化中', '正在初始化'];
-                const loginTextMarkers = ['扫码登录', '微信扫码', '请使用微信扫码登录', '请在手机上确认登录'];
-                const maxFrameDepth = 3;
-                const maxShadowDepth = 4;
-
-                function collectFrames(doc, path, depth, result) {
-                    result.push({ doc, path: 'frame:' + path });
-                    if (depth >= maxFrameDepth) return;
-
-                    let iframes = [];
-                    try {
-                        iframes = Array.from(doc.querySelectorAll('iframe'));
-                    } catch (_) {
-                        iframes = [];
-                    }
-
-                    for (let i = 0; i < iframes.length; i += 1) {
-                        let childDoc = null;
-                        try {
-                            childDoc = iframes[i].contentDocument;
-                        } catch (_) {
-                            childDoc = null;
-                        }
-                        if (!childDoc) continue;
-                        collectFrames(childDoc, path + '/' + i, depth + 1, result);
-                    }
-                }
-
-                function collectRoots(root, framePath, shadowPath, depth, roots) {
-                    const context = shadowPath ? (framePath + '|' + shadowPath) : framePath;
-                    roots.push({ root, context });
-                    if (depth >= maxShadowDepth) return;
-
-                    let nodes = [];
-                    try {
-                        nodes = typeof root.querySelectorAll === 'function'
-                            ? Array.from(root.querySelectorAll('*'))
-                            : [];
-                    } catch (_) {
-                        nodes = [];
-                    }
-
-                    for (let i = 0; i < nodes.length; i += 1) {
-                        const el = nodes[i];
-                        if (!el || !el.shadowRoot) continue;
-                        const tag = (el.tagName || 'shadow').toLowerCase();
-                        const nextShadowPath = shadowPath
-                            ? (shadowPath + '/shadow:' + tag + '[' + i + ']')
-                            : ('shadow:' + tag + '[' + i + ']');
-                        collectRoots(el.shadowRoot, framePath, nextShadowPath, depth + 1, roots);
-                    }
-                }
-
-                function probeContext(rootCtx) {
-                    let fileInputs = [];
-                    try {
-                        fileInputs = Array.from(rootCtx.root.querySelectorAll("input[type='file']"));
-                    } catch (_) {
-                        fileInputs = [];
-                    }
-                    for (const input of fileInputs) {
-                        if (input && input.files && input.files.length > 0) {
-                            return 'file:selected:' + input.files.length + '@' + rootCtx.context;
-                        }
-                    }
-
-                    let contextText = '';
-                    try {
-                        contextText = rootCtx.root.body
-                            ? (rootCtx.root.body.innerText || '')
-                            : (rootCtx.root.textContent || '');
-                    } catch (_) {
-                        contextText = '';
-                    }
-                    const normalized = normalize(contextText);
-
-                    for (const marker of initTextMarkers) {
-                        if (marker && normalized.includes(marker)) {
-                            return '';
-                        }
-                    }
-                    for (const marker of loginTextMarkers) {
-                        if (marker && normalized.includes(marker)) {
-                            return '';
-                        }
-                    }
-
-                    for (const selector of progressSelectors) {
-                        let node = null;
-                        try {
-                            node = rootCtx.root.querySelector(selector);
-                        } catch (_) {
-                            node = null;
-                        }
-                        if (!node) continue;
-                        const text = normalize(node.textContent || '').slice(0, 60);
-                        if (text && uploadTextMarkers.some((marker) => text.includes(marker))) {
-                            return ('progress:' + text) + '@' + rootCtx.context;
-                        }
-                    }
-
-                    for (const marker of uploadTextMarkers) {
-                        if (marker && normalized.includes(marker)) {
-                            return 'text:uploading@' + rootCtx.context;
-                        }
-                    }
-                    return '';
-                }
-
-                const frames = [];
-                collectFrames(document, 'top', 0, frames);
-                for (const frameCtx of frames) {
-                    const roots = [];
-                    collectRoots(frameCtx.doc, frameCtx.path, '', 0, roots);
-                    for (const rootCtx of roots) {
-                        const hit = probeContext(rootCtx);
-                        if (hit) return hit;
-                    }
-                }
-                return '';
-            })()
-            "#
-        }
-        "youtube" => {
-            r#"
-            (function() {
-                const href = window.location.href || '';
-                if (href.includes('studio.youtube.com') && (href.includes('/upload') || href.includes('uploading') || href.includes('video_id='))) {
-                    return 'url:upload';
-                }
-
-                const fileInputs = Array.from(document.querySelectorAll('input[type="file"]'));
-                for (const input of fileInputs) {
-                    if (input && input.files && input.files.length > 0) {
-                        return 'file:selected:' + input.files.length;
-                    }
-                }
-
-                const progress = document.querySelector('ytcp-video-upload-progress, ytcp-upload-progress, ytcp-uploads-dialog, [id*="progress"], [class*="progress"], [class*="upload-progress"]');
-                if (progress) {
-                    const text = ((progress.textContent || '').trim().replace(/\s+/g, ' ')).slice(0, 80);
-                    return text ? ('progress:' + text) : 'progress:visible';
-                }
-
-                const pageText = (document.body && document.body.innerText) ? document.body.innerText : '';
-                if (pageText.includes('Uploading') || pageText.includes('Processing') || pageText.includes('Checking') || pageText.includes('Checks complete') || pageText.includes('Upload complete') || pageText.includes('上传中') || pageText.includes('处理中')) {
-                    return 'text:uploading';
-                }
-                return '';
-            })()
-            "#
-        }
-        _ => {
-            r#"
-            (function() {
-                const input = document.querySelector('input[type="file"]');
-                if (input && input.files && input.files.length > 0) {
-                    return 'file:selected';
-                }
-                return '';
-            })()
-            "#
+/// A hit for the first upload request seen since the last poll, without waiting
+fn drain_upload_requests(requests: &mut BoxStream<'static, UploadRequestSeen>) -> Vec<SignalHit> {
+    let mut hits = Vec::new();
+    while let Some(Some(request)) = requests.next().now_or_never() {
+        if hits.is_empty() {
+            hits.push(SignalHit {
+                kind: "network",
+                weight: NETWORK_WEIGHT,
+                signal: format!("network:{}:{}", request.method, extract_host(&request.url)),
+            });
         }
-    };
-
-    let signal: String = page
-        .evaluate(js)
-        .await
-        .map(|v| v.into_value().unwrap_or_else(|_| String::new()))
-        .unwrap_or_default();
-
-    if signal.is_empty() {
-        None
-    } else {
-        Some(signal)
     }
+    hits
+}
+
+async fn detect_upload_start_signals<P: PageOps>(
+    page: &P,
+    signals: &UploadSignals,
+) -> Vec<SignalHit> {
+    let raw = page.evaluate_json(&signals.script()).await.ok();
+    raw.as_ref()
+        .and_then(|value| value.as_str())
+        .and_then(|raw| serde_json::from_str::<PageSnapshot>(raw).ok())
+        .map(|snapshot| signals.hits(&snapshot))
+        .unwrap_or_default()
 }
 
 fn extract_host(url: &str) -> String {
@@ -972,7 +802,13 @@ fn score_url_match(url: &str, expected_url: &str, expected_host: &str) -> i32 {
     0
 }
 
-pub async fn set_file_input(page: &Page, selector: &str, file_path: &str) -> Result<()> {
+/// Set the files of the input matched by `selector` over CDP. Several paths
+/// only work on inputs with the `multiple` attribute.
+pub async fn set_file_input<P: PageOps>(
+    page: &P,
+    selector: &str,
+    file_paths: &[&str],
+) -> Result<()> {
     // Keep inputs interactable in case site toggles hidden state.
     let make_visible_js = format!(
         r#"
@@ -990,25 +826,13 @@ pub async fn set_file_input(page: &Page, selector: &str, file_path: &str) -> Res
         "#,
         escape_js_single(selector)
     );
-    page.evaluate(make_visible_js.as_str()).await.ok();
-
-    let doc = page
-        .execute(GetDocumentParams::builder().depth(0).build())
-        .await
-        .context("获取文档失败")?;
-    let root_node_id = doc.result.root.node_id;
-
-    let query = QuerySelectorParams::new(root_node_id, selector);
-    let query_result = page
-        .execute(query)
-        .await
-        .with_context(|| format!("查询选择器 {} 失败", selector))?;
-
-    let node_id = query_result.result.node_id;
+    page.execute_script(&make_visible_js).await.ok();
 
-    let mut set_files = SetFileInputFilesParams::new(vec![file_path.to_string()]);
-    set_files.node_id = Some(node_id);
-    page.execute(set_files)
+    let node_id = page
+        .query_selector_node(selector)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("选择器 {} 没有匹配的节点", selector))?;
+    page.set_input_files(FileInputNode::Node(node_id), file_paths)
         .await
         .with_context(|| format!("通过 CDP 设置文件失败，选择器 {}", selector))?;
 
@@ -1080,136 +904,703 @@ pub async fn fill_text_input(
         return Ok("skipped_empty".to_string());
     }
 
-    let selectors_js = js_string_array(selectors);
-    let value_json = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
-    let editable_json =
-        serde_json::to_string(&editable_selector.unwrap_or("")).unwrap_or_else(|_| "\"\"".into());
+    let selectors_js = js_string_array(selectors);
+    let value_json = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
+    let editable_json =
+        serde_json::to_string(&editable_selector.unwrap_or("")).unwrap_or_else(|_| "\"\"".into());
+
+    let script = format!(
+        r#"
+        (function() {{
+            const value = {};
+            const selectors = [{}];
+            for (const sel of selectors) {{
+                let el = null;
+                try {{
+                    el = document.querySelector(sel);
+                }} catch (_) {{
+                    el = null;
+                }}
+                if (!el) continue;
+
+                if (typeof el.focus === 'function') el.focus();
+                if ('value' in el) {{
+                    el.value = value;
+                }} else {{
+                    el.textContent = value;
+                }}
+                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return 'input:' + sel;
+            }}
+
+            const editableSelector = {};
+            const editableNodes = editableSelector
+                ? document.querySelectorAll(editableSelector)
+                : document.querySelectorAll('[contenteditable=\"true\"]');
+            for (const el of editableNodes) {{
+                const rect = el.getBoundingClientRect();
+                if (!rect || rect.width < 10 || rect.height < 10) continue;
+                if (typeof el.focus === 'function') el.focus();
+                el.textContent = value;
+                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return 'editable';
+            }}
+
+            return 'not_found';
+        }})()
+        "#,
+        value_json, selectors_js, editable_json
+    );
+
+    let result: String = page
+        .evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
+        .unwrap_or_else(|_| "error".to_string());
+
+    Ok(result)
+}
+
+/// 按附近标签文字定位输入框：先看 placeholder / aria-label，再看 `<label for>`，
+/// 最后按与标签文字的距离打分，取标签下方或右侧最近的可见输入框。
+/// 标签按给定顺序尝试，返回 `label_proximity:<标签>` 或 `not_found`。
+pub async fn fill_by_label_proximity(page: &Page, value: &str, labels: &[&str]) -> Result<String> {
+    if value.trim().is_empty() {
+        return Ok("skipped_empty".to_string());
+    }
+    if labels.is_empty() {
+        return Ok("not_found".to_string());
+    }
+
+    let labels_js = js_string_array(labels);
+    let value_json = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
+    let script = format!(
+        r#"
+        (function() {{
+            const value = {};
+            const labels = [{}];
+            const MAX_DISTANCE = 400;
+            const FILLED_ATTR = 'data-mpp-label-fill';
+
+            const visible = (el) => {{
+                const rect = el.getBoundingClientRect();
+                return rect && rect.width >= 10 && rect.height >= 10;
+            }};
+            const candidates = Array.from(document.querySelectorAll(
+                'input:not([type]), input[type="text"], input[type="search"], textarea, [contenteditable="true"]'
+            )).filter((el) => visible(el) && !el.disabled && !el.readOnly && !el.hasAttribute(FILLED_ATTR));
+            if (!candidates.length) return 'not_found';
+
+            const fill = (el, label) => {{
+                if (typeof el.focus === 'function') el.focus();
+                if ('value' in el) {{
+                    el.value = value;
+                }} else {{
+                    el.textContent = value;
+                }}
+                el.setAttribute(FILLED_ATTR, label);
+                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return 'label_proximity:' + label;
+            }};
+
+            for (const label of labels) {{
+                const direct = candidates.find((el) => {{
+                    const hint = (el.getAttribute('placeholder') || '') + ' '
+                        + (el.getAttribute('aria-label') || '') + ' '
+                        + (el.getAttribute('data-placeholder') || '');
+                    return hint.includes(label);
+                }});
+                if (direct) return fill(direct, label);
+
+                // 只看文字短小的叶子元素，避免整块容器的文字命中
+                const labelNodes = Array.from(document.querySelectorAll('label, span, div, p, h1, h2, h3, h4, dt, th'))
+                    .filter((node) => {{
+                        const text = (node.textContent || '').trim();
+                        return text && text.length <= 20 && text.includes(label)
+                            && node.children.length <= 2 && visible(node);
+                    }});
+
+                for (const node of labelNodes) {{
+                    if (node.tagName === 'LABEL' && node.htmlFor) {{
+                        const target = document.getElementById(node.htmlFor);
+                        if (target && candidates.includes(target)) return fill(target, label);
+                    }}
+                }}
+
+                let best = null;
+                let bestScore = Infinity;
+                for (const node of labelNodes) {{
+                    const from = node.getBoundingClientRect();
+                    for (const el of candidates) {{
+                        const to = el.getBoundingClientRect();
+                        const dx = Math.max(0, to.left - from.right, from.left - to.right);
+                        const dy = Math.max(0, to.top - from.bottom, from.top - to.bottom);
+                        // 输入框在标签上方或左侧时加罚分
+                        const above = to.bottom <= from.top ? 200 : 0;
+                        const left = to.right <= from.left ? 100 : 0;
+                        const score = Math.hypot(dx, dy) + above + left;
+                        if (score < bestScore) {{
+                            bestScore = score;
+                            best = el;
+                        }}
+                    }}
+                }}
+                if (best && bestScore <= MAX_DISTANCE) return fill(best, label);
+            }}
+            return 'not_found';
+        }})()
+        "#,
+        value_json, labels_js
+    );
+
+    let result: String = page
+        .evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
+        .unwrap_or_else(|_| "error".to_string());
+
+    Ok(result)
+}
+
+pub async fn add_tags_via_input(page: &Page, tags: &[String], selectors: &[&str]) -> Result<usize> {
+    if tags.is_empty() || selectors.is_empty() {
+        return Ok(0);
+    }
+
+    let selectors_js = js_string_array(selectors);
+    let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+    let script = format!(
+        r#"
+        (function() {{
+            const selectors = [{}];
+            const tags = {};
+            let added = 0;
+
+            for (const rawTag of tags) {{
+                const tag = (rawTag || '').trim();
+                if (!tag) continue;
+
+                let target = null;
+                for (const sel of selectors) {{
+                    try {{
+                        target = document.querySelector(sel);
+                    }} catch (_) {{
+                        target = null;
+                    }}
+                    if (target) break;
+                }}
+
+                if (!target) continue;
+                if (typeof target.focus === 'function') target.focus();
+
+                if ('value' in target) {{
+                    target.value = tag;
+                }} else {{
+                    target.textContent = tag;
+                }}
+                target.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                target.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                target.dispatchEvent(
+                    new KeyboardEvent('keydown', {{
+                        key: 'Enter',
+                        code: 'Enter',
+                        keyCode: 13,
+                        which: 13,
+                        bubbles: true
+                    }})
+                );
+                target.dispatchEvent(
+                    new KeyboardEvent('keyup', {{
+                        key: 'Enter',
+                        code: 'Enter',
+                        keyCode: 13,
+                        which: 13,
+                        bubbles: true
+                    }})
+                );
+                added += 1;
+            }}
+            return added;
+        }})()
+        "#,
+        selectors_js, tags_json
+    );
+
+    let added: i64 = page
+        .evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or(0))
+        .unwrap_or(0);
+
+    Ok(added.max(0) as usize)
+}
+
+/// 按可见文字点击元素（例如"定时发布"单选项）。
+/// 优先文字完全相等的元素，其次是包含该文字且文本较短的元素，同等条件下点击面积最小的节点。
+pub async fn click_by_text(
+    page: &Page,
+    text_markers: &[&str],
+    scope_selector: Option<&str>,
+) -> Result<String> {
+    if text_markers.is_empty() {
+        bail!("[文字点击] 文字列表为空");
+    }
+
+    let markers_js = js_string_array(text_markers);
+    let scope_json =
+        serde_json::to_string(&scope_selector.unwrap_or("")).unwrap_or_else(|_| "\"\"".into());
+    let script = format!(
+        r#"
+        (function() {{
+            const markers = [{}];
+            const scopeSelector = {};
+            let scope = document;
+            if (scopeSelector) {{
+                try {{
+                    scope = document.querySelector(scopeSelector) || document;
+                }} catch (_) {{
+                    scope = document;
+                }}
+            }}
+            const normalize = (v) => (v || '').replace(/\s+/g, '');
+            const candidates = Array.from(scope.querySelectorAll(
+                'label, button, span, div, a, [role="radio"], [role="button"], [role="tab"]'
+            ));
+            for (const marker of markers) {{
+                const target = normalize(marker);
+                let best = null;
+                let bestScore = Infinity;
+                for (const el of candidates) {{
+                    const text = normalize(el.innerText || el.textContent);
+                    if (!text || !text.includes(target)) continue;
+                    if (text !== target && text.length > target.length * 2 + 4) continue;
+                    const rect = el.getBoundingClientRect();
+                    const style = window.getComputedStyle(el);
+                    if (!rect || rect.width < 4 || rect.height < 4) continue;
+                    if (style && (style.visibility === 'hidden' || style.display === 'none')) continue;
+                    const score = (text === target ? 0 : 1e7) + rect.width * rect.height;
+                    if (score < bestScore) {{
+                        best = el;
+                        bestScore = score;
+                    }}
+                }}
+                if (best) {{
+                    best.scrollIntoView({{ block: 'center' }});
+                    best.click();
+                    return 'text:' + marker;
+                }}
+            }}
+            return 'not_found';
+        }})()
+        "#,
+        markers_js, scope_json
+    );
+
+    let marker: String = page
+        .evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
+        .unwrap_or_else(|_| "error".to_string());
+
+    if marker.starts_with("text:") {
+        return Ok(marker);
+    }
+    bail!(
+        "[文字点击] 未找到可点击的文字元素（markers={} result={}）",
+        text_markers.join(","),
+        marker
+    );
+}
+
+/// 聚焦第一个可见输入框，清空后用 CDP 输入文本，可选按回车确认。
+/// 适用于日期选择器、标签联想框等只响应真实键盘输入、不认 value 赋值的受控组件。
+pub async fn type_into_input(
+    page: &Page,
+    selectors: &[&str],
+    text: &str,
+    press_enter: bool,
+) -> Result<String> {
+    if selectors.is_empty() {
+        bail!("[键盘输入] 选择器列表为空");
+    }
+
+    let selectors_js = js_string_array(selectors);
+    let focus_js = format!(
+        r#"
+        (function() {{
+            const selectors = [{}];
+            for (const sel of selectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    continue;
+                }}
+                for (const el of nodes) {{
+                    const rect = el.getBoundingClientRect();
+                    if (!rect || rect.width < 4 || rect.height < 4) continue;
+                    el.scrollIntoView({{ block: 'center' }});
+                    if (typeof el.click === 'function') el.click();
+                    if (typeof el.focus === 'function') el.focus();
+                    if (typeof el.select === 'function') el.select();
+                    return 'input:' + sel;
+                }}
+            }}
+            return 'not_found';
+        }})()
+        "#,
+        selectors_js
+    );
+
+    let marker: String = page
+        .evaluate(focus_js.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
+        .unwrap_or_else(|_| "error".to_string());
+    if !marker.starts_with("input:") {
+        bail!(
+            "[键盘输入] 未找到可见输入框（selectors={} result={}）",
+            selectors.join(","),
+            marker
+        );
+    }
+
+    page.execute(InsertTextParams::new(text))
+        .await
+        .context("[键盘输入] 发送 insertText 失败")?;
+    if press_enter {
+        cdp_press_enter(page).await?;
+    }
+    Ok(marker)
+}
+
+/// 在下拉/联想列表中点击与文本匹配的选项。
+/// 优先选择同时包含 `preferred_markers`（如"活动"）的选项，其次是文字完全相等的选项；
+/// 没有匹配项时返回 None，由调用方决定是否回车直接输入。
+pub async fn click_matching_option(
+    page: &Page,
+    option_selectors: &[&str],
+    text: &str,
+    preferred_markers: &[&str],
+) -> Result<Option<String>> {
+    if option_selectors.is_empty() {
+        return Ok(None);
+    }
+
+    let selectors_js = js_string_array(option_selectors);
+    let preferred_js = js_string_array(preferred_markers);
+    let text_json = serde_json::to_string(text).unwrap_or_else(|_| "\"\"".to_string());
+    let script = format!(
+        r#"
+        (function() {{
+            const selectors = [{}];
+            const preferred = [{}];
+            const normalize = (v) => (v || '').replace(/\s+/g, '').replace(/^#/, '');
+            const target = normalize({});
+            let exact = null;
+            for (const sel of selectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    continue;
+                }}
+                for (const el of nodes) {{
+                    const rect = el.getBoundingClientRect();
+                    if (!rect || rect.width < 4 || rect.height < 4) continue;
+                    const text = normalize(el.innerText || el.textContent);
+                    if (!text.includes(target)) continue;
+                    const hit = preferred.find((m) => text.includes(normalize(m)));
+                    if (hit) {{
+                        el.click();
+                        return 'preferred:' + hit;
+                    }}
+                    if (!exact && text === target) exact = el;
+                }}
+            }}
+            if (exact) {{
+                exact.click();
+                return 'exact';
+            }}
+            return 'none';
+        }})()
+        "#,
+        selectors_js, preferred_js, text_json
+    );
+
+    let marker: String = page
+        .evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "none".to_string()))
+        .unwrap_or_else(|_| "none".to_string());
+
+    if marker == "none" {
+        Ok(None)
+    } else {
+        Ok(Some(marker))
+    }
+}
+
+pub async fn cdp_press_enter(page: &Page) -> Result<()> {
+    for event_type in [DispatchKeyEventType::KeyDown, DispatchKeyEventType::KeyUp] {
+        let mut event = DispatchKeyEventParams::new(event_type);
+        event.key = Some("Enter".into());
+        event.code = Some("Enter".into());
+        event.windows_virtual_key_code = Some(13);
+        event.native_virtual_key_code = Some(13);
+        page.execute(event)
+            .await
+            .context("[键盘输入] 发送回车失败")?;
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct PageComment {
+    pub index: usize,
+    pub author: String,
+    pub text: String,
+    /// 评论内带有 `own_badge_selectors` 命中的作者标识，即账号本人所发
+    #[serde(default)]
+    pub own: bool,
+}
+
+/// 读取页面上当前渲染出来的评论列表（只读，不滚动加载更多）。
+pub async fn collect_comments(
+    page: &Page,
+    item_selectors: &[&str],
+    author_selectors: &[&str],
+    text_selectors: &[&str],
+    own_badge_selectors: &[&str],
+) -> Result<Vec<PageComment>> {
+    let script = format!(
+        r#"
+        (function() {{
+            const itemSelectors = [{}];
+            const authorSelectors = [{}];
+            const textSelectors = [{}];
+            const ownSelectors = [{}];
+            const has = (root, selectors) => selectors.some((sel) => {{
+                try {{
+                    return !!root.querySelector(sel);
+                }} catch (_) {{
+                    return false;
+                }}
+            }});
+            const pick = (root, selectors) => {{
+                for (const sel of selectors) {{
+                    let el = null;
+                    try {{
+                        el = root.querySelector(sel);
+                    }} catch (_) {{
+                        el = null;
+                    }}
+                    if (el) return (el.innerText || el.textContent || '').trim();
+                }}
+                return '';
+            }};
+            for (const sel of itemSelectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    continue;
+                }}
+                if (!nodes.length) continue;
+                return JSON.stringify(nodes.map((node, index) => ({{
+                    index,
+                    author: pick(node, authorSelectors),
+                    text: pick(node, textSelectors),
+                    own: has(node, ownSelectors),
+                }})).filter((c) => c.text));
+            }}
+            return '[]';
+        }})()
+        "#,
+        js_string_array(item_selectors),
+        js_string_array(author_selectors),
+        js_string_array(text_selectors),
+        js_string_array(own_badge_selectors)
+    );
+
+    let raw: String = page
+        .evaluate(script.as_str())
+        .await
+        .context("[评论读取] 执行脚本失败")?
+        .into_value()
+        .unwrap_or_else(|_| "[]".to_string());
+    Ok(serde_json::from_str(&raw).unwrap_or_default())
+}
 
+/// 在第 `index` 条评论（按 `item_selectors` 第一个命中的选择器计数）内点击"回复"入口。
+pub async fn click_comment_reply(
+    page: &Page,
+    item_selectors: &[&str],
+    index: usize,
+    reply_text_markers: &[&str],
+) -> Result<()> {
     let script = format!(
         r#"
         (function() {{
-            const value = {};
-            const selectors = [{}];
-            for (const sel of selectors) {{
-                let el = null;
+            const itemSelectors = [{}];
+            const markers = [{}];
+            const index = {};
+            for (const sel of itemSelectors) {{
+                let nodes = [];
                 try {{
-                    el = document.querySelector(sel);
+                    nodes = Array.from(document.querySelectorAll(sel));
                 }} catch (_) {{
-                    el = null;
+                    continue;
                 }}
-                if (!el) continue;
-
-                if (typeof el.focus === 'function') el.focus();
-                if ('value' in el) {{
-                    el.value = value;
-                }} else {{
-                    el.textContent = value;
+                if (!nodes.length) continue;
+                const item = nodes[index];
+                if (!item) return 'item_not_found';
+                const candidates = Array.from(item.querySelectorAll('span, button, div, a'));
+                for (const el of candidates) {{
+                    const text = (el.innerText || el.textContent || '').trim();
+                    if (markers.includes(text)) {{
+                        el.click();
+                        return 'clicked';
+                    }}
                 }}
-                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                return 'input:' + sel;
-            }}
-
-            const editableSelector = {};
-            const editableNodes = editableSelector
-                ? document.querySelectorAll(editableSelector)
-                : document.querySelectorAll('[contenteditable=\"true\"]');
-            for (const el of editableNodes) {{
-                const rect = el.getBoundingClientRect();
-                if (!rect || rect.width < 10 || rect.height < 10) continue;
-                if (typeof el.focus === 'function') el.focus();
-                el.textContent = value;
-                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                return 'editable';
+                return 'reply_not_found';
             }}
-
-            return 'not_found';
+            return 'item_not_found';
         }})()
         "#,
-        value_json, selectors_js, editable_json
+        js_string_array(item_selectors),
+        js_string_array(reply_text_markers),
+        index
     );
 
-    let result: String = page
+    let status: String = page
         .evaluate(script.as_str())
         .await
         .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
         .unwrap_or_else(|_| "error".to_string());
-
-    Ok(result)
-}
-
-pub async fn add_tags_via_input(page: &Page, tags: &[String], selectors: &[&str]) -> Result<usize> {
-    if tags.is_empty() || selectors.is_empty() {
-        return Ok(0);
+    if status != "clicked" {
+        bail!(
+            "[评论回复] 未能打开回复框（index={} status={}）",
+            index,
+            status
+        );
     }
+    Ok(())
+}
 
-    let selectors_js = js_string_array(selectors);
-    let tags_json = serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string());
+/// 在第 `index` 条评论容器内执行 `body`（可使用变量 `item`），返回脚本结果。
+/// 回复框与发送按钮都只在该容器内查找，避免误写进其他评论或页面主评论框。
+async fn eval_in_comment(page: &Page, item_selectors: &[&str], index: usize, body: &str) -> String {
     let script = format!(
         r#"
         (function() {{
-            const selectors = [{}];
-            const tags = {};
-            let added = 0;
-
-            for (const rawTag of tags) {{
-                const tag = (rawTag || '').trim();
-                if (!tag) continue;
-
-                let target = null;
+            const itemSelectors = [{}];
+            const index = {};
+            const visible = (el) => {{
+                const rect = el.getBoundingClientRect();
+                const style = window.getComputedStyle(el);
+                return !!rect && rect.width >= 4 && rect.height >= 4
+                    && style.visibility !== 'hidden' && style.display !== 'none';
+            }};
+            const find = (root, selectors) => {{
                 for (const sel of selectors) {{
+                    let nodes = [];
                     try {{
-                        target = document.querySelector(sel);
+                        nodes = Array.from(root.querySelectorAll(sel));
                     }} catch (_) {{
-                        target = null;
+                        continue;
                     }}
-                    if (target) break;
+                    const el = nodes.find(visible);
+                    if (el) return el;
                 }}
-
-                if (!target) continue;
-                if (typeof target.focus === 'function') target.focus();
-
-                if ('value' in target) {{
-                    target.value = tag;
-                }} else {{
-                    target.textContent = tag;
+                return null;
+            }};
+            for (const sel of itemSelectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    continue;
                 }}
-                target.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                target.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                target.dispatchEvent(
-                    new KeyboardEvent('keydown', {{
-                        key: 'Enter',
-                        code: 'Enter',
-                        keyCode: 13,
-                        which: 13,
-                        bubbles: true
-                    }})
-                );
-                target.dispatchEvent(
-                    new KeyboardEvent('keyup', {{
-                        key: 'Enter',
-                        code: 'Enter',
-                        keyCode: 13,
-                        which: 13,
-                        bubbles: true
-                    }})
-                );
-                added += 1;
+                if (!nodes.length) continue;
+                const item = nodes[index];
+                if (!item) return 'item_not_found';
+                {}
             }}
-            return added;
+            return 'item_not_found';
         }})()
         "#,
-        selectors_js, tags_json
+        js_string_array(item_selectors),
+        index,
+        body
     );
+    page.evaluate(script.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
+        .unwrap_or_else(|_| "error".to_string())
+}
 
-    let added: i64 = page
-        .evaluate(script.as_str())
+/// 聚焦第 `index` 条评论内的回复输入框，并用 CDP 输入文本。
+pub async fn type_into_comment_reply(
+    page: &Page,
+    item_selectors: &[&str],
+    index: usize,
+    input_selectors: &[&str],
+    text: &str,
+) -> Result<()> {
+    let body = format!(
+        r#"
+                const input = find(item, [{}]);
+                if (!input) return 'input_not_found';
+                input.scrollIntoView({{ block: 'center' }});
+                if (typeof input.click === 'function') input.click();
+                if (typeof input.focus === 'function') input.focus();
+                return 'focused';
+        "#,
+        js_string_array(input_selectors)
+    );
+    let status = eval_in_comment(page, item_selectors, index, &body).await;
+    if status != "focused" {
+        bail!(
+            "[评论回复] 评论内未找到回复输入框（index={} status={}）",
+            index,
+            status
+        );
+    }
+    page.execute(InsertTextParams::new(text))
         .await
-        .map(|v| v.into_value().unwrap_or(0))
-        .unwrap_or(0);
+        .context("[评论回复] 发送 insertText 失败")?;
+    Ok(())
+}
 
-    Ok(added.max(0) as usize)
+/// 点击第 `index` 条评论内的发送按钮。
+pub async fn click_comment_submit(
+    page: &Page,
+    item_selectors: &[&str],
+    index: usize,
+    submit_selectors: &[&str],
+) -> Result<()> {
+    let body = format!(
+        r#"
+                const button = find(item, [{}]);
+                if (!button) return 'submit_not_found';
+                button.click();
+                return 'clicked';
+        "#,
+        js_string_array(submit_selectors)
+    );
+    let status = eval_in_comment(page, item_selectors, index, &body).await;
+    if status != "clicked" {
+        bail!(
+            "[评论回复] 评论内未找到发送按钮（index={} status={}）",
+            index,
+            status
+        );
+    }
+    Ok(())
 }
 
 fn js_string_array(values: &[&str]) -> String {
@@ -1238,10 +1629,79 @@ pub async fn wait(secs: f64) {
     tokio::time::sleep(Duration::from_secs_f64(secs)).await;
 }
 
-async fn disable_file_chooser_intercept(page: &Page) {
-    let _ = page
-        .execute(SetInterceptFileChooserDialogParams { enabled: false })
-        .await;
+/// Wait for the next chooser event and return the input it belongs to
+async fn wait_for_chooser_node(
+    events: &mut BoxStream<'static, FileChooserOpened>,
+    timeout: Duration,
+) -> Option<BackendNodeId> {
+    match tokio::time::timeout(timeout, events.next()).await {
+        Ok(Some(event)) => {
+            info!(
+                "[文件选择器] 收到文件选择器打开事件：backend_node_id={:?}",
+                event.backend_node_id
+            );
+            event.backend_node_id
+        }
+        Ok(None) => {
+            warn!("[文件选择器] 事件流结束，未收到事件");
+            None
+        }
+        Err(_) => {
+            warn!("[文件选择器] 等待文件选择器事件超时");
+            None
+        }
+    }
+}
+
+async fn disable_file_chooser_intercept<P: PageOps>(page: &P) {
+    let _ = page.set_file_chooser_intercept(false).await;
+}
+
+/// 检查页面里与文件选择相关的原生方法是否被其他扩展或自动化工具改写。
+/// 被改写时 CDP 的文件选择器拦截往往收不到事件；返回被改写的方法，未发现返回 None。
+/// addEventListener 常被监控 SDK 包装，不作为冲突依据。
+async fn detect_chooser_conflict<P: PageOps>(page: &P) -> Option<String> {
+    let script = r#"
+        (function() {
+            const isNative = (fn) => {
+                try {
+                    return typeof fn === 'function'
+                        && Function.prototype.toString.call(fn).includes('[native code]');
+                } catch (_) {
+                    return false;
+                }
+            };
+            const patched = [];
+            if (!isNative(HTMLInputElement.prototype.click)) patched.push('HTMLInputElement.click');
+            if (!isNative(HTMLElement.prototype.click)) patched.push('HTMLElement.click');
+            if (typeof window.showOpenFilePicker === 'function' && !isNative(window.showOpenFilePicker)) {
+                patched.push('showOpenFilePicker');
+            }
+            const files = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, 'files');
+            if (files && files.set && !isNative(files.set)) patched.push('HTMLInputElement.files');
+            return patched.join(',');
+        })()
+    "#;
+    let patched = page
+        .evaluate_json(script)
+        .await
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    if patched.is_empty() {
+        None
+    } else {
+        warn!("[文件选择器] 检测到文件选择相关方法被改写：{}", patched);
+        Some(patched)
+    }
+}
+
+fn chooser_conflict_error(detail: &str, patched: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "CHOOSER_CONFLICT: [文件选择器] 未收到文件选择器事件，页面中的文件选择方法已被其他扩展或自动化工具改写（{} patched={}）",
+        detail,
+        patched
+    )
 }
 
 #[derive(Clone, Debug)]
@@ -1494,8 +1954,8 @@ async fn js_click_geometry_candidate(
 /// 4. 用 backend_node_id 调用 DOM.setFileInputFiles 设置文件
 ///
 /// 这样 Chrome 会触发真正的原生 change/input 事件，React 应用（如抖音）可以检测到。
-pub async fn upload_file_via_file_chooser(
-    page: &Page,
+pub async fn upload_file_via_file_chooser<P: PageOps>(
+    page: &P,
     file_path: &str,
     input_selector: &str,
 ) -> Result<()> {
@@ -1505,15 +1965,14 @@ pub async fn upload_file_via_file_chooser(
     );
 
     // 第1步：启用文件选择器拦截
-    let intercept_params = SetInterceptFileChooserDialogParams { enabled: true };
-    page.execute(intercept_params)
+    page.set_file_chooser_intercept(true)
         .await
         .context("[文件选择器] 启用文件选择器拦截失败")?;
     info!("[文件选择器] 文件选择器拦截已启用");
 
     // 第2步：在点击之前开始监听 EventFileChooserOpened 事件
     let mut event_stream = page
-        .event_listener::<EventFileChooserOpened>()
+        .file_chooser_listener()
         .await
         .context("[文件选择器] 创建事件监听器失败")?;
 
@@ -1538,11 +1997,12 @@ pub async fn upload_file_via_file_chooser(
         }})()"#,
         escape_js_single(input_selector)
     );
-    let click_result: String = page
-        .evaluate(make_clickable_js.as_str())
+    let click_result = page
+        .evaluate_json(&make_clickable_js)
         .await
-        .map(|v| v.into_value().unwrap_or_else(|_| "error".into()))
-        .unwrap_or_else(|_| "error".into());
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "error".into());
     info!("[文件选择器] 点击结果：{}", click_result);
 
     if click_result == "not_found" {
@@ -1552,59 +2012,36 @@ pub async fn upload_file_via_file_chooser(
 
     // 第4步：等待 EventFileChooserOpened 事件（带超时）
     info!("[文件选择器] 等待文件选择器打开事件...");
-    let event = tokio::time::timeout(Duration::from_secs(5), event_stream.next()).await;
-
-    let backend_node_id: Option<BackendNodeId> = match event {
-        Ok(Some(evt)) => {
-            info!(
-                "[文件选择器] 收到文件选择器打开事件：mode={:?}, backend_node_id={:?}",
-                evt.mode, evt.backend_node_id
-            );
-            evt.backend_node_id
-        }
-        Ok(None) => {
-            warn!("[文件选择器] 事件流结束，未收到事件");
-            None
-        }
-        Err(_) => {
-            warn!("[文件选择器] 等待文件选择器事件超时");
-            None
-        }
-    };
+    let backend_node_id = wait_for_chooser_node(&mut event_stream, Duration::from_secs(5)).await;
 
     // 第5步：用 backend_node_id 设置文件（会触发真正的原生事件）
-    let mut set_files = SetFileInputFilesParams::new(vec![file_path.to_string()]);
-    if let Some(bn_id) = backend_node_id {
-        set_files.backend_node_id = Some(bn_id);
-        info!(
-            "[文件选择器] 使用 backend_node_id：{:?}",
-            set_files.backend_node_id
-        );
+    let node = if let Some(bn_id) = backend_node_id {
+        info!("[文件选择器] 使用 backend_node_id：{:?}", bn_id);
+        FileInputNode::Backend(bn_id)
     } else {
         // 降级方案：通过选择器查询节点
         info!("[文件选择器] 没有 backend_node_id，降级使用 querySelector");
-        let doc = page
-            .execute(GetDocumentParams::builder().depth(0).build())
-            .await
-            .context("[文件选择器] 获取文档失败")?;
-        let root_node_id = doc.result.root.node_id;
-        let query = QuerySelectorParams::new(root_node_id, input_selector);
-        let query_result = page
-            .execute(query)
+        let queried = page
+            .query_selector_node(input_selector)
             .await
             .context("[文件选择器] 查询选择器失败")?;
-        if *query_result.result.node_id.inner() <= 0 {
+        let Some(node_id) = queried else {
+            let conflict = detect_chooser_conflict(page).await;
             disable_file_chooser_intercept(page).await;
+            if let Some(patched) = conflict {
+                let detail = format!("selector={}", input_selector);
+                return Err(chooser_conflict_error(&detail, &patched));
+            }
             bail!(
                 "[文件选择器] 通过选择器查询到的节点无效：{}",
                 input_selector
             );
-        }
-        set_files.node_id = Some(query_result.result.node_id);
-    }
+        };
+        FileInputNode::Node(node_id)
+    };
 
     let set_result = page
-        .execute(set_files)
+        .set_input_files(node, &[file_path])
         .await
         .context("[文件选择器] 通过 CDP 设置文件失败");
     disable_file_chooser_intercept(page).await;
@@ -2865,10 +3302,15 @@ pub async fn upload_file_via_click_to_open_file_chooser(
     if let Some(bn_id) = backend_node_id.clone() {
         set_files.backend_node_id = Some(bn_id);
     } else {
+        let conflict = detect_chooser_conflict(page).await;
         if platform == "wechat" {
             let current = current_url(page).await;
             let file_inputs = gather_file_inputs_summary(page).await;
             disable_file_chooser_intercept(page).await;
+            if let Some(patched) = conflict {
+                let detail = format!("platform={} event_state={}", platform, event_state);
+                return Err(chooser_conflict_error(&detail, &patched));
+            }
             bail!(
                 "WECHAT_CHOOSER_NOT_OPENED: [文件选择器-点击触发] 多轮点击后仍未收到文件选择器事件（platform={} event_state={} click_status={} clicked_marker={} frame_count={} frame_path={} shadow_root_count={} clicked_context={} selector_hits={} text_hit_count={} scanned_nodes={} selector_scanned_nodes={} text_scanned_nodes={} hotspot_scanned_nodes={} geometry_scanned_nodes={} blocked_text_hit={} weak_ready_probe={} click_chain={} candidate_summary={} geometry_candidate_count={} geometry_top_summary={} geometry_selected={} geometry_selected_reason={} click_method={} click_round={} human_summary={} current_url={} file_inputs={})",
                 platform,
@@ -2915,6 +3357,10 @@ pub async fn upload_file_via_click_to_open_file_chooser(
             let current = current_url(page).await;
             let file_inputs = gather_file_inputs_summary(page).await;
             disable_file_chooser_intercept(page).await;
+            if let Some(patched) = conflict {
+                let detail = format!("platform={} event_state={}", platform, event_state);
+                return Err(chooser_conflict_error(&detail, &patched));
+            }
             bail!(
                 "[文件选择器-点击触发] 未获取到有效文件输入节点（platform={} event_state={} click_status={} clicked_marker={} frame_count={} frame_path={} shadow_root_count={} clicked_context={} selector_hits={} text_hit_count={} scanned_nodes={} selector_scanned_nodes={} text_scanned_nodes={} hotspot_scanned_nodes={} geometry_scanned_nodes={} blocked_text_hit={} weak_ready_probe={} click_chain={} candidate_summary={} geometry_candidate_count={} geometry_top_summary={} geometry_selected={} geometry_selected_reason={} click_method={} human_summary={} current_url={} file_inputs={})",
                 platform,
@@ -3358,3 +3804,203 @@ pub async fn upload_file_via_drag_drop(
         found_selector, drop_target_source, drop_context, drop_score
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::page_ops::mock::ScriptedPage;
+    use chromiumoxide::cdp::browser_protocol::dom::NodeId;
+    use serde_json::{json, Value};
+
+    /// Occurs only in the signal snapshot script
+    const SNAPSHOT_SCRIPT: &str = "collectShadowRoots";
+
+    const SIGNALS: UploadSignals = UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &["[class*='progress']"],
+                text_markers: &[],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    };
+
+    fn snapshot(files: usize, selector_hits: Value) -> Value {
+        let snapshot = json!({
+            "url": "https://example.com/upload",
+            "contexts": [{
+                "context": "frame:top",
+                "files": files,
+                "text_hits": [],
+                "selector_hits": selector_hits,
+            }],
+        });
+        Value::String(snapshot.to_string())
+    }
+
+    #[tokio::test]
+    async fn upload_signal_waits_for_corroboration() {
+        let page = ScriptedPage::new().on(
+            SNAPSHOT_SCRIPT,
+            vec![
+                snapshot(1, json!([])),
+                snapshot(
+                    1,
+                    json!([{ "selector": "[class*='progress']", "text": "12%" }]),
+                ),
+            ],
+        );
+        let signal =
+            wait_for_upload_start_signal(&page, &SIGNALS, 5, Duration::from_millis(1), false).await;
+        assert_eq!(
+            signal.as_deref(),
+            Some("file:selected:1+progress:12%;confidence=0.70")
+        );
+        assert_eq!(page.script_count(SNAPSHOT_SCRIPT), 2);
+    }
+
+    #[tokio::test]
+    async fn lone_file_signal_is_returned_as_borderline() {
+        let page = ScriptedPage::new().on(SNAPSHOT_SCRIPT, vec![snapshot(1, json!([]))]);
+        let signal =
+            wait_for_upload_start_signal(&page, &SIGNALS, 1, Duration::from_millis(50), false)
+                .await;
+        assert_eq!(
+            signal.as_deref(),
+            Some("file:selected:1;confidence=0.40;borderline")
+        );
+    }
+
+    #[tokio::test]
+    async fn progress_element_alone_never_counts_as_started() {
+        let page = ScriptedPage::new().on(
+            SNAPSHOT_SCRIPT,
+            vec![snapshot(
+                0,
+                json!([{ "selector": "[class*='progress']", "text": "" }]),
+            )],
+        );
+        let signal =
+            wait_for_upload_start_signal(&page, &SIGNALS, 1, Duration::from_millis(50), false)
+                .await;
+        assert_eq!(signal, None);
+    }
+
+    #[tokio::test]
+    async fn failing_evaluation_yields_no_signal() {
+        let page = ScriptedPage::new();
+        let signal =
+            wait_for_upload_start_signal(&page, &SIGNALS, 1, Duration::from_millis(50), false)
+                .await;
+        assert_eq!(signal, None);
+        assert!(page.script_count(SNAPSHOT_SCRIPT) > 1);
+    }
+
+    #[tokio::test]
+    async fn upload_request_confirms_on_its_own_when_watching_network() {
+        let page = ScriptedPage::new()
+            .on(SNAPSHOT_SCRIPT, vec![snapshot(0, json!([]))])
+            .with_upload_request("https://upload.example.com/vod/chunk?part=1");
+        let signal =
+            wait_for_upload_start_signal(&page, &SIGNALS, 5, Duration::from_millis(1), true).await;
+        assert_eq!(
+            signal.as_deref(),
+            Some("network:POST:upload.example.com;confidence=1.00")
+        );
+    }
+
+    #[tokio::test]
+    async fn chooser_event_supplies_backend_node() {
+        let page = ScriptedPage::new().with_chooser_event(FileChooserOpened {
+            backend_node_id: Some(BackendNodeId::new(42)),
+        });
+        let mut events = page.file_chooser_listener().await.unwrap();
+        assert_eq!(
+            wait_for_chooser_node(&mut events, Duration::from_millis(100)).await,
+            Some(BackendNodeId::new(42))
+        );
+        assert_eq!(
+            wait_for_chooser_node(&mut events, Duration::from_millis(100)).await,
+            None
+        );
+    }
+
+    fn file_sets(page: &ScriptedPage) -> Vec<(FileInputNode, Vec<String>)> {
+        page.file_sets
+            .lock()
+            .map(|sets| sets.clone())
+            .unwrap_or_default()
+    }
+
+    fn intercepts(page: &ScriptedPage) -> Vec<bool> {
+        page.intercepts
+            .lock()
+            .map(|i| i.clone())
+            .unwrap_or_default()
+    }
+
+    #[tokio::test]
+    async fn file_chooser_sets_file_on_reported_node() {
+        let page = ScriptedPage::new()
+            .on("input.click()", vec![json!("clicked")])
+            .with_chooser_event(FileChooserOpened {
+                backend_node_id: Some(BackendNodeId::new(7)),
+            });
+        upload_file_via_file_chooser(&page, "/tmp/a.mp4", "input[type='file']")
+            .await
+            .expect("upload");
+        assert_eq!(
+            file_sets(&page),
+            vec![(
+                FileInputNode::Backend(BackendNodeId::new(7)),
+                vec!["/tmp/a.mp4".to_string()]
+            )]
+        );
+        assert_eq!(intercepts(&page), vec![true, false]);
+    }
+
+    #[tokio::test]
+    async fn file_chooser_without_event_falls_back_to_selector() {
+        let page = ScriptedPage::new()
+            .on("input.click()", vec![json!("clicked")])
+            .with_node("input[type='file']");
+        upload_file_via_file_chooser(&page, "/tmp/a.mp4", "input[type='file']")
+            .await
+            .expect("upload");
+        assert_eq!(
+            file_sets(&page),
+            vec![(
+                FileInputNode::Node(NodeId::new(1)),
+                vec!["/tmp/a.mp4".to_string()]
+            )]
+        );
+        assert_eq!(intercepts(&page), vec![true, false]);
+    }
+
+    #[tokio::test]
+    async fn file_chooser_missing_input_turns_interception_off() {
+        let page = ScriptedPage::new().on("input.click()", vec![json!("not_found")]);
+        let err = upload_file_via_file_chooser(&page, "/tmp/a.mp4", "input[type='file']")
+            .await
+            .expect_err("no input");
+        assert!(err.to_string().contains("找不到 input 元素"));
+        assert!(file_sets(&page).is_empty());
+        assert_eq!(intercepts(&page), vec![true, false]);
+    }
+
+    #[tokio::test]
+    async fn file_chooser_reports_patched_page_methods() {
+        let page = ScriptedPage::new()
+            .on("input.click()", vec![json!("clicked")])
+            .on("patched.join", vec![json!("HTMLInputElement.click")]);
+        let err = upload_file_via_file_chooser(&page, "/tmp/a.mp4", "input[type='file']")
+            .await
+            .expect_err("conflict");
+        assert!(err.to_string().starts_with("CHOOSER_CONFLICT:"));
+        assert!(err.to_string().contains("patched=HTMLInputElement.click"));
+        assert_eq!(intercepts(&page), vec![true, false]);
+    }
+}
@@ -0,0 +1,93 @@
+use super::page_handle::PageHandle;
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::network::{
+    EventLoadingFinished, EventRequestWillBeSentExtraInfo, Headers,
+};
+use chromiumoxide::page::Page;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// The upload keeps running in the page after automation hands over; metering
+/// stops once the page has been quiet this long
+pub const SETTLE_IDLE: Duration = Duration::from_secs(60);
+/// Upper bound for metering one run, however busy the page stays
+const MAX_METERING: Duration = Duration::from_secs(3 * 3600);
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BandwidthUsage {
+    /// Request bodies, from the Content-Length of each request sent
+    pub bytes_sent: u64,
+    /// Encoded response bytes
+    pub bytes_received: u64,
+}
+
+enum Traffic {
+    Sent(u64),
+    Received(u64),
+}
+
+/// Counts a page's network traffic from CDP Network events. Events queue up in
+/// the listener until `settle` drains them, so nothing is lost while the
+/// automation is busy with the page.
+pub struct BandwidthMeter {
+    events: BoxStream<'static, Traffic>,
+    /// Keeps the CDP connection, and with it the event streams, alive after
+    /// the automation that opened it has returned
+    connection: Option<PageHandle>,
+}
+
+fn content_length(headers: &Headers) -> u64 {
+    headers
+        .inner()
+        .as_object()
+        .and_then(|headers| {
+            headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        })
+        .and_then(|(_, value)| value.as_str())
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+impl BandwidthMeter {
+    pub async fn start(page: &Page) -> Result<Self> {
+        let sent = page
+            .event_listener::<EventRequestWillBeSentExtraInfo>()
+            .await
+            .context("创建上行流量监听失败")?
+            .map(|event| Traffic::Sent(content_length(&event.headers)));
+        let received = page
+            .event_listener::<EventLoadingFinished>()
+            .await
+            .context("创建下行流量监听失败")?
+            .map(|event| Traffic::Received(event.encoded_data_length.max(0.0) as u64));
+        Ok(Self {
+            events: stream::select(sent, received).boxed(),
+            connection: None,
+        })
+    }
+
+    /// Hold on to the page's browser connection until metering ends
+    pub fn keep_alive(&mut self, page: PageHandle) {
+        self.connection = Some(page);
+    }
+
+    /// Count traffic until none has been seen for `idle`, the page goes away or
+    /// the metering limit is reached. `Duration::ZERO` takes only what has
+    /// already arrived.
+    pub async fn settle(mut self, idle: Duration) -> BandwidthUsage {
+        let started = Instant::now();
+        let mut usage = BandwidthUsage::default();
+        loop {
+            let wait = idle.min(MAX_METERING.saturating_sub(started.elapsed()));
+            match tokio::time::timeout(wait, self.events.next()).await {
+                Ok(Some(Traffic::Sent(bytes))) => usage.bytes_sent += bytes,
+                Ok(Some(Traffic::Received(bytes))) => usage.bytes_received += bytes,
+                Ok(None) | Err(_) => break,
+            }
+        }
+        usage
+    }
+}
@@ -1,3 +1,4 @@
+use super::profile_lock;
 use anyhow::{bail, Context, Result};
 use log::{info, warn};
 use serde::Deserialize;
@@ -13,6 +14,8 @@ const DEBUG_PORT_END: u16 = 9800;
 struct CdpTarget {
     #[serde(rename = "type")]
     target_type: String,
+    #[serde(default)]
+    url: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -105,10 +108,18 @@ pub fn detect_chrome() -> Result<PathBuf> {
     bail!("Could not find Chrome browser. Please install Google Chrome.")
 }
 
+/// Default base directory for Chrome profiles, used unless relocated
+pub fn default_profiles_base_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Cannot find home directory")?;
+    Ok(home.join(".multi-publisher").join("profiles"))
+}
+
 /// Get the base directory for storing Chrome profiles
 pub fn get_profiles_base_dir() -> Result<PathBuf> {
-    let home = dirs::home_dir().context("Cannot find home directory")?;
-    let base = home.join(".multi-publisher").join("profiles");
+    let base = match crate::startup::profiles_dir_override() {
+        Some(dir) => dir,
+        None => default_profiles_base_dir()?,
+    };
     std::fs::create_dir_all(&base)?;
     Ok(base)
 }
@@ -140,11 +151,21 @@ pub fn next_profile_index(platform: &str) -> Result<u32> {
     Ok(max_index + 1)
 }
 
-/// Launch Chrome with a debugging port and return (Child, port)
+/// Title prefix given to an account's Chrome windows, so several open
+/// accounts can be told apart in the taskbar
+pub fn window_name(platform_name: &str, display_name: &str) -> String {
+    format!("{} · {}", platform_name, display_name)
+}
+
+/// Launch Chrome with a debugging port and return (Child, port).
+/// `window_name` labels the new window (`--window-name`); `extra_flags` are the
+/// account's own flags, already checked against the allowlist.
 pub fn launch_chrome_with_debug(
     chrome_path: &Path,
     profile_dir: &Path,
     url: &str,
+    window_name: Option<&str>,
+    extra_flags: &[String],
 ) -> Result<(Child, u16)> {
     let port = allocate_port()?;
     info!(
@@ -154,7 +175,8 @@ pub fn launch_chrome_with_debug(
         url
     );
 
-    let child = Command::new(chrome_path)
+    let mut command = Command::new(chrome_path);
+    command
         .arg(format!("--user-data-dir={}", profile_dir.display()))
         .arg(format!("--remote-debugging-port={}", port))
         .arg("--new-window")
@@ -165,7 +187,12 @@ pub fn launch_chrome_with_debug(
         .arg("--disable-background-timer-throttling")
         .arg("--disable-backgrounding-occluded-windows")
         .arg("--disable-renderer-backgrounding")
-        .arg(format!("--window-size={},{}", 1280, 800))
+        .arg(format!("--window-size={},{}", 1280, 800));
+    if let Some(name) = window_name {
+        command.arg(format!("--window-name={}", name));
+    }
+    command.args(extra_flags);
+    let child = command
         .arg(url)
         .spawn()
         .context("Failed to launch Chrome")?;
@@ -179,14 +206,22 @@ pub fn launch_chrome_with_debug(
     Ok((child, port))
 }
 
-/// Launch Chrome for login (no automation needed)
+/// Launch Chrome for login (no automation needed). The debugging port is only
+/// used to watch the window's URL until the login completes.
 pub fn launch_chrome_for_login(
     chrome_path: &Path,
     profile_dir: &Path,
     login_url: &str,
-) -> Result<Child> {
-    let (child, _port) = launch_chrome_with_debug(chrome_path, profile_dir, login_url)?;
-    Ok(child)
+    window_name: Option<&str>,
+    extra_flags: &[String],
+) -> Result<(Child, u16)> {
+    launch_chrome_with_debug(
+        chrome_path,
+        profile_dir,
+        login_url,
+        window_name,
+        extra_flags,
+    )
 }
 
 /// Ask Chrome to open a URL in a new window for the given profile.
@@ -196,6 +231,7 @@ pub fn open_url_in_profile_new_window(
     chrome_path: &Path,
     profile_dir: &Path,
     url: &str,
+    extra_flags: &[String],
 ) -> Result<()> {
     info!(
         "[Chrome session] request new window profile={} url={}",
@@ -210,6 +246,7 @@ pub fn open_url_in_profile_new_window(
         .arg("--disable-default-apps")
         .arg("--deny-permission-prompts")
         .arg(format!("--window-size={},{}", 1280, 800))
+        .args(extra_flags)
         .arg(url)
         .spawn()
         .context("Failed to request Chrome new window")?;
@@ -226,11 +263,17 @@ pub fn open_url_in_profile_new_window(
 /// Prepare a usable Chrome session for one profile:
 /// - Reuse existing debuggable session when possible.
 /// - If profile is busy but not attachable, return PROFILE_BUSY.
-/// - Otherwise launch a new Chrome instance.
+/// - Otherwise launch a new Chrome instance, labelled with `window_name`.
+///
+/// `_lock` is the caller's lock on `profile_dir`, held for as long as the
+/// session is used.
 pub async fn prepare_chrome_session(
     chrome_path: &Path,
     profile_dir: &Path,
     url: &str,
+    window_name: Option<&str>,
+    extra_flags: &[String],
+    _lock: &profile_lock::ProfileLockGuard,
 ) -> Result<ChromeSession> {
     if let Some(port) = discover_profile_debug_port(profile_dir).await? {
         info!(
@@ -250,7 +293,8 @@ pub async fn prepare_chrome_session(
         );
     }
 
-    let (_child, port) = launch_chrome_with_debug(chrome_path, profile_dir, url)?;
+    let (_child, port) =
+        launch_chrome_with_debug(chrome_path, profile_dir, url, window_name, extra_flags)?;
     Ok(ChromeSession {
         port,
         mode: ChromeSessionMode::LaunchedNew,
@@ -432,7 +476,7 @@ fn singleton_lock_pid(profile_dir: &Path) -> Option<u32> {
 }
 
 #[cfg(unix)]
-fn is_pid_running(pid: u32) -> bool {
+pub fn is_pid_running(pid: u32) -> bool {
     let pid_text = pid.to_string();
     let output = match Command::new("ps")
         .args(["-p", &pid_text, "-o", "pid="])
@@ -448,7 +492,7 @@ fn is_pid_running(pid: u32) -> bool {
 }
 
 #[cfg(not(unix))]
-fn is_pid_running(_pid: u32) -> bool {
+pub fn is_pid_running(_pid: u32) -> bool {
     false
 }
 
@@ -478,6 +522,22 @@ async fn has_page_target(port: u16) -> Result<bool> {
     Ok(targets.iter().any(|target| target.target_type == "page"))
 }
 
+/// URLs of the open pages of the Chrome listening on `port`
+pub async fn page_urls(port: u16) -> Result<Vec<String>> {
+    let list_url = format!("http://127.0.0.1:{}/json/list", port);
+    let targets: Vec<CdpTarget> = reqwest::get(&list_url)
+        .await
+        .context("请求 Chrome json/list 失败")?
+        .json()
+        .await
+        .context("解析 Chrome json/list 失败")?;
+    Ok(targets
+        .into_iter()
+        .filter(|target| target.target_type == "page")
+        .map(|target| target.url)
+        .collect())
+}
+
 /// Delete a Chrome profile directory
 pub fn delete_profile(profile_dir: &Path) -> Result<()> {
     if profile_dir.exists() {
@@ -0,0 +1,39 @@
+use anyhow::{bail, Result};
+
+/// Flags an account may add to its Chrome command line, and whether each
+/// takes a `=value`. Anything that could weaken the profile's security or
+/// clash with automation (debugging port, user data dir) is left out.
+const ALLOWED_FLAGS: &[(&str, bool)] = &[
+    ("--lang", true),
+    ("--accept-lang", true),
+    ("--force-device-scale-factor", true),
+    ("--proxy-server", true),
+    ("--proxy-pac-url", true),
+    ("--proxy-bypass-list", true),
+    ("--no-proxy-server", false),
+    ("--disable-gpu", false),
+    ("--force-dark-mode", false),
+];
+
+/// Check every flag against the allowlist
+pub fn validate(flags: &[String]) -> Result<()> {
+    for flag in flags {
+        let (name, value) = match flag.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (flag.as_str(), None),
+        };
+        let Some((_, takes_value)) = ALLOWED_FLAGS.iter().find(|(allowed, _)| *allowed == name)
+        else {
+            bail!("Chrome flag {} is not allowed", name);
+        };
+        match value {
+            Some(_) if !takes_value => bail!("Chrome flag {} takes no value", name),
+            Some(value) if value.is_empty() || value.chars().any(char::is_control) => {
+                bail!("Chrome flag {} has an invalid value", name)
+            }
+            None if *takes_value => bail!("Chrome flag {} needs a value", name),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use super::chrome;
+use crate::database::queries::{self, Account};
+use crate::tasks;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::Browser;
+use futures::StreamExt;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+/// Off by default: the console runs arbitrary JS inside logged-in platform pages.
+pub const SETTING_DEVTOOLS_CONSOLE_ENABLED: &str = "devtools_console_enabled";
+
+const EVALUATE_TIMEOUT_SECS: u64 = 15;
+
+#[derive(Debug, Deserialize)]
+struct JsonListTarget {
+    id: String,
+    #[serde(rename = "type")]
+    target_type: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+}
+
+/// One open page in an account's debuggable Chrome session
+#[derive(Debug, Clone, Serialize)]
+pub struct DevtoolsPage {
+    pub account_id: i64,
+    pub platform: String,
+    pub port: u16,
+    pub target_id: String,
+    pub title: String,
+    pub url: String,
+}
+
+pub fn ensure_enabled(conn: &Connection) -> Result<()> {
+    if !queries::get_setting_bool(conn, SETTING_DEVTOOLS_CONSOLE_ENABLED, false)? {
+        return Err(anyhow!(
+            "DevTools console is disabled. Enable it in settings first."
+        ));
+    }
+    Ok(())
+}
+
+/// List page targets of every account whose Chrome is currently debuggable
+pub async fn list_pages(accounts: &[Account]) -> Result<Vec<DevtoolsPage>> {
+    let mut pages = Vec::new();
+    for account in accounts {
+        let Some(port) =
+            chrome::discover_profile_debug_port(Path::new(&account.chrome_profile_dir)).await?
+        else {
+            continue;
+        };
+        let list_url = format!("http://127.0.0.1:{}/json/list", port);
+        let targets: Vec<JsonListTarget> = match reqwest::get(&list_url).await {
+            Ok(resp) => resp.json().await.unwrap_or_default(),
+            Err(_) => continue,
+        };
+        pages.extend(
+            targets
+                .into_iter()
+                .filter(|target| target.target_type == "page")
+                .map(|target| DevtoolsPage {
+                    account_id: account.id,
+                    platform: account.platform.clone(),
+                    port,
+                    target_id: target.id,
+                    title: target.title,
+                    url: target.url,
+                }),
+        );
+    }
+    Ok(pages)
+}
+
+/// Evaluate `script` in the page `target_id` of `account`'s Chrome and return
+/// its JSON result. The port is looked up from the account's running profile,
+/// so only sessions the app launched can be reached. The browser is only
+/// detached afterwards, never closed.
+pub async fn evaluate(
+    account: &Account,
+    target_id: &str,
+    script: &str,
+) -> Result<serde_json::Value> {
+    let port = chrome::discover_profile_debug_port(Path::new(&account.chrome_profile_dir))
+        .await?
+        .ok_or_else(|| anyhow!("账号 {} 没有正在运行的 Chrome 调试会话", account.id))?;
+    let debug_url = format!("http://127.0.0.1:{}", port);
+    let (browser, mut handler) = Browser::connect(&debug_url)
+        .await
+        .context(format!("连接 Chrome 端口 {} 失败", port))?;
+    tasks::global().spawn(format!("cdp_handler:devtools:{}", port), async move {
+        while let Some(_event) = handler.next().await {}
+    });
+
+    let pages = browser.pages().await.context("获取页面列表失败")?;
+    let page = pages
+        .into_iter()
+        .find(|page| page.target_id().inner() == target_id)
+        .ok_or_else(|| anyhow!("页面 {} 已不存在", target_id))?;
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(EVALUATE_TIMEOUT_SECS),
+        page.evaluate(script),
+    )
+    .await
+    .map_err(|_| anyhow!("执行超时（{} 秒）", EVALUATE_TIMEOUT_SECS))?
+    .context("执行 JavaScript 失败")?;
+    Ok(result.value().cloned().unwrap_or(serde_json::Value::Null))
+}
@@ -0,0 +1,126 @@
+use crate::tasks;
+use anyhow::{anyhow, Context, Result};
+use chromiumoxide::cdp::browser_protocol::browser::{
+    DownloadProgressState, EventDownloadProgress, EventDownloadWillBegin,
+    SetDownloadBehaviorBehavior, SetDownloadBehaviorParams,
+};
+use chromiumoxide::page::Page;
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use std::collections::HashMap;
+use std::path::Path;
+
+const MAX_NAME_CHARS: usize = 80;
+
+enum Download {
+    Began {
+        guid: String,
+        suggested_filename: String,
+    },
+    Finished {
+        guid: String,
+        completed: bool,
+    },
+}
+
+/// Route files the page downloads (Bilibili cover crop exports, YouTube
+/// processing reports) into `dir` instead of the user's Downloads folder.
+/// Chrome saves each one under its download guid; once complete it is renamed
+/// to `<prefix>-<nn>-<suggested name>`, numbered in the order downloads began.
+/// Chrome restores its own download behavior when the CDP connection closes.
+pub async fn capture(page: &Page, dir: &Path, prefix: &str) -> Result<()> {
+    std::fs::create_dir_all(dir).context("创建下载目录失败")?;
+    let began = page
+        .event_listener::<EventDownloadWillBegin>()
+        .await
+        .context("创建下载开始监听失败")?
+        .map(|event| Download::Began {
+            guid: event.guid.clone(),
+            suggested_filename: event.suggested_filename.clone(),
+        });
+    let progress = page
+        .event_listener::<EventDownloadProgress>()
+        .await
+        .context("创建下载进度监听失败")?
+        .filter_map(|event| async move {
+            let completed = match event.state {
+                DownloadProgressState::Completed => true,
+                DownloadProgressState::Canceled => false,
+                DownloadProgressState::InProgress => return None,
+            };
+            Some(Download::Finished {
+                guid: event.guid.clone(),
+                completed,
+            })
+        });
+    let params = SetDownloadBehaviorParams::builder()
+        .behavior(SetDownloadBehaviorBehavior::AllowAndName)
+        .download_path(dir.to_string_lossy().to_string())
+        .events_enabled(true)
+        .build()
+        .map_err(|e| anyhow!(e))?;
+    page.execute(params).await.context("设置下载目录失败")?;
+
+    let dir = dir.to_path_buf();
+    let prefix = prefix.to_string();
+    let mut events = stream::select(began, progress).boxed();
+    tasks::global().spawn(format!("downloads:{}", prefix), async move {
+        let mut pending = HashMap::new();
+        let mut count = 0;
+        while let Some(event) = events.next().await {
+            match event {
+                Download::Began {
+                    guid,
+                    suggested_filename,
+                } => {
+                    count += 1;
+                    let target = dir.join(file_name(&prefix, count, &suggested_filename));
+                    info!("[下载拦截] 开始下载 {} -> {}", guid, target.display());
+                    pending.insert(guid, target);
+                }
+                Download::Finished { guid, completed } => {
+                    let Some(target) = pending.remove(&guid) else {
+                        continue;
+                    };
+                    finish(&dir.join(&guid), &target, completed);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+fn finish(saved: &Path, target: &Path, completed: bool) {
+    if !completed {
+        let _ = std::fs::remove_file(saved);
+        warn!("[下载拦截] 下载已取消：{}", target.display());
+        return;
+    }
+    match std::fs::rename(saved, target) {
+        Ok(()) => info!("[下载拦截] 已保存 {}", target.display()),
+        Err(e) => warn!(
+            "[下载拦截] 重命名 {} 为 {} 失败：{}",
+            saved.display(),
+            target.display(),
+            e
+        ),
+    }
+}
+
+/// `<prefix>-<nn>-<name>`, with characters that are unsafe in file names replaced
+fn file_name(prefix: &str, index: usize, suggested: &str) -> String {
+    let name = suggested
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    let name = name.trim().trim_start_matches('.');
+    let name = if name.is_empty() { "download" } else { name };
+    let name = name.chars().take(MAX_NAME_CHARS).collect::<String>();
+    format!("{}-{:02}-{}", prefix, index, name)
+}
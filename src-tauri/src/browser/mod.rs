@@ -1,2 +1,12 @@
 pub mod automation;
+pub mod bandwidth;
 pub mod chrome;
+pub mod chrome_flags;
+pub mod devtools;
+pub mod downloads;
+pub mod page_handle;
+pub mod page_ops;
+pub mod prewarm;
+pub mod probe;
+pub mod profile_lock;
+pub mod signals;
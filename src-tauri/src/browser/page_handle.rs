@@ -0,0 +1,164 @@
+use super::automation;
+use super::page_ops::{FileChooserOpened, FileInputNode, PageOps, UploadRequestSeen};
+use anyhow::Result;
+use chromiumoxide::browser::Browser;
+use chromiumoxide::cdp::browser_protocol::dom::NodeId;
+use chromiumoxide::page::Page;
+use futures::stream::BoxStream;
+use log::{info, warn};
+use serde_json::Value;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A live page answers a trivial evaluate well within this
+const LIVENESS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Error texts chromiumoxide surfaces once a page's target or session is gone
+const DETACHED_ERROR_MARKERS: &[&str] = &[
+    "session with given id not found",
+    "no target with given id",
+    "target closed",
+    "execution context was destroyed",
+    "cannot find context with specified id",
+    "receiver is gone",
+    "channel closed",
+];
+
+/// Whether `err` means the page handle went stale rather than the script failing
+pub fn is_detached(err: &anyhow::Error) -> bool {
+    let text = format!("{:#}", err).to_ascii_lowercase();
+    DETACHED_ERROR_MARKERS
+        .iter()
+        .any(|marker| text.contains(marker))
+}
+
+/// The automation's page together with the browser connection it came from.
+/// A full navigation (e.g. Xiaohongshu redirecting to /publish/success) can
+/// leave the page's CDP session stale; the handle then re-selects the target
+/// page by URL and carries on with that one.
+pub struct PageHandle {
+    browser: Browser,
+    page: Mutex<Page>,
+    expected_url: String,
+    reattach_count: AtomicUsize,
+}
+
+impl PageHandle {
+    pub fn new(browser: Browser, page: Page, expected_url: &str) -> Self {
+        Self {
+            browser,
+            page: Mutex::new(page),
+            expected_url: expected_url.to_string(),
+            reattach_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// The current page; a later reattach may replace it
+    pub fn page(&self) -> Page {
+        self.page
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// How often the page had to be re-selected during this run
+    pub fn reattach_count(&self) -> usize {
+        self.reattach_count.load(Ordering::Relaxed)
+    }
+
+    /// Ping the current page and reattach when it no longer answers. Called at
+    /// step boundaries, where a navigation may just have happened.
+    pub async fn ensure_attached(&self) -> Result<Page> {
+        let page = self.page();
+        match tokio::time::timeout(LIVENESS_TIMEOUT, page.evaluate("1")).await {
+            Ok(Ok(_)) => Ok(page),
+            Ok(Err(e)) => {
+                warn!("[CDP] 页面会话不可用，重新选择页面：{}", e);
+                self.reattach().await
+            }
+            Err(_) => {
+                warn!(
+                    "[CDP] 页面 {} 秒内无响应，重新选择页面",
+                    LIVENESS_TIMEOUT.as_secs()
+                );
+                self.reattach().await
+            }
+        }
+    }
+
+    /// Re-select the target page by URL and make it current
+    pub async fn reattach(&self) -> Result<Page> {
+        let page = automation::reselect_target_page(&self.browser, &self.expected_url).await?;
+        *self
+            .page
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = page.clone();
+        let count = self.reattach_count.fetch_add(1, Ordering::Relaxed) + 1;
+        info!(
+            "[CDP] 已重新附加页面（第 {} 次）：{}",
+            count, self.expected_url
+        );
+        Ok(page)
+    }
+
+    /// Run `op` on the current page; when the page turns out to be detached,
+    /// reattach and run it once more
+    async fn with_reattach<T, F, Fut>(&self, op: F) -> Result<T>
+    where
+        F: Fn(Page) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        match op(self.page()).await {
+            Err(e) if is_detached(&e) => {
+                warn!("[CDP] 页面会话已失效，重新附加后重试：{}", e);
+                op(self.reattach().await?).await
+            }
+            result => result,
+        }
+    }
+}
+
+impl PageOps for PageHandle {
+    async fn evaluate_json(&self, script: &str) -> Result<Value> {
+        self.with_reattach(|page| async move { page.evaluate_json(script).await })
+            .await
+    }
+
+    async fn execute_script(&self, script: &str) -> Result<()> {
+        self.with_reattach(|page| async move { page.execute_script(script).await })
+            .await
+    }
+
+    async fn file_chooser_listener(&self) -> Result<BoxStream<'static, FileChooserOpened>> {
+        self.with_reattach(|page| async move { page.file_chooser_listener().await })
+            .await
+    }
+
+    async fn upload_request_listener(&self) -> Result<BoxStream<'static, UploadRequestSeen>> {
+        self.with_reattach(|page| async move { page.upload_request_listener().await })
+            .await
+    }
+
+    async fn set_file_chooser_intercept(&self, enabled: bool) -> Result<()> {
+        self.with_reattach(|page| async move { page.set_file_chooser_intercept(enabled).await })
+            .await
+    }
+
+    async fn query_selector_node(&self, selector: &str) -> Result<Option<NodeId>> {
+        self.with_reattach(|page| async move { page.query_selector_node(selector).await })
+            .await
+    }
+
+    async fn set_input_files(&self, node: FileInputNode, files: &[&str]) -> Result<()> {
+        self.with_reattach(|page| async move { page.set_input_files(node, files).await })
+            .await
+    }
+
+    async fn save_full_screenshot(&self, path: &Path) -> Result<()> {
+        self.with_reattach(|page| async move { page.save_full_screenshot(path).await })
+            .await
+    }
+}
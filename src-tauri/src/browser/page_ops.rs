@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use chromiumoxide::cdp::browser_protocol::dom::{
+    BackendNodeId, GetDocumentParams, NodeId, QuerySelectorParams, SetFileInputFilesParams,
+};
+use chromiumoxide::cdp::browser_protocol::network::{EventRequestWillBeSent, Request};
+use chromiumoxide::cdp::browser_protocol::page::{
+    EventFileChooserOpened, SetInterceptFileChooserDialogParams,
+};
+use chromiumoxide::page::{Page, ScreenshotParams};
+use futures::future;
+use futures::stream::{BoxStream, StreamExt};
+use serde_json::Value;
+use std::future::Future;
+use std::path::Path;
+
+/// The part of a file chooser event the upload strategies use
+#[derive(Debug, Clone, Default)]
+pub struct FileChooserOpened {
+    pub backend_node_id: Option<BackendNodeId>,
+}
+
+/// The file input `DOM.setFileInputFiles` targets: the node a file chooser
+/// event reported, or one found by selector
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileInputNode {
+    Backend(BackendNodeId),
+    Node(NodeId),
+}
+
+/// URL fragments typical of chunked upload endpoints
+const UPLOAD_REQUEST_URL_MARKERS: &[&str] = &["upload", "chunk", "slice"];
+
+/// An outgoing request that looks like it carries file data
+#[derive(Debug, Clone)]
+pub struct UploadRequestSeen {
+    pub method: String,
+    pub url: String,
+}
+
+/// POST/PUT/PATCH with a body, either sent to an upload-looking URL or with a
+/// body CDP does not inline (file parts and large blobs). Small inlined bodies
+/// to other URLs are telemetry and the like.
+fn as_upload_request(request: &Request) -> Option<UploadRequestSeen> {
+    let method = request.method.to_ascii_uppercase();
+    if !matches!(method.as_str(), "POST" | "PUT" | "PATCH") || request.has_post_data != Some(true) {
+        return None;
+    }
+    let url = request.url.to_ascii_lowercase();
+    let body_inlined = request
+        .post_data_entries
+        .as_ref()
+        .is_some_and(|entries| !entries.is_empty() && entries.iter().all(|e| e.bytes.is_some()));
+    let url_hit = UPLOAD_REQUEST_URL_MARKERS
+        .iter()
+        .any(|marker| url.contains(marker));
+    (url_hit || !body_inlined).then(|| UploadRequestSeen {
+        method,
+        url: request.url.clone(),
+    })
+}
+
+/// The few page operations the upload decision logic needs. `Page` implements
+/// it for real runs; tests drive the same logic with a scripted page. Mouse
+/// and keyboard input still take `Page` directly.
+///
+/// Method names avoid `Page`'s inherent `execute`/`event_listener`, which would
+/// otherwise shadow them.
+pub trait PageOps: Sync {
+    /// Evaluate `script` and return its value as JSON
+    fn evaluate_json(&self, script: &str) -> impl Future<Output = Result<Value>> + Send;
+
+    /// Evaluate `script` for its side effects only
+    fn execute_script(&self, script: &str) -> impl Future<Output = Result<()>> + Send;
+
+    /// Stream of file chooser events; only fires while chooser interception is on
+    fn file_chooser_listener(
+        &self,
+    ) -> impl Future<Output = Result<BoxStream<'static, FileChooserOpened>>> + Send;
+
+    /// Stream of outgoing requests that look like file uploads
+    fn upload_request_listener(
+        &self,
+    ) -> impl Future<Output = Result<BoxStream<'static, UploadRequestSeen>>> + Send;
+
+    /// Turn CDP file chooser interception on or off
+    fn set_file_chooser_intercept(&self, enabled: bool) -> impl Future<Output = Result<()>> + Send;
+
+    /// First node matching `selector` in the document; None when nothing matches
+    fn query_selector_node(
+        &self,
+        selector: &str,
+    ) -> impl Future<Output = Result<Option<NodeId>>> + Send;
+
+    /// Hand `files` to the file input `node`
+    fn set_input_files(
+        &self,
+        node: FileInputNode,
+        files: &[&str],
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    /// Full page screenshot written to `path`
+    fn save_full_screenshot(&self, path: &Path) -> impl Future<Output = Result<()>> + Send;
+}
+
+impl PageOps for Page {
+    async fn evaluate_json(&self, script: &str) -> Result<Value> {
+        let result = self.evaluate(script).await?;
+        Ok(result.into_value().unwrap_or(Value::Null))
+    }
+
+    async fn execute_script(&self, script: &str) -> Result<()> {
+        self.evaluate(script).await?;
+        Ok(())
+    }
+
+    async fn file_chooser_listener(&self) -> Result<BoxStream<'static, FileChooserOpened>> {
+        let events = self
+            .event_listener::<EventFileChooserOpened>()
+            .await
+            .context("创建文件选择器事件监听失败")?;
+        Ok(events
+            .map(|event| FileChooserOpened {
+                backend_node_id: event.backend_node_id,
+            })
+            .boxed())
+    }
+
+    async fn upload_request_listener(&self) -> Result<BoxStream<'static, UploadRequestSeen>> {
+        let events = self
+            .event_listener::<EventRequestWillBeSent>()
+            .await
+            .context("创建网络请求监听失败")?;
+        Ok(events
+            .filter_map(|event| future::ready(as_upload_request(&event.request)))
+            .boxed())
+    }
+
+    async fn set_file_chooser_intercept(&self, enabled: bool) -> Result<()> {
+        self.execute(SetInterceptFileChooserDialogParams { enabled })
+            .await?;
+        Ok(())
+    }
+
+    async fn query_selector_node(&self, selector: &str) -> Result<Option<NodeId>> {
+        let doc = self
+            .execute(GetDocumentParams::builder().depth(0).build())
+            .await
+            .context("获取文档失败")?;
+        let query = QuerySelectorParams::new(doc.result.root.node_id, selector);
+        let found = self
+            .execute(query)
+            .await
+            .with_context(|| format!("查询选择器 {} 失败", selector))?;
+        let node_id = found.result.node_id;
+        Ok((*node_id.inner() > 0).then_some(node_id))
+    }
+
+    async fn set_input_files(&self, node: FileInputNode, files: &[&str]) -> Result<()> {
+        let mut params =
+            SetFileInputFilesParams::new(files.iter().map(|path| path.to_string()).collect());
+        match node {
+            FileInputNode::Backend(id) => params.backend_node_id = Some(id),
+            FileInputNode::Node(id) => params.node_id = Some(id),
+        }
+        self.execute(params).await?;
+        Ok(())
+    }
+
+    async fn save_full_screenshot(&self, path: &Path) -> Result<()> {
+        let params = ScreenshotParams::builder().full_page(true).build();
+        self.save_screenshot(params, path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use super::{FileChooserOpened, FileInputNode, PageOps, UploadRequestSeen};
+    use anyhow::{anyhow, Result};
+    use chromiumoxide::cdp::browser_protocol::dom::NodeId;
+    use futures::stream::{self, BoxStream, StreamExt};
+    use serde_json::Value;
+    use std::collections::VecDeque;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    /// Answers scripts from a list of rules: the first rule whose needle occurs
+    /// in the script hands out its next response, repeating the last one once
+    /// the list runs down. Every script is recorded, as are the chooser
+    /// interception switches and the files handed to inputs.
+    #[derive(Default)]
+    pub struct ScriptedPage {
+        rules: Mutex<Vec<(String, VecDeque<Value>)>>,
+        chooser_events: Mutex<Vec<FileChooserOpened>>,
+        upload_requests: Mutex<Vec<UploadRequestSeen>>,
+        nodes: Mutex<Vec<String>>,
+        pub scripts: Mutex<Vec<String>>,
+        pub intercepts: Mutex<Vec<bool>>,
+        pub file_sets: Mutex<Vec<(FileInputNode, Vec<String>)>>,
+    }
+
+    impl ScriptedPage {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn on(self, needle: &str, responses: Vec<Value>) -> Self {
+            if let Ok(mut rules) = self.rules.lock() {
+                rules.push((needle.to_string(), responses.into()));
+            }
+            self
+        }
+
+        pub fn with_chooser_event(self, event: FileChooserOpened) -> Self {
+            if let Ok(mut events) = self.chooser_events.lock() {
+                events.push(event);
+            }
+            self
+        }
+
+        pub fn with_upload_request(self, url: &str) -> Self {
+            if let Ok(mut requests) = self.upload_requests.lock() {
+                requests.push(UploadRequestSeen {
+                    method: "POST".to_string(),
+                    url: url.to_string(),
+                });
+            }
+            self
+        }
+
+        /// Let `selector` resolve to a node; its id is its position plus one
+        pub fn with_node(self, selector: &str) -> Self {
+            if let Ok(mut nodes) = self.nodes.lock() {
+                nodes.push(selector.to_string());
+            }
+            self
+        }
+
+        pub fn script_count(&self, needle: &str) -> usize {
+            self.scripts
+                .lock()
+                .map(|scripts| scripts.iter().filter(|s| s.contains(needle)).count())
+                .unwrap_or(0)
+        }
+
+        fn answer(&self, script: &str) -> Result<Value> {
+            if let Ok(mut scripts) = self.scripts.lock() {
+                scripts.push(script.to_string());
+            }
+            let mut rules = self.rules.lock().map_err(|e| anyhow!(e.to_string()))?;
+            let (_, responses) = rules
+                .iter_mut()
+                .find(|(needle, _)| script.contains(needle.as_str()))
+                .ok_or_else(|| anyhow!("no scripted response"))?;
+            match responses.len() {
+                0 => Err(anyhow!("no scripted response")),
+                1 => Ok(responses[0].clone()),
+                _ => Ok(responses.pop_front().unwrap_or(Value::Null)),
+            }
+        }
+    }
+
+    impl PageOps for ScriptedPage {
+        async fn evaluate_json(&self, script: &str) -> Result<Value> {
+            self.answer(script)
+        }
+
+        async fn execute_script(&self, script: &str) -> Result<()> {
+            if let Ok(mut scripts) = self.scripts.lock() {
+                scripts.push(script.to_string());
+            }
+            Ok(())
+        }
+
+        async fn file_chooser_listener(&self) -> Result<BoxStream<'static, FileChooserOpened>> {
+            let events = self
+                .chooser_events
+                .lock()
+                .map(|mut events| std::mem::take(&mut *events))
+                .unwrap_or_default();
+            Ok(stream::iter(events).boxed())
+        }
+
+        async fn upload_request_listener(&self) -> Result<BoxStream<'static, UploadRequestSeen>> {
+            let requests = self
+                .upload_requests
+                .lock()
+                .map(|mut requests| std::mem::take(&mut *requests))
+                .unwrap_or_default();
+            Ok(stream::iter(requests).boxed())
+        }
+
+        async fn set_file_chooser_intercept(&self, enabled: bool) -> Result<()> {
+            if let Ok(mut intercepts) = self.intercepts.lock() {
+                intercepts.push(enabled);
+            }
+            Ok(())
+        }
+
+        async fn query_selector_node(&self, selector: &str) -> Result<Option<NodeId>> {
+            let nodes = self.nodes.lock().map_err(|e| anyhow!(e.to_string()))?;
+            Ok(nodes
+                .iter()
+                .position(|node| node == selector)
+                .map(|index| NodeId::new(index as i64 + 1)))
+        }
+
+        async fn set_input_files(&self, node: FileInputNode, files: &[&str]) -> Result<()> {
+            if let Ok(mut file_sets) = self.file_sets.lock() {
+                file_sets.push((node, files.iter().map(|f| f.to_string()).collect()));
+            }
+            Ok(())
+        }
+
+        async fn save_full_screenshot(&self, _path: &Path) -> Result<()> {
+            Err(anyhow!("no screenshots on a scripted page"))
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use super::{automation, chrome, profile_lock};
+use crate::database::queries::Account;
+use crate::platforms;
+use crate::tasks;
+use anyhow::{anyhow, bail, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Same readiness budget the publish path gives a fresh Chrome
+const CHROME_READY_TIMEOUT_SECS: u64 = 30;
+/// Longest a publish waits for a pre-warm of the same profile to finish
+const WAIT_LIMIT: Duration = Duration::from_secs(60);
+
+/// Profiles being pre-warmed; the flag turns true once the pre-warm ends
+static WARMING: LazyLock<Mutex<HashMap<PathBuf, watch::Receiver<bool>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// An account that could not be pre-warmed, e.g. because its profile is in use
+#[derive(Debug, Clone, Serialize)]
+pub struct PrewarmSkipped {
+    pub account_id: i64,
+    pub reason: String,
+}
+
+/// Launch (or reuse) the account's Chrome and open its upload page in the
+/// background. The session is left running so the publish run reuses it
+/// instead of launching Chrome and waiting for the page itself.
+pub fn start(chrome_path: &Path, account: &Account) -> Result<()> {
+    let cfg = platforms::publish_config(&account.platform)
+        .ok_or_else(|| anyhow!("Unknown platform: {}", account.platform))?;
+    let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+
+    let (done, watcher) = watch::channel(false);
+    let lock = {
+        let mut warming = WARMING.lock().map_err(|e| anyhow!(e.to_string()))?;
+        if warming.contains_key(&profile_dir) {
+            bail!("该账号正在预热");
+        }
+        let lock =
+            profile_lock::acquire(&profile_dir, "prewarm", &format!("account {}", account.id))?;
+        warming.insert(profile_dir.clone(), watcher);
+        lock
+    };
+
+    let chrome_path = chrome_path.to_path_buf();
+    let window_name = chrome::window_name(
+        &platforms::display_name(&account.platform),
+        &account.display_name,
+    );
+    let flags = account.chrome_flags.clone();
+    let account_id = account.id;
+    let platform = account.platform.clone();
+    tasks::global().spawn(format!("prewarm:{}", account_id), async move {
+        let started = Instant::now();
+        match warm(&chrome_path, &profile_dir, cfg, &window_name, &flags, &lock).await {
+            Ok(url) => info!(
+                "[预热] account={} platform={} 上传页已就绪 url={} elapsed_ms={}",
+                account_id,
+                platform,
+                url,
+                started.elapsed().as_millis()
+            ),
+            Err(e) => warn!(
+                "[预热] account={} platform={} 预热失败：{}",
+                account_id, platform, e
+            ),
+        }
+        // Free the profile before waking a publish waiting for this pre-warm
+        drop(lock);
+        if let Ok(mut warming) = WARMING.lock() {
+            warming.remove(&profile_dir);
+        }
+        let _ = done.send(true);
+    });
+    Ok(())
+}
+
+async fn warm(
+    chrome_path: &Path,
+    profile_dir: &Path,
+    cfg: &platforms::PlatformPublishConfig,
+    window_name: &str,
+    flags: &[String],
+    lock: &profile_lock::ProfileLockGuard,
+) -> Result<String> {
+    let session = chrome::prepare_chrome_session(
+        chrome_path,
+        profile_dir,
+        cfg.upload_url,
+        Some(window_name),
+        flags,
+        lock,
+    )
+    .await?;
+    let port =
+        chrome::wait_for_chrome_ready(&session, profile_dir, CHROME_READY_TIMEOUT_SECS).await?;
+    // Attaching once selects or opens the upload tab; dropping the connection
+    // leaves Chrome and the loaded page for the publish run
+    let (_browser, page) = automation::connect_to_chrome(port, cfg.upload_url).await?;
+    let url = page.url().await?.unwrap_or_default();
+    if platforms::is_login_url(&url, &cfg.login_guard) {
+        bail!("LOGIN_REQUIRED: 上传页跳转到了登录页：{}", url);
+    }
+    Ok(url)
+}
+
+/// Wait for a running pre-warm of this profile, so publishing picks up its
+/// session instead of racing it for the profile
+pub async fn wait_for(profile_dir: &Path) {
+    let watcher = WARMING
+        .lock()
+        .ok()
+        .and_then(|warming| warming.get(profile_dir).cloned());
+    let Some(mut watcher) = watcher else {
+        return;
+    };
+    info!("[预热] 等待预热完成：{}", profile_dir.display());
+    if tokio::time::timeout(WAIT_LIMIT, watcher.wait_for(|done| *done))
+        .await
+        .is_err()
+    {
+        warn!(
+            "[预热] 等待超过 {} 秒，直接继续发布：{}",
+            WAIT_LIMIT.as_secs(),
+            profile_dir.display()
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use super::{automation, chrome, profile_lock};
+use anyhow::Result;
+use chromiumoxide::browser::Browser;
+use chromiumoxide::page::Page;
+use log::warn;
+use std::path::Path;
+
+const CHROME_READY_TIMEOUT_SECS: u64 = 30;
+
+/// A background tab opened in an account's Chrome profile for read-mostly jobs
+/// (health checks, comment monitoring). Reuses a running debuggable Chrome when
+/// there is one and never touches the user's existing tabs.
+pub struct ProbeSession {
+    browser: Browser,
+    pub page: Page,
+    launched_new: bool,
+    /// Keeps the profile locked until the session is dropped
+    _lock: profile_lock::ProfileLockGuard,
+}
+
+impl ProbeSession {
+    pub async fn open(
+        chrome_path: &Path,
+        profile_dir: &Path,
+        url: &str,
+        extra_flags: &[String],
+    ) -> Result<Self> {
+        let lock = profile_lock::acquire(profile_dir, "probe", url)?;
+        let session = chrome::prepare_chrome_session(
+            chrome_path,
+            profile_dir,
+            "about:blank",
+            None,
+            extra_flags,
+            &lock,
+        )
+        .await?;
+        let port =
+            chrome::wait_for_chrome_ready(&session, profile_dir, CHROME_READY_TIMEOUT_SECS).await?;
+        let (browser, page) = automation::open_probe_page(port, url).await?;
+        Ok(Self {
+            browser,
+            page,
+            launched_new: session.mode == chrome::ChromeSessionMode::LaunchedNew,
+            _lock: lock,
+        })
+    }
+
+    /// Close the probe tab, and Chrome itself if this session started it.
+    pub async fn close(mut self) {
+        if let Err(e) = self.page.close().await {
+            warn!("[Probe] 关闭页面失败：{}", e);
+        }
+        if self.launched_new {
+            if let Err(e) = self.browser.close().await {
+                warn!("[Probe] 关闭 Chrome 失败：{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::chrome;
+use anyhow::{bail, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// App-level lock next to Chrome's own Singleton files. Chrome's SingletonLock
+/// only says "a browser is running"; this one says which part of the app owns it.
+const LOCK_FILE: &str = ".multipublisher.lock";
+/// Locks held by this process for longer than this are treated as leftovers
+const STALE_AFTER_HOURS: i64 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileLockInfo {
+    /// `publish` or `login`
+    pub holder: String,
+    /// Process keeping the lock alive: the app itself, or the Chrome it launched
+    pub pid: u32,
+    pub acquired_at: String,
+    pub detail: String,
+    #[serde(default)]
+    pub stale: bool,
+}
+
+fn lock_path(profile_dir: &Path) -> PathBuf {
+    profile_dir.join(LOCK_FILE)
+}
+
+/// Current lock of a profile, with `stale` filled in. None when unlocked.
+pub fn inspect(profile_dir: &Path) -> Option<ProfileLockInfo> {
+    let body = std::fs::read(lock_path(profile_dir)).ok()?;
+    let mut info: ProfileLockInfo = match serde_json::from_slice(&body) {
+        Ok(info) => info,
+        Err(_) => {
+            return Some(ProfileLockInfo {
+                holder: "unknown".to_string(),
+                pid: 0,
+                acquired_at: String::new(),
+                detail: "unreadable lock file".to_string(),
+                stale: true,
+            })
+        }
+    };
+    info.stale = is_stale(&info);
+    Some(info)
+}
+
+fn is_stale(info: &ProfileLockInfo) -> bool {
+    if info.pid != std::process::id() {
+        return !chrome::is_pid_running(info.pid);
+    }
+    chrono::DateTime::parse_from_rfc3339(&info.acquired_at)
+        .map(|at| chrono::Utc::now() - at.with_timezone(&chrono::Utc))
+        .map(|age| age > chrono::Duration::hours(STALE_AFTER_HOURS))
+        .unwrap_or(true)
+}
+
+fn lock_body(holder: &str, pid: u32, detail: &str) -> Result<Vec<u8>> {
+    let info = ProfileLockInfo {
+        holder: holder.to_string(),
+        pid,
+        acquired_at: chrono::Utc::now().to_rfc3339(),
+        detail: detail.to_string(),
+        stale: false,
+    };
+    Ok(serde_json::to_vec_pretty(&info)?)
+}
+
+/// Create the lock file, failing with `AlreadyExists` when someone holds it
+fn create_lock(profile_dir: &Path, body: &[u8]) -> std::io::Result<()> {
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(lock_path(profile_dir))?;
+    file.write_all(body)
+}
+
+fn ensure_free(profile_dir: &Path) -> Result<()> {
+    let Some(info) = inspect(profile_dir) else {
+        return Ok(());
+    };
+    if info.stale {
+        warn!(
+            "[Profile lock] clearing stale lock profile={} holder={} pid={}",
+            profile_dir.display(),
+            info.holder,
+            info.pid
+        );
+        release(profile_dir);
+        return Ok(());
+    }
+    bail!(
+        "PROFILE_LOCKED: 该账号配置正被占用（holder={} pid={} since={}）。请等待其结束，或在账号页解除锁定后重试。",
+        info.holder,
+        info.pid,
+        info.acquired_at
+    );
+}
+
+/// Hold the profile for the duration of an in-app operation such as publishing
+pub struct ProfileLockGuard {
+    profile_dir: PathBuf,
+    handed_off: bool,
+}
+
+impl ProfileLockGuard {
+    /// Keep the profile locked for a Chrome window the user keeps open, e.g.
+    /// the login window. The lock then follows the Chrome process and goes
+    /// stale once it exits, instead of being released with this guard.
+    pub fn hand_to_window(mut self, holder: &str, chrome_pid: u32) -> Result<()> {
+        std::fs::write(
+            lock_path(&self.profile_dir),
+            lock_body(holder, chrome_pid, "")?,
+        )?;
+        self.handed_off = true;
+        Ok(())
+    }
+}
+
+impl Drop for ProfileLockGuard {
+    fn drop(&mut self) {
+        if !self.handed_off {
+            release(&self.profile_dir);
+        }
+    }
+}
+
+/// Lock the profile for this process. Creating the lock file is atomic, so two
+/// callers racing for the same profile can't both win; a stale lock is cleared
+/// and the creation retried once.
+pub fn acquire(profile_dir: &Path, holder: &str, detail: &str) -> Result<ProfileLockGuard> {
+    let body = lock_body(holder, std::process::id(), detail)?;
+    for _ in 0..2 {
+        match create_lock(profile_dir, &body) {
+            Ok(()) => {
+                return Ok(ProfileLockGuard {
+                    profile_dir: profile_dir.to_path_buf(),
+                    handed_off: false,
+                })
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => ensure_free(profile_dir)?,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    bail!("PROFILE_LOCKED: 该账号配置正被占用。请等待其结束，或在账号页解除锁定后重试。");
+}
+
+/// Remove the lock regardless of holder
+pub fn release(profile_dir: &Path) {
+    let path = lock_path(profile_dir);
+    if path.exists() {
+        if let Err(e) = std::fs::remove_file(&path) {
+            warn!("[Profile lock] failed to remove {}: {}", path.display(), e);
+        } else {
+            info!("[Profile lock] released profile={}", profile_dir.display());
+        }
+    }
+}
@@ -0,0 +1,650 @@
+use serde::Deserialize;
+
+/// Longest element text kept in a `progress:<text>` signal
+const SELECTOR_TEXT_LIMIT: usize = 80;
+
+/// One way of recognising that an upload has started. Matchers are tried in
+/// order and the first hit becomes the reported signal.
+#[derive(Debug, Clone, Copy)]
+pub enum SignalMatcher {
+    /// `url:<label>` when the page URL contains any of `patterns`
+    UrlContains {
+        label: &'static str,
+        patterns: &'static [&'static str],
+    },
+    /// `file:selected:<n>` when a file input holds at least one file
+    FileInputNonempty,
+    /// `<label>:<text>` (or `<label>:visible` for an empty element) when one of
+    /// `selectors` matches. With `text_markers`, the matched element's text
+    /// must also contain one of them.
+    SelectorExists {
+        label: &'static str,
+        selectors: &'static [&'static str],
+        text_markers: &'static [&'static str],
+    },
+    /// `text:<label>` when the page text contains any of `markers`
+    TextIncludes {
+        label: &'static str,
+        markers: &'static [&'static str],
+    },
+}
+
+impl SignalMatcher {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::UrlContains { .. } => "url",
+            Self::FileInputNonempty => "file",
+            Self::SelectorExists { .. } => "selector",
+            Self::TextIncludes { .. } => "text",
+        }
+    }
+
+    /// How much one hit of this kind says about the upload having started. The
+    /// editor URL is conclusive on its own; a filled file input or upload text
+    /// need a second signal; a progress-like element is the weakest because
+    /// several pages render one before any file is injected.
+    fn weight(&self) -> f32 {
+        match self {
+            Self::UrlContains { .. } => 1.0,
+            Self::FileInputNonempty => 0.4,
+            Self::TextIncludes { .. } => 0.4,
+            Self::SelectorExists { .. } => 0.3,
+        }
+    }
+}
+
+/// Declarative upload-start detection of one platform
+#[derive(Debug, Clone, Copy)]
+pub struct UploadSignals {
+    pub matchers: &'static [SignalMatcher],
+    /// Page text that means the page is not really there yet (initialising,
+    /// login wall). A context showing one only reports URL and file signals.
+    pub veto_text_markers: &'static [&'static str],
+    /// Also scan same-origin iframes and open shadow roots; every signal then
+    /// carries an `@<context>` suffix naming where it was found
+    pub deep_scan: bool,
+}
+
+/// Facts gathered by the detection script; matchers are evaluated on this in Rust.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageSnapshot {
+    pub url: String,
+    pub contexts: Vec<ContextSnapshot>,
+}
+
+/// One document or shadow root that was scanned
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ContextSnapshot {
+    pub context: String,
+    /// Highest file count over the context's file inputs
+    pub files: usize,
+    /// Text markers present in the context's text
+    pub text_hits: Vec<String>,
+    /// Selectors with at least one match, with the first match's text
+    pub selector_hits: Vec<SelectorHit>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SelectorHit {
+    pub selector: String,
+    pub text: String,
+}
+
+/// One matcher that fired
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalHit {
+    /// `url`, `file`, `selector`, `text`, or `network` for an observed upload request
+    pub kind: &'static str,
+    pub weight: f32,
+    pub signal: String,
+}
+
+/// An observed upload request is as conclusive as an upload URL
+pub const NETWORK_WEIGHT: f32 = 1.0;
+
+/// Score at which corroborating signals confirm that the upload started
+pub const CONFIRM_SCORE: f32 = 0.7;
+/// Scores from here up to `CONFIRM_SCORE` are accepted when the wait runs out,
+/// but reported as borderline
+pub const BORDERLINE_SCORE: f32 = 0.4;
+/// Signals only corroborate each other when seen within this window
+pub const CORROBORATION_WINDOW_MS: u64 = 5000;
+
+/// Signals that together make the case for an upload having started
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedSignal {
+    /// Strongest first
+    pub signals: Vec<String>,
+    pub confidence: f32,
+}
+
+impl ConfirmedSignal {
+    pub fn borderline(&self) -> bool {
+        self.confidence < CONFIRM_SCORE
+    }
+
+    /// `<strongest>+<others>;confidence=<score>`, with `;borderline` appended
+    /// below the confirmation score. The strongest signal stays first so the
+    /// signal source can still be read off the prefix.
+    pub fn describe(&self) -> String {
+        let mut out = format!(
+            "{};confidence={:.2}",
+            self.signals.join("+"),
+            self.confidence
+        );
+        if self.borderline() {
+            out.push_str(";borderline");
+        }
+        out
+    }
+}
+
+/// Accumulates hits across polls. Each kind counts once, with its latest hit;
+/// hits older than `CORROBORATION_WINDOW_MS` drop out.
+#[derive(Debug, Default)]
+pub struct SignalConfirmation {
+    seen: Vec<(u64, SignalHit)>,
+}
+
+impl SignalConfirmation {
+    /// Record the hits of one poll at `at_ms` since the wait began; returns the
+    /// signals once their combined score reaches `CONFIRM_SCORE`
+    pub fn observe(&mut self, at_ms: u64, hits: Vec<SignalHit>) -> Option<ConfirmedSignal> {
+        for hit in hits {
+            match self.seen.iter_mut().find(|(_, seen)| seen.kind == hit.kind) {
+                Some(entry) => *entry = (at_ms, hit),
+                None => self.seen.push((at_ms, hit)),
+            }
+        }
+        self.seen
+            .retain(|(seen_at, _)| at_ms.saturating_sub(*seen_at) <= CORROBORATION_WINDOW_MS);
+        self.current().filter(|signal| !signal.borderline())
+    }
+
+    /// What has been seen so far if it at least reaches `BORDERLINE_SCORE`;
+    /// used once the wait runs out
+    pub fn best_effort(&self) -> Option<ConfirmedSignal> {
+        self.current()
+            .filter(|signal| signal.confidence >= BORDERLINE_SCORE)
+    }
+
+    fn current(&self) -> Option<ConfirmedSignal> {
+        if self.seen.is_empty() {
+            return None;
+        }
+        let mut hits = self.seen.iter().map(|(_, hit)| hit).collect::<Vec<_>>();
+        hits.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        let confidence = hits.iter().map(|hit| hit.weight).sum::<f32>().min(1.0);
+        Some(ConfirmedSignal {
+            signals: hits.iter().map(|hit| hit.signal.clone()).collect(),
+            confidence,
+        })
+    }
+}
+
+/// Gathers a `PageSnapshot` for the selectors and markers it is called with.
+/// The same script serves every platform.
+const SNAPSHOT_JS: &str = r#"
+(function(spec) {
+    const normalize = (value) => (value || '').replace(/\s+/g, ' ').trim();
+    const maxFrameDepth = 3;
+    const maxShadowDepth = 4;
+
+    function collectFrames(doc, path, depth, result) {
+        result.push({ root: doc, context: 'frame:' + path });
+        if (!spec.deep || depth >= maxFrameDepth) return;
+        let iframes = [];
+        try {
+            iframes = Array.from(doc.querySelectorAll('iframe'));
+        } catch (_) {
+            iframes = [];
+        }
+        for (let i = 0; i < iframes.length; i += 1) {
+            let childDoc = null;
+            try {
+                childDoc = iframes[i].contentDocument;
+            } catch (_) {
+                childDoc = null;
+            }
+            if (childDoc) collectFrames(childDoc, path + '/' + i, depth + 1, result);
+        }
+    }
+
+    function collectShadowRoots(root, context, depth, result) {
+        if (depth >= maxShadowDepth) return;
+        let nodes = [];
+        try {
+            nodes = Array.from(root.querySelectorAll('*'));
+        } catch (_) {
+            nodes = [];
+        }
+        for (let i = 0; i < nodes.length; i += 1) {
+            const el = nodes[i];
+            if (!el || !el.shadowRoot) continue;
+            const tag = (el.tagName || 'shadow').toLowerCase();
+            const shadowContext = context + '|shadow:' + tag + '[' + i + ']';
+            result.push({ root: el.shadowRoot, context: shadowContext });
+            collectShadowRoots(el.shadowRoot, shadowContext, depth + 1, result);
+        }
+    }
+
+    const contexts = [];
+    collectFrames(document, 'top', 0, contexts);
+    if (spec.deep) {
+        for (const frame of contexts.slice()) {
+            collectShadowRoots(frame.root, frame.context, 0, contexts);
+        }
+    }
+
+    return JSON.stringify({
+        url: window.location.href || '',
+        contexts: contexts.map((ctx) => {
+            let files = 0;
+            try {
+                for (const input of ctx.root.querySelectorAll("input[type='file']")) {
+                    files = Math.max(files, (input.files && input.files.length) || 0);
+                }
+            } catch (_) {}
+            let text = '';
+            try {
+                text = ctx.root.body ? (ctx.root.body.innerText || '') : (ctx.root.textContent || '');
+            } catch (_) {}
+            text = normalize(text);
+            const selectorHits = [];
+            for (const selector of spec.selectors) {
+                let node = null;
+                try {
+                    node = ctx.root.querySelector(selector);
+                } catch (_) {
+                    node = null;
+                }
+                if (node) {
+                    selectorHits.push({ selector, text: normalize(node.textContent).slice(0, spec.textLimit) });
+                }
+            }
+            return {
+                context: ctx.context,
+                files,
+                text_hits: spec.markers.filter((marker) => marker && text.includes(marker)),
+                selector_hits: selectorHits,
+            };
+        }),
+    });
+})
+"#;
+
+impl UploadSignals {
+    /// The detection script for these signals, ready for `page.evaluate`
+    pub fn script(&self) -> String {
+        let spec = serde_json::json!({
+            "deep": self.deep_scan,
+            "selectors": self.selectors(),
+            "markers": self.markers(),
+            "textLimit": SELECTOR_TEXT_LIMIT,
+        });
+        format!("{}({})", SNAPSHOT_JS.trim(), spec)
+    }
+
+    /// Every matcher that fires, in matcher order, each with the first
+    /// context it fired in
+    pub fn hits(&self, snapshot: &PageSnapshot) -> Vec<SignalHit> {
+        self.matchers
+            .iter()
+            .filter_map(|matcher| {
+                snapshot.contexts.iter().find_map(|ctx| {
+                    let signal = self.match_context(matcher, &snapshot.url, ctx)?;
+                    Some(SignalHit {
+                        kind: matcher.kind(),
+                        weight: matcher.weight(),
+                        signal: if self.deep_scan {
+                            format!("{}@{}", signal, ctx.context)
+                        } else {
+                            signal
+                        },
+                    })
+                })
+            })
+            .collect()
+    }
+
+    fn match_context(
+        &self,
+        matcher: &SignalMatcher,
+        url: &str,
+        ctx: &ContextSnapshot,
+    ) -> Option<String> {
+        let vetoed = ctx
+            .text_hits
+            .iter()
+            .any(|hit| self.veto_text_markers.contains(&hit.as_str()));
+        match matcher {
+            SignalMatcher::UrlContains { label, patterns } => patterns
+                .iter()
+                .any(|pattern| url.contains(pattern))
+                .then(|| format!("url:{}", label)),
+            SignalMatcher::FileInputNonempty => {
+                (ctx.files > 0).then(|| format!("file:selected:{}", ctx.files))
+            }
+            SignalMatcher::SelectorExists { .. } | SignalMatcher::TextIncludes { .. } if vetoed => {
+                None
+            }
+            SignalMatcher::SelectorExists {
+                label,
+                selectors,
+                text_markers,
+            } => {
+                let hit = selectors.iter().find_map(|selector| {
+                    ctx.selector_hits.iter().find(|hit| {
+                        hit.selector == *selector
+                            && (text_markers.is_empty()
+                                || text_markers.iter().any(|m| hit.text.contains(m)))
+                    })
+                })?;
+                let text = if hit.text.is_empty() {
+                    "visible"
+                } else {
+                    &hit.text
+                };
+                Some(format!("{}:{}", label, text))
+            }
+            SignalMatcher::TextIncludes { label, markers } => markers
+                .iter()
+                .any(|marker| ctx.text_hits.iter().any(|hit| hit == marker))
+                .then(|| format!("text:{}", label)),
+        }
+    }
+
+    fn selectors(&self) -> Vec<&'static str> {
+        let mut selectors = Vec::new();
+        for matcher in self.matchers {
+            if let SignalMatcher::SelectorExists {
+                selectors: list, ..
+            } = matcher
+            {
+                for selector in *list {
+                    if !selectors.contains(selector) {
+                        selectors.push(*selector);
+                    }
+                }
+            }
+        }
+        selectors
+    }
+
+    fn markers(&self) -> Vec<&'static str> {
+        let mut markers = self.veto_text_markers.to_vec();
+        for matcher in self.matchers {
+            if let SignalMatcher::TextIncludes { markers: list, .. } = matcher {
+                for marker in *list {
+                    if !markers.contains(marker) {
+                        markers.push(*marker);
+                    }
+                }
+            }
+        }
+        markers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UPLOADING: &[&str] = &["上传中", "处理中"];
+
+    const SIGNALS: UploadSignals = UploadSignals {
+        matchers: &[
+            SignalMatcher::UrlContains {
+                label: "post",
+                patterns: &["/publish/post"],
+            },
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &["[class*='progress']", "[class*='percent']"],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: UPLOADING,
+            },
+        ],
+        veto_text_markers: &["初始化中"],
+        deep_scan: false,
+    };
+
+    fn context(name: &str) -> ContextSnapshot {
+        ContextSnapshot {
+            context: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn snapshot(url: &str, contexts: Vec<ContextSnapshot>) -> PageSnapshot {
+        PageSnapshot {
+            url: url.to_string(),
+            contexts,
+        }
+    }
+
+    /// Strongest-ordered first signal, as the old single-signal detection reported it
+    fn first(signals: &UploadSignals, page: &PageSnapshot) -> Option<String> {
+        signals.hits(page).into_iter().next().map(|hit| hit.signal)
+    }
+
+    fn signal_hit(kind: &'static str, weight: f32, signal: &str) -> SignalHit {
+        SignalHit {
+            kind,
+            weight,
+            signal: signal.to_string(),
+        }
+    }
+
+    fn hit(selector: &str, text: &str) -> SelectorHit {
+        SelectorHit {
+            selector: selector.to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn empty_page_has_no_signal() {
+        let page = snapshot("https://example.com/upload", vec![context("frame:top")]);
+        assert_eq!(first(&SIGNALS, &page), None);
+    }
+
+    #[test]
+    fn url_matcher_wins_by_order() {
+        let mut ctx = context("frame:top");
+        ctx.files = 1;
+        let page = snapshot("https://example.com/publish/post?id=1", vec![ctx]);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("url:post"));
+    }
+
+    #[test]
+    fn file_input_reports_count() {
+        let mut ctx = context("frame:top");
+        ctx.files = 2;
+        let page = snapshot("https://example.com/upload", vec![ctx]);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("file:selected:2"));
+    }
+
+    #[test]
+    fn selector_hit_uses_element_text_or_visible() {
+        let mut ctx = context("frame:top");
+        ctx.selector_hits = vec![hit("[class*='percent']", "")];
+        let page = snapshot("https://example.com/upload", vec![ctx.clone()]);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("progress:visible"));
+
+        ctx.selector_hits = vec![
+            hit("[class*='percent']", "35%"),
+            hit("[class*='progress']", "上传中 35%"),
+        ];
+        let page = snapshot("https://example.com/upload", vec![ctx]);
+        assert_eq!(
+            first(&SIGNALS, &page).as_deref(),
+            Some("progress:上传中 35%")
+        );
+    }
+
+    #[test]
+    fn selector_text_markers_filter_hits() {
+        const STRICT: UploadSignals = UploadSignals {
+            matchers: &[SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &["[class*='loading']"],
+                text_markers: UPLOADING,
+            }],
+            veto_text_markers: &[],
+            deep_scan: false,
+        };
+        let mut ctx = context("frame:top");
+        ctx.selector_hits = vec![hit("[class*='loading']", "加载中")];
+        let page = snapshot("https://example.com", vec![ctx.clone()]);
+        assert_eq!(first(&STRICT, &page), None);
+
+        ctx.selector_hits = vec![hit("[class*='loading']", "处理中")];
+        let page = snapshot("https://example.com", vec![ctx]);
+        assert_eq!(first(&STRICT, &page).as_deref(), Some("progress:处理中"));
+    }
+
+    #[test]
+    fn text_matcher_needs_a_reported_marker() {
+        let mut ctx = context("frame:top");
+        ctx.text_hits = vec!["处理中".to_string()];
+        let page = snapshot("https://example.com", vec![ctx]);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("text:uploading"));
+    }
+
+    #[test]
+    fn veto_blocks_page_signals_but_not_files() {
+        let mut ctx = context("frame:top");
+        ctx.text_hits = vec!["初始化中".to_string(), "上传中".to_string()];
+        ctx.selector_hits = vec![hit("[class*='progress']", "")];
+        let page = snapshot("https://example.com", vec![ctx.clone()]);
+        assert_eq!(first(&SIGNALS, &page), None);
+
+        ctx.files = 1;
+        let page = snapshot("https://example.com", vec![ctx]);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("file:selected:1"));
+    }
+
+    #[test]
+    fn deep_scan_tags_the_matching_context() {
+        const DEEP: UploadSignals = UploadSignals {
+            deep_scan: true,
+            ..SIGNALS
+        };
+        let top = context("frame:top");
+        let mut shadow = context("frame:top|shadow:wujie-app[3]");
+        shadow.text_hits = vec!["上传中".to_string()];
+        let page = snapshot("https://example.com", vec![top, shadow]);
+        assert_eq!(
+            first(&DEEP, &page).as_deref(),
+            Some("text:uploading@frame:top|shadow:wujie-app[3]")
+        );
+    }
+
+    #[test]
+    fn script_carries_deduplicated_selectors_and_markers() {
+        assert_eq!(
+            SIGNALS.selectors(),
+            vec!["[class*='progress']", "[class*='percent']"]
+        );
+        assert_eq!(SIGNALS.markers(), vec!["初始化中", "上传中", "处理中"]);
+        let script = SIGNALS.script();
+        assert!(script.contains("\"deep\":false"));
+        assert!(script.contains("初始化中"));
+    }
+
+    #[test]
+    fn snapshot_deserializes_script_output() {
+        let raw = r#"{"url":"https://x/y","contexts":[{"context":"frame:top","files":1,"text_hits":["上传中"],"selector_hits":[{"selector":"a","text":"b"}]}]}"#;
+        let page: PageSnapshot = serde_json::from_str(raw).unwrap();
+        assert_eq!(page.contexts[0].files, 1);
+        assert_eq!(first(&SIGNALS, &page).as_deref(), Some("file:selected:1"));
+    }
+
+    #[test]
+    fn hits_report_every_matcher_with_its_weight() {
+        let mut ctx = context("frame:top");
+        ctx.files = 1;
+        ctx.text_hits = vec!["上传中".to_string()];
+        let page = snapshot("https://example.com/upload", vec![ctx]);
+        let hits = SIGNALS.hits(&page);
+        assert_eq!(
+            hits,
+            vec![
+                signal_hit("file", 0.4, "file:selected:1"),
+                signal_hit("text", 0.4, "text:uploading"),
+            ]
+        );
+    }
+
+    #[test]
+    fn progress_element_alone_is_not_enough() {
+        let mut confirmation = SignalConfirmation::default();
+        let progress = signal_hit("selector", 0.3, "progress:visible");
+        assert_eq!(confirmation.observe(0, vec![progress.clone()]), None);
+        assert_eq!(confirmation.observe(2000, vec![progress]), None);
+        assert_eq!(confirmation.best_effort(), None);
+    }
+
+    #[test]
+    fn corroborating_signals_confirm_across_polls() {
+        let mut confirmation = SignalConfirmation::default();
+        let file = signal_hit("file", 0.4, "file:selected:1");
+        let progress = signal_hit("selector", 0.3, "progress:12%");
+        assert_eq!(confirmation.observe(0, vec![file]), None);
+        let confirmed = confirmation.observe(1500, vec![progress]).unwrap();
+        assert_eq!(confirmed.signals, vec!["file:selected:1", "progress:12%"]);
+        assert!((confirmed.confidence - 0.7).abs() < 1e-6);
+        assert_eq!(
+            confirmed.describe(),
+            "file:selected:1+progress:12%;confidence=0.70"
+        );
+    }
+
+    #[test]
+    fn stale_signals_do_not_corroborate() {
+        let mut confirmation = SignalConfirmation::default();
+        let file = signal_hit("file", 0.4, "file:selected:1");
+        let progress = signal_hit("selector", 0.3, "progress:12%");
+        confirmation.observe(0, vec![file]);
+        assert_eq!(
+            confirmation.observe(CORROBORATION_WINDOW_MS + 1, vec![progress]),
+            None
+        );
+    }
+
+    #[test]
+    fn url_signal_is_conclusive_and_score_is_capped() {
+        let mut confirmation = SignalConfirmation::default();
+        let confirmed = confirmation
+            .observe(
+                0,
+                vec![
+                    signal_hit("file", 0.4, "file:selected:1"),
+                    signal_hit("url", 1.0, "url:post"),
+                ],
+            )
+            .unwrap();
+        assert_eq!(confirmed.signals[0], "url:post");
+        assert_eq!(confirmed.confidence, 1.0);
+    }
+
+    #[test]
+    fn single_file_signal_is_borderline_on_timeout() {
+        let mut confirmation = SignalConfirmation::default();
+        assert_eq!(
+            confirmation.observe(0, vec![signal_hit("file", 0.4, "file:selected:1")]),
+            None
+        );
+        let best = confirmation.best_effort().unwrap();
+        assert!(best.borderline());
+        assert_eq!(
+            best.describe(),
+            "file:selected:1;confidence=0.40;borderline"
+        );
+    }
+}
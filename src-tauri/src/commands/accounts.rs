@@ -1,23 +1,39 @@
-use crate::browser::chrome;
+use crate::browser::probe::ProbeSession;
+use crate::browser::profile_lock::{self, ProfileLockInfo};
+use crate::browser::{chrome, chrome_flags};
+use crate::content::title_variation::{self, TitleVariation};
+use crate::content::variables;
 use crate::database::queries;
-use crate::database::Database;
+use crate::database::{Database, Storage};
+use crate::health::{account_test, login_status, session_expiry};
+use crate::media::watermark::WatermarkConfig;
 use crate::platforms;
-use tauri::State;
+use crate::platforms::wechat::{self, WechatCollection};
+use crate::platforms::CustomFields;
+use crate::scheduler::window::PublishWindows;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+/// Time for the WeChat Channels create page to render before opening the collection picker
+const WECHAT_PAGE_SETTLE_SECS: u64 = 6;
+/// Upper bound for one add_accounts_bulk call
+const MAX_BULK_ACCOUNTS: u32 = 50;
 
 #[tauri::command]
-pub fn get_accounts(db: State<'_, Database>) -> Result<Vec<queries::Account>, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    queries::get_all_accounts(&conn).map_err(|e| e.to_string())
+pub fn get_accounts(storage: State<'_, Arc<dyn Storage>>) -> Result<Vec<queries::Account>, String> {
+    storage.get_all_accounts().map_err(|e| e.to_string())
 }
 
 #[tauri::command]
 pub fn add_account(
-    db: State<'_, Database>,
+    storage: State<'_, Arc<dyn Storage>>,
     platform: String,
     display_name: String,
 ) -> Result<queries::Account, String> {
     // Validate platform
-    let platform_info = platforms::get_platform_info(&platform)
+    let platform_info = platforms::available_platform_info(&platform)
         .ok_or_else(|| format!("Unknown platform: {}", platform))?;
 
     // Create Chrome profile directory
@@ -26,13 +42,13 @@ pub fn add_account(
     let profile_dir_str = profile_dir.to_string_lossy().to_string();
 
     // Insert into database
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
     let name = if display_name.is_empty() {
-        format!("{} 账号 {}", platform_info.name, index)
+        format!("{} {}", default_account_prefix(&platform_info), index)
     } else {
         display_name
     };
-    let id = queries::insert_account(&conn, &platform, &name, &profile_dir_str)
+    let id = storage
+        .insert_account(&platform, &name, &profile_dir_str)
         .map_err(|e| e.to_string())?;
 
     // Return the created account
@@ -45,13 +61,94 @@ pub fn add_account(
         is_logged_in: false,
         last_checked_at: None,
         created_at: chrono::Utc::now().to_rfc3339(),
+        chrome_flags: Vec::new(),
     })
 }
 
+/// `<platform> 账号`, or `<platform> account` with the English locale
+fn default_account_prefix(info: &platforms::PlatformInfo) -> String {
+    if platforms::english() {
+        format!("{} account", info.name_en)
+    } else {
+        format!("{} 账号", info.name)
+    }
+}
+
+/// Create `count` accounts of one platform with their profile directories.
+/// Names are `<name_prefix> <n>` (default `<platform> 账号 <n>`, localized) where n is the
+/// profile index. Nothing is kept if any account fails to be created.
 #[tauri::command]
-pub fn delete_account(db: State<'_, Database>, account_id: i64) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    let profile_dir = queries::delete_account(&conn, account_id).map_err(|e| e.to_string())?;
+pub fn add_accounts_bulk(
+    storage: State<'_, Arc<dyn Storage>>,
+    platform: String,
+    count: u32,
+    name_prefix: Option<String>,
+) -> Result<Vec<queries::Account>, String> {
+    let platform_info = platforms::available_platform_info(&platform)
+        .ok_or_else(|| format!("Unknown platform: {}", platform))?;
+    if count == 0 || count > MAX_BULK_ACCOUNTS {
+        return Err(format!("Count must be between 1 and {}", MAX_BULK_ACCOUNTS));
+    }
+    let prefix = name_prefix
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| default_account_prefix(&platform_info));
+
+    let first_index = chrome::next_profile_index(&platform).map_err(|e| e.to_string())?;
+    let mut created = Vec::new();
+    for index in first_index..first_index + count {
+        match chrome::create_profile_dir(&platform, index) {
+            Ok(dir) => created.push((
+                format!("{} {}", prefix, index),
+                dir.to_string_lossy().to_string(),
+            )),
+            Err(e) => {
+                remove_profile_dirs(&created);
+                return Err(e.to_string());
+            }
+        }
+    }
+
+    let ids = match storage.insert_accounts(&platform, &created) {
+        Ok(ids) => ids,
+        Err(e) => {
+            remove_profile_dirs(&created);
+            return Err(e.to_string());
+        }
+    };
+    let created_at = chrono::Utc::now().to_rfc3339();
+    Ok(ids
+        .into_iter()
+        .zip(created)
+        .map(
+            |(id, (display_name, chrome_profile_dir))| queries::Account {
+                id,
+                platform: platform.clone(),
+                display_name,
+                avatar_url: None,
+                chrome_profile_dir,
+                is_logged_in: false,
+                last_checked_at: None,
+                created_at: created_at.clone(),
+                chrome_flags: Vec::new(),
+            },
+        )
+        .collect())
+}
+
+fn remove_profile_dirs(created: &[(String, String)]) {
+    for (_, dir) in created {
+        if let Err(e) = std::fs::remove_dir_all(dir) {
+            log::warn!("Failed to remove profile dir {}: {}", dir, e);
+        }
+    }
+}
+
+#[tauri::command]
+pub fn delete_account(storage: State<'_, Arc<dyn Storage>>, account_id: i64) -> Result<(), String> {
+    let profile_dir = storage
+        .delete_account(account_id)
+        .map_err(|e| e.to_string())?;
 
     // Clean up Chrome profile directory
     let profile_path = std::path::PathBuf::from(&profile_dir);
@@ -64,22 +161,24 @@ pub fn delete_account(db: State<'_, Database>, account_id: i64) -> Result<(), St
 
 #[tauri::command]
 pub fn update_account_name(
-    db: State<'_, Database>,
+    storage: State<'_, Arc<dyn Storage>>,
     account_id: i64,
     display_name: String,
 ) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    queries::update_account_display_name(&conn, account_id, &display_name)
+    storage
+        .update_account_display_name(account_id, &display_name)
         .map_err(|e| e.to_string())
 }
 
 /// Launch Chrome for the user to log in to a platform
 #[tauri::command]
-pub fn open_login(db: State<'_, Database>, account_id: i64) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
+pub fn open_login(
+    app: AppHandle,
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<(), String> {
     // Get account info
-    let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+    let accounts = storage.get_all_accounts().map_err(|e| e.to_string())?;
     let account = accounts
         .iter()
         .find(|a| a.id == account_id)
@@ -92,20 +191,32 @@ pub fn open_login(db: State<'_, Database>, account_id: i64) -> Result<(), String
     // Detect Chrome
     let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
 
-    // Launch Chrome for login
+    // Launch Chrome for login; the lock follows the login window's lifetime
     let profile_dir = std::path::PathBuf::from(&account.chrome_profile_dir);
-    chrome::launch_chrome_for_login(&chrome_path, &profile_dir, &platform_info.login_url)
+    let lock = profile_lock::acquire(&profile_dir, "login", "").map_err(|e| e.to_string())?;
+    let window_name = chrome::window_name(
+        platform_info.localized_name(platforms::english()),
+        &account.display_name,
+    );
+    let (child, port) = chrome::launch_chrome_for_login(
+        &chrome_path,
+        &profile_dir,
+        &platform_info.login_url,
+        Some(&window_name),
+        &account.chrome_flags,
+    )
+    .map_err(|e| e.to_string())?;
+    lock.hand_to_window("login", child.id())
         .map_err(|e| e.to_string())?;
+    login_status::watch_login(app, account.id, account.platform.clone(), port);
 
     Ok(())
 }
 
 /// Open a platform's creator page in Chrome (quick access)
 #[tauri::command]
-pub fn open_platform(db: State<'_, Database>, account_id: i64) -> Result<(), String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-
-    let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+pub fn open_platform(storage: State<'_, Arc<dyn Storage>>, account_id: i64) -> Result<(), String> {
+    let accounts = storage.get_all_accounts().map_err(|e| e.to_string())?;
     let account = accounts
         .iter()
         .find(|a| a.id == account_id)
@@ -116,9 +227,21 @@ pub fn open_platform(db: State<'_, Database>, account_id: i64) -> Result<(), Str
 
     let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
     let profile_dir = std::path::PathBuf::from(&account.chrome_profile_dir);
+    // Held only while launching, so no other launch races this one
+    let _lock = profile_lock::acquire(&profile_dir, "open", "").map_err(|e| e.to_string())?;
 
-    chrome::launch_chrome_with_debug(&chrome_path, &profile_dir, &platform_info.upload_url)
-        .map_err(|e| e.to_string())?;
+    let window_name = chrome::window_name(
+        platform_info.localized_name(platforms::english()),
+        &account.display_name,
+    );
+    chrome::launch_chrome_with_debug(
+        &chrome_path,
+        &profile_dir,
+        &platform_info.upload_url,
+        Some(&window_name),
+        &account.chrome_flags,
+    )
+    .map_err(|e| e.to_string())?;
 
     Ok(())
 }
@@ -131,5 +254,349 @@ pub fn update_login_status(
     is_logged_in: bool,
 ) -> Result<(), String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    queries::update_account_login_status(&conn, account_id, is_logged_in).map_err(|e| e.to_string())
+    queries::update_account_login_status(&conn, account_id, is_logged_in)
+        .map_err(|e| e.to_string())?;
+    let observed = if is_logged_in {
+        session_expiry::record_login(&conn, account_id)
+    } else {
+        session_expiry::record_expired(&conn, account_id)
+    };
+    observed.map_err(|e| e.to_string())
+}
+
+/// Probe the login state of all accounts (optionally only one platform's) and
+/// return a summary; the stored `is_logged_in` flags are updated along the way
+#[tauri::command]
+pub async fn refresh_all_login_status(
+    db: State<'_, Database>,
+    platform: Option<String>,
+    concurrency: Option<usize>,
+) -> Result<login_status::LoginRefreshSummary, String> {
+    login_status::refresh_all(
+        &db,
+        platform.as_deref(),
+        concurrency.unwrap_or(login_status::DEFAULT_CONCURRENCY),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Detect the login state of the given accounts in their own Chrome profiles
+/// and store it (`is_logged_in`, `last_checked_at`)
+#[tauri::command]
+pub async fn check_account_login(
+    db: State<'_, Database>,
+    account_ids: Vec<i64>,
+    concurrency: Option<usize>,
+) -> Result<Vec<login_status::AccountLoginCheck>, String> {
+    login_status::check_accounts(
+        &db,
+        &account_ids,
+        concurrency.unwrap_or(login_status::DEFAULT_CONCURRENCY),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Background login probe results, newest first; all accounts when
+/// `account_id` is None
+#[tauri::command]
+pub fn get_account_health(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: Option<i64>,
+    limit: Option<i64>,
+) -> Result<Vec<queries::AccountHealth>, String> {
+    storage
+        .get_account_health(account_id, limit.unwrap_or(100).clamp(1, 1000))
+        .map_err(|e| e.to_string())
+}
+
+/// Walk one account through launch, CDP attach, login and the upload page
+/// guard without uploading, and return a per-step scorecard
+#[tauri::command]
+pub async fn test_account(
+    db: State<'_, Database>,
+    account_id: i64,
+) -> Result<account_test::AccountScorecard, String> {
+    account_test::run(&db, account_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Estimated days until each account's login cookies expire, based on the
+/// platform's typical session lifetime and the account's last authenticated action
+#[tauri::command]
+pub fn get_account_expiry_estimates(
+    db: State<'_, Database>,
+) -> Result<Vec<session_expiry::AccountExpiryEstimate>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    session_expiry::estimate_all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_account_watermark(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<Option<WatermarkConfig>, String> {
+    let raw = storage
+        .get_account_watermark(account_id)
+        .map_err(|e| e.to_string())?;
+    match raw {
+        Some(raw) => serde_json::from_str(&raw)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Set (or clear with `None`) the watermark applied to this account's uploads
+#[tauri::command]
+pub fn set_account_watermark(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    config: Option<WatermarkConfig>,
+) -> Result<(), String> {
+    let raw = match config {
+        Some(config) => {
+            config.validate().map_err(|e| e.to_string())?;
+            Some(serde_json::to_string(&config).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    storage
+        .update_account_watermark(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Default platform fields (visibility, 分区, original/repost...) applied to
+/// this account's publishes wherever the request leaves them unset
+#[tauri::command]
+pub fn get_account_publish_defaults(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<CustomFields, String> {
+    let raw = storage
+        .get_account_publish_defaults(account_id)
+        .map_err(|e| e.to_string())?;
+    match raw {
+        Some(raw) => serde_json::from_str(&raw).map_err(|e| e.to_string()),
+        None => Ok(CustomFields::default()),
+    }
+}
+
+/// Set (or clear with `None`) the account's default platform fields; they are
+/// checked against the account platform's field schema
+#[tauri::command]
+pub fn set_account_publish_defaults(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    defaults: Option<CustomFields>,
+) -> Result<(), String> {
+    let raw = match defaults.filter(|defaults| !defaults.is_empty()) {
+        Some(defaults) => {
+            let account = storage
+                .get_all_accounts()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .find(|a| a.id == account_id)
+                .ok_or_else(|| format!("Account {} not found", account_id))?;
+            defaults
+                .validate(&account.platform)
+                .map_err(|e| e.to_string())?;
+            Some(serde_json::to_string(&defaults).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    storage
+        .update_account_publish_defaults(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Values this account's `{account.<key>}` placeholders resolve to in titles,
+/// descriptions, tags, text platform fields and user scripts
+#[tauri::command]
+pub fn get_account_variables(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<BTreeMap<String, String>, String> {
+    let raw = storage
+        .get_account_variables(account_id)
+        .map_err(|e| e.to_string())?;
+    Ok(variables::parse(raw.as_deref()))
+}
+
+/// Replace the account's variables; an empty map clears them. `id`, `name`
+/// and `platform` are built in and cannot be set.
+#[tauri::command]
+pub fn set_account_variables(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    variables: BTreeMap<String, String>,
+) -> Result<(), String> {
+    variables::validate(&variables).map_err(|e| e.to_string())?;
+    let raw = if variables.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&variables).map_err(|e| e.to_string())?)
+    };
+    storage
+        .update_account_variables(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Title variation applied to this account's publishes
+#[tauri::command]
+pub fn get_account_title_variation(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<TitleVariation, String> {
+    let raw = storage
+        .get_account_title_variation(account_id)
+        .map_err(|e| e.to_string())?;
+    Ok(title_variation::parse(raw.as_deref()))
+}
+
+/// Set (or clear with `None`) the account's title variation, e.g. to keep
+/// Douyin matrix accounts from posting identical titles
+#[tauri::command]
+pub fn set_account_title_variation(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    variation: Option<TitleVariation>,
+) -> Result<(), String> {
+    let raw = match variation.filter(|variation| *variation != TitleVariation::default()) {
+        Some(variation) => {
+            variation.validate().map_err(|e| e.to_string())?;
+            Some(serde_json::to_string(&variation).map_err(|e| e.to_string())?)
+        }
+        None => None,
+    };
+    storage
+        .update_account_title_variation(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Replace the account's extra Chrome flags (e.g. `--lang=en-US`,
+/// `--proxy-pac-url=...`); only allowlisted flags are accepted
+#[tauri::command]
+pub fn set_account_chrome_flags(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    flags: Vec<String>,
+) -> Result<(), String> {
+    let flags = flags
+        .into_iter()
+        .map(|flag| flag.trim().to_string())
+        .filter(|flag| !flag.is_empty())
+        .collect::<Vec<_>>();
+    chrome_flags::validate(&flags).map_err(|e| e.to_string())?;
+    let raw = if flags.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&flags).map_err(|e| e.to_string())?)
+    };
+    storage
+        .update_account_chrome_flags(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Daily time windows (local time) the account may publish in
+#[tauri::command]
+pub fn get_account_publish_windows(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<PublishWindows, String> {
+    let raw = storage
+        .get_account_publish_windows(account_id)
+        .map_err(|e| e.to_string())?;
+    Ok(PublishWindows::parse(raw.as_deref()))
+}
+
+/// Set the account's publishing windows; an empty list allows any time
+#[tauri::command]
+pub fn set_account_publish_windows(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+    windows: PublishWindows,
+) -> Result<(), String> {
+    windows.validate().map_err(|e| e.to_string())?;
+    let raw = if windows.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&windows).map_err(|e| e.to_string())?)
+    };
+    storage
+        .update_account_publish_windows(account_id, raw.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// App-level profile lock of an account, if any
+#[tauri::command]
+pub fn get_profile_lock(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<Option<ProfileLockInfo>, String> {
+    let account = storage
+        .get_all_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("Account {} not found", account_id))?;
+    Ok(profile_lock::inspect(std::path::Path::new(
+        &account.chrome_profile_dir,
+    )))
+}
+
+/// Force-release an account's profile lock, e.g. after a crash left it behind
+#[tauri::command]
+pub fn release_profile_lock(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<(), String> {
+    let account = storage
+        .get_all_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("Account {} not found", account_id))?;
+    profile_lock::release(std::path::Path::new(&account.chrome_profile_dir));
+    Ok(())
+}
+
+/// List the WeChat Channels collections (合集) offered by the account's post picker
+#[tauri::command]
+pub async fn list_wechat_collections(
+    storage: State<'_, Arc<dyn Storage>>,
+    account_id: i64,
+) -> Result<Vec<WechatCollection>, String> {
+    let account = storage
+        .get_all_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("Account {} not found", account_id))?;
+    if account.platform != "wechat" {
+        return Err(format!(
+            "Account {} is not a WeChat Channels account",
+            account_id
+        ));
+    }
+
+    let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
+    let profile_dir = std::path::PathBuf::from(&account.chrome_profile_dir);
+    let upload_url = platforms::get_platform_info("wechat")
+        .map(|info| info.upload_url)
+        .unwrap_or_default();
+    let probe = ProbeSession::open(
+        &chrome_path,
+        &profile_dir,
+        &upload_url,
+        &account.chrome_flags,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    tokio::time::sleep(Duration::from_secs(WECHAT_PAGE_SETTLE_SECS)).await;
+    let collections = wechat::list_collections(&probe.page).await;
+    probe.close().await;
+    collections.map_err(|e| e.to_string())
 }
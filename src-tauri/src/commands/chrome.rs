@@ -28,3 +28,11 @@ pub fn detect_chrome() -> ChromeStatus {
 pub fn get_platforms() -> Vec<crate::platforms::traits::PlatformInfo> {
     crate::platforms::all_platforms()
 }
+
+/// Platform-specific fields the publish form can offer for `platform`
+#[tauri::command]
+pub fn get_platform_fields(
+    platform: String,
+) -> Vec<crate::platforms::capabilities::CustomFieldSpec> {
+    crate::platforms::capabilities::capability_schema(&platform).to_vec()
+}
@@ -0,0 +1,151 @@
+use crate::content::assist::{ContentAssistant, OpenAiCompatibleAssistant, SuggestRequest};
+use crate::content::bundle::{self, ConflictPolicy, ExportSummary, ImportReport};
+use crate::content::{self, PlatformOverride};
+use crate::database::queries;
+use crate::database::{Database, Storage};
+use crate::platforms::selector_overrides;
+use crate::startup;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+/// Generate per-platform title/description/tag suggestions from the master content.
+/// The result has the same shape as `PublishRequest.platform_overrides`; nothing is applied
+/// until the user submits it with a publish request.
+#[tauri::command]
+pub async fn suggest_content(
+    db: State<'_, Database>,
+    request: SuggestRequest,
+) -> Result<HashMap<String, PlatformOverride>, String> {
+    if request.platforms.is_empty() {
+        return Err("No platforms selected".to_string());
+    }
+    let assistant = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        OpenAiCompatibleAssistant::from_settings(&conn).map_err(|e| e.to_string())?
+    };
+    assistant.suggest(&request).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_templates(
+    storage: State<'_, Arc<dyn Storage>>,
+) -> Result<Vec<queries::PublishTemplate>, String> {
+    storage.get_templates().map_err(|e| e.to_string())
+}
+
+/// Save the publish form as a named template, replacing any template with the same name
+#[tauri::command]
+pub fn save_template(
+    storage: State<'_, Arc<dyn Storage>>,
+    name: String,
+    title: String,
+    description: String,
+    tags: Vec<String>,
+    platform_overrides: HashMap<String, PlatformOverride>,
+) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Template name must not be empty".to_string());
+    }
+    let tags_json = serde_json::to_string(&tags).map_err(|e| e.to_string())?;
+    let overrides_json = serde_json::to_string(&platform_overrides).map_err(|e| e.to_string())?;
+    storage
+        .upsert_template(name, &title, &description, &tags_json, &overrides_json)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_tag_sets(storage: State<'_, Arc<dyn Storage>>) -> Result<Vec<queries::TagSet>, String> {
+    storage.get_tag_sets().map_err(|e| e.to_string())
+}
+
+/// Create a named tag set, or rename/replace an existing one when `tag_set_id` is given
+#[tauri::command]
+pub fn save_tag_set(
+    storage: State<'_, Arc<dyn Storage>>,
+    tag_set_id: Option<i64>,
+    name: String,
+    tags: Vec<String>,
+) -> Result<i64, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Tag set name must not be empty".to_string());
+    }
+    let tags = content::merge_tags(&tags, []);
+    if storage
+        .get_tag_sets()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .any(|set| set.name == name && Some(set.id) != tag_set_id)
+    {
+        return Err(format!("Tag set \"{}\" already exists", name));
+    }
+    match tag_set_id {
+        Some(id) => {
+            storage
+                .update_tag_set(id, name, &tags)
+                .map_err(|e| e.to_string())?;
+            Ok(id)
+        }
+        None => storage
+            .insert_tag_set(name, &tags)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+#[tauri::command]
+pub fn delete_tag_set(storage: State<'_, Arc<dyn Storage>>, tag_set_id: i64) -> Result<(), String> {
+    storage
+        .delete_tag_set(tag_set_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_template(
+    storage: State<'_, Arc<dyn Storage>>,
+    template_id: i64,
+) -> Result<(), String> {
+    storage
+        .delete_template(template_id)
+        .map_err(|e| e.to_string())
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    startup::data_dir(app).map_err(|e| e.to_string())
+}
+
+/// Export templates, reply rules, selector overrides and knowledge base links
+/// into one `.mpubconfig` file
+#[tauri::command]
+pub fn export_config_bundle(
+    app: AppHandle,
+    db: State<'_, Database>,
+    path: String,
+) -> Result<ExportSummary, String> {
+    let data_dir = app_data_dir(&app)?;
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    bundle::export(&conn, &data_dir, Path::new(&path)).map_err(|e| e.to_string())
+}
+
+/// Import a `.mpubconfig` file. `on_conflict` is `skip` (default), `overwrite` or `rename`.
+#[tauri::command]
+pub fn import_config_bundle(
+    app: AppHandle,
+    db: State<'_, Database>,
+    path: String,
+    on_conflict: Option<ConflictPolicy>,
+) -> Result<ImportReport, String> {
+    let data_dir = app_data_dir(&app)?;
+    let mut conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let report = bundle::import(
+        &mut conn,
+        &data_dir,
+        Path::new(&path),
+        on_conflict.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())?;
+    selector_overrides::reload(&data_dir);
+    Ok(report)
+}
@@ -0,0 +1,264 @@
+use crate::artifacts::ArtifactStore;
+use crate::browser::devtools::{self, DevtoolsPage};
+use crate::database::integrity::DatabaseStatus;
+use crate::database::queries;
+use crate::database::Database;
+use crate::diagnostics::crash::{self, CrashReportSummary};
+use crate::diagnostics::network::{self, NetworkProbe};
+use crate::diagnostics::observer::{self, ObserverSession};
+use crate::diagnostics::snapshot::{self, SnapshotDiff};
+use crate::diagnostics::telemetry;
+use crate::diagnostics::timeout_tuning::{self, TunedTimeouts};
+use crate::platforms;
+use crate::startup::{self, StartupStatus};
+use crate::tasks::{BackgroundTaskInfo, TaskRegistry};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn list_crash_reports(
+    artifacts: State<'_, ArtifactStore>,
+) -> Result<Vec<CrashReportSummary>, String> {
+    crash::list_reports(&artifacts.crash_dir()).map_err(|e| e.to_string())
+}
+
+/// Upload pending crash reports. Only allowed after the user opted in and
+/// configured an endpoint in settings.
+#[tauri::command]
+pub async fn upload_crash_reports(
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+) -> Result<usize, String> {
+    let endpoint = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        crash::upload_endpoint(&conn).map_err(|e| e.to_string())?
+    };
+    let endpoint = endpoint.ok_or_else(|| {
+        "Crash report upload is disabled. Enable it and set an endpoint in settings first."
+            .to_string()
+    })?;
+    crash::upload_pending_reports(&artifacts.crash_dir(), &endpoint)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Strategy statistics gathered on this machine while telemetry is `local` or `share`
+#[tauri::command]
+pub fn get_strategy_stats(db: State<'_, Database>) -> Result<Vec<queries::StrategyStat>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_strategy_stats(&conn).map_err(|e| e.to_string())
+}
+
+/// Upload page snapshots recorded by publish runs of a platform, newest first
+#[tauri::command]
+pub fn get_page_snapshot_history(
+    db: State<'_, Database>,
+    platform: String,
+) -> Result<Vec<queries::PageSnapshotRecord>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_page_snapshot_history(&conn, &platform).map_err(|e| e.to_string())
+}
+
+/// Structural differences between a platform's upload page on two dates
+/// (`YYYY-MM-DD`, UTC), from the last snapshot recorded on or before each
+#[tauri::command]
+pub fn diff_page_snapshots(
+    db: State<'_, Database>,
+    platform: String,
+    from_date: String,
+    to_date: String,
+) -> Result<SnapshotDiff, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    snapshot::diff(&conn, &platform, &from_date, &to_date).map_err(|e| e.to_string())
+}
+
+/// Send the counts gathered since the last submission. Only allowed in `share`
+/// mode with an endpoint configured in settings.
+#[tauri::command]
+pub async fn submit_strategy_stats(db: State<'_, Database>) -> Result<usize, String> {
+    telemetry::submit_pending(&db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn clear_strategy_stats(db: State<'_, Database>) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::clear_strategy_stats(&conn).map_err(|e| e.to_string())
+}
+
+/// Upload strategy order learned for each account, with the counts behind it
+#[tauri::command]
+pub fn get_strategy_order(
+    db: State<'_, Database>,
+) -> Result<Vec<platforms::strategy_order::LearnedOrder>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    platforms::strategy_order::overview(&conn).map_err(|e| e.to_string())
+}
+
+/// Forget the learned order so the built-in one is used again; None resets
+/// every platform or account
+#[tauri::command]
+pub fn reset_strategy_order(
+    db: State<'_, Database>,
+    platform: Option<String>,
+    account_id: Option<i64>,
+) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::clear_strategy_outcomes(&conn, platform.as_deref(), account_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Signal timeouts each platform's fast runs currently use, with the
+/// latency percentiles they were tuned from
+#[tauri::command]
+pub fn get_tuned_timeouts(db: State<'_, Database>) -> Result<Vec<TunedTimeouts>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    platforms::registry::entries()
+        .iter()
+        .map(|entry| timeout_tuning::tuned_timeouts(&conn, entry.id()))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| e.to_string())
+}
+
+/// Share a running task read-only: the returned URL and token let a helper
+/// follow its progress, phase log and screenshots until the token expires
+#[tauri::command]
+pub async fn start_observer_session(
+    app: AppHandle,
+    task_id: i64,
+    minutes: Option<u64>,
+) -> Result<ObserverSession, String> {
+    observer::start(&app, task_id, minutes)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn stop_observer_session(token: String) -> Result<(), String> {
+    if !observer::stop(&token) {
+        return Err("Observer session not found or already expired".to_string());
+    }
+    Ok(())
+}
+
+/// Open pages of every debuggable account Chrome session, for the DevTools console
+#[tauri::command]
+pub async fn list_devtools_pages(db: State<'_, Database>) -> Result<Vec<DevtoolsPage>, String> {
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        devtools::ensure_enabled(&conn).map_err(|e| e.to_string())?;
+        queries::get_all_accounts(&conn).map_err(|e| e.to_string())?
+    };
+    devtools::list_pages(&accounts)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Evaluate JS in one page of an account's automation Chrome. Gated by the
+/// `devtools_console_enabled` setting.
+#[tauri::command]
+pub async fn evaluate_devtools_js(
+    db: State<'_, Database>,
+    account_id: i64,
+    target_id: String,
+    script: String,
+) -> Result<serde_json::Value, String> {
+    let account = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        devtools::ensure_enabled(&conn).map_err(|e| e.to_string())?;
+        queries::get_all_accounts(&conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|a| a.id == account_id)
+            .ok_or_else(|| format!("Account {} not found", account_id))?
+    };
+    log::info!(
+        "[DevTools] evaluate account={} target={}",
+        account_id,
+        target_id
+    );
+    devtools::evaluate(&account, &target_id, &script)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Background tasks spawned by the app, including recently finished ones
+#[tauri::command]
+pub fn list_background_tasks(registry: State<'_, TaskRegistry>) -> Vec<BackgroundTaskInfo> {
+    registry.list()
+}
+
+/// Measure the uplink now, bypassing the cached result. In `auto` network mode
+/// the next publish run reuses this measurement.
+#[tauri::command]
+pub async fn probe_network_speed() -> Result<NetworkProbe, String> {
+    network::probe_uplink(true).await.map_err(|e| e.to_string())
+}
+
+/// Result of the startup integrity check, including any repair that was done
+#[tauri::command]
+pub fn get_database_status(status: State<'_, DatabaseStatus>) -> DatabaseStatus {
+    status.inner().clone()
+}
+
+/// Whether setup completed, or why the app runs in degraded mode
+#[tauri::command]
+pub fn get_startup_status(status: State<'_, StartupStatus>) -> StartupStatus {
+    status.inner().clone()
+}
+
+/// Use another data directory (database and artifacts) from the next start
+/// on; `None` returns to the default location
+#[tauri::command]
+pub fn set_data_dir_override(path: Option<String>) -> Result<(), String> {
+    startup::set_data_dir_override(path.as_deref().map(Path::new)).map_err(|e| e.to_string())
+}
+
+/// Restart the app, e.g. after choosing another data directory
+#[tauri::command]
+pub fn restart_app(app: AppHandle) {
+    app.restart();
+}
+
+/// Help articles linked from failed publish results
+#[tauri::command]
+pub fn list_kb_articles(db: State<'_, Database>) -> Result<Vec<queries::KbArticle>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_kb_articles(&conn).map_err(|e| e.to_string())
+}
+
+/// Link an error code to a help article, for one platform or (with no
+/// platform) all of them; replaces the existing entry for the same pair
+#[tauri::command]
+pub fn save_kb_article(
+    db: State<'_, Database>,
+    platform: Option<String>,
+    error_code: String,
+    title: String,
+    url: String,
+) -> Result<i64, String> {
+    let error_code = error_code.trim().to_ascii_uppercase();
+    if error_code.is_empty() {
+        return Err("Error code must not be empty".to_string());
+    }
+    let platform = platform.filter(|p| !p.trim().is_empty());
+    if let Some(platform) = &platform {
+        if platforms::get_platform_info(platform).is_none() {
+            return Err(format!("Unknown platform: {}", platform));
+        }
+    }
+    let url = url.trim();
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("Article URL must start with http:// or https://".to_string());
+    }
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::upsert_kb_article(&conn, platform.as_deref(), &error_code, title.trim(), url)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_kb_article(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::delete_kb_article(&conn, id).map_err(|e| e.to_string())
+}
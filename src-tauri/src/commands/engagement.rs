@@ -0,0 +1,60 @@
+use crate::database::queries;
+use crate::database::Database;
+use crate::engagement::comment_reply;
+use crate::platforms;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_reply_rules(db: State<'_, Database>) -> Result<Vec<queries::ReplyRule>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_reply_rules(&conn).map_err(|e| e.to_string())
+}
+
+/// Add a keyword → canned reply rule. `platform: None` applies to every platform.
+#[tauri::command]
+pub fn add_reply_rule(
+    db: State<'_, Database>,
+    platform: Option<String>,
+    keyword: String,
+    reply: String,
+) -> Result<i64, String> {
+    if let Some(platform) = &platform {
+        platforms::get_platform_info(platform)
+            .ok_or_else(|| format!("Unknown platform: {}", platform))?;
+    }
+    if keyword.trim().is_empty() || reply.trim().is_empty() {
+        return Err("Keyword and reply must not be empty".to_string());
+    }
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::insert_reply_rule(&conn, platform.as_deref(), keyword.trim(), reply.trim())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_reply_rule(db: State<'_, Database>, rule_id: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::delete_reply_rule(&conn, rule_id).map_err(|e| e.to_string())
+}
+
+/// Record the public URL of a published post so its comments can be monitored
+#[tauri::command]
+pub fn set_post_url(
+    db: State<'_, Database>,
+    task_platform_id: i64,
+    post_url: Option<String>,
+) -> Result<(), String> {
+    let post_url = post_url
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::update_task_platform_post_url(&conn, task_platform_id, post_url.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Run one comment auto-reply pass now. Returns the number of replies sent.
+#[tauri::command]
+pub async fn run_comment_replies(db: State<'_, Database>) -> Result<usize, String> {
+    comment_reply::run_once(&db)
+        .await
+        .map_err(|e| e.to_string())
+}
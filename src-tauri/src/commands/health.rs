@@ -0,0 +1,120 @@
+use crate::artifacts::ArtifactStore;
+use crate::browser::chrome;
+use crate::database::queries;
+use crate::database::{Database, Storage};
+use crate::health::config_smoke::{self, ConfigSmokeReport};
+use crate::health::review_queue::{self, ReviewQueueStatus};
+use crate::health::selector_check::{self, PlatformSelectorReport};
+use crate::health::url_drift;
+use crate::platforms::{
+    self,
+    selector_overrides::{self, SelectorOverrides},
+};
+use crate::startup;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+/// Latest upload URL health result per platform
+#[tauri::command]
+pub fn get_url_health(db: State<'_, Database>) -> Result<Vec<queries::UrlHealthCheck>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_latest_url_health_checks(&conn).map_err(|e| e.to_string())
+}
+
+/// Check every platform's upload URL now, regardless of the periodic schedule
+#[tauri::command]
+pub async fn run_url_health_check(
+    db: State<'_, Database>,
+) -> Result<Vec<queries::UrlHealthCheck>, String> {
+    url_drift::run_all(&db).await.map_err(|e| e.to_string())
+}
+
+/// Parse every platform's selector override file without opening a browser
+#[tauri::command]
+pub fn validate_selector_overrides(app: AppHandle) -> Result<Vec<SelectorOverrides>, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    Ok(platforms::all_platforms()
+        .iter()
+        .map(|info| selector_overrides::load(&data_dir, &info.id))
+        .collect())
+}
+
+/// Re-read the selector override files so the next publish uses them,
+/// without restarting the app
+#[tauri::command]
+pub fn reload_selector_overrides(app: AppHandle) -> Result<Vec<SelectorOverrides>, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    Ok(selector_overrides::reload(&data_dir))
+}
+
+/// Check every platform config and selector override file, compile their
+/// scripts and run them against the built-in HTML fixtures, or the ones in
+/// `fixtures_dir` where it has a `<platform>.html`
+#[tauri::command]
+pub async fn run_config_smoke_test(
+    app: AppHandle,
+    fixtures_dir: Option<String>,
+) -> Result<ConfigSmokeReport, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    let fixtures_dir = fixtures_dir.map(PathBuf::from);
+    Ok(config_smoke::run(Some(&data_dir), fixtures_dir.as_deref()).await)
+}
+
+/// Selectors a platform is published with right now, overrides merged in
+#[tauri::command]
+pub fn get_effective_selectors(
+    platform: String,
+) -> Result<Vec<selector_overrides::EffectiveSelectors>, String> {
+    let cfg = platforms::publish_config(&platform)
+        .ok_or_else(|| format!("Unknown platform: {}", platform))?;
+    Ok(selector_overrides::effective_selectors(cfg))
+}
+
+/// Count matches of every built-in and override selector on each upload page and
+/// save a highlighted screenshot per platform under `artifacts/selector_gallery`
+#[tauri::command]
+pub async fn run_selector_health_check(
+    app: AppHandle,
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+) -> Result<Vec<PlatformSelectorReport>, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    let gallery_dir = artifacts
+        .dir("selector_gallery")
+        .map_err(|e| e.to_string())?
+        .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+    std::fs::create_dir_all(&gallery_dir).map_err(|e| e.to_string())?;
+    selector_check::run_all(&db, &data_dir, Some(&gallery_dir))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read how many of a WeChat Channels account's posts are under review now;
+/// new publishes of the account are held while this reaches the configured limit
+#[tauri::command]
+pub async fn refresh_wechat_review_queue(
+    db: State<'_, Database>,
+    account_id: i64,
+) -> Result<ReviewQueueStatus, String> {
+    let account = db
+        .get_all_accounts()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|a| a.id == account_id)
+        .ok_or_else(|| format!("Account {} not found", account_id))?;
+    if account.platform != "wechat" {
+        return Err(format!(
+            "Account {} is not a WeChat Channels account",
+            account_id
+        ));
+    }
+    let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
+    review_queue::refresh(
+        &db,
+        &chrome_path,
+        account_id,
+        std::path::Path::new(&account.chrome_profile_dir),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
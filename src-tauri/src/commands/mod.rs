@@ -1,3 +1,9 @@
 pub mod accounts;
 pub mod chrome;
+pub mod content;
+pub mod diagnostics;
+pub mod engagement;
+pub mod health;
 pub mod publish;
+pub mod security;
+pub mod settings;
@@ -1,14 +1,41 @@
-use crate::browser::{automation, chrome};
+use crate::artifacts::ArtifactStore;
+use crate::browser::bandwidth::{self, BandwidthMeter};
+use crate::browser::downloads;
+use crate::browser::page_handle::{self, PageHandle};
+use crate::browser::{automation, chrome, prewarm, profile_lock};
+use crate::content::title_variation::{self, TitleVariation};
+use crate::content::variables::{self, AccountVariables};
+use crate::content::{self, share, PlatformOverride};
 use crate::database::queries;
-use crate::database::Database;
-use crate::platforms;
+use crate::database::{Database, Storage};
+use crate::diagnostics::automation_log::AutomationLogSink;
+use crate::diagnostics::estimate::{self, PublishEstimate};
+use crate::diagnostics::network::NetworkSettings;
+use crate::diagnostics::phase_log;
+use crate::diagnostics::progress::{self, ProgressHandle, TaskProgress};
+use crate::diagnostics::snapshot::{self, PageSnapshot};
+use crate::diagnostics::telemetry;
+use crate::diagnostics::timeout_tuning;
+use crate::health::{review_queue, session_expiry};
+use crate::hooks::{self, HookPoint, PublishHooks};
+use crate::media::transcribe::Transcriber;
+use crate::media::{ffmpeg, watermark};
+use crate::platforms::strategy_order::{self, UploadStrategy};
+use crate::platforms::{self, capabilities, AutomationContext, PlatformAutomation};
+use crate::scheduler::{self, window::PublishWindows};
+use crate::startup;
+use crate::tasks::RunClaim;
 use log::info;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio_util::sync::CancellationToken;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublishRequest {
     pub video_path: String,
     pub title: String,
@@ -17,6 +44,51 @@ pub struct PublishRequest {
     pub is_original: bool,
     pub manual_confirm: bool,
     pub account_ids: Vec<i64>,
+    /// Requested publish time, RFC 3339 or local `YYYY-MM-DD HH:MM`. None publishes immediately.
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    /// Release time handed to the platform's own scheduler, same formats as
+    /// `publish_at`. Unlike `publish_at` there is no app-side fallback: an
+    /// account whose platform cannot schedule fails instead of publishing now.
+    #[serde(default)]
+    pub platform_scheduled_at: Option<String>,
+    /// Per-platform title/description/tags keyed by platform id
+    #[serde(default)]
+    pub platform_overrides: HashMap<String, PlatformOverride>,
+    /// Transcribe the video into an .srt and upload it where the platform supports captions
+    #[serde(default)]
+    pub generate_captions: bool,
+    /// Platform-specific options (category, visibility, collection...) keyed by platform id
+    #[serde(default)]
+    pub platform_fields: HashMap<String, platforms::CustomFields>,
+    /// `public`, `unlisted`, `friends` or `private` for every platform whose
+    /// `visibility` field offers it; a visibility in `platform_fields` takes precedence
+    #[serde(default)]
+    pub visibility: Option<String>,
+    /// Cover image to upload where the platform supports a custom cover
+    #[serde(default)]
+    pub cover_path: Option<String>,
+    /// Images or videos attached after the main video in the same post, on
+    /// platforms that allow mixed media (WeChat Channels); others ignore them
+    #[serde(default)]
+    pub extra_media: Vec<String>,
+    /// `video` or `images`. Image posts upload `images` instead of the video
+    /// and only go to platforms that support them.
+    #[serde(default)]
+    pub post_type: platforms::PostType,
+    /// Images of an image post, in display order
+    #[serde(default)]
+    pub images: Vec<String>,
+    /// Saved tag sets merged after the ad-hoc `tags`
+    #[serde(default)]
+    pub tag_set_ids: Vec<i64>,
+    /// `fast`, `thorough` or `diagnostic`. None uses the `automation_profile` setting.
+    #[serde(default)]
+    pub automation_profile: Option<platforms::AutomationProfileKind>,
+    /// Run against the bundled local upload page instead of the real platforms,
+    /// in a throwaway Chrome profile; accounts' own profiles are never opened
+    #[serde(default)]
+    pub simulate: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -36,20 +108,71 @@ pub struct PlatformTaskResult {
     pub debug_port_used: Option<u16>,
     pub session_mode: Option<String>,
     pub automation_phase: Option<String>,
+    pub schedule_mode: Option<String>,
+    /// Set for transient platform-side failures (maintenance); scheduled runs
+    /// defer the account and retry after this many seconds
+    pub retry_after_secs: Option<u64>,
+    /// Name shown on the Chrome window this run opened; None when an already
+    /// open window was reused or Chrome never started
+    pub window_title: Option<String>,
+    /// Help article for `error_code` on this platform from the local knowledge base
+    pub kb_link: Option<String>,
+    /// Screenshot of the filled form, taken before handing over for manual confirmation
+    pub preview_path: Option<String>,
 }
 
 const ACTION_HINT_CLOSE_WINDOW: &str = "请先关闭该账号已打开的 Chrome 窗口后重试。";
+const ACTION_HINT_PROFILE_LOCKED: &str =
+    "该账号正被本应用的其他操作占用（登录窗口或另一个发布任务），请等待其结束或在账号页解除锁定后重试。";
 const ACTION_HINT_CHECK_CHROME: &str = "请确认 Chrome 已成功打开并停留在目标平台页面后重试。";
 const ACTION_HINT_AUTOMATION_TIMEOUT: &str = "上传可能已开始，请在 Chrome 页面继续并重试提交。";
 const ACTION_HINT_TARGET_PAGE_NOT_FOUND: &str =
     "未定位到目标平台上传页，已尝试新开窗口。请在 Chrome 打开对应平台上传页后重试。";
 const ACTION_HINT_TARGET_PAGE_NOT_READY: &str = "页面未完成加载，请等待页面稳定后重试。";
+const ACTION_HINT_RISK_CONTROL: &str =
+    "平台触发了安全验证或地区限制，请在 Chrome 中手动完成验证（必要时切换网络）后再重试，短时间内不要反复重试。";
+const ACTION_HINT_PLATFORM_MAINTENANCE: &str =
+    "平台正在维护或暂停上传，请等待平台恢复后再发布；定时任务会自动顺延重试。";
 const ACTION_HINT_LOGIN_REQUIRED: &str = "请先在 Chrome 完成微信扫码登录，再重试上传。";
 const ACTION_HINT_WECHAT_CHOOSER_NOT_OPENED: &str =
     "微信上传入口暂不可交互，已多轮重试仍未触发文件选择器。请稍等页面稳定后重试。";
+const ACTION_HINT_CHOOSER_CONFLICT: &str =
+    "其他浏览器扩展或自动化工具正在拦截文件选择框，请在该账号的 Chrome 中停用相关扩展（或关闭其他自动化工具）后重试。";
+const ACTION_HINT_SUBMIT_FAILED: &str =
+    "视频已上传并填写，但自动发布未完成。请在 Chrome 页面检查后手动点击发布。";
+const ACTION_HINT_SUBMIT_BLOCKED_VERIFICATION: &str =
+    "发布前核对发现页面内容与本次任务不一致，已停止自动发布。请在 Chrome 页面核对标题、文件和可见范围后手动发布。";
 const ACTION_HINT_WECHAT_UPLOAD_SIGNAL_TIMEOUT: &str =
     "微信已完成文件注入，但未观测到上传信号。请在 Chrome 页面确认是否已开始上传。";
-const AUTOMATION_TIMEOUT_SECS: u64 = 45;
+const ACTION_HINT_WECHAT_MEDIA_INCOMPLETE: &str =
+    "主视频已上传，但部分附加图片或视频未能加入动态。请在 Chrome 页面手动补充后再发表。";
+const ACTION_HINT_INTERVENTION_REQUIRED: &str =
+    "页面出现需要人工选择的弹窗，请在 Chrome 页面完成选择后继续发表。";
+const ACTION_HINT_DURATION_OUT_OF_RANGE: &str =
+    "视频时长不符合该平台要求，请剪辑后单独发布到该平台。";
+const ACTION_HINT_WATERMARK_FAILED: &str =
+    "水印处理失败，请检查 ffmpeg 是否可用以及水印图片路径，或在账号设置中关闭水印后重试。";
+
+const ACTION_HINT_OUTSIDE_PUBLISH_WINDOW: &str =
+    "该账号设置了允许发布的时间段，本次发布已顺延到下一个时间段；如需立即发布，请在账号设置中调整时间段。";
+const ACTION_HINT_WECHAT_REVIEW_THROTTLED: &str =
+    "视频号审核中的作品过多，本次发布已暂缓，待审核通过后会再次尝试；也可在设置中调整审核中上限。";
+
+/// How long a run deferred by platform maintenance waits before retrying
+const MAINTENANCE_RETRY_AFTER_SECS: u64 = 30 * 60;
+/// How long a WeChat publish held by the review queue waits before retrying
+const REVIEW_THROTTLE_RETRY_AFTER_SECS: u64 = 20 * 60;
+/// How long an account another task is publishing with waits before retrying
+const ACCOUNT_BUSY_RETRY_AFTER_SECS: u64 = 10 * 60;
+
+/// Retry delay for error codes that mean "try again later" rather than failure
+pub(crate) fn retry_after_secs(code: &str) -> Option<u64> {
+    match code {
+        "PLATFORM_MAINTENANCE" => Some(MAINTENANCE_RETRY_AFTER_SECS),
+        "WECHAT_REVIEW_THROTTLED" => Some(REVIEW_THROTTLE_RETRY_AFTER_SECS),
+        _ => None,
+    }
+}
 
 #[derive(Debug, Clone)]
 struct PlatformAutomationError {
@@ -57,6 +180,8 @@ struct PlatformAutomationError {
     message: String,
     action_hint: Option<String>,
     debug_port_used: Option<u16>,
+    /// Upload page structure when automation failed on the page
+    snapshot: Option<PageSnapshot>,
 }
 
 impl PlatformAutomationError {
@@ -67,6 +192,7 @@ impl PlatformAutomationError {
             message: strip_error_code_prefix(raw),
             action_hint,
             debug_port_used: None,
+            snapshot: None,
         }
     }
 
@@ -74,21 +200,82 @@ impl PlatformAutomationError {
         self.debug_port_used = Some(port);
         self
     }
+
+    fn with_snapshot(mut self, snapshot: Option<PageSnapshot>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+}
+
+/// Emitted when automation pauses on something only the user can resolve
+const INTERVENTION_REQUIRED_EVENT: &str = "intervention_required";
+
+#[derive(Debug, Clone, Serialize)]
+struct InterventionRequired {
+    task_id: i64,
+    account_id: i64,
+    platform: String,
+    message: String,
 }
 
-#[derive(Debug, Clone)]
 struct AutomationSuccess {
     message: String,
     debug_port_used: u16,
     automation_phase: &'static str,
+    schedule_mode: Option<&'static str>,
+    preview_path: Option<String>,
+    /// Meters the upload that continues after automation hands over
+    bandwidth: Option<BandwidthMeter>,
+    snapshot: Option<PageSnapshot>,
+}
+
+/// Drops a publish task's live progress and cancel token however the run
+/// exits, including an early `?` return
+struct PublishRunGuard(i64);
+
+impl Drop for PublishRunGuard {
+    fn drop(&mut self) {
+        progress::finish_task(self.0);
+        crate::tasks::global().finish_publish(self.0);
+    }
+}
+
+/// One account of a publish task, resolved before automation starts
+struct AccountRun {
+    account_id: i64,
+    platform: String,
+    profile_dir: String,
+    watermark: Option<watermark::WatermarkConfig>,
+    task_platform_id: i64,
+    /// Label for the account's Chrome window
+    window_name: String,
+    /// Request fields merged over the account's defaults
+    custom_fields: platforms::CustomFields,
+    publish_windows: PublishWindows,
+    chrome_flags: Vec<String>,
+    /// Values for `{account.<key>}` placeholders in this account's content
+    variables: AccountVariables,
+    title_variation: TitleVariation,
 }
 
 /// Create a publish task and automate Chrome for each platform
 #[tauri::command]
 pub async fn create_publish_task(
+    app: AppHandle,
     db: State<'_, Database>,
-    request: PublishRequest,
+    artifacts: State<'_, ArtifactStore>,
+    mut request: PublishRequest,
 ) -> Result<PublishResult, String> {
+    let image_post = request.post_type == platforms::PostType::Images;
+    if image_post {
+        platforms::xiaohongshu::validate_images(&request.images).map_err(|e| e.to_string())?;
+        if request.generate_captions {
+            return Err("Captions are not available for image posts".to_string());
+        }
+        // The first image stands in for the video in the task record
+        request.video_path = request.images[0].clone();
+    }
+
     // Validate video file exists
     let video_path = Path::new(&request.video_path);
     if !video_path.exists() {
@@ -108,11 +295,104 @@ pub async fn create_publish_task(
         metadata.len() as f64 / (1024.0 * 1024.0)
     );
 
+    if let Some(cover) = request
+        .cover_path
+        .as_deref()
+        .filter(|p| !p.trim().is_empty())
+    {
+        if !Path::new(cover).is_file() {
+            return Err(format!("Cover image not found: {}", cover));
+        }
+    }
+    platforms::wechat::validate_extra_media(&request.extra_media).map_err(|e| e.to_string())?;
+    if image_post {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+        if let Some(account) = accounts.iter().find(|account| {
+            request.account_ids.contains(&account.id)
+                && !platforms::capabilities::supports_post_type(
+                    &account.platform,
+                    request.post_type,
+                )
+        }) {
+            return Err(format!(
+                "{} does not support image posts",
+                platforms::display_name(&account.platform)
+            ));
+        }
+    }
+    for (platform, fields) in &request.platform_fields {
+        fields.validate(platform).map_err(|e| e.to_string())?;
+    }
+    if let Some(visibility) = request.visibility.as_deref() {
+        if !matches!(visibility, "public" | "unlisted" | "friends" | "private") {
+            return Err(format!(
+                "Visibility must be public, unlisted, friends or private: {}",
+                visibility
+            ));
+        }
+    }
+
+    let tags = if request.tag_set_ids.is_empty() {
+        request.tags.clone()
+    } else {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let tag_sets = queries::get_tag_sets(&conn).map_err(|e| e.to_string())?;
+        let mut selected = Vec::new();
+        for id in &request.tag_set_ids {
+            let set = tag_sets
+                .iter()
+                .find(|set| set.id == *id)
+                .ok_or_else(|| format!("Tag set {} not found", id))?;
+            selected.push(set.tags.as_slice());
+        }
+        content::merge_tags(&request.tags, selected)
+    };
+
+    let publish_at = request
+        .publish_at
+        .as_deref()
+        .filter(|raw| !raw.trim().is_empty())
+        .map(parse_publish_at)
+        .transpose()?;
+    let platform_scheduled_at = request
+        .platform_scheduled_at
+        .as_deref()
+        .filter(|raw| !raw.trim().is_empty())
+        .map(parse_publish_at)
+        .transpose()?;
+    if publish_at.is_some() && platform_scheduled_at.is_some() {
+        return Err("Set either publish_at or platform_scheduled_at, not both".to_string());
+    }
+    let mut payload = platforms::PublishPayload {
+        video_path: request.video_path.clone(),
+        post_type: request.post_type,
+        images: request.images.clone(),
+        title: request.title.clone(),
+        description: request.description.clone().unwrap_or_default(),
+        tags: tags.clone(),
+        publish_at,
+        platform_scheduled_at,
+        subtitle_path: None,
+        cover_path: request
+            .cover_path
+            .clone()
+            .filter(|path| !path.trim().is_empty()),
+        extra_media: request.extra_media.clone(),
+        custom_fields: platforms::CustomFields::default(),
+        profile: platforms::AutomationProfile::default(),
+        progress: None,
+        automation_log: None,
+        user_scripts: platforms::user_scripts::UserScripts::default(),
+        manual_confirm: request.manual_confirm,
+        cancel: CancellationToken::new(),
+    };
+
     // Create the main task in DB
-    let (task_id, accounts_info) = {
+    let (task_id, accounts_info, ffmpeg_setting, transcriber, profile_kind, network) = {
         let conn = db.conn.lock().map_err(|e| e.to_string())?;
 
-        let tags_json = serde_json::to_string(&request.tags).unwrap_or_default();
+        let tags_json = serde_json::to_string(&tags).unwrap_or_default();
         let task_id = queries::insert_publish_task(
             &conn,
             &request.video_path,
@@ -120,9 +400,38 @@ pub async fn create_publish_task(
             request.description.as_deref(),
             Some(&tags_json),
             request.is_original,
-            None,
+            publish_at
+                .or(platform_scheduled_at)
+                .map(|at| at.to_rfc3339())
+                .as_deref(),
+        )
+        .map_err(|e| e.to_string())?;
+        let schedule_mode = if platform_scheduled_at.is_some() {
+            Some(platforms::SCHEDULE_MODE_PLATFORM)
+        } else {
+            publish_at.map(|_| platforms::SCHEDULE_MODE_APP)
+        };
+        if let Some(mode) = schedule_mode {
+            queries::update_task_schedule_mode(&conn, task_id, mode).map_err(|e| e.to_string())?;
+        }
+        let images_json =
+            image_post.then(|| serde_json::to_string(&payload.images).unwrap_or_default());
+        queries::update_task_post_options(
+            &conn,
+            task_id,
+            payload.post_type.as_str(),
+            images_json.as_deref(),
+            request.manual_confirm,
         )
         .map_err(|e| e.to_string())?;
+        if let Some(cover) = &payload.cover_path {
+            queries::update_task_cover_path(&conn, task_id, cover).map_err(|e| e.to_string())?;
+        }
+        if !payload.extra_media.is_empty() {
+            let media_json = serde_json::to_string(&payload.extra_media).unwrap_or_default();
+            queries::update_task_extra_media(&conn, task_id, &media_json)
+                .map_err(|e| e.to_string())?;
+        }
 
         let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
 
@@ -134,113 +443,736 @@ pub async fn create_publish_task(
                 .find(|a| a.id == *account_id)
                 .ok_or_else(|| format!("Account {} not found", account_id))?;
 
-            queries::insert_task_platform(&conn, task_id, *account_id)
-                .map_err(|e| e.to_string())?;
+            let overrides = request.platform_overrides.get(&account.platform);
+            let custom_tags = overrides
+                .and_then(|o| o.tags.as_ref())
+                .map(|tags| serde_json::to_string(tags).unwrap_or_default());
+            // Fields the request leaves unset fall back to the account's defaults
+            let defaults = queries::get_account_publish_defaults(&conn, account.id)
+                .map_err(|e| e.to_string())?
+                .and_then(|raw| serde_json::from_str::<platforms::CustomFields>(&raw).ok())
+                .unwrap_or_default();
+            let mut custom_fields = request
+                .platform_fields
+                .get(&account.platform)
+                .cloned()
+                .unwrap_or_default();
+            if let Some(visibility) = request.visibility.as_deref() {
+                custom_fields.default_choice(&account.platform, "visibility", visibility);
+            }
+            let mut custom_fields = custom_fields.merged_over(&defaults);
+            // Platforms with a 自制/转载 choice follow the task's original flag
+            // unless the request or the account sets it
+            custom_fields.default_choice(
+                &account.platform,
+                "copyright",
+                if request.is_original {
+                    "original"
+                } else {
+                    "repost"
+                },
+            );
+            let custom_fields_json = Some(&custom_fields)
+                .filter(|fields| !fields.is_empty())
+                .map(|fields| serde_json::to_string(fields).unwrap_or_default());
+            let task_platform_id = queries::insert_task_platform(
+                &conn,
+                task_id,
+                *account_id,
+                overrides.and_then(|o| o.title.as_deref()),
+                overrides.and_then(|o| o.description.as_deref()),
+                custom_tags.as_deref(),
+                custom_fields_json.as_deref(),
+            )
+            .map_err(|e| e.to_string())?;
 
-            accounts_info.push((
-                account.id,
-                account.platform.clone(),
-                account.chrome_profile_dir.clone(),
-            ));
+            let watermark_config = queries::get_account_watermark(&conn, account.id)
+                .map_err(|e| e.to_string())?
+                .and_then(|raw| serde_json::from_str::<watermark::WatermarkConfig>(&raw).ok())
+                .filter(|config| config.enabled && !image_post);
+
+            accounts_info.push(AccountRun {
+                account_id: account.id,
+                platform: account.platform.clone(),
+                profile_dir: account.chrome_profile_dir.clone(),
+                watermark: watermark_config,
+                task_platform_id,
+                window_name: chrome::window_name(
+                    &platforms::display_name(&account.platform),
+                    &account.display_name,
+                ),
+                custom_fields,
+                publish_windows: PublishWindows::load(&conn, account.id)
+                    .map_err(|e| e.to_string())?,
+                chrome_flags: account.chrome_flags.clone(),
+                variables: AccountVariables::new(
+                    account,
+                    variables::parse(
+                        queries::get_account_variables(&conn, account.id)
+                            .map_err(|e| e.to_string())?
+                            .as_deref(),
+                    ),
+                ),
+                title_variation: title_variation::parse(
+                    queries::get_account_title_variation(&conn, account.id)
+                        .map_err(|e| e.to_string())?
+                        .as_deref(),
+                ),
+            });
+        }
+
+        let ffmpeg_setting =
+            queries::get_setting(&conn, ffmpeg::SETTING_FFMPEG_PATH).map_err(|e| e.to_string())?;
+        let transcriber = if request.generate_captions {
+            Transcriber::from_settings(&conn).map_err(|e| e.to_string())?
+        } else {
+            None
+        };
+        let profile_kind = match request.automation_profile {
+            Some(kind) => kind,
+            None => queries::get_setting(&conn, platforms::profile::SETTING_AUTOMATION_PROFILE)
+                .map_err(|e| e.to_string())?
+                .as_deref()
+                .and_then(platforms::AutomationProfileKind::parse)
+                .unwrap_or_default(),
+        };
+        let network = NetworkSettings::load(&conn).map_err(|e| e.to_string())?;
+        (
+            task_id,
+            accounts_info,
+            ffmpeg_setting,
+            transcriber,
+            profile_kind,
+            network,
+        )
+    };
+
+    payload.cancel = crate::tasks::global().start_publish(task_id);
+    progress::start_task(
+        task_id,
+        &accounts_info
+            .iter()
+            .map(|run| (run.account_id, run.platform.clone()))
+            .collect::<Vec<_>>(),
+    );
+    let run_guard = PublishRunGuard(task_id);
+
+    payload.profile = platforms::AutomationProfile::new(profile_kind);
+    if network.is_slow().await {
+        payload.profile = payload.profile.with_slow_network(network.slow_factor);
+    }
+    match artifacts.dir("screenshots") {
+        Ok(dir) => {
+            let task_dir = dir.join(format!("task-{}", task_id));
+            if payload.profile.wants_screenshots() {
+                payload.profile.screenshot_dir = Some(task_dir.clone());
+            }
+            payload.profile.download_dir = Some(task_dir.join("downloads"));
+            payload.profile.evidence_dir = Some(task_dir);
+        }
+        Err(e) => log::warn!("Screenshots disabled for task {}: {}", task_id, e),
+    }
+    info!(
+        "Automation profile for task {}: {} (slow_network={}, timeout_factor={})",
+        task_id,
+        profile_kind.as_str(),
+        payload.profile.slow_network,
+        payload.profile.timeout_factor
+    );
+
+    // Captions are optional: a failed transcription only skips the subtitle upload
+    if request.generate_captions {
+        match &transcriber {
+            Some(transcriber) => {
+                match generate_captions(
+                    &artifacts,
+                    ffmpeg_setting.as_deref(),
+                    transcriber,
+                    task_id,
+                    video_path,
+                )
+                .await
+                {
+                    Ok(srt) => payload.subtitle_path = Some(srt),
+                    Err(e) => log::warn!("Caption generation failed for task {}: {}", task_id, e),
+                }
+            }
+            None => log::warn!("Captions requested but transcription is turned off in settings"),
         }
+    }
 
-        (task_id, accounts_info)
+    // Pre-flight: platforms whose duration limits the file violates are skipped up front
+    let duration_secs = if image_post {
+        None
+    } else {
+        probe_video_duration(ffmpeg_setting.as_deref(), video_path).await
     };
 
     // Detect Chrome
     let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
+    let simulate_profile_root = if request.simulate {
+        info!("Task {} runs in simulated mode", task_id);
+        Some(artifacts.dir("simulate").map_err(|e| e.to_string())?)
+    } else {
+        None
+    };
+
+    // Hooks act on real files and accounts, so simulated runs skip them
+    let hooks = if request.simulate {
+        PublishHooks::default()
+    } else {
+        load_publish_hooks(&app)
+    };
+    hooks::run(
+        &hooks,
+        HookPoint::PrePublish,
+        serde_json::json!({
+            "task_id": task_id,
+            "video_path": payload.video_path,
+            "title": payload.title,
+            "description": payload.description,
+            "tags": payload.tags,
+            "publish_at": payload.publish_at.map(|at| at.to_rfc3339()),
+            "accounts": accounts_info
+                .iter()
+                .map(|run| serde_json::json!({
+                    "account_id": run.account_id,
+                    "platform": run.platform,
+                }))
+                .collect::<Vec<_>>(),
+        }),
+    )
+    .await;
 
     let mut platform_tasks = Vec::new();
+    // Per-account automation wall times, persisted for the publish estimator
+    let video_bytes = std::fs::metadata(video_path).map(|m| m.len()).unwrap_or(0);
+    let mut timings = Vec::new();
 
     // Process each platform
-    for (account_id, platform, profile_dir_str) in &accounts_info {
+    for run in &accounts_info {
+        let AccountRun {
+            account_id,
+            platform,
+            profile_dir: profile_dir_str,
+            watermark: watermark_config,
+            task_platform_id,
+            window_name,
+            custom_fields,
+            publish_windows,
+            chrome_flags,
+            variables,
+            title_variation,
+        } = run;
         let platform_info = platforms::get_platform_info(platform)
             .ok_or_else(|| format!("Unknown platform: {}", platform))?;
 
-        let profile_dir = std::path::PathBuf::from(profile_dir_str);
+        // Held until this account's result is recorded at the end of the iteration
+        let _run_guard = match crate::tasks::global().claim_account_run(task_id, *account_id) {
+            RunClaim::Owner(guard) => guard,
+            RunClaim::InFlight(mut done) => {
+                info!(
+                    "Task {} account {} is already running, attaching to that run",
+                    task_id, account_id
+                );
+                let _ = done.wait_for(|done| *done).await;
+                platform_tasks.push(attached_result(
+                    &db,
+                    task_id,
+                    *task_platform_id,
+                    *account_id,
+                    platform,
+                ));
+                continue;
+            }
+            // Only this account waits; the task's other accounts still run
+            RunClaim::Busy {
+                task_id: busy_task_id,
+            } => {
+                let err = PlatformAutomationError::from_raw(&format!(
+                    "PROFILE_LOCKED: 该账号正在任务 {} 中发布，本次已顺延 {} 分钟后重试",
+                    busy_task_id,
+                    ACCOUNT_BUSY_RETRY_AFTER_SECS / 60
+                ));
+                record_result(
+                    &db,
+                    &mut platform_tasks,
+                    task_id,
+                    *task_platform_id,
+                    PlatformTaskResult {
+                        account_id: *account_id,
+                        platform: platform.clone(),
+                        status: "deferred".into(),
+                        message: Some(err.message),
+                        error_code: Some(err.code),
+                        action_hint: err.action_hint,
+                        debug_port_used: None,
+                        session_mode: None,
+                        automation_phase: Some("deferred".into()),
+                        schedule_mode: None,
+                        retry_after_secs: Some(ACCOUNT_BUSY_RETRY_AFTER_SECS),
+                        window_title: None,
+                        kb_link: None,
+                        preview_path: None,
+                    },
+                );
+                schedule_retry(&db, &request, *account_id, ACCOUNT_BUSY_RETRY_AFTER_SECS);
+                continue;
+            }
+        };
+
+        if payload.cancel.is_cancelled() {
+            record_result(
+                &db,
+                &mut platform_tasks,
+                task_id,
+                *task_platform_id,
+                cancelled_result(*account_id, platform, None),
+            );
+            continue;
+        }
+        if let Some(reason) =
+            duration_secs.and_then(|secs| capabilities::duration_violation(platform, secs))
+        {
+            let err =
+                PlatformAutomationError::from_raw(&format!("DURATION_OUT_OF_RANGE: {}", reason));
+            record_result(
+                &db,
+                &mut platform_tasks,
+                task_id,
+                *task_platform_id,
+                PlatformTaskResult {
+                    account_id: *account_id,
+                    platform: platform.clone(),
+                    status: "failed".into(),
+                    message: Some(err.message),
+                    error_code: Some(err.code),
+                    action_hint: err.action_hint,
+                    debug_port_used: None,
+                    session_mode: None,
+                    automation_phase: Some("preflight_failed".into()),
+                    schedule_mode: None,
+                    retry_after_secs: None,
+                    window_title: None,
+                    kb_link: None,
+                    preview_path: None,
+                },
+            );
+            continue;
+        }
+        // Accounts limited to publishing windows wait for the next one to open
+        let wanted_at = payload.publish_at.unwrap_or_else(chrono::Local::now);
+        if !request.simulate && !publish_windows.allows(wanted_at) {
+            let opens_at = publish_windows.next_allowed(wanted_at);
+            let err = PlatformAutomationError::from_raw(&format!(
+                "OUTSIDE_PUBLISH_WINDOW: 该账号仅允许在 {} 发布，已顺延至 {}",
+                publish_windows.describe(),
+                opens_at.format("%Y-%m-%d %H:%M")
+            ));
+            // A minute into the window, so the retry does not land just before it opens
+            let retry_after = (opens_at - chrono::Local::now()).num_seconds().max(0) as u64 + 60;
+            record_result(
+                &db,
+                &mut platform_tasks,
+                task_id,
+                *task_platform_id,
+                PlatformTaskResult {
+                    account_id: *account_id,
+                    platform: platform.clone(),
+                    status: "deferred".into(),
+                    message: Some(err.message),
+                    error_code: Some(err.code),
+                    action_hint: err.action_hint,
+                    debug_port_used: None,
+                    session_mode: None,
+                    automation_phase: Some("deferred".into()),
+                    schedule_mode: None,
+                    retry_after_secs: Some(retry_after),
+                    window_title: None,
+                    kb_link: None,
+                    preview_path: None,
+                },
+            );
+            schedule_retry(&db, &request, *account_id, retry_after);
+            continue;
+        }
+        // App-side scheduling for platforms that cannot schedule on their
+        // upload page: the account runs from the scheduler queue at that time
+        if let Some(at) = payload.publish_at.filter(|_| {
+            !request.simulate
+                && !platforms::registry::find(platform).is_some_and(|entry| entry.native_schedule)
+        }) {
+            record_result(
+                &db,
+                &mut platform_tasks,
+                task_id,
+                *task_platform_id,
+                PlatformTaskResult {
+                    account_id: *account_id,
+                    platform: platform.clone(),
+                    status: "deferred".into(),
+                    message: Some(format!(
+                        "{} 不支持平台定时发布，已加入应用定时队列，将于 {} 发布",
+                        platform_info.name,
+                        at.format("%Y-%m-%d %H:%M")
+                    )),
+                    error_code: None,
+                    action_hint: None,
+                    debug_port_used: None,
+                    session_mode: None,
+                    automation_phase: Some("deferred".into()),
+                    schedule_mode: Some(platforms::SCHEDULE_MODE_APP.into()),
+                    retry_after_secs: None,
+                    window_title: None,
+                    kb_link: None,
+                    preview_path: None,
+                },
+            );
+            schedule_app_publish(&db, &request, *account_id, at);
+            continue;
+        }
+        // WeChat limits how many posts may sit in review; hold this one until the queue drains
+        if platform == "wechat" && !request.simulate {
+            if let Some(queue) = review_queue::hold_before_publish(
+                &db,
+                &chrome_path,
+                *account_id,
+                Path::new(profile_dir_str),
+            )
+            .await
+            {
+                let err = PlatformAutomationError::from_raw(&format!(
+                    "WECHAT_REVIEW_THROTTLED: 视频号当前有 {} 条作品审核中（上限 {}），本次发布已暂缓",
+                    queue.in_review, queue.threshold
+                ));
+                let retry_after = retry_after_secs(&err.code);
+                record_result(
+                    &db,
+                    &mut platform_tasks,
+                    task_id,
+                    *task_platform_id,
+                    PlatformTaskResult {
+                        account_id: *account_id,
+                        platform: platform.clone(),
+                        status: "deferred".into(),
+                        message: Some(err.message),
+                        error_code: Some(err.code),
+                        action_hint: err.action_hint,
+                        debug_port_used: None,
+                        session_mode: None,
+                        automation_phase: Some("deferred".into()),
+                        schedule_mode: None,
+                        retry_after_secs: retry_after,
+                        window_title: None,
+                        kb_link: None,
+                        preview_path: None,
+                    },
+                );
+                if let Some(secs) = retry_after {
+                    schedule_retry(&db, &request, *account_id, secs);
+                }
+                continue;
+            }
+        }
+        let account_started = Instant::now();
+        set_task_platform_status(&db, *task_platform_id, "running", None, None);
+
+        // Each account may carry its own branding; render it onto a temp copy
+        let mut account_payload = payload.clone();
+        if let Some(overrides) = request.platform_overrides.get(platform) {
+            overrides.apply(&mut account_payload);
+        }
+        account_payload.custom_fields = custom_fields.clone();
+        account_payload.user_scripts = load_user_scripts(&app, platform);
+        variables.apply(&mut account_payload);
+        apply_title_variation(
+            &db,
+            *task_platform_id,
+            title_variation,
+            &mut account_payload,
+        );
+        account_payload.profile.upload_strategy_order =
+            load_strategy_order(&db, platform, *account_id, &account_payload.profile);
+        if !request.simulate {
+            tune_signal_timeouts(&db, platform, &mut account_payload.profile);
+        }
+        account_payload.profile.download_dir = payload
+            .profile
+            .download_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("account-{}", account_id)));
+        let account_progress = ProgressHandle::new(task_id, *account_id);
+        account_payload.progress = Some(account_progress);
+        account_payload.automation_log =
+            Some(AutomationLogSink::new(&app, *task_platform_id, platform));
+        if let Some(config) = watermark_config {
+            match prepare_watermarked_copy(
+                &artifacts,
+                ffmpeg_setting.as_deref(),
+                task_id,
+                *account_id,
+                &payload.video_path,
+                config,
+            )
+            .await
+            {
+                Ok(path) => account_payload.video_path = path,
+                Err(e) => {
+                    let err =
+                        PlatformAutomationError::from_raw(&format!("WATERMARK_FAILED: {}", e));
+                    record_result(
+                        &db,
+                        &mut platform_tasks,
+                        task_id,
+                        *task_platform_id,
+                        PlatformTaskResult {
+                            account_id: *account_id,
+                            platform: platform.clone(),
+                            status: "failed".into(),
+                            message: Some(err.message),
+                            error_code: Some(err.code),
+                            action_hint: err.action_hint,
+                            debug_port_used: None,
+                            session_mode: None,
+                            automation_phase: Some("watermark_failed".into()),
+                            schedule_mode: None,
+                            retry_after_secs: None,
+                            window_title: None,
+                            kb_link: None,
+                            preview_path: None,
+                        },
+                    );
+                    continue;
+                }
+            }
+        }
+
+        let profile_dir = match &simulate_profile_root {
+            Some(root) => root.join(format!("profile-{}", platform)),
+            None => std::path::PathBuf::from(profile_dir_str),
+        };
+        let upload_url = if request.simulate {
+            platforms::simulated::upload_url_for(platform)
+                .await
+                .map_err(|e| e.to_string())?
+        } else {
+            platform_info.upload_url.clone()
+        };
 
         info!(
             "Publishing to {} (account {})",
             platform_info.name, account_id
         );
 
-        let session_result =
-            chrome::prepare_chrome_session(&chrome_path, &profile_dir, &platform_info.upload_url)
-                .await;
+        account_progress.phase("session_preparing");
+        prewarm::wait_for(&profile_dir).await;
+        // Held until this account's automation finishes, released on drop
+        let profile_lock =
+            profile_lock::acquire(&profile_dir, "publish", &format!("task {}", task_id));
+        let session_result = match &profile_lock {
+            Ok(lock) => {
+                chrome::prepare_chrome_session(
+                    &chrome_path,
+                    &profile_dir,
+                    &upload_url,
+                    Some(window_name),
+                    chrome_flags,
+                    lock,
+                )
+                .await
+            }
+            Err(e) => Err(anyhow::anyhow!(e.to_string())),
+        };
 
         match session_result {
             Ok(session) => {
                 let session_mode = Some(session.mode.as_str().to_string());
-                let automation_result = tokio::time::timeout(
-                    std::time::Duration::from_secs(AUTOMATION_TIMEOUT_SECS),
-                    automate_platform(
-                        &chrome_path,
-                        &session,
-                        &profile_dir,
-                        platform,
-                        &platform_info.upload_url,
-                        &request.video_path,
-                        &request.title,
-                        request.description.as_deref().unwrap_or(""),
-                        &request.tags,
-                    ),
-                )
-                .await;
+                let window_title = (session.mode == chrome::ChromeSessionMode::LaunchedNew)
+                    .then(|| window_name.clone());
+                let automation_timeout = automation_timeout(&account_payload);
+                // Cancelling drops the automation future, which closes its CDP connection
+                let automation_result = tokio::select! {
+                    result = tokio::time::timeout(
+                        automation_timeout,
+                        automate_platform(
+                            &chrome_path,
+                            &session,
+                            &profile_dir,
+                            platform,
+                            &upload_url,
+                            &account_payload,
+                            request.simulate,
+                        ),
+                    ) => result,
+                    _ = payload.cancel.cancelled() => {
+                        Ok(Err(PlatformAutomationError::from_raw("CANCELLED: 发布任务已取消")))
+                    }
+                };
 
                 match automation_result {
                     Ok(Ok(success)) => {
-                        let status = if success.automation_phase == "manual_continue" {
-                            "launched"
-                        } else {
-                            "automated"
+                        let status = match success.automation_phase {
+                            "manual_continue" => "launched",
+                            "submitted" => "published",
+                            _ => "automated",
                         };
-                        platform_tasks.push(PlatformTaskResult {
-                            account_id: *account_id,
-                            platform: platform.clone(),
-                            status: status.into(),
-                            message: Some(success.message),
-                            error_code: None,
-                            action_hint: None,
-                            debug_port_used: Some(success.debug_port_used),
-                            session_mode,
-                            automation_phase: Some(success.automation_phase.into()),
-                        });
+                        hooks::run(
+                            &hooks,
+                            HookPoint::PostUpload,
+                            serde_json::json!({
+                                "task_id": task_id,
+                                "account_id": account_id,
+                                "platform": platform,
+                                "video_path": account_payload.video_path,
+                                "status": status,
+                                "automation_phase": success.automation_phase,
+                            }),
+                        )
+                        .await;
+                        if let Some(meter) = success.bandwidth {
+                            spawn_bandwidth_settle(&app, *task_platform_id, meter);
+                        }
+                        record_page_snapshot(&db, platform, success.snapshot.as_ref());
+                        // The platform could not take the schedule; publish again at that time
+                        if success.schedule_mode == Some(platforms::SCHEDULE_MODE_APP) {
+                            if let Some(at) = account_payload.publish_at {
+                                schedule_app_publish(&db, &request, *account_id, at);
+                            }
+                        }
+                        record_result(
+                            &db,
+                            &mut platform_tasks,
+                            task_id,
+                            *task_platform_id,
+                            PlatformTaskResult {
+                                account_id: *account_id,
+                                platform: platform.clone(),
+                                status: status.into(),
+                                message: Some(success.message),
+                                error_code: None,
+                                action_hint: None,
+                                debug_port_used: Some(success.debug_port_used),
+                                session_mode,
+                                automation_phase: Some(success.automation_phase.into()),
+                                schedule_mode: success.schedule_mode.map(Into::into),
+                                retry_after_secs: None,
+                                window_title,
+                                kb_link: None,
+                                preview_path: success.preview_path,
+                            },
+                        );
+                    }
+                    Ok(Err(err)) if err.code == "CANCELLED" => {
+                        info!("Automation cancelled for {}", platform_info.name);
+                        record_result(
+                            &db,
+                            &mut platform_tasks,
+                            task_id,
+                            *task_platform_id,
+                            cancelled_result(*account_id, platform, session_mode),
+                        );
                     }
                     Ok(Err(err)) => {
                         info!(
                             "Automation failed for {}: {}",
                             platform_info.name, err.message
                         );
-                        platform_tasks.push(PlatformTaskResult {
-                            account_id: *account_id,
-                            platform: platform.clone(),
-                            status: "launched".into(),
-                            message: Some(format!(
-                                "Chrome 已打开 {}，但自动填充失败：{}。请手动操作。",
-                                platform_info.name, err.message
-                            )),
-                            error_code: Some(err.code),
-                            action_hint: err.action_hint,
-                            debug_port_used: err.debug_port_used.or(Some(session.port)),
-                            session_mode,
-                            automation_phase: Some("automation_failed".into()),
-                        });
+                        record_page_snapshot(&db, platform, err.snapshot.as_ref());
+                        let retry_after = retry_after_secs(&err.code);
+                        let phase = if err.code == "INTERVENTION_REQUIRED" {
+                            // Automation stops here; the page is left as-is for the user
+                            let event = InterventionRequired {
+                                task_id,
+                                account_id: *account_id,
+                                platform: platform.clone(),
+                                message: err.message.clone(),
+                            };
+                            if let Err(e) = app.emit(INTERVENTION_REQUIRED_EVENT, event) {
+                                log::warn!("Failed to emit intervention event: {}", e);
+                            }
+                            "intervention_required"
+                        } else if retry_after.is_some() {
+                            "deferred"
+                        } else {
+                            "automation_failed"
+                        };
+                        record_result(
+                            &db,
+                            &mut platform_tasks,
+                            task_id,
+                            *task_platform_id,
+                            PlatformTaskResult {
+                                account_id: *account_id,
+                                platform: platform.clone(),
+                                status: if retry_after.is_some() {
+                                    "deferred".into()
+                                } else {
+                                    "launched".into()
+                                },
+                                message: Some(if platforms::english() {
+                                    format!(
+                                        "Chrome opened {} but filling the form failed: {}. Please continue manually.",
+                                        platforms::display_name(platform),
+                                        err.message
+                                    )
+                                } else {
+                                    format!(
+                                        "Chrome 已打开 {}，但自动填充失败：{}。请手动操作。",
+                                        platforms::display_name(platform),
+                                        err.message
+                                    )
+                                }),
+                                error_code: Some(err.code),
+                                action_hint: err.action_hint,
+                                debug_port_used: err.debug_port_used.or(Some(session.port)),
+                                session_mode,
+                                automation_phase: Some(phase.into()),
+                                schedule_mode: None,
+                                retry_after_secs: retry_after,
+                                window_title,
+                                kb_link: None,
+                                preview_path: None,
+                            },
+                        );
+                        if let Some(secs) = retry_after {
+                            schedule_retry(&db, &request, *account_id, secs);
+                        }
                     }
                     Err(_) => {
-                        platform_tasks.push(PlatformTaskResult {
-                            account_id: *account_id,
-                            platform: platform.clone(),
-                            status: "launched".into(),
-                            message: Some(format!(
-                                "Chrome 已打开 {}，自动化处理超时（{} 秒）。请手动继续。",
-                                platform_info.name, AUTOMATION_TIMEOUT_SECS
-                            )),
-                            error_code: Some("AUTOMATION_TIMEOUT".into()),
-                            action_hint: Some(ACTION_HINT_AUTOMATION_TIMEOUT.into()),
-                            debug_port_used: Some(session.port),
-                            session_mode,
-                            automation_phase: Some("timeout".into()),
-                        });
+                        record_result(
+                            &db,
+                            &mut platform_tasks,
+                            task_id,
+                            *task_platform_id,
+                            PlatformTaskResult {
+                                account_id: *account_id,
+                                platform: platform.clone(),
+                                status: "launched".into(),
+                                message: Some(if platforms::english() {
+                                    format!(
+                                        "Chrome opened {} but automation timed out ({} s). Please continue manually.",
+                                        platforms::display_name(platform),
+                                        automation_timeout.as_secs()
+                                    )
+                                } else {
+                                    format!(
+                                        "Chrome 已打开 {}，自动化处理超时（{} 秒）。请手动继续。",
+                                        platforms::display_name(platform),
+                                        automation_timeout.as_secs()
+                                    )
+                                }),
+                                error_code: Some("AUTOMATION_TIMEOUT".into()),
+                                action_hint: Some(ACTION_HINT_AUTOMATION_TIMEOUT.into()),
+                                debug_port_used: Some(session.port),
+                                session_mode,
+                                automation_phase: Some("timeout".into()),
+                                schedule_mode: None,
+                                retry_after_secs: None,
+                                window_title,
+                                kb_link: None,
+                                preview_path: None,
+                            },
+                        );
                     }
                 }
             }
@@ -256,32 +1188,144 @@ pub async fn create_publish_task(
                 } else {
                     "automation_failed"
                 };
-                platform_tasks.push(PlatformTaskResult {
-                    account_id: *account_id,
-                    platform: platform.clone(),
-                    status: status.into(),
-                    message: Some(err.message),
-                    error_code: Some(err.code),
-                    action_hint: err.action_hint,
-                    debug_port_used: err.debug_port_used,
-                    session_mode: Some("manual_only".into()),
-                    automation_phase: Some(phase.into()),
-                });
+                record_result(
+                    &db,
+                    &mut platform_tasks,
+                    task_id,
+                    *task_platform_id,
+                    PlatformTaskResult {
+                        account_id: *account_id,
+                        platform: platform.clone(),
+                        status: status.into(),
+                        message: Some(err.message),
+                        error_code: Some(err.code),
+                        action_hint: err.action_hint,
+                        debug_port_used: err.debug_port_used,
+                        session_mode: Some("manual_only".into()),
+                        automation_phase: Some(phase.into()),
+                        schedule_mode: None,
+                        retry_after_secs: None,
+                        window_title: None,
+                        kb_link: None,
+                        preview_path: None,
+                    },
+                );
             }
         }
+        let duration_ms = account_started.elapsed().as_millis() as i64;
+        if let Ok(conn) = db.conn.lock() {
+            if let Err(e) =
+                queries::update_task_platform_duration(&conn, *task_platform_id, duration_ms)
+            {
+                log::warn!(
+                    "Failed to record duration for task platform {}: {}",
+                    task_platform_id,
+                    e
+                );
+            }
+        }
+        // Simulated runs say nothing about real upload times
+        if let Some(result) = platform_tasks.last().filter(|_| !request.simulate) {
+            let signal_latency_ms = progress::snapshot(task_id).and_then(|snapshot| {
+                snapshot
+                    .accounts
+                    .iter()
+                    .find(|account| account.account_id == *account_id)
+                    .and_then(|account| account.signal_latency_ms)
+            });
+            let timing = queries::PublishTiming {
+                video_bytes: video_bytes as i64,
+                duration_ms,
+                signal_latency_ms: signal_latency_ms.map(|ms| ms as i64),
+            };
+            timings.push((*account_id, platform.clone(), timing, result.status.clone()));
+        }
     }
 
+    drop(run_guard);
+
     // Update task status
-    {
+    let task_status = {
         let conn = db.conn.lock().map_err(|e| e.to_string())?;
-        let has_automated = platform_tasks.iter().any(|t| t.status == "automated");
-        let new_status = if has_automated {
+        let has_automated = platform_tasks
+            .iter()
+            .any(|t| matches!(t.status.as_str(), "automated" | "published"));
+        let all_deferred =
+            !platform_tasks.is_empty() && platform_tasks.iter().all(|t| t.status == "deferred");
+        let all_published =
+            !platform_tasks.is_empty() && platform_tasks.iter().all(|t| t.status == "published");
+        let new_status = if all_published {
+            "completed"
+        } else if payload.cancel.is_cancelled() && !has_automated {
+            "cancelled"
+        } else if has_automated {
             "publishing"
+        } else if all_deferred {
+            "deferred"
         } else {
             "partial"
         };
         queries::update_task_status(&conn, task_id, new_status).map_err(|e| e.to_string())?;
+
+        for (account_id, platform, timing, status) in &timings {
+            if let Err(e) = queries::insert_publish_timing(
+                &conn,
+                task_id,
+                *account_id,
+                platform,
+                timing,
+                status,
+            ) {
+                log::warn!(
+                    "Failed to record publish timing for task {}: {}",
+                    task_id,
+                    e
+                );
+            }
+        }
+
+        // Feed the cookie expiry estimate; a failure here must not fail the publish result
+        for task in platform_tasks.iter().filter(|_| !request.simulate) {
+            let observed = if matches!(task.status.as_str(), "automated" | "published") {
+                session_expiry::record_authenticated(&conn, task.account_id)
+            } else if task.error_code.as_deref() == Some("LOGIN_REQUIRED") {
+                session_expiry::record_expired(&conn, task.account_id)
+            } else {
+                continue;
+            };
+            if let Err(e) = observed {
+                log::warn!("[登录有效期] account={} 更新失败: {}", task.account_id, e);
+            }
+        }
+        if !request.simulate {
+            record_strategy_stats(&conn, task_id, &platform_tasks);
+        }
+        new_status
+    };
+
+    for result in platform_tasks
+        .iter()
+        .filter(|t| t.error_code.is_some() && t.status != "deferred")
+    {
+        hooks::run(
+            &hooks,
+            HookPoint::OnFailure,
+            serde_json::json!({ "task_id": task_id, "result": result }),
+        )
+        .await;
     }
+    hooks::run(
+        &hooks,
+        HookPoint::PostPublish,
+        serde_json::json!({
+            "task_id": task_id,
+            "status": task_status,
+            "video_path": payload.video_path,
+            "title": payload.title,
+            "results": platform_tasks,
+        }),
+    )
+    .await;
 
     Ok(PublishResult {
         task_id,
@@ -289,89 +1333,660 @@ pub async fn create_publish_task(
     })
 }
 
-/// Run platform-specific automation via CDP
-async fn automate_platform(
-    _chrome_path: &Path,
-    session: &chrome::ChromeSession,
-    profile_dir: &Path,
+fn cancelled_result(
+    account_id: i64,
     platform: &str,
-    upload_url: &str,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<AutomationSuccess, PlatformAutomationError> {
-    // Wait for Chrome to be ready
-    info!(
-        "Waiting for Chrome to be ready: port={} mode={} platform={} target_url={}",
-        session.port,
-        session.mode.as_str(),
-        platform,
-        upload_url
-    );
-    let session_ready_start = Instant::now();
-    let ready_port = chrome::wait_for_chrome_ready(session, profile_dir, 30)
-        .await
-        .map_err(|e| {
-            PlatformAutomationError::from_raw(&e.to_string()).with_debug_port(session.port)
-        })?;
-    let session_ready_ms = session_ready_start.elapsed().as_millis();
-    info!(
-        "[Automation timing] platform={} session_ready_ms={} ready_port={} session_mode={}",
-        platform,
-        session_ready_ms,
-        ready_port,
-        session.mode.as_str()
-    );
-
-    // Connect via CDP
-    let cdp_connect_start = Instant::now();
-    info!("Connecting to Chrome via CDP on port {}...", ready_port);
-    let (_browser, page) = automation::connect_to_chrome(ready_port, upload_url)
-        .await
-        .map_err(|e| {
-            PlatformAutomationError::from_raw(&e.to_string()).with_debug_port(ready_port)
-        })?;
-    let cdp_connect_ms = cdp_connect_start.elapsed().as_millis();
-    info!(
-        "[Automation timing] platform={} cdp_connect_ms={} ready_port={}",
-        platform, cdp_connect_ms, ready_port
-    );
+    session_mode: Option<String>,
+) -> PlatformTaskResult {
+    PlatformTaskResult {
+        account_id,
+        platform: platform.to_string(),
+        status: "cancelled".into(),
+        message: Some("发布任务已取消".into()),
+        error_code: Some("CANCELLED".into()),
+        action_hint: None,
+        debug_port_used: None,
+        session_mode,
+        automation_phase: Some("cancelled".into()),
+        schedule_mode: None,
+        retry_after_secs: None,
+        window_title: None,
+        kb_link: None,
+        preview_path: None,
+    }
+}
 
-    // Run platform-specific automation
-    let upload_trigger_start = Instant::now();
-    let upload_signal = match platform {
-        "douyin" => crate::platforms::douyin::auto_publish(&page, video_path, title, description, tags)
-            .await,
-        "xiaohongshu" => {
-            crate::platforms::xiaohongshu::auto_publish(&page, video_path, title, description, tags)
-                .await
+/// Vary the rendered title per the account's settings and record what was
+/// published, so the task shows which title each account got
+fn apply_title_variation(
+    db: &Database,
+    task_platform_id: i64,
+    variation: &TitleVariation,
+    payload: &mut platforms::PublishPayload,
+) {
+    let varied = match variation.apply(&payload.title) {
+        Ok(Some(varied)) => varied,
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!("[标题变体] 生成失败，使用原标题：{}", e);
+            return;
         }
-        "bilibili" => {
-            crate::platforms::bilibili::auto_publish(&page, video_path, title, description, tags)
-                .await
+    };
+    info!("[标题变体] {} -> {}", payload.title, varied);
+    let stored = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .and_then(|conn| {
+            queries::update_task_platform_applied_title(&conn, task_platform_id, &varied)
+        });
+    if let Err(e) = stored {
+        log::warn!(
+            "Failed to store applied title for task platform {}: {}",
+            task_platform_id,
+            e
+        );
+    }
+    payload.title = varied;
+}
+
+/// Result of an account whose run another trigger of the same task carried
+/// out, read back from what that run recorded
+fn attached_result(
+    db: &Database,
+    task_id: i64,
+    task_platform_id: i64,
+    account_id: i64,
+    platform: &str,
+) -> PlatformTaskResult {
+    let row = db.conn.lock().ok().and_then(|conn| {
+        queries::get_task_platforms(&conn, task_id)
+            .ok()?
+            .into_iter()
+            .find(|row| row.id == task_platform_id)
+    });
+    PlatformTaskResult {
+        account_id,
+        platform: platform.to_string(),
+        status: row
+            .as_ref()
+            .map_or_else(|| "failed".to_string(), |row| row.status.clone()),
+        message: row.and_then(|row| row.error_message),
+        error_code: None,
+        action_hint: None,
+        debug_port_used: None,
+        session_mode: None,
+        automation_phase: Some("attached".into()),
+        schedule_mode: None,
+        retry_after_secs: None,
+        window_title: None,
+        kb_link: None,
+        preview_path: None,
+    }
+}
+
+/// Collect an account's result, mark it finished in the live progress snapshot
+/// and persist it right away, so a crash later in the run does not leave it
+/// looking unfinished
+fn record_result(
+    db: &Database,
+    platform_tasks: &mut Vec<PlatformTaskResult>,
+    task_id: i64,
+    task_platform_id: i64,
+    mut result: PlatformTaskResult,
+) {
+    if let Some(code) = &result.error_code {
+        result.kb_link = db.conn.lock().ok().and_then(|conn| {
+            queries::find_kb_link(&conn, &result.platform, code)
+                .ok()
+                .flatten()
+        });
+    }
+    progress::finish_account(
+        task_id,
+        result.account_id,
+        &result.status,
+        result.automation_phase.as_deref(),
+    );
+    set_task_platform_status(
+        db,
+        task_platform_id,
+        &result.status,
+        result.message.as_deref(),
+        result.error_code.as_deref(),
+    );
+    if let Some(path) = &result.preview_path {
+        let stored = db
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))
+            .and_then(|conn| queries::update_task_platform_preview(&conn, task_platform_id, path));
+        if let Err(e) = stored {
+            log::warn!(
+                "Failed to store preview for task platform {}: {}",
+                task_platform_id,
+                e
+            );
         }
-        "wechat" => crate::platforms::wechat::auto_publish(&page, video_path, title, description, tags)
-            .await,
-        "youtube" => {
-            crate::platforms::youtube::auto_publish(&page, video_path, title, description, tags)
-                .await
+    }
+    platform_tasks.push(result);
+}
+
+/// Count the upload strategy and signal each run ended with. Only runs that
+/// reached the upload strategies count; preflight failures, deferred and
+/// manual runs say nothing about selectors. The strategies each run tried also
+/// feed the learned per-account strategy order.
+fn record_strategy_stats(
+    conn: &rusqlite::Connection,
+    task_id: i64,
+    platform_tasks: &[PlatformTaskResult],
+) {
+    let Some(progress) = progress::snapshot(task_id) else {
+        return;
+    };
+    for result in platform_tasks {
+        let succeeded = match result.status.as_str() {
+            "automated" | "published" => true,
+            "failed" => false,
+            _ => continue,
+        };
+        let Some(account) = progress
+            .accounts
+            .iter()
+            .find(|account| account.account_id == result.account_id)
+            .filter(|account| account.strategy.is_some())
+        else {
+            continue;
+        };
+        if let Err(e) = strategy_order::record(
+            conn,
+            &result.platform,
+            result.account_id,
+            &account.strategy_attempts,
+        ) {
+            log::warn!("Failed to record strategy outcomes: {}", e);
         }
-        _ => {
-            return Ok(AutomationSuccess {
-                message: "Chrome 已打开到平台上传页面。请手动完成操作。".into(),
-                debug_port_used: ready_port,
-                automation_phase: "manual_continue",
-            });
+        if let Err(e) = telemetry::record(
+            conn,
+            &result.platform,
+            account.strategy.as_deref(),
+            account.last_signal.as_deref(),
+            succeeded,
+        ) {
+            log::warn!("Failed to record strategy statistics: {}", e);
         }
     }
-    .map_err(|e| {
-        let normalized = normalize_platform_error(e.to_string());
-        PlatformAutomationError::from_raw(&normalized).with_debug_port(ready_port)
-    })?;
+}
 
-    let upload_trigger_ms = upload_trigger_start.elapsed().as_millis();
-    info!(
+/// Store the selector snapshot of the page a run ended on, so markup changes
+/// show up in the platform's snapshot history; failures only log a warning
+fn record_page_snapshot(db: &Database, platform: &str, page_snapshot: Option<&PageSnapshot>) {
+    let Some(page_snapshot) = page_snapshot else {
+        return;
+    };
+    let recorded = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .and_then(|conn| snapshot::record(&conn, platform, page_snapshot));
+    if let Err(e) = recorded {
+        log::warn!("Failed to store page snapshot for {}: {}", platform, e);
+    }
+}
+
+/// Keep metering the run's page in the background until the upload has gone
+/// quiet, then store the bytes with the task platform row
+fn spawn_bandwidth_settle(app: &AppHandle, task_platform_id: i64, meter: BandwidthMeter) {
+    let app = app.clone();
+    crate::tasks::global().spawn(
+        format!("bandwidth:task_platform{}", task_platform_id),
+        async move {
+            let usage = meter.settle(bandwidth::SETTLE_IDLE).await;
+            let db = app.state::<Database>();
+            let stored = db
+                .conn
+                .lock()
+                .map_err(|e| anyhow::anyhow!(e.to_string()))
+                .and_then(|conn| {
+                    queries::update_task_platform_bandwidth(
+                        &conn,
+                        task_platform_id,
+                        usage.bytes_sent,
+                        usage.bytes_received,
+                    )
+                });
+            match stored {
+                Ok(()) => info!(
+                    "Task platform {} used {} bytes up, {} bytes down",
+                    task_platform_id, usage.bytes_sent, usage.bytes_received
+                ),
+                Err(e) => log::warn!(
+                    "Failed to store bandwidth for task platform {}: {}",
+                    task_platform_id,
+                    e
+                ),
+            }
+        },
+    );
+}
+
+/// The platform's pre/post-fill snippets; an unreadable file only skips them
+fn load_user_scripts(app: &AppHandle, platform: &str) -> platforms::user_scripts::UserScripts {
+    let loaded =
+        startup::data_dir(app).and_then(|dir| platforms::user_scripts::load(&dir, platform));
+    match loaded {
+        Ok(scripts) => scripts,
+        Err(e) => {
+            log::warn!("User scripts for {} skipped: {}", platform, e);
+            Default::default()
+        }
+    }
+}
+
+/// Upload strategies ordered by how well they worked for this account; the
+/// built-in order when nothing has been learned or the lookup fails
+fn load_strategy_order(
+    db: &Database,
+    platform: &str,
+    account_id: i64,
+    profile: &platforms::AutomationProfile,
+) -> Vec<UploadStrategy> {
+    let default = UploadStrategy::default_order(platform, profile.direct_file_set_first);
+    let learned = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .and_then(|conn| strategy_order::learned_order(&conn, platform, account_id, &default));
+    match learned {
+        Ok(order) => {
+            if order != default {
+                info!(
+                    "Account {} tries upload strategies in learned order {:?}",
+                    account_id,
+                    order.iter().map(|s| s.label()).collect::<Vec<_>>()
+                );
+            }
+            order
+        }
+        Err(e) => {
+            log::warn!("Learned strategy order for {} skipped: {}", platform, e);
+            default
+        }
+    }
+}
+
+/// Fit the signal timeouts to how fast this platform's upload signals showed
+/// up recently; errors keep the profile's built-in timeouts
+fn tune_signal_timeouts(db: &Database, platform: &str, profile: &mut platforms::AutomationProfile) {
+    let tuned = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .and_then(|conn| timeout_tuning::apply(&conn, platform, profile));
+    match tuned {
+        Ok(()) => info!(
+            "Signal timeouts for {}: fast={}s slow_fallback={}s",
+            platform, profile.fast_signal_timeout_secs, profile.slow_fallback_signal_timeout_secs
+        ),
+        Err(e) => log::warn!("Signal timeout tuning for {} skipped: {}", platform, e),
+    }
+}
+
+/// The configured pipeline hooks; an unreadable file only skips them
+fn load_publish_hooks(app: &AppHandle) -> PublishHooks {
+    match startup::data_dir(app).and_then(|dir| hooks::load(&dir)) {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            log::warn!("Publish hooks skipped: {}", e);
+            Default::default()
+        }
+    }
+}
+
+/// Best effort: a failed status write must not abort the publish loop
+fn set_task_platform_status(
+    db: &Database,
+    id: i64,
+    status: &str,
+    message: Option<&str>,
+    error_code: Option<&str>,
+) {
+    let written = db
+        .conn
+        .lock()
+        .map_err(|e| anyhow::anyhow!(e.to_string()))
+        .and_then(|conn| {
+            queries::update_task_platform_status(&conn, id, status, message, error_code)
+        });
+    if let Err(e) = written {
+        log::warn!("Failed to update task platform {} to {}: {}", id, status, e);
+    }
+}
+
+fn report_phase(payload: &platforms::PublishPayload, phase: &str) {
+    if let Some(progress) = &payload.progress {
+        progress.phase(phase);
+    }
+}
+
+/// Filled-form screenshots of a task, one per account that got that far, for
+/// reviewing every platform from one gallery before confirming in Chrome
+#[tauri::command]
+pub fn get_task_previews(
+    db: State<'_, Database>,
+    task_id: i64,
+) -> Result<Vec<queries::TaskPreview>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_task_previews(&conn, task_id).map_err(|e| e.to_string())
+}
+
+/// Title, per-platform status, links and times of a task, plus the same as a
+/// text block for pasting into a team chat
+#[tauri::command]
+pub fn get_task_share_summary(
+    db: State<'_, Database>,
+    task_id: i64,
+) -> Result<share::TaskShareSummary, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    share::build(&conn, task_id).map_err(|e| e.to_string())
+}
+
+/// Launch Chrome and open the upload pages of the selected accounts in the
+/// background while the user is still composing, so publishing skips the
+/// session setup. Returns the accounts that were not pre-warmed and why.
+#[tauri::command]
+pub fn prewarm_publish(
+    db: State<'_, Database>,
+    account_ids: Vec<i64>,
+) -> Result<Vec<prewarm::PrewarmSkipped>, String> {
+    let chrome_path = chrome::detect_chrome().map_err(|e| e.to_string())?;
+    let accounts = db.get_all_accounts().map_err(|e| e.to_string())?;
+    let mut skipped = Vec::new();
+    for account_id in account_ids {
+        let started = match accounts.iter().find(|a| a.id == account_id) {
+            Some(account) => prewarm::start(&chrome_path, account),
+            None => Err(anyhow::anyhow!("Account {} not found", account_id)),
+        };
+        if let Err(e) = started {
+            skipped.push(prewarm::PrewarmSkipped {
+                account_id,
+                reason: e.to_string(),
+            });
+        }
+    }
+    Ok(skipped)
+}
+
+/// Live phase, strategy, last signal and elapsed time per account of a publish
+/// task. Kept in memory only, so None for tasks from before the last restart.
+#[tauri::command]
+pub fn get_task_progress(task_id: i64) -> Result<Option<TaskProgress>, String> {
+    Ok(progress::snapshot(task_id))
+}
+
+/// Probed video duration, or None when ffmpeg is unavailable (duration checks are then skipped)
+async fn probe_video_duration(ffmpeg_setting: Option<&str>, video: &Path) -> Option<f64> {
+    let ffmpeg = match ffmpeg::detect_ffmpeg(ffmpeg_setting) {
+        Ok(ffmpeg) => ffmpeg,
+        Err(e) => {
+            log::warn!("Skipping duration pre-flight: {}", e);
+            return None;
+        }
+    };
+    match ffmpeg::probe_duration_secs(&ffmpeg, video).await {
+        Ok(secs) => Some(secs),
+        Err(e) => {
+            log::warn!("Skipping duration pre-flight: {}", e);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformEligibility {
+    pub platform: String,
+    pub eligible: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoEligibility {
+    /// None when ffmpeg is unavailable and the duration could not be checked
+    pub duration_secs: Option<f64>,
+    pub platforms: Vec<PlatformEligibility>,
+    /// Expected run time for the eligible platforms, or for the eligible
+    /// accounts among `account_ids` when given
+    pub estimate: PublishEstimate,
+    /// Accounts among `account_ids` whose publishing windows exclude the
+    /// requested time; those runs would be deferred
+    pub window_warnings: Vec<PublishWindowWarning>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishWindowWarning {
+    pub account_id: i64,
+    pub platform: String,
+    /// `18:00–22:00、07:00–09:00`
+    pub windows: String,
+    /// When the next window opens, RFC 3339
+    pub next_allowed_at: String,
+}
+
+/// Check the video against each platform's duration limits before publishing,
+/// estimate how long publishing it would take, and flag accounts whose
+/// publishing windows exclude `publish_at` (or now)
+#[tauri::command]
+pub async fn check_video_eligibility(
+    db: State<'_, Database>,
+    video_path: String,
+    platforms: Vec<String>,
+    account_ids: Option<Vec<i64>>,
+    publish_at: Option<String>,
+) -> Result<VideoEligibility, String> {
+    let wanted_at = match publish_at.as_deref().filter(|raw| !raw.trim().is_empty()) {
+        Some(raw) => parse_publish_at(raw)?,
+        None => chrono::Local::now(),
+    };
+    let ffmpeg_setting = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        queries::get_setting(&conn, ffmpeg::SETTING_FFMPEG_PATH).map_err(|e| e.to_string())?
+    };
+    let video = Path::new(&video_path);
+    let duration_secs = probe_video_duration(ffmpeg_setting.as_deref(), video).await;
+    let platforms = platforms
+        .into_iter()
+        .map(|platform| {
+            let reason =
+                duration_secs.and_then(|secs| capabilities::duration_violation(&platform, secs));
+            PlatformEligibility {
+                eligible: reason.is_none(),
+                platform,
+                reason,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let video_bytes = std::fs::metadata(video).map(|m| m.len()).unwrap_or(0);
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let is_eligible = |platform: &str| {
+        platforms
+            .iter()
+            .any(|p| p.eligible && p.platform == platform)
+    };
+    let targets = match &account_ids {
+        Some(ids) => queries::get_all_accounts(&conn)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|a| ids.contains(&a.id) && is_eligible(&a.platform))
+            .map(|a| (Some(a.id), a.platform))
+            .collect::<Vec<_>>(),
+        None => platforms
+            .iter()
+            .filter(|p| p.eligible)
+            .map(|p| (None, p.platform.clone()))
+            .collect(),
+    };
+    let estimate = estimate::estimate(&conn, video_bytes, &targets).map_err(|e| e.to_string())?;
+
+    let mut window_warnings = Vec::new();
+    for (account_id, platform) in &targets {
+        let Some(account_id) = account_id else {
+            continue;
+        };
+        let windows = PublishWindows::load(&conn, *account_id).map_err(|e| e.to_string())?;
+        if !windows.allows(wanted_at) {
+            window_warnings.push(PublishWindowWarning {
+                account_id: *account_id,
+                platform: platform.clone(),
+                windows: windows.describe(),
+                next_allowed_at: windows.next_allowed(wanted_at).to_rfc3339(),
+            });
+        }
+    }
+    Ok(VideoEligibility {
+        duration_secs,
+        platforms,
+        estimate,
+        window_warnings,
+    })
+}
+
+/// Run platform-specific automation via CDP
+async fn automate_platform(
+    _chrome_path: &Path,
+    session: &chrome::ChromeSession,
+    profile_dir: &Path,
+    platform: &str,
+    upload_url: &str,
+    payload: &platforms::PublishPayload,
+    simulate: bool,
+) -> Result<AutomationSuccess, PlatformAutomationError> {
+    // Wait for Chrome to be ready
+    info!(
+        "Waiting for Chrome to be ready: port={} mode={} platform={} target_url={}",
+        session.port,
+        session.mode.as_str(),
+        platform,
+        upload_url
+    );
+    let session_ready_start = Instant::now();
+    let ready_port = chrome::wait_for_chrome_ready(session, profile_dir, 30)
+        .await
+        .map_err(|e| {
+            phase_log::record(platform, "session_failed", &e.to_string());
+            PlatformAutomationError::from_raw(&e.to_string()).with_debug_port(session.port)
+        })?;
+    phase_log::record(platform, "session_ready", &format!("port={}", ready_port));
+    report_phase(payload, "session_ready");
+    let session_ready_ms = session_ready_start.elapsed().as_millis();
+    info!(
+        "[Automation timing] platform={} session_ready_ms={} ready_port={} session_mode={}",
+        platform,
+        session_ready_ms,
+        ready_port,
+        session.mode.as_str()
+    );
+
+    // Connect via CDP
+    let cdp_connect_start = Instant::now();
+    info!("Connecting to Chrome via CDP on port {}...", ready_port);
+    let (browser, page) = automation::connect_to_chrome(ready_port, upload_url)
+        .await
+        .map_err(|e| {
+            phase_log::record(platform, "cdp_failed", &e.to_string());
+            PlatformAutomationError::from_raw(&e.to_string()).with_debug_port(ready_port)
+        })?;
+    phase_log::record(platform, "cdp_connected", upload_url);
+    let page = PageHandle::new(browser, page, upload_url);
+    report_phase(payload, "cdp_connected");
+    let meter = match BandwidthMeter::start(&page.page()).await {
+        Ok(meter) => Some(meter),
+        Err(e) => {
+            log::warn!("[流量统计] platform={} 无法监听网络流量：{}", platform, e);
+            None
+        }
+    };
+    if let Some(dir) = &payload.profile.download_dir {
+        if let Err(e) = downloads::capture(&page.page(), dir, platform).await {
+            log::warn!("[下载拦截] platform={} 无法接管下载：{}", platform, e);
+        }
+    }
+    let cdp_connect_ms = cdp_connect_start.elapsed().as_millis();
+    info!(
+        "[Automation timing] platform={} cdp_connect_ms={} ready_port={}",
+        platform, cdp_connect_ms, ready_port
+    );
+
+    // Run platform-specific automation
+    let upload_trigger_start = Instant::now();
+    let simulated_cfg = if simulate {
+        Some(platforms::simulated::publish_config().await.map_err(|e| {
+            PlatformAutomationError::from_raw(&e.to_string()).with_debug_port(ready_port)
+        })?)
+    } else {
+        None
+    };
+    let (steps, cfg): (&dyn PlatformAutomation, &platforms::PlatformPublishConfig) =
+        match (platforms::registry::find(platform), simulated_cfg.as_ref()) {
+            (_, Some(cfg)) => (&platforms::simulated::Simulated, cfg),
+            (Some(entry), None) => (entry.automation, entry.effective_config()),
+            (None, None) => {
+                return Ok(AutomationSuccess {
+                    message: "Chrome 已打开到平台上传页面。请手动完成操作。".into(),
+                    debug_port_used: ready_port,
+                    automation_phase: "manual_continue",
+                    schedule_mode: payload.publish_at.map(|_| platforms::SCHEDULE_MODE_APP),
+                    preview_path: None,
+                    bandwidth: None,
+                    snapshot: None,
+                });
+            }
+        };
+    let ctx = AutomationContext {
+        handle: &page,
+        payload,
+        cfg,
+    };
+    let automated = run_automation_steps(steps, &ctx).await;
+    // Taken on failures too: a platform redesign usually shows up as failures first
+    let page_snapshot = if simulate {
+        None
+    } else {
+        snapshot::capture(&page, platform).await
+    };
+    let upload_signal = automated.map_err(|e| {
+        let normalized = normalize_platform_error(e.to_string());
+        phase_log::record(platform, "automation_failed", &normalized);
+        let err = PlatformAutomationError::from_raw(&normalized)
+            .with_debug_port(ready_port)
+            .with_snapshot(page_snapshot.clone());
+        log_step(
+            payload,
+            "automation_failed",
+            &err.code,
+            upload_trigger_start,
+            &normalized,
+        );
+        err
+    })?;
+
+    if page.reattach_count() > 0 {
+        phase_log::record(
+            platform,
+            "cdp_reattached",
+            &format!("count={}", page.reattach_count()),
+        );
+    }
+    let upload_trigger_ms = upload_trigger_start.elapsed().as_millis();
+    phase_log::record(platform, "upload_started", &upload_signal);
+    log_step(
+        payload,
+        "upload_started",
+        "signal",
+        upload_trigger_start,
+        &upload_signal,
+    );
+    info!(
         "[Automation timing] platform={} upload_trigger_ms={} signal={} ready_port={} session_mode={}",
         platform,
         upload_trigger_ms,
@@ -380,28 +1995,262 @@ async fn automate_platform(
         session.mode.as_str()
     );
 
-    let platform_name = platform_display_name(platform);
-    Ok(AutomationSuccess {
-        message: format!(
+    let english = platforms::english();
+    let platform_name = platforms::display_name(platform);
+    let mut message = if english {
+        format!(
+            "{}: upload started and basic fields filled ({}). Check and publish in Chrome.",
+            platform_name, upload_signal
+        )
+    } else {
+        format!(
             "{}：已触发上传并尝试填写基础信息（{}）。请在 Chrome 继续检查并发布。",
             platform_name, upload_signal
-        ),
+        )
+    };
+    if let Some(at) = payload.platform_scheduled_at {
+        if !upload_signal.contains(&format!("schedule={}", platforms::SCHEDULE_MODE_PLATFORM)) {
+            let normalized = normalize_platform_error(format!(
+                "{} 未能设置平台定时发布（{}），已停止以免立即发布",
+                platform_name,
+                at.format("%Y-%m-%d %H:%M")
+            ));
+            phase_log::record(platform, "automation_failed", &normalized);
+            let err = PlatformAutomationError::from_raw(&normalized)
+                .with_debug_port(ready_port)
+                .with_snapshot(page_snapshot);
+            log_step(
+                payload,
+                "automation_failed",
+                &err.code,
+                upload_trigger_start,
+                &normalized,
+            );
+            return Err(err);
+        }
+    }
+    let schedule_mode = payload.platform_scheduled_at.or(payload.publish_at).map(|publish_at| {
+        let time = publish_at.format("%Y-%m-%d %H:%M");
+        if upload_signal.contains(&format!("schedule={}", platforms::SCHEDULE_MODE_PLATFORM)) {
+            message.push_str(&if english {
+                format!(" Scheduled on the platform for {}.", time)
+            } else {
+                format!("已在平台设置定时发布：{}。", time)
+            });
+            platforms::SCHEDULE_MODE_PLATFORM
+        } else {
+            message.push_str(&if english {
+                format!(
+                    " The platform could not schedule it, so it was not published now; the app publishes it again at {}.",
+                    time
+                )
+            } else {
+                format!(
+                    "平台未能设置定时，本次未发布，应用将于 {} 重新发布。",
+                    time
+                )
+            });
+            platforms::SCHEDULE_MODE_APP
+        }
+    });
+    let preview_path = platforms::capture_preview(&page, payload, platform).await;
+    let mut automation_phase = "upload_started";
+    // Submitting now would release an app-scheduled video early, so it waits
+    // for the user at the publish time instead
+    let app_scheduled = schedule_mode == Some(platforms::SCHEDULE_MODE_APP);
+    if !payload.manual_confirm && app_scheduled {
+        info!("[{}] 平台未设置定时，跳过自动发布", platform);
+    }
+    if !payload.manual_confirm && !app_scheduled {
+        let submit_started = Instant::now();
+        let submitted = submit_publish(steps, &ctx).await.map_err(|e| {
+            let normalized = normalize_platform_error(e.to_string());
+            phase_log::record(platform, "submit_failed", &normalized);
+            let err = PlatformAutomationError::from_raw(&normalized).with_debug_port(ready_port);
+            log_step(
+                payload,
+                "submit_failed",
+                &err.code,
+                submit_started,
+                &normalized,
+            );
+            err
+        })?;
+        phase_log::record(platform, "submitted", &submitted);
+        log_step(payload, "submitted", "ok", submit_started, &submitted);
+        message = if english {
+            format!(
+                "{}: uploaded, filled and published ({}, {}).",
+                platform_name, upload_signal, submitted
+            )
+        } else {
+            format!(
+                "{}：已上传、填写并自动发布（{}，{}）。",
+                platform_name, upload_signal, submitted
+            )
+        };
+        automation_phase = "submitted";
+    }
+    let bandwidth = meter.map(|mut meter| {
+        meter.keep_alive(page);
+        meter
+    });
+    Ok(AutomationSuccess {
+        message,
         debug_port_used: ready_port,
-        automation_phase: "upload_started",
+        automation_phase,
+        schedule_mode,
+        preview_path,
+        bandwidth,
+        snapshot: page_snapshot,
     })
 }
 
+/// Persist one step of the run for the troubleshooting panel
+fn log_step(
+    payload: &platforms::PublishPayload,
+    phase: &str,
+    result: &str,
+    started: Instant,
+    detail: &str,
+) {
+    if let Some(log) = &payload.automation_log {
+        log.record(phase, None, result, started.elapsed(), detail);
+    }
+}
+
+/// Upload the video and fill the form, leaving the page ready to submit
+async fn run_automation_steps(
+    steps: &dyn PlatformAutomation,
+    ctx: &AutomationContext<'_>,
+) -> anyhow::Result<String> {
+    attached_step(ctx, true, || steps.prepare_page(ctx)).await?;
+    // Uploading twice could post the video twice, so it is never repeated
+    let upload = attached_step(ctx, false, || steps.upload(ctx)).await?;
+    attached_step(ctx, true, || steps.fill_metadata(ctx, &upload)).await
+}
+
+/// Click the final publish button and wait for the platform's success page
+async fn submit_publish(
+    steps: &dyn PlatformAutomation,
+    ctx: &AutomationContext<'_>,
+) -> anyhow::Result<String> {
+    attached_step(ctx, true, || steps.verify_before_submit(ctx)).await?;
+    let clicked = attached_step(ctx, false, || steps.confirm_publish(ctx)).await?;
+    attached_step(ctx, true, || steps.verify_success(ctx, &clicked)).await
+}
+
+/// Run one step on a live page: the page is re-resolved before the step, and
+/// a `repeatable` step whose page detached under it (a navigation mid-step)
+/// runs once more on the reattached page
+async fn attached_step<T, F, Fut>(
+    ctx: &AutomationContext<'_>,
+    repeatable: bool,
+    step: F,
+) -> anyhow::Result<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    ctx.handle.ensure_attached().await?;
+    match step().await {
+        Err(e) if repeatable && page_handle::is_detached(&e) => {
+            log::warn!("[CDP] 步骤执行中页面会话失效，重新附加后重试：{}", e);
+            ctx.handle.reattach().await?;
+            step().await
+        }
+        result => result,
+    }
+}
+
+async fn prepare_watermarked_copy(
+    artifacts: &ArtifactStore,
+    ffmpeg_setting: Option<&str>,
+    task_id: i64,
+    account_id: i64,
+    video_path: &str,
+    config: &watermark::WatermarkConfig,
+) -> anyhow::Result<String> {
+    let ffmpeg_path = ffmpeg::detect_ffmpeg(ffmpeg_setting)?;
+    let out_dir = artifacts.dir("watermarked")?;
+    watermark::prune_old_copies(&out_dir);
+
+    let output = out_dir.join(format!("task{}-account{}.mp4", task_id, account_id));
+    watermark::apply(&ffmpeg_path, Path::new(video_path), &output, config).await?;
+    Ok(output.to_string_lossy().to_string())
+}
+
+async fn generate_captions(
+    artifacts: &ArtifactStore,
+    ffmpeg_setting: Option<&str>,
+    transcriber: &Transcriber,
+    task_id: i64,
+    video_path: &Path,
+) -> anyhow::Result<String> {
+    let ffmpeg_path = ffmpeg::detect_ffmpeg(ffmpeg_setting)?;
+    let out_dir = artifacts.dir("captions")?;
+    let srt = transcriber
+        .transcribe(
+            &ffmpeg_path,
+            video_path,
+            &out_dir,
+            &format!("task{}", task_id),
+        )
+        .await?;
+    Ok(srt.to_string_lossy().to_string())
+}
+
+fn parse_publish_at(raw: &str) -> Result<chrono::DateTime<chrono::Local>, String> {
+    let raw = raw.trim();
+    let parsed = chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|at| at.with_timezone(&chrono::Local))
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+                .iter()
+                .find_map(|fmt| chrono::NaiveDateTime::parse_from_str(raw, fmt).ok())
+                .and_then(|naive| naive.and_local_timezone(chrono::Local).single())
+        })
+        .ok_or_else(|| format!("Invalid publish_at: {}", raw))?;
+    if parsed <= chrono::Local::now() {
+        return Err(format!("publish_at must be in the future: {}", raw));
+    }
+    Ok(parsed)
+}
+
+/// Overall limit for one account's automation. Submitting waits for the
+/// upload to finish, so it gets its own budget on top.
+fn automation_timeout(payload: &platforms::PublishPayload) -> std::time::Duration {
+    let mut timeout = std::time::Duration::from_secs(payload.profile.automation_timeout_secs);
+    if !payload.manual_confirm {
+        timeout += payload
+            .profile
+            .scaled(platforms::SUBMIT_READY_TIMEOUT_SECS + platforms::SUBMIT_VERIFY_TIMEOUT_SECS);
+    }
+    timeout
+}
+
 fn normalize_platform_error(raw: String) -> String {
     let upper = raw.to_uppercase();
     if upper.contains("TARGET_PAGE_NOT_FOUND")
         || upper.contains("TARGET_PAGE_NOT_READY")
+        || upper.contains("RISK_CONTROL")
+        || upper.contains("PLATFORM_MAINTENANCE")
+        || upper.contains("WECHAT_REVIEW_THROTTLED")
         || upper.contains("LOGIN_REQUIRED")
         || upper.contains("WECHAT_CHOOSER_NOT_OPENED")
+        || upper.contains("CHOOSER_CONFLICT")
         || upper.contains("WECHAT_UPLOAD_SIGNAL_TIMEOUT")
+        || upper.contains("WECHAT_MEDIA_INCOMPLETE")
         || upper.contains("PROFILE_BUSY")
+        || upper.contains("PROFILE_LOCKED")
+        || upper.contains("INTERVENTION_REQUIRED")
         || upper.contains("CDP_NO_PAGE")
         || upper.contains("CHROME_NOT_READY")
         || upper.contains("AUTOMATION_TIMEOUT")
+        || upper.contains("SUBMIT_FAILED")
+        || upper.contains("SUBMIT_BLOCKED_VERIFICATION")
+        || upper.contains("CANCELLED")
     {
         raw
     } else {
@@ -409,17 +2258,6 @@ fn normalize_platform_error(raw: String) -> String {
     }
 }
 
-fn platform_display_name(platform: &str) -> &'static str {
-    match platform {
-        "douyin" => "抖音",
-        "xiaohongshu" => "小红书",
-        "bilibili" => "哔哩哔哩",
-        "wechat" => "微信视频号",
-        "youtube" => "YouTube",
-        _ => "平台",
-    }
-}
-
 fn classify_error(raw: &str) -> (&'static str, Option<String>) {
     let upper = raw.to_uppercase();
     if upper.contains("TARGET_PAGE_NOT_FOUND") {
@@ -428,6 +2266,21 @@ fn classify_error(raw: &str) -> (&'static str, Option<String>) {
             Some(ACTION_HINT_TARGET_PAGE_NOT_FOUND.to_string()),
         );
     }
+    if upper.contains("RISK_CONTROL") {
+        return ("RISK_CONTROL", Some(ACTION_HINT_RISK_CONTROL.to_string()));
+    }
+    if upper.contains("PLATFORM_MAINTENANCE") {
+        return (
+            "PLATFORM_MAINTENANCE",
+            Some(ACTION_HINT_PLATFORM_MAINTENANCE.to_string()),
+        );
+    }
+    if upper.contains("WECHAT_REVIEW_THROTTLED") {
+        return (
+            "WECHAT_REVIEW_THROTTLED",
+            Some(ACTION_HINT_WECHAT_REVIEW_THROTTLED.to_string()),
+        );
+    }
     if upper.contains("TARGET_PAGE_NOT_READY") {
         return (
             "TARGET_PAGE_NOT_READY",
@@ -440,6 +2293,24 @@ fn classify_error(raw: &str) -> (&'static str, Option<String>) {
             Some(ACTION_HINT_LOGIN_REQUIRED.to_string()),
         );
     }
+    if upper.starts_with("CANCELLED") {
+        return ("CANCELLED", None);
+    }
+    if upper.contains("SUBMIT_BLOCKED_VERIFICATION") {
+        return (
+            "SUBMIT_BLOCKED_VERIFICATION",
+            Some(ACTION_HINT_SUBMIT_BLOCKED_VERIFICATION.to_string()),
+        );
+    }
+    if upper.contains("SUBMIT_FAILED") {
+        return ("SUBMIT_FAILED", Some(ACTION_HINT_SUBMIT_FAILED.to_string()));
+    }
+    if upper.contains("CHOOSER_CONFLICT") {
+        return (
+            "CHOOSER_CONFLICT",
+            Some(ACTION_HINT_CHOOSER_CONFLICT.to_string()),
+        );
+    }
     if upper.contains("WECHAT_CHOOSER_NOT_OPENED") {
         return (
             "WECHAT_CHOOSER_NOT_OPENED",
@@ -452,9 +2323,27 @@ fn classify_error(raw: &str) -> (&'static str, Option<String>) {
             Some(ACTION_HINT_WECHAT_UPLOAD_SIGNAL_TIMEOUT.to_string()),
         );
     }
+    if upper.contains("WECHAT_MEDIA_INCOMPLETE") {
+        return (
+            "WECHAT_MEDIA_INCOMPLETE",
+            Some(ACTION_HINT_WECHAT_MEDIA_INCOMPLETE.to_string()),
+        );
+    }
     if upper.contains("PROFILE_BUSY") {
         return ("PROFILE_BUSY", Some(ACTION_HINT_CLOSE_WINDOW.to_string()));
     }
+    if upper.contains("INTERVENTION_REQUIRED") {
+        return (
+            "INTERVENTION_REQUIRED",
+            Some(ACTION_HINT_INTERVENTION_REQUIRED.to_string()),
+        );
+    }
+    if upper.contains("PROFILE_LOCKED") {
+        return (
+            "PROFILE_LOCKED",
+            Some(ACTION_HINT_PROFILE_LOCKED.to_string()),
+        );
+    }
     if upper.contains("CDP_NO_PAGE") {
         return ("CDP_NO_PAGE", Some(ACTION_HINT_CLOSE_WINDOW.to_string()));
     }
@@ -488,6 +2377,24 @@ fn classify_error(raw: &str) -> (&'static str, Option<String>) {
             Some(ACTION_HINT_AUTOMATION_TIMEOUT.to_string()),
         );
     }
+    if upper.contains("DURATION_OUT_OF_RANGE") {
+        return (
+            "DURATION_OUT_OF_RANGE",
+            Some(ACTION_HINT_DURATION_OUT_OF_RANGE.to_string()),
+        );
+    }
+    if upper.contains("OUTSIDE_PUBLISH_WINDOW") {
+        return (
+            "OUTSIDE_PUBLISH_WINDOW",
+            Some(ACTION_HINT_OUTSIDE_PUBLISH_WINDOW.to_string()),
+        );
+    }
+    if upper.contains("WATERMARK_FAILED") {
+        return (
+            "WATERMARK_FAILED",
+            Some(ACTION_HINT_WATERMARK_FAILED.to_string()),
+        );
+    }
 
     ("UNKNOWN", None)
 }
@@ -496,14 +2403,27 @@ fn strip_error_code_prefix(raw: &str) -> String {
     let candidates = [
         "TARGET_PAGE_NOT_FOUND:",
         "TARGET_PAGE_NOT_READY:",
+        "RISK_CONTROL:",
+        "PLATFORM_MAINTENANCE:",
+        "WECHAT_REVIEW_THROTTLED:",
         "LOGIN_REQUIRED:",
         "WECHAT_CHOOSER_NOT_OPENED:",
+        "CHOOSER_CONFLICT:",
+        "SUBMIT_FAILED:",
+        "SUBMIT_BLOCKED_VERIFICATION:",
+        "CANCELLED:",
         "WECHAT_UPLOAD_SIGNAL_TIMEOUT:",
+        "WECHAT_MEDIA_INCOMPLETE:",
         "PROFILE_BUSY:",
+        "PROFILE_LOCKED:",
+        "INTERVENTION_REQUIRED:",
         "CDP_NO_PAGE:",
         "CHROME_NOT_READY:",
         "AUTOMATION_FAILED:",
         "AUTOMATION_TIMEOUT:",
+        "WATERMARK_FAILED:",
+        "DURATION_OUT_OF_RANGE:",
+        "OUTSIDE_PUBLISH_WINDOW:",
     ];
     let upper = raw.to_uppercase();
     for prefix in candidates {
@@ -514,9 +2434,552 @@ fn strip_error_code_prefix(raw: &str) -> String {
     raw.to_string()
 }
 
+/// Publish the accounts of an interrupted task again, as a new task. Accounts
+/// that finished before the interruption are left out.
+#[tauri::command]
+pub async fn requeue_interrupted_task(
+    app: AppHandle,
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+    task_id: i64,
+) -> Result<PublishResult, String> {
+    let (request, requeued) = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let task = queries::get_task(&conn, task_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let rows = queries::get_task_platforms(&conn, task_id)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|row| row.status == "interrupted")
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return Err(format!("Task {} has no interrupted accounts", task_id));
+        }
+        let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+        for row in &rows {
+            queries::update_task_platform_status(&conn, row.id, "requeued", None, None)
+                .map_err(|e| e.to_string())?;
+        }
+        let request = request_from_task(task, &rows, &accounts);
+        (request, rows)
+    };
+
+    info!(
+        "Requeueing {} interrupted account(s) of task {}",
+        requeued.len(),
+        task_id
+    );
+    let result = create_publish_task(app, db.clone(), artifacts, request).await;
+    if result.is_err() {
+        for row in &requeued {
+            set_task_platform_status(
+                &db,
+                row.id,
+                "interrupted",
+                row.error_message.as_deref(),
+                row.error_code.as_deref(),
+            );
+        }
+    }
+    result
+}
+
+/// Rebuild the request that created `task`, limited to the accounts of `rows`
+fn request_from_task(
+    task: queries::PublishTask,
+    rows: &[queries::TaskPlatform],
+    accounts: &[queries::Account],
+) -> PublishRequest {
+    let mut platform_overrides = HashMap::new();
+    let mut platform_fields = HashMap::new();
+    for row in rows {
+        let Some(account) = accounts.iter().find(|a| a.id == row.account_id) else {
+            continue;
+        };
+        platform_overrides.insert(
+            account.platform.clone(),
+            PlatformOverride {
+                title: row.custom_title.clone(),
+                description: row.custom_description.clone(),
+                tags: row
+                    .custom_tags
+                    .as_deref()
+                    .and_then(|raw| serde_json::from_str(raw).ok()),
+            },
+        );
+        if let Some(fields) = row
+            .custom_fields
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+        {
+            platform_fields.insert(account.platform.clone(), fields);
+        }
+    }
+
+    // A publish time that has passed meanwhile publishes right away
+    let scheduled_at = task.scheduled_at.filter(|at| parse_publish_at(at).is_ok());
+    let platform_scheduled =
+        task.schedule_mode.as_deref() == Some(platforms::SCHEDULE_MODE_PLATFORM);
+    let (publish_at, platform_scheduled_at) = if platform_scheduled {
+        (None, scheduled_at)
+    } else {
+        (scheduled_at, None)
+    };
+
+    PublishRequest {
+        video_path: task.video_path,
+        title: task.title,
+        description: task.description,
+        tags: task
+            .tags
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default(),
+        is_original: task.is_original,
+        // Tasks stored before the choice was kept stop before the final click
+        manual_confirm: task.manual_confirm.unwrap_or(true),
+        account_ids: rows.iter().map(|row| row.account_id).collect(),
+        publish_at,
+        platform_scheduled_at,
+        platform_overrides,
+        generate_captions: false,
+        platform_fields,
+        // Already part of the stored platform fields
+        visibility: None,
+        cover_path: task.cover_path,
+        extra_media: task
+            .extra_media
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default(),
+        post_type: task
+            .post_type
+            .as_deref()
+            .and_then(platforms::PostType::from_name)
+            .unwrap_or_default(),
+        images: task
+            .images
+            .as_deref()
+            .and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default(),
+        tag_set_ids: Vec::new(),
+        automation_profile: None,
+        simulate: false,
+    }
+}
+
+/// Changes applied to a cloned task; every field left `None` keeps the
+/// original task's value
+#[derive(Debug, Default, Deserialize)]
+pub struct CloneOverrides {
+    #[serde(default)]
+    pub video_path: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub account_ids: Option<Vec<i64>>,
+    /// Same formats as `PublishRequest::publish_at`. Replaces the original
+    /// schedule, including one handed to the platform.
+    #[serde(default)]
+    pub publish_at: Option<String>,
+    /// Same as `PublishRequest::platform_scheduled_at`; replaces the original
+    /// schedule and wins over `publish_at` when both are given
+    #[serde(default)]
+    pub platform_scheduled_at: Option<String>,
+    #[serde(default)]
+    pub cover_path: Option<String>,
+    #[serde(default)]
+    pub extra_media: Option<Vec<String>>,
+    /// Replaces the original override of each platform listed
+    #[serde(default)]
+    pub platform_overrides: HashMap<String, PlatformOverride>,
+    /// Replaces the original fields of each platform listed
+    #[serde(default)]
+    pub platform_fields: HashMap<String, platforms::CustomFields>,
+    #[serde(default)]
+    pub manual_confirm: Option<bool>,
+}
+
+/// Publish a historical task again as a new task: same video, metadata and
+/// accounts unless `overrides` changes them
+#[tauri::command]
+pub async fn clone_publish_task(
+    app: AppHandle,
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+    task_id: i64,
+    overrides: Option<CloneOverrides>,
+) -> Result<PublishResult, String> {
+    let request = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        let task = queries::get_task(&conn, task_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let rows = queries::get_task_platforms(&conn, task_id).map_err(|e| e.to_string())?;
+        let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+        let mut request = request_from_task(task, &rows, &accounts);
+
+        let overrides = overrides.unwrap_or_default();
+        if let Some(video_path) = overrides.video_path {
+            request.video_path = video_path;
+        }
+        if let Some(title) = overrides.title {
+            request.title = title;
+        }
+        if let Some(description) = overrides.description {
+            request.description = Some(description);
+        }
+        if let Some(tags) = overrides.tags {
+            request.tags = tags;
+        }
+        if let Some(account_ids) = overrides.account_ids {
+            request.account_ids = account_ids;
+        }
+        if let Some(publish_at) = overrides.publish_at {
+            request.publish_at = Some(publish_at);
+            request.platform_scheduled_at = None;
+        }
+        if let Some(at) = overrides.platform_scheduled_at {
+            request.platform_scheduled_at = Some(at);
+            request.publish_at = None;
+        }
+        if let Some(cover_path) = overrides.cover_path {
+            request.cover_path = Some(cover_path);
+        }
+        if let Some(extra_media) = overrides.extra_media {
+            request.extra_media = extra_media;
+        }
+        if let Some(manual_confirm) = overrides.manual_confirm {
+            request.manual_confirm = manual_confirm;
+        }
+        request
+            .platform_overrides
+            .extend(overrides.platform_overrides);
+        request.platform_fields.extend(overrides.platform_fields);
+        request
+    };
+
+    info!(
+        "Cloning task {} onto {} account(s)",
+        task_id,
+        request.account_ids.len()
+    );
+    create_publish_task(app, db, artifacts, request).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskCleanupSummary {
+    pub deleted: usize,
+    /// Tasks left alone because they are still running
+    pub skipped_running: Vec<i64>,
+    pub bytes_freed: u64,
+}
+
+/// Delete tasks with their platform rows, timings and artifacts. Tasks that are
+/// still running are skipped.
+fn delete_tasks(
+    db: &Database,
+    artifacts: &ArtifactStore,
+    ids: &[i64],
+) -> Result<TaskCleanupSummary, String> {
+    let running = progress::running_task_ids();
+    let (to_delete, skipped_running): (Vec<i64>, Vec<i64>) =
+        ids.iter().partition(|id| !running.contains(id));
+    let deleted = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        queries::delete_publish_tasks(&conn, &to_delete).map_err(|e| e.to_string())?
+    };
+    let bytes_freed = to_delete
+        .iter()
+        .map(|id| artifacts.remove_task_files(*id))
+        .sum();
+    info!(
+        "Deleted {} task(s), freed {} bytes, skipped {} running",
+        deleted,
+        bytes_freed,
+        skipped_running.len()
+    );
+    Ok(TaskCleanupSummary {
+        deleted,
+        skipped_running,
+        bytes_freed,
+    })
+}
+
+/// Delete the given publish tasks and everything recorded for them
+#[tauri::command]
+pub fn delete_publish_tasks(
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+    ids: Vec<i64>,
+) -> Result<TaskCleanupSummary, String> {
+    delete_tasks(&db, &artifacts, &ids)
+}
+
+/// Delete every publish task created more than `days` days ago
+#[tauri::command]
+pub fn purge_tasks_older_than(
+    db: State<'_, Database>,
+    artifacts: State<'_, ArtifactStore>,
+    days: u32,
+) -> Result<TaskCleanupSummary, String> {
+    let ids = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        queries::get_task_ids_older_than(&conn, days).map_err(|e| e.to_string())?
+    };
+    delete_tasks(&db, &artifacts, &ids)
+}
+
 /// Get all publish tasks
 #[tauri::command]
-pub fn get_publish_tasks(db: State<'_, Database>) -> Result<Vec<queries::PublishTask>, String> {
+pub fn get_publish_tasks(
+    storage: State<'_, Arc<dyn Storage>>,
+) -> Result<Vec<queries::PublishTask>, String> {
+    storage.get_all_tasks().map_err(|e| e.to_string())
+}
+
+/// A task with the outcome of each of its accounts
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskDetail {
+    pub task: queries::PublishTask,
+    pub platforms: Vec<queries::TaskPlatform>,
+}
+
+/// One task of the publish history with every account's status, error and
+/// publish time
+#[tauri::command]
+pub fn get_task_detail(db: State<'_, Database>, task_id: i64) -> Result<TaskDetail, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let task = queries::get_task(&conn, task_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let platforms = queries::get_task_platforms(&conn, task_id).map_err(|e| e.to_string())?;
+    Ok(TaskDetail { task, platforms })
+}
+
+/// Recorded automation steps of every account of a task: each upload strategy
+/// attempt with its diagnostics, and failures with their page fingerprints
+#[tauri::command]
+pub fn get_task_logs(
+    db: State<'_, Database>,
+    task_id: i64,
+) -> Result<Vec<queries::AutomationLog>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_automation_logs(&conn, task_id).map_err(|e| e.to_string())
+}
+
+/// One platform on one calendar day
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarCell {
+    pub platform: String,
+    pub total: i64,
+    /// Runs of tasks with a requested publish time
+    pub scheduled: i64,
+    /// Run count per status (`automated`, `failed`, `deferred`...)
+    pub statuses: BTreeMap<String, i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarDay {
+    /// `YYYY-MM-DD`, local time
+    pub date: String,
+    pub cells: Vec<CalendarCell>,
+}
+
+/// Scheduled and past account runs of `month` (`YYYY-MM`) per day and
+/// platform; days without runs are left out
+#[tauri::command]
+pub fn get_publish_calendar(
+    db: State<'_, Database>,
+    month: String,
+) -> Result<Vec<CalendarDay>, String> {
+    let month = month.trim();
+    if chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_err() {
+        return Err(format!("Invalid month {:?}, expected YYYY-MM", month));
+    }
+    let rows = {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        queries::get_calendar_rows(&conn, month).map_err(|e| e.to_string())?
+    };
+
+    let mut days: Vec<CalendarDay> = Vec::new();
+    for row in rows {
+        if days.last().is_none_or(|day| day.date != row.day) {
+            days.push(CalendarDay {
+                date: row.day.clone(),
+                cells: Vec::new(),
+            });
+        }
+        let Some(day) = days.last_mut() else {
+            continue;
+        };
+        if day
+            .cells
+            .last()
+            .is_none_or(|cell| cell.platform != row.platform)
+        {
+            day.cells.push(CalendarCell {
+                platform: row.platform.clone(),
+                total: 0,
+                scheduled: 0,
+                statuses: BTreeMap::new(),
+            });
+        }
+        let Some(cell) = day.cells.last_mut() else {
+            continue;
+        };
+        cell.total += row.count;
+        if row.scheduled {
+            cell.scheduled += row.count;
+        }
+        *cell.statuses.entry(row.status).or_default() += row.count;
+    }
+    Ok(days)
+}
+
+/// Bytes and wall time of publishing runs per day and account over the last
+/// `days` days (default 30). Bytes are counted for runs that reached the
+/// upload; they stay empty for runs that failed before it.
+#[tauri::command]
+pub fn get_publish_usage(
+    db: State<'_, Database>,
+    days: Option<u32>,
+) -> Result<Vec<queries::PublishUsage>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_publish_usage(&conn, days.unwrap_or(30).max(1)).map_err(|e| e.to_string())
+}
+
+/// Queue `request` to start at `run_at` (RFC 3339 or local `YYYY-MM-DD HH:MM`).
+/// Unlike `publish_at`, which asks the platform to release an already uploaded
+/// video later, the upload itself waits until `run_at`.
+#[tauri::command]
+pub fn schedule_publish_task(
+    db: State<'_, Database>,
+    request: PublishRequest,
+    run_at: String,
+) -> Result<i64, String> {
+    let run_at = parse_publish_at(&run_at)?;
+    if request.post_type == platforms::PostType::Images {
+        platforms::xiaohongshu::validate_images(&request.images).map_err(|e| e.to_string())?;
+    } else if !Path::new(&request.video_path).is_file() {
+        return Err(format!("Video file not found: {}", request.video_path));
+    }
+    if request.account_ids.is_empty() {
+        return Err("No accounts selected".to_string());
+    }
+    if let Some(publish_at) = request.publish_at.as_deref() {
+        parse_publish_at(publish_at)?;
+    }
+    if let Some(at) = request.platform_scheduled_at.as_deref() {
+        parse_publish_at(at)?;
+    }
+    let payload = scheduler::payload::encode(&request).map_err(|e| e.to_string())?;
+    let run_at = scheduler::queue::format_run_at(run_at.with_timezone(&chrono::Utc));
+
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let accounts = queries::get_all_accounts(&conn).map_err(|e| e.to_string())?;
+    if let Some(missing) = request
+        .account_ids
+        .iter()
+        .find(|id| !accounts.iter().any(|account| account.id == **id))
+    {
+        return Err(format!("Account {} not found", missing));
+    }
+    let id = queries::insert_scheduled_task(&conn, &run_at, &request.title, &payload)
+        .map_err(|e| e.to_string())?;
+    info!("Scheduled task {} to start at {}", id, run_at);
+    Ok(id)
+}
+
+/// Queue a deferred account to run again in `retry_after_secs`, as a
+/// single-account job in the same queue `schedule_publish_task` uses.
+/// Simulated runs are never retried.
+fn schedule_retry(db: &Database, request: &PublishRequest, account_id: i64, retry_after_secs: u64) {
+    if request.simulate {
+        return;
+    }
+    let run_at = chrono::Local::now() + chrono::Duration::seconds(retry_after_secs as i64);
+    let mut retry = request.clone();
+    // A publish time the retry would pass is dropped, like a rerun's. That
+    // covers a time handed to the platform's own scheduler too.
+    retry.publish_at = retry
+        .publish_at
+        .filter(|at| parse_publish_at(at).is_ok_and(|at| at > run_at));
+    retry.platform_scheduled_at = retry
+        .platform_scheduled_at
+        .filter(|at| parse_publish_at(at).is_ok_and(|at| at > run_at));
+    queue_account_run(db, retry, account_id, run_at);
+}
+
+/// App-side scheduling: queue the account to publish right away at `at`,
+/// for a platform that cannot schedule the release itself
+fn schedule_app_publish(
+    db: &Database,
+    request: &PublishRequest,
+    account_id: i64,
+    at: chrono::DateTime<chrono::Local>,
+) {
+    if request.simulate {
+        return;
+    }
+    let mut scheduled = request.clone();
+    scheduled.publish_at = None;
+    queue_account_run(db, scheduled, account_id, at);
+}
+
+fn queue_account_run(
+    db: &Database,
+    mut request: PublishRequest,
+    account_id: i64,
+    run_at: chrono::DateTime<chrono::Local>,
+) {
+    request.account_ids = vec![account_id];
+    let run_at = scheduler::queue::format_run_at(run_at.with_timezone(&chrono::Utc));
+    let queued = scheduler::payload::encode(&request).and_then(|payload| {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::insert_scheduled_task(&conn, &run_at, &request.title, &payload)
+    });
+    match queued {
+        Ok(id) => info!(
+            "Account {} queued as scheduled task {} at {}",
+            account_id, id, run_at
+        ),
+        Err(e) => log::warn!("Failed to queue a run for account {}: {}", account_id, e),
+    }
+}
+
+/// Stop a running publish task. The account being automated stops at its next
+/// checkpoint; accounts not yet started are marked cancelled.
+#[tauri::command]
+pub fn cancel_publish_task(task_id: i64) -> Result<(), String> {
+    if crate::tasks::global().cancel_publish(task_id) {
+        Ok(())
+    } else {
+        Err(format!("Task {} is not running", task_id))
+    }
+}
+
+/// Cancel a scheduled task that has not started yet
+#[tauri::command]
+pub fn cancel_scheduled_task(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    if !queries::cancel_scheduled_task(&conn, id).map_err(|e| e.to_string())? {
+        return Err(format!("Scheduled task {} is not pending", id));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_scheduled_tasks(
+    db: State<'_, Database>,
+    include_finished: Option<bool>,
+) -> Result<Vec<queries::ScheduledTask>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    queries::get_all_tasks(&conn).map_err(|e| e.to_string())
+    queries::get_scheduled_tasks(&conn, include_finished.unwrap_or(false))
+        .map_err(|e| e.to_string())
 }
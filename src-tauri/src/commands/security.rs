@@ -0,0 +1,125 @@
+use crate::database::queries;
+use crate::database::Database;
+use crate::security::api_tokens::{self, ApiScope, CreatedApiToken};
+use crate::security::vault::{AccountNotes, Vault, VaultStatus};
+use tauri::State;
+
+const DEFAULT_AUDIT_LIMIT: i64 = 200;
+
+#[tauri::command]
+pub fn get_vault_status(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+) -> Result<VaultStatus, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault.status(&conn).map_err(|e| e.to_string())
+}
+
+/// Choose the vault passphrase. Only allowed once; there is no recovery.
+#[tauri::command]
+pub fn setup_vault(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    passphrase: String,
+) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault.setup(&conn, &passphrase).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn unlock_vault(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    passphrase: String,
+) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault.unlock(&conn, &passphrase).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn lock_vault(db: State<'_, Database>, vault: State<'_, Vault>) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault.lock(&conn).map_err(|e| e.to_string())
+}
+
+/// IDs of accounts that have notes; works while the vault is locked
+#[tauri::command]
+pub fn get_accounts_with_notes(db: State<'_, Database>) -> Result<Vec<i64>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_account_ids_with_notes(&conn).map_err(|e| e.to_string())
+}
+
+/// Decrypt an account's notes. Fails while the vault is locked.
+#[tauri::command]
+pub fn get_account_notes(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    account_id: i64,
+) -> Result<Option<AccountNotes>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault
+        .read_notes(&conn, account_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_account_notes(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    account_id: i64,
+    notes: AccountNotes,
+) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    vault
+        .write_notes(&conn, account_id, &notes)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_vault_audit_log(
+    db: State<'_, Database>,
+    limit: Option<i64>,
+) -> Result<Vec<queries::VaultAuditEntry>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_vault_audit_log(&conn, limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+        .map_err(|e| e.to_string())
+}
+
+/// Create a token for the local API/CLI. The returned token is shown once;
+/// only its hash is stored.
+#[tauri::command]
+pub fn create_api_token(
+    db: State<'_, Database>,
+    name: String,
+    scope: ApiScope,
+) -> Result<CreatedApiToken, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    api_tokens::create(&conn, &name, scope).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_api_tokens(db: State<'_, Database>) -> Result<Vec<queries::ApiToken>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    queries::get_api_tokens(&conn).map_err(|e| e.to_string())
+}
+
+/// Revoke a token; requests using it are refused from then on
+#[tauri::command]
+pub fn revoke_api_token(db: State<'_, Database>, id: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    if !queries::revoke_api_token(&conn, id).map_err(|e| e.to_string())? {
+        return Err(format!("API token {} not found or already revoked", id));
+    }
+    Ok(())
+}
+
+/// Run the same check the API applies to each request, e.g. to test a token
+#[tauri::command]
+pub fn verify_api_token(
+    db: State<'_, Database>,
+    token: String,
+    scope: ApiScope,
+) -> Result<queries::ApiToken, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    api_tokens::authorize(&conn, &token, scope).map_err(|e| e.to_string())
+}
@@ -0,0 +1,101 @@
+use crate::database::{Database, Storage};
+use crate::hooks::{self, PublishHooks};
+use crate::platforms;
+use crate::platforms::user_scripts::{self, UserScripts};
+use crate::relocation::{self, RelocationSummary, StorageLocations};
+use crate::startup;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+#[tauri::command]
+pub fn get_settings(
+    storage: State<'_, Arc<dyn Storage>>,
+) -> Result<HashMap<String, String>, String> {
+    let settings = storage.get_all_settings().map_err(|e| e.to_string())?;
+    Ok(settings.into_iter().collect())
+}
+
+#[tauri::command]
+pub fn update_setting(
+    storage: State<'_, Arc<dyn Storage>>,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    let key = key.trim();
+    if key.is_empty() {
+        return Err("Setting key must not be empty".to_string());
+    }
+    storage
+        .set_setting(key, &value)
+        .map_err(|e| e.to_string())?;
+    if key == platforms::SETTING_EXPERIMENTAL_PLATFORMS {
+        platforms::set_experimental_enabled(Some(&value));
+    }
+    if key == platforms::SETTING_LOCALE {
+        platforms::set_locale(Some(&value));
+    }
+    Ok(())
+}
+
+/// Pre/post-fill JS snippets configured for a platform
+#[tauri::command]
+pub fn get_user_scripts(app: AppHandle, platform: String) -> Result<UserScripts, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    user_scripts::load(&data_dir, &platform).map_err(|e| e.to_string())
+}
+
+/// Store a platform's snippets in `scripts.d/<platform>.json`; empty snippets
+/// remove the file
+#[tauri::command]
+pub fn set_user_scripts(
+    app: AppHandle,
+    platform: String,
+    scripts: UserScripts,
+) -> Result<(), String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    user_scripts::save(&data_dir, &platform, &scripts).map_err(|e| e.to_string())
+}
+
+/// Scripts run at the publish pipeline's hook points
+#[tauri::command]
+pub fn get_publish_hooks(app: AppHandle) -> Result<PublishHooks, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    hooks::load(&data_dir).map_err(|e| e.to_string())
+}
+
+/// Store the hook scripts in `hooks.json`; each must be an absolute path to an
+/// existing file. With no scripts set the file is removed.
+#[tauri::command]
+pub fn set_publish_hooks(app: AppHandle, hooks: PublishHooks) -> Result<(), String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    hooks::save(&data_dir, &hooks).map_err(|e| e.to_string())
+}
+
+/// Where the data directory and Chrome profiles currently live
+#[tauri::command]
+pub fn get_storage_locations(app: AppHandle) -> Result<StorageLocations, String> {
+    let data_dir = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    relocation::locations(&data_dir).map_err(|e| e.to_string())
+}
+
+/// Move the Chrome profiles and/or the data directory (database and
+/// artifacts) to new absolute paths. Profiles move immediately; the data
+/// directory is copied and used after a restart.
+#[tauri::command]
+pub fn relocate_storage(
+    app: AppHandle,
+    db: State<'_, Database>,
+    data_dir: Option<String>,
+    profiles_dir: Option<String>,
+) -> Result<RelocationSummary, String> {
+    let current = startup::data_dir(&app).map_err(|e| e.to_string())?;
+    relocation::relocate(
+        &db,
+        &current,
+        data_dir.as_deref().map(Path::new),
+        profiles_dir.as_deref().map(Path::new),
+    )
+    .map_err(|e| e.to_string())
+}
@@ -0,0 +1,220 @@
+use super::PlatformOverride;
+use crate::database::queries;
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub const SETTING_ENDPOINT: &str = "ai_assist_endpoint";
+pub const SETTING_API_KEY: &str = "ai_assist_api_key";
+pub const SETTING_MODEL: &str = "ai_assist_model";
+
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+
+/// Platform writing conventions passed to the model.
+const PLATFORM_GUIDES: &[(&str, &str)] = &[
+    (
+        "douyin",
+        "抖音：标题不超过 30 字，口语化、有钩子；话题标签 3-5 个",
+    ),
+    (
+        "xiaohongshu",
+        "小红书：标题不超过 20 字，可用 emoji；正文分段，话题标签 5-10 个",
+    ),
+    (
+        "bilibili",
+        "哔哩哔哩：标题不超过 80 字；简介说明看点；标签最多 10 个、每个不超过 20 字",
+    ),
+    (
+        "wechat",
+        "微信视频号：短标题 6-16 字；描述简洁；话题标签 1-3 个",
+    ),
+    (
+        "youtube",
+        "YouTube：标题不超过 100 字符；描述首两行最重要；标签为关键词",
+    ),
+    (
+        "kuaishou",
+        "快手：没有单独标题，作品描述不超过 500 字；话题以 # 开头写在描述里",
+    ),
+    (
+        "zhihu",
+        "知乎：标题不超过 50 字，像问题或观点；简介交代核心信息；话题最多 5 个",
+    ),
+    (
+        "tiktok",
+        "TikTok：没有单独标题，说明文字不超过 4000 字符，面向海外观众宜用英文；#hashtag 写在说明里",
+    ),
+    (
+        "weibo",
+        "微博：标题不超过 30 字；简介口语化、开头抓人；话题 1-3 个，贴合热点",
+    ),
+    (
+        "instagram",
+        "Instagram：没有单独标题，说明文字不超过 2200 字符、首行最重要；#hashtag 最多 30 个",
+    ),
+];
+
+/// Master content the suggestions are derived from.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuggestRequest {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub platforms: Vec<String>,
+}
+
+/// Something that can turn master content into per-platform suggestions.
+pub trait ContentAssistant {
+    async fn suggest(&self, request: &SuggestRequest) -> Result<HashMap<String, PlatformOverride>>;
+}
+
+/// Any chat-completions API that speaks the OpenAI wire format.
+pub struct OpenAiCompatibleAssistant {
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl OpenAiCompatibleAssistant {
+    /// Build from settings. Fails when no endpoint has been configured.
+    pub fn from_settings(conn: &Connection) -> Result<Self> {
+        let endpoint = queries::get_setting(conn, SETTING_ENDPOINT)?
+            .map(|v| v.trim().trim_end_matches('/').to_string())
+            .filter(|v| !v.is_empty())
+            .context("AI_ASSIST_NOT_CONFIGURED: 请先在设置中填写 AI 接口地址")?;
+        let api_key = queries::get_setting(conn, SETTING_API_KEY)?
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+        let model = queries::get_setting(conn, SETTING_MODEL)?
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .build()?;
+        Ok(Self {
+            endpoint,
+            api_key,
+            model,
+            client,
+        })
+    }
+
+    fn build_prompt(request: &SuggestRequest) -> String {
+        let guides: Vec<&str> = request
+            .platforms
+            .iter()
+            .filter_map(|platform| {
+                PLATFORM_GUIDES
+                    .iter()
+                    .find(|(id, _)| id == platform)
+                    .map(|(_, guide)| *guide)
+            })
+            .collect();
+        format!(
+            "根据以下原始内容，为每个平台改写标题、描述和标签。\n\
+             平台规范：\n{}\n\n\
+             原始标题：{}\n原始描述：{}\n原始标签：{}\n\n\
+             只输出 JSON，格式为 {{\"<平台id>\": {{\"title\": \"...\", \"description\": \"...\", \"tags\": [\"...\"]}}}}，\
+             平台 id 取自：{}",
+            guides.join("\n"),
+            request.title,
+            request.description.as_deref().unwrap_or(""),
+            request.tags.join("、"),
+            request.platforms.join(", ")
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    temperature: f32,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+impl ContentAssistant for OpenAiCompatibleAssistant {
+    async fn suggest(&self, request: &SuggestRequest) -> Result<HashMap<String, PlatformOverride>> {
+        let prompt = Self::build_prompt(request);
+        let body = ChatRequest {
+            model: &self.model,
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: "你是多平台短视频运营助手，擅长按各平台规范改写文案。",
+                },
+                ChatMessage {
+                    role: "user",
+                    content: &prompt,
+                },
+            ],
+            temperature: 0.7,
+        };
+
+        let mut http = self
+            .client
+            .post(format!("{}/chat/completions", self.endpoint))
+            .json(&body);
+        if let Some(key) = &self.api_key {
+            http = http.bearer_auth(key);
+        }
+        let resp = http.send().await.context("请求 AI 接口失败")?;
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            bail!(
+                "AI 接口返回错误 {}：{}",
+                status,
+                text.chars().take(300).collect::<String>()
+            );
+        }
+        let parsed: ChatResponse = resp.json().await.context("AI 接口响应格式无法解析")?;
+        let content = parsed
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .context("AI 接口没有返回内容")?;
+
+        let mut suggestions = parse_suggestions(&content)?;
+        suggestions.retain(|platform, _| request.platforms.contains(platform));
+        Ok(suggestions)
+    }
+}
+
+/// Models sometimes wrap JSON in prose or code fences; take the outermost object.
+fn parse_suggestions(content: &str) -> Result<HashMap<String, PlatformOverride>> {
+    let start = content.find('{').context("AI 返回内容中没有 JSON")?;
+    let end = content.rfind('}').context("AI 返回内容中没有 JSON")?;
+    if end < start {
+        bail!("AI 返回内容中没有 JSON");
+    }
+    serde_json::from_str(&content[start..=end]).context("AI 返回的 JSON 格式不符合预期")
+}
@@ -0,0 +1,367 @@
+use super::PlatformOverride;
+use crate::database::queries;
+use crate::platforms::{self, selector_overrides};
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+pub const BUNDLE_EXTENSION: &str = "mpubconfig";
+const BUNDLE_FORMAT: &str = "mpubconfig";
+/// Bumped whenever the bundle layout changes incompatibly
+const BUNDLE_VERSION: u32 = 1;
+/// Per-platform selector override files, `<app_data_dir>/selectors.d/<platform>.json`
+pub const SELECTOR_OVERRIDES_DIR: &str = "selectors.d";
+
+/// Shareable snapshot of templates, reply rules, selector overrides and
+/// knowledge base links
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    #[serde(default)]
+    pub templates: Vec<BundleTemplate>,
+    #[serde(default)]
+    pub reply_rules: Vec<BundleReplyRule>,
+    #[serde(default)]
+    pub selector_overrides: BTreeMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub kb_articles: Vec<BundleKbArticle>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub platform_overrides: HashMap<String, PlatformOverride>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleReplyRule {
+    pub platform: Option<String>,
+    pub keyword: String,
+    pub reply: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleKbArticle {
+    pub platform: Option<String>,
+    pub error_code: String,
+    pub title: String,
+    pub url: String,
+}
+
+/// What to do when an imported item already exists locally
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    #[default]
+    Skip,
+    Overwrite,
+    /// Keep both; the imported template gets a ` (2)` style suffix.
+    /// Items without a name (rules, selector files) are skipped instead.
+    Rename,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+    pub renamed: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub templates: usize,
+    pub reply_rules: usize,
+    pub selector_overrides: usize,
+    pub kb_articles: usize,
+}
+
+pub fn selector_overrides_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(SELECTOR_OVERRIDES_DIR)
+}
+
+pub fn export(conn: &Connection, app_data_dir: &Path, path: &Path) -> Result<ExportSummary> {
+    let templates = queries::get_templates(conn)?
+        .into_iter()
+        .map(|t| BundleTemplate {
+            tags: serde_json::from_str(&t.tags).unwrap_or_default(),
+            platform_overrides: serde_json::from_str(&t.platform_overrides).unwrap_or_default(),
+            name: t.name,
+            title: t.title,
+            description: t.description,
+        })
+        .collect::<Vec<_>>();
+    let reply_rules = queries::get_reply_rules(conn)?
+        .into_iter()
+        .map(|r| BundleReplyRule {
+            platform: r.platform,
+            keyword: r.keyword,
+            reply: r.reply,
+        })
+        .collect::<Vec<_>>();
+    let kb_articles = queries::get_kb_articles(conn)?
+        .into_iter()
+        .map(|a| BundleKbArticle {
+            platform: a.platform,
+            error_code: a.error_code,
+            title: a.title,
+            url: a.url,
+        })
+        .collect::<Vec<_>>();
+
+    let mut selector_overrides = BTreeMap::new();
+    if let Ok(entries) = std::fs::read_dir(selector_overrides_dir(app_data_dir)) {
+        for entry in entries.flatten() {
+            let file = entry.path();
+            if file.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(platform) = file.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let body = std::fs::read(&file)?;
+            let value = serde_json::from_slice(&body)
+                .with_context(|| format!("选择器覆盖文件无法解析：{}", file.display()))?;
+            selector_overrides.insert(platform.to_string(), value);
+        }
+    }
+
+    let bundle = ConfigBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        templates,
+        reply_rules,
+        selector_overrides,
+        kb_articles,
+    };
+    let path = if path.extension().and_then(|e| e.to_str()) == Some(BUNDLE_EXTENSION) {
+        path.to_path_buf()
+    } else {
+        path.with_extension(BUNDLE_EXTENSION)
+    };
+    std::fs::write(&path, serde_json::to_vec_pretty(&bundle)?)?;
+
+    Ok(ExportSummary {
+        path: path.to_string_lossy().to_string(),
+        templates: bundle.templates.len(),
+        reply_rules: bundle.reply_rules.len(),
+        selector_overrides: bundle.selector_overrides.len(),
+        kb_articles: bundle.kb_articles.len(),
+    })
+}
+
+/// Database entries are imported in one transaction, so a bad entry leaves
+/// nothing half-imported; selector override files are written after it commits
+pub fn import(
+    conn: &mut Connection,
+    app_data_dir: &Path,
+    path: &Path,
+    policy: ConflictPolicy,
+) -> Result<ImportReport> {
+    let body = std::fs::read(path).with_context(|| format!("读取 {} 失败", path.display()))?;
+    let bundle: ConfigBundle = serde_json::from_slice(&body).context("配置文件格式无效")?;
+    if bundle.format != BUNDLE_FORMAT {
+        bail!("不是 .{} 配置文件", BUNDLE_EXTENSION);
+    }
+    if bundle.version > BUNDLE_VERSION {
+        bail!(
+            "配置文件版本 {} 高于当前支持的版本 {}，请先升级应用",
+            bundle.version,
+            BUNDLE_VERSION
+        );
+    }
+
+    let mut report = ImportReport::default();
+    let tx = conn.transaction()?;
+    import_templates(&tx, bundle.templates, policy, &mut report)?;
+    import_reply_rules(&tx, bundle.reply_rules, policy, &mut report)?;
+    import_kb_articles(&tx, bundle.kb_articles, policy, &mut report)?;
+    tx.commit()?;
+    import_selector_overrides(app_data_dir, bundle.selector_overrides, policy, &mut report)?;
+    Ok(report)
+}
+
+fn import_templates(
+    conn: &Connection,
+    templates: Vec<BundleTemplate>,
+    policy: ConflictPolicy,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let mut existing = queries::get_templates(conn)?
+        .into_iter()
+        .map(|t| t.name)
+        .collect::<Vec<_>>();
+    for template in templates {
+        let name = template.name.trim().to_string();
+        if name.is_empty() {
+            report.warnings.push("跳过未命名的模板".to_string());
+            continue;
+        }
+        let name = if !existing.contains(&name) {
+            report.imported += 1;
+            name
+        } else {
+            match policy {
+                ConflictPolicy::Skip => {
+                    report.skipped += 1;
+                    continue;
+                }
+                ConflictPolicy::Overwrite => {
+                    report.overwritten += 1;
+                    name
+                }
+                ConflictPolicy::Rename => {
+                    let renamed = (2..)
+                        .map(|n| format!("{} ({})", name, n))
+                        .find(|candidate| !existing.contains(candidate))
+                        .unwrap_or_default();
+                    report.renamed.push(renamed.clone());
+                    renamed
+                }
+            }
+        };
+        existing.push(name.clone());
+        queries::upsert_template(
+            conn,
+            &name,
+            &template.title,
+            &template.description,
+            &serde_json::to_string(&template.tags)?,
+            &serde_json::to_string(&template.platform_overrides)?,
+        )?;
+    }
+    Ok(())
+}
+
+fn import_reply_rules(
+    conn: &Connection,
+    rules: Vec<BundleReplyRule>,
+    policy: ConflictPolicy,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let existing = queries::get_reply_rules(conn)?;
+    for rule in rules {
+        if let Some(platform) = &rule.platform {
+            if platforms::get_platform_info(platform).is_none() {
+                report
+                    .warnings
+                    .push(format!("跳过未知平台 {} 的回复规则", platform));
+                continue;
+            }
+        }
+        let conflict = existing
+            .iter()
+            .find(|r| r.platform == rule.platform && r.keyword == rule.keyword);
+        match (conflict, policy) {
+            (None, _) => report.imported += 1,
+            (Some(old), ConflictPolicy::Overwrite) => {
+                queries::delete_reply_rule(conn, old.id)?;
+                report.overwritten += 1;
+            }
+            (Some(_), _) => {
+                report.skipped += 1;
+                continue;
+            }
+        }
+        queries::insert_reply_rule(conn, rule.platform.as_deref(), &rule.keyword, &rule.reply)?;
+    }
+    Ok(())
+}
+
+fn import_kb_articles(
+    conn: &Connection,
+    articles: Vec<BundleKbArticle>,
+    policy: ConflictPolicy,
+    report: &mut ImportReport,
+) -> Result<()> {
+    let existing = queries::get_kb_articles(conn)?;
+    for article in articles {
+        if let Some(platform) = &article.platform {
+            if platforms::get_platform_info(platform).is_none() {
+                report
+                    .warnings
+                    .push(format!("跳过未知平台 {} 的帮助文章", platform));
+                continue;
+            }
+        }
+        let conflict = existing
+            .iter()
+            .any(|a| a.platform == article.platform && a.error_code == article.error_code);
+        match (conflict, policy) {
+            (false, _) => report.imported += 1,
+            (true, ConflictPolicy::Overwrite) => report.overwritten += 1,
+            (true, _) => {
+                report.skipped += 1;
+                continue;
+            }
+        }
+        queries::upsert_kb_article(
+            conn,
+            article.platform.as_deref(),
+            &article.error_code,
+            &article.title,
+            &article.url,
+        )?;
+    }
+    Ok(())
+}
+
+fn import_selector_overrides(
+    app_data_dir: &Path,
+    overrides: BTreeMap<String, serde_json::Value>,
+    policy: ConflictPolicy,
+    report: &mut ImportReport,
+) -> Result<()> {
+    if overrides.is_empty() {
+        return Ok(());
+    }
+    let dir = selector_overrides_dir(app_data_dir);
+    std::fs::create_dir_all(&dir)?;
+    for (platform, value) in overrides {
+        if platforms::publish_config(&platform).is_none() {
+            report
+                .warnings
+                .push(format!("跳过未知平台 {} 的选择器覆盖", platform));
+            continue;
+        }
+        let file = dir.join(format!("{}.json", platform));
+        if file.exists() {
+            if !matches!(policy, ConflictPolicy::Overwrite) {
+                report.skipped += 1;
+                continue;
+            }
+            report.overwritten += 1;
+        } else {
+            report.imported += 1;
+        }
+        report.warnings.extend(
+            selector_overrides::parse(&platform, &value)
+                .invalid
+                .into_iter()
+                .map(|invalid| {
+                    format!(
+                        "{} 的选择器覆盖 {} 无效（{}），发布时将忽略：{}",
+                        platform, invalid.field, invalid.selector, invalid.reason
+                    )
+                }),
+        );
+        std::fs::write(&file, serde_json::to_vec_pretty(&value)?)?;
+    }
+    Ok(())
+}
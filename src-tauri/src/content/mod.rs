@@ -0,0 +1,52 @@
+pub mod assist;
+pub mod bundle;
+pub mod share;
+pub mod title_variation;
+pub mod variables;
+
+use crate::platforms::PublishPayload;
+use serde::{Deserialize, Serialize};
+
+/// Per-platform replacement for the master title/description/tags.
+/// Fields left as `None` fall back to the master content.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlatformOverride {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+/// Ad-hoc tags first, then each tag set in order; blanks and duplicates dropped
+pub fn merge_tags<'a>(
+    ad_hoc: &'a [String],
+    tag_sets: impl IntoIterator<Item = &'a [String]>,
+) -> Vec<String> {
+    let mut merged: Vec<String> = Vec::new();
+    let all = ad_hoc
+        .iter()
+        .chain(tag_sets.into_iter().flatten())
+        .map(|tag| tag.trim());
+    for tag in all {
+        if !tag.is_empty() && !merged.iter().any(|existing| existing == tag) {
+            merged.push(tag.to_string());
+        }
+    }
+    merged
+}
+
+impl PlatformOverride {
+    pub fn apply(&self, payload: &mut PublishPayload) {
+        if let Some(title) = self.title.as_ref().filter(|t| !t.trim().is_empty()) {
+            payload.title = title.clone();
+        }
+        if let Some(description) = &self.description {
+            payload.description = description.clone();
+        }
+        if let Some(tags) = &self.tags {
+            payload.tags = tags.clone();
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::database::queries::{self, Account};
+use crate::platforms;
+use anyhow::{anyhow, Result};
+use rusqlite::Connection;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedPlatform {
+    pub platform: String,
+    pub platform_name: String,
+    pub account_name: String,
+    pub status: String,
+    pub post_url: Option<String>,
+    pub published_at: Option<String>,
+}
+
+/// What a finished task published where, as data and as a plain-text block
+/// for pasting into a team chat
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskShareSummary {
+    pub task_id: i64,
+    pub title: String,
+    pub status: String,
+    pub created_at: String,
+    pub scheduled_at: Option<String>,
+    pub platforms: Vec<SharedPlatform>,
+    pub text: String,
+}
+
+pub fn build(conn: &Connection, task_id: i64) -> Result<TaskShareSummary> {
+    let task =
+        queries::get_task(conn, task_id)?.ok_or_else(|| anyhow!("Task {} not found", task_id))?;
+    let accounts = queries::get_all_accounts(conn)?;
+    let platforms = queries::get_task_platforms(conn, task_id)?
+        .into_iter()
+        .map(|row| shared_platform(&row, &accounts))
+        .collect::<Vec<_>>();
+
+    let mut summary = TaskShareSummary {
+        task_id,
+        title: task.title,
+        status: task.status,
+        created_at: task.created_at,
+        scheduled_at: task.scheduled_at,
+        platforms,
+        text: String::new(),
+    };
+    summary.text = render_text(&summary);
+    Ok(summary)
+}
+
+fn shared_platform(row: &queries::TaskPlatform, accounts: &[Account]) -> SharedPlatform {
+    let account = accounts.iter().find(|a| a.id == row.account_id);
+    let platform = account.map(|a| a.platform.clone()).unwrap_or_default();
+    SharedPlatform {
+        platform_name: platforms::display_name(&platform),
+        platform,
+        account_name: account.map(|a| a.display_name.clone()).unwrap_or_else(|| {
+            if platforms::english() {
+                format!("Account {}", row.account_id)
+            } else {
+                format!("账号 {}", row.account_id)
+            }
+        }),
+        status: row.status.clone(),
+        post_url: row.post_url.clone().filter(|url| !url.trim().is_empty()),
+        published_at: row.published_at.clone(),
+    }
+}
+
+fn render_text(summary: &TaskShareSummary) -> String {
+    let english = platforms::english();
+    let mut lines = if english {
+        vec![
+            format!("[Publish summary] {}", summary.title),
+            format!(
+                "Status: {}  created {}",
+                status_label(&summary.status, english),
+                summary.created_at
+            ),
+        ]
+    } else {
+        vec![
+            format!("【发布汇总】{}", summary.title),
+            format!(
+                "状态：{}  创建于 {}",
+                status_label(&summary.status, english),
+                summary.created_at
+            ),
+        ]
+    };
+    if let Some(at) = &summary.scheduled_at {
+        lines.push(if english {
+            format!("Scheduled for: {}", at)
+        } else {
+            format!("计划发布时间：{}", at)
+        });
+    }
+    for platform in &summary.platforms {
+        let status = status_label(&platform.status, english);
+        let mut line = if english {
+            format!(
+                "- {} ({}): {}",
+                platform.platform_name, platform.account_name, status
+            )
+        } else {
+            format!(
+                "- {}（{}）：{}",
+                platform.platform_name, platform.account_name, status
+            )
+        };
+        if let Some(at) = &platform.published_at {
+            line.push_str(&format!(" {}", at));
+        }
+        if let Some(url) = &platform.post_url {
+            line.push_str(&format!(" {}", url));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn status_label(status: &str, english: bool) -> &str {
+    if english {
+        return match status {
+            "published" => "published",
+            "completed" => "completed",
+            "automated" => "uploaded, waiting for confirmation",
+            "publishing" => "publishing",
+            "launched" => "needs manual follow-up",
+            "deferred" => "deferred",
+            "partial" => "partially completed",
+            "failed" => "failed",
+            "cancelled" => "cancelled",
+            "interrupted" => "interrupted",
+            "pending" | "running" => "in progress",
+            other => other,
+        };
+    }
+    match status {
+        "published" => "已发布",
+        "completed" => "已完成",
+        "automated" => "已上传，待确认发布",
+        "publishing" => "发布中",
+        "launched" => "需手动继续",
+        "deferred" => "已顺延",
+        "partial" => "部分完成",
+        "failed" => "失败",
+        "cancelled" => "已取消",
+        "interrupted" => "已中断",
+        "pending" | "running" => "进行中",
+        other => other,
+    }
+}
@@ -0,0 +1,100 @@
+use anyhow::{anyhow, bail, Result};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+const MAX_SUFFIXES: usize = 50;
+const MAX_SUFFIX_LEN: usize = 20;
+const MAX_SYNONYM_GROUPS: usize = 50;
+const MAX_WORD_LEN: usize = 20;
+
+/// Per-account title mutation. Douyin down-ranks the same title posted by
+/// several accounts, so matrix accounts can each publish a slightly different
+/// one: a synonym swapped in, a suffix (emoji or short text) appended, or both.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TitleVariation {
+    #[serde(default)]
+    pub enabled: bool,
+    /// One of these is appended at random
+    #[serde(default)]
+    pub suffixes: Vec<String>,
+    /// Groups of interchangeable words; the first word of the title found in
+    /// a group is replaced by another word of that group at random
+    #[serde(default)]
+    pub synonyms: Vec<Vec<String>>,
+}
+
+impl TitleVariation {
+    pub fn validate(&self) -> Result<()> {
+        if self.suffixes.len() > MAX_SUFFIXES {
+            bail!("后缀最多 {} 个", MAX_SUFFIXES);
+        }
+        if let Some(suffix) = self
+            .suffixes
+            .iter()
+            .find(|s| s.trim().is_empty() || s.chars().count() > MAX_SUFFIX_LEN)
+        {
+            bail!(
+                "后缀“{}”无效：不能为空且不超过 {} 个字符",
+                suffix,
+                MAX_SUFFIX_LEN
+            );
+        }
+        if self.synonyms.len() > MAX_SYNONYM_GROUPS {
+            bail!("同义词组最多 {} 组", MAX_SYNONYM_GROUPS);
+        }
+        for group in &self.synonyms {
+            if group.len() < 2 {
+                bail!("每组同义词至少需要 2 个词");
+            }
+            if let Some(word) = group
+                .iter()
+                .find(|w| w.trim().is_empty() || w.chars().count() > MAX_WORD_LEN)
+            {
+                bail!(
+                    "同义词“{}”无效：不能为空且不超过 {} 个字符",
+                    word,
+                    MAX_WORD_LEN
+                );
+            }
+        }
+        if self.enabled && self.suffixes.is_empty() && self.synonyms.is_empty() {
+            bail!("已启用标题变体，但未配置后缀或同义词");
+        }
+        Ok(())
+    }
+
+    /// The varied title, or `None` when disabled or nothing applied
+    pub fn apply(&self, title: &str) -> Result<Option<String>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        let mut varied = title.to_string();
+        let swap = self.synonyms.iter().find_map(|group| {
+            let word = group.iter().find(|word| title.contains(word.as_str()))?;
+            let others = group.iter().filter(|w| *w != word).collect::<Vec<_>>();
+            Some((word, others))
+        });
+        if let Some((word, others)) = swap {
+            let replacement = others[random_index(others.len())?];
+            varied = varied.replacen(word.as_str(), replacement, 1);
+        }
+        if !self.suffixes.is_empty() {
+            varied.push_str(&self.suffixes[random_index(self.suffixes.len())?]);
+        }
+        Ok(Some(varied).filter(|varied| varied != title))
+    }
+}
+
+/// Stored JSON object; unreadable or missing data counts as disabled
+pub fn parse(raw: Option<&str>) -> TitleVariation {
+    raw.and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default()
+}
+
+fn random_index(len: usize) -> Result<usize> {
+    let mut bytes = [0u8; 4];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("生成随机数失败"))?;
+    Ok(u32::from_le_bytes(bytes) as usize % len)
+}
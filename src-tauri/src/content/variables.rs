@@ -0,0 +1,108 @@
+use crate::database::queries::Account;
+use crate::platforms::PublishPayload;
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+/// Placeholders look like `{account.playlist_id}`
+const PLACEHOLDER_PREFIX: &str = "{account.";
+/// Filled from the account itself; user variables cannot shadow them
+const BUILTIN_KEYS: &[&str] = &["id", "name", "platform"];
+const MAX_KEY_LEN: usize = 40;
+const MAX_VALUE_LEN: usize = 500;
+const MAX_VARIABLES: usize = 50;
+
+fn is_valid_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_KEY_LEN
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Check user-defined variables before they are stored
+pub fn validate(variables: &BTreeMap<String, String>) -> Result<()> {
+    if variables.len() > MAX_VARIABLES {
+        bail!("每个账号最多 {} 个变量", MAX_VARIABLES);
+    }
+    for (key, value) in variables {
+        if !is_valid_key(key) {
+            bail!(
+                "变量名 {} 无效：只能使用字母、数字和下划线，且不超过 {} 个字符",
+                key,
+                MAX_KEY_LEN
+            );
+        }
+        if BUILTIN_KEYS.contains(&key.as_str()) {
+            bail!("变量名 {} 为内置变量，不能自定义", key);
+        }
+        if value.chars().count() > MAX_VALUE_LEN {
+            bail!("变量 {} 的值超过 {} 个字符", key, MAX_VALUE_LEN);
+        }
+    }
+    Ok(())
+}
+
+/// Stored JSON object; unreadable or missing data counts as no variables
+pub fn parse(raw: Option<&str>) -> BTreeMap<String, String> {
+    raw.and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default()
+}
+
+/// The variables `{account.<key>}` placeholders of one account resolve to
+#[derive(Debug, Clone, Default)]
+pub struct AccountVariables(BTreeMap<String, String>);
+
+impl AccountVariables {
+    pub fn new(account: &Account, custom: BTreeMap<String, String>) -> Self {
+        let mut variables = custom;
+        variables.insert("id".into(), account.id.to_string());
+        variables.insert("name".into(), account.display_name.clone());
+        variables.insert("platform".into(), account.platform.clone());
+        Self(variables)
+    }
+
+    /// Replace known placeholders; unknown ones are left as written so the
+    /// mistake shows up in the published text instead of vanishing
+    pub fn render(&self, template: &str) -> String {
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find(PLACEHOLDER_PREFIX) {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + PLACEHOLDER_PREFIX.len()..];
+            let resolved = after.find('}').and_then(|end| {
+                let key = &after[..end];
+                let value = is_valid_key(key).then(|| self.0.get(key)).flatten()?;
+                Some((value, end))
+            });
+            match resolved {
+                Some((value, end)) => {
+                    rendered.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    rendered.push_str(PLACEHOLDER_PREFIX);
+                    rest = after;
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+
+    /// Render the account's content, text platform fields and user scripts
+    pub fn apply(&self, payload: &mut PublishPayload) {
+        payload.title = self.render(&payload.title);
+        payload.description = self.render(&payload.description);
+        for tag in &mut payload.tags {
+            *tag = self.render(tag);
+        }
+        payload.custom_fields.render_text(|text| self.render(text));
+        for script in [
+            &mut payload.user_scripts.pre_fill_js,
+            &mut payload.user_scripts.post_fill_js,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *script = self.render(script);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{bail, Result};
+use log::{error, info, warn};
+use rusqlite::{Connection, ErrorCode};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+pub const STATE_OK: &str = "ok";
+/// Indexes were rebuilt in place
+pub const STATE_REPAIRED: &str = "repaired";
+/// Readable data was copied into a fresh file; the damaged one was backed up
+pub const STATE_RECOVERED: &str = "recovered";
+/// Nothing could be salvaged; the app started with an empty database
+pub const STATE_RECREATED: &str = "recreated";
+/// Even an empty database could not be created; data lives in memory only
+pub const STATE_FAILED: &str = "failed";
+
+/// Problems reported by the integrity check are capped at this many lines
+const MAX_DETAILS: usize = 20;
+
+/// Outcome of the startup database check, shown to the user when it is not `ok`
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseStatus {
+    pub state: &'static str,
+    /// Integrity check findings and recovery steps taken
+    pub details: Vec<String>,
+    /// Where the damaged files were moved
+    pub backup_path: Option<String>,
+    pub checked_at: String,
+}
+
+impl DatabaseStatus {
+    pub fn failed(details: Vec<String>) -> Self {
+        Self::new(STATE_FAILED, details, None)
+    }
+
+    fn new(state: &'static str, details: Vec<String>, backup_path: Option<&Path>) -> Self {
+        Self {
+            state,
+            details,
+            backup_path: backup_path.map(|path| path.display().to_string()),
+            checked_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// `PRAGMA quick_check`; an empty list means the database is healthy
+fn quick_check(conn: &Connection) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare("PRAGMA quick_check")?;
+    let rows = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows
+        .into_iter()
+        .filter(|row| row != "ok")
+        .take(MAX_DETAILS)
+        .collect())
+}
+
+/// Only these errors say the file itself is damaged. Anything else (busy or
+/// locked by another instance, I/O or permission errors) says nothing about
+/// the data, so the file must be left alone.
+fn is_corruption(e: &rusqlite::Error) -> bool {
+    matches!(
+        e.sqlite_error_code(),
+        Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
+
+/// Full `PRAGMA integrity_check` of a candidate replacement file
+fn integrity_ok(path: &Path) -> bool {
+    Connection::open(path)
+        .and_then(|conn| {
+            conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0))
+        })
+        .is_ok_and(|result| result == "ok")
+}
+
+fn sidecar(db_path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", db_path.display(), suffix))
+}
+
+/// Move the database and its WAL/SHM files aside as `<name>.corrupt-<time>`
+fn back_up_damaged(db_path: &Path) -> Result<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let backup = sidecar(db_path, &format!(".corrupt-{}", stamp));
+    std::fs::rename(db_path, &backup)?;
+    for suffix in ["-wal", "-shm"] {
+        let from = sidecar(db_path, suffix);
+        if from.exists() {
+            std::fs::rename(&from, sidecar(&backup, suffix))?;
+        }
+    }
+    Ok(backup)
+}
+
+/// Copy what SQLite can still read into a fresh file next to the database
+fn vacuum_into_fresh(conn: &Connection, db_path: &Path) -> Result<PathBuf> {
+    let fresh = sidecar(db_path, ".recovered");
+    if fresh.exists() {
+        std::fs::remove_file(&fresh)?;
+    }
+    conn.execute("VACUUM INTO ?1", [fresh.to_string_lossy().to_string()])?;
+    if !integrity_ok(&fresh) {
+        let _ = std::fs::remove_file(&fresh);
+        bail!("recovered copy failed the integrity check");
+    }
+    Ok(fresh)
+}
+
+/// Open the database, verifying it first. A damaged file is repaired in place
+/// when a reindex suffices, otherwise salvaged into a fresh file or, as a last
+/// resort, replaced by an empty one; the damaged files are kept as a backup.
+/// Errors that do not mean corruption leave the file untouched and start with
+/// an in-memory database instead.
+pub fn open_checked(db_path: &Path) -> (Connection, DatabaseStatus) {
+    let mut details = Vec::new();
+    let conn = match Connection::open(db_path) {
+        Ok(conn) => match quick_check(&conn) {
+            Ok(problems) if problems.is_empty() => {
+                return (conn, DatabaseStatus::new(STATE_OK, details, None));
+            }
+            Ok(problems) => {
+                details.extend(problems);
+                Some(conn)
+            }
+            Err(e) if is_corruption(&e) => {
+                details.push(format!("quick_check failed: {}", e));
+                Some(conn)
+            }
+            Err(e) => {
+                details.push(format!("quick_check failed, file left untouched: {}", e));
+                warn!("[DB] cannot check {}: {}", db_path.display(), e);
+                return in_memory(details);
+            }
+        },
+        Err(e) if is_corruption(&e) => {
+            details.push(format!("open failed: {}", e));
+            None
+        }
+        Err(e) => {
+            details.push(format!("open failed, file left untouched: {}", e));
+            warn!("[DB] cannot open {}: {}", db_path.display(), e);
+            return in_memory(details);
+        }
+    };
+    warn!(
+        "[DB] integrity problems in {}: {}",
+        db_path.display(),
+        details.join("; ")
+    );
+
+    if let Some(conn) = conn {
+        if conn.execute_batch("REINDEX;").is_ok() && quick_check(&conn).is_ok_and(|p| p.is_empty())
+        {
+            details.push("reindexed".to_string());
+            info!("[DB] repaired by REINDEX");
+            return (conn, DatabaseStatus::new(STATE_REPAIRED, details, None));
+        }
+        match vacuum_into_fresh(&conn, db_path) {
+            Ok(fresh) => {
+                drop(conn);
+                match back_up_damaged(db_path).and_then(|backup| {
+                    std::fs::rename(&fresh, db_path)
+                        .map(|_| backup)
+                        .map_err(Into::into)
+                }) {
+                    Ok(backup) => match Connection::open(db_path) {
+                        Ok(conn) => {
+                            details.push("salvaged readable data into a fresh file".to_string());
+                            info!("[DB] recovered, damaged file kept at {}", backup.display());
+                            return (
+                                conn,
+                                DatabaseStatus::new(STATE_RECOVERED, details, Some(&backup)),
+                            );
+                        }
+                        Err(e) => details.push(format!("reopen after recovery failed: {}", e)),
+                    },
+                    Err(e) => details.push(format!("replacing damaged file failed: {}", e)),
+                }
+            }
+            Err(e) => {
+                details.push(format!("vacuum into fresh file failed: {}", e));
+                drop(conn);
+            }
+        }
+    }
+
+    let backup = if db_path.exists() {
+        match back_up_damaged(db_path) {
+            Ok(backup) => Some(backup),
+            Err(e) => {
+                details.push(format!("backing up damaged file failed: {}", e));
+                None
+            }
+        }
+    } else {
+        None
+    };
+    if db_path.exists() && backup.is_none() {
+        // The damaged file is still in place; do not overwrite it
+        return in_memory(details);
+    }
+    match Connection::open(db_path) {
+        Ok(conn) => {
+            details.push("started with an empty database".to_string());
+            warn!("[DB] could not salvage data, started with an empty database");
+            (
+                conn,
+                DatabaseStatus::new(STATE_RECREATED, details, backup.as_deref()),
+            )
+        }
+        Err(e) => {
+            details.push(format!("creating an empty database failed: {}", e));
+            in_memory(details)
+        }
+    }
+}
+
+/// Last resort so the app still starts and can show what went wrong
+fn in_memory(mut details: Vec<String>) -> (Connection, DatabaseStatus) {
+    error!("[DB] falling back to an in-memory database; nothing will be saved");
+    details.push("using an in-memory database".to_string());
+    let conn = Connection::open_in_memory().expect("in-memory SQLite is always available");
+    (conn, DatabaseStatus::failed(details))
+}
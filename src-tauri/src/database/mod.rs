@@ -1,20 +1,38 @@
+pub mod integrity;
 pub mod queries;
 pub mod schema;
+pub mod storage;
+
+pub use storage::Storage;
 
 use anyhow::Result;
-use rusqlite::Connection;
+use integrity::DatabaseStatus;
+use log::warn;
+use rusqlite::{Connection, OpenFlags};
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long the read-only connection waits on a checkpoint before giving up
+const READER_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
+/// Clones share the same connections; the `Storage` state is one of them
+#[derive(Clone)]
 pub struct Database {
-    pub conn: Mutex<Connection>,
+    pub conn: Arc<Mutex<Connection>>,
+    /// Read-only connection for list queries the UI refreshes, so they are not
+    /// stuck behind a publish holding `conn`. WAL lets it read while the
+    /// writer commits. None for an in-memory database.
+    reader: Option<Arc<Mutex<Connection>>>,
 }
 
 impl Database {
-    pub fn new(app_data_dir: &PathBuf) -> Result<Self> {
+    /// Open the database, checking its integrity first and repairing or
+    /// replacing a damaged file; the status says which happened
+    pub fn new(app_data_dir: &PathBuf) -> Result<(Self, DatabaseStatus)> {
         std::fs::create_dir_all(app_data_dir)?;
         let db_path = app_data_dir.join("multipublisher.db");
-        let conn = Connection::open(db_path)?;
+        let (conn, status) = integrity::open_checked(&db_path);
 
         // Enable WAL mode for better concurrent access
         conn.execute_batch("PRAGMA journal_mode=WAL;")?;
@@ -22,8 +40,35 @@ impl Database {
         // Create tables
         schema::create_tables(&conn)?;
 
-        Ok(Self {
-            conn: Mutex::new(conn),
-        })
+        let reader = match conn.path().filter(|path| !path.is_empty()) {
+            Some(path) => match open_reader(path) {
+                Ok(reader) => Some(Arc::new(Mutex::new(reader))),
+                Err(e) => {
+                    warn!(
+                        "[DB] read-only connection unavailable, reads share the writer: {}",
+                        e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok((
+            Self {
+                conn: Arc::new(Mutex::new(conn)),
+                reader,
+            },
+            status,
+        ))
     }
 }
+
+fn open_reader(path: &str) -> Result<Connection> {
+    let reader = Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    reader.busy_timeout(READER_BUSY_TIMEOUT)?;
+    Ok(reader)
+}
@@ -12,6 +12,8 @@ pub struct Account {
     pub is_logged_in: bool,
     pub last_checked_at: Option<String>,
     pub created_at: String,
+    /// Extra Chrome command-line flags for this account's windows
+    pub chrome_flags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +28,17 @@ pub struct PublishTask {
     pub status: String,
     pub scheduled_at: Option<String>,
     pub created_at: String,
+    /// JSON array of image/video paths attached after the main video
+    pub extra_media: Option<String>,
+    /// Who publishes at `scheduled_at`: `app` or `platform`. Unset on tasks
+    /// stored before the column existed, which were all app-scheduled.
+    pub schedule_mode: Option<String>,
+    /// `video` or `images`; unset on tasks stored before the column existed
+    pub post_type: Option<String>,
+    /// JSON array of the image paths of an image post
+    pub images: Option<String>,
+    /// Unset on tasks stored before the column existed
+    pub manual_confirm: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,9 +49,188 @@ pub struct TaskPlatform {
     pub custom_title: Option<String>,
     pub custom_description: Option<String>,
     pub custom_tags: Option<String>,
+    pub custom_fields: Option<String>,
     pub status: String,
     pub error_message: Option<String>,
     pub published_at: Option<String>,
+    pub post_url: Option<String>,
+    /// Title after the account's title variation, when it changed the title
+    pub applied_title: Option<String>,
+    /// Classified failure code (`LOGIN_REQUIRED`, `SUBMIT_FAILED`...) of the last run
+    pub error_code: Option<String>,
+}
+
+/// Filled-form screenshot of one account in a publish task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskPreview {
+    pub task_platform_id: i64,
+    pub account_id: i64,
+    pub platform: String,
+    pub display_name: String,
+    pub status: String,
+    pub preview_path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UrlHealthCheck {
+    pub id: i64,
+    pub platform: String,
+    pub account_id: Option<i64>,
+    pub configured_url: String,
+    pub final_url: Option<String>,
+    pub status: String,
+    pub detail: Option<String>,
+    pub checked_at: String,
+}
+
+/// One background login probe of an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountHealth {
+    pub id: i64,
+    pub account_id: i64,
+    pub status: String,
+    /// None when the probe could not tell
+    pub is_logged_in: Option<bool>,
+    pub final_url: Option<String>,
+    pub detail: Option<String>,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplyRule {
+    pub id: i64,
+    pub platform: Option<String>,
+    pub keyword: String,
+    pub reply: String,
+    pub enabled: bool,
+    pub created_at: String,
+}
+
+/// Help article for an error code. `platform` None applies to every platform;
+/// a platform-specific entry wins over it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KbArticle {
+    pub id: i64,
+    pub platform: Option<String>,
+    pub error_code: String,
+    pub title: String,
+    pub url: String,
+    pub updated_at: String,
+}
+
+/// Structure of a platform's upload page as seen by one publish run;
+/// `snapshot` is JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageSnapshotRecord {
+    pub id: i64,
+    pub platform: String,
+    pub app_version: String,
+    pub structure_hash: String,
+    pub snapshot: String,
+    pub captured_at: String,
+}
+
+/// Reusable publish form content. `tags` and `platform_overrides` are JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishTemplate {
+    pub id: i64,
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    pub tags: String,
+    pub platform_overrides: String,
+    pub updated_at: String,
+}
+
+/// One access to the notes vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultAuditEntry {
+    pub id: i64,
+    pub account_id: Option<i64>,
+    pub action: String,
+    pub at: String,
+}
+
+/// A local API token; only the hash of the token itself is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    /// `stats`, `publish` or `admin`
+    pub scope: String,
+    /// First characters of the token, to tell tokens apart in the UI
+    pub token_prefix: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+    pub revoked_at: Option<String>,
+}
+
+/// A publish request queued to start at `run_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    pub id: i64,
+    /// UTC, RFC 3339
+    pub run_at: String,
+    pub title: String,
+    /// `pending`, `running`, `done`, `failed`, `cancelled` or `interrupted`
+    pub status: String,
+    /// Publish task created when the job ran
+    pub task_id: Option<i64>,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+}
+
+/// Runs of one platform that ended with `outcome` after this upload strategy
+/// and signal; `count` is either the total or the not-yet-submitted part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStat {
+    pub platform: String,
+    pub strategy: String,
+    pub signal: String,
+    /// `success` or `failure`
+    pub outcome: String,
+    pub count: i64,
+}
+
+/// How often one upload strategy started the upload for one account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyOutcome {
+    pub platform: String,
+    pub account_id: i64,
+    pub strategy: String,
+    pub successes: i64,
+    pub failures: i64,
+    pub updated_at: String,
+}
+
+/// Named, reusable list of tags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagSet {
+    pub id: i64,
+    pub name: String,
+    pub tags: Vec<String>,
+    pub updated_at: String,
+}
+
+/// Login/activity timestamps used to estimate when an account's cookies expire
+#[derive(Debug, Clone)]
+pub struct AccountSessionTimes {
+    pub account_id: i64,
+    pub platform: String,
+    pub is_logged_in: bool,
+    pub last_login_at: Option<String>,
+    pub last_authenticated_at: Option<String>,
+}
+
+/// A published post whose comments can be monitored
+#[derive(Debug, Clone)]
+pub struct MonitoredPost {
+    pub task_platform_id: i64,
+    pub platform: String,
+    pub chrome_profile_dir: String,
+    pub chrome_flags: Vec<String>,
+    pub post_url: String,
 }
 
 // ========== Account Queries ==========
@@ -56,9 +248,29 @@ pub fn insert_account(
     Ok(conn.last_insert_rowid())
 }
 
+/// Insert `(display_name, chrome_profile_dir)` accounts of one platform in a
+/// single transaction; either all are created or none
+pub fn insert_accounts(
+    conn: &Connection,
+    platform: &str,
+    accounts: &[(String, String)],
+) -> Result<Vec<i64>> {
+    let tx = conn.unchecked_transaction()?;
+    let mut ids = Vec::with_capacity(accounts.len());
+    for (display_name, chrome_profile_dir) in accounts {
+        tx.execute(
+            "INSERT INTO accounts (platform, display_name, chrome_profile_dir) VALUES (?1, ?2, ?3)",
+            params![platform, display_name, chrome_profile_dir],
+        )?;
+        ids.push(tx.last_insert_rowid());
+    }
+    tx.commit()?;
+    Ok(ids)
+}
+
 pub fn get_all_accounts(conn: &Connection) -> Result<Vec<Account>> {
     let mut stmt = conn.prepare(
-        "SELECT id, platform, display_name, avatar_url, chrome_profile_dir, is_logged_in, last_checked_at, created_at FROM accounts ORDER BY created_at DESC"
+        "SELECT id, platform, display_name, avatar_url, chrome_profile_dir, is_logged_in, last_checked_at, created_at, chrome_flags FROM accounts ORDER BY created_at DESC"
     )?;
     let accounts = stmt
         .query_map([], |row| {
@@ -71,6 +283,7 @@ pub fn get_all_accounts(conn: &Connection) -> Result<Vec<Account>> {
                 is_logged_in: row.get(5)?,
                 last_checked_at: row.get(6)?,
                 created_at: row.get(7)?,
+                chrome_flags: parse_chrome_flags(row.get(8)?),
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -93,6 +306,14 @@ pub fn update_account_display_name(conn: &Connection, id: i64, display_name: &st
     Ok(())
 }
 
+pub fn update_account_profile_dir(conn: &Connection, id: i64, profile_dir: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET chrome_profile_dir = ?1 WHERE id = ?2",
+        params![profile_dir, id],
+    )?;
+    Ok(())
+}
+
 pub fn delete_account(conn: &Connection, id: i64) -> Result<String> {
     // Get profile dir before deleting
     let profile_dir: String = conn.query_row(
@@ -101,82 +322,1669 @@ pub fn delete_account(conn: &Connection, id: i64) -> Result<String> {
         |row| row.get(0),
     )?;
     conn.execute("DELETE FROM accounts WHERE id = ?1", params![id])?;
+    conn.execute(
+        "DELETE FROM account_notes WHERE account_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM account_health WHERE account_id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM strategy_outcomes WHERE account_id = ?1",
+        params![id],
+    )?;
     Ok(profile_dir)
 }
 
-// ========== Publish Task Queries ==========
+pub fn get_account_watermark(conn: &Connection, id: i64) -> Result<Option<String>> {
+    let config: Option<String> = conn.query_row(
+        "SELECT watermark_config FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(config)
+}
 
-pub fn insert_publish_task(
+pub fn update_account_watermark(conn: &Connection, id: i64, config: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET watermark_config = ?1 WHERE id = ?2",
+        params![config, id],
+    )?;
+    Ok(())
+}
+
+/// Per-account default platform fields, a JSON object
+pub fn get_account_publish_defaults(conn: &Connection, id: i64) -> Result<Option<String>> {
+    let defaults: Option<String> = conn.query_row(
+        "SELECT publish_defaults FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(defaults)
+}
+
+pub fn update_account_publish_defaults(
     conn: &Connection,
-    video_path: &str,
-    title: &str,
-    description: Option<&str>,
-    tags: Option<&str>,
-    is_original: bool,
-    scheduled_at: Option<&str>,
-) -> Result<i64> {
+    id: i64,
+    defaults: Option<&str>,
+) -> Result<()> {
     conn.execute(
-        "INSERT INTO publish_tasks (video_path, title, description, tags, is_original, scheduled_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        params![video_path, title, description, tags, is_original, scheduled_at],
+        "UPDATE accounts SET publish_defaults = ?1 WHERE id = ?2",
+        params![defaults, id],
     )?;
-    Ok(conn.last_insert_rowid())
+    Ok(())
+}
+
+/// Per-account template variables, a JSON object of strings
+pub fn get_account_variables(conn: &Connection, id: i64) -> Result<Option<String>> {
+    let variables: Option<String> = conn.query_row(
+        "SELECT variables FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(variables)
 }
 
-pub fn insert_task_platform(conn: &Connection, task_id: i64, account_id: i64) -> Result<i64> {
+pub fn update_account_variables(conn: &Connection, id: i64, variables: Option<&str>) -> Result<()> {
     conn.execute(
-        "INSERT INTO publish_task_platforms (task_id, account_id) VALUES (?1, ?2)",
-        params![task_id, account_id],
+        "UPDATE accounts SET variables = ?1 WHERE id = ?2",
+        params![variables, id],
     )?;
-    Ok(conn.last_insert_rowid())
+    Ok(())
 }
 
-pub fn update_task_platform_status(
+/// Per-account title variation settings, a JSON object
+pub fn get_account_title_variation(conn: &Connection, id: i64) -> Result<Option<String>> {
+    let variation: Option<String> = conn.query_row(
+        "SELECT title_variation FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(variation)
+}
+
+pub fn update_account_title_variation(
     conn: &Connection,
     id: i64,
-    status: &str,
-    error_message: Option<&str>,
+    variation: Option<&str>,
 ) -> Result<()> {
-    if status == "published" {
-        conn.execute(
-            "UPDATE publish_task_platforms SET status = ?1, published_at = datetime('now') WHERE id = ?2",
-            params![status, id],
+    conn.execute(
+        "UPDATE accounts SET title_variation = ?1 WHERE id = ?2",
+        params![variation, id],
+    )?;
+    Ok(())
+}
+
+/// Allowed daily publishing windows, a JSON object
+pub fn get_account_publish_windows(conn: &Connection, id: i64) -> Result<Option<String>> {
+    let windows: Option<String> = conn.query_row(
+        "SELECT publish_windows FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(windows)
+}
+
+pub fn update_account_publish_windows(
+    conn: &Connection,
+    id: i64,
+    windows: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET publish_windows = ?1 WHERE id = ?2",
+        params![windows, id],
+    )?;
+    Ok(())
+}
+
+/// Stored flags are validated on write; unreadable JSON counts as none
+fn parse_chrome_flags(raw: Option<String>) -> Vec<String> {
+    raw.and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default()
+}
+
+pub fn get_account_chrome_flags(conn: &Connection, id: i64) -> Result<Vec<String>> {
+    let raw: Option<String> = conn.query_row(
+        "SELECT chrome_flags FROM accounts WHERE id = ?1",
+        params![id],
+        |row| row.get(0),
+    )?;
+    Ok(parse_chrome_flags(raw))
+}
+
+pub fn update_account_chrome_flags(conn: &Connection, id: i64, flags: Option<&str>) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET chrome_flags = ?1 WHERE id = ?2",
+        params![flags, id],
+    )?;
+    Ok(())
+}
+
+pub fn mark_account_logged_in(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET last_login_at = datetime('now'), last_authenticated_at = datetime('now') WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+pub fn mark_account_authenticated(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE accounts SET last_authenticated_at = datetime('now') WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+/// Clear the account's login time and return the session times as they were
+/// before, in one transaction. None when it was already cleared, so an expiry
+/// reported again by another check is not counted twice.
+pub fn take_account_session_times(
+    conn: &Connection,
+    id: i64,
+) -> Result<Option<AccountSessionTimes>> {
+    let tx = conn.unchecked_transaction()?;
+    let times = {
+        let mut stmt = tx.prepare(
+            "SELECT id, platform, is_logged_in, last_login_at, last_authenticated_at FROM accounts
+             WHERE id = ?1 AND last_login_at IS NOT NULL",
         )?;
-    } else {
-        conn.execute(
-            "UPDATE publish_task_platforms SET status = ?1, error_message = ?2 WHERE id = ?3",
-            params![status, error_message, id],
+        let mut rows = stmt.query_map(params![id], session_times_from_row)?;
+        rows.next().transpose()?
+    };
+    if times.is_some() {
+        tx.execute(
+            "UPDATE accounts SET last_login_at = NULL WHERE id = ?1",
+            params![id],
         )?;
     }
+    tx.commit()?;
+    Ok(times)
+}
+
+fn session_times_from_row(row: &rusqlite::Row) -> rusqlite::Result<AccountSessionTimes> {
+    Ok(AccountSessionTimes {
+        account_id: row.get(0)?,
+        platform: row.get(1)?,
+        is_logged_in: row.get(2)?,
+        last_login_at: row.get(3)?,
+        last_authenticated_at: row.get(4)?,
+    })
+}
+
+pub fn get_account_session_times(conn: &Connection) -> Result<Vec<AccountSessionTimes>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, is_logged_in, last_login_at, last_authenticated_at FROM accounts ORDER BY id",
+    )?;
+    let times = stmt
+        .query_map([], session_times_from_row)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(times)
+}
+
+pub fn insert_session_lifetime(
+    conn: &Connection,
+    platform: &str,
+    lifetime_days: f64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO session_lifetimes (platform, lifetime_days) VALUES (?1, ?2)",
+        params![platform, lifetime_days],
+    )?;
     Ok(())
 }
 
-pub fn update_task_status(conn: &Connection, id: i64, status: &str) -> Result<()> {
+pub fn get_recent_session_lifetimes(
+    conn: &Connection,
+    platform: &str,
+    limit: i64,
+) -> Result<Vec<f64>> {
+    let mut stmt = conn.prepare(
+        "SELECT lifetime_days FROM session_lifetimes WHERE platform = ?1 ORDER BY id DESC LIMIT ?2",
+    )?;
+    let lifetimes = stmt
+        .query_map(params![platform, limit], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<f64>, _>>()?;
+    Ok(lifetimes)
+}
+
+// ========== Publish Timing Queries ==========
+
+/// Wall time of one account's automation run
+#[derive(Debug, Clone)]
+pub struct PublishTiming {
+    pub video_bytes: i64,
+    pub duration_ms: i64,
+    /// Time the upload signal took to show; None when the run never got one
+    pub signal_latency_ms: Option<i64>,
+}
+
+pub fn insert_publish_timing(
+    conn: &Connection,
+    task_id: i64,
+    account_id: i64,
+    platform: &str,
+    timing: &PublishTiming,
+    status: &str,
+) -> Result<()> {
     conn.execute(
-        "UPDATE publish_tasks SET status = ?1 WHERE id = ?2",
-        params![status, id],
+        "INSERT INTO publish_timings (task_id, account_id, platform, video_bytes, duration_ms, signal_latency_ms, status) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            task_id,
+            account_id,
+            platform,
+            timing.video_bytes,
+            timing.duration_ms,
+            timing.signal_latency_ms,
+            status
+        ],
     )?;
     Ok(())
 }
 
-pub fn get_all_tasks(conn: &Connection) -> Result<Vec<PublishTask>> {
+/// One automation step of an account's run, as written by the log sink
+pub struct AutomationStep<'a> {
+    pub platform: &'a str,
+    /// `upload`, `automation_failed`, `submit_failed`...
+    pub phase: &'a str,
+    /// Upload strategy label, for strategy attempts
+    pub strategy: Option<&'a str>,
+    /// `signal`, `no_signal`, `failed` or an error code
+    pub result: &'a str,
+    pub elapsed_ms: i64,
+    /// Diagnostics gathered during the step, page fingerprints included
+    pub detail: &'a str,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutomationLog {
+    pub id: i64,
+    pub task_platform_id: i64,
+    pub account_id: i64,
+    pub platform: String,
+    pub phase: String,
+    pub strategy: Option<String>,
+    pub result: String,
+    pub elapsed_ms: i64,
+    pub detail: String,
+    pub recorded_at: String,
+}
+
+pub fn insert_automation_log(
+    conn: &Connection,
+    task_platform_id: i64,
+    step: &AutomationStep,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO automation_logs (task_platform_id, platform, phase, strategy, result, elapsed_ms, detail) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            task_platform_id,
+            step.platform,
+            step.phase,
+            step.strategy,
+            step.result,
+            step.elapsed_ms,
+            step.detail
+        ],
+    )?;
+    Ok(())
+}
+
+/// Automation steps of every account of a task, in the order they were recorded
+pub fn get_automation_logs(conn: &Connection, task_id: i64) -> Result<Vec<AutomationLog>> {
     let mut stmt = conn.prepare(
-        "SELECT id, video_path, title, description, tags, cover_path, is_original, status, scheduled_at, created_at FROM publish_tasks ORDER BY created_at DESC"
+        "SELECT l.id, l.task_platform_id, tp.account_id, l.platform, l.phase, l.strategy, l.result, l.elapsed_ms, l.detail, l.recorded_at
+         FROM automation_logs l
+         JOIN publish_task_platforms tp ON tp.id = l.task_platform_id
+         WHERE tp.task_id = ?1
+         ORDER BY l.id",
     )?;
-    let tasks = stmt
-        .query_map([], |row| {
-            Ok(PublishTask {
+    let logs = stmt
+        .query_map(params![task_id], |row| {
+            Ok(AutomationLog {
                 id: row.get(0)?,
-                video_path: row.get(1)?,
-                title: row.get(2)?,
-                description: row.get(3)?,
-                tags: row.get(4)?,
-                cover_path: row.get(5)?,
-                is_original: row.get(6)?,
-                status: row.get(7)?,
-                scheduled_at: row.get(8)?,
-                created_at: row.get(9)?,
+                task_platform_id: row.get(1)?,
+                account_id: row.get(2)?,
+                platform: row.get(3)?,
+                phase: row.get(4)?,
+                strategy: row.get(5)?,
+                result: row.get(6)?,
+                elapsed_ms: row.get(7)?,
+                detail: row.get(8)?,
+                recorded_at: row.get(9)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
-    Ok(tasks)
+    Ok(logs)
+}
+
+/// Upload signal latencies of the most recent runs of `platform` that got one,
+/// whatever their outcome
+pub fn get_recent_signal_latencies(
+    conn: &Connection,
+    platform: &str,
+    limit: i64,
+) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT signal_latency_ms FROM publish_timings WHERE platform = ?1 AND signal_latency_ms IS NOT NULL ORDER BY id DESC LIMIT ?2",
+    )?;
+    let latencies = stmt
+        .query_map(params![platform, limit], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(latencies)
+}
+
+/// Most recent runs of `platform` that ended in `status`, newest first
+pub fn get_recent_publish_timings(
+    conn: &Connection,
+    platform: &str,
+    status: &str,
+    limit: i64,
+) -> Result<Vec<PublishTiming>> {
+    let mut stmt = conn.prepare(
+        "SELECT video_bytes, duration_ms, signal_latency_ms FROM publish_timings WHERE platform = ?1 AND status = ?2 ORDER BY id DESC LIMIT ?3",
+    )?;
+    let timings = stmt
+        .query_map(params![platform, status, limit], |row| {
+            Ok(PublishTiming {
+                video_bytes: row.get(0)?,
+                duration_ms: row.get(1)?,
+                signal_latency_ms: row.get(2)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(timings)
+}
+
+// ========== Publish Task Queries ==========
+
+pub fn insert_publish_task(
+    conn: &Connection,
+    video_path: &str,
+    title: &str,
+    description: Option<&str>,
+    tags: Option<&str>,
+    is_original: bool,
+    scheduled_at: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO publish_tasks (video_path, title, description, tags, is_original, scheduled_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![video_path, title, description, tags, is_original, scheduled_at],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_task_extra_media(conn: &Connection, id: i64, extra_media: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_tasks SET extra_media = ?1 WHERE id = ?2",
+        params![extra_media, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_schedule_mode(conn: &Connection, id: i64, schedule_mode: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_tasks SET schedule_mode = ?1 WHERE id = ?2",
+        params![schedule_mode, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_post_options(
+    conn: &Connection,
+    id: i64,
+    post_type: &str,
+    images: Option<&str>,
+    manual_confirm: bool,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_tasks SET post_type = ?1, images = ?2, manual_confirm = ?3 WHERE id = ?4",
+        params![post_type, images, manual_confirm, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_cover_path(conn: &Connection, id: i64, cover_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_tasks SET cover_path = ?1 WHERE id = ?2",
+        params![cover_path, id],
+    )?;
+    Ok(())
+}
+
+pub fn insert_task_platform(
+    conn: &Connection,
+    task_id: i64,
+    account_id: i64,
+    custom_title: Option<&str>,
+    custom_description: Option<&str>,
+    custom_tags: Option<&str>,
+    custom_fields: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO publish_task_platforms (task_id, account_id, custom_title, custom_description, custom_tags, custom_fields) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![task_id, account_id, custom_title, custom_description, custom_tags, custom_fields],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_task_platform_status(
+    conn: &Connection,
+    id: i64,
+    status: &str,
+    error_message: Option<&str>,
+    error_code: Option<&str>,
+) -> Result<()> {
+    if status == "published" {
+        conn.execute(
+            "UPDATE publish_task_platforms SET status = ?1, published_at = datetime('now') WHERE id = ?2",
+            params![status, id],
+        )?;
+    } else {
+        conn.execute(
+            "UPDATE publish_task_platforms SET status = ?1, error_message = ?2, error_code = ?3 WHERE id = ?4",
+            params![status, error_message, error_code, id],
+        )?;
+    }
+    Ok(())
+}
+
+pub fn update_task_platform_preview(conn: &Connection, id: i64, preview_path: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_task_platforms SET preview_path = ?1 WHERE id = ?2",
+        params![preview_path, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_platform_applied_title(conn: &Connection, id: i64, title: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_task_platforms SET applied_title = ?1 WHERE id = ?2",
+        params![title, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_platform_duration(conn: &Connection, id: i64, duration_ms: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_task_platforms SET duration_ms = ?1 WHERE id = ?2",
+        params![duration_ms, id],
+    )?;
+    Ok(())
+}
+
+pub fn update_task_platform_bandwidth(
+    conn: &Connection,
+    id: i64,
+    bytes_sent: u64,
+    bytes_received: u64,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_task_platforms SET bytes_sent = ?1, bytes_received = ?2 WHERE id = ?3",
+        params![bytes_sent as i64, bytes_received as i64, id],
+    )?;
+    Ok(())
+}
+
+pub fn get_task_previews(conn: &Connection, task_id: i64) -> Result<Vec<TaskPreview>> {
+    let mut stmt = conn.prepare(
+        "SELECT tp.id, tp.account_id, a.platform, a.display_name, tp.status, tp.preview_path
+         FROM publish_task_platforms tp
+         JOIN accounts a ON a.id = tp.account_id
+         WHERE tp.task_id = ?1 AND tp.preview_path IS NOT NULL
+         ORDER BY tp.id",
+    )?;
+    let previews = stmt
+        .query_map(params![task_id], |row| {
+            Ok(TaskPreview {
+                task_platform_id: row.get(0)?,
+                account_id: row.get(1)?,
+                platform: row.get(2)?,
+                display_name: row.get(3)?,
+                status: row.get(4)?,
+                preview_path: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(previews)
+}
+
+pub fn get_task_platforms(conn: &Connection, task_id: i64) -> Result<Vec<TaskPlatform>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, task_id, account_id, custom_title, custom_description, custom_tags, custom_fields, status, error_message, published_at, post_url, applied_title, error_code FROM publish_task_platforms WHERE task_id = ?1 ORDER BY id",
+    )?;
+    let rows = stmt
+        .query_map(params![task_id], |row| {
+            Ok(TaskPlatform {
+                id: row.get(0)?,
+                task_id: row.get(1)?,
+                account_id: row.get(2)?,
+                custom_title: row.get(3)?,
+                custom_description: row.get(4)?,
+                custom_tags: row.get(5)?,
+                custom_fields: row.get(6)?,
+                status: row.get(7)?,
+                error_message: row.get(8)?,
+                published_at: row.get(9)?,
+                post_url: row.get(10)?,
+                applied_title: row.get(11)?,
+                error_code: row.get(12)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Rows of tasks that never reached their final status update, i.e. the app
+/// went down mid-run, are marked `interrupted`. Only run at startup, before
+/// any publish can be in flight. Returns (tasks, task platforms) marked.
+pub fn mark_orphaned_tasks_interrupted(conn: &Connection) -> Result<(usize, usize)> {
+    let platforms = conn.execute(
+        "UPDATE publish_task_platforms SET status = 'interrupted', error_message = COALESCE(error_message, 'app exited during automation')
+         WHERE status IN ('pending', 'running')
+           AND task_id IN (SELECT id FROM publish_tasks WHERE status = 'pending')",
+        [],
+    )?;
+    let tasks = conn.execute(
+        "UPDATE publish_tasks SET status = 'interrupted' WHERE status = 'pending'",
+        [],
+    )?;
+    Ok((tasks, platforms))
+}
+
+pub fn update_task_status(conn: &Connection, id: i64, status: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_tasks SET status = ?1 WHERE id = ?2",
+        params![status, id],
+    )?;
+    Ok(())
+}
+
+const TASK_COLUMNS: &str =
+    "id, video_path, title, description, tags, cover_path, is_original, status, scheduled_at, created_at, extra_media, schedule_mode, post_type, images, manual_confirm";
+
+fn task_from_row(row: &rusqlite::Row) -> rusqlite::Result<PublishTask> {
+    Ok(PublishTask {
+        id: row.get(0)?,
+        video_path: row.get(1)?,
+        title: row.get(2)?,
+        description: row.get(3)?,
+        tags: row.get(4)?,
+        cover_path: row.get(5)?,
+        is_original: row.get(6)?,
+        status: row.get(7)?,
+        scheduled_at: row.get(8)?,
+        created_at: row.get(9)?,
+        extra_media: row.get(10)?,
+        schedule_mode: row.get(11)?,
+        post_type: row.get(12)?,
+        images: row.get(13)?,
+        manual_confirm: row.get(14)?,
+    })
+}
+
+pub fn get_task(conn: &Connection, id: i64) -> Result<Option<PublishTask>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM publish_tasks WHERE id = ?1",
+        TASK_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map(params![id], task_from_row)?;
+    Ok(rows.next().transpose()?)
+}
+
+pub fn get_all_tasks(conn: &Connection) -> Result<Vec<PublishTask>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM publish_tasks ORDER BY created_at DESC",
+        TASK_COLUMNS
+    ))?;
+    let tasks = stmt
+        .query_map([], task_from_row)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(tasks)
+}
+
+/// Delete tasks together with their platform rows, timing records and
+/// automation logs in one transaction. Returns how many tasks were deleted.
+pub fn delete_publish_tasks(conn: &Connection, ids: &[i64]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut deleted = 0;
+    for id in ids {
+        tx.execute(
+            "DELETE FROM automation_logs WHERE task_platform_id IN (SELECT id FROM publish_task_platforms WHERE task_id = ?1)",
+            params![id],
+        )?;
+        tx.execute(
+            "DELETE FROM publish_task_platforms WHERE task_id = ?1",
+            params![id],
+        )?;
+        tx.execute(
+            "DELETE FROM publish_timings WHERE task_id = ?1",
+            params![id],
+        )?;
+        deleted += tx.execute("DELETE FROM publish_tasks WHERE id = ?1", params![id])?;
+    }
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Tasks created more than `days` days ago, newest first
+pub fn get_task_ids_older_than(conn: &Connection, days: u32) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM publish_tasks WHERE created_at < datetime('now', ?1) ORDER BY id DESC",
+    )?;
+    let ids = stmt
+        .query_map(params![format!("-{} days", days)], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+/// Account runs grouped by local day, platform and status
+#[derive(Debug, Clone)]
+pub struct CalendarRow {
+    pub day: String,
+    pub platform: String,
+    pub status: String,
+    /// Runs of tasks with a requested publish time
+    pub scheduled: bool,
+    pub count: i64,
+}
+
+/// Bandwidth and wall time of one account's runs on one local day
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishUsage {
+    pub day: String,
+    pub account_id: i64,
+    pub platform: String,
+    pub display_name: String,
+    pub runs: i64,
+    pub duration_ms: i64,
+    pub bytes_sent: i64,
+    pub bytes_received: i64,
+}
+
+/// Per day and account totals of runs created in the last `days` days
+pub fn get_publish_usage(conn: &Connection, days: u32) -> Result<Vec<PublishUsage>> {
+    let mut stmt = conn.prepare(
+        "SELECT date(t.created_at, 'localtime') AS day, tp.account_id, a.platform, a.display_name,
+                COUNT(*), COALESCE(SUM(tp.duration_ms), 0),
+                COALESCE(SUM(tp.bytes_sent), 0), COALESCE(SUM(tp.bytes_received), 0)
+         FROM publish_task_platforms tp
+         JOIN publish_tasks t ON t.id = tp.task_id
+         JOIN accounts a ON a.id = tp.account_id
+         WHERE t.created_at >= datetime('now', ?1)
+         GROUP BY day, tp.account_id
+         ORDER BY day DESC, a.platform, a.display_name",
+    )?;
+    let rows = stmt
+        .query_map(params![format!("-{} days", days)], |row| {
+            Ok(PublishUsage {
+                day: row.get(0)?,
+                account_id: row.get(1)?,
+                platform: row.get(2)?,
+                display_name: row.get(3)?,
+                runs: row.get(4)?,
+                duration_ms: row.get(5)?,
+                bytes_sent: row.get(6)?,
+                bytes_received: row.get(7)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+/// Account runs falling into `month` (`YYYY-MM`, local time). A run counts on
+/// its requested publish time, else when it was published, else when created.
+pub fn get_calendar_rows(conn: &Connection, month: &str) -> Result<Vec<CalendarRow>> {
+    let mut stmt = conn.prepare(
+        "SELECT date(COALESCE(t.scheduled_at, tp.published_at, t.created_at), 'localtime') AS day,
+                a.platform, tp.status, t.scheduled_at IS NOT NULL, COUNT(*)
+         FROM publish_task_platforms tp
+         JOIN publish_tasks t ON t.id = tp.task_id
+         JOIN accounts a ON a.id = tp.account_id
+         WHERE strftime('%Y-%m', COALESCE(t.scheduled_at, tp.published_at, t.created_at), 'localtime') = ?1
+         GROUP BY day, a.platform, tp.status, t.scheduled_at IS NOT NULL
+         ORDER BY day, a.platform",
+    )?;
+    let rows = stmt
+        .query_map(params![month], |row| {
+            Ok(CalendarRow {
+                day: row.get(0)?,
+                platform: row.get(1)?,
+                status: row.get(2)?,
+                scheduled: row.get(3)?,
+                count: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+// ========== Settings Queries ==========
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM app_settings WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn get_setting_bool(conn: &Connection, key: &str, default: bool) -> Result<bool> {
+    Ok(match get_setting(conn, key)? {
+        Some(value) => matches!(value.trim(), "1" | "true" | "yes" | "on"),
+        None => default,
+    })
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO app_settings (key, value, updated_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub fn get_all_settings(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT key, value FROM app_settings ORDER BY key")?;
+    let settings = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(settings)
+}
+
+// ========== URL Health Queries ==========
+
+pub fn insert_url_health_check(
+    conn: &Connection,
+    platform: &str,
+    account_id: Option<i64>,
+    configured_url: &str,
+    final_url: Option<&str>,
+    status: &str,
+    detail: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO url_health_checks (platform, account_id, configured_url, final_url, status, detail) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![platform, account_id, configured_url, final_url, status, detail],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Latest check result per platform
+pub fn get_latest_url_health_checks(conn: &Connection) -> Result<Vec<UrlHealthCheck>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, account_id, configured_url, final_url, status, detail, checked_at FROM url_health_checks
+         WHERE id IN (SELECT MAX(id) FROM url_health_checks GROUP BY platform)
+         ORDER BY platform"
+    )?;
+    let checks = stmt
+        .query_map([], |row| {
+            Ok(UrlHealthCheck {
+                id: row.get(0)?,
+                platform: row.get(1)?,
+                account_id: row.get(2)?,
+                configured_url: row.get(3)?,
+                final_url: row.get(4)?,
+                status: row.get(5)?,
+                detail: row.get(6)?,
+                checked_at: row.get(7)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(checks)
+}
+
+pub fn insert_account_health(
+    conn: &Connection,
+    account_id: i64,
+    status: &str,
+    is_logged_in: Option<bool>,
+    final_url: Option<&str>,
+    detail: Option<&str>,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO account_health (account_id, status, is_logged_in, final_url, detail) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![account_id, status, is_logged_in, final_url, detail],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Newest first; all accounts when `account_id` is None
+pub fn get_account_health(
+    conn: &Connection,
+    account_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<AccountHealth>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, status, is_logged_in, final_url, detail, checked_at FROM account_health
+         WHERE ?1 IS NULL OR account_id = ?1
+         ORDER BY id DESC LIMIT ?2",
+    )?;
+    let rows = stmt
+        .query_map(params![account_id, limit], |row| {
+            Ok(AccountHealth {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                status: row.get(2)?,
+                is_logged_in: row.get(3)?,
+                final_url: row.get(4)?,
+                detail: row.get(5)?,
+                checked_at: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+pub fn prune_account_health(conn: &Connection, keep_days: u32) -> Result<usize> {
+    let removed = conn.execute(
+        "DELETE FROM account_health WHERE checked_at < datetime('now', ?1)",
+        params![format!("-{} days", keep_days)],
+    )?;
+    Ok(removed)
+}
+
+pub fn update_task_platform_post_url(
+    conn: &Connection,
+    id: i64,
+    post_url: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE publish_task_platforms SET post_url = ?1 WHERE id = ?2",
+        params![post_url, id],
+    )?;
+    Ok(())
+}
+
+// ========== Publish Template Queries ==========
+
+/// Insert a template, or replace the one with the same name. `templates` has
+/// no unique name constraint, so the replace is an update that may miss.
+pub fn upsert_template(
+    conn: &Connection,
+    name: &str,
+    title: &str,
+    description: &str,
+    tags: &str,
+    platform_overrides: &str,
+) -> Result<i64> {
+    let updated = conn.execute(
+        "UPDATE templates SET title_template = ?2, description_template = ?3, tags = ?4,
+             platform_overrides = ?5, updated_at = datetime('now') WHERE name = ?1",
+        params![name, title, description, tags, platform_overrides],
+    )?;
+    if updated == 0 {
+        conn.execute(
+            "INSERT INTO templates (name, title_template, description_template, tags, platform_overrides) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![name, title, description, tags, platform_overrides],
+        )?;
+    }
+    let id = conn.query_row(
+        "SELECT id FROM templates WHERE name = ?1 ORDER BY id LIMIT 1",
+        params![name],
+        |row| row.get(0),
+    )?;
+    Ok(id)
+}
+
+pub fn get_templates(conn: &Connection) -> Result<Vec<PublishTemplate>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, COALESCE(title_template, ''), COALESCE(description_template, ''),
+             COALESCE(tags, '[]'), COALESCE(platform_overrides, '{}'), updated_at
+         FROM templates ORDER BY name",
+    )?;
+    let templates = stmt
+        .query_map([], |row| {
+            Ok(PublishTemplate {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                title: row.get(2)?,
+                description: row.get(3)?,
+                tags: row.get(4)?,
+                platform_overrides: row.get(5)?,
+                updated_at: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(templates)
+}
+
+pub fn delete_template(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM templates WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// ========== Account Notes Vault Queries ==========
+
+/// Posts of an account waiting for platform review, as last scraped
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountReviewCount {
+    pub account_id: i64,
+    pub in_review: i64,
+    pub checked_at: String,
+}
+
+pub fn upsert_account_review_count(
+    conn: &Connection,
+    account_id: i64,
+    in_review: i64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO account_review_counts (account_id, in_review) VALUES (?1, ?2)
+         ON CONFLICT(account_id) DO UPDATE SET in_review = excluded.in_review, checked_at = datetime('now')",
+        params![account_id, in_review],
+    )?;
+    Ok(())
+}
+
+pub fn get_account_review_count(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Option<AccountReviewCount>> {
+    let mut stmt = conn.prepare(
+        "SELECT account_id, in_review, checked_at FROM account_review_counts WHERE account_id = ?1",
+    )?;
+    let mut rows = stmt.query(params![account_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(AccountReviewCount {
+            account_id: row.get(0)?,
+            in_review: row.get(1)?,
+            checked_at: row.get(2)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+pub fn get_vault_config(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM vault_config WHERE key = ?1")?;
+    let mut rows = stmt.query(params![key])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn set_vault_config(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO vault_config (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub fn upsert_account_notes(conn: &Connection, account_id: i64, ciphertext: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO account_notes (account_id, ciphertext) VALUES (?1, ?2)
+         ON CONFLICT(account_id) DO UPDATE SET ciphertext = excluded.ciphertext, updated_at = datetime('now')",
+        params![account_id, ciphertext],
+    )?;
+    Ok(())
+}
+
+pub fn get_account_notes(conn: &Connection, account_id: i64) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT ciphertext FROM account_notes WHERE account_id = ?1")?;
+    let mut rows = stmt.query(params![account_id])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn delete_account_notes(conn: &Connection, account_id: i64) -> Result<()> {
+    conn.execute(
+        "DELETE FROM account_notes WHERE account_id = ?1",
+        params![account_id],
+    )?;
+    Ok(())
+}
+
+/// Accounts that have notes, without decrypting anything
+pub fn get_account_ids_with_notes(conn: &Connection) -> Result<Vec<i64>> {
+    let mut stmt = conn.prepare("SELECT account_id FROM account_notes ORDER BY account_id")?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+pub fn insert_vault_audit(conn: &Connection, account_id: Option<i64>, action: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO vault_audit_log (account_id, action) VALUES (?1, ?2)",
+        params![account_id, action],
+    )?;
+    Ok(())
+}
+
+pub fn get_vault_audit_log(conn: &Connection, limit: i64) -> Result<Vec<VaultAuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account_id, action, at FROM vault_audit_log ORDER BY id DESC LIMIT ?1",
+    )?;
+    let entries = stmt
+        .query_map(params![limit], |row| {
+            Ok(VaultAuditEntry {
+                id: row.get(0)?,
+                account_id: row.get(1)?,
+                action: row.get(2)?,
+                at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+// ========== API Token Queries ==========
+
+const API_TOKEN_COLUMNS: &str =
+    "id, name, scope, token_prefix, created_at, last_used_at, revoked_at";
+
+fn map_api_token(row: &rusqlite::Row) -> rusqlite::Result<ApiToken> {
+    Ok(ApiToken {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        scope: row.get(2)?,
+        token_prefix: row.get(3)?,
+        created_at: row.get(4)?,
+        last_used_at: row.get(5)?,
+        revoked_at: row.get(6)?,
+    })
+}
+
+pub fn insert_api_token(
+    conn: &Connection,
+    name: &str,
+    scope: &str,
+    token_hash: &str,
+    token_prefix: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO api_tokens (name, scope, token_hash, token_prefix) VALUES (?1, ?2, ?3, ?4)",
+        params![name, scope, token_hash, token_prefix],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_api_tokens(conn: &Connection) -> Result<Vec<ApiToken>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM api_tokens ORDER BY id DESC",
+        API_TOKEN_COLUMNS
+    ))?;
+    let tokens = stmt
+        .query_map([], map_api_token)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(tokens)
+}
+
+/// Active (not revoked) token with this hash
+pub fn find_api_token_by_hash(conn: &Connection, token_hash: &str) -> Result<Option<ApiToken>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM api_tokens WHERE token_hash = ?1 AND revoked_at IS NULL",
+        API_TOKEN_COLUMNS
+    ))?;
+    let mut rows = stmt.query(params![token_hash])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(map_api_token(row)?)),
+        None => Ok(None),
+    }
+}
+
+pub fn touch_api_token(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE api_tokens SET last_used_at = datetime('now') WHERE id = ?1",
+        params![id],
+    )?;
+    Ok(())
+}
+
+pub fn revoke_api_token(conn: &Connection, id: i64) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE api_tokens SET revoked_at = datetime('now') WHERE id = ?1 AND revoked_at IS NULL",
+        params![id],
+    )?;
+    Ok(changed > 0)
+}
+
+// ========== Scheduled Task Queries ==========
+
+const SCHEDULED_TASK_COLUMNS: &str =
+    "id, run_at, title, status, task_id, error, created_at, finished_at";
+
+fn map_scheduled_task(row: &rusqlite::Row) -> rusqlite::Result<ScheduledTask> {
+    Ok(ScheduledTask {
+        id: row.get(0)?,
+        run_at: row.get(1)?,
+        title: row.get(2)?,
+        status: row.get(3)?,
+        task_id: row.get(4)?,
+        error: row.get(5)?,
+        created_at: row.get(6)?,
+        finished_at: row.get(7)?,
+    })
+}
+
+pub fn insert_scheduled_task(
+    conn: &Connection,
+    run_at: &str,
+    title: &str,
+    payload: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO scheduled_tasks (run_at, title, payload) VALUES (?1, ?2, ?3)",
+        params![run_at, title, payload],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Scheduled tasks by start time; finished ones only when asked for
+pub fn get_scheduled_tasks(
+    conn: &Connection,
+    include_finished: bool,
+) -> Result<Vec<ScheduledTask>> {
+    let filter = if include_finished {
+        ""
+    } else {
+        "WHERE status IN ('pending', 'running')"
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM scheduled_tasks {} ORDER BY run_at, id",
+        SCHEDULED_TASK_COLUMNS, filter
+    ))?;
+    let tasks = stmt
+        .query_map([], map_scheduled_task)?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(tasks)
+}
+
+/// Scheduled tasks that have not started yet
+pub fn count_pending_scheduled_tasks(conn: &Connection) -> Result<i64> {
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM scheduled_tasks WHERE status = 'pending'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Pending jobs whose start time has passed, with their stored request JSON.
+/// `now` must use the same UTC RFC 3339 format as `run_at`.
+pub fn get_due_scheduled_tasks(conn: &Connection, now: &str) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, payload FROM scheduled_tasks
+         WHERE status = 'pending' AND run_at <= ?1 ORDER BY run_at, id",
+    )?;
+    let due = stmt
+        .query_map(params![now], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(due)
+}
+
+/// Stored request JSON of every job that has not started yet
+pub fn get_pending_scheduled_payloads(conn: &Connection) -> Result<Vec<(i64, String)>> {
+    let mut stmt = conn
+        .prepare("SELECT id, payload FROM scheduled_tasks WHERE status = 'pending' ORDER BY id")?;
+    let pending = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(pending)
+}
+
+pub fn update_scheduled_task_payload(conn: &Connection, id: i64, payload: &str) -> Result<()> {
+    conn.execute(
+        "UPDATE scheduled_tasks SET payload = ?1 WHERE id = ?2 AND status = 'pending'",
+        params![payload, id],
+    )?;
+    Ok(())
+}
+
+/// Move a pending job to `running`; false when it was cancelled meanwhile
+pub fn claim_scheduled_task(conn: &Connection, id: i64) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE scheduled_tasks SET status = 'running', started_at = datetime('now')
+         WHERE id = ?1 AND status = 'pending'",
+        params![id],
+    )?;
+    Ok(changed > 0)
+}
+
+pub fn finish_scheduled_task(
+    conn: &Connection,
+    id: i64,
+    status: &str,
+    task_id: Option<i64>,
+    error: Option<&str>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE scheduled_tasks SET status = ?1, task_id = ?2, error = ?3, finished_at = datetime('now')
+         WHERE id = ?4",
+        params![status, task_id, error, id],
+    )?;
+    Ok(())
+}
+
+/// Cancel a job that has not started yet
+pub fn cancel_scheduled_task(conn: &Connection, id: i64) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE scheduled_tasks SET status = 'cancelled', finished_at = datetime('now')
+         WHERE id = ?1 AND status = 'pending'",
+        params![id],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Jobs left `running` by a previous session; their publish task is
+/// reconciled separately by `mark_orphaned_tasks_interrupted`
+pub fn mark_orphaned_scheduled_tasks_interrupted(conn: &Connection) -> Result<usize> {
+    let changed = conn.execute(
+        "UPDATE scheduled_tasks SET status = 'interrupted', finished_at = datetime('now')
+         WHERE status = 'running'",
+        [],
+    )?;
+    Ok(changed)
+}
+
+// ========== Strategy Stat Queries ==========
+
+pub fn increment_strategy_stat(
+    conn: &Connection,
+    platform: &str,
+    strategy: &str,
+    signal: &str,
+    outcome: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO strategy_stats (platform, strategy, signal, outcome, count, unsent)
+         VALUES (?1, ?2, ?3, ?4, 1, 1)
+         ON CONFLICT(platform, strategy, signal, outcome) DO UPDATE SET
+            count = count + 1, unsent = unsent + 1, updated_at = datetime('now')",
+        params![platform, strategy, signal, outcome],
+    )?;
+    Ok(())
+}
+
+fn query_strategy_stats(conn: &Connection, sql: &str) -> Result<Vec<StrategyStat>> {
+    let mut stmt = conn.prepare(sql)?;
+    let stats = stmt
+        .query_map([], |row| {
+            Ok(StrategyStat {
+                platform: row.get(0)?,
+                strategy: row.get(1)?,
+                signal: row.get(2)?,
+                outcome: row.get(3)?,
+                count: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(stats)
+}
+
+/// All-time counts, most frequent first per platform
+pub fn get_strategy_stats(conn: &Connection) -> Result<Vec<StrategyStat>> {
+    query_strategy_stats(
+        conn,
+        "SELECT platform, strategy, signal, outcome, count FROM strategy_stats
+         ORDER BY platform, count DESC",
+    )
+}
+
+/// Counts gathered since the last submission
+pub fn get_unsent_strategy_stats(conn: &Connection) -> Result<Vec<StrategyStat>> {
+    query_strategy_stats(
+        conn,
+        "SELECT platform, strategy, signal, outcome, unsent FROM strategy_stats
+         WHERE unsent > 0 ORDER BY platform, strategy",
+    )
+}
+
+/// Subtract submitted counts; runs counted while the request was in flight stay unsent
+pub fn mark_strategy_stats_sent(conn: &Connection, sent: &[StrategyStat]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    for stat in sent {
+        tx.execute(
+            "UPDATE strategy_stats SET unsent = MAX(unsent - ?1, 0)
+             WHERE platform = ?2 AND strategy = ?3 AND signal = ?4 AND outcome = ?5",
+            params![
+                stat.count,
+                stat.platform,
+                stat.strategy,
+                stat.signal,
+                stat.outcome
+            ],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+pub fn clear_strategy_stats(conn: &Connection) -> Result<()> {
+    conn.execute("DELETE FROM strategy_stats", [])?;
+    Ok(())
+}
+
+pub fn record_strategy_outcome(
+    conn: &Connection,
+    platform: &str,
+    account_id: i64,
+    strategy: &str,
+    succeeded: bool,
+) -> Result<()> {
+    let (successes, failures) = if succeeded { (1, 0) } else { (0, 1) };
+    conn.execute(
+        "INSERT INTO strategy_outcomes (platform, account_id, strategy, successes, failures)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(platform, account_id, strategy) DO UPDATE SET
+            successes = successes + ?4, failures = failures + ?5, updated_at = datetime('now')",
+        params![platform, account_id, strategy, successes, failures],
+    )?;
+    Ok(())
+}
+
+/// Per-account strategy outcomes; all platforms when `platform` is None
+pub fn get_strategy_outcomes(
+    conn: &Connection,
+    platform: Option<&str>,
+) -> Result<Vec<StrategyOutcome>> {
+    let mut stmt = conn.prepare(
+        "SELECT platform, account_id, strategy, successes, failures, updated_at
+         FROM strategy_outcomes WHERE ?1 IS NULL OR platform = ?1
+         ORDER BY platform, account_id, strategy",
+    )?;
+    let outcomes = stmt
+        .query_map(params![platform], |row| {
+            Ok(StrategyOutcome {
+                platform: row.get(0)?,
+                account_id: row.get(1)?,
+                strategy: row.get(2)?,
+                successes: row.get(3)?,
+                failures: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(outcomes)
+}
+
+/// Forget learned outcomes; None matches every platform or account
+pub fn clear_strategy_outcomes(
+    conn: &Connection,
+    platform: Option<&str>,
+    account_id: Option<i64>,
+) -> Result<usize> {
+    let removed = conn.execute(
+        "DELETE FROM strategy_outcomes
+         WHERE (?1 IS NULL OR platform = ?1) AND (?2 IS NULL OR account_id = ?2)",
+        params![platform, account_id],
+    )?;
+    Ok(removed)
+}
+
+// ========== Tag Set Queries ==========
+
+pub fn insert_tag_set(conn: &Connection, name: &str, tags: &[String]) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO tag_sets (name, tags) VALUES (?1, ?2)",
+        params![name, serde_json::to_string(tags)?],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_tag_set(conn: &Connection, id: i64, name: &str, tags: &[String]) -> Result<()> {
+    conn.execute(
+        "UPDATE tag_sets SET name = ?1, tags = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![name, serde_json::to_string(tags)?, id],
+    )?;
+    Ok(())
+}
+
+pub fn get_tag_sets(conn: &Connection) -> Result<Vec<TagSet>> {
+    let mut stmt = conn.prepare("SELECT id, name, tags, updated_at FROM tag_sets ORDER BY name")?;
+    let sets = stmt
+        .query_map([], |row| {
+            let tags: String = row.get(2)?;
+            Ok(TagSet {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                tags: serde_json::from_str(&tags).unwrap_or_default(),
+                updated_at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(sets)
+}
+
+pub fn delete_tag_set(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM tag_sets WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+// ========== Comment Reply Queries ==========
+
+pub fn insert_reply_rule(
+    conn: &Connection,
+    platform: Option<&str>,
+    keyword: &str,
+    reply: &str,
+) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO reply_rules (platform, keyword, reply) VALUES (?1, ?2, ?3)",
+        params![platform, keyword, reply],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_reply_rules(conn: &Connection) -> Result<Vec<ReplyRule>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, keyword, reply, enabled, created_at FROM reply_rules ORDER BY id",
+    )?;
+    let rules = stmt
+        .query_map([], |row| {
+            Ok(ReplyRule {
+                id: row.get(0)?,
+                platform: row.get(1)?,
+                keyword: row.get(2)?,
+                reply: row.get(3)?,
+                enabled: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(rules)
+}
+
+pub fn delete_reply_rule(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM reply_rules WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+pub fn get_monitored_posts(conn: &Connection) -> Result<Vec<MonitoredPost>> {
+    let mut stmt = conn.prepare(
+        "SELECT tp.id, a.platform, a.chrome_profile_dir, tp.post_url, a.chrome_flags
+         FROM publish_task_platforms tp JOIN accounts a ON a.id = tp.account_id
+         WHERE tp.post_url IS NOT NULL AND tp.post_url != ''
+         ORDER BY tp.id DESC",
+    )?;
+    let posts = stmt
+        .query_map([], |row| {
+            Ok(MonitoredPost {
+                task_platform_id: row.get(0)?,
+                platform: row.get(1)?,
+                chrome_profile_dir: row.get(2)?,
+                chrome_flags: parse_chrome_flags(row.get(4)?),
+                post_url: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(posts)
+}
+
+pub fn has_replied_comment(
+    conn: &Connection,
+    task_platform_id: i64,
+    comment_key: &str,
+) -> Result<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM comment_replies WHERE task_platform_id = ?1 AND comment_key = ?2",
+        params![task_platform_id, comment_key],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+pub fn insert_comment_reply(
+    conn: &Connection,
+    task_platform_id: i64,
+    comment_key: &str,
+    rule_id: i64,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO comment_replies (task_platform_id, comment_key, rule_id) VALUES (?1, ?2, ?3)",
+        params![task_platform_id, comment_key, rule_id],
+    )?;
+    Ok(())
+}
+
+/// Replies sent on a platform during the last hour, across all accounts
+pub fn count_recent_replies(conn: &Connection, platform: &str) -> Result<i64> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM comment_replies cr
+         JOIN publish_task_platforms tp ON tp.id = cr.task_platform_id
+         JOIN accounts a ON a.id = tp.account_id
+         WHERE a.platform = ?1 AND cr.replied_at >= datetime('now', '-1 hour')",
+        params![platform],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+// ========== Knowledge Base Queries ==========
+
+/// Insert or replace the article for `(platform, error_code)`
+pub fn upsert_kb_article(
+    conn: &Connection,
+    platform: Option<&str>,
+    error_code: &str,
+    title: &str,
+    url: &str,
+) -> Result<i64> {
+    conn.execute(
+        "DELETE FROM kb_articles WHERE platform IS ?1 AND error_code = ?2",
+        params![platform, error_code],
+    )?;
+    conn.execute(
+        "INSERT INTO kb_articles (platform, error_code, title, url) VALUES (?1, ?2, ?3, ?4)",
+        params![platform, error_code, title, url],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_kb_articles(conn: &Connection) -> Result<Vec<KbArticle>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, error_code, title, url, updated_at FROM kb_articles ORDER BY error_code, platform",
+    )?;
+    let articles = stmt
+        .query_map([], |row| {
+            Ok(KbArticle {
+                id: row.get(0)?,
+                platform: row.get(1)?,
+                error_code: row.get(2)?,
+                title: row.get(3)?,
+                url: row.get(4)?,
+                updated_at: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(articles)
+}
+
+pub fn delete_kb_article(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM kb_articles WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+/// Link for an error on a platform, preferring the platform's own article
+pub fn find_kb_link(conn: &Connection, platform: &str, error_code: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT url FROM kb_articles
+         WHERE error_code = ?2 AND (platform = ?1 OR platform IS NULL)
+         ORDER BY platform IS NULL LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![platform, error_code])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(row.get(0)?)),
+        None => Ok(None),
+    }
+}
+
+/// Store a page snapshot and keep only the newest `keep` of the platform
+pub fn insert_page_snapshot(
+    conn: &Connection,
+    platform: &str,
+    app_version: &str,
+    structure_hash: &str,
+    snapshot: &str,
+    keep: usize,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO page_snapshots (platform, app_version, structure_hash, snapshot)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![platform, app_version, structure_hash, snapshot],
+    )?;
+    conn.execute(
+        "DELETE FROM page_snapshots WHERE platform = ?1 AND id NOT IN (
+            SELECT id FROM page_snapshots WHERE platform = ?1 ORDER BY id DESC LIMIT ?2
+         )",
+        params![platform, keep as i64],
+    )?;
+    Ok(())
+}
+
+/// Newest snapshot of a platform captured before `before` (UTC, like `captured_at`)
+pub fn latest_page_snapshot_before(
+    conn: &Connection,
+    platform: &str,
+    before: &str,
+) -> Result<Option<PageSnapshotRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, app_version, structure_hash, snapshot, captured_at
+         FROM page_snapshots WHERE platform = ?1 AND captured_at < ?2
+         ORDER BY captured_at DESC, id DESC LIMIT 1",
+    )?;
+    let mut rows = stmt.query(params![platform, before])?;
+    match rows.next()? {
+        Some(row) => Ok(Some(PageSnapshotRecord {
+            id: row.get(0)?,
+            platform: row.get(1)?,
+            app_version: row.get(2)?,
+            structure_hash: row.get(3)?,
+            snapshot: row.get(4)?,
+            captured_at: row.get(5)?,
+        })),
+        None => Ok(None),
+    }
+}
+
+/// Snapshots of a platform, newest first
+pub fn get_page_snapshot_history(
+    conn: &Connection,
+    platform: &str,
+) -> Result<Vec<PageSnapshotRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, platform, app_version, structure_hash, snapshot, captured_at
+         FROM page_snapshots WHERE platform = ?1 ORDER BY captured_at DESC, id DESC",
+    )?;
+    let snapshots = stmt
+        .query_map(params![platform], |row| {
+            Ok(PageSnapshotRecord {
+                id: row.get(0)?,
+                platform: row.get(1)?,
+                app_version: row.get(2)?,
+                structure_hash: row.get(3)?,
+                snapshot: row.get(4)?,
+                captured_at: row.get(5)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(snapshots)
 }
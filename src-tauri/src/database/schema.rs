@@ -51,7 +51,232 @@ pub fn create_tables(conn: &Connection) -> Result<()> {
             created_at TEXT NOT NULL DEFAULT (datetime('now')),
             updated_at TEXT NOT NULL DEFAULT (datetime('now'))
         );
+
+        CREATE TABLE IF NOT EXISTS app_settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS url_health_checks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT NOT NULL,
+            account_id INTEGER,
+            configured_url TEXT NOT NULL,
+            final_url TEXT,
+            status TEXT NOT NULL,
+            detail TEXT,
+            checked_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS session_lifetimes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT NOT NULL,
+            lifetime_days REAL NOT NULL,
+            observed_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS publish_timings (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_id INTEGER NOT NULL,
+            account_id INTEGER NOT NULL,
+            platform TEXT NOT NULL,
+            video_bytes INTEGER NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            recorded_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS account_review_counts (
+            account_id INTEGER PRIMARY KEY,
+            in_review INTEGER NOT NULL,
+            checked_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS account_notes (
+            account_id INTEGER PRIMARY KEY,
+            ciphertext TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS vault_config (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS vault_audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id INTEGER,
+            action TEXT NOT NULL,
+            at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS tag_sets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            tags TEXT NOT NULL DEFAULT '[]',
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS reply_rules (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT,
+            keyword TEXT NOT NULL,
+            reply TEXT NOT NULL,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS comment_replies (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_platform_id INTEGER NOT NULL,
+            comment_key TEXT NOT NULL,
+            rule_id INTEGER,
+            replied_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (task_platform_id, comment_key),
+            FOREIGN KEY (task_platform_id) REFERENCES publish_task_platforms(id)
+        );
+
+        CREATE TABLE IF NOT EXISTS kb_articles (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT,
+            error_code TEXT NOT NULL,
+            title TEXT NOT NULL,
+            url TEXT NOT NULL,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS api_tokens (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            token_hash TEXT NOT NULL UNIQUE,
+            token_prefix TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            last_used_at TEXT,
+            revoked_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS scheduled_tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            run_at TEXT NOT NULL,
+            title TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            task_id INTEGER,
+            error TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            started_at TEXT,
+            finished_at TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS strategy_stats (
+            platform TEXT NOT NULL,
+            strategy TEXT NOT NULL,
+            signal TEXT NOT NULL,
+            outcome TEXT NOT NULL,
+            count INTEGER NOT NULL DEFAULT 0,
+            unsent INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (platform, strategy, signal, outcome)
+        );
+
+        CREATE TABLE IF NOT EXISTS account_health (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            account_id INTEGER NOT NULL,
+            status TEXT NOT NULL,
+            is_logged_in INTEGER,
+            final_url TEXT,
+            detail TEXT,
+            checked_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS strategy_outcomes (
+            platform TEXT NOT NULL,
+            account_id INTEGER NOT NULL,
+            strategy TEXT NOT NULL,
+            successes INTEGER NOT NULL DEFAULT 0,
+            failures INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (platform, account_id, strategy)
+        );
+
+        CREATE TABLE IF NOT EXISTS page_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            platform TEXT NOT NULL,
+            app_version TEXT NOT NULL,
+            structure_hash TEXT NOT NULL,
+            snapshot TEXT NOT NULL,
+            captured_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_page_snapshots_platform
+            ON page_snapshots(platform, captured_at);
+
+        CREATE TABLE IF NOT EXISTS automation_logs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            task_platform_id INTEGER NOT NULL,
+            platform TEXT NOT NULL,
+            phase TEXT NOT NULL,
+            strategy TEXT,
+            result TEXT NOT NULL,
+            elapsed_ms INTEGER NOT NULL,
+            detail TEXT NOT NULL DEFAULT '',
+            recorded_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (task_platform_id) REFERENCES publish_task_platforms(id)
+        );
+        CREATE INDEX IF NOT EXISTS idx_automation_logs_task_platform
+            ON automation_logs(task_platform_id);
+        ",
+    )?;
+
+    // Columns added after the initial schema
+    add_column_if_missing(conn, "accounts", "watermark_config", "TEXT")?;
+    add_column_if_missing(conn, "publish_task_platforms", "post_url", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "last_login_at", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "last_authenticated_at", "TEXT")?;
+    add_column_if_missing(conn, "publish_tasks", "extra_media", "TEXT")?;
+    add_column_if_missing(conn, "publish_task_platforms", "custom_fields", "TEXT")?;
+    add_column_if_missing(conn, "publish_task_platforms", "preview_path", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "publish_defaults", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "publish_windows", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "chrome_flags", "TEXT")?;
+    add_column_if_missing(conn, "accounts", "variables", "TEXT")?;
+    add_column_if_missing(conn, "publish_task_platforms", "duration_ms", "INTEGER")?;
+    add_column_if_missing(conn, "publish_task_platforms", "bytes_sent", "INTEGER")?;
+    add_column_if_missing(conn, "publish_task_platforms", "bytes_received", "INTEGER")?;
+    add_column_if_missing(conn, "accounts", "title_variation", "TEXT")?;
+    add_column_if_missing(conn, "publish_task_platforms", "applied_title", "TEXT")?;
+    add_column_if_missing(conn, "publish_timings", "signal_latency_ms", "INTEGER")?;
+    add_column_if_missing(conn, "publish_task_platforms", "error_code", "TEXT")?;
+    add_column_if_missing(conn, "templates", "platform_overrides", "TEXT")?;
+    add_column_if_missing(conn, "publish_tasks", "schedule_mode", "TEXT")?;
+    add_column_if_missing(conn, "publish_tasks", "post_type", "TEXT")?;
+    add_column_if_missing(conn, "publish_tasks", "images", "TEXT")?;
+    add_column_if_missing(conn, "publish_tasks", "manual_confirm", "INTEGER")?;
+
+    // The vault salt and check value used to live in app_settings, where the
+    // settings commands could read and overwrite them
+    conn.execute_batch(
+        "
+        INSERT OR IGNORE INTO vault_config (key, value)
+            SELECT key, value FROM app_settings WHERE key IN ('vault_salt', 'vault_check');
+        DELETE FROM app_settings WHERE key IN ('vault_salt', 'vault_check');
         ",
     )?;
     Ok(())
 }
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|name| name.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {};",
+            table, column, decl
+        ))?;
+    }
+    Ok(())
+}
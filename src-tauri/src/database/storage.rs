@@ -0,0 +1,244 @@
+use super::queries::{self, Account, AccountHealth, PublishTask, PublishTemplate, TagSet};
+use super::Database;
+use anyhow::{anyhow, Result};
+use log::warn;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::MutexGuard;
+
+/// File next to the database that selects the storage backend. It cannot live
+/// in `app_settings`, since the settings table itself is part of the backend.
+const STORAGE_CONFIG_FILE: &str = "storage.json";
+
+/// Persistence operations of the account, template, tag set, task list and
+/// settings commands, which take it as `State<'_, Arc<dyn Storage>>`. SQLite
+/// (`Database`) is the default implementation; another backend implements the
+/// same trait so those commands do not change when the backend does.
+///
+/// The publish pipeline, login and health checks, diagnostics and the other
+/// background jobs still take `Database` and run several queries under one
+/// connection lock. They stay on SQLite until they are moved here too.
+pub trait Storage: Send + Sync {
+    // Accounts
+    fn insert_account(&self, platform: &str, display_name: &str, profile_dir: &str) -> Result<i64>;
+    /// `(display_name, chrome_profile_dir)` pairs of one platform; all or none are created
+    fn insert_accounts(&self, platform: &str, accounts: &[(String, String)]) -> Result<Vec<i64>>;
+    fn get_all_accounts(&self) -> Result<Vec<Account>>;
+    fn update_account_display_name(&self, id: i64, display_name: &str) -> Result<()>;
+    fn delete_account(&self, id: i64) -> Result<String>;
+    fn get_account_health(&self, account_id: Option<i64>, limit: i64)
+        -> Result<Vec<AccountHealth>>;
+
+    // Per-account settings, stored as JSON; `None` clears them
+    fn get_account_watermark(&self, id: i64) -> Result<Option<String>>;
+    fn update_account_watermark(&self, id: i64, config: Option<&str>) -> Result<()>;
+    fn get_account_publish_defaults(&self, id: i64) -> Result<Option<String>>;
+    fn update_account_publish_defaults(&self, id: i64, defaults: Option<&str>) -> Result<()>;
+    fn get_account_variables(&self, id: i64) -> Result<Option<String>>;
+    fn update_account_variables(&self, id: i64, variables: Option<&str>) -> Result<()>;
+    fn get_account_title_variation(&self, id: i64) -> Result<Option<String>>;
+    fn update_account_title_variation(&self, id: i64, variation: Option<&str>) -> Result<()>;
+    fn get_account_publish_windows(&self, id: i64) -> Result<Option<String>>;
+    fn update_account_publish_windows(&self, id: i64, windows: Option<&str>) -> Result<()>;
+    fn update_account_chrome_flags(&self, id: i64, flags: Option<&str>) -> Result<()>;
+
+    // Templates and tag sets
+    fn get_templates(&self) -> Result<Vec<PublishTemplate>>;
+    fn upsert_template(
+        &self,
+        name: &str,
+        title: &str,
+        description: &str,
+        tags: &str,
+        platform_overrides: &str,
+    ) -> Result<i64>;
+    fn delete_template(&self, id: i64) -> Result<()>;
+    fn get_tag_sets(&self) -> Result<Vec<TagSet>>;
+    fn insert_tag_set(&self, name: &str, tags: &[String]) -> Result<i64>;
+    fn update_tag_set(&self, id: i64, name: &str, tags: &[String]) -> Result<()>;
+    fn delete_tag_set(&self, id: i64) -> Result<()>;
+
+    // Publish tasks
+    fn get_all_tasks(&self) -> Result<Vec<PublishTask>>;
+
+    // Settings
+    fn set_setting(&self, key: &str, value: &str) -> Result<()>;
+    fn get_all_settings(&self) -> Result<Vec<(String, String)>>;
+}
+
+impl Database {
+    fn lock(&self) -> Result<MutexGuard<'_, rusqlite::Connection>> {
+        self.conn.lock().map_err(|e| anyhow!(e.to_string()))
+    }
+
+    /// The read-only connection when there is one, otherwise the writer
+    fn read_lock(&self) -> Result<MutexGuard<'_, rusqlite::Connection>> {
+        match &self.reader {
+            Some(reader) => reader.lock().map_err(|e| anyhow!(e.to_string())),
+            None => self.lock(),
+        }
+    }
+}
+
+impl Storage for Database {
+    fn insert_account(&self, platform: &str, display_name: &str, profile_dir: &str) -> Result<i64> {
+        queries::insert_account(&*self.lock()?, platform, display_name, profile_dir)
+    }
+
+    fn insert_accounts(&self, platform: &str, accounts: &[(String, String)]) -> Result<Vec<i64>> {
+        queries::insert_accounts(&*self.lock()?, platform, accounts)
+    }
+
+    fn get_all_accounts(&self) -> Result<Vec<Account>> {
+        queries::get_all_accounts(&*self.read_lock()?)
+    }
+
+    fn update_account_display_name(&self, id: i64, display_name: &str) -> Result<()> {
+        queries::update_account_display_name(&*self.lock()?, id, display_name)
+    }
+
+    fn delete_account(&self, id: i64) -> Result<String> {
+        queries::delete_account(&*self.lock()?, id)
+    }
+
+    fn get_account_health(
+        &self,
+        account_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<AccountHealth>> {
+        queries::get_account_health(&*self.read_lock()?, account_id, limit)
+    }
+
+    fn get_account_watermark(&self, id: i64) -> Result<Option<String>> {
+        queries::get_account_watermark(&*self.read_lock()?, id)
+    }
+
+    fn update_account_watermark(&self, id: i64, config: Option<&str>) -> Result<()> {
+        queries::update_account_watermark(&*self.lock()?, id, config)
+    }
+
+    fn get_account_publish_defaults(&self, id: i64) -> Result<Option<String>> {
+        queries::get_account_publish_defaults(&*self.read_lock()?, id)
+    }
+
+    fn update_account_publish_defaults(&self, id: i64, defaults: Option<&str>) -> Result<()> {
+        queries::update_account_publish_defaults(&*self.lock()?, id, defaults)
+    }
+
+    fn get_account_variables(&self, id: i64) -> Result<Option<String>> {
+        queries::get_account_variables(&*self.read_lock()?, id)
+    }
+
+    fn update_account_variables(&self, id: i64, variables: Option<&str>) -> Result<()> {
+        queries::update_account_variables(&*self.lock()?, id, variables)
+    }
+
+    fn get_account_title_variation(&self, id: i64) -> Result<Option<String>> {
+        queries::get_account_title_variation(&*self.read_lock()?, id)
+    }
+
+    fn update_account_title_variation(&self, id: i64, variation: Option<&str>) -> Result<()> {
+        queries::update_account_title_variation(&*self.lock()?, id, variation)
+    }
+
+    fn get_account_publish_windows(&self, id: i64) -> Result<Option<String>> {
+        queries::get_account_publish_windows(&*self.read_lock()?, id)
+    }
+
+    fn update_account_publish_windows(&self, id: i64, windows: Option<&str>) -> Result<()> {
+        queries::update_account_publish_windows(&*self.lock()?, id, windows)
+    }
+
+    fn update_account_chrome_flags(&self, id: i64, flags: Option<&str>) -> Result<()> {
+        queries::update_account_chrome_flags(&*self.lock()?, id, flags)
+    }
+
+    fn get_templates(&self) -> Result<Vec<PublishTemplate>> {
+        queries::get_templates(&*self.read_lock()?)
+    }
+
+    fn upsert_template(
+        &self,
+        name: &str,
+        title: &str,
+        description: &str,
+        tags: &str,
+        platform_overrides: &str,
+    ) -> Result<i64> {
+        queries::upsert_template(
+            &*self.lock()?,
+            name,
+            title,
+            description,
+            tags,
+            platform_overrides,
+        )
+    }
+
+    fn delete_template(&self, id: i64) -> Result<()> {
+        queries::delete_template(&*self.lock()?, id)
+    }
+
+    fn get_tag_sets(&self) -> Result<Vec<TagSet>> {
+        queries::get_tag_sets(&*self.read_lock()?)
+    }
+
+    fn insert_tag_set(&self, name: &str, tags: &[String]) -> Result<i64> {
+        queries::insert_tag_set(&*self.lock()?, name, tags)
+    }
+
+    fn update_tag_set(&self, id: i64, name: &str, tags: &[String]) -> Result<()> {
+        queries::update_tag_set(&*self.lock()?, id, name, tags)
+    }
+
+    fn delete_tag_set(&self, id: i64) -> Result<()> {
+        queries::delete_tag_set(&*self.lock()?, id)
+    }
+
+    fn get_all_tasks(&self) -> Result<Vec<PublishTask>> {
+        queries::get_all_tasks(&*self.read_lock()?)
+    }
+
+    fn set_setting(&self, key: &str, value: &str) -> Result<()> {
+        queries::set_setting(&*self.lock()?, key, value)
+    }
+
+    fn get_all_settings(&self) -> Result<Vec<(String, String)>> {
+        queries::get_all_settings(&*self.lock()?)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StorageConfig {
+    /// `sqlite` (default) or a `postgres://` URL
+    #[serde(default)]
+    database_url: Option<String>,
+}
+
+/// Report which backend `storage.json` asks for. Postgres is not compiled into
+/// this build yet, so any non-SQLite request falls back to the local database.
+pub fn check_backend(app_data_dir: &Path) {
+    let path = app_data_dir.join(STORAGE_CONFIG_FILE);
+    let config = match std::fs::read(&path) {
+        Ok(body) => match serde_json::from_slice::<StorageConfig>(&body) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("[Storage] invalid {}: {}; using SQLite", path.display(), e);
+                return;
+            }
+        },
+        Err(_) => return,
+    };
+    match config.database_url.as_deref().map(str::trim) {
+        None | Some("") | Some("sqlite") => {}
+        Some(url) if url.starts_with("postgres://") || url.starts_with("postgresql://") => warn!(
+            "[Storage] {} requests a Postgres backend, which is not available in this build; using SQLite",
+            path.display()
+        ),
+        Some(url) => warn!(
+            "[Storage] unsupported database_url in {}: {}; using SQLite",
+            path.display(),
+            url
+        ),
+    }
+}
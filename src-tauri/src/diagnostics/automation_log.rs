@@ -0,0 +1,70 @@
+use crate::database::queries::{self, AutomationStep};
+use crate::database::Database;
+use log::warn;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Writes one account's automation steps (upload strategy attempts, failures
+/// with their page fingerprints) to `automation_logs` under the run's task
+/// platform row, so the troubleshooting panel can show them after the run.
+#[derive(Clone)]
+pub struct AutomationLogSink {
+    app: AppHandle,
+    task_platform_id: i64,
+    platform: String,
+}
+
+impl std::fmt::Debug for AutomationLogSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AutomationLogSink")
+            .field("task_platform_id", &self.task_platform_id)
+            .field("platform", &self.platform)
+            .finish()
+    }
+}
+
+impl AutomationLogSink {
+    pub fn new(app: &AppHandle, task_platform_id: i64, platform: &str) -> Self {
+        Self {
+            app: app.clone(),
+            task_platform_id,
+            platform: platform.to_string(),
+        }
+    }
+
+    /// Best effort: a failed write only logs a warning
+    pub fn record(
+        &self,
+        phase: &str,
+        strategy: Option<&str>,
+        result: &str,
+        elapsed: Duration,
+        detail: &str,
+    ) {
+        let db = self.app.state::<Database>();
+        let written = db
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))
+            .and_then(|conn| {
+                queries::insert_automation_log(
+                    &conn,
+                    self.task_platform_id,
+                    &AutomationStep {
+                        platform: &self.platform,
+                        phase,
+                        strategy,
+                        result,
+                        elapsed_ms: elapsed.as_millis() as i64,
+                        detail,
+                    },
+                )
+            });
+        if let Err(e) = written {
+            warn!(
+                "[自动化日志] task_platform={} phase={} 写入失败：{}",
+                self.task_platform_id, phase, e
+            );
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use super::phase_log::{self, PhaseRecord};
+use crate::database::queries;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub const SETTING_UPLOAD_ENABLED: &str = "crash_upload_enabled";
+pub const SETTING_UPLOAD_ENDPOINT: &str = "crash_upload_endpoint";
+
+const UPLOADED_SUFFIX: &str = ".uploaded";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub app_version: String,
+    pub created_at: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub recent_phases: Vec<PhaseRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    pub file_name: String,
+    pub path: String,
+    pub created_at: String,
+    pub message: String,
+    pub uploaded: bool,
+}
+
+/// Install a panic hook that writes a crash report into `crash_dir` before
+/// handing over to the previous (default) hook.
+pub fn install_panic_hook(crash_dir: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        let message = if let Some(s) = panic_info.payload().downcast_ref::<&str>() {
+            (*s).to_string()
+        } else if let Some(s) = panic_info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+        let report = CrashReport {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("unnamed")
+                .to_string(),
+            message,
+            location: panic_info
+                .location()
+                .map(|loc| format!("{}:{}:{}", loc.file(), loc.line(), loc.column())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            recent_phases: phase_log::snapshot(),
+        };
+        match write_report(&crash_dir, &report) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        previous(panic_info);
+    }));
+}
+
+fn write_report(crash_dir: &Path, report: &CrashReport) -> Result<PathBuf> {
+    std::fs::create_dir_all(crash_dir)?;
+    let file_name = format!(
+        "crash-{}-{}.json",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    );
+    let path = crash_dir.join(file_name);
+    std::fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    Ok(path)
+}
+
+/// List crash reports, newest first.
+pub fn list_reports(crash_dir: &Path) -> Result<Vec<CrashReportSummary>> {
+    let mut reports = Vec::new();
+    let entries = match std::fs::read_dir(crash_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(reports),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let (created_at, message) = match read_report(&path) {
+            Ok(report) => (report.created_at, report.message),
+            Err(_) => (String::new(), "unreadable crash report".to_string()),
+        };
+        reports.push(CrashReportSummary {
+            uploaded: uploaded_marker(&path).exists(),
+            path: path.to_string_lossy().to_string(),
+            file_name,
+            created_at,
+            message,
+        });
+    }
+    reports.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(reports)
+}
+
+fn read_report(path: &Path) -> Result<CrashReport> {
+    let body = std::fs::read(path)?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+fn uploaded_marker(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(UPLOADED_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Upload endpoint, only when the user explicitly opted in and configured one.
+pub fn upload_endpoint(conn: &Connection) -> Result<Option<String>> {
+    if !queries::get_setting_bool(conn, SETTING_UPLOAD_ENABLED, false)? {
+        return Ok(None);
+    }
+    Ok(queries::get_setting(conn, SETTING_UPLOAD_ENDPOINT)?
+        .map(|endpoint| endpoint.trim().to_string())
+        .filter(|endpoint| !endpoint.is_empty()))
+}
+
+/// Upload every report that has not been uploaded yet. Returns the number uploaded.
+pub async fn upload_pending_reports(crash_dir: &Path, endpoint: &str) -> Result<usize> {
+    let client = reqwest::Client::new();
+    let mut uploaded = 0;
+    for summary in list_reports(crash_dir)? {
+        if summary.uploaded {
+            continue;
+        }
+        let path = PathBuf::from(&summary.path);
+        let report = match read_report(&path) {
+            Ok(report) => report,
+            Err(e) => {
+                warn!("[Crash] skip unreadable report {}: {}", summary.file_name, e);
+                continue;
+            }
+        };
+        let resp = client
+            .post(endpoint)
+            .json(&report)
+            .send()
+            .await
+            .with_context(|| format!("上传崩溃报告 {} 失败", summary.file_name))?;
+        if !resp.status().is_success() {
+            warn!(
+                "[Crash] upload rejected report={} status={}",
+                summary.file_name,
+                resp.status()
+            );
+            continue;
+        }
+        std::fs::write(uploaded_marker(&path), chrono::Utc::now().to_rfc3339())?;
+        uploaded += 1;
+        info!("[Crash] uploaded report {}", summary.file_name);
+    }
+    Ok(uploaded)
+}
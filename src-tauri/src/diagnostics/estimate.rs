@@ -0,0 +1,137 @@
+use super::network;
+use crate::database::queries;
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Rough automation times used until enough runs have been recorded
+const DEFAULT_AUTOMATION_SECS: &[(&str, f64)] = &[
+    ("douyin", 90.0),
+    ("xiaohongshu", 60.0),
+    ("bilibili", 75.0),
+    ("wechat", 120.0),
+    ("youtube", 90.0),
+    ("kuaishou", 75.0),
+    ("zhihu", 75.0),
+    ("tiktok", 90.0),
+    ("weibo", 75.0),
+    ("instagram", 90.0),
+];
+const FALLBACK_AUTOMATION_SECS: f64 = 90.0;
+/// Recorded runs needed before they replace the default
+const MIN_OBSERVATIONS: usize = 3;
+const OBSERVATION_WINDOW: i64 = 20;
+/// Runs within this size ratio of the video count as similar
+const SIMILAR_SIZE_RATIO: f64 = 2.0;
+/// Uplink assumed for the transfer time when none has been measured
+const ASSUMED_UPLINK_MBPS: f64 = 20.0;
+
+/// Outcome whose run times feed the estimate
+pub const TIMED_STATUS: &str = "automated";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformEstimate {
+    /// None when estimating by platform only
+    pub account_id: Option<i64>,
+    pub platform: String,
+    /// Driving the upload page until the upload starts and fields are filled
+    pub automation_secs: f64,
+    /// `observed_similar_size`, `observed` (median of recent runs) or `default`
+    pub automation_source: &'static str,
+    pub samples: usize,
+    /// Transferring the file after automation hands over to the page
+    pub upload_secs: f64,
+    pub total_secs: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishEstimate {
+    pub video_bytes: u64,
+    pub uplink_mbps: f64,
+    /// `measured` (cached network probe) or `assumed`
+    pub uplink_source: &'static str,
+    pub platforms: Vec<PlatformEstimate>,
+    /// Accounts are automated one after another while earlier uploads keep
+    /// going in their own windows, sharing the uplink
+    pub total_secs: f64,
+}
+
+fn typical_automation_secs(
+    conn: &Connection,
+    platform: &str,
+    video_bytes: u64,
+) -> Result<(f64, &'static str, usize)> {
+    let observed =
+        queries::get_recent_publish_timings(conn, platform, TIMED_STATUS, OBSERVATION_WINDOW)?;
+    // Runs with a similar file size are the better predictor once there are enough
+    let similar = observed
+        .iter()
+        .filter(|t| {
+            let ratio = t.video_bytes.max(1) as f64 / video_bytes.max(1) as f64;
+            (1.0 / SIMILAR_SIZE_RATIO..=SIMILAR_SIZE_RATIO).contains(&ratio)
+        })
+        .collect::<Vec<_>>();
+    let (basis, source) = if similar.len() >= MIN_OBSERVATIONS {
+        (similar, "observed_similar_size")
+    } else {
+        (observed.iter().collect(), "observed")
+    };
+    if basis.len() >= MIN_OBSERVATIONS {
+        let mut secs = basis
+            .iter()
+            .map(|t| t.duration_ms as f64 / 1000.0)
+            .collect::<Vec<_>>();
+        secs.sort_by(|a, b| a.total_cmp(b));
+        return Ok((secs[secs.len() / 2], source, basis.len()));
+    }
+    let default = DEFAULT_AUTOMATION_SECS
+        .iter()
+        .find(|(id, _)| *id == platform)
+        .map(|(_, secs)| *secs)
+        .unwrap_or(FALLBACK_AUTOMATION_SECS);
+    Ok((default, "default", observed.len()))
+}
+
+fn round1(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+/// Estimate publish time for `targets` (account id, platform) and a video of
+/// `video_bytes`
+pub fn estimate(
+    conn: &Connection,
+    video_bytes: u64,
+    targets: &[(Option<i64>, String)],
+) -> Result<PublishEstimate> {
+    let (uplink_mbps, uplink_source) = match network::cached_probe() {
+        Some(probe) if probe.uplink_mbps > 0.0 => (probe.uplink_mbps, "measured"),
+        _ => (ASSUMED_UPLINK_MBPS, "assumed"),
+    };
+    let upload_secs = video_bytes as f64 * 8.0 / (uplink_mbps * 1_000_000.0);
+
+    let mut platforms = Vec::new();
+    for (account_id, platform) in targets {
+        let (automation_secs, automation_source, samples) =
+            typical_automation_secs(conn, platform, video_bytes)?;
+        platforms.push(PlatformEstimate {
+            account_id: *account_id,
+            platform: platform.clone(),
+            automation_secs: round1(automation_secs),
+            automation_source,
+            samples,
+            upload_secs: round1(upload_secs),
+            total_secs: round1(automation_secs + upload_secs),
+        });
+    }
+
+    let automation_total: f64 = platforms.iter().map(|p| p.automation_secs).sum();
+    let first_automation = platforms.first().map(|p| p.automation_secs).unwrap_or(0.0);
+    let transfer_total = upload_secs * platforms.len() as f64;
+    Ok(PublishEstimate {
+        video_bytes,
+        uplink_mbps,
+        uplink_source,
+        total_secs: round1(automation_total.max(first_automation + transfer_total)),
+        platforms,
+    })
+}
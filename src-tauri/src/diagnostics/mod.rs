@@ -0,0 +1,10 @@
+pub mod automation_log;
+pub mod crash;
+pub mod estimate;
+pub mod network;
+pub mod observer;
+pub mod phase_log;
+pub mod progress;
+pub mod snapshot;
+pub mod telemetry;
+pub mod timeout_tuning;
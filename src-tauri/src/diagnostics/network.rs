@@ -0,0 +1,141 @@
+use crate::database::queries;
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// `auto`, `normal` or `slow`
+pub const SETTING_NETWORK_MODE: &str = "network_mode";
+/// Multiplier applied to signal and guard timeouts in slow-network mode
+pub const SETTING_SLOW_NETWORK_FACTOR: &str = "slow_network_timeout_factor";
+
+pub const DEFAULT_SLOW_NETWORK_FACTOR: u64 = 3;
+const MAX_SLOW_NETWORK_FACTOR: u64 = 10;
+
+/// Uplinks measured below this are treated as slow in `auto` mode
+pub const SLOW_UPLINK_MBPS: f64 = 10.0;
+
+/// Accepts and discards POST bodies; used only to time a small upload
+const PROBE_ENDPOINT: &str = "https://speed.cloudflare.com/__up";
+const PROBE_BYTES: usize = 1024 * 1024;
+const PROBE_TIMEOUT: Duration = Duration::from_secs(15);
+/// A measurement is reused for this long before probing again
+const PROBE_CACHE_TTL: Duration = Duration::from_secs(30 * 60);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMode {
+    /// Probe the uplink before a publish run and switch to slow mode when needed
+    #[default]
+    Auto,
+    Normal,
+    Slow,
+}
+
+impl NetworkMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "normal" => Some(Self::Normal),
+            "slow" => Some(Self::Slow),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkProbe {
+    pub uplink_mbps: f64,
+    pub slow: bool,
+    pub measured_at: String,
+}
+
+/// Network mode and timeout factor as configured in settings
+pub struct NetworkSettings {
+    pub mode: NetworkMode,
+    pub slow_factor: u64,
+}
+
+impl NetworkSettings {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        let mode = queries::get_setting(conn, SETTING_NETWORK_MODE)?
+            .as_deref()
+            .and_then(NetworkMode::parse)
+            .unwrap_or_default();
+        let slow_factor = queries::get_setting(conn, SETTING_SLOW_NETWORK_FACTOR)?
+            .and_then(|raw| raw.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_SLOW_NETWORK_FACTOR)
+            .clamp(1, MAX_SLOW_NETWORK_FACTOR);
+        Ok(Self { mode, slow_factor })
+    }
+
+    /// Whether this run should use slow-network timeouts. In `auto` mode a
+    /// failed probe counts as a normal network, so an offline probe endpoint
+    /// never slows every run down.
+    pub async fn is_slow(&self) -> bool {
+        match self.mode {
+            NetworkMode::Normal => false,
+            NetworkMode::Slow => true,
+            NetworkMode::Auto => match probe_uplink(false).await {
+                Ok(probe) => probe.slow,
+                Err(e) => {
+                    warn!("[网络探测] 上行带宽探测失败，按普通网络处理：{}", e);
+                    false
+                }
+            },
+        }
+    }
+}
+
+static LAST_PROBE: LazyLock<Mutex<Option<(Instant, NetworkProbe)>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// The last measurement if it is still fresh, without probing
+pub fn cached_probe() -> Option<NetworkProbe> {
+    let last = LAST_PROBE.lock().ok()?;
+    last.as_ref()
+        .filter(|(at, _)| at.elapsed() < PROBE_CACHE_TTL)
+        .map(|(_, probe)| probe.clone())
+}
+
+/// Time a 1 MB upload to estimate the uplink. Results are cached for half an
+/// hour unless `force` is set.
+pub async fn probe_uplink(force: bool) -> Result<NetworkProbe> {
+    if !force {
+        if let Some(probe) = cached_probe() {
+            return Ok(probe);
+        }
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .context("创建探测客户端失败")?;
+    let start = Instant::now();
+    let resp = client
+        .post(PROBE_ENDPOINT)
+        .body(vec![0u8; PROBE_BYTES])
+        .send()
+        .await
+        .context("上行带宽探测请求失败")?;
+    if !resp.status().is_success() {
+        bail!("上行带宽探测返回 HTTP {}", resp.status());
+    }
+    let secs = start.elapsed().as_secs_f64().max(0.001);
+    let uplink_mbps = (PROBE_BYTES as f64 * 8.0 / 1_000_000.0) / secs;
+    let probe = NetworkProbe {
+        uplink_mbps: (uplink_mbps * 10.0).round() / 10.0,
+        slow: uplink_mbps < SLOW_UPLINK_MBPS,
+        measured_at: chrono::Utc::now().to_rfc3339(),
+    };
+    info!(
+        "[网络探测] 上行约 {:.1} Mbps，slow={}",
+        probe.uplink_mbps, probe.slow
+    );
+    if let Ok(mut last) = LAST_PROBE.lock() {
+        *last = Some((Instant::now(), probe.clone()));
+    }
+    Ok(probe)
+}
@@ -0,0 +1,288 @@
+use super::progress;
+use crate::database::{queries, Database};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use log::{info, warn};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::OnceCell;
+
+/// `true` listens on all interfaces so a helper on the LAN can connect;
+/// read when the server first starts. Peers outside the private address
+/// ranges are still refused.
+pub const SETTING_OBSERVER_ALLOW_LAN: &str = "observer_allow_lan";
+
+const TOKEN_PREFIX: &str = "obs_";
+const TOKEN_BYTES: usize = 24;
+const DEFAULT_MINUTES: u64 = 60;
+const MAX_MINUTES: u64 = 240;
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// A read-only view on one publish task, handed to a helper as a URL
+#[derive(Debug, Clone, Serialize)]
+pub struct ObserverSession {
+    pub task_id: i64,
+    pub token: String,
+    /// Base URL; every request needs `?token=` or an `Authorization: Bearer` header
+    pub url: String,
+    pub expires_at: String,
+}
+
+struct Grant {
+    task_id: i64,
+    expires: Instant,
+}
+
+static GRANTS: LazyLock<Mutex<HashMap<String, Grant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static SERVER: OnceCell<SocketAddr> = OnceCell::const_new();
+
+/// Open an observer session for `task_id`, starting the server on first use.
+/// The token only grants GET access to that task and expires on its own.
+pub async fn start(app: &AppHandle, task_id: i64, minutes: Option<u64>) -> Result<ObserverSession> {
+    let addr = *SERVER
+        .get_or_try_init(|| async {
+            let allow_lan = {
+                let db = app.state::<Database>();
+                let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+                queries::get_setting_bool(&conn, SETTING_OBSERVER_ALLOW_LAN, false)?
+            };
+            let bind = if allow_lan {
+                "0.0.0.0:0"
+            } else {
+                "127.0.0.1:0"
+            };
+            let listener = TcpListener::bind(bind)
+                .await
+                .context("启动观察模式服务失败")?;
+            let addr = listener.local_addr()?;
+            crate::tasks::global().spawn("observer:server", serve(listener, app.clone()));
+            info!("[观察模式] 只读服务已启动：{}", addr);
+            Ok::<_, anyhow::Error>(addr)
+        })
+        .await?;
+
+    let minutes = minutes.unwrap_or(DEFAULT_MINUTES).clamp(1, MAX_MINUTES);
+    let mut secret = [0u8; TOKEN_BYTES];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| anyhow!("生成观察令牌失败"))?;
+    let token = format!("{}{}", TOKEN_PREFIX, BASE64_URL.encode(secret));
+    let ttl = Duration::from_secs(minutes * 60);
+    let expires_at = chrono::Local::now() + chrono::Duration::minutes(minutes as i64);
+    let mut grants = GRANTS.lock().map_err(|e| anyhow!(e.to_string()))?;
+    grants.retain(|_, grant| grant.expires > Instant::now());
+    grants.insert(
+        token.clone(),
+        Grant {
+            task_id,
+            expires: Instant::now() + ttl,
+        },
+    );
+    // A wildcard bind is reached through the machine's LAN address
+    let host = if addr.ip().is_unspecified() {
+        lan_address().unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    } else {
+        addr.ip()
+    };
+    Ok(ObserverSession {
+        task_id,
+        token,
+        url: format!("http://{}", SocketAddr::new(host, addr.port())),
+        expires_at: expires_at.to_rfc3339(),
+    })
+}
+
+/// Revoke a token before it expires; false if it was unknown or already expired
+pub fn stop(token: &str) -> bool {
+    GRANTS
+        .lock()
+        .map(|mut grants| grants.remove(token).is_some())
+        .unwrap_or(false)
+}
+
+fn task_for(token: &str) -> Option<i64> {
+    let mut grants = GRANTS.lock().ok()?;
+    match grants.get(token) {
+        Some(grant) if grant.expires > Instant::now() => Some(grant.task_id),
+        Some(_) => {
+            grants.remove(token);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Address of the interface that routes outward. Connecting a UDP socket sends
+/// nothing; it only picks the local address.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    Some(socket.local_addr().ok()?.ip())
+}
+
+/// Loopback, or a private / link-local LAN address
+fn is_local_peer(ip: IpAddr) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6
+            .to_ipv4_mapped()
+            .map(IpAddr::V4)
+            .unwrap_or(IpAddr::V6(v6)),
+        ip => ip,
+    };
+    match ip {
+        IpAddr::V4(v4) => v4.is_loopback() || v4.is_private() || v4.is_link_local(),
+        IpAddr::V6(v6) => v6.is_loopback(),
+    }
+}
+
+async fn serve(listener: TcpListener, app: AppHandle) {
+    loop {
+        match listener.accept().await {
+            Ok((_, peer)) if !is_local_peer(peer.ip()) => {
+                warn!("[观察模式] 拒绝来自非局域网地址的连接：{}", peer);
+            }
+            Ok((stream, _)) => {
+                let app = app.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = respond(stream, &app).await {
+                        warn!("[观察模式] 处理请求失败：{}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("[观察模式] 接受连接失败：{}", e),
+        }
+    }
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn text(status: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn json(value: &impl Serialize) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "application/json",
+            body: serde_json::to_vec(value).unwrap_or_default(),
+        }
+    }
+}
+
+/// Only GET is served, so a token can never start, cancel or change anything
+async fn respond(mut stream: TcpStream, app: &AppHandle) -> Result<()> {
+    let mut buffer = vec![0u8; MAX_REQUEST_BYTES];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let mut request_line = request.lines().next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("");
+    let target = request_line.next().unwrap_or("/");
+    let (route, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = parse_query(query);
+    let token = query.get("token").cloned().or_else(|| {
+        request.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            name.eq_ignore_ascii_case("authorization").then(|| {
+                value
+                    .trim()
+                    .trim_start_matches("Bearer ")
+                    .trim()
+                    .to_string()
+            })
+        })
+    });
+
+    let response = if method != "GET" {
+        Response::text("405 Method Not Allowed", "observer sessions are read-only")
+    } else {
+        match token.as_deref().and_then(task_for) {
+            None => Response::text("401 Unauthorized", "invalid or expired observer token"),
+            Some(task_id) => route_request(app, task_id, route, &query)
+                .unwrap_or_else(|e| Response::text("404 Not Found", &e.to_string())),
+        }
+    };
+
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&response.body).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+fn route_request(
+    app: &AppHandle,
+    task_id: i64,
+    route: &str,
+    query: &HashMap<String, String>,
+) -> Result<Response> {
+    match route {
+        "/progress" => {
+            let progress = progress::snapshot(task_id)
+                .ok_or_else(|| anyhow!("task {} has no live progress", task_id))?;
+            Ok(Response::json(&progress))
+        }
+        "/logs" => {
+            let db = app.state::<Database>();
+            let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+            let logs = queries::get_automation_logs(&conn, task_id)?;
+            Ok(Response::json(&logs))
+        }
+        "/screenshots" => {
+            let db = app.state::<Database>();
+            let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+            Ok(Response::json(&queries::get_task_previews(&conn, task_id)?))
+        }
+        "/screenshot" => {
+            let account_id = query
+                .get("account_id")
+                .and_then(|raw| raw.parse::<i64>().ok())
+                .ok_or_else(|| anyhow!("account_id is required"))?;
+            let path = {
+                let db = app.state::<Database>();
+                let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+                queries::get_task_previews(&conn, task_id)?
+                    .into_iter()
+                    .find(|preview| preview.account_id == account_id)
+                    .map(|preview| preview.preview_path)
+                    .ok_or_else(|| anyhow!("no screenshot for account {}", account_id))?
+            };
+            Ok(Response {
+                status: "200 OK",
+                content_type: "image/png",
+                body: std::fs::read(&path)?,
+            })
+        }
+        _ => bail!("unknown route {}", route),
+    }
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{LazyLock, Mutex};
+
+const MAX_RECORDS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseRecord {
+    pub at: String,
+    pub platform: String,
+    pub phase: String,
+    pub detail: String,
+}
+
+static RECENT_PHASES: LazyLock<Mutex<VecDeque<PhaseRecord>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::with_capacity(MAX_RECORDS)));
+
+/// Remember an automation phase transition so crash reports can show what the
+/// app was doing right before it went down.
+pub fn record(platform: &str, phase: &str, detail: &str) {
+    let Ok(mut records) = RECENT_PHASES.lock() else {
+        return;
+    };
+    if records.len() >= MAX_RECORDS {
+        records.pop_front();
+    }
+    records.push_back(PhaseRecord {
+        at: chrono::Utc::now().to_rfc3339(),
+        platform: platform.to_string(),
+        phase: phase.to_string(),
+        detail: detail.chars().take(300).collect(),
+    });
+}
+
+/// Snapshot of the most recent phases, oldest first.
+///
+/// Uses `try_lock` because this runs inside the panic hook, where the panicking
+/// thread may already hold the lock.
+pub fn snapshot() -> Vec<PhaseRecord> {
+    match RECENT_PHASES.try_lock() {
+        Ok(records) => records.iter().cloned().collect(),
+        Err(_) => Vec::new(),
+    }
+}
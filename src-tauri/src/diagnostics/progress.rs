@@ -0,0 +1,212 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Finished tasks kept around so a frontend that reloads right after a publish
+/// can still read the final state.
+const MAX_TASKS: usize = 20;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_RUNNING: &str = "running";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountProgress {
+    pub account_id: i64,
+    pub platform: String,
+    /// `pending`, `running`, or the final platform task status
+    pub status: String,
+    pub phase: String,
+    /// Upload strategy currently being tried, e.g. `A:file_chooser`
+    pub strategy: Option<String>,
+    /// Upload strategies tried in this run and whether each started the upload
+    pub strategy_attempts: Vec<(String, bool)>,
+    pub last_signal: Option<String>,
+    /// From the upload action that got the signal until the signal showed
+    pub signal_latency_ms: Option<u64>,
+    pub elapsed_ms: u64,
+    #[serde(skip)]
+    started: Option<Instant>,
+    #[serde(skip)]
+    ended: Option<Instant>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskProgress {
+    pub task_id: i64,
+    pub started_at: String,
+    pub elapsed_ms: u64,
+    pub finished: bool,
+    pub accounts: Vec<AccountProgress>,
+    #[serde(skip)]
+    started: Option<Instant>,
+    #[serde(skip)]
+    ended: Option<Instant>,
+}
+
+static TASKS: LazyLock<Mutex<BTreeMap<i64, TaskProgress>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+fn elapsed_ms(started: Option<Instant>, ended: Option<Instant>) -> u64 {
+    match (started, ended) {
+        (Some(started), Some(ended)) => ended.duration_since(started).as_millis() as u64,
+        (Some(started), None) => started.elapsed().as_millis() as u64,
+        _ => 0,
+    }
+}
+
+fn with_account(task_id: i64, account_id: i64, update: impl FnOnce(&mut AccountProgress)) {
+    let Ok(mut tasks) = TASKS.lock() else {
+        return;
+    };
+    if let Some(account) = tasks.get_mut(&task_id).and_then(|task| {
+        task.accounts
+            .iter_mut()
+            .find(|a| a.account_id == account_id)
+    }) {
+        update(account);
+    }
+}
+
+/// Register a publish task and the accounts it will run, all pending
+pub fn start_task(task_id: i64, accounts: &[(i64, String)]) {
+    let Ok(mut tasks) = TASKS.lock() else {
+        return;
+    };
+    while tasks.len() >= MAX_TASKS {
+        let oldest = tasks
+            .iter()
+            .find(|(_, task)| task.finished)
+            .or_else(|| tasks.iter().next())
+            .map(|(id, _)| *id);
+        match oldest {
+            Some(id) => tasks.remove(&id),
+            None => break,
+        };
+    }
+    tasks.insert(
+        task_id,
+        TaskProgress {
+            task_id,
+            started_at: chrono::Utc::now().to_rfc3339(),
+            elapsed_ms: 0,
+            finished: false,
+            accounts: accounts
+                .iter()
+                .map(|(account_id, platform)| AccountProgress {
+                    account_id: *account_id,
+                    platform: platform.clone(),
+                    status: STATUS_PENDING.to_string(),
+                    phase: STATUS_PENDING.to_string(),
+                    strategy: None,
+                    strategy_attempts: Vec::new(),
+                    last_signal: None,
+                    signal_latency_ms: None,
+                    elapsed_ms: 0,
+                    started: None,
+                    ended: None,
+                })
+                .collect(),
+            started: Some(Instant::now()),
+            ended: None,
+        },
+    );
+}
+
+/// Record the final status of one account
+pub fn finish_account(task_id: i64, account_id: i64, status: &str, phase: Option<&str>) {
+    with_account(task_id, account_id, |account| {
+        account.status = status.to_string();
+        if let Some(phase) = phase {
+            account.phase = phase.to_string();
+        }
+        account.started.get_or_insert_with(Instant::now);
+        account.ended = Some(Instant::now());
+    });
+}
+
+pub fn finish_task(task_id: i64) {
+    let Ok(mut tasks) = TASKS.lock() else {
+        return;
+    };
+    if let Some(task) = tasks.get_mut(&task_id) {
+        task.finished = true;
+        task.ended = Some(Instant::now());
+    }
+}
+
+/// Tasks registered in this session that have not finished yet
+pub fn running_task_ids() -> Vec<i64> {
+    let Ok(tasks) = TASKS.lock() else {
+        return Vec::new();
+    };
+    tasks
+        .values()
+        .filter(|task| !task.finished)
+        .map(|task| task.task_id)
+        .collect()
+}
+
+/// Live state of a task with elapsed times filled in; None once evicted
+pub fn snapshot(task_id: i64) -> Option<TaskProgress> {
+    let tasks = TASKS.lock().ok()?;
+    let mut task = tasks.get(&task_id)?.clone();
+    task.elapsed_ms = elapsed_ms(task.started, task.ended);
+    for account in &mut task.accounts {
+        account.elapsed_ms = elapsed_ms(account.started, account.ended);
+    }
+    Some(task)
+}
+
+/// Progress reporter for one account of a running task, carried in the
+/// publish payload so platform automation can report strategies and signals.
+#[derive(Debug, Clone, Copy)]
+pub struct ProgressHandle {
+    task_id: i64,
+    account_id: i64,
+}
+
+impl ProgressHandle {
+    pub fn new(task_id: i64, account_id: i64) -> Self {
+        Self {
+            task_id,
+            account_id,
+        }
+    }
+
+    pub fn phase(&self, phase: &str) {
+        with_account(self.task_id, self.account_id, |account| {
+            if account.started.is_none() {
+                account.started = Some(Instant::now());
+                account.status = STATUS_RUNNING.to_string();
+            }
+            account.phase = phase.to_string();
+        });
+    }
+
+    pub fn strategy(&self, strategy: &str) {
+        with_account(self.task_id, self.account_id, |account| {
+            account.strategy = Some(strategy.to_string());
+        });
+    }
+
+    pub fn strategy_attempt(&self, strategy: &str, succeeded: bool) {
+        with_account(self.task_id, self.account_id, |account| {
+            account
+                .strategy_attempts
+                .push((strategy.to_string(), succeeded));
+        });
+    }
+
+    pub fn signal(&self, signal: &str) {
+        with_account(self.task_id, self.account_id, |account| {
+            account.last_signal = Some(signal.to_string());
+        });
+    }
+
+    pub fn signal_latency(&self, latency: Duration) {
+        with_account(self.task_id, self.account_id, |account| {
+            account.signal_latency_ms = Some(latency.as_millis() as u64);
+        });
+    }
+}
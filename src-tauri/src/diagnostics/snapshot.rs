@@ -0,0 +1,210 @@
+use crate::browser::page_handle::PageHandle;
+use crate::database::queries::{self, PageSnapshotRecord};
+use crate::platforms::{registry, selector_overrides};
+use anyhow::{anyhow, bail, Result};
+use log::warn;
+use ring::digest::{digest, SHA256};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Snapshots kept per platform; older ones are pruned on insert
+const MAX_SNAPSHOTS_PER_PLATFORM: usize = 200;
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Presence of each configured selector plus frame and shadow root counts.
+/// Only presence is recorded: match counts vary with the content on the page,
+/// while a selector that stops matching means the markup changed.
+const SNAPSHOT_JS: &str = r#"
+(function(fields) {
+    const hit = (selector) => {
+        try {
+            return document.querySelector(selector) !== null;
+        } catch (_) {
+            return false;
+        }
+    };
+    let shadowRoots = 0;
+    const walk = (root) => {
+        for (const el of root.querySelectorAll('*')) {
+            if (el.shadowRoot) {
+                shadowRoots += 1;
+                walk(el.shadowRoot);
+            }
+        }
+    };
+    walk(document);
+    return JSON.stringify({
+        url_path: location.host + location.pathname,
+        fields: fields.map((f) => ({ field: f.field, selectors: f.selectors, hits: f.selectors.map(hit) })),
+        frames: document.querySelectorAll('iframe, frame').length,
+        shadow_roots: shadowRoots,
+    });
+})
+"#;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldHits {
+    pub field: String,
+    pub selectors: Vec<String>,
+    pub hits: Vec<bool>,
+}
+
+/// Structure of an upload page right after automation filled it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageSnapshot {
+    pub url_path: String,
+    pub fields: Vec<FieldHits>,
+    pub frames: u32,
+    pub shadow_roots: u32,
+}
+
+impl PageSnapshot {
+    /// Identical structures hash the same, so a changed hash between runs is
+    /// the cheap signal and the diff explains it
+    fn structure_hash(&self) -> Result<String> {
+        let body = serde_json::to_string(self)?;
+        Ok(digest(&SHA256, body.as_bytes())
+            .as_ref()
+            .iter()
+            .take(8)
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+
+    fn hit(&self, field: &str, selector: &str) -> Option<bool> {
+        let field = self.fields.iter().find(|f| f.field == field)?;
+        let idx = field.selectors.iter().position(|s| s == selector)?;
+        field.hits.get(idx).copied()
+    }
+}
+
+/// Snapshot the page of a publish run. Failures only log: the snapshot is a
+/// maintenance signal and must never affect the run.
+pub async fn capture(handle: &PageHandle, platform: &str) -> Option<PageSnapshot> {
+    let cfg = registry::find(platform)?.effective_config();
+    let mut fields = selector_overrides::builtin_selectors(cfg);
+    fields.push(("submit_selectors", cfg.submit.selectors));
+    let fields = fields
+        .into_iter()
+        .filter(|(_, selectors)| !selectors.is_empty())
+        .map(|(field, selectors)| serde_json::json!({ "field": field, "selectors": selectors }))
+        .collect::<Vec<_>>();
+    let script = format!("{}({})", SNAPSHOT_JS, serde_json::Value::Array(fields));
+    let captured = tokio::time::timeout(CAPTURE_TIMEOUT, async {
+        let page = handle.ensure_attached().await?;
+        let raw: String = page.evaluate(script.as_str()).await?.into_value()?;
+        anyhow::Ok(serde_json::from_str::<PageSnapshot>(&raw)?)
+    })
+    .await
+    .unwrap_or_else(|_| Err(anyhow!("超时")));
+    match captured {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            warn!("[页面快照] platform={} 采集失败：{}", platform, e);
+            None
+        }
+    }
+}
+
+pub fn record(conn: &Connection, platform: &str, snapshot: &PageSnapshot) -> Result<()> {
+    queries::insert_page_snapshot(
+        conn,
+        platform,
+        env!("CARGO_PKG_VERSION"),
+        &snapshot.structure_hash()?,
+        &serde_json::to_string(snapshot)?,
+        MAX_SNAPSHOTS_PER_PLATFORM,
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotSide {
+    pub id: i64,
+    pub app_version: String,
+    pub structure_hash: String,
+    pub captured_at: String,
+    pub url_path: String,
+    pub frames: u32,
+    pub shadow_roots: u32,
+}
+
+/// A selector whose presence differs. `None` means the selector was not in
+/// that app version's config, so config changes are told apart from page changes.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectorChange {
+    pub field: String,
+    pub selector: String,
+    pub before: Option<bool>,
+    pub after: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotDiff {
+    pub platform: String,
+    pub from: SnapshotSide,
+    pub to: SnapshotSide,
+    pub changed: bool,
+    pub selectors: Vec<SelectorChange>,
+}
+
+fn load(record: PageSnapshotRecord) -> Result<(SnapshotSide, PageSnapshot)> {
+    let snapshot: PageSnapshot = serde_json::from_str(&record.snapshot)?;
+    let side = SnapshotSide {
+        id: record.id,
+        app_version: record.app_version,
+        structure_hash: record.structure_hash,
+        captured_at: record.captured_at,
+        url_path: snapshot.url_path.clone(),
+        frames: snapshot.frames,
+        shadow_roots: snapshot.shadow_roots,
+    };
+    Ok((side, snapshot))
+}
+
+/// Compare the last snapshot taken on or before each date (`YYYY-MM-DD`, UTC)
+pub fn diff(
+    conn: &Connection,
+    platform: &str,
+    from_date: &str,
+    to_date: &str,
+) -> Result<SnapshotDiff> {
+    let last_on = |date: &str| -> Result<(SnapshotSide, PageSnapshot)> {
+        let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| anyhow!("日期格式无效：{}（应为 YYYY-MM-DD）", date))?;
+        let before = (day + chrono::Days::new(1)).format("%Y-%m-%d").to_string();
+        match queries::latest_page_snapshot_before(conn, platform, &before)? {
+            Some(record) => load(record),
+            None => bail!("{} 在 {} 及之前没有页面快照", platform, date),
+        }
+    };
+    let (from, before) = last_on(from_date)?;
+    let (to, after) = last_on(to_date)?;
+
+    let mut selectors = Vec::new();
+    let mut seen = Vec::new();
+    for field in before.fields.iter().chain(&after.fields) {
+        for selector in &field.selectors {
+            if seen.contains(&(&field.field, selector)) {
+                continue;
+            }
+            seen.push((&field.field, selector));
+            let change = SelectorChange {
+                field: field.field.clone(),
+                selector: selector.clone(),
+                before: before.hit(&field.field, selector),
+                after: after.hit(&field.field, selector),
+            };
+            if change.before != change.after {
+                selectors.push(change);
+            }
+        }
+    }
+    Ok(SnapshotDiff {
+        platform: platform.to_string(),
+        changed: from.structure_hash != to.structure_hash,
+        from,
+        to,
+        selectors,
+    })
+}
@@ -0,0 +1,126 @@
+use crate::database::queries::{self, StrategyStat};
+use anyhow::{bail, Context, Result};
+use log::info;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// `off`, `local` or `share`
+pub const SETTING_TELEMETRY_MODE: &str = "telemetry_mode";
+pub const SETTING_TELEMETRY_ENDPOINT: &str = "telemetry_endpoint";
+
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryMode {
+    /// Nothing is counted
+    #[default]
+    Off,
+    /// Counted for the user's own statistics, never sent
+    Local,
+    /// Counted and, on request, sent anonymized to the configured endpoint
+    Share,
+}
+
+impl TelemetryMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "local" => Some(Self::Local),
+            "share" => Some(Self::Share),
+            _ => None,
+        }
+    }
+
+    pub fn load(conn: &Connection) -> Result<Self> {
+        Ok(queries::get_setting(conn, SETTING_TELEMETRY_MODE)?
+            .as_deref()
+            .and_then(Self::parse)
+            .unwrap_or_default())
+    }
+}
+
+/// Reduce an upload signal to its kind and configured label, e.g.
+/// `progress:bar@frame[1];fill=...` becomes `progress:bar`. File counts and
+/// frame paths are dropped; labels come from the platform configs.
+fn signal_key(signal: &str) -> String {
+    let head = signal.split(';').next().unwrap_or_default();
+    let head = head.split('@').next().unwrap_or_default();
+    let mut parts = head.split(':');
+    let kind = parts.next().unwrap_or_default();
+    match parts.next() {
+        Some(label) => format!("{}:{}", kind, label),
+        None => kind.to_string(),
+    }
+}
+
+/// Count one account run by the strategy and signal it ended with. Only
+/// successes and failures are counted; nothing is stored when telemetry is off.
+pub fn record(
+    conn: &Connection,
+    platform: &str,
+    strategy: Option<&str>,
+    signal: Option<&str>,
+    succeeded: bool,
+) -> Result<()> {
+    if TelemetryMode::load(conn)? == TelemetryMode::Off {
+        return Ok(());
+    }
+    let outcome = if succeeded { "success" } else { "failure" };
+    queries::increment_strategy_stat(
+        conn,
+        platform,
+        strategy.unwrap_or("none"),
+        &signal.map(signal_key).unwrap_or_default(),
+        outcome,
+    )
+}
+
+#[derive(Debug, Serialize)]
+struct Submission<'a> {
+    app_version: &'static str,
+    counts: &'a [StrategyStat],
+}
+
+/// Send counts gathered since the last submission. The payload holds only the
+/// app version and per-platform strategy/signal/outcome counts: no account,
+/// video, title or URL.
+pub async fn submit_pending(db: &crate::database::Database) -> Result<usize> {
+    let (endpoint, pending) = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        if TelemetryMode::load(&conn)? != TelemetryMode::Share {
+            bail!("Sharing statistics is not enabled");
+        }
+        let endpoint = queries::get_setting(&conn, SETTING_TELEMETRY_ENDPOINT)?
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .context("No statistics endpoint configured")?;
+        (endpoint, queries::get_unsent_strategy_stats(&conn)?)
+    };
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(SUBMIT_TIMEOUT)
+        .build()
+        .context("创建统计上报客户端失败")?;
+    let resp = client
+        .post(&endpoint)
+        .json(&Submission {
+            app_version: env!("CARGO_PKG_VERSION"),
+            counts: &pending,
+        })
+        .send()
+        .await
+        .context("统计上报请求失败")?;
+    if !resp.status().is_success() {
+        bail!("统计上报返回 HTTP {}", resp.status());
+    }
+
+    let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    queries::mark_strategy_stats_sent(&conn, &pending)?;
+    info!("[统计] 已上报 {} 条策略统计", pending.len());
+    Ok(pending.len())
+}
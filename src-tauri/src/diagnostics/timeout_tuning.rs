@@ -0,0 +1,111 @@
+use crate::database::queries;
+use crate::platforms::{self, AutomationProfile, AutomationProfileKind};
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// `false` keeps the built-in signal timeouts
+pub const SETTING_TIMEOUT_TUNING: &str = "signal_timeout_tuning_enabled";
+/// Bounds for tuned signal timeouts, in seconds
+pub const SETTING_TIMEOUT_MIN_SECS: &str = "signal_timeout_min_secs";
+pub const SETTING_TIMEOUT_MAX_SECS: &str = "signal_timeout_max_secs";
+
+const DEFAULT_MIN_SECS: u64 = platforms::FAST_SIGNAL_TIMEOUT_SECS;
+const DEFAULT_MAX_SECS: u64 = 30;
+/// Recorded latencies needed before they replace the built-in timeouts
+const MIN_SAMPLES: usize = 5;
+const SAMPLE_WINDOW: i64 = 30;
+/// The fallback wait is a last chance, so it gets headroom over the slow tail
+const SLOW_HEADROOM: f64 = 2.0;
+
+/// Signal timeouts for one platform, derived from recent upload signal latencies
+#[derive(Debug, Clone, Serialize)]
+pub struct TunedTimeouts {
+    pub platform: String,
+    pub samples: usize,
+    /// None while there are too few samples; the built-in timeouts apply
+    pub p75_ms: Option<i64>,
+    pub p95_ms: Option<i64>,
+    pub fast_signal_timeout_secs: u64,
+    pub slow_fallback_signal_timeout_secs: u64,
+    /// `tuned`, `default` (too few samples) or `disabled`
+    pub source: &'static str,
+}
+
+struct Bounds {
+    min_secs: u64,
+    max_secs: u64,
+}
+
+impl Bounds {
+    fn load(conn: &Connection) -> Result<Self> {
+        let read = |key: &str, default: u64| -> Result<u64> {
+            Ok(queries::get_setting(conn, key)?
+                .and_then(|raw| raw.trim().parse::<u64>().ok())
+                .unwrap_or(default))
+        };
+        let min_secs = read(SETTING_TIMEOUT_MIN_SECS, DEFAULT_MIN_SECS)?.max(1);
+        let max_secs = read(SETTING_TIMEOUT_MAX_SECS, DEFAULT_MAX_SECS)?.max(min_secs);
+        Ok(Self { min_secs, max_secs })
+    }
+
+    fn clamp_ms(&self, ms: f64) -> u64 {
+        ((ms / 1000.0).ceil() as u64).clamp(self.min_secs, self.max_secs)
+    }
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[i64], pct: f64) -> i64 {
+    let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub fn tuned_timeouts(conn: &Connection, platform: &str) -> Result<TunedTimeouts> {
+    let mut tuned = TunedTimeouts {
+        platform: platform.to_string(),
+        samples: 0,
+        p75_ms: None,
+        p95_ms: None,
+        fast_signal_timeout_secs: platforms::FAST_SIGNAL_TIMEOUT_SECS,
+        slow_fallback_signal_timeout_secs: platforms::SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS,
+        source: "disabled",
+    };
+    if !queries::get_setting_bool(conn, SETTING_TIMEOUT_TUNING, true)? {
+        return Ok(tuned);
+    }
+    let mut latencies = queries::get_recent_signal_latencies(conn, platform, SAMPLE_WINDOW)?;
+    tuned.samples = latencies.len();
+    if latencies.len() < MIN_SAMPLES {
+        tuned.source = "default";
+        return Ok(tuned);
+    }
+    latencies.sort_unstable();
+    let p75 = percentile(&latencies, 75.0);
+    let p95 = percentile(&latencies, 95.0);
+    let bounds = Bounds::load(conn)?;
+    tuned.fast_signal_timeout_secs = bounds.clamp_ms(p75 as f64);
+    tuned.slow_fallback_signal_timeout_secs = bounds
+        .clamp_ms(p95 as f64 * SLOW_HEADROOM)
+        .max(tuned.fast_signal_timeout_secs);
+    tuned.p75_ms = Some(p75);
+    tuned.p95_ms = Some(p95);
+    tuned.source = "tuned";
+    Ok(tuned)
+}
+
+/// Replace the fast profile's signal timeouts with tuned ones, keeping the
+/// slow-network factor. Thorough and diagnostic runs keep their deliberately
+/// long waits.
+pub fn apply(conn: &Connection, platform: &str, profile: &mut AutomationProfile) -> Result<()> {
+    if profile.kind != AutomationProfileKind::Fast {
+        return Ok(());
+    }
+    let tuned = tuned_timeouts(conn, platform)?;
+    if tuned.source != "tuned" {
+        return Ok(());
+    }
+    profile.fast_signal_timeout_secs = tuned.fast_signal_timeout_secs * profile.timeout_factor;
+    profile.slow_fallback_signal_timeout_secs =
+        tuned.slow_fallback_signal_timeout_secs * profile.timeout_factor;
+    Ok(())
+}
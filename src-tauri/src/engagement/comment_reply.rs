@@ -0,0 +1,362 @@
+use crate::browser::automation;
+use crate::browser::chrome;
+use crate::browser::probe::ProbeSession;
+use crate::database::queries::{self, MonitoredPost, ReplyRule};
+use crate::database::Database;
+use crate::tasks::TaskRegistry;
+use anyhow::Result;
+use log::{info, warn};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// Per-platform enable flag is `comment_reply_enabled_<platform>`; all off by default.
+pub const SETTING_ENABLED_PREFIX: &str = "comment_reply_enabled_";
+pub const SETTING_MAX_PER_HOUR: &str = "comment_reply_max_per_hour";
+pub const SETTING_MIN_INTERVAL_SECS: &str = "comment_reply_min_interval_secs";
+pub const SETTING_POLL_MINUTES: &str = "comment_reply_poll_minutes";
+
+const DEFAULT_MAX_PER_HOUR: i64 = 10;
+const DEFAULT_MIN_INTERVAL_SECS: u64 = 90;
+const DEFAULT_POLL_MINUTES: u64 = 30;
+const STARTUP_DELAY_SECS: u64 = 300;
+const PAGE_SETTLE_SECS: u64 = 6;
+const REPLY_BOX_WAIT_MS: u64 = 500;
+
+/// Where comments live on a post page and how to answer one.
+struct CommentPageConfig {
+    platform: &'static str,
+    item_selectors: &'static [&'static str],
+    author_selectors: &'static [&'static str],
+    text_selectors: &'static [&'static str],
+    /// Badge the platform shows on comments written by the post's author
+    own_badge_selectors: &'static [&'static str],
+    reply_text_markers: &'static [&'static str],
+    reply_input_selectors: &'static [&'static str],
+    submit_selectors: &'static [&'static str],
+}
+
+const COMMENT_PAGES: &[CommentPageConfig] = &[
+    CommentPageConfig {
+        platform: "douyin",
+        item_selectors: &["[data-e2e='comment-item']", "[class*='comment-item']"],
+        author_selectors: &["[class*='username']", "[class*='nickname']"],
+        text_selectors: &["[class*='comment-content']", "[class*='content']"],
+        own_badge_selectors: &["[class*='author-tag']", "[class*='authorTag']"],
+        reply_text_markers: &["回复"],
+        reply_input_selectors: &[
+            "[class*='comment-input'] [contenteditable='true']",
+            "textarea",
+        ],
+        submit_selectors: &[
+            "[class*='comment'] button[class*='send']",
+            "button[class*='submit']",
+        ],
+    },
+    CommentPageConfig {
+        platform: "xiaohongshu",
+        item_selectors: &[".comment-item", "[class*='comment-item']"],
+        author_selectors: &[".author .name", "[class*='name']"],
+        text_selectors: &[".content", "[class*='content']"],
+        own_badge_selectors: &[".author .tag", "[class*='author-tag']"],
+        reply_text_markers: &["回复"],
+        reply_input_selectors: &["#content-textarea", "[contenteditable='true']", "textarea"],
+        submit_selectors: &["button.submit", "button[class*='submit']"],
+    },
+    CommentPageConfig {
+        platform: "bilibili",
+        item_selectors: &[".reply-item", "[class*='reply-item']"],
+        author_selectors: &[".user-name", "[class*='user-name']"],
+        text_selectors: &[".reply-content", "[class*='reply-content']"],
+        own_badge_selectors: &[".up-icon", "[class*='up-tag']"],
+        reply_text_markers: &["回复"],
+        reply_input_selectors: &[".reply-box-textarea", "textarea"],
+        submit_selectors: &[".reply-box-send", "[class*='reply-box-send']"],
+    },
+    CommentPageConfig {
+        platform: "youtube",
+        item_selectors: &["ytd-comment-thread-renderer"],
+        author_selectors: &["#author-text"],
+        text_selectors: &["#content-text"],
+        // Only the thread's top comment; the creator's replies sit under #replies
+        own_badge_selectors: &["#comment ytd-author-comment-badge-renderer"],
+        reply_text_markers: &["Reply", "回复"],
+        reply_input_selectors: &["#contenteditable-root", "[contenteditable='true']"],
+        submit_selectors: &["#submit-button button", "ytd-button-renderer#submit-button"],
+    },
+];
+
+pub fn enabled_setting_key(platform: &str) -> String {
+    format!("{}{}", SETTING_ENABLED_PREFIX, platform)
+}
+
+struct ReplyLimits {
+    max_per_hour: i64,
+    min_interval: Duration,
+}
+
+fn read_limits(conn: &Connection) -> ReplyLimits {
+    let read = |key: &str| {
+        queries::get_setting(conn, key)
+            .ok()
+            .flatten()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+    };
+    ReplyLimits {
+        max_per_hour: read(SETTING_MAX_PER_HOUR)
+            .map(|v| v as i64)
+            .unwrap_or(DEFAULT_MAX_PER_HOUR),
+        min_interval: Duration::from_secs(
+            read(SETTING_MIN_INTERVAL_SECS)
+                .unwrap_or(DEFAULT_MIN_INTERVAL_SECS)
+                .max(DEFAULT_MIN_INTERVAL_SECS / 3),
+        ),
+    }
+}
+
+/// First enabled rule whose keyword appears in the comment. Platform-specific
+/// rules win over rules that apply to every platform.
+fn match_rule<'a>(rules: &'a [ReplyRule], platform: &str, text: &str) -> Option<&'a ReplyRule> {
+    let text = text.to_lowercase();
+    let applies = |rule: &&ReplyRule| {
+        rule.enabled
+            && !rule.keyword.trim().is_empty()
+            && text.contains(&rule.keyword.to_lowercase())
+    };
+    rules
+        .iter()
+        .filter(|rule| rule.platform.as_deref() == Some(platform))
+        .find(applies)
+        .or_else(|| {
+            rules
+                .iter()
+                .filter(|rule| rule.platform.is_none())
+                .find(applies)
+        })
+}
+
+fn comment_key(author: &str, text: &str) -> String {
+    let text: String = text.chars().take(120).collect();
+    format!("{}|{}", author, text)
+}
+
+/// Check every monitored post on enabled platforms once. Returns replies sent.
+pub async fn run_once(db: &Database) -> Result<usize> {
+    let (posts, rules, limits) = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let posts: Vec<MonitoredPost> = queries::get_monitored_posts(&conn)?
+            .into_iter()
+            .filter(|post| {
+                queries::get_setting_bool(&conn, &enabled_setting_key(&post.platform), false)
+                    .unwrap_or(false)
+            })
+            .collect();
+        (posts, queries::get_reply_rules(&conn)?, read_limits(&conn))
+    };
+    if posts.is_empty() || rules.iter().all(|rule| !rule.enabled) {
+        return Ok(0);
+    }
+
+    let chrome_path = chrome::detect_chrome()?;
+    let mut sent = 0;
+    for post in &posts {
+        let Some(page_cfg) = COMMENT_PAGES.iter().find(|c| c.platform == post.platform) else {
+            continue;
+        };
+        match process_post(db, &chrome_path, post, page_cfg, &rules, &limits).await {
+            Ok(count) => sent += count,
+            Err(e) => warn!(
+                "[评论回复] 处理失败 task_platform={} url={}：{}",
+                post.task_platform_id, post.post_url, e
+            ),
+        }
+    }
+    Ok(sent)
+}
+
+async fn process_post(
+    db: &Database,
+    chrome_path: &Path,
+    post: &MonitoredPost,
+    page_cfg: &CommentPageConfig,
+    rules: &[ReplyRule],
+    limits: &ReplyLimits,
+) -> Result<usize> {
+    let profile_dir = PathBuf::from(&post.chrome_profile_dir);
+    let probe = ProbeSession::open(
+        chrome_path,
+        &profile_dir,
+        &post.post_url,
+        &post.chrome_flags,
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_secs(PAGE_SETTLE_SECS)).await;
+
+    let result = reply_on_page(db, &probe, post, page_cfg, rules, limits).await;
+    probe.close().await;
+    result
+}
+
+async fn reply_on_page(
+    db: &Database,
+    probe: &ProbeSession,
+    post: &MonitoredPost,
+    page_cfg: &CommentPageConfig,
+    rules: &[ReplyRule],
+    limits: &ReplyLimits,
+) -> Result<usize> {
+    let comments = read_comments(probe, page_cfg).await?;
+
+    let mut sent = 0;
+    for comment in comments {
+        if is_own_comment(&comment, rules) {
+            continue;
+        }
+        let Some(rule) = match_rule(rules, &post.platform, &comment.text) else {
+            continue;
+        };
+        let key = comment_key(&comment.author, &comment.text);
+        {
+            let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            if queries::has_replied_comment(&conn, post.task_platform_id, &key)? {
+                continue;
+            }
+            if queries::count_recent_replies(&conn, &post.platform)? >= limits.max_per_hour {
+                info!(
+                    "[评论回复] {} 已达到每小时回复上限，本轮停止",
+                    post.platform
+                );
+                return Ok(sent);
+            }
+        }
+
+        // Earlier replies may have re-rendered the list, so the comment is
+        // found again by author and text instead of trusting its old position
+        let Some(index) = read_comments(probe, page_cfg)
+            .await?
+            .into_iter()
+            .find(|current| comment_key(&current.author, &current.text) == key)
+            .map(|current| current.index)
+        else {
+            warn!(
+                "[评论回复] 评论已不在页面上，跳过 task_platform={} author={}",
+                post.task_platform_id, comment.author
+            );
+            continue;
+        };
+
+        // One comment the page would not take a reply on must not end the round
+        if let Err(e) = send_reply(probe, page_cfg, index, &rule.reply).await {
+            warn!(
+                "[评论回复] 回复失败，跳过该评论 task_platform={} author={}：{}",
+                post.task_platform_id, comment.author, e
+            );
+            continue;
+        }
+
+        {
+            let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+            queries::insert_comment_reply(&conn, post.task_platform_id, &key, rule.id)?;
+        }
+        sent += 1;
+        info!(
+            "[评论回复] platform={} task_platform={} rule={} author={}",
+            post.platform, post.task_platform_id, rule.id, comment.author
+        );
+        tokio::time::sleep(limits.min_interval).await;
+    }
+    Ok(sent)
+}
+
+async fn read_comments(
+    probe: &ProbeSession,
+    page_cfg: &CommentPageConfig,
+) -> Result<Vec<automation::PageComment>> {
+    automation::collect_comments(
+        &probe.page,
+        page_cfg.item_selectors,
+        page_cfg.author_selectors,
+        page_cfg.text_selectors,
+        page_cfg.own_badge_selectors,
+    )
+    .await
+}
+
+/// Written by the account itself: it carries the platform's author badge, or
+/// it is one of our own canned replies showing up in the list
+fn is_own_comment(comment: &automation::PageComment, rules: &[ReplyRule]) -> bool {
+    comment.own
+        || rules
+            .iter()
+            .any(|rule| !rule.reply.trim().is_empty() && comment.text.trim() == rule.reply.trim())
+}
+
+/// Open the reply box of one comment, type the reply and send it, all within
+/// that comment's container
+async fn send_reply(
+    probe: &ProbeSession,
+    page_cfg: &CommentPageConfig,
+    index: usize,
+    reply: &str,
+) -> Result<()> {
+    automation::click_comment_reply(
+        &probe.page,
+        page_cfg.item_selectors,
+        index,
+        page_cfg.reply_text_markers,
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_millis(REPLY_BOX_WAIT_MS)).await;
+    automation::type_into_comment_reply(
+        &probe.page,
+        page_cfg.item_selectors,
+        index,
+        page_cfg.reply_input_selectors,
+        reply,
+    )
+    .await?;
+    if automation::click_comment_submit(
+        &probe.page,
+        page_cfg.item_selectors,
+        index,
+        page_cfg.submit_selectors,
+    )
+    .await
+    .is_err()
+    {
+        automation::cdp_press_enter(&probe.page).await?;
+    }
+    Ok(())
+}
+
+/// Poll monitored posts in the background. Does nothing until at least one
+/// platform is enabled in settings.
+pub fn spawn_periodic(app: AppHandle) {
+    let registry = app.state::<TaskRegistry>().inner().clone();
+    registry.spawn("comment_reply:periodic", async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            let db = app.state::<Database>();
+            let poll_minutes = db
+                .conn
+                .lock()
+                .ok()
+                .and_then(|conn| {
+                    queries::get_setting(&conn, SETTING_POLL_MINUTES)
+                        .ok()
+                        .flatten()
+                })
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .filter(|minutes| *minutes > 0)
+                .unwrap_or(DEFAULT_POLL_MINUTES);
+
+            match run_once(&db).await {
+                Ok(0) => {}
+                Ok(sent) => info!("[评论回复] 本轮共回复 {} 条评论", sent),
+                Err(e) => warn!("[评论回复] 本轮执行失败：{}", e),
+            }
+            tokio::time::sleep(Duration::from_secs(poll_minutes * 60)).await;
+        }
+    });
+}
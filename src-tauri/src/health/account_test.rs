@@ -0,0 +1,226 @@
+use super::session_expiry;
+use crate::browser::{automation, chrome, profile_lock};
+use crate::database::queries;
+use crate::database::Database;
+use crate::platforms;
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+pub const STEP_PASS: &str = "pass";
+pub const STEP_FAIL: &str = "fail";
+pub const STEP_SKIPPED: &str = "skipped";
+
+const CHROME_READY_TIMEOUT_SECS: u64 = 30;
+/// Time allowed for client-side redirects to settle before reading the landing URL
+const LANDING_SETTLE_SECS: u64 = 6;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountTestStep {
+    /// `profile`, `launch`, `cdp_attach`, `login` or `upload_guard`
+    pub name: &'static str,
+    /// `pass`, `fail` or `skipped`
+    pub status: &'static str,
+    pub duration_ms: u64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountScorecard {
+    pub account_id: i64,
+    pub platform: String,
+    pub display_name: String,
+    /// Every step passed
+    pub passed: bool,
+    /// Time from launching (or finding) Chrome until its debugging endpoint answered
+    pub session_ready_ms: Option<u64>,
+    pub session_mode: Option<String>,
+    pub final_url: Option<String>,
+    pub steps: Vec<AccountTestStep>,
+}
+
+impl AccountScorecard {
+    fn push(&mut self, name: &'static str, started: Instant, result: Result<String>) -> bool {
+        let (status, detail) = match result {
+            Ok(detail) => (STEP_PASS, detail),
+            Err(e) => (STEP_FAIL, e.to_string()),
+        };
+        self.steps.push(AccountTestStep {
+            name,
+            status,
+            duration_ms: started.elapsed().as_millis() as u64,
+            detail,
+        });
+        status == STEP_PASS
+    }
+
+    /// Record the steps that could not run after a failure
+    fn skip(&mut self, names: &[&'static str]) {
+        for name in names {
+            self.steps.push(AccountTestStep {
+                name,
+                status: STEP_SKIPPED,
+                duration_ms: 0,
+                detail: String::new(),
+            });
+        }
+    }
+}
+
+/// Run the publish path up to the upload page for one account, without
+/// uploading anything: profile lock, Chrome launch, CDP attach, login state
+/// and the upload page guard. The profile is locked for the duration, and the
+/// stored login state is updated when the test could tell.
+pub async fn run(db: &Database, account_id: i64) -> Result<AccountScorecard> {
+    let account = {
+        let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+            .into_iter()
+            .find(|a| a.id == account_id)
+            .ok_or_else(|| anyhow!("Account {} not found", account_id))?
+    };
+    let cfg = platforms::publish_config(&account.platform)
+        .ok_or_else(|| anyhow!("Unknown platform: {}", account.platform))?;
+    let chrome_path = chrome::detect_chrome()?;
+
+    let mut card = AccountScorecard {
+        account_id: account.id,
+        platform: account.platform.clone(),
+        display_name: account.display_name.clone(),
+        passed: false,
+        session_ready_ms: None,
+        session_mode: None,
+        final_url: None,
+        steps: Vec::new(),
+    };
+    info!(
+        "[账号自检] account={} platform={} 开始",
+        account.id, account.platform
+    );
+
+    let started = Instant::now();
+    let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+    let lock = if profile_dir.is_dir() {
+        profile_lock::acquire(&profile_dir, "test", &format!("account {}", account.id))
+    } else {
+        Err(anyhow!("配置目录不存在：{}", profile_dir.display()))
+    };
+    let lock = match lock {
+        Ok(lock) => {
+            card.push("profile", started, Ok(profile_dir.display().to_string()));
+            lock
+        }
+        Err(e) => {
+            card.push("profile", started, Err(e));
+            card.skip(&["launch", "cdp_attach", "login", "upload_guard"]);
+            return Ok(finish(card));
+        }
+    };
+
+    let started = Instant::now();
+    let session = chrome::prepare_chrome_session(
+        &chrome_path,
+        &profile_dir,
+        "about:blank",
+        None,
+        &account.chrome_flags,
+        &lock,
+    )
+    .await;
+    let ready = match session {
+        Ok(session) => {
+            chrome::wait_for_chrome_ready(&session, &profile_dir, CHROME_READY_TIMEOUT_SECS)
+                .await
+                .map(|port| (session.mode, port))
+        }
+        Err(e) => Err(e),
+    };
+    let (mode, port) = match ready {
+        Ok(ready) => ready,
+        Err(e) => {
+            card.push("launch", started, Err(e));
+            card.skip(&["cdp_attach", "login", "upload_guard"]);
+            return Ok(finish(card));
+        }
+    };
+    card.session_ready_ms = Some(started.elapsed().as_millis() as u64);
+    card.session_mode = Some(mode.as_str().to_string());
+    card.push("launch", started, Ok(format!("port={}", port)));
+
+    let started = Instant::now();
+    let (mut browser, page) = match automation::open_probe_page(port, cfg.upload_url).await {
+        Ok(opened) => opened,
+        Err(e) => {
+            card.push("cdp_attach", started, Err(e));
+            card.skip(&["login", "upload_guard"]);
+            return Ok(finish(card));
+        }
+    };
+    card.push("cdp_attach", started, Ok(cfg.upload_url.to_string()));
+
+    let started = Instant::now();
+    tokio::time::sleep(Duration::from_secs(LANDING_SETTLE_SECS)).await;
+    let final_url = page.url().await.ok().flatten().unwrap_or_default();
+    card.final_url = Some(final_url.clone());
+    let logged_in = !platforms::is_login_url(&final_url, &cfg.login_guard);
+    let login_result = if logged_in {
+        Ok(final_url.clone())
+    } else {
+        Err(anyhow!("跳转到登录页：{}", final_url))
+    };
+    if card.push("login", started, login_result) {
+        let started = Instant::now();
+        let guard = platforms::check_upload_guard(&page, cfg)
+            .await
+            .map(|_| "上传页守卫通过".to_string());
+        card.push("upload_guard", started, guard);
+    } else {
+        card.skip(&["upload_guard"]);
+    }
+    store_login_state(db, account.id, logged_in);
+
+    if let Err(e) = page.close().await {
+        warn!("[账号自检] 关闭页面失败：{}", e);
+    }
+    if mode == chrome::ChromeSessionMode::LaunchedNew {
+        if let Err(e) = browser.close().await {
+            warn!("[账号自检] 关闭 Chrome 失败：{}", e);
+        }
+    }
+    drop(lock);
+    Ok(finish(card))
+}
+
+fn finish(mut card: AccountScorecard) -> AccountScorecard {
+    card.passed = card.steps.iter().all(|step| step.status == STEP_PASS);
+    let failed = card
+        .steps
+        .iter()
+        .find(|step| step.status == STEP_FAIL)
+        .map(|step| step.name)
+        .unwrap_or("none");
+    info!(
+        "[账号自检] account={} passed={} failed_step={} session_ready_ms={:?}",
+        card.account_id, card.passed, failed, card.session_ready_ms
+    );
+    card
+}
+
+fn store_login_state(db: &Database, account_id: i64, logged_in: bool) {
+    let Ok(conn) = db.conn.lock() else {
+        return;
+    };
+    let stored =
+        queries::update_account_login_status(&conn, account_id, logged_in).and_then(|_| {
+            if logged_in {
+                session_expiry::record_authenticated(&conn, account_id)
+            } else {
+                session_expiry::record_expired(&conn, account_id)
+            }
+        });
+    if let Err(e) = stored {
+        warn!("[账号自检] account={} 保存登录状态失败：{}", account_id, e);
+    }
+}
@@ -0,0 +1,350 @@
+use super::selector_check::{self, SelectorCheck, STATUS_INVALID};
+use crate::browser::chrome;
+use crate::browser::probe::ProbeSession;
+use crate::browser::signals::PageSnapshot;
+use crate::platforms::registry::{self, PlatformEntry};
+use crate::platforms::selector_overrides::{self, InvalidSelector, SelectorOverrides};
+use crate::platforms::{self, PlatformPublishConfig};
+use anyhow::{Context, Result};
+use chromiumoxide::page::Page;
+use log::{info, warn};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Command-line switch that runs the smoke test instead of the app
+pub const CLI_FLAG: &str = "--config-smoke-test";
+
+macro_rules! builtin_fixture {
+    ($platform:literal) => {
+        (
+            $platform,
+            include_str!(concat!("../../fixtures/smoke/", $platform, ".html")),
+        )
+    };
+}
+
+/// Upload page snapshots shipped in `src-tauri/fixtures/smoke`, one per
+/// platform; a `<platform>.html` in the `--fixtures` directory replaces one
+const BUILTIN_FIXTURES: &[(&str, &str)] = &[
+    builtin_fixture!("douyin"),
+    builtin_fixture!("xiaohongshu"),
+    builtin_fixture!("bilibili"),
+    builtin_fixture!("wechat"),
+    builtin_fixture!("youtube"),
+    builtin_fixture!("kuaishou"),
+    builtin_fixture!("zhihu"),
+    builtin_fixture!("tiktok"),
+    builtin_fixture!("weibo"),
+    builtin_fixture!("instagram"),
+];
+
+const BLANK_PAGE: &str = "<html><head></head><body></body></html>";
+
+enum Fixture {
+    File(PathBuf),
+    Builtin(&'static str, &'static str),
+}
+
+impl Fixture {
+    fn find(platform: &str, fixtures_dir: Option<&Path>) -> Option<Self> {
+        let file = fixtures_dir
+            .map(|dir| dir.join(format!("{}.html", platform)))
+            .filter(|path| path.is_file());
+        match file {
+            Some(path) => Some(Self::File(path)),
+            None => BUILTIN_FIXTURES
+                .iter()
+                .find(|(id, _)| *id == platform)
+                .map(|(id, html)| Self::Builtin(id, html)),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Self::File(path) => path.to_string_lossy().to_string(),
+            Self::Builtin(id, _) => format!("builtin:{}", id),
+        }
+    }
+
+    fn html(&self) -> Result<String> {
+        match self {
+            Self::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("读取 fixture {} 失败", path.display())),
+            Self::Builtin(_, html) => Ok(html.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformSmokeReport {
+    pub platform: String,
+    /// Fixture the scripts ran against, a path or `builtin:<platform>`; None
+    /// when the platform has none, which fails the test
+    pub fixture: Option<String>,
+    /// Schema, syntax and script errors; any entry fails the test
+    pub errors: Vec<InvalidSelector>,
+    /// Selector results on the fixture; `no_match` is listed but does not fail
+    pub checks: Vec<SelectorCheck>,
+    /// Upload signals the fixture already shows
+    pub signal_hits: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigSmokeReport {
+    pub passed: bool,
+    /// Set when headless Chrome could not run; only the static checks were done
+    pub browser_error: Option<String>,
+    pub platforms: Vec<PlatformSmokeReport>,
+}
+
+/// Load every platform config with its override file from `app_data_dir`,
+/// check all selectors statically, then in headless Chrome compile every
+/// marker script the automation runs and run the selector and upload signal
+/// scripts against the platform's fixture (`<fixtures_dir>/<platform>.html`,
+/// else the built-in one). Without Chrome only the static checks run and the
+/// test fails.
+pub async fn run(app_data_dir: Option<&Path>, fixtures_dir: Option<&Path>) -> ConfigSmokeReport {
+    let mut reports = registry::entries()
+        .iter()
+        .map(|entry| {
+            let overrides = app_data_dir
+                .map(|dir| selector_overrides::load(dir, entry.id()))
+                .unwrap_or_else(|| SelectorOverrides {
+                    platform: entry.id().to_string(),
+                    ..Default::default()
+                });
+            let report = static_check(entry, &overrides, fixtures_dir);
+            (entry, overrides, report)
+        })
+        .collect::<Vec<_>>();
+
+    let browser_error = match HeadlessBrowser::open().await {
+        Ok(browser) => {
+            for (entry, overrides, report) in &mut reports {
+                let page = &browser.probe.page;
+                if let Err(e) = browser_check(page, entry, overrides, fixtures_dir, report).await {
+                    report
+                        .errors
+                        .push(issue(entry.id(), "", "", format!("{:#}", e)));
+                }
+            }
+            browser.close().await;
+            None
+        }
+        Err(e) => {
+            warn!("[配置冒烟测试] 无法启动无头 Chrome，只做静态检查：{:#}", e);
+            Some(format!("{:#}", e))
+        }
+    };
+
+    let platforms = reports
+        .into_iter()
+        .map(|(_, _, report)| report)
+        .collect::<Vec<_>>();
+    let errors = platforms.iter().map(|p| p.errors.len()).sum::<usize>();
+    let passed = errors == 0 && browser_error.is_none();
+    info!(
+        "[配置冒烟测试] platforms={} errors={} passed={}",
+        platforms.len(),
+        errors,
+        passed
+    );
+    ConfigSmokeReport {
+        passed,
+        browser_error,
+        platforms,
+    }
+}
+
+fn issue(platform: &str, field: &str, selector: &str, reason: String) -> InvalidSelector {
+    InvalidSelector {
+        platform: platform.to_string(),
+        field: field.to_string(),
+        selector: selector.to_string(),
+        reason,
+    }
+}
+
+fn static_check(
+    entry: &PlatformEntry,
+    overrides: &SelectorOverrides,
+    fixtures_dir: Option<&Path>,
+) -> PlatformSmokeReport {
+    let cfg = entry.config;
+    let mut errors = overrides.invalid.clone();
+    let info = (entry.info)();
+    if info.id != cfg.id {
+        errors.push(issue(
+            cfg.id,
+            "id",
+            "",
+            format!("平台信息 id {} 与配置 id 不一致", info.id),
+        ));
+    }
+    if !cfg.upload_url.starts_with("https://") && !cfg.upload_url.starts_with("http://") {
+        errors.push(issue(
+            cfg.id,
+            "upload_url",
+            "",
+            format!("上传地址无效：{}", cfg.upload_url),
+        ));
+    }
+    for (field, selectors) in selector_overrides::builtin_selectors(cfg) {
+        for selector in selectors {
+            if let Err(reason) = selector_overrides::check_syntax(selector) {
+                errors.push(issue(cfg.id, field, selector, reason));
+            }
+        }
+    }
+    let fixture = Fixture::find(cfg.id, fixtures_dir);
+    if fixture.is_none() {
+        errors.push(issue(
+            cfg.id,
+            "fixture",
+            "",
+            "缺少上传页 fixture".to_string(),
+        ));
+    }
+    PlatformSmokeReport {
+        platform: cfg.id.to_string(),
+        fixture: fixture.map(|fixture| fixture.label()),
+        errors,
+        checks: Vec::new(),
+        signal_hits: Vec::new(),
+    }
+}
+
+async fn browser_check(
+    page: &Page,
+    entry: &PlatformEntry,
+    overrides: &SelectorOverrides,
+    fixtures_dir: Option<&Path>,
+    report: &mut PlatformSmokeReport,
+) -> Result<()> {
+    let html = match Fixture::find(entry.id(), fixtures_dir) {
+        Some(fixture) => fixture.html()?,
+        None => BLANK_PAGE.to_string(),
+    };
+    page.set_content(html).await.context("加载 fixture 失败")?;
+    compile_marker_scripts(page, entry.config, report).await?;
+
+    let checks =
+        selector_check::evaluate(page, &selector_check::candidates(entry.config, overrides))
+            .await
+            .context("执行选择器检测脚本失败")?;
+    for check in checks.iter().filter(|check| check.status == STATUS_INVALID) {
+        report.errors.push(issue(
+            entry.id(),
+            &check.field,
+            &check.selector,
+            check.error.clone().unwrap_or_default(),
+        ));
+    }
+    report.checks = checks;
+
+    let signals = &entry.config.upload_signals;
+    let raw: String = page
+        .evaluate(signals.script().as_str())
+        .await
+        .context("上传信号脚本执行失败")?
+        .into_value()?;
+    let snapshot: PageSnapshot = serde_json::from_str(&raw).context("解析上传信号结果失败")?;
+    report.signal_hits = signals
+        .hits(&snapshot)
+        .into_iter()
+        .map(|hit| hit.signal)
+        .collect();
+    Ok(())
+}
+
+/// Compile (without running) each detection script the automation evaluates
+/// on the page, so marker text that breaks a script shows up here
+async fn compile_marker_scripts(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    report: &mut PlatformSmokeReport,
+) -> Result<()> {
+    for (name, script) in platforms::marker_scripts(cfg) {
+        let check = format!(
+            "(function() {{ try {{ new Function({}); return ''; }} catch (e) {{ return String(e); }} }})()",
+            serde_json::to_string(&script)?
+        );
+        let error: String = page
+            .evaluate(check.as_str())
+            .await
+            .with_context(|| format!("编译 {} 脚本失败", name))?
+            .into_value()?;
+        if !error.is_empty() {
+            report.errors.push(issue(cfg.id, name, "", error));
+        }
+    }
+    Ok(())
+}
+
+/// Chrome in a throwaway profile, so no account session is touched
+struct HeadlessBrowser {
+    probe: ProbeSession,
+    profile_dir: PathBuf,
+}
+
+impl HeadlessBrowser {
+    async fn open() -> Result<Self> {
+        let chrome_path = chrome::detect_chrome()?;
+        let profile_dir =
+            std::env::temp_dir().join(format!("mpp-config-smoke-{}", std::process::id()));
+        std::fs::create_dir_all(&profile_dir)?;
+        let probe = ProbeSession::open(
+            &chrome_path,
+            &profile_dir,
+            "about:blank",
+            &["--headless=new".to_string()],
+        )
+        .await?;
+        Ok(Self { probe, profile_dir })
+    }
+
+    async fn close(self) {
+        self.probe.close().await;
+        if let Err(e) = std::fs::remove_dir_all(&self.profile_dir) {
+            warn!("[配置冒烟测试] 删除临时 profile 失败：{}", e);
+        }
+    }
+}
+
+/// `--config-smoke-test [--data-dir DIR] [--fixtures DIR]`: print the report
+/// as JSON and return the process exit code (0 passed, 1 failed, 2 bad usage).
+/// Without `--data-dir` only the built-in configs are checked; without
+/// `--fixtures` the built-in fixtures are used.
+pub fn cli(args: &[String]) -> i32 {
+    let mut data_dir = None;
+    let mut fixtures_dir = None;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        let slot = match arg.as_str() {
+            CLI_FLAG => continue,
+            "--data-dir" => &mut data_dir,
+            "--fixtures" => &mut fixtures_dir,
+            other => {
+                eprintln!("unknown argument: {}", other);
+                return 2;
+            }
+        };
+        match rest.next() {
+            Some(value) => *slot = Some(PathBuf::from(value)),
+            None => {
+                eprintln!("{} needs a directory", arg);
+                return 2;
+            }
+        }
+    }
+    let report = tauri::async_runtime::block_on(run(data_dir.as_deref(), fixtures_dir.as_deref()));
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("failed to serialize report: {}", e),
+    }
+    if report.passed {
+        0
+    } else {
+        1
+    }
+}
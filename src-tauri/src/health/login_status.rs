@@ -0,0 +1,345 @@
+use super::{session_expiry, url_drift};
+use crate::browser::chrome;
+use crate::browser::page_ops::PageOps;
+use crate::browser::probe::ProbeSession;
+use crate::browser::profile_lock;
+use crate::database::queries::{self, Account};
+use crate::database::Database;
+use crate::platforms::{self, PlatformPublishConfig};
+use crate::tasks;
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const DEFAULT_CONCURRENCY: usize = 3;
+/// Each probe may start its own Chrome; more than this slows the machine down
+/// more than it saves time.
+const MAX_CONCURRENCY: usize = 6;
+
+/// The account's profile is in use by a login window or a publish task
+pub const STATUS_BUSY: &str = "busy";
+pub const STATUS_UNSUPPORTED: &str = "unsupported";
+
+/// Emitted when a login window reaches a page only a logged-in session sees
+pub const LOGIN_COMPLETED_EVENT: &str = "login_completed";
+const LOGIN_WATCH_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const LOGIN_WATCH_POLL: Duration = Duration::from_secs(3);
+/// Failed polls in a row after which the login window counts as closed
+const LOGIN_WATCH_MAX_MISSES: u32 = 5;
+/// Time allowed for redirects and the page header to render before checking markers
+const PROBE_SETTLE_SECS: u64 = 6;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountLoginCheck {
+    pub account_id: i64,
+    pub platform: String,
+    pub display_name: String,
+    /// `ok`, `login_required`, `drift`, `error`, `busy` or `unsupported`
+    pub status: String,
+    /// New login state; None when the probe was inconclusive and the stored
+    /// state was left as is
+    pub is_logged_in: Option<bool>,
+    pub final_url: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginCompleted {
+    pub account_id: i64,
+    pub platform: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginRefreshSummary {
+    pub total: usize,
+    pub logged_in: usize,
+    pub logged_out: usize,
+    /// Probes that could not decide either way (errors, drift, busy profiles)
+    pub unknown: usize,
+    pub checks: Vec<AccountLoginCheck>,
+}
+
+/// Probe the login state of every account (or every account of `platform`),
+/// at most `concurrency` at a time, and store the results. Accounts whose
+/// profile is locked by another operation are skipped rather than disturbed.
+pub async fn refresh_all(
+    db: &Database,
+    platform: Option<&str>,
+    concurrency: usize,
+) -> Result<LoginRefreshSummary> {
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+    };
+    let accounts = accounts
+        .into_iter()
+        .filter(|account| platform.is_none_or(|p| account.platform == p))
+        .collect::<Vec<_>>();
+    let checks = run_checks(db, accounts, concurrency).await?;
+
+    let logged_in = checks
+        .iter()
+        .filter(|c| c.is_logged_in == Some(true))
+        .count();
+    let logged_out = checks
+        .iter()
+        .filter(|c| c.is_logged_in == Some(false))
+        .count();
+    let summary = LoginRefreshSummary {
+        total: checks.len(),
+        logged_in,
+        logged_out,
+        unknown: checks.len() - logged_in - logged_out,
+        checks,
+    };
+    info!(
+        "[登录巡检] 完成：已登录 {}，未登录 {}，未确定 {}",
+        summary.logged_in, summary.logged_out, summary.unknown
+    );
+    Ok(summary)
+}
+
+/// Probe the login state of the given accounts and store the results; unknown
+/// ids are ignored
+pub async fn check_accounts(
+    db: &Database,
+    account_ids: &[i64],
+    concurrency: usize,
+) -> Result<Vec<AccountLoginCheck>> {
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+    };
+    let accounts = accounts
+        .into_iter()
+        .filter(|account| account_ids.contains(&account.id))
+        .collect::<Vec<_>>();
+    run_checks(db, accounts, concurrency).await
+}
+
+async fn run_checks(
+    db: &Database,
+    accounts: Vec<Account>,
+    concurrency: usize,
+) -> Result<Vec<AccountLoginCheck>> {
+    let chrome_path = chrome::detect_chrome()?;
+    let concurrency = concurrency.clamp(1, MAX_CONCURRENCY);
+    info!(
+        "[登录巡检] 开始检测 {} 个账号，并发 {}",
+        accounts.len(),
+        concurrency
+    );
+
+    let mut checks = stream::iter(accounts)
+        .map(|account| {
+            let chrome_path = chrome_path.clone();
+            async move { check_one(&chrome_path, &account).await }
+        })
+        .buffer_unordered(concurrency)
+        .collect::<Vec<_>>()
+        .await;
+    checks.sort_by_key(|check| check.account_id);
+
+    let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    for check in &checks {
+        let Some(is_logged_in) = check.is_logged_in else {
+            continue;
+        };
+        let stored = queries::update_account_login_status(&conn, check.account_id, is_logged_in)
+            .and_then(|_| {
+                if is_logged_in {
+                    session_expiry::record_authenticated(&conn, check.account_id)
+                } else {
+                    session_expiry::record_expired(&conn, check.account_id)
+                }
+            });
+        if let Err(e) = stored {
+            warn!("[登录巡检] account={} 保存失败：{}", check.account_id, e);
+        }
+    }
+    Ok(checks)
+}
+
+async fn check_one(chrome_path: &Path, account: &Account) -> AccountLoginCheck {
+    let mut check = AccountLoginCheck {
+        account_id: account.id,
+        platform: account.platform.clone(),
+        display_name: account.display_name.clone(),
+        status: STATUS_UNSUPPORTED.to_string(),
+        is_logged_in: None,
+        final_url: None,
+        detail: String::new(),
+    };
+    let Some(cfg) = platforms::publish_config(&account.platform) else {
+        check.detail = format!("不支持的平台：{}", account.platform);
+        return check;
+    };
+    if let Some(lock) = profile_lock::inspect(&PathBuf::from(&account.chrome_profile_dir))
+        .filter(|lock| !lock.stale)
+    {
+        check.status = STATUS_BUSY.to_string();
+        check.detail = format!("账号浏览器正被占用：{}", lock.holder);
+        return check;
+    }
+
+    let (status, final_url, detail) = probe_login(chrome_path, account, cfg).await;
+    check.is_logged_in = match status {
+        url_drift::STATUS_OK => Some(true),
+        url_drift::STATUS_LOGIN_REQUIRED => Some(false),
+        _ => None,
+    };
+    check.status = status.to_string();
+    check.final_url = final_url;
+    check.detail = detail;
+    check
+}
+
+/// Open the creator page in the account's profile and decide the login state:
+/// a login URL or a login prompt means logged out, a header avatar means
+/// logged in, otherwise the landing URL decides as in the URL health check.
+async fn probe_login(
+    chrome_path: &Path,
+    account: &Account,
+    cfg: &PlatformPublishConfig,
+) -> (&'static str, Option<String>, String) {
+    let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+    let probe = match ProbeSession::open(
+        chrome_path,
+        &profile_dir,
+        cfg.upload_url,
+        &account.chrome_flags,
+    )
+    .await
+    {
+        Ok(probe) => probe,
+        Err(e) => return (url_drift::STATUS_ERROR, None, e.to_string()),
+    };
+    tokio::time::sleep(Duration::from_secs(PROBE_SETTLE_SECS)).await;
+    let final_url = probe.page.url().await.ok().flatten().unwrap_or_default();
+    let markers = if platforms::is_login_url(&final_url, &cfg.login_guard) {
+        None
+    } else {
+        Some(login_markers(&probe.page, cfg).await)
+    };
+    probe.close().await;
+
+    match markers {
+        Some((login_text, _)) if !login_text.is_empty() => (
+            url_drift::STATUS_LOGIN_REQUIRED,
+            Some(final_url),
+            format!("页面出现登录提示：{}", login_text),
+        ),
+        Some((_, avatar)) if !avatar.is_empty() => (
+            url_drift::STATUS_OK,
+            Some(final_url),
+            format!("检测到登录头像：{}", avatar),
+        ),
+        _ => {
+            let (status, detail) = url_drift::classify_landing(&final_url, cfg);
+            (status, Some(final_url), detail)
+        }
+    }
+}
+
+/// First visible login prompt text and first visible avatar selector on the
+/// page; empty strings when none matched
+async fn login_markers<P: PageOps>(page: &P, cfg: &PlatformPublishConfig) -> (String, String) {
+    let to_js = |values: &[&str]| serde_json::to_string(values).unwrap_or_else(|_| "[]".into());
+    let js = format!(
+        r#"
+        (function(textMarkers, avatarSelectors) {{
+            const text = (document.body && document.body.innerText) || '';
+            const loginText = textMarkers.find((marker) => text.includes(marker)) || '';
+            const avatar = avatarSelectors.find((sel) => {{
+                try {{
+                    return Array.from(document.querySelectorAll(sel)).some((el) => {{
+                        const rect = el.getBoundingClientRect();
+                        return rect.width > 0 && rect.height > 0;
+                    }});
+                }} catch (_) {{
+                    return false;
+                }}
+            }}) || '';
+            return {{ loginText, avatar }};
+        }})({}, {})
+        "#,
+        to_js(cfg.login_guard.text_markers),
+        to_js(cfg.login_guard.avatar_selectors)
+    );
+    let Ok(value) = page.evaluate_json(&js).await else {
+        return (String::new(), String::new());
+    };
+    let field = |key: &str| {
+        value
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    (field("loginText"), field("avatar"))
+}
+
+/// Watch the login window on `port` until one of its pages matches the
+/// platform's login guard success markers, then mark the account logged in and
+/// emit LOGIN_COMPLETED_EVENT. Gives up when the window closes or times out.
+pub fn watch_login(app: AppHandle, account_id: i64, platform: String, port: u16) {
+    let Some(cfg) = platforms::publish_config(&platform) else {
+        return;
+    };
+    if cfg.login_guard.success_url_markers.is_empty() {
+        return;
+    }
+    tasks::global().spawn(format!("login_watch:{}", account_id), async move {
+        let start = Instant::now();
+        let mut misses = 0;
+        while start.elapsed() < LOGIN_WATCH_TIMEOUT {
+            tokio::time::sleep(LOGIN_WATCH_POLL).await;
+            let urls = match chrome::page_urls(port).await {
+                Ok(urls) => {
+                    misses = 0;
+                    urls
+                }
+                Err(_) => {
+                    misses += 1;
+                    if misses >= LOGIN_WATCH_MAX_MISSES {
+                        info!("[登录确认] account={} 登录窗口已关闭", account_id);
+                        return;
+                    }
+                    continue;
+                }
+            };
+            let Some(url) = urls
+                .into_iter()
+                .find(|url| platforms::is_login_success_url(url, &cfg.login_guard))
+            else {
+                continue;
+            };
+            info!("[登录确认] account={} 登录完成：{}", account_id, url);
+            if let Err(e) = mark_logged_in(&app, account_id) {
+                warn!("[登录确认] account={} 保存失败：{}", account_id, e);
+            }
+            let event = LoginCompleted {
+                account_id,
+                platform,
+                url,
+            };
+            if let Err(e) = app.emit(LOGIN_COMPLETED_EVENT, event) {
+                warn!("[登录确认] 发送事件失败：{}", e);
+            }
+            return;
+        }
+        info!("[登录确认] account={} 超时仍未确认登录", account_id);
+    });
+}
+
+fn mark_logged_in(app: &AppHandle, account_id: i64) -> Result<()> {
+    let db = app.state::<Database>();
+    let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    queries::update_account_login_status(&conn, account_id, true)?;
+    session_expiry::record_login(&conn, account_id)
+}
@@ -0,0 +1,8 @@
+pub mod account_test;
+pub mod config_smoke;
+pub mod login_status;
+pub mod review_queue;
+pub mod selector_check;
+pub mod session_expiry;
+pub mod session_monitor;
+pub mod url_drift;
@@ -0,0 +1,126 @@
+use crate::browser::probe::ProbeSession;
+use crate::database::queries::{self, AccountReviewCount};
+use crate::database::Database;
+use crate::platforms::wechat;
+use anyhow::{anyhow, Result};
+use chrono::{NaiveDateTime, Utc};
+use log::{info, warn};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+
+/// Hold new WeChat Channels publishes of an account while this many of its
+/// posts are under review; 0 turns the hold off
+pub const SETTING_WECHAT_MAX_IN_REVIEW: &str = "wechat_max_in_review";
+pub const DEFAULT_WECHAT_MAX_IN_REVIEW: i64 = 5;
+
+/// A scraped count is trusted this long before the content manager is read again
+const COUNT_TTL_MINUTES: i64 = 10;
+/// Time for the content manager list to render
+const PAGE_SETTLE_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReviewQueueStatus {
+    pub account_id: i64,
+    pub in_review: i64,
+    pub checked_at: String,
+    pub threshold: i64,
+    /// New publishes of this account are held until `in_review` drops below `threshold`
+    pub held: bool,
+}
+
+pub fn threshold(conn: &Connection) -> Result<i64> {
+    Ok(queries::get_setting(conn, SETTING_WECHAT_MAX_IN_REVIEW)?
+        .and_then(|raw| raw.trim().parse::<i64>().ok())
+        .unwrap_or(DEFAULT_WECHAT_MAX_IN_REVIEW)
+        .max(0))
+}
+
+fn status(count: AccountReviewCount, threshold: i64) -> ReviewQueueStatus {
+    ReviewQueueStatus {
+        account_id: count.account_id,
+        in_review: count.in_review,
+        held: threshold > 0 && count.in_review >= threshold,
+        checked_at: count.checked_at,
+        threshold,
+    }
+}
+
+fn is_fresh(count: &AccountReviewCount) -> bool {
+    NaiveDateTime::parse_from_str(&count.checked_at, "%Y-%m-%d %H:%M:%S")
+        .map(|at| (Utc::now() - at.and_utc()).num_minutes() < COUNT_TTL_MINUTES)
+        .unwrap_or(false)
+}
+
+/// Read the account's under-review count from the WeChat content manager in a
+/// background tab and store it
+pub async fn refresh(
+    db: &Database,
+    chrome_path: &Path,
+    account_id: i64,
+    profile_dir: &Path,
+) -> Result<ReviewQueueStatus> {
+    let chrome_flags = {
+        let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+        queries::get_account_chrome_flags(&conn, account_id)?
+    };
+    let probe = ProbeSession::open(
+        chrome_path,
+        profile_dir,
+        wechat::CONTENT_MANAGER_URL,
+        &chrome_flags,
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_secs(PAGE_SETTLE_SECS)).await;
+    let counted = wechat::count_in_review(&probe.page).await;
+    probe.close().await;
+    let in_review = counted? as i64;
+
+    let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+    queries::upsert_account_review_count(&conn, account_id, in_review)?;
+    let count = queries::get_account_review_count(&conn, account_id)?
+        .ok_or_else(|| anyhow!("review count of account {} was not stored", account_id))?;
+    info!(
+        "[视频号审核队列] account={} 审核中 {}",
+        account_id, in_review
+    );
+    Ok(status(count, threshold(&conn)?))
+}
+
+/// Checked before each WeChat publish, while the profile is still free. Uses
+/// the stored count when it is recent and scrapes otherwise. Returns the status
+/// when the publish must be held; a failed scrape never holds a publish.
+pub async fn hold_before_publish(
+    db: &Database,
+    chrome_path: &Path,
+    account_id: i64,
+    profile_dir: &Path,
+) -> Option<ReviewQueueStatus> {
+    let cached = {
+        let conn = db.conn.lock().ok()?;
+        let threshold = threshold(&conn).ok()?;
+        if threshold == 0 {
+            return None;
+        }
+        queries::get_account_review_count(&conn, account_id)
+            .ok()
+            .flatten()
+            .filter(is_fresh)
+            .map(|count| status(count, threshold))
+    };
+    let current = match cached {
+        Some(current) => current,
+        None => match refresh(db, chrome_path, account_id, profile_dir).await {
+            Ok(current) => current,
+            Err(e) => {
+                warn!(
+                    "[视频号审核队列] account={} 读取审核中数量失败，不暂缓发布：{}",
+                    account_id, e
+                );
+                return None;
+            }
+        },
+    };
+    current.held.then_some(current)
+}
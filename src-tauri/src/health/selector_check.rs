@@ -0,0 +1,334 @@
+use crate::browser::chrome;
+use crate::browser::probe::ProbeSession;
+use crate::database::queries::{self, Account};
+use crate::database::Database;
+use crate::platforms::selector_overrides::{self, SelectorOverrides};
+use crate::platforms::{self, PlatformPublishConfig};
+use anyhow::{Context, Result};
+use chromiumoxide::page::{Page, ScreenshotParams};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const STATUS_INVALID: &str = "invalid";
+
+pub const SOURCE_BUILTIN: &str = "builtin";
+pub const SOURCE_OVERRIDE: &str = "override";
+
+/// Time allowed for the upload page to render before selectors are counted.
+const PAGE_SETTLE_SECS: u64 = 8;
+
+/// Each selector is first parsed against a detached fragment, so a syntax error
+/// is reported as `invalid` instead of aborting the whole script; only then is
+/// it counted against the live document.
+const CHECK_SELECTORS_JS: &str = r#"
+(function(selectors) {
+    const scratch = document.createDocumentFragment();
+    return JSON.stringify(selectors.map((selector) => {
+        try {
+            scratch.querySelector(selector);
+        } catch (e) {
+            return { status: 'invalid', count: 0, error: String((e && e.message) || e) };
+        }
+        let count = 0;
+        try {
+            count = document.querySelectorAll(selector).length;
+        } catch (e) {
+            return { status: 'invalid', count: 0, error: String((e && e.message) || e) };
+        }
+        return { status: count > 0 ? 'ok' : 'no_match', count, error: '' };
+    }));
+})
+"#;
+
+/// Outlines every element hit by a selector, colored per config field, with a
+/// small label naming the field, so the gallery screenshot shows what each
+/// selector currently targets. Invalid selectors are skipped.
+const HIGHLIGHT_JS: &str = r#"
+(function(entries) {
+    const palette = ['#e6194b', '#3cb44b', '#4363d8', '#f58231', '#911eb4',
+        '#42d4f4', '#f032e6', '#9a6324', '#808000'];
+    const fields = [...new Set(entries.map((e) => e.field))];
+    let marked = 0;
+    for (const entry of entries) {
+        const color = palette[fields.indexOf(entry.field) % palette.length];
+        let nodes = [];
+        try {
+            nodes = Array.from(document.querySelectorAll(entry.selector));
+        } catch (_) {
+            continue;
+        }
+        for (const el of nodes) {
+            if (el.dataset.mpubHighlighted) continue;
+            el.dataset.mpubHighlighted = '1';
+            el.style.outline = '3px solid ' + color;
+            el.style.outlineOffset = '-2px';
+            const rect = el.getBoundingClientRect();
+            if (rect.width < 1 || rect.height < 1) continue;
+            const label = document.createElement('div');
+            label.textContent = entry.field + (entry.source === 'override' ? ' (override)' : '');
+            label.style.cssText = 'position:absolute;z-index:2147483647;padding:1px 4px;'
+                + 'font:11px monospace;color:#fff;pointer-events:none;background:' + color + ';'
+                + 'left:' + (rect.left + window.scrollX) + 'px;top:' + Math.max(0, rect.top + window.scrollY - 14) + 'px;';
+            document.body.appendChild(label);
+            marked += 1;
+        }
+    }
+    return marked;
+})
+"#;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectorCheck {
+    pub field: String,
+    pub selector: String,
+    /// `builtin` or `override`
+    pub source: String,
+    /// `ok`, `no_match` or `invalid`
+    pub status: String,
+    pub match_count: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlatformSelectorReport {
+    pub platform: String,
+    pub account_id: Option<i64>,
+    pub page_url: Option<String>,
+    /// Set when the page could not be checked at all
+    pub error: Option<String>,
+    /// Screenshot with matched elements outlined, when a gallery dir was given
+    pub screenshot_path: Option<String>,
+    pub checks: Vec<SelectorCheck>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageResult {
+    status: String,
+    count: usize,
+    error: String,
+}
+
+/// Evaluate every built-in and override selector of each platform on its upload
+/// page, using one logged-in account per platform. Overrides rejected at load
+/// time are listed as `invalid` without being sent to the page. With
+/// `gallery_dir`, each page is also saved as `<platform>.png` with the matched
+/// elements highlighted.
+pub async fn run_all(
+    db: &Database,
+    app_data_dir: &Path,
+    gallery_dir: Option<&Path>,
+) -> Result<Vec<PlatformSelectorReport>> {
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+    };
+    let chrome_path = chrome::detect_chrome()?;
+
+    let mut reports = Vec::new();
+    for info in platforms::all_platforms() {
+        let Some(cfg) = platforms::publish_config(&info.id) else {
+            continue;
+        };
+        let overrides = selector_overrides::load(app_data_dir, cfg.id);
+        let mut checks = overrides
+            .invalid
+            .iter()
+            .map(|invalid| SelectorCheck {
+                field: invalid.field.clone(),
+                selector: invalid.selector.clone(),
+                source: SOURCE_OVERRIDE.to_string(),
+                status: STATUS_INVALID.to_string(),
+                match_count: 0,
+                error: Some(invalid.reason.clone()),
+            })
+            .collect::<Vec<_>>();
+
+        let candidates = candidates(cfg, &overrides);
+
+        let account = accounts
+            .iter()
+            .find(|a| a.platform == info.id && a.is_logged_in);
+        let mut report = PlatformSelectorReport {
+            platform: info.id.clone(),
+            account_id: account.map(|a| a.id),
+            page_url: None,
+            error: None,
+            screenshot_path: None,
+            checks: Vec::new(),
+        };
+        match account {
+            Some(account) => match check_page(
+                &chrome_path,
+                account,
+                cfg,
+                &candidates,
+                gallery_dir.map(|dir| dir.join(format!("{}.png", cfg.id))),
+            )
+            .await
+            {
+                Ok(PageCheck {
+                    page_url,
+                    results,
+                    screenshot_path,
+                }) => {
+                    report.page_url = Some(page_url);
+                    report.screenshot_path = screenshot_path;
+                    checks.extend(results);
+                }
+                Err(e) => report.error = Some(e.to_string()),
+            },
+            None => report.error = Some("没有已登录的账号可用于检测".to_string()),
+        }
+
+        let invalid = checks.iter().filter(|c| c.status == STATUS_INVALID).count();
+        if invalid > 0 || report.error.is_some() {
+            warn!(
+                "[选择器巡检] platform={} invalid={} error={:?}",
+                cfg.id, invalid, report.error
+            );
+        } else {
+            info!("[选择器巡检] platform={} checked={}", cfg.id, checks.len());
+        }
+        report.checks = checks;
+        reports.push(report);
+    }
+    Ok(reports)
+}
+
+/// Every built-in selector of `cfg` followed by the loaded override ones, as
+/// `(field, selector, source)`
+pub(crate) fn candidates(
+    cfg: &PlatformPublishConfig,
+    overrides: &SelectorOverrides,
+) -> Vec<(String, String, &'static str)> {
+    let mut candidates = Vec::new();
+    for (field, selectors) in selector_overrides::builtin_selectors(cfg) {
+        for selector in selectors {
+            candidates.push((field.to_string(), selector.to_string(), SOURCE_BUILTIN));
+        }
+    }
+    for (field, selectors) in &overrides.fields {
+        for selector in selectors {
+            candidates.push((field.clone(), selector.clone(), SOURCE_OVERRIDE));
+        }
+    }
+    candidates
+}
+
+/// Run the selector check script on whatever document `page` shows now
+pub(crate) async fn evaluate(
+    page: &Page,
+    candidates: &[(String, String, &'static str)],
+) -> Result<Vec<SelectorCheck>> {
+    let selectors = candidates
+        .iter()
+        .map(|(_, selector, _)| selector.as_str())
+        .collect::<Vec<_>>();
+    let script = format!(
+        "{}({})",
+        CHECK_SELECTORS_JS.trim(),
+        serde_json::to_string(&selectors)?
+    );
+    let raw: String = page.evaluate(script.as_str()).await?.into_value()?;
+    let results: Vec<PageResult> = serde_json::from_str(&raw).context("解析选择器检测结果失败")?;
+    Ok(into_checks(candidates.to_vec(), results))
+}
+
+fn into_checks(
+    candidates: Vec<(String, String, &'static str)>,
+    results: Vec<PageResult>,
+) -> Vec<SelectorCheck> {
+    candidates
+        .into_iter()
+        .zip(results)
+        .map(|((field, selector, source), result)| SelectorCheck {
+            field,
+            selector,
+            source: source.to_string(),
+            status: result.status,
+            match_count: result.count,
+            error: Some(result.error).filter(|e| !e.is_empty()),
+        })
+        .collect()
+}
+
+struct PageCheck {
+    page_url: String,
+    results: Vec<SelectorCheck>,
+    screenshot_path: Option<String>,
+}
+
+async fn check_page(
+    chrome_path: &Path,
+    account: &Account,
+    cfg: &PlatformPublishConfig,
+    candidates: &[(String, String, &'static str)],
+    screenshot: Option<PathBuf>,
+) -> Result<PageCheck> {
+    let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+    let probe = ProbeSession::open(
+        chrome_path,
+        &profile_dir,
+        cfg.upload_url,
+        &account.chrome_flags,
+    )
+    .await?;
+    tokio::time::sleep(Duration::from_secs(PAGE_SETTLE_SECS)).await;
+
+    let evaluated = async {
+        let page_url = probe.page.url().await?.unwrap_or_default();
+        let results = evaluate(&probe.page, candidates).await?;
+        anyhow::Ok((page_url, results))
+    }
+    .await;
+    let screenshot_path = match (&evaluated, screenshot) {
+        (Ok(_), Some(path)) => match capture_highlighted(&probe, candidates, &path).await {
+            Ok(()) => Some(path.to_string_lossy().to_string()),
+            Err(e) => {
+                warn!("[选择器巡检] platform={} 截图失败：{}", cfg.id, e);
+                None
+            }
+        },
+        _ => None,
+    };
+    probe.close().await;
+
+    let (page_url, results) = evaluated.context("执行选择器检测脚本失败")?;
+    Ok(PageCheck {
+        page_url,
+        results,
+        screenshot_path,
+    })
+}
+
+async fn capture_highlighted(
+    probe: &ProbeSession,
+    candidates: &[(String, String, &str)],
+    path: &Path,
+) -> Result<()> {
+    let entries = candidates
+        .iter()
+        .map(|(field, selector, source)| {
+            serde_json::json!({ "field": field, "selector": selector, "source": source })
+        })
+        .collect::<Vec<_>>();
+    let script = format!(
+        "{}({})",
+        HIGHLIGHT_JS.trim(),
+        serde_json::to_string(&entries)?
+    );
+    probe
+        .page
+        .evaluate(script.as_str())
+        .await
+        .context("注入高亮样式失败")?;
+    let params = ScreenshotParams::builder().full_page(true).build();
+    probe
+        .page
+        .save_screenshot(params, path)
+        .await
+        .context("保存截图失败")?;
+    Ok(())
+}
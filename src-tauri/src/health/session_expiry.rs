@@ -0,0 +1,137 @@
+use crate::database::queries::{self, AccountSessionTimes};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use log::info;
+use rusqlite::Connection;
+use serde::Serialize;
+
+/// Rough session lifetimes used until enough expiries have been observed.
+const DEFAULT_LIFETIME_DAYS: &[(&str, f64)] = &[
+    ("douyin", 60.0),
+    ("xiaohongshu", 30.0),
+    ("bilibili", 180.0),
+    ("wechat", 4.0),
+    ("youtube", 180.0),
+    ("kuaishou", 30.0),
+    ("zhihu", 90.0),
+    ("tiktok", 60.0),
+    ("weibo", 30.0),
+    ("instagram", 90.0),
+];
+const FALLBACK_LIFETIME_DAYS: f64 = 30.0;
+/// Observed expiries needed before they replace the default lifetime
+const MIN_OBSERVATIONS: usize = 3;
+const OBSERVATION_WINDOW: i64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountExpiryEstimate {
+    pub account_id: i64,
+    pub platform: String,
+    pub is_logged_in: bool,
+    pub last_authenticated_at: Option<String>,
+    pub typical_lifetime_days: f64,
+    /// `observed` (median of recent expiries) or `default`
+    pub lifetime_source: &'static str,
+    pub estimated_expires_at: Option<String>,
+    pub days_until_expiry: Option<f64>,
+}
+
+fn parse_db_time(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+/// User confirmed a fresh login.
+pub fn record_login(conn: &Connection, account_id: i64) -> Result<()> {
+    queries::mark_account_logged_in(conn, account_id)
+}
+
+/// An automated action succeeded behind the platform's login wall.
+pub fn record_authenticated(conn: &Connection, account_id: i64) -> Result<()> {
+    queries::mark_account_authenticated(conn, account_id)
+}
+
+/// The session was found expired; remember how long it lasted, counted from
+/// the same point `estimate` counts from. The login time is cleared as it is
+/// read, so only the first report after a login records a sample and repeated
+/// checks of the same expired session do not skew the median.
+pub fn record_expired(conn: &Connection, account_id: i64) -> Result<()> {
+    let Some(times) = queries::take_account_session_times(conn, account_id)? else {
+        return Ok(());
+    };
+    let Some(basis) = session_basis(&times) else {
+        return Ok(());
+    };
+    let lifetime_days = (Utc::now() - basis).num_minutes() as f64 / (24.0 * 60.0);
+    if lifetime_days > 0.0 {
+        info!(
+            "[登录有效期] account={} platform={} observed_lifetime_days={:.1}",
+            account_id, times.platform, lifetime_days
+        );
+        queries::insert_session_lifetime(conn, &times.platform, lifetime_days)?;
+    }
+    Ok(())
+}
+
+/// Where a session's lifetime is counted from. Most platforms extend the
+/// session on activity, so this is the latest sign of life.
+fn session_basis(times: &AccountSessionTimes) -> Option<DateTime<Utc>> {
+    [
+        times.last_authenticated_at.as_deref(),
+        times.last_login_at.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .filter_map(parse_db_time)
+    .max()
+}
+
+fn typical_lifetime(conn: &Connection, platform: &str) -> Result<(f64, &'static str)> {
+    let mut observed = queries::get_recent_session_lifetimes(conn, platform, OBSERVATION_WINDOW)?;
+    if observed.len() >= MIN_OBSERVATIONS {
+        observed.sort_by(|a, b| a.total_cmp(b));
+        return Ok((observed[observed.len() / 2], "observed"));
+    }
+    let default = DEFAULT_LIFETIME_DAYS
+        .iter()
+        .find(|(id, _)| *id == platform)
+        .map(|(_, days)| *days)
+        .unwrap_or(FALLBACK_LIFETIME_DAYS);
+    Ok((default, "default"))
+}
+
+fn estimate(
+    times: AccountSessionTimes,
+    lifetime_days: f64,
+    source: &'static str,
+) -> AccountExpiryEstimate {
+    // A logged-out account has no session left to expire
+    let basis = session_basis(&times).filter(|_| times.is_logged_in);
+    let expires_at =
+        basis.map(|at| at + chrono::Duration::minutes((lifetime_days * 24.0 * 60.0) as i64));
+    let days_until_expiry = expires_at.map(|at| {
+        let days = (at - Utc::now()).num_minutes() as f64 / (24.0 * 60.0);
+        (days * 10.0).round() / 10.0
+    });
+
+    AccountExpiryEstimate {
+        account_id: times.account_id,
+        platform: times.platform,
+        is_logged_in: times.is_logged_in,
+        last_authenticated_at: times.last_authenticated_at,
+        typical_lifetime_days: lifetime_days,
+        lifetime_source: source,
+        estimated_expires_at: expires_at.map(|at| at.to_rfc3339()),
+        days_until_expiry,
+    }
+}
+
+pub fn estimate_all(conn: &Connection) -> Result<Vec<AccountExpiryEstimate>> {
+    let mut estimates = Vec::new();
+    for times in queries::get_account_session_times(conn)? {
+        let (lifetime_days, source) = typical_lifetime(conn, &times.platform)?;
+        estimates.push(estimate(times, lifetime_days, source));
+    }
+    Ok(estimates)
+}
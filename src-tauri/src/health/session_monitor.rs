@@ -0,0 +1,132 @@
+use super::login_status::{self, AccountLoginCheck};
+use crate::database::queries;
+use crate::database::Database;
+use crate::tasks::TaskRegistry;
+use anyhow::Result;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const SETTING_ENABLED: &str = "session_monitor_enabled";
+pub const SETTING_INTERVAL_MINUTES: &str = "session_monitor_interval_minutes";
+
+/// Emitted when a probe finds an account that was logged in is now logged out
+pub const ACCOUNT_LOGGED_OUT_EVENT: &str = "account_logged_out";
+
+const DEFAULT_INTERVAL_MINUTES: u64 = 6 * 60;
+/// Probing opens Chrome for every account; more often than this is not useful
+const MIN_INTERVAL_MINUTES: u64 = 15;
+/// Delay before the first run so it does not compete with app startup
+const STARTUP_DELAY_SECS: u64 = 180;
+const HISTORY_KEEP_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountLoggedOut {
+    pub account_id: i64,
+    pub platform: String,
+    pub display_name: String,
+    pub detail: String,
+}
+
+/// Probe every account, store each result in `account_health` and emit
+/// ACCOUNT_LOGGED_OUT_EVENT for accounts that flipped from logged in to logged
+/// out. Returns the checks of this run.
+pub async fn run_once(app: &AppHandle) -> Result<Vec<AccountLoginCheck>> {
+    let db = app.state::<Database>();
+    let was_logged_in = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+            .into_iter()
+            .map(|account| (account.id, account.is_logged_in))
+            .collect::<HashMap<_, _>>()
+    };
+    let summary = login_status::refresh_all(&db, None, login_status::DEFAULT_CONCURRENCY).await?;
+
+    {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        for check in &summary.checks {
+            if let Err(e) = queries::insert_account_health(
+                &conn,
+                check.account_id,
+                &check.status,
+                check.is_logged_in,
+                check.final_url.as_deref(),
+                Some(&check.detail),
+            ) {
+                warn!("[会话巡检] account={} 记录失败：{}", check.account_id, e);
+            }
+        }
+        if let Err(e) = queries::prune_account_health(&conn, HISTORY_KEEP_DAYS) {
+            warn!("[会话巡检] 清理历史记录失败：{}", e);
+        }
+    }
+
+    for check in &summary.checks {
+        let flipped = check.is_logged_in == Some(false)
+            && was_logged_in.get(&check.account_id).copied() == Some(true);
+        if !flipped {
+            continue;
+        }
+        warn!(
+            "[会话巡检] account={} platform={} 登录已失效：{}",
+            check.account_id, check.platform, check.detail
+        );
+        let event = AccountLoggedOut {
+            account_id: check.account_id,
+            platform: check.platform.clone(),
+            display_name: check.display_name.clone(),
+            detail: check.detail.clone(),
+        };
+        if let Err(e) = app.emit(ACCOUNT_LOGGED_OUT_EVENT, event) {
+            warn!("[会话巡检] 发送事件失败：{}", e);
+        }
+    }
+    Ok(summary.checks)
+}
+
+/// Run the monitor periodically while the user has it enabled in settings.
+/// Disabled by default because it opens Chrome windows in the background.
+pub fn spawn_periodic(app: AppHandle) {
+    let registry = app.state::<TaskRegistry>().inner().clone();
+    registry.spawn("session_monitor:periodic", async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            let (enabled, interval_minutes) = {
+                let db = app.state::<Database>();
+                let guard = db.conn.lock();
+                match guard {
+                    Ok(conn) => (
+                        queries::get_setting_bool(&conn, SETTING_ENABLED, false).unwrap_or(false),
+                        queries::get_setting(&conn, SETTING_INTERVAL_MINUTES)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.trim().parse::<u64>().ok())
+                            .map(|minutes| minutes.max(MIN_INTERVAL_MINUTES))
+                            .unwrap_or(DEFAULT_INTERVAL_MINUTES),
+                    ),
+                    Err(_) => (false, DEFAULT_INTERVAL_MINUTES),
+                }
+            };
+
+            if enabled {
+                match run_once(&app).await {
+                    Ok(checks) => {
+                        let logged_out = checks
+                            .iter()
+                            .filter(|c| c.is_logged_in == Some(false))
+                            .count();
+                        info!(
+                            "[会话巡检] 完成：{} 个账号，{} 个未登录",
+                            checks.len(),
+                            logged_out
+                        );
+                    }
+                    Err(e) => warn!("[会话巡检] 执行失败：{}", e),
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(interval_minutes * 60)).await;
+        }
+    });
+}
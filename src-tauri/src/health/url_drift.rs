@@ -0,0 +1,190 @@
+use super::session_expiry;
+use crate::browser::chrome;
+use crate::browser::probe::ProbeSession;
+use crate::database::queries::{self, Account, UrlHealthCheck};
+use crate::database::Database;
+use crate::platforms::{self, PlatformPublishConfig};
+use crate::tasks::TaskRegistry;
+use anyhow::{Context, Result};
+use log::{info, warn};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+pub const SETTING_ENABLED: &str = "url_health_check_enabled";
+pub const SETTING_INTERVAL_HOURS: &str = "url_health_check_interval_hours";
+
+pub const STATUS_OK: &str = "ok";
+pub const STATUS_DRIFT: &str = "drift";
+pub const STATUS_LOGIN_REQUIRED: &str = "login_required";
+pub const STATUS_NO_ACCOUNT: &str = "no_account";
+pub const STATUS_ERROR: &str = "error";
+
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+/// Delay before the first periodic run so it does not compete with app startup.
+const STARTUP_DELAY_SECS: u64 = 120;
+/// Time allowed for client-side redirects to settle before reading the final URL.
+const LANDING_SETTLE_SECS: u64 = 6;
+
+/// Check every platform's configured upload URL with one logged-in account and
+/// record the landing URL. Platforms without a logged-in account are recorded
+/// as `no_account` so the UI can tell them apart from healthy ones.
+pub async fn run_all(db: &Database) -> Result<Vec<UrlHealthCheck>> {
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+    };
+    let chrome_path = chrome::detect_chrome()?;
+
+    for info in platforms::all_platforms() {
+        let Some(cfg) = platforms::publish_config(&info.id) else {
+            continue;
+        };
+        let account = accounts
+            .iter()
+            .find(|a| a.platform == info.id && a.is_logged_in);
+        let (status, final_url, detail) = match account {
+            Some(account) => check_account(&chrome_path, account, cfg).await,
+            None => (
+                STATUS_NO_ACCOUNT,
+                None,
+                "没有已登录的账号可用于检测".to_string(),
+            ),
+        };
+        if status != STATUS_OK {
+            warn!(
+                "[URL巡检] platform={} status={} final_url={:?} detail={}",
+                info.id, status, final_url, detail
+            );
+        } else {
+            info!(
+                "[URL巡检] platform={} status=ok final_url={:?}",
+                info.id, final_url
+            );
+        }
+
+        let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        queries::insert_url_health_check(
+            &conn,
+            &info.id,
+            account.map(|a| a.id),
+            cfg.upload_url,
+            final_url.as_deref(),
+            status,
+            Some(&detail),
+        )?;
+        if let Some(account) = account {
+            let observed = match status {
+                STATUS_OK => session_expiry::record_authenticated(&conn, account.id),
+                STATUS_LOGIN_REQUIRED => session_expiry::record_expired(&conn, account.id),
+                _ => Ok(()),
+            };
+            if let Err(e) = observed {
+                warn!("[登录有效期] account={} 更新失败: {}", account.id, e);
+            }
+        }
+    }
+
+    let conn = db.conn.lock().map_err(|e| anyhow::anyhow!(e.to_string()))?;
+    queries::get_latest_url_health_checks(&conn)
+}
+
+/// Open the upload URL in the account's profile and classify where it lands
+pub async fn check_account(
+    chrome_path: &Path,
+    account: &Account,
+    cfg: &PlatformPublishConfig,
+) -> (&'static str, Option<String>, String) {
+    match load_landing_url(chrome_path, account, cfg.upload_url).await {
+        Ok(final_url) => {
+            let (status, detail) = classify_landing(&final_url, cfg);
+            (status, Some(final_url), detail)
+        }
+        Err(e) => (STATUS_ERROR, None, e.to_string()),
+    }
+}
+
+async fn load_landing_url(chrome_path: &Path, account: &Account, url: &str) -> Result<String> {
+    let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+    let probe = ProbeSession::open(chrome_path, &profile_dir, url, &account.chrome_flags).await?;
+
+    tokio::time::sleep(Duration::from_secs(LANDING_SETTLE_SECS)).await;
+    let final_url = probe.page.url().await.context("读取页面 URL 失败");
+    probe.close().await;
+    Ok(final_url?.unwrap_or_default())
+}
+
+fn url_path(url: &str) -> &str {
+    let rest = url.split("//").nth(1).unwrap_or(url);
+    let path = rest.find('/').map(|idx| &rest[idx..]).unwrap_or("/");
+    path.split(['?', '#']).next().unwrap_or("/")
+}
+
+/// Compare where the upload URL actually landed against the platform config.
+pub fn classify_landing(final_url: &str, cfg: &PlatformPublishConfig) -> (&'static str, String) {
+    if platforms::is_login_url(final_url, &cfg.login_guard) {
+        return (
+            STATUS_LOGIN_REQUIRED,
+            format!("跳转到登录页：{}", final_url),
+        );
+    }
+    if !final_url.contains(cfg.target_host) {
+        return (
+            STATUS_DRIFT,
+            format!("上传页域名变化：{} -> {}", cfg.target_host, final_url),
+        );
+    }
+
+    let configured_path = url_path(cfg.upload_url);
+    let final_path = url_path(final_url);
+    if platforms::is_target_url(final_url, cfg) {
+        let detail = if final_path.starts_with(configured_path) {
+            "上传页地址未变化".to_string()
+        } else {
+            format!("跳转到已知路径：{} -> {}", configured_path, final_path)
+        };
+        return (STATUS_OK, detail);
+    }
+    (
+        STATUS_DRIFT,
+        format!(
+            "上传页路径变化（新规范路径可能为 {}）：{} -> {}",
+            final_path, configured_path, final_path
+        ),
+    )
+}
+
+/// Run the check periodically while the user has it enabled in settings.
+/// Disabled by default because it opens Chrome windows in the background.
+pub fn spawn_periodic(app: AppHandle) {
+    let registry = app.state::<TaskRegistry>().inner().clone();
+    registry.spawn("url_health_check:periodic", async move {
+        tokio::time::sleep(Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        loop {
+            let db = app.state::<Database>();
+            let (enabled, interval_hours) = match db.conn.lock() {
+                Ok(conn) => (
+                    queries::get_setting_bool(&conn, SETTING_ENABLED, false).unwrap_or(false),
+                    queries::get_setting(&conn, SETTING_INTERVAL_HOURS)
+                        .ok()
+                        .flatten()
+                        .and_then(|v| v.trim().parse::<u64>().ok())
+                        .filter(|hours| *hours > 0)
+                        .unwrap_or(DEFAULT_INTERVAL_HOURS),
+                ),
+                Err(_) => (false, DEFAULT_INTERVAL_HOURS),
+            };
+
+            if enabled {
+                match run_all(&db).await {
+                    Ok(checks) => {
+                        let drifted = checks.iter().filter(|c| c.status == STATUS_DRIFT).count();
+                        info!("[URL巡检] 完成，{} 个平台疑似上传地址变化", drifted);
+                    }
+                    Err(e) => warn!("[URL巡检] 执行失败：{}", e),
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(interval_hours * 3600)).await;
+        }
+    });
+}
@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// File under the app data dir holding the hook configuration
+pub const HOOKS_FILE: &str = "hooks.json";
+
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 30;
+const MAX_HOOK_TIMEOUT_SECS: u64 = 600;
+/// Longest stdout/stderr excerpt kept in the log
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// Points in the publish pipeline where a hook script can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPoint {
+    /// Once per task, before the first account is processed
+    PrePublish,
+    /// Per account, after the video was uploaded and the form filled
+    PostUpload,
+    /// Once per task, after every account has a result
+    PostPublish,
+    /// Per account whose run failed (deferred runs are not failures)
+    OnFailure,
+}
+
+impl HookPoint {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PrePublish => "pre_publish",
+            Self::PostUpload => "post_upload",
+            Self::PostPublish => "post_publish",
+            Self::OnFailure => "on_failure",
+        }
+    }
+}
+
+/// Local scripts run at the hook points. Each receives a JSON object on stdin
+/// with a `hook` field naming the point. Hooks are best effort: a failing or
+/// slow script is logged and killed, never failing the publish.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishHooks {
+    #[serde(default)]
+    pub pre_publish: Option<String>,
+    #[serde(default)]
+    pub post_upload: Option<String>,
+    #[serde(default)]
+    pub post_publish: Option<String>,
+    #[serde(default)]
+    pub on_failure: Option<String>,
+    /// Per-script limit; 0 uses the default
+    #[serde(default)]
+    pub timeout_secs: u64,
+}
+
+impl PublishHooks {
+    fn scripts(&self) -> [(HookPoint, &Option<String>); 4] {
+        [
+            (HookPoint::PrePublish, &self.pre_publish),
+            (HookPoint::PostUpload, &self.post_upload),
+            (HookPoint::PostPublish, &self.post_publish),
+            (HookPoint::OnFailure, &self.on_failure),
+        ]
+    }
+
+    pub fn script(&self, point: HookPoint) -> Option<&Path> {
+        self.scripts()
+            .into_iter()
+            .find(|(p, _)| *p == point)
+            .and_then(|(_, script)| script.as_deref())
+            .map(str::trim)
+            .filter(|script| !script.is_empty())
+            .map(Path::new)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts()
+            .iter()
+            .all(|(point, _)| self.script(*point).is_none())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        let secs = match self.timeout_secs {
+            0 => DEFAULT_HOOK_TIMEOUT_SECS,
+            secs => secs.min(MAX_HOOK_TIMEOUT_SECS),
+        };
+        Duration::from_secs(secs)
+    }
+
+    /// Every configured script must be an absolute path to an existing file
+    pub fn validate(&self) -> Result<()> {
+        for (point, _) in self.scripts() {
+            let Some(script) = self.script(point) else {
+                continue;
+            };
+            if !script.is_absolute() {
+                bail!("{} hook must be an absolute path", point.as_str());
+            }
+            if !script.is_file() {
+                bail!("{} hook not found: {}", point.as_str(), script.display());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hooks_file(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(HOOKS_FILE)
+}
+
+/// Load `hooks.json`; a missing file means no hooks
+pub fn load(app_data_dir: &Path) -> Result<PublishHooks> {
+    let file = hooks_file(app_data_dir);
+    let Ok(body) = std::fs::read(&file) else {
+        return Ok(PublishHooks::default());
+    };
+    serde_json::from_slice(&body).with_context(|| format!("{} is not valid", file.display()))
+}
+
+/// Write (or with no scripts configured, remove) `hooks.json`
+pub fn save(app_data_dir: &Path, hooks: &PublishHooks) -> Result<()> {
+    let file = hooks_file(app_data_dir);
+    if hooks.is_empty() {
+        if file.exists() {
+            std::fs::remove_file(&file)?;
+        }
+        return Ok(());
+    }
+    hooks.validate()?;
+    std::fs::create_dir_all(app_data_dir)?;
+    std::fs::write(&file, serde_json::to_vec_pretty(hooks)?)?;
+    Ok(())
+}
+
+/// Run the script configured for `point`, if any, with `payload` on stdin
+pub async fn run(hooks: &PublishHooks, point: HookPoint, mut payload: Value) {
+    let Some(script) = hooks.script(point) else {
+        return;
+    };
+    if let Some(fields) = payload.as_object_mut() {
+        fields.insert("hook".into(), point.as_str().into());
+    }
+    match run_script(script, &payload, hooks.timeout()).await {
+        Ok(stdout) => info!(
+            "Hook {} ({}) finished: {}",
+            point.as_str(),
+            script.display(),
+            stdout
+        ),
+        Err(e) => warn!(
+            "Hook {} ({}) skipped: {:#}",
+            point.as_str(),
+            script.display(),
+            e
+        ),
+    }
+}
+
+async fn run_script(script: &Path, payload: &Value, timeout: Duration) -> Result<String> {
+    let mut child = Command::new(script)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("could not start the script")?;
+    let input = serde_json::to_vec(payload)?;
+    let waited = tokio::time::timeout(timeout, async {
+        if let Some(mut stdin) = child.stdin.take() {
+            // A script that ignores stdin may close it early; that is fine
+            let _ = stdin.write_all(&input).await;
+        }
+        child.wait_with_output().await
+    })
+    .await;
+    // On timeout the child is dropped here, which kills it
+    let output = waited
+        .map_err(|_| anyhow::anyhow!("timed out after {} s", timeout.as_secs()))?
+        .context("waiting for the script failed")?;
+    if !output.status.success() {
+        bail!(
+            "exit code {:?}: {}",
+            output.status.code(),
+            excerpt(&output.stderr)
+        );
+    }
+    Ok(excerpt(&output.stdout))
+}
+
+fn excerpt(raw: &[u8]) -> String {
+    String::from_utf8_lossy(raw)
+        .trim()
+        .chars()
+        .take(MAX_OUTPUT_CHARS)
+        .collect()
+}
@@ -1,10 +1,38 @@
+mod artifacts;
 mod browser;
 mod commands;
+mod content;
 mod database;
+mod diagnostics;
+mod engagement;
+mod health;
+mod hooks;
+mod media;
 mod platforms;
+mod relocation;
+mod scheduler;
+mod security;
+mod startup;
+mod tasks;
+mod tray;
 
-use database::Database;
-use tauri::Manager;
+use artifacts::ArtifactStore;
+use database::{Database, Storage};
+use startup::{InstanceLock, SetupError, StartupStatus};
+use std::sync::Arc;
+use tasks::TaskRegistry;
+use tauri::{Manager, RunEvent};
+
+/// Command-line modes that run without opening the app, such as
+/// `--config-smoke-test` for CI; `None` means start the app normally
+pub fn run_cli() -> Option<i32> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == health::config_smoke::CLI_FLAG) {
+        env_logger::init();
+        return Some(health::config_smoke::cli(&args));
+    }
+    None
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -14,36 +42,240 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
-            // Initialize database in app data directory
-            let app_data_dir = app
-                .path()
-                .app_data_dir()
-                .expect("Failed to get app data dir");
-            let db = Database::new(&app_data_dir).expect("Failed to initialize database");
-            app.manage(db);
-
-            log::info!(
-                "MultiPublisher initialized. DB at: {}",
-                app_data_dir.display()
-            );
+            // Managed first: the exit handler needs it even when setup fails
+            app.manage(tasks::global().clone());
+            let status = match init(app) {
+                Ok(status) => status,
+                Err(e) => {
+                    log::error!("Setup failed, starting in degraded mode: {}", e);
+                    StartupStatus::degraded(&e)
+                }
+            };
+            app.manage(status);
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Chrome
             commands::chrome::detect_chrome,
             commands::chrome::get_platforms,
+            commands::chrome::get_platform_fields,
             // Accounts
             commands::accounts::get_accounts,
             commands::accounts::add_account,
+            commands::accounts::add_accounts_bulk,
             commands::accounts::delete_account,
             commands::accounts::update_account_name,
             commands::accounts::open_login,
             commands::accounts::open_platform,
             commands::accounts::update_login_status,
+            commands::accounts::refresh_all_login_status,
+            commands::accounts::check_account_login,
+            commands::accounts::test_account,
+            commands::accounts::get_account_health,
+            commands::accounts::get_account_watermark,
+            commands::accounts::set_account_watermark,
+            commands::accounts::get_account_publish_defaults,
+            commands::accounts::set_account_publish_defaults,
+            commands::accounts::get_account_variables,
+            commands::accounts::set_account_variables,
+            commands::accounts::get_account_title_variation,
+            commands::accounts::set_account_title_variation,
+            commands::accounts::get_account_publish_windows,
+            commands::accounts::set_account_chrome_flags,
+            commands::accounts::set_account_publish_windows,
+            commands::accounts::get_account_expiry_estimates,
+            commands::accounts::list_wechat_collections,
+            commands::accounts::get_profile_lock,
+            commands::accounts::release_profile_lock,
             // Publish
             commands::publish::create_publish_task,
+            commands::publish::prewarm_publish,
             commands::publish::get_publish_tasks,
+            commands::publish::get_task_detail,
+            commands::publish::get_task_logs,
+            commands::publish::check_video_eligibility,
+            commands::publish::get_task_progress,
+            commands::publish::get_task_previews,
+            commands::publish::get_task_share_summary,
+            commands::publish::requeue_interrupted_task,
+            commands::publish::clone_publish_task,
+            commands::publish::delete_publish_tasks,
+            commands::publish::purge_tasks_older_than,
+            commands::publish::get_publish_calendar,
+            commands::publish::get_publish_usage,
+            commands::publish::schedule_publish_task,
+            commands::publish::cancel_scheduled_task,
+            commands::publish::list_scheduled_tasks,
+            commands::publish::cancel_publish_task,
+            // Content
+            commands::content::suggest_content,
+            commands::content::get_templates,
+            commands::content::save_template,
+            commands::content::delete_template,
+            commands::content::get_tag_sets,
+            commands::content::save_tag_set,
+            commands::content::delete_tag_set,
+            commands::content::export_config_bundle,
+            commands::content::import_config_bundle,
+            // Engagement
+            commands::engagement::get_reply_rules,
+            commands::engagement::add_reply_rule,
+            commands::engagement::delete_reply_rule,
+            commands::engagement::set_post_url,
+            commands::engagement::run_comment_replies,
+            // Notes vault
+            commands::security::get_vault_status,
+            commands::security::setup_vault,
+            commands::security::unlock_vault,
+            commands::security::lock_vault,
+            commands::security::get_accounts_with_notes,
+            commands::security::get_account_notes,
+            commands::security::set_account_notes,
+            commands::security::get_vault_audit_log,
+            commands::security::create_api_token,
+            commands::security::list_api_tokens,
+            commands::security::revoke_api_token,
+            commands::security::verify_api_token,
+            // Settings
+            commands::settings::get_settings,
+            commands::settings::update_setting,
+            commands::settings::get_user_scripts,
+            commands::settings::set_user_scripts,
+            commands::settings::get_publish_hooks,
+            commands::settings::set_publish_hooks,
+            commands::settings::get_storage_locations,
+            commands::settings::relocate_storage,
+            // Diagnostics
+            commands::diagnostics::list_crash_reports,
+            commands::diagnostics::upload_crash_reports,
+            commands::diagnostics::get_strategy_stats,
+            commands::diagnostics::submit_strategy_stats,
+            commands::diagnostics::clear_strategy_stats,
+            commands::diagnostics::get_page_snapshot_history,
+            commands::diagnostics::diff_page_snapshots,
+            commands::diagnostics::get_strategy_order,
+            commands::diagnostics::get_tuned_timeouts,
+            commands::diagnostics::start_observer_session,
+            commands::diagnostics::stop_observer_session,
+            commands::diagnostics::reset_strategy_order,
+            commands::diagnostics::list_devtools_pages,
+            commands::diagnostics::evaluate_devtools_js,
+            commands::diagnostics::list_background_tasks,
+            commands::diagnostics::probe_network_speed,
+            commands::diagnostics::get_database_status,
+            commands::diagnostics::get_startup_status,
+            commands::diagnostics::set_data_dir_override,
+            commands::diagnostics::restart_app,
+            commands::diagnostics::list_kb_articles,
+            commands::diagnostics::save_kb_article,
+            commands::diagnostics::delete_kb_article,
+            // Health
+            commands::health::get_url_health,
+            commands::health::run_url_health_check,
+            commands::health::validate_selector_overrides,
+            commands::health::run_config_smoke_test,
+            commands::health::reload_selector_overrides,
+            commands::health::get_effective_selectors,
+            commands::health::refresh_wechat_review_queue,
+            commands::health::run_selector_health_check,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::Exit = event {
+                app.state::<TaskRegistry>().abort_all();
+                if let Some(lock) = app.try_state::<InstanceLock>() {
+                    lock.release();
+                }
+            }
+        });
+}
+
+/// Open the data directory and database and start the background jobs.
+/// Nothing is managed unless everything it depends on came up.
+fn init(app: &mut tauri::App) -> Result<StartupStatus, SetupError> {
+    let (app_data_dir, overridden) = startup::resolve_data_dir(app)?;
+    // Two instances on one data directory fight over profile locks and can
+    // publish the same task twice. Dropping it on an early return below releases it
+    let instance_lock = startup::acquire_instance_lock(&app_data_dir)?;
+    let artifacts = ArtifactStore::new(&app_data_dir);
+    let registry = tasks::global().clone();
+    diagnostics::crash::install_panic_hook(artifacts.crash_dir());
+
+    database::storage::check_backend(&app_data_dir);
+    let (db, db_status) =
+        Database::new(&app_data_dir).map_err(|e| SetupError::Database(e.to_string()))?;
+    if db_status.state != database::integrity::STATE_OK {
+        log::warn!(
+            "Database opened in state {}: {}",
+            db_status.state,
+            db_status.details.join("; ")
+        );
+    }
+
+    // A crash or forced quit mid-publish leaves rows that never got their final status
+    if let Ok(conn) = db.conn.lock() {
+        match database::queries::mark_orphaned_tasks_interrupted(&conn) {
+            Ok((0, 0)) => {}
+            Ok((tasks, platforms)) => log::warn!(
+                "Marked {} task(s) and {} account run(s) from an unfinished session as interrupted",
+                tasks,
+                platforms
+            ),
+            Err(e) => log::warn!("Failed to reconcile unfinished publish tasks: {}", e),
+        }
+    }
+
+    if let Ok(conn) = db.conn.lock() {
+        let raw = database::queries::get_setting(&conn, platforms::SETTING_EXPERIMENTAL_PLATFORMS)
+            .ok()
+            .flatten();
+        platforms::set_experimental_enabled(raw.as_deref());
+        let locale = database::queries::get_setting(&conn, platforms::SETTING_LOCALE)
+            .ok()
+            .flatten();
+        platforms::set_locale(locale.as_deref());
+    }
+    platforms::selector_overrides::reload(&app_data_dir);
+
+    // Upload crash reports from previous runs, only if the user opted in
+    let crash_endpoint = db
+        .conn
+        .lock()
+        .ok()
+        .and_then(|conn| diagnostics::crash::upload_endpoint(&conn).ok().flatten());
+    if let Some(endpoint) = crash_endpoint {
+        let crash_dir = artifacts.crash_dir();
+        registry.spawn("crash_report:upload", async move {
+            match diagnostics::crash::upload_pending_reports(&crash_dir, &endpoint).await {
+                Ok(count) if count > 0 => log::info!("Uploaded {} crash report(s)", count),
+                Ok(_) => {}
+                Err(e) => log::warn!("Crash report upload failed: {}", e),
+            }
+        });
+    }
+
+    let storage: Arc<dyn Storage> = Arc::new(db.clone());
+    app.manage(storage);
+    app.manage(db);
+    app.manage(db_status);
+    app.manage(artifacts);
+    app.manage(security::vault::Vault::default());
+    if let Some(lock) = instance_lock {
+        app.manage(lock);
+    }
+
+    health::url_drift::spawn_periodic(app.handle().clone());
+    health::session_monitor::spawn_periodic(app.handle().clone());
+    engagement::comment_reply::spawn_periodic(app.handle().clone());
+    scheduler::queue::spawn_worker(app.handle().clone());
+    if let Err(e) = tray::init(app) {
+        log::warn!("Tray icon unavailable: {}", e);
+    }
+
+    log::info!(
+        "MultiPublisher initialized. DB at: {}",
+        app_data_dir.display()
+    );
+    Ok(StartupStatus::ready(&app_data_dir, overridden))
 }
@@ -0,0 +1,76 @@
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Optional user override for the ffmpeg binary location.
+pub const SETTING_FFMPEG_PATH: &str = "ffmpeg_path";
+
+const STDERR_TAIL_CHARS: usize = 800;
+
+/// Find ffmpeg: the configured path first, then PATH.
+pub fn detect_ffmpeg(configured: Option<&str>) -> Result<PathBuf> {
+    if let Some(path) = configured.map(str::trim).filter(|p| !p.is_empty()) {
+        let path = PathBuf::from(path);
+        if path.is_file() {
+            return Ok(path);
+        }
+        bail!(
+            "FFMPEG_NOT_FOUND: 配置的 ffmpeg 路径不存在：{}",
+            path.display()
+        );
+    }
+    which::which("ffmpeg")
+        .context("FFMPEG_NOT_FOUND: 未找到 ffmpeg，请安装 ffmpeg 或在设置中指定路径")
+}
+
+/// Run ffmpeg with the given arguments, returning the tail of stderr on failure.
+pub async fn run(ffmpeg: &Path, args: &[String]) -> Result<()> {
+    let output = Command::new(ffmpeg)
+        .args(["-hide_banner", "-loglevel", "error"])
+        .args(args)
+        .output()
+        .await
+        .with_context(|| format!("启动 ffmpeg 失败：{}", ffmpeg.display()))?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let tail: String = stderr
+        .chars()
+        .rev()
+        .take(STDERR_TAIL_CHARS)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    bail!("ffmpeg 退出码 {:?}：{}", output.status.code(), tail.trim());
+}
+
+/// Read the container duration from `ffmpeg -i` output, in seconds.
+pub async fn probe_duration_secs(ffmpeg: &Path, video: &Path) -> Result<f64> {
+    // Without an output file ffmpeg exits non-zero, but still prints the input summary
+    let output = Command::new(ffmpeg)
+        .arg("-hide_banner")
+        .arg("-i")
+        .arg(video)
+        .output()
+        .await
+        .with_context(|| format!("启动 ffmpeg 失败：{}", ffmpeg.display()))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Duration:"))
+        .and_then(|rest| rest.split(',').next())
+        .and_then(parse_timestamp)
+        .with_context(|| format!("无法读取视频时长：{}", video.display()))
+}
+
+/// `HH:MM:SS.xx` -> seconds
+fn parse_timestamp(raw: &str) -> Option<f64> {
+    let mut parts = raw.trim().split(':');
+    let hours: f64 = parts.next()?.parse().ok()?;
+    let minutes: f64 = parts.next()?.parse().ok()?;
+    let seconds: f64 = parts.next()?.parse().ok()?;
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
@@ -0,0 +1,3 @@
+pub mod ffmpeg;
+pub mod transcribe;
+pub mod watermark;
@@ -0,0 +1,240 @@
+use super::ffmpeg;
+use crate::database::queries;
+use anyhow::{bail, Context, Result};
+use log::info;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// `off` (default), `local` (whisper.cpp CLI) or `remote` (OpenAI-compatible API)
+pub const SETTING_MODE: &str = "transcription_mode";
+pub const SETTING_WHISPER_CLI: &str = "whisper_cli_path";
+pub const SETTING_WHISPER_MODEL: &str = "whisper_model_path";
+pub const SETTING_ENDPOINT: &str = "transcription_endpoint";
+pub const SETTING_API_KEY: &str = "transcription_api_key";
+pub const SETTING_LANGUAGE: &str = "transcription_language";
+
+const DEFAULT_REMOTE_MODEL: &str = "whisper-1";
+const REMOTE_TIMEOUT_SECS: u64 = 600;
+/// OpenAI-compatible transcription endpoints reject files over 25 MB
+const REMOTE_MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+/// Speech stays intelligible at this rate; about 14 MB per hour of audio
+const REMOTE_AUDIO_BITRATE: &str = "32k";
+
+pub enum Transcriber {
+    Local {
+        cli: PathBuf,
+        model: PathBuf,
+        language: Option<String>,
+    },
+    Remote {
+        endpoint: String,
+        api_key: Option<String>,
+        language: Option<String>,
+    },
+}
+
+fn non_empty_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    Ok(queries::get_setting(conn, key)?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty()))
+}
+
+impl Transcriber {
+    /// Build from settings. Returns None when transcription is turned off.
+    pub fn from_settings(conn: &Connection) -> Result<Option<Self>> {
+        let mode = non_empty_setting(conn, SETTING_MODE)?.unwrap_or_else(|| "off".into());
+        let language = non_empty_setting(conn, SETTING_LANGUAGE)?;
+        match mode.as_str() {
+            "off" => Ok(None),
+            "local" => {
+                let cli = match non_empty_setting(conn, SETTING_WHISPER_CLI)? {
+                    Some(path) => PathBuf::from(path),
+                    None => which::which("whisper-cli")
+                        .or_else(|_| which::which("whisper-cpp"))
+                        .context("未找到 whisper.cpp 命令行程序，请在设置中指定路径")?,
+                };
+                let model = non_empty_setting(conn, SETTING_WHISPER_MODEL)?
+                    .map(PathBuf::from)
+                    .context("请先在设置中指定 whisper 模型文件")?;
+                Ok(Some(Self::Local {
+                    cli,
+                    model,
+                    language,
+                }))
+            }
+            "remote" => {
+                let endpoint = non_empty_setting(conn, SETTING_ENDPOINT)?
+                    .map(|v| v.trim_end_matches('/').to_string())
+                    .context("请先在设置中填写语音识别接口地址")?;
+                Ok(Some(Self::Remote {
+                    endpoint,
+                    api_key: non_empty_setting(conn, SETTING_API_KEY)?,
+                    language,
+                }))
+            }
+            other => bail!("未知的语音识别模式：{}", other),
+        }
+    }
+
+    /// Transcribe `video` into `<out_dir>/<stem>.srt` and return its path.
+    pub async fn transcribe(
+        &self,
+        ffmpeg_path: &Path,
+        video: &Path,
+        out_dir: &Path,
+        stem: &str,
+    ) -> Result<PathBuf> {
+        // whisper.cpp reads PCM; a remote upload is compressed to stay under the size limit
+        let remote = matches!(self, Self::Remote { .. });
+        let audio = out_dir.join(format!("{}.{}", stem, if remote { "m4a" } else { "wav" }));
+        let srt = out_dir.join(format!("{}.srt", stem));
+        extract_audio(ffmpeg_path, video, &audio, remote).await?;
+
+        let result = match self {
+            Self::Local {
+                cli,
+                model,
+                language,
+            } => transcribe_local(cli, model, language.as_deref(), &audio, &srt).await,
+            Self::Remote {
+                endpoint,
+                api_key,
+                language,
+            } => {
+                transcribe_remote(
+                    endpoint,
+                    api_key.as_deref(),
+                    language.as_deref(),
+                    &audio,
+                    &srt,
+                )
+                .await
+            }
+        };
+        let _ = std::fs::remove_file(&audio);
+        result?;
+
+        info!("[字幕] 已生成字幕文件：{}", srt.display());
+        Ok(srt)
+    }
+}
+
+/// whisper expects 16 kHz mono; `compressed` encodes it as low-bitrate AAC
+/// instead of PCM
+async fn extract_audio(
+    ffmpeg_path: &Path,
+    video: &Path,
+    audio: &Path,
+    compressed: bool,
+) -> Result<()> {
+    let mut args: Vec<String> = vec![
+        "-y".into(),
+        "-i".into(),
+        video.to_string_lossy().to_string(),
+        "-vn".into(),
+        "-ac".into(),
+        "1".into(),
+        "-ar".into(),
+        "16000".into(),
+        "-c:a".into(),
+    ];
+    if compressed {
+        args.extend(["aac".into(), "-b:a".into(), REMOTE_AUDIO_BITRATE.into()]);
+    } else {
+        args.push("pcm_s16le".into());
+    }
+    args.push(audio.to_string_lossy().to_string());
+    ffmpeg::run(ffmpeg_path, &args)
+        .await
+        .context("提取音轨失败")
+}
+
+async fn transcribe_local(
+    cli: &Path,
+    model: &Path,
+    language: Option<&str>,
+    audio: &Path,
+    srt: &Path,
+) -> Result<()> {
+    // whisper.cpp appends ".srt" to the -of base name itself
+    let out_base = srt.with_extension("");
+    let mut cmd = Command::new(cli);
+    cmd.arg("-m")
+        .arg(model)
+        .arg("-f")
+        .arg(audio)
+        .arg("-osrt")
+        .arg("-of")
+        .arg(&out_base);
+    if let Some(language) = language {
+        cmd.arg("-l").arg(language);
+    }
+    let output = cmd
+        .output()
+        .await
+        .with_context(|| format!("启动 whisper 失败：{}", cli.display()))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "whisper 退出码 {:?}：{}",
+            output.status.code(),
+            stderr.chars().take(500).collect::<String>()
+        );
+    }
+    if !srt.is_file() {
+        bail!("whisper 未生成字幕文件：{}", srt.display());
+    }
+    Ok(())
+}
+
+async fn transcribe_remote(
+    endpoint: &str,
+    api_key: Option<&str>,
+    language: Option<&str>,
+    audio: &Path,
+    srt: &Path,
+) -> Result<()> {
+    let size = tokio::fs::metadata(audio).await?.len();
+    if size > REMOTE_MAX_UPLOAD_BYTES {
+        bail!(
+            "音轨 {:.1} MB 超过语音识别接口的 {} MB 上限，请改用本地 whisper 或先剪短视频",
+            size as f64 / (1024.0 * 1024.0),
+            REMOTE_MAX_UPLOAD_BYTES / (1024 * 1024)
+        );
+    }
+    let bytes = tokio::fs::read(audio).await?;
+    let part = reqwest::multipart::Part::bytes(bytes)
+        .file_name("audio.m4a")
+        .mime_str("audio/mp4")?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", part)
+        .text("model", DEFAULT_REMOTE_MODEL)
+        .text("response_format", "srt");
+    if let Some(language) = language {
+        form = form.text("language", language.to_string());
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REMOTE_TIMEOUT_SECS))
+        .build()?;
+    let mut request = client
+        .post(format!("{}/audio/transcriptions", endpoint))
+        .multipart(form);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+    let resp = request.send().await.context("请求语音识别接口失败")?;
+    let status = resp.status();
+    let body = resp.text().await.unwrap_or_default();
+    if !status.is_success() {
+        bail!(
+            "语音识别接口返回错误 {}：{}",
+            status,
+            body.chars().take(300).collect::<String>()
+        );
+    }
+    tokio::fs::write(srt, body).await?;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use super::ffmpeg;
+use anyhow::{bail, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// Watermarked copies are kept until uploads have surely finished reading them.
+const KEEP_COPIES_FOR: Duration = Duration::from_secs(48 * 3600);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+/// Per-account watermark settings, stored as JSON on the account row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatermarkConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// PNG image to overlay (transparency is preserved)
+    pub image_path: String,
+    #[serde(default)]
+    pub position: WatermarkPosition,
+    /// 0.0 (invisible) – 1.0 (opaque)
+    #[serde(default = "default_opacity")]
+    pub opacity: f32,
+    /// Distance from the video edge, in pixels
+    #[serde(default = "default_margin")]
+    pub margin: u32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_opacity() -> f32 {
+    0.8
+}
+
+fn default_margin() -> u32 {
+    24
+}
+
+impl WatermarkConfig {
+    pub fn validate(&self) -> Result<()> {
+        let image = Path::new(&self.image_path);
+        if !image.is_file() {
+            bail!("水印图片不存在：{}", self.image_path);
+        }
+        let is_png = image
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("png"))
+            .unwrap_or(false);
+        if !is_png {
+            bail!("水印图片必须是 PNG：{}", self.image_path);
+        }
+        if !(0.0..=1.0).contains(&self.opacity) {
+            bail!("水印不透明度必须在 0 到 1 之间：{}", self.opacity);
+        }
+        Ok(())
+    }
+
+    fn filter_graph(&self) -> String {
+        let m = self.margin;
+        let overlay = match self.position {
+            WatermarkPosition::TopLeft => format!("x={m}:y={m}"),
+            WatermarkPosition::TopRight => format!("x=W-w-{m}:y={m}"),
+            WatermarkPosition::BottomLeft => format!("x={m}:y=H-h-{m}"),
+            WatermarkPosition::BottomRight => format!("x=W-w-{m}:y=H-h-{m}"),
+            WatermarkPosition::Center => "x=(W-w)/2:y=(H-h)/2".to_string(),
+        };
+        format!(
+            "[1:v]format=rgba,colorchannelmixer=aa={:.2}[wm];[0:v][wm]overlay={}[out]",
+            self.opacity, overlay
+        )
+    }
+}
+
+/// Render `input` with the watermark into `output`. Audio is copied untouched.
+pub async fn apply(
+    ffmpeg_path: &Path,
+    input: &Path,
+    output: &Path,
+    config: &WatermarkConfig,
+) -> Result<()> {
+    config.validate()?;
+    info!(
+        "[水印] 开始处理：input={} output={} position={:?} opacity={}",
+        input.display(),
+        output.display(),
+        config.position,
+        config.opacity
+    );
+    let args: Vec<String> = vec![
+        "-y".into(),
+        "-i".into(),
+        input.to_string_lossy().to_string(),
+        "-i".into(),
+        config.image_path.clone(),
+        "-filter_complex".into(),
+        config.filter_graph(),
+        "-map".into(),
+        "[out]".into(),
+        "-map".into(),
+        "0:a?".into(),
+        "-c:v".into(),
+        "libx264".into(),
+        "-preset".into(),
+        "veryfast".into(),
+        "-crf".into(),
+        "20".into(),
+        "-c:a".into(),
+        "copy".into(),
+        "-movflags".into(),
+        "+faststart".into(),
+        output.to_string_lossy().to_string(),
+    ];
+    ffmpeg::run(ffmpeg_path, &args).await
+}
+
+/// Remove watermarked copies older than the retention window.
+pub fn prune_old_copies(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .map(|age| age > KEEP_COPIES_FOR)
+            .unwrap_or(false);
+        if expired {
+            if let Err(e) = std::fs::remove_file(entry.path()) {
+                warn!("[水印] 清理旧文件失败 {}: {}", entry.path().display(), e);
+            }
+        }
+    }
+}
@@ -1,12 +1,63 @@
-use super::common::{self, PlatformPublishConfig};
-use super::traits::PlatformInfo;
-use anyhow::Result;
+use super::capabilities::{CustomFieldSpec, CustomFields, DurationLimits, FieldKind};
+use super::common::{self, LoginGuardConfig, PlatformPublishConfig, SubmitConfig, UploadOutcome};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{bail, Result};
 use chromiumoxide::page::Page;
+use futures::future::BoxFuture;
+use log::{info, warn};
+use std::time::Duration;
 
-const BILIBILI_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+/// B站单个稿件最多 10 个标签，单个标签最长 20 个字符
+const MAX_TAGS: usize = 10;
+const MAX_TAG_CHARS: usize = 20;
+/// B站标签不接受的字符，出现时直接去掉
+const FORBIDDEN_TAG_CHARS: &[char] = &[
+    '#', '@', ',', '，', ';', '；', '、', '/', '\\', '<', '>', '"', '\'', '&',
+];
+const TAG_SUGGESTION_WAIT_MS: u64 = 600;
+const TAG_AFTER_ENTER_WAIT_MS: u64 = 200;
+const TAG_SUGGESTION_SELECTORS: &[&str] = &[
+    "[class*='tag'] [class*='suggest'] li",
+    "[class*='tag'] [class*='suggest'] [class*='item']",
+    "[class*='tag'] [class*='dropdown'] [class*='item']",
+    "[class*='tag-list'] [class*='item']",
+    "[class*='hot-tag'] [class*='item']",
+];
+/// 活动话题在联想列表中带"活动"标记，选中后会进入活动专属 UI
+const ACTIVITY_TAG_MARKERS: &[&str] = &["活动"];
+const SETTING_STEP_WAIT_MS: u64 = 500;
+/// 类型单选在“自制 / 转载”两项之间切换
+const COPYRIGHT_SCOPE_SELECTOR: &str = "[class*='type-check']";
+const SOURCE_INPUT_SELECTORS: &[&str] = &[
+    "input[placeholder*='转载视频请注明来源']",
+    "[class*='copyright'] input[placeholder*='来源']",
+    "input[placeholder*='来源']",
+];
+const CATEGORY_TRIGGER_SELECTORS: &[&str] = &[
+    "[class*='video-human-type'] [class*='select-controller']",
+    "[class*='type-select'] [class*='select-controller']",
+    "[class*='select-container'] [class*='select-controller']",
+];
+const CATEGORY_OPTION_SELECTORS: &[&str] = &[
+    "[class*='drop-cascader'] [class*='item']",
+    "[class*='drop-list'] [class*='item']",
+    "[class*='select-dropdown'] [class*='item']",
+];
+const ACTIVITY_TRIGGER_TEXT_MARKERS: &[&str] = &["参与活动", "选择活动"];
+const ACTIVITY_OPTION_SELECTORS: &[&str] = &[
+    "[class*='activity'] [class*='item']",
+    "[class*='topic-list'] [class*='item']",
+];
+
+pub(super) const BILIBILI_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     id: "bilibili",
     name: "哔哩哔哩",
     upload_url: "https://member.bilibili.com/platform/upload/video/frame",
+    upload_url_variants: &[],
     target_host: "member.bilibili.com",
     allowed_paths: &["/platform/upload", "/video/frame", "/article"],
     surface_selectors: &[
@@ -41,7 +92,44 @@ const BILIBILI_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     weak_ready_min_body_text_len: 0,
     blocked_text_markers: &[],
     init_text_markers: &[],
-    login_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["passport.bilibili.com"],
+        text_markers: &[],
+        success_url_markers: &["member.bilibili.com", "www.bilibili.com"],
+        avatar_selectors: &[
+            ".header-avatar-wrap img",
+            ".user-con img",
+            "[class*='avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[],
+    risk_control_url_markers: &[],
+    maintenance_text_markers: &["系统维护中", "服务器维护", "暂停投稿", "投稿功能维护"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::UrlContains {
+                label: "post",
+                patterns: &["/upload-manager", "/video/edit", "/archive"],
+            },
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='progress']",
+                    "[class*='upload-progress']",
+                    "[class*='percent']",
+                    "[class*='uploading']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "处理中", "转码中", "投稿"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
     title_selectors: &[
         "input[placeholder*='标题']",
         "input[placeholder*='稿件标题']",
@@ -62,6 +150,19 @@ const BILIBILI_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
         "[class*='tag'] input",
         "input[name*='tag']",
     ],
+    subtitle_input_selectors: &[
+        "input[type='file'][accept*='.srt']",
+        "[class*='subtitle'] input[type='file']",
+    ],
+    subtitle_open_text_markers: &["更多设置"],
+    blocking_dialog: None,
+    editor_ready: None,
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["立即投稿"],
+        success_url_markers: &["/platform/upload-manager"],
+        success_text_markers: &["稿件投递成功", "投稿成功"],
+    },
 };
 
 pub fn info() -> PlatformInfo {
@@ -72,16 +173,260 @@ pub fn info() -> PlatformInfo {
         login_url: "https://passport.bilibili.com/login".into(),
         upload_url: BILIBILI_CONFIG.upload_url.into(),
         color: "#fb7299".into(),
+        experimental: false,
+    }
+}
+
+const CUSTOM_FIELDS: &[CustomFieldSpec] = &[
+    CustomFieldSpec {
+        key: "category",
+        label: "分区",
+        kind: FieldKind::Text { max_len: 30 },
+    },
+    CustomFieldSpec {
+        key: "copyright",
+        label: "类型",
+        kind: FieldKind::Choice {
+            options: &["original", "repost"],
+        },
+    },
+    CustomFieldSpec {
+        key: "source_url",
+        label: "转载来源",
+        kind: FieldKind::Text { max_len: 200 },
+    },
+    CustomFieldSpec {
+        key: "activity",
+        label: "活动",
+        kind: FieldKind::Text { max_len: 50 },
+    },
+];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &BILIBILI_CONFIG,
+    automation: &Bilibili,
+    duration_limits: DurationLimits::UNLIMITED,
+    custom_fields: CUSTOM_FIELDS,
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
+
+pub struct Bilibili;
+
+impl PlatformAutomation for Bilibili {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+}
+
+#[derive(Debug, Default)]
+struct TagPlan {
+    accepted: Vec<String>,
+    renamed: Vec<(String, String)>,
+    skipped: Vec<(String, &'static str)>,
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    // 标签走 B站专用流程，通用填表只负责标题和简介
+    let plan = plan_tags(&payload.tags);
+    let mut base_payload = payload.clone();
+    base_payload.tags.clear();
+    let mut signal = common::fill_metadata(
+        &AutomationContext {
+            payload: &base_payload,
+            ..*ctx
+        },
+        upload,
+    )
+    .await?;
+    let page = &ctx.handle.ensure_attached().await?;
+    signal.push_str(&fill_post_settings(page, &payload.custom_fields).await);
+
+    if payload.tags.is_empty() {
+        return Ok(signal);
+    }
+
+    let mut added = 0;
+    let mut activity = Vec::new();
+    for tag in &plan.accepted {
+        match add_tag(page, ctx.cfg.tag_selectors, tag).await {
+            Ok(Some(marker)) if marker.starts_with("preferred:") => {
+                activity.push(tag.clone());
+                added += 1;
+            }
+            Ok(_) => added += 1,
+            Err(e) => warn!("[哔哩哔哩标签] 标签 {} 填写失败：{}", tag, e),
+        }
+    }
+    info!(
+        "[哔哩哔哩标签] added={}/{} activity={:?} renamed={:?} skipped={:?}",
+        added,
+        plan.accepted.len(),
+        activity,
+        plan.renamed,
+        plan.skipped
+    );
+
+    let mut report = format!("{};bili_tags={}/{}", signal, added, payload.tags.len());
+    if !activity.is_empty() {
+        report.push_str(&format!(";activity_tags={}", activity.join(",")));
+    }
+    if !plan.renamed.is_empty() {
+        let renamed: Vec<String> = plan
+            .renamed
+            .iter()
+            .map(|(from, to)| format!("{}->{}", from, to))
+            .collect();
+        report.push_str(&format!(";renamed_tags={}", renamed.join(",")));
+    }
+    if !plan.skipped.is_empty() {
+        let skipped: Vec<String> = plan
+            .skipped
+            .iter()
+            .map(|(tag, reason)| format!("{}({})", tag, reason))
+            .collect();
+        report.push_str(&format!(";skipped_tags={}", skipped.join(",")));
+    }
+    Ok(report)
+}
+
+/// 类型、分区和活动。失败只记录在信号里：B站会按标题推荐分区，类型默认自制，
+/// 用户在发布前仍能在页面上改。
+async fn fill_post_settings(page: &Page, fields: &CustomFields) -> String {
+    let mut report = String::new();
+    if let Some(copyright) = fields.text("copyright") {
+        let status = match set_copyright(page, copyright, fields.text("source_url")).await {
+            Ok(()) => copyright,
+            Err(e) => {
+                warn!("[哔哩哔哩设置] 类型设置失败：{}", e);
+                "failed"
+            }
+        };
+        report.push_str(&format!(";bili_copyright={}", status));
+    }
+    if let Some(category) = fields.text("category") {
+        let status = match set_category(page, category).await {
+            Ok(()) => "ok",
+            Err(e) => {
+                warn!("[哔哩哔哩设置] 分区设置失败：{}", e);
+                "failed"
+            }
+        };
+        report.push_str(&format!(";bili_category={}", status));
+    }
+    if let Some(activity) = fields.text("activity") {
+        let status = match set_activity(page, activity).await {
+            Ok(()) => "ok",
+            Err(e) => {
+                warn!("[哔哩哔哩设置] 活动选择失败：{}", e);
+                "failed"
+            }
+        };
+        report.push_str(&format!(";bili_activity={}", status));
+    }
+    report
+}
+
+/// 选“自制”或“转载”；转载稿件必须填写来源
+async fn set_copyright(page: &Page, copyright: &str, source_url: Option<&str>) -> Result<()> {
+    let marker = if copyright == "repost" {
+        "转载"
+    } else {
+        "自制"
+    };
+    automation::click_by_text(page, &[marker], Some(COPYRIGHT_SCOPE_SELECTOR)).await?;
+    info!("[哔哩哔哩设置] 类型已选择：{}", marker);
+    if copyright != "repost" {
+        return Ok(());
+    }
+    let Some(source_url) = source_url else {
+        bail!("转载稿件未提供来源");
+    };
+    tokio::time::sleep(Duration::from_millis(SETTING_STEP_WAIT_MS)).await;
+    automation::type_into_input(page, SOURCE_INPUT_SELECTORS, source_url, false).await?;
+    info!("[哔哩哔哩设置] 转载来源已填写：{}", source_url);
+    Ok(())
+}
+
+/// 分区写作“主分区/子分区”，如“生活/日常”；只写一级时沿用页面推荐的子分区
+async fn set_category(page: &Page, category: &str) -> Result<()> {
+    automation::click_first_visible(page, CATEGORY_TRIGGER_SELECTORS).await?;
+    for level in category.split('/').map(str::trim).filter(|l| !l.is_empty()) {
+        tokio::time::sleep(Duration::from_millis(SETTING_STEP_WAIT_MS)).await;
+        if automation::click_matching_option(page, CATEGORY_OPTION_SELECTORS, level, &[])
+            .await?
+            .is_none()
+        {
+            bail!("分区列表中没有“{}”", level);
+        }
+    }
+    info!("[哔哩哔哩设置] 分区已选择：{}", category);
+    Ok(())
+}
+
+async fn set_activity(page: &Page, activity: &str) -> Result<()> {
+    automation::click_by_text(page, ACTIVITY_TRIGGER_TEXT_MARKERS, None).await?;
+    tokio::time::sleep(Duration::from_millis(SETTING_STEP_WAIT_MS)).await;
+    match automation::click_matching_option(page, ACTIVITY_OPTION_SELECTORS, activity, &[]).await? {
+        Some(marker) => {
+            info!("[哔哩哔哩设置] 活动已选择：{} ({})", activity, marker);
+            Ok(())
+        }
+        None => bail!("活动列表中没有“{}”", activity),
     }
 }
 
-pub async fn auto_publish(
-    page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<String> {
-    common::auto_publish_with_config(page, video_path, title, description, tags, &BILIBILI_CONFIG)
-        .await
+/// 按 B站规则清洗标签：去掉非法字符、截断超长标签、去重，并限制总数。
+fn plan_tags(tags: &[String]) -> TagPlan {
+    let mut plan = TagPlan::default();
+    for raw in tags {
+        let original = raw.trim();
+        let cleaned: String = original
+            .chars()
+            .filter(|c| !c.is_whitespace() && !FORBIDDEN_TAG_CHARS.contains(c))
+            .take(MAX_TAG_CHARS)
+            .collect();
+        if cleaned.is_empty() {
+            plan.skipped.push((original.to_string(), "invalid"));
+            continue;
+        }
+        if plan.accepted.contains(&cleaned) {
+            plan.skipped.push((original.to_string(), "duplicate"));
+            continue;
+        }
+        if plan.accepted.len() >= MAX_TAGS {
+            plan.skipped.push((original.to_string(), "limit"));
+            continue;
+        }
+        if cleaned != original {
+            plan.renamed.push((original.to_string(), cleaned.clone()));
+        }
+        plan.accepted.push(cleaned);
+    }
+    plan
+}
+
+/// 输入单个标签：联想列表里有匹配项（优先活动标签）就点选，否则回车直接添加。
+async fn add_tag(page: &Page, selectors: &[&str], tag: &str) -> Result<Option<String>> {
+    automation::type_into_input(page, selectors, tag, false).await?;
+    tokio::time::sleep(Duration::from_millis(TAG_SUGGESTION_WAIT_MS)).await;
+
+    let picked = automation::click_matching_option(
+        page,
+        TAG_SUGGESTION_SELECTORS,
+        tag,
+        ACTIVITY_TAG_MARKERS,
+    )
+    .await?;
+    if picked.is_none() {
+        automation::cdp_press_enter(page).await?;
+    }
+    tokio::time::sleep(Duration::from_millis(TAG_AFTER_ENTER_WAIT_MS)).await;
+    Ok(picked)
 }
@@ -0,0 +1,233 @@
+use super::registry;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Value type accepted for one platform-specific field
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldKind {
+    Text { max_len: usize },
+    Choice { options: &'static [&'static str] },
+    Number { min: f64, max: f64 },
+    Bool,
+    List { max_items: usize },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomFieldSpec {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub kind: FieldKind,
+}
+
+/// Video duration a platform accepts, in seconds. `None` means no limit.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DurationLimits {
+    pub min_secs: Option<f64>,
+    pub max_secs: Option<f64>,
+}
+
+impl DurationLimits {
+    pub const UNLIMITED: Self = Self {
+        min_secs: None,
+        max_secs: None,
+    };
+}
+
+/// What a publish uploads: one video, or a set of images posted as a note
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PostType {
+    #[default]
+    Video,
+    Images,
+}
+
+impl PostType {
+    /// Name stored on the task row, matching the serialized form
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Video => "video",
+            Self::Images => "images",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "video" => Some(Self::Video),
+            "images" => Some(Self::Images),
+            _ => None,
+        }
+    }
+}
+
+/// Whether `platform`'s automation can publish `post_type`
+pub fn supports_post_type(platform: &str, post_type: PostType) -> bool {
+    match post_type {
+        PostType::Video => true,
+        PostType::Images => platform == "xiaohongshu",
+    }
+}
+
+/// Limits of the web upload pages, which can be stricter than the mobile apps
+pub fn duration_limits(platform: &str) -> DurationLimits {
+    registry::find(platform)
+        .map(|entry| entry.duration_limits)
+        .unwrap_or(DurationLimits::UNLIMITED)
+}
+
+/// Why a video of `duration_secs` cannot go to `platform`, if it can't
+pub fn duration_violation(platform: &str, duration_secs: f64) -> Option<String> {
+    let limits = duration_limits(platform);
+    if let Some(min) = limits.min_secs.filter(|min| duration_secs < *min) {
+        return Some(format!(
+            "视频时长 {:.0} 秒，短于平台要求的 {:.0} 秒",
+            duration_secs, min
+        ));
+    }
+    if let Some(max) = limits.max_secs.filter(|max| duration_secs > *max) {
+        return Some(format!(
+            "视频时长 {:.0} 秒，超过平台上限 {:.0} 秒",
+            duration_secs, max
+        ));
+    }
+    None
+}
+
+/// Extra fields a platform accepts beyond title/description/tags
+pub fn capability_schema(platform: &str) -> &'static [CustomFieldSpec] {
+    registry::find(platform)
+        .map(|entry| entry.custom_fields)
+        .unwrap_or(&[])
+}
+
+/// Platform-specific options for one task platform, stored as a JSON object
+/// in `publish_task_platforms.custom_fields`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomFields(Map<String, Value>);
+
+impl CustomFields {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        self.0
+            .get(key)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    pub fn number(&self, key: &str) -> Option<f64> {
+        self.0.get(key).and_then(Value::as_f64)
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        self.0.get(key).and_then(Value::as_bool)
+    }
+
+    pub fn list(&self, key: &str) -> Vec<String> {
+        self.0
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|items| {
+                items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|s| s.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Set `key` to `value` when it is unset and `value` is one of the
+    /// platform's options for that choice field; otherwise leave it as is
+    pub fn default_choice(&mut self, platform: &str, key: &str, value: &str) {
+        if self.0.get(key).is_some_and(|value| !value.is_null()) {
+            return;
+        }
+        let accepted = capability_schema(platform).iter().any(|spec| {
+            spec.key == key
+                && matches!(spec.kind, FieldKind::Choice { options } if options.contains(&value))
+        });
+        if accepted {
+            self.0
+                .insert(key.to_string(), Value::String(value.to_string()));
+        }
+    }
+
+    /// These fields with every key left unset (or null) taken from `defaults`
+    pub fn merged_over(mut self, defaults: &CustomFields) -> CustomFields {
+        for (key, value) in &defaults.0 {
+            if self.0.get(key).is_none_or(Value::is_null) {
+                self.0.insert(key.clone(), value.clone());
+            }
+        }
+        self
+    }
+
+    /// Rewrite every string value, including the items of list fields
+    pub fn render_text(&mut self, render: impl Fn(&str) -> String) {
+        for value in self.0.values_mut() {
+            match value {
+                Value::String(text) => *text = render(text),
+                Value::Array(items) => {
+                    for item in items {
+                        if let Value::String(text) = item {
+                            *text = render(text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reject unknown keys and values that don't match the platform's schema
+    pub fn validate(&self, platform: &str) -> Result<()> {
+        let schema = capability_schema(platform);
+        for (key, value) in &self.0 {
+            let Some(spec) = schema.iter().find(|spec| spec.key == key) else {
+                bail!("{} 不支持自定义字段 {}", platform, key);
+            };
+            if value.is_null() {
+                continue;
+            }
+            match &spec.kind {
+                FieldKind::Text { max_len } => match value.as_str() {
+                    Some(s) if s.chars().count() <= *max_len => {}
+                    Some(_) => bail!("{}.{} 超过 {} 个字符", platform, key, max_len),
+                    None => bail!("{}.{} 必须是字符串", platform, key),
+                },
+                FieldKind::Choice { options } => match value.as_str() {
+                    Some(s) if options.contains(&s) => {}
+                    _ => bail!("{}.{} 必须是 {} 之一", platform, key, options.join("/")),
+                },
+                FieldKind::Number { min, max } => match value.as_f64() {
+                    Some(n) if n >= *min && n <= *max => {}
+                    Some(_) => bail!("{}.{} 必须在 {} 到 {} 之间", platform, key, min, max),
+                    None => bail!("{}.{} 必须是数字", platform, key),
+                },
+                FieldKind::Bool => {
+                    if !value.is_boolean() {
+                        bail!("{}.{} 必须是布尔值", platform, key);
+                    }
+                }
+                FieldKind::List { max_items } => match value.as_array() {
+                    Some(items) if items.iter().any(|item| !item.is_string()) => {
+                        bail!("{}.{} 只能包含字符串", platform, key)
+                    }
+                    Some(items) if items.len() > *max_items => {
+                        bail!("{}.{} 最多 {} 项", platform, key, max_items)
+                    }
+                    Some(_) => {}
+                    None => bail!("{}.{} 必须是字符串数组", platform, key),
+                },
+            }
+        }
+        Ok(())
+    }
+}
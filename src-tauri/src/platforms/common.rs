@@ -1,26 +1,54 @@
+use super::capabilities::{CustomFields, PostType};
+use super::profile::AutomationProfile;
+use super::strategy_order::UploadStrategy;
+use super::traits::AutomationContext;
+use super::user_scripts::{self, UserScripts};
 use crate::browser::automation;
+use crate::browser::page_handle::PageHandle;
+use crate::browser::page_ops::PageOps;
+use crate::browser::signals::UploadSignals;
+use crate::diagnostics::automation_log::AutomationLogSink;
+use crate::diagnostics::phase_log;
+use crate::diagnostics::progress::ProgressHandle;
 use anyhow::{bail, Result};
-use chromiumoxide::page::Page;
+use chromiumoxide::page::{Page, ScreenshotParams};
+use chrono::{DateTime, Local};
 use log::{info, warn};
 use std::path::Path;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 pub const QUICK_SURFACE_WAIT_SECS: u64 = 2;
 pub const FAST_SIGNAL_TIMEOUT_SECS: u64 = 2;
 pub const SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS: u64 = 6;
 pub const FAST_POLL_INTERVAL_MS: u64 = 200;
 pub const PRE_CLICK_WAIT_MS: u64 = 300;
+/// 点击向导“下一步”后等待下一屏渲染的时间
+const ADVANCE_WAIT_MS: u64 = 1500;
 pub const WEAK_READY_SELF_HEAL_TIMEOUT_SECS: u64 = 8;
 pub const WEAK_READY_RELOAD_WAIT_MS: u64 = 400;
 pub const WECHAT_GUARD_TIMEOUT_SECS: u64 = 20;
 pub const WECHAT_CLICK_RETRY_ROUNDS: usize = 3;
 pub const WECHAT_CLICK_RETRY_WAIT_MS: u64 = 2300;
 pub const WECHAT_INTERACTIVE_RECHECK_TIMEOUT_SECS: u64 = 3;
+/// 自动提交时等待发布按钮可点（通常要等视频上传完成）的上限
+pub const SUBMIT_READY_TIMEOUT_SECS: u64 = 600;
+/// 点击发布后等待成功页的上限
+pub const SUBMIT_VERIFY_TIMEOUT_SECS: u64 = 30;
+const SUBMIT_POLL_INTERVAL_MS: u64 = 1000;
 
+/// 配置的选择器都失效时，按附近标签文字找标题/描述输入框
+const TITLE_LABELS: &[&str] = &["标题", "Title"];
+const DESCRIPTION_LABELS: &[&str] = &["描述", "简介", "Description"];
+
+#[derive(Clone, Copy)]
 pub struct PlatformPublishConfig {
     pub id: &'static str,
     pub name: &'static str,
     pub upload_url: &'static str,
+    /// 平台灰度下发的其他上传入口，按顺序尝试；主入口守卫因 anchor_miss 失败时才使用。
+    /// 路径需同时出现在 `allowed_paths` 中
+    pub upload_url_variants: &'static [&'static str],
     pub target_host: &'static str,
     pub allowed_paths: &'static [&'static str],
     pub surface_selectors: &'static [&'static str],
@@ -36,12 +64,161 @@ pub struct PlatformPublishConfig {
     pub weak_ready_min_body_text_len: usize,
     pub blocked_text_markers: &'static [&'static str],
     pub init_text_markers: &'static [&'static str],
-    pub login_text_markers: &'static [&'static str],
+    /// 登录页识别与登录完成判定，与上传页守卫分开配置
+    pub login_guard: LoginGuardConfig,
+    /// 风控/地区限制验证页的文案；命中后返回 RISK_CONTROL 并自动截图
+    pub risk_control_text_markers: &'static [&'static str],
+    /// 风控验证页的 URL 片段（平台会跳转到独立的验证地址）
+    pub risk_control_url_markers: &'static [&'static str],
+    /// 平台维护/停服公告的文案；命中后返回 PLATFORM_MAINTENANCE，定时任务可稍后重试
+    pub maintenance_text_markers: &'static [&'static str],
+    /// 上传开始信号的声明式匹配规则
+    pub upload_signals: UploadSignals,
     pub title_selectors: &'static [&'static str],
     pub title_editable_selector: Option<&'static str>,
     pub description_selectors: &'static [&'static str],
     pub description_editable_selector: Option<&'static str>,
     pub tag_selectors: &'static [&'static str],
+    /// 字幕文件 input；为空表示该平台不支持自动上传字幕
+    pub subtitle_input_selectors: &'static [&'static str],
+    /// 露出字幕上传入口前需要依次点击的文字
+    pub subtitle_open_text_markers: &'static [&'static str],
+    /// 会挡住上传或发表的强制选择弹窗；None 表示平台没有这类弹窗
+    pub blocking_dialog: Option<BlockingDialogConfig>,
+    /// 上传开始后、填表前等待编辑区出现；None 表示信号确认后立即填表
+    pub editor_ready: Option<EditorReadyConfig>,
+    /// 关闭手动确认时点击的最终发布按钮，以及发布成功的判定
+    pub submit: SubmitConfig,
+}
+
+/// 登录页守卫。发布流程据此把会话过期的跳转识别为 LOGIN_REQUIRED，
+/// 登录窗口据此确认用户已完成登录。
+#[derive(Clone, Copy)]
+pub struct LoginGuardConfig {
+    /// 登录页 URL 片段（host 或 host+路径），命中即视为停在登录页
+    pub url_markers: &'static [&'static str],
+    /// 登录页上的文案（扫码登录等），用于 URL 不变、只弹出登录框的平台
+    pub text_markers: &'static [&'static str],
+    /// 只有登录后才会到达的 URL 片段；为空表示无法据 URL 确认登录完成
+    pub success_url_markers: &'static [&'static str],
+    /// 只有登录后才会出现的元素（顶栏头像等），用于登录状态检测
+    pub avatar_selectors: &'static [&'static str],
+}
+
+/// 最终发布按钮与发布成功的判定。按钮先按选择器找，再按按钮文字完全匹配找，
+/// 避免点到“定时发布”等同样含有“发布”的控件。
+#[derive(Clone, Copy)]
+pub struct SubmitConfig {
+    pub selectors: &'static [&'static str],
+    /// 按钮文字，需与按钮文字完全一致
+    pub text_markers: &'static [&'static str],
+    /// 发布成功后跳转到的 URL 片段
+    pub success_url_markers: &'static [&'static str],
+    /// 发布成功后页面上出现的文案
+    pub success_text_markers: &'static [&'static str],
+}
+
+/// 编辑区就绪条件：任一选择器命中可见元素，或正文出现任一文案即视为就绪。
+/// 超时只记警告并照常填表，由填表结果决定是否失败。
+#[derive(Clone, Copy)]
+pub struct EditorReadyConfig {
+    pub selectors: &'static [&'static str],
+    pub text_markers: &'static [&'static str],
+    /// 多步向导（裁剪、滤镜……）在编辑区出现前需要点击的按钮文字；
+    /// 等待期间编辑区未就绪时点击文字完全相同的可用按钮
+    pub advance_text_markers: &'static [&'static str],
+    pub timeout_secs: u64,
+}
+
+/// 强制选择弹窗（例如视频号的配乐/原声选择）。检测到后先尝试自动选择，
+/// 选不掉时返回 INTERVENTION_REQUIRED，交给用户在 Chrome 里处理。
+#[derive(Clone, Copy)]
+pub struct BlockingDialogConfig {
+    pub name: &'static str,
+    pub dialog_selector: &'static str,
+    pub detect_text_markers: &'static [&'static str],
+    pub choice_text_markers: &'static [&'static str],
+    pub confirm_text_markers: &'static [&'static str],
+    /// 写入结果信号的值，例如 `music=original`
+    pub resolved_signal: &'static str,
+}
+
+/// 定时方式：平台原生定时（schedule=platform）或由应用按计划时间兜底（schedule=app）
+pub const SCHEDULE_MODE_PLATFORM: &str = "platform";
+pub const SCHEDULE_MODE_APP: &str = "app";
+
+/// 一次发布需要填写到平台页面的内容
+#[derive(Debug, Clone)]
+pub struct PublishPayload {
+    pub video_path: String,
+    /// 图文模式下 video_path 不上传，改为上传 images
+    pub post_type: PostType,
+    /// 图文笔记的图片，按顺序上传
+    pub images: Vec<String>,
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    /// 用户指定的发布时间；None 表示立即发布
+    pub publish_at: Option<DateTime<Local>>,
+    /// 只交给平台原生定时的发布时间；平台设置失败时整个账号失败，不由应用兜底
+    pub platform_scheduled_at: Option<DateTime<Local>>,
+    /// 自动生成的 .srt 字幕文件
+    pub subtitle_path: Option<String>,
+    /// 用户提供的封面图片
+    pub cover_path: Option<String>,
+    /// 主视频之后附加到同一条动态的图片或视频，仅支持混排的平台使用
+    pub extra_media: Vec<String>,
+    /// 平台专属选项（分区、可见范围、合集等），已按 capability schema 校验
+    pub custom_fields: CustomFields,
+    /// 等待时长、重试次数与上传策略顺序
+    pub profile: AutomationProfile,
+    /// 发布任务中的进度上报；None 表示不在发布任务中运行
+    pub progress: Option<ProgressHandle>,
+    /// 按 task_platform_id 落库的自动化步骤日志；None 表示不记录
+    pub automation_log: Option<AutomationLogSink>,
+    /// 用户为该平台配置的填表前/后 JS 片段
+    pub user_scripts: UserScripts,
+    /// true 时填表后停下由用户点击发布；false 时自动点击发布并确认成功页
+    pub manual_confirm: bool,
+    /// 用户取消发布任务时触发；轮询循环在每轮检查
+    pub cancel: CancellationToken,
+}
+
+impl PublishPayload {
+    fn report_strategy(&self, strategy: &str) {
+        if let Some(progress) = &self.progress {
+            progress.strategy(strategy);
+        }
+    }
+
+    /// 记录一次上传策略的结果，供下次发布调整策略顺序
+    fn report_strategy_attempt(&self, strategy: &str, succeeded: bool) {
+        if let Some(progress) = &self.progress {
+            progress.strategy_attempt(strategy, succeeded);
+        }
+    }
+
+    /// 记录一步自动化（上传策略尝试等）及其诊断信息，供排障面板查看
+    fn log_step(
+        &self,
+        phase: &str,
+        strategy: Option<&str>,
+        result: &str,
+        elapsed: Duration,
+        detail: &str,
+    ) {
+        if let Some(log) = &self.automation_log {
+            log.record(phase, strategy, result, elapsed, detail);
+        }
+    }
+
+    /// 任务已被取消时返回 CANCELLED，让自动化在下一个检查点停下
+    pub(super) fn ensure_active(&self) -> Result<()> {
+        if self.cancel.is_cancelled() {
+            bail!("CANCELLED: 发布任务已取消");
+        }
+        Ok(())
+    }
 }
 
 struct FillSummary {
@@ -74,44 +251,75 @@ struct UploadPageProbe {
     ready_kind: String,
 }
 
-pub async fn auto_publish_with_config(
-    page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-    cfg: &PlatformPublishConfig,
-) -> Result<String> {
-    info!("开始 {} 自动发布：{}", cfg.name, video_path);
-    let file_ext = Path::new(video_path)
+/// 上传步骤交给填表步骤的结果
+#[derive(Debug, Clone)]
+pub struct UploadOutcome {
+    /// 确认上传开始的信号，例如 `progress:bar`
+    pub signal: String,
+    /// 等待信号期间已处理的强制弹窗
+    pub dialog_signal: Option<&'static str>,
+    pub diagnostics: Vec<String>,
+}
+
+fn file_extension(video_path: &str) -> String {
+    Path::new(video_path)
         .extension()
         .and_then(|ext| ext.to_str())
         .unwrap_or("")
-        .to_ascii_lowercase();
-    if file_ext == "mov" {
+        .to_ascii_lowercase()
+}
+
+/// 打开上传页：进入上传上下文并通过登录/风控守卫，再短暂等待上传区域出现
+pub async fn prepare_page(ctx: &AutomationContext<'_>) -> Result<()> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    let page = &handle.ensure_attached().await?;
+    let profile = &payload.profile;
+    info!("开始 {} 自动发布：{}", cfg.name, payload.video_path);
+    if file_extension(&payload.video_path) == "mov" {
         warn!(
             "[{}上传] 当前文件扩展名为 .mov，部分场景兼容性较差，建议优先使用 .mp4",
             cfg.name
         );
     }
 
-    ensure_upload_context(page, cfg).await?;
+    ensure_upload_context(page, cfg, payload).await?;
+    payload.ensure_active()?;
+    checkpoint(page, cfg, payload, "upload_context", "").await;
 
     info!("[{}上传] 第1步：快速确认上传页面就绪...", cfg.name);
-    if !wait_for_upload_surface_brief(page, cfg, QUICK_SURFACE_WAIT_SECS).await {
+    if !wait_for_upload_surface_brief(page, cfg, profile.quick_surface_wait_secs).await {
         warn!(
             "[{}上传] 在 {} 秒内未确认到上传区域，继续尝试上传动作。",
-            cfg.name, QUICK_SURFACE_WAIT_SECS
+            cfg.name, profile.quick_surface_wait_secs
         );
     }
 
+    checkpoint(page, cfg, payload, "surface", "").await;
+    Ok(())
+}
+
+/// 按策略顺序上传视频直到确认上传信号，再等待编辑区就绪
+pub async fn upload(ctx: &AutomationContext<'_>) -> Result<UploadOutcome> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    let page = &handle.ensure_attached().await?;
+    let video_path = payload.video_path.as_str();
+    let profile = &payload.profile;
+    let file_ext = file_extension(video_path);
+
     info!("[{}上传] 第2步：上传视频文件...", cfg.name);
     let mut upload_signal: Option<String> = None;
     let mut upload_action_performed = false;
-    let mut wechat_file_set_success = false;
-    let mut wechat_chooser_event_state = "none".to_string();
-    let mut wechat_click_round: u8 = 0;
-    let mut wechat_click_method = "none".to_string();
+    let mut wechat_click = WechatClickState::default();
+    let mut attempt_started = std::time::Instant::now();
+    let mut signal_latency = None;
     let mut upload_diagnostics = vec![format!(
         "file_ext={}",
         if file_ext.is_empty() {
@@ -121,217 +329,1081 @@ pub async fn auto_publish_with_config(
         }
     )];
 
+    for strategy in profile.strategy_order(cfg.id) {
+        if upload_signal.is_some() {
+            break;
+        }
+        if strategy == UploadStrategy::DragDrop
+            && cfg.id == "wechat"
+            && wechat_click.file_set_success
+        {
+            // 微信点击上传已写入文件，只等上传信号确认；再拖拽会重复上传
+            upload_diagnostics.push(
+                "D:file_set_success skip_drag_drop_waiting_for_signal_confirmation".to_string(),
+            );
+            continue;
+        }
+        payload.report_strategy(strategy.label());
+        payload.ensure_active()?;
+        attempt_started = std::time::Instant::now();
+        let diagnostics_before = upload_diagnostics.len();
+        let (performed, signal) = match strategy {
+            UploadStrategy::Chooser => {
+                upload_via_file_chooser(page, cfg, profile, video_path, &mut upload_diagnostics)
+                    .await
+            }
+            UploadStrategy::DirectSet => {
+                upload_via_direct_file_set(page, cfg, profile, video_path, &mut upload_diagnostics)
+                    .await
+            }
+            UploadStrategy::DragDrop => {
+                upload_via_drag_drop(page, cfg, profile, video_path, &mut upload_diagnostics).await
+            }
+            UploadStrategy::ClickUpload if cfg.id == "wechat" => {
+                upload_via_wechat_click(
+                    page,
+                    cfg,
+                    payload,
+                    video_path,
+                    &mut upload_diagnostics,
+                    &mut wechat_click,
+                )
+                .await?
+            }
+            UploadStrategy::ClickUpload => {
+                upload_via_click(page, cfg, profile, video_path, &mut upload_diagnostics).await
+            }
+        };
+        upload_action_performed |= performed;
+        payload.report_strategy_attempt(strategy.label(), signal.is_some());
+        payload.log_step(
+            "upload",
+            Some(strategy.label()),
+            match (&signal, performed) {
+                (Some(_), _) => "signal",
+                (None, true) => "no_signal",
+                (None, false) => "failed",
+            },
+            attempt_started.elapsed(),
+            &upload_diagnostics[diagnostics_before..].join(" | "),
+        );
+        if signal.is_some() {
+            signal_latency = Some(attempt_started.elapsed());
+        }
+        upload_signal = signal;
+    }
+
+    if upload_signal.is_none() && !upload_action_performed {
+        bail!(
+            "[{}上传] 所有上传策略均失败，请手动上传。诊断：{}",
+            cfg.name,
+            upload_diagnostics.join(" | ")
+        );
+    }
+
+    let mut dialog_signal = None;
+    if upload_signal.is_none() {
+        // 未等到上传信号时，常见原因是强制弹窗挡住了页面
+        payload.report_strategy("fallback:wait_signal");
+        payload.ensure_active()?;
+        dialog_signal = resolve_blocking_dialog(page, cfg).await?;
+        let fallback_started = std::time::Instant::now();
+        if let Some(signal) = wait_for_upload_signal(page, cfg, profile, profile.slow_fallback_signal_timeout_secs).await {
+            upload_diagnostics.push(format!("fallback:signal={}", signal));
+            // 兜底等到的信号仍算在最后一次上传动作上，慢页面的延迟才能被记下
+            signal_latency = Some(attempt_started.elapsed());
+            upload_signal = Some(signal);
+        } else {
+            upload_diagnostics.push(format!(
+                "fallback:no_signal({}s)",
+                profile.slow_fallback_signal_timeout_secs
+            ));
+        }
+        payload.log_step(
+            "upload",
+            Some("fallback:wait_signal"),
+            if upload_signal.is_some() {
+                "signal"
+            } else {
+                "no_signal"
+            },
+            fallback_started.elapsed(),
+            upload_diagnostics.last().map(String::as_str).unwrap_or(""),
+        );
+    }
+
+    if upload_signal.is_none() && cfg.id == "wechat" && wechat_click.file_set_success {
+        upload_diagnostics.push("fallback:signal=chooser:file_set".to_string());
+        upload_signal = Some("chooser:file_set".to_string());
+    }
+
+    let started_signal = match upload_signal {
+        Some(signal) => signal,
+        None => {
+            if cfg.id == "wechat" {
+                bail!(
+                    "WECHAT_UPLOAD_SIGNAL_TIMEOUT: [{}上传] 已执行上传动作，但在快速检测与兜底检测中都未检测到上传信号。diagnosis: chooser_event_state={} click_round={} click_method={} 诊断：{}",
+                    cfg.name,
+                    wechat_click.chooser_event_state,
+                    wechat_click.click_round,
+                    wechat_click.click_method,
+                    upload_diagnostics.join(" | ")
+                );
+            }
+            bail!(
+                "[{}上传] 已执行上传动作，但在快速检测与兜底检测中都未检测到上传信号。诊断：{}",
+                cfg.name,
+                upload_diagnostics.join(" | ")
+            );
+        }
+    };
+    if let Some(progress) = &payload.progress {
+        progress.signal(&started_signal);
+        if let Some(latency) = signal_latency {
+            progress.signal_latency(latency);
+        }
+    }
+    checkpoint(page, cfg, payload, "upload_signal", &started_signal).await;
+    let signal_source = upload_signal_source(&started_signal);
+    info!(
+        "[{}上传] 上传信号确认：signal={} signal_source={} chooser_event_state={} click_round={} click_method={}",
+        cfg.name,
+        started_signal,
+        signal_source,
+        wechat_click.chooser_event_state,
+        wechat_click.click_round,
+        wechat_click.click_method
+    );
+
+    payload.ensure_active()?;
+    // 上传开始后部分平台会整页跳转到编辑页，原页面会话失效时按 URL 重新附加
+    let page = &handle.ensure_attached().await?;
+    if let Some(editor) = &cfg.editor_ready {
+        let ready = wait_for_editor_ready(handle, cfg, editor, profile).await;
+        checkpoint(page, cfg, payload, "editor_ready", &ready).await;
+    }
+    Ok(UploadOutcome {
+        signal: started_signal,
+        dialog_signal,
+        diagnostics: upload_diagnostics,
+    })
+}
+
+/// 执行用户脚本并填写标题、描述、标签和字幕，返回 `<上传信号>;fill=...` 报告
+pub async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    let started_signal = &upload.signal;
+    let upload_diagnostics = &upload.diagnostics;
+    let mut dialog_signal = upload.dialog_signal;
+
+    payload.ensure_active()?;
+    let page = &handle.ensure_attached().await?;
+    let mut script_markers = Vec::new();
+    if let Some(script) = payload.user_scripts.pre_fill_js.as_deref() {
+        script_markers.push(run_user_script(page, cfg, payload, "pre_fill_js", script).await);
+    }
+
+    let fill_summary = fill_basic_fields(
+        page,
+        &payload.title,
+        &payload.description,
+        &payload.tags,
+        cfg,
+    )
+    .await;
+    info!(
+        "[{}填表] title={} desc={} tags={}/{}",
+        cfg.name,
+        fill_summary.title_marker,
+        fill_summary.description_marker,
+        fill_summary.tags_added,
+        fill_summary.tags_total
+    );
+
+    checkpoint(page, cfg, payload, "fill", &fill_summary.title_marker).await;
+
+    if let Some(script) = payload.user_scripts.post_fill_js.as_deref() {
+        script_markers.push(run_user_script(page, cfg, payload, "post_fill_js", script).await);
+    }
+
+    if !fill_summary.title_ok && !fill_summary.description_ok {
+        if cfg.fill_failure_is_error {
+            bail!(
+                "[{}填表] 上传已触发（signal={}），但标题和描述均未命中可编辑字段。诊断：{}",
+                cfg.name,
+                started_signal,
+                upload_diagnostics.join(" | ")
+            );
+        }
+        warn!(
+            "[{}填表] 上传已触发（signal={}），但标题和描述均未命中可编辑字段。已按平台策略降级为非阻断。诊断：{}",
+            cfg.name,
+            started_signal,
+            upload_diagnostics.join(" | ")
+        );
+    }
+
+    if fill_summary.tags_total > 0 && fill_summary.tags_added < fill_summary.tags_total {
+        warn!(
+            "[{}填表] 标签填充部分失败：{}/{}",
+            cfg.name, fill_summary.tags_added, fill_summary.tags_total
+        );
+    }
+
+    let mut result = format!(
+        "{};fill=title:{},desc:{},tags:{}/{}",
+        started_signal,
+        marker_status(&fill_summary.title_marker),
+        marker_status(&fill_summary.description_marker),
+        fill_summary.tags_added,
+        fill_summary.tags_total
+    );
+    for marker in &script_markers {
+        result.push_str(&format!(";{}", marker));
+    }
+    if let Some(subtitle_path) = &payload.subtitle_path {
+        let status = upload_subtitle(page, cfg, subtitle_path).await;
+        result.push_str(&format!(";subtitle={}", status));
+    }
+    // 弹窗也可能在填表后、发表前才出现
+    if dialog_signal.is_none() {
+        dialog_signal = resolve_blocking_dialog(page, cfg).await?;
+    }
+    if let Some(signal) = dialog_signal {
+        result.push_str(&format!(";{}", signal));
+    }
+    checkpoint(page, cfg, payload, "done", &result).await;
+    Ok(result)
+}
+
+/// 执行用户配置的 JS 片段，返回值写入检查点；跳过空片段
+async fn run_user_script(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+    stage: &'static str,
+    script: &str,
+) -> String {
+    if script.trim().is_empty() {
+        return format!("{}=empty", stage);
+    }
+    let timeout = payload.user_scripts.timeout();
+    let outcome = user_scripts::run(page, cfg.name, stage, script, timeout).await;
+    let detail = format!("{}:{}", outcome.status, outcome.output);
+    checkpoint(page, cfg, payload, stage, &detail).await;
+    outcome.marker()
+}
+
+/// 按自动化档位记录每一步：thorough/diagnostic 截图，diagnostic 额外写入阶段日志。
+/// 截图失败只记警告，不影响发布流程。
+async fn checkpoint(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+    step: &str,
+    detail: &str,
+) {
+    if let Some(progress) = &payload.progress {
+        progress.phase(step);
+    }
+    let profile = &payload.profile;
+    if profile.trace {
+        phase_log::record(cfg.id, &format!("step:{}", step), detail);
+    }
+    let Some(dir) = &profile.screenshot_dir else {
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        warn!("[{}截图] 创建目录失败：{}", cfg.name, e);
+        return;
+    }
+    let path = dir.join(format!(
+        "{}-{}-{}.png",
+        Local::now().format("%H%M%S%3f"),
+        cfg.id,
+        step
+    ));
+    let params = ScreenshotParams::builder().full_page(true).build();
+    match page.save_screenshot(params, &path).await {
+        Ok(_) => info!("[{}截图] {} -> {}", cfg.name, step, path.display()),
+        Err(e) => warn!("[{}截图] {} 失败：{}", cfg.name, step, e),
+    }
+}
+
+/// 风控验证页检测：先比对 URL，再扫描正文文案。返回命中的标记，未命中返回 None。
+async fn detect_risk_control<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    url: &str,
+) -> Option<String> {
+    if let Some(marker) = cfg
+        .risk_control_url_markers
+        .iter()
+        .find(|marker| url.contains(*marker))
+    {
+        return Some(format!("url:{}", marker));
+    }
+    find_text_marker(page, cfg.risk_control_text_markers).await
+}
+
+/// 维护公告检测：扫描正文中的维护/暂停上传文案
+async fn detect_maintenance<P: PageOps>(page: &P, cfg: &PlatformPublishConfig) -> Option<String> {
+    find_text_marker(page, cfg.maintenance_text_markers).await
+}
+
+/// 在正文中查找文案的脚本，返回第一个命中的文案，未命中返回空串
+fn text_marker_script(markers: &[&str]) -> String {
+    format!(
+        r#"
+        (function(markers) {{
+            const text = (document.body && document.body.innerText) || '';
+            return markers.find((marker) => text.includes(marker)) || '';
+        }})([{}])
+        "#,
+        js_array(markers)
+    )
+}
+
+/// 返回正文中第一个命中的文案（`text:<marker>`）
+pub(super) async fn find_text_marker<P: PageOps>(page: &P, markers: &[&str]) -> Option<String> {
+    if markers.is_empty() {
+        return None;
+    }
+    let js = text_marker_script(markers);
+    let hit = page.evaluate_json(&js).await.ok()?;
+    match hit.as_str() {
+        Some(hit) if !hit.is_empty() => Some(format!("text:{}", hit)),
+        _ => None,
+    }
+}
+
+/// 发布前核对的一项平台选项
+pub enum SelectionCheck {
+    /// 文字为 `label` 的单选项（或其所在选项）处于选中状态
+    CheckedLabel { field: &'static str, label: String },
+    /// `selector` 命中元素即视为已按配置选中
+    Selector {
+        field: &'static str,
+        selector: String,
+    },
+}
+
+/// 自动提交前的最终核对：页面标题与本次请求一致、文件框里是本次的文件、
+/// 平台选项按配置选中。任一项不一致时返回 SUBMIT_BLOCKED_VERIFICATION 并停在
+/// 发布前，页面读不到的项只记为 unverified，不阻断发布。
+pub async fn verify_before_submit(
+    ctx: &AutomationContext<'_>,
+    checks: Vec<SelectionCheck>,
+) -> Result<String> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    payload.ensure_active()?;
+    if let Some(progress) = &payload.progress {
+        progress.phase("submit_check");
+    }
+    let page = &handle.ensure_attached().await?;
+    let mut mismatches = Vec::new();
+    let mut verified = Vec::new();
+
+    let expected_title = normalize_for_compare(&payload.title);
+    if cfg.title_selectors.is_empty() || expected_title.is_empty() {
+        verified.push("title=skipped".to_string());
+    } else {
+        match read_field_text(page, cfg.title_selectors).await {
+            Some(actual) => {
+                let actual = normalize_for_compare(&actual);
+                // 平台输入框的 maxlength 会截断标题，页面值是请求标题的前缀即可
+                if !actual.is_empty() && expected_title.starts_with(&actual) {
+                    verified.push("title=ok".to_string());
+                } else {
+                    mismatches.push(format!("标题为“{}”，应为“{}”", actual, payload.title));
+                }
+            }
+            None => verified.push("title=unverified".to_string()),
+        }
+    }
+
+    let media = if payload.post_type == PostType::Images {
+        payload.images.clone()
+    } else {
+        vec![payload.video_path.clone()]
+    };
+    let expected_files = media
+        .iter()
+        .filter_map(|path| Path::new(path).file_name()?.to_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    let attached = attached_file_names(page).await;
+    if attached.is_empty() {
+        verified.push("file=unverified".to_string());
+    } else if attached.iter().any(|name| expected_files.contains(name)) {
+        verified.push("file=ok".to_string());
+    } else {
+        mismatches.push(format!(
+            "已附加文件为 {}，应为 {}",
+            attached.join("、"),
+            expected_files.join("、")
+        ));
+    }
+
+    for check in &checks {
+        let (field, selected) = match check {
+            SelectionCheck::CheckedLabel { field, label } => {
+                (*field, label_checked(page, label).await)
+            }
+            SelectionCheck::Selector { field, selector } => {
+                (*field, selector_match_count(page, selector).await > 0)
+            }
+        };
+        if selected {
+            verified.push(format!("{}=ok", field));
+        } else {
+            let expected = payload.custom_fields.text(field).unwrap_or("默认");
+            mismatches.push(format!("{} 未选中“{}”", field, expected));
+        }
+    }
+
+    if !mismatches.is_empty() {
+        bail!(
+            "SUBMIT_BLOCKED_VERIFICATION: [{}提交] 发布前核对不一致，已停止发布：{}",
+            cfg.name,
+            mismatches.join("；")
+        );
+    }
+    let summary = verified.join(",");
+    info!("[{}提交] 发布前核对通过：{}", cfg.name, summary);
+    Ok(summary)
+}
+
+fn normalize_for_compare(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// 第一个命中选择器的输入框的值（或可编辑区文字）；都没命中时为 None
+async fn read_field_text(page: &Page, selectors: &[&str]) -> Option<String> {
+    let script = format!(
+        r#"(() => {{
+            for (const selector of {}) {{
+                let el = null;
+                try {{ el = document.querySelector(selector); }} catch (_) {{ continue; }}
+                if (!el) continue;
+                return typeof el.value === 'string' ? el.value : (el.innerText || '');
+            }}
+            return null;
+        }})()"#,
+        serde_json::to_string(selectors).unwrap_or_default()
+    );
+    page.evaluate(script)
+        .await
+        .ok()
+        .and_then(|v| v.into_value::<Option<String>>().ok())
+        .flatten()
+}
+
+/// 页面所有文件框里已选中的文件名
+async fn attached_file_names(page: &Page) -> Vec<String> {
+    let script = r#"(() => Array.from(document.querySelectorAll("input[type='file']"))
+        .flatMap((input) => Array.from(input.files || []).map((file) => file.name)))()"#;
+    page.evaluate(script)
+        .await
+        .ok()
+        .and_then(|v| v.into_value::<Vec<String>>().ok())
+        .unwrap_or_default()
+}
+
+/// 文字为 `label` 的选项本身或所在的几层父元素是否处于选中状态
+async fn label_checked(page: &Page, label: &str) -> bool {
+    let script = format!(
+        r#"(() => {{
+            const target = {}.replace(/\s+/g, '');
+            const isChecked = (el) =>
+                el.matches('input:checked') ||
+                !!el.querySelector('input:checked') ||
+                el.getAttribute('aria-checked') === 'true' ||
+                el.getAttribute('aria-selected') === 'true' ||
+                /(^|[-_\s])(checked|active|selected)/i.test(el.className || '');
+            const nodes = document.querySelectorAll('label, span, div, [role="radio"], [role="option"]');
+            for (const node of nodes) {{
+                const text = (node.innerText || node.textContent || '').replace(/\s+/g, '');
+                if (text !== target) continue;
+                let el = node;
+                for (let depth = 0; el && depth < 4; depth += 1, el = el.parentElement) {{
+                    if (isChecked(el)) return true;
+                }}
+            }}
+            return false;
+        }})()"#,
+        serde_json::to_string(label).unwrap_or_default()
+    );
+    page.evaluate(script)
+        .await
+        .ok()
+        .and_then(|v| v.into_value::<bool>().ok())
+        .unwrap_or(false)
+}
+
+/// 自动提交第一步：等待发布按钮可点后点击，返回命中的按钮。按钮一直不可点时
+/// 返回 SUBMIT_FAILED，上传与填表结果保留在页面上。
+pub async fn confirm_publish(ctx: &AutomationContext<'_>) -> Result<String> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    let submit = &cfg.submit;
+    if submit.selectors.is_empty() && submit.text_markers.is_empty() {
+        bail!("SUBMIT_FAILED: [{}提交] 平台未配置发布按钮", cfg.name);
+    }
+    if let Some(progress) = &payload.progress {
+        progress.phase("submit");
+    }
+
+    let ready_timeout = payload.profile.scaled(SUBMIT_READY_TIMEOUT_SECS);
+    let ready_start = std::time::Instant::now();
+    let clicked = loop {
+        payload.ensure_active()?;
+        let page = handle.ensure_attached().await?;
+        let state = click_submit_button(&page, submit).await;
+        if let Some(marker) = state.strip_prefix("clicked:") {
+            break marker.to_string();
+        }
+        if ready_start.elapsed() >= ready_timeout {
+            bail!(
+                "SUBMIT_FAILED: [{}提交] {} 秒内发布按钮未就绪（state={}）",
+                cfg.name,
+                ready_timeout.as_secs(),
+                state
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(SUBMIT_POLL_INTERVAL_MS)).await;
+    };
+    info!("[{}提交] 已点击发布按钮：{}", cfg.name, clicked);
+    Ok(clicked)
+}
+
+/// 自动提交第二步：点击发布后等待成功页，返回 `submit=verified:<命中>`；
+/// 平台未配置成功判定时返回 `submit=clicked:<按钮>`，超时未确认返回 SUBMIT_FAILED
+pub async fn verify_success(ctx: &AutomationContext<'_>, clicked: &str) -> Result<String> {
+    let AutomationContext {
+        handle,
+        payload,
+        cfg,
+    } = *ctx;
+    let submit = &cfg.submit;
+    if submit.success_url_markers.is_empty() && submit.success_text_markers.is_empty() {
+        return Ok(format!("submit=clicked:{}", clicked));
+    }
+    let verify_timeout = payload.profile.scaled(SUBMIT_VERIFY_TIMEOUT_SECS);
+    let verify_start = std::time::Instant::now();
+    loop {
+        payload.ensure_active()?;
+        // 发布后常会整页跳转，每轮都确认页面仍可用
+        if let Ok(page) = handle.ensure_attached().await {
+            let url = current_url(&page).await;
+            let hit = match submit
+                .success_url_markers
+                .iter()
+                .find(|marker| url.contains(*marker))
+            {
+                Some(marker) => Some(format!("url:{}", marker)),
+                None => find_text_marker(&page, submit.success_text_markers).await,
+            };
+            if let Some(hit) = hit {
+                info!("[{}提交] 已确认发布成功：{}", cfg.name, hit);
+                return Ok(format!("submit=verified:{}", hit));
+            }
+        }
+        if verify_start.elapsed() >= verify_timeout {
+            bail!(
+                "SUBMIT_FAILED: [{}提交] 已点击发布（{}），但 {} 秒内未确认发布成功",
+                cfg.name,
+                clicked,
+                verify_timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(SUBMIT_POLL_INTERVAL_MS)).await;
+    }
+}
+
+fn submit_button_script(submit: &SubmitConfig) -> String {
+    format!(
+        r#"
+        (function(selectors, markers) {{
+            const visible = (el) => {{
+                const rect = el.getBoundingClientRect();
+                return rect && rect.width > 0 && rect.height > 0;
+            }};
+            const disabled = (el) => el.disabled
+                || el.getAttribute('aria-disabled') === 'true'
+                || /(^|[-_ ])disabled?([-_ ]|$)/i.test(el.className || '');
+            const candidates = [];
+            for (const sel of selectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    nodes = [];
+                }}
+                for (const el of nodes) candidates.push([el, 'selector:' + sel]);
+            }}
+            const buttons = Array.from(document.querySelectorAll('button, [role="button"]'));
+            for (const marker of markers) {{
+                for (const el of buttons) {{
+                    if ((el.innerText || el.textContent || '').trim() === marker) {{
+                        candidates.push([el, 'text:' + marker]);
+                    }}
+                }}
+            }}
+            let blocked = '';
+            for (const [el, hit] of candidates) {{
+                if (!visible(el)) continue;
+                if (disabled(el)) {{
+                    blocked = blocked || hit;
+                    continue;
+                }}
+                el.scrollIntoView({{ block: 'center' }});
+                el.click();
+                return 'clicked:' + hit;
+            }}
+            return blocked ? 'disabled:' + blocked : 'not_found';
+        }})([{}], [{}])
+        "#,
+        js_array(submit.selectors),
+        js_array(submit.text_markers)
+    )
+}
+
+/// 找到可点的发布按钮并点击，返回 `clicked:<命中>`、`disabled:<命中>` 或 `not_found`
+async fn click_submit_button<P: PageOps>(page: &P, submit: &SubmitConfig) -> String {
+    let js = submit_button_script(submit);
+    match page.evaluate_json(&js).await {
+        Ok(value) => value.as_str().unwrap_or("not_found").to_string(),
+        Err(e) => format!("error:{}", e),
+    }
+}
+
+/// 自动化结束、交给用户确认发布前，对填好的表单截一张预览图，便于在应用内
+/// 集中核对各平台内容。不论自动化档位都会截图；截图失败只记警告。
+pub async fn capture_preview(
+    handle: &PageHandle,
+    payload: &PublishPayload,
+    platform: &str,
+) -> Option<String> {
+    let dir = payload
+        .profile
+        .screenshot_dir
+        .as_ref()
+        .or(payload.profile.evidence_dir.as_ref())?;
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        warn!("[预览截图] 创建目录失败：{}", e);
+        return None;
+    }
+    let page = match handle.ensure_attached().await {
+        Ok(page) => page,
+        Err(e) => {
+            warn!("[预览截图] {} 页面已断开：{}", platform, e);
+            return None;
+        }
+    };
+    let path = dir.join(format!(
+        "{}-{}-preview.png",
+        Local::now().format("%H%M%S%3f"),
+        platform
+    ));
+    let params = ScreenshotParams::builder().full_page(true).build();
+    match page.save_screenshot(params, &path).await {
+        Ok(_) => {
+            info!("[预览截图] {} -> {}", platform, path.display());
+            Some(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            warn!("[预览截图] {} 失败：{}", platform, e);
+            None
+        }
+    }
+}
+
+/// 风控页不论自动化档位都截图留证；优先写入档位截图目录，否则写入留证目录。
+async fn capture_risk_control<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+) -> Option<String> {
+    let dir = payload
+        .profile
+        .screenshot_dir
+        .as_ref()
+        .or(payload.profile.evidence_dir.as_ref())?;
+    if let Err(e) = std::fs::create_dir_all(dir) {
+        warn!("[{}风控] 创建截图目录失败：{}", cfg.name, e);
+        return None;
+    }
+    let path = dir.join(format!(
+        "{}-{}-risk_control.png",
+        Local::now().format("%H%M%S%3f"),
+        cfg.id
+    ));
+    match page.save_full_screenshot(&path).await {
+        Ok(()) => {
+            warn!("[{}风控] 已保存验证页截图：{}", cfg.name, path.display());
+            Some(path.to_string_lossy().to_string())
+        }
+        Err(e) => {
+            warn!("[{}风控] 截图失败：{}", cfg.name, e);
+            None
+        }
+    }
+}
+
+/// 检测并处理强制选择弹窗。没有弹窗返回 None；自动选择成功返回结果信号；
+/// 自动选择失败返回 INTERVENTION_REQUIRED 错误，页面保持原样等待用户处理。
+async fn resolve_blocking_dialog(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+) -> Result<Option<&'static str>> {
+    let Some(dialog) = &cfg.blocking_dialog else {
+        return Ok(None);
+    };
+    if !blocking_dialog_visible(page, dialog).await {
+        return Ok(None);
+    }
+    info!("[{}弹窗] 检测到{}弹窗，尝试自动处理", cfg.name, dialog.name);
+
+    let chosen = automation::click_by_text(
+        page,
+        dialog.choice_text_markers,
+        Some(dialog.dialog_selector),
+    )
+    .await;
+    if let Ok(marker) = &chosen {
+        tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
+        if !dialog.confirm_text_markers.is_empty() {
+            let _ = automation::click_by_text(
+                page,
+                dialog.confirm_text_markers,
+                Some(dialog.dialog_selector),
+            )
+            .await;
+            tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
+        }
+        if !blocking_dialog_visible(page, dialog).await {
+            info!("[{}弹窗] 已自动选择：{}", cfg.name, marker);
+            return Ok(Some(dialog.resolved_signal));
+        }
+    }
+    bail!(
+        "INTERVENTION_REQUIRED: [{}] {}弹窗无法自动处理（{}），请在 Chrome 页面手动选择后继续发表。",
+        cfg.name,
+        dialog.name,
+        chosen.err().map(|e| e.to_string()).unwrap_or_else(|| "弹窗未关闭".to_string())
+    );
+}
+
+fn blocking_dialog_script(dialog: &BlockingDialogConfig) -> String {
+    format!(
+        r#"
+        (function() {{
+            const markers = [{}];
+            let dialogs = [];
+            try {{
+                dialogs = Array.from(document.querySelectorAll('{}'));
+            }} catch (_) {{
+                return false;
+            }}
+            return dialogs.some((el) => {{
+                const rect = el.getBoundingClientRect();
+                if (!rect || rect.width < 4 || rect.height < 4) return false;
+                const text = (el.innerText || '').replace(/\s+/g, '');
+                return markers.some((m) => text.includes(m));
+            }});
+        }})()
+        "#,
+        js_array(dialog.detect_text_markers),
+        escape_js_single(dialog.dialog_selector)
+    )
+}
+
+async fn blocking_dialog_visible(page: &Page, dialog: &BlockingDialogConfig) -> bool {
+    let js = blocking_dialog_script(dialog);
+    page.evaluate(js.as_str())
+        .await
+        .map(|v| v.into_value().unwrap_or(false))
+        .unwrap_or(false)
+}
+
+/// 微信策略D多轮点击的结果，上传信号确认与失败诊断时输出
+struct WechatClickState {
+    file_set_success: bool,
+    chooser_event_state: String,
+    click_round: u8,
+    click_method: String,
+}
+
+impl Default for WechatClickState {
+    fn default() -> Self {
+        Self {
+            file_set_success: false,
+            chooser_event_state: "none".to_string(),
+            click_round: 0,
+            click_method: "none".to_string(),
+        }
+    }
+}
+
+/// 策略A。返回（是否执行了上传动作，上传信号）
+async fn upload_via_file_chooser<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    profile: &AutomationProfile,
+    video_path: &str,
+    diagnostics: &mut Vec<String>,
+) -> (bool, Option<String>) {
+    let mut performed = false;
+    let mut found = None;
     for selector in cfg.file_input_selectors {
         info!("[{}上传] 策略A：文件选择器拦截，选择器：{}", cfg.name, selector);
         let count = selector_match_count(page, selector).await;
         if count <= 0 {
-            upload_diagnostics.push(format!("A:{} count=0", selector));
+            diagnostics.push(format!("A:{} count=0", selector));
             continue;
         }
-        upload_diagnostics.push(format!("A:{} count={}", selector, count));
+        diagnostics.push(format!("A:{} count={}", selector, count));
 
         match automation::upload_file_via_file_chooser(page, video_path, selector).await {
             Ok(()) => {
-                upload_action_performed = true;
-                if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                    upload_signal = Some(signal.clone());
-                    upload_diagnostics.push(format!("A:{} signal={}", selector, signal));
+                performed = true;
+                if let Some(signal) = wait_for_upload_signal(page, cfg, profile, profile.fast_signal_timeout_secs).await {
+                    found = Some(signal.clone());
+                    diagnostics.push(format!("A:{} signal={}", selector, signal));
                     break;
                 }
-                upload_diagnostics.push(format!(
+                diagnostics.push(format!(
                     "A:{} no_signal_fast({}s)",
-                    selector, FAST_SIGNAL_TIMEOUT_SECS
+                    selector, profile.fast_signal_timeout_secs
                 ));
             }
             Err(e) => {
-                upload_diagnostics.push(format!("A:{} failed={}", selector, e));
+                diagnostics.push(format!("A:{} failed={}", selector, e));
             }
         }
     }
+    (performed, found)
+}
 
-    if upload_signal.is_none() {
-        info!(
-            "[{}上传] 策略A失败，尝试策略B：setFileInputFiles + 事件派发...",
-            cfg.name
-        );
-        for selector in cfg.file_input_selectors {
-            let count = selector_match_count(page, selector).await;
-            if count <= 0 {
-                upload_diagnostics.push(format!("B:{} count=0", selector));
-                continue;
-            }
+/// 策略B。返回（是否执行了上传动作，上传信号）
+async fn upload_via_direct_file_set<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    profile: &AutomationProfile,
+    video_path: &str,
+    diagnostics: &mut Vec<String>,
+) -> (bool, Option<String>) {
+    let mut performed = false;
+    let mut found = None;
+    info!("[{}上传] 策略B：setFileInputFiles + 事件派发...", cfg.name);
+    for selector in cfg.file_input_selectors {
+        let count = selector_match_count(page, selector).await;
+        if count <= 0 {
+            diagnostics.push(format!("B:{} count=0", selector));
+            continue;
+        }
 
-            match automation::set_file_input(page, selector, video_path).await {
-                Ok(()) => {
-                    upload_action_performed = true;
-                    let dispatch_js = format!(
-                        r#"
-                        (function() {{
-                            const el = document.querySelector('{}');
-                            if (!el) return 'not_found';
-                            el.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                            el.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                            return 'dispatched:files=' + (el.files ? el.files.length : 0);
-                        }})()"#,
-                        escape_js_single(selector)
-                    );
-                    let dispatch_result: String = page
-                        .evaluate(dispatch_js.as_str())
-                        .await
-                        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
-                        .unwrap_or_else(|_| "error".to_string());
-                    upload_diagnostics.push(format!("B:{} dispatch={}", selector, dispatch_result));
-
-                    if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                        upload_signal = Some(signal.clone());
-                        upload_diagnostics.push(format!("B:{} signal={}", selector, signal));
-                        break;
-                    }
-                    upload_diagnostics.push(format!(
-                        "B:{} no_signal_fast({}s)",
-                        selector, FAST_SIGNAL_TIMEOUT_SECS
-                    ));
-                }
-                Err(e) => {
-                    upload_diagnostics.push(format!("B:{} failed={}", selector, e));
+        match automation::set_file_input(page, selector, &[video_path]).await {
+            Ok(()) => {
+                performed = true;
+                let dispatch_js = format!(
+                    r#"
+                    (function() {{
+                        const el = document.querySelector('{}');
+                        if (!el) return 'not_found';
+                        el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                        el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                        return 'dispatched:files=' + (el.files ? el.files.length : 0);
+                    }})()"#,
+                    escape_js_single(selector)
+                );
+                let dispatch_result = page
+                    .evaluate_json(&dispatch_js)
+                    .await
+                    .ok()
+                    .and_then(|v| v.as_str().map(str::to_string))
+                    .unwrap_or_else(|| "error".to_string());
+                diagnostics.push(format!("B:{} dispatch={}", selector, dispatch_result));
+
+                if let Some(signal) = wait_for_upload_signal(page, cfg, profile, profile.fast_signal_timeout_secs).await {
+                    found = Some(signal.clone());
+                    diagnostics.push(format!("B:{} signal={}", selector, signal));
+                    break;
                 }
+                diagnostics.push(format!(
+                    "B:{} no_signal_fast({}s)",
+                    selector, profile.fast_signal_timeout_secs
+                ));
+            }
+            Err(e) => {
+                diagnostics.push(format!("B:{} failed={}", selector, e));
             }
         }
     }
+    (performed, found)
+}
 
-    if upload_signal.is_none() && cfg.id == "wechat" {
-        info!("[{}上传] 策略B失败，微信优先尝试策略D：点击上传入口...", cfg.name);
-        let click_retry_start = std::time::Instant::now();
-        for round in 1..=WECHAT_CLICK_RETRY_ROUNDS {
-            upload_diagnostics.push(format!(
-                "D:round={} start_ms={}",
-                round,
-                click_retry_start.elapsed().as_millis()
-            ));
-
-            if round > 1 {
-                let interactive_probe = wait_for_wechat_interactive_ready(
-                    page,
-                    cfg,
-                    WECHAT_INTERACTIVE_RECHECK_TIMEOUT_SECS,
-                )
-                .await;
-                if let Some(probe) = interactive_probe {
-                    upload_diagnostics.push(format!(
-                        "D:round={} interactive_ready candidates={} context={}",
-                        round,
-                        probe.interactive_candidate_count,
-                        if probe.interactive_context.is_empty() {
-                            "none"
-                        } else {
-                            &probe.interactive_context
-                        }
-                    ));
-                } else {
-                    upload_diagnostics.push(format!(
-                        "D:round={} interactive_pending(timeout={}s)",
-                        round, WECHAT_INTERACTIVE_RECHECK_TIMEOUT_SECS
-                    ));
-                }
-                tokio::time::sleep(Duration::from_millis(WECHAT_CLICK_RETRY_WAIT_MS)).await;
+/// 策略C。返回（是否执行了上传动作，上传信号）
+async fn upload_via_drag_drop(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    profile: &AutomationProfile,
+    video_path: &str,
+    diagnostics: &mut Vec<String>,
+) -> (bool, Option<String>) {
+    info!("[{}上传] 尝试策略C：拖拽上传...", cfg.name);
+    match automation::upload_file_via_drag_drop(page, video_path, cfg.id, cfg.drop_zone_selectors)
+        .await
+    {
+        Ok(selector) => {
+            diagnostics.push(format!("C:drag_drop selector={}", selector));
+            let signal =
+                wait_for_upload_signal(page, cfg, profile, profile.fast_signal_timeout_secs).await;
+            match &signal {
+                Some(signal) => diagnostics.push(format!("C:signal={}", signal)),
+                None => diagnostics.push(format!(
+                    "C:no_signal_fast({}s)",
+                    profile.fast_signal_timeout_secs
+                )),
             }
+            (true, signal)
+        }
+        Err(e) => {
+            diagnostics.push(format!("C:failed={}", e));
+            (false, None)
+        }
+    }
+}
 
-            if !cfg.pre_click_selectors.is_empty() {
-                match automation::click_first_visible(page, cfg.pre_click_selectors).await {
-                    Ok(marker) => {
-                        upload_diagnostics.push(format!("D:round={} pre_click={}", round, marker));
-                        tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
-                    }
-                    Err(e) => {
-                        upload_diagnostics.push(format!("D:round={} pre_click_failed={}", round, e));
-                    }
-                }
+/// 策略D：点击一次上传入口。返回（是否执行了上传动作，上传信号）
+async fn upload_via_click(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    profile: &AutomationProfile,
+    video_path: &str,
+    diagnostics: &mut Vec<String>,
+) -> (bool, Option<String>) {
+    info!("[{}上传] 尝试策略D：点击上传入口...", cfg.name);
+    if !cfg.pre_click_selectors.is_empty() {
+        match automation::click_first_visible(page, cfg.pre_click_selectors).await {
+            Ok(marker) => {
+                diagnostics.push(format!("D:pre_click={}", marker));
+                tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
             }
-
-            match automation::upload_file_via_click_to_open_file_chooser(
-                page,
-                video_path,
-                cfg.id,
-                cfg.click_selectors,
-                cfg.click_text_markers,
-            )
-            .await
-            {
-                Ok(click_result) => {
-                    upload_action_performed = true;
-                    wechat_file_set_success = wechat_file_set_success || click_result.file_set;
-                    wechat_chooser_event_state = click_result.chooser_event_state.clone();
-                    wechat_click_round = wechat_click_round.max(click_result.click_round);
-                    wechat_click_method = click_result.click_method.clone();
-                    upload_diagnostics.push(format!(
-                        "D:round={} clicked={} chooser_opened={} chooser_event_state={} click_method={} click_round={} clicked_context={} signal_source={}",
-                        round,
-                        click_result.marker,
-                        click_result.chooser_opened,
-                        click_result.chooser_event_state,
-                        click_result.click_method,
-                        click_result.click_round,
-                        if click_result.clicked_context.is_empty() {
-                            "none"
-                        } else {
-                            &click_result.clicked_context
-                        },
-                        click_result.signal_source
-                    ));
-                    if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                        upload_signal = Some(signal.clone());
-                        upload_diagnostics.push(format!("D:round={} signal={}", round, signal));
-                        break;
-                    }
-                    upload_diagnostics.push(format!(
-                        "D:round={} no_signal_fast({}s)",
-                        round, FAST_SIGNAL_TIMEOUT_SECS
-                    ));
-                }
-                Err(e) => {
-                    upload_diagnostics.push(format!("D:round={} failed={}", round, e));
-                }
+            Err(e) => {
+                diagnostics.push(format!("D:pre_click_failed={}", e));
             }
         }
-
-        upload_diagnostics.push(format!(
-            "D:summary chooser_event_state={} click_round={} click_method={} file_set_success={}",
-            wechat_chooser_event_state, wechat_click_round, wechat_click_method, wechat_file_set_success
-        ));
     }
 
-    if upload_signal.is_none() && cfg.id != "wechat" {
-        info!("[{}上传] 策略B失败，尝试策略C：拖拽上传...", cfg.name);
-        match automation::upload_file_via_drag_drop(page, video_path, cfg.id, cfg.drop_zone_selectors)
-            .await
-        {
-            Ok(selector) => {
-                upload_action_performed = true;
-                upload_diagnostics.push(format!("C:drag_drop selector={}", selector));
-                if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                    upload_signal = Some(signal.clone());
-                    upload_diagnostics.push(format!("C:signal={}", signal));
+    match automation::upload_file_via_click_to_open_file_chooser(
+        page,
+        video_path,
+        cfg.id,
+        cfg.click_selectors,
+        cfg.click_text_markers,
+    )
+    .await
+    {
+        Ok(click_result) => {
+            diagnostics.push(format!(
+                "D:clicked={} chooser_opened={} chooser_event_state={} click_method={} click_round={} clicked_context={} signal_source={}",
+                click_result.marker,
+                click_result.chooser_opened,
+                click_result.chooser_event_state,
+                click_result.click_method,
+                click_result.click_round,
+                if click_result.clicked_context.is_empty() {
+                    "none"
                 } else {
-                    upload_diagnostics
-                        .push(format!("C:no_signal_fast({}s)", FAST_SIGNAL_TIMEOUT_SECS));
-                }
-            }
-            Err(e) => {
-                upload_diagnostics.push(format!("C:failed={}", e));
+                    &click_result.clicked_context
+                },
+                click_result.signal_source
+            ));
+            let signal =
+                wait_for_upload_signal(page, cfg, profile, profile.fast_signal_timeout_secs).await;
+            match &signal {
+                Some(signal) => diagnostics.push(format!("D:signal={}", signal)),
+                None => diagnostics.push(format!(
+                    "D:no_signal_fast({}s)",
+                    profile.fast_signal_timeout_secs
+                )),
             }
+            (true, signal)
+        }
+        Err(e) => {
+            diagnostics.push(format!("D:failed={}", e));
+            (false, None)
         }
     }
+}
+
+/// 微信策略D：上传入口常晚于页面出现，多轮点击，每轮前重新确认可交互。
+/// 返回（是否执行了上传动作，上传信号）
+async fn upload_via_wechat_click(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+    video_path: &str,
+    diagnostics: &mut Vec<String>,
+    state: &mut WechatClickState,
+) -> Result<(bool, Option<String>)> {
+    info!("[{}上传] 尝试策略D：点击上传入口...", cfg.name);
+    let profile = &payload.profile;
+    let mut performed = false;
+    let mut found = None;
+    let click_retry_start = std::time::Instant::now();
+    let interactive_recheck_secs = WECHAT_INTERACTIVE_RECHECK_TIMEOUT_SECS * profile.timeout_factor;
+    for round in 1..=profile.wechat_click_retry_rounds {
+        payload.ensure_active()?;
+        diagnostics.push(format!(
+            "D:round={} start_ms={}",
+            round,
+            click_retry_start.elapsed().as_millis()
+        ));
+
+        if round > 1 {
+            let interactive_probe =
+                wait_for_wechat_interactive_ready(page, cfg, interactive_recheck_secs).await;
+            if let Some(probe) = interactive_probe {
+                diagnostics.push(format!(
+                    "D:round={} interactive_ready candidates={} context={}",
+                    round,
+                    probe.interactive_candidate_count,
+                    if probe.interactive_context.is_empty() {
+                        "none"
+                    } else {
+                        &probe.interactive_context
+                    }
+                ));
+            } else {
+                diagnostics.push(format!(
+                    "D:round={} interactive_pending(timeout={}s)",
+                    round, interactive_recheck_secs
+                ));
+            }
+            tokio::time::sleep(Duration::from_millis(WECHAT_CLICK_RETRY_WAIT_MS)).await;
+        }
 
-    if upload_signal.is_none() && cfg.id != "wechat" {
-        info!("[{}上传] 策略C后仍未触发，尝试策略D：点击上传入口...", cfg.name);
         if !cfg.pre_click_selectors.is_empty() {
             match automation::click_first_visible(page, cfg.pre_click_selectors).await {
                 Ok(marker) => {
-                    upload_diagnostics.push(format!("D:pre_click={}", marker));
+                    diagnostics.push(format!("D:round={} pre_click={}", round, marker));
                     tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
                 }
                 Err(e) => {
-                    upload_diagnostics.push(format!("D:pre_click_failed={}", e));
+                    diagnostics.push(format!("D:round={} pre_click_failed={}", round, e));
                 }
             }
         }
@@ -343,11 +1415,17 @@ pub async fn auto_publish_with_config(
             cfg.click_selectors,
             cfg.click_text_markers,
         )
-        .await {
+        .await
+        {
             Ok(click_result) => {
-                upload_action_performed = true;
-                upload_diagnostics.push(format!(
-                    "D:clicked={} chooser_opened={} chooser_event_state={} click_method={} click_round={} clicked_context={} signal_source={}",
+                performed = true;
+                state.file_set_success = state.file_set_success || click_result.file_set;
+                state.chooser_event_state = click_result.chooser_event_state.clone();
+                state.click_round = state.click_round.max(click_result.click_round);
+                state.click_method = click_result.click_method.clone();
+                diagnostics.push(format!(
+                    "D:round={} clicked={} chooser_opened={} chooser_event_state={} click_method={} click_round={} clicked_context={} signal_source={}",
+                    round,
                     click_result.marker,
                     click_result.chooser_opened,
                     click_result.chooser_event_state,
@@ -356,148 +1434,70 @@ pub async fn auto_publish_with_config(
                     if click_result.clicked_context.is_empty() {
                         "none"
                     } else {
-                        &click_result.clicked_context
-                    },
-                    click_result.signal_source
-                ));
-                if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                    upload_signal = Some(signal.clone());
-                    upload_diagnostics.push(format!("D:signal={}", signal));
-                } else {
-                    upload_diagnostics
-                        .push(format!("D:no_signal_fast({}s)", FAST_SIGNAL_TIMEOUT_SECS));
-                }
-            }
-            Err(e) => {
-                upload_diagnostics.push(format!("D:failed={}", e));
-            }
-        }
-    }
-
-    if upload_signal.is_none() && cfg.id == "wechat" && !wechat_file_set_success {
-        info!("[{}上传] 微信策略D后仍未触发，回退尝试策略C：拖拽上传...", cfg.name);
-        match automation::upload_file_via_drag_drop(page, video_path, cfg.id, cfg.drop_zone_selectors)
-            .await
-        {
-            Ok(selector) => {
-                upload_action_performed = true;
-                upload_diagnostics.push(format!("C:drag_drop selector={}", selector));
-                if let Some(signal) = wait_for_upload_signal(page, cfg, FAST_SIGNAL_TIMEOUT_SECS).await {
-                    upload_signal = Some(signal.clone());
-                    upload_diagnostics.push(format!("C:signal={}", signal));
-                } else {
-                    upload_diagnostics
-                        .push(format!("C:no_signal_fast({}s)", FAST_SIGNAL_TIMEOUT_SECS));
+                        &click_result.clicked_context
+                    },
+                    click_result.signal_source
+                ));
+                if let Some(signal) =
+                    wait_for_upload_signal(page, cfg, profile, profile.fast_signal_timeout_secs)
+                        .await
+                {
+                    diagnostics.push(format!("D:round={} signal={}", round, signal));
+                    found = Some(signal);
+                    break;
                 }
+                diagnostics.push(format!(
+                    "D:round={} no_signal_fast({}s)",
+                    round, profile.fast_signal_timeout_secs
+                ));
             }
             Err(e) => {
-                upload_diagnostics.push(format!("C:failed={}", e));
+                diagnostics.push(format!("D:round={} failed={}", round, e));
             }
         }
-    } else if upload_signal.is_none() && cfg.id == "wechat" && wechat_file_set_success {
-        upload_diagnostics.push(
-            "D:file_set_success skip_drag_drop_waiting_for_signal_confirmation".to_string(),
-        );
     }
 
-    if upload_signal.is_none() && !upload_action_performed {
-        bail!(
-            "[{}上传] 所有上传策略均失败，请手动上传。诊断：{}",
-            cfg.name,
-            upload_diagnostics.join(" | ")
-        );
-    }
+    diagnostics.push(format!(
+        "D:summary chooser_event_state={} click_round={} click_method={} file_set_success={}",
+        state.chooser_event_state, state.click_round, state.click_method, state.file_set_success
+    ));
+    Ok((performed, found))
+}
 
-    if upload_signal.is_none() {
-        if let Some(signal) = wait_for_upload_signal(page, cfg, SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS).await {
-            upload_diagnostics.push(format!("fallback:signal={}", signal));
-            upload_signal = Some(signal);
-        } else {
-            upload_diagnostics.push(format!(
-                "fallback:no_signal({}s)",
-                SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS
-            ));
-        }
+/// 上传字幕文件。字幕是可选项，失败只记录日志不阻断发布。
+async fn upload_subtitle(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    subtitle_path: &str,
+) -> &'static str {
+    if cfg.subtitle_input_selectors.is_empty() {
+        info!("[{}字幕] 平台暂不支持自动上传字幕，跳过", cfg.name);
+        return "unsupported";
     }
 
-    if upload_signal.is_none() && cfg.id == "wechat" && wechat_file_set_success {
-        upload_diagnostics.push("fallback:signal=chooser:file_set".to_string());
-        upload_signal = Some("chooser:file_set".to_string());
+    for marker in cfg.subtitle_open_text_markers {
+        if automation::click_by_text(page, &[marker], None)
+            .await
+            .is_ok()
+        {
+            tokio::time::sleep(Duration::from_millis(PRE_CLICK_WAIT_MS)).await;
+        }
     }
 
-    let started_signal = match upload_signal {
-        Some(signal) => signal,
-        None => {
-            if cfg.id == "wechat" {
-                bail!(
-                    "WECHAT_UPLOAD_SIGNAL_TIMEOUT: [{}上传] 已执行上传动作，但在快速检测与兜底检测中都未检测到上传信号。diagnosis: chooser_event_state={} click_round={} click_method={} 诊断：{}",
-                    cfg.name,
-                    wechat_chooser_event_state,
-                    wechat_click_round,
-                    wechat_click_method,
-                    upload_diagnostics.join(" | ")
-                );
-            }
-            bail!(
-                "[{}上传] 已执行上传动作，但在快速检测与兜底检测中都未检测到上传信号。诊断：{}",
-                cfg.name,
-                upload_diagnostics.join(" | ")
-            );
+    for selector in cfg.subtitle_input_selectors {
+        if selector_match_count(page, selector).await <= 0 {
+            continue;
         }
-    };
-    let signal_source = upload_signal_source(&started_signal);
-    info!(
-        "[{}上传] 上传信号确认：signal={} signal_source={} chooser_event_state={} click_round={} click_method={}",
-        cfg.name,
-        started_signal,
-        signal_source,
-        wechat_chooser_event_state,
-        wechat_click_round,
-        wechat_click_method
-    );
-
-    let fill_summary = fill_basic_fields(page, title, description, tags, cfg).await;
-    info!(
-        "[{}填表] title={} desc={} tags={}/{}",
-        cfg.name,
-        fill_summary.title_marker,
-        fill_summary.description_marker,
-        fill_summary.tags_added,
-        fill_summary.tags_total
-    );
-
-    if !fill_summary.title_ok && !fill_summary.description_ok {
-        if cfg.fill_failure_is_error {
-            bail!(
-                "[{}填表] 上传已触发（signal={}），但标题和描述均未命中可编辑字段。诊断：{}",
-                cfg.name,
-                started_signal,
-                upload_diagnostics.join(" | ")
-            );
+        match automation::set_file_input(page, selector, &[subtitle_path]).await {
+            Ok(()) => {
+                info!("[{}字幕] 已上传字幕：{}", cfg.name, subtitle_path);
+                return "ok";
+            }
+            Err(e) => warn!("[{}字幕] 字幕上传失败（{}）：{}", cfg.name, selector, e),
         }
-        warn!(
-            "[{}填表] 上传已触发（signal={}），但标题和描述均未命中可编辑字段。已按平台策略降级为非阻断。诊断：{}",
-            cfg.name,
-            started_signal,
-            upload_diagnostics.join(" | ")
-        );
-    }
-
-    if fill_summary.tags_total > 0 && fill_summary.tags_added < fill_summary.tags_total {
-        warn!(
-            "[{}填表] 标签填充部分失败：{}/{}",
-            cfg.name, fill_summary.tags_added, fill_summary.tags_total
-        );
     }
-
-    Ok(format!(
-        "{};fill=title:{},desc:{},tags:{}/{}",
-        started_signal,
-        marker_status(&fill_summary.title_marker),
-        marker_status(&fill_summary.description_marker),
-        fill_summary.tags_added,
-        fill_summary.tags_total
-    ))
+    warn!("[{}字幕] 未找到字幕上传入口", cfg.name);
+    "failed"
 }
 
 async fn fill_basic_fields(
@@ -521,6 +1521,8 @@ async fn fill_basic_fields(
             "error".to_string()
         }
     };
+    let title_marker =
+        fill_by_labels_on_miss(page, cfg, "标题", title, TITLE_LABELS, title_marker).await;
 
     let description_marker = match automation::fill_text_input(
         page,
@@ -536,6 +1538,15 @@ async fn fill_basic_fields(
             "error".to_string()
         }
     };
+    let description_marker = fill_by_labels_on_miss(
+        page,
+        cfg,
+        "描述",
+        description,
+        DESCRIPTION_LABELS,
+        description_marker,
+    )
+    .await;
 
     let tags_total = tags.len();
     let tags_added = if tags_total == 0 {
@@ -560,14 +1571,111 @@ async fn fill_basic_fields(
     }
 }
 
-async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Result<()> {
+/// 选择器都没命中（not_found / error）时，退回按标签文字就近定位
+async fn fill_by_labels_on_miss(
+    page: &Page,
+    cfg: &PlatformPublishConfig,
+    field: &str,
+    value: &str,
+    labels: &[&str],
+    marker: String,
+) -> String {
+    if is_fill_success(&marker) || marker == "skipped_empty" {
+        return marker;
+    }
+    match automation::fill_by_label_proximity(page, value, labels).await {
+        Ok(fallback) if is_fill_success(&fallback) => {
+            info!(
+                "[{}填表] {}选择器未命中（{}），已按标签就近填写：{}",
+                cfg.name, field, marker, fallback
+            );
+            fallback
+        }
+        Ok(_) => marker,
+        Err(e) => {
+            warn!("[{}填表] {}标签就近填写执行异常：{}", cfg.name, field, e);
+            marker
+        }
+    }
+}
+
+/// 只跑上传页守卫（登录、风控、维护、上传入口就绪），不上传也不填表；供账号自检使用
+pub async fn check_upload_guard<P: PageOps>(page: &P, cfg: &PlatformPublishConfig) -> Result<()> {
+    let payload = PublishPayload {
+        video_path: String::new(),
+        post_type: PostType::Video,
+        images: Vec::new(),
+        title: String::new(),
+        description: String::new(),
+        tags: Vec::new(),
+        publish_at: None,
+        platform_scheduled_at: None,
+        subtitle_path: None,
+        cover_path: None,
+        extra_media: Vec::new(),
+        custom_fields: CustomFields::default(),
+        profile: AutomationProfile::default(),
+        progress: None,
+        automation_log: None,
+        user_scripts: UserScripts::default(),
+        manual_confirm: true,
+        cancel: CancellationToken::new(),
+    };
+    ensure_upload_context(page, cfg, &payload).await
+}
+
+/// 依次在主上传入口和各备用入口上运行页面守卫。只有页面找不到上传锚点
+/// （anchor_miss）时才换下一个入口；登录、风控等失败直接返回。
+async fn ensure_upload_context<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+) -> Result<()> {
+    let mut entries = vec![cfg.upload_url];
+    entries.extend_from_slice(cfg.upload_url_variants);
+    for (index, upload_url) in entries.iter().enumerate() {
+        let err = match guard_upload_entry(page, cfg, payload, upload_url).await {
+            Ok(()) => {
+                if index > 0 {
+                    info!("[{}上传] 已切换到备用上传入口：{}", cfg.name, upload_url);
+                }
+                return Ok(());
+            }
+            Err(err) => err,
+        };
+        let message = err.to_string();
+        let anchor_miss = message.starts_with("TARGET_PAGE_NOT_READY")
+            && message.contains("ready_kind=anchor_miss");
+        let Some(next_url) = entries.get(index + 1).filter(|_| anchor_miss) else {
+            return Err(err);
+        };
+        warn!(
+            "[{}上传] 上传入口 {} 未找到上传锚点，改试备用入口 {}",
+            cfg.name, upload_url, next_url
+        );
+    }
+    unreachable!("the primary upload URL is always tried")
+}
+
+async fn guard_upload_entry<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    payload: &PublishPayload,
+    upload_url: &str,
+) -> Result<()> {
     let before_url = current_url(page).await;
     info!("[{}上传] 页面守卫：当前 URL={}", cfg.name, before_url);
     let is_wechat = cfg.id == "wechat";
 
-    if !is_target_url(&before_url, cfg) {
-        let nav_js = format!("window.location.href = '{}'; 'navigating';", cfg.upload_url);
-        page.evaluate(nav_js.as_str()).await.map_err(|e| {
+    // 备用入口必须真正跳过去，仅停在任一允许路径上不够
+    let on_entry = if upload_url == cfg.upload_url {
+        is_target_url(&before_url, cfg)
+    } else {
+        before_url.starts_with(upload_url)
+    };
+    if !on_entry {
+        let nav_js = format!("window.location.href = '{}'; 'navigating';", upload_url);
+        page.execute_script(&nav_js).await.map_err(|e| {
             anyhow::anyhow!(
                 "TARGET_PAGE_NOT_READY: 跳转 {} 上传页失败：{}",
                 cfg.name,
@@ -576,7 +1684,7 @@ async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Resu
         })?;
     }
 
-    let timeout = Duration::from_secs(if is_wechat {
+    let timeout = payload.profile.scaled(if is_wechat {
         WECHAT_GUARD_TIMEOUT_SECS
     } else {
         15
@@ -585,6 +1693,7 @@ async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Resu
     let mut last_url = before_url;
     let mut weak_ready_self_heal_attempted = false;
     loop {
+        payload.ensure_active()?;
         let host_ok = last_url.contains(cfg.target_host);
         let path_ok = path_allowed(&last_url, cfg.allowed_paths);
         let probe = probe_upload_page(page, cfg).await;
@@ -595,9 +1704,30 @@ async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Resu
         };
         let (weak_ready, weak_ready_reason) = compute_weak_ready(surface_ok, &probe, cfg);
         let fingerprint = format_probe_fingerprint(&probe);
-        let login_url_hit = is_wechat && is_wechat_login_url(&last_url);
+        let login_url_hit = is_login_url(&last_url, &cfg.login_guard);
+
+        if let Some(risk_hit) = detect_risk_control(page, cfg, &last_url).await {
+            let screenshot = capture_risk_control(page, cfg, payload).await;
+            bail!(
+                "RISK_CONTROL: {} 触发风控验证页。当前URL={} risk_hit={} screenshot={} fingerprint={}",
+                cfg.name,
+                last_url,
+                risk_hit,
+                screenshot.as_deref().unwrap_or("none"),
+                fingerprint
+            );
+        }
+        if let Some(maintenance_hit) = detect_maintenance(page, cfg).await {
+            bail!(
+                "PLATFORM_MAINTENANCE: {} 正在维护或暂停上传，稍后自动重试。当前URL={} maintenance_hit={} fingerprint={}",
+                cfg.name,
+                last_url,
+                maintenance_hit,
+                fingerprint
+            );
+        }
 
-        if is_wechat && (login_url_hit || !probe.login_text_hit.is_empty()) {
+        if login_url_hit || !probe.login_text_hit.is_empty() {
             bail!(
                 "LOGIN_REQUIRED: {} 上传页需要登录。当前URL={} login_url_hit={} login_text_hit={} ready_kind={} weak_ready_reason={} self_heal_attempted={} fingerprint={}",
                 cfg.name,
@@ -690,7 +1820,8 @@ async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Resu
                     surface_ok,
                     fingerprint
                 );
-                let healed = self_heal_weak_ready_page(page, cfg).await;
+                let healed =
+                    self_heal_weak_ready_page(page, cfg, upload_url, &payload.profile).await;
                 let after_heal_probe = probe_upload_page(page, cfg).await;
                 let after_heal_fingerprint = format_probe_fingerprint(&after_heal_probe);
                 warn!(
@@ -737,7 +1868,8 @@ async fn ensure_upload_context(page: &Page, cfg: &PlatformPublishConfig) -> Resu
                         surface_ok,
                         fingerprint
                     );
-                    let healed = self_heal_weak_ready_page(page, cfg).await;
+                    let healed =
+                        self_heal_weak_ready_page(page, cfg, upload_url, &payload.profile).await;
                     let after_heal_probe = probe_upload_page(page, cfg).await;
                     let after_heal_fingerprint = format_probe_fingerprint(&after_heal_probe);
                     warn!(
@@ -817,16 +1949,18 @@ async fn wait_for_upload_surface_brief(
     false
 }
 
-async fn wait_for_upload_signal(
-    page: &Page,
+async fn wait_for_upload_signal<P: PageOps>(
+    page: &P,
     cfg: &PlatformPublishConfig,
+    profile: &AutomationProfile,
     timeout_secs: u64,
 ) -> Option<String> {
     automation::wait_for_upload_start_signal(
         page,
-        cfg.id,
+        &cfg.upload_signals,
         timeout_secs,
-        Duration::from_millis(FAST_POLL_INTERVAL_MS),
+        Duration::from_millis(profile.poll_interval_ms),
+        profile.slow_network,
     )
     .await
 }
@@ -873,14 +2007,100 @@ fn upload_signal_source(signal: &str) -> &'static str {
     }
 }
 
-async fn has_upload_surface(page: &Page, cfg: &PlatformPublishConfig) -> bool {
-    if cfg.id == "wechat" {
-        return wechat_upload_ready(&probe_upload_page(page, cfg).await);
+fn editor_ready_script(editor: &EditorReadyConfig) -> String {
+    format!(
+        r#"
+        (function() {{
+            const selectors = [{}];
+            for (const sel of selectors) {{
+                let nodes = [];
+                try {{
+                    nodes = Array.from(document.querySelectorAll(sel));
+                }} catch (_) {{
+                    continue;
+                }}
+                for (const el of nodes) {{
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width > 0 && rect.height > 0) return 'selector:' + sel;
+                }}
+            }}
+            const text = (document.body && document.body.innerText) ? document.body.innerText : '';
+            const markers = [{}];
+            for (const marker of markers) {{
+                if (marker && text.includes(marker)) return 'text:' + marker;
+            }}
+            const advance = [{}];
+            const normalize = (v) => (v || '').replace(/\s+/g, '');
+            for (const marker of advance) {{
+                const buttons = Array.from(document.querySelectorAll('button, [role="button"]'));
+                for (const el of buttons) {{
+                    if (normalize(el.innerText || el.textContent) !== normalize(marker)) continue;
+                    if (el.disabled || el.getAttribute('aria-disabled') === 'true') continue;
+                    const rect = el.getBoundingClientRect();
+                    if (rect.width > 0 && rect.height > 0) {{
+                        el.click();
+                        return 'advance:' + marker;
+                    }}
+                }}
+            }}
+            return '';
+        }})()
+        "#,
+        js_array(editor.selectors),
+        js_array(editor.text_markers),
+        js_array(editor.advance_text_markers)
+    )
+}
+
+/// 轮询编辑区就绪条件，返回命中的标记；超时返回 `timeout(<秒>s)`
+async fn wait_for_editor_ready<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    editor: &EditorReadyConfig,
+    profile: &AutomationProfile,
+) -> String {
+    let js = editor_ready_script(editor);
+
+    let timeout_secs = editor.timeout_secs * profile.timeout_factor;
+    info!(
+        "[{}填表] 等待编辑区就绪（最长 {} 秒）",
+        cfg.name, timeout_secs
+    );
+    let start = std::time::Instant::now();
+    while start.elapsed() <= Duration::from_secs(timeout_secs) {
+        let hit = page
+            .evaluate_json(&js)
+            .await
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        if let Some(marker) = hit.strip_prefix("advance:") {
+            info!("[{}填表] 编辑区未就绪，点击向导按钮：{}", cfg.name, marker);
+            tokio::time::sleep(Duration::from_millis(ADVANCE_WAIT_MS)).await;
+            continue;
+        }
+        if !hit.is_empty() {
+            info!(
+                "[{}填表] 编辑区已就绪：{}（等待 {} 毫秒）",
+                cfg.name,
+                hit,
+                start.elapsed().as_millis()
+            );
+            return hit;
+        }
+        tokio::time::sleep(Duration::from_millis(profile.poll_interval_ms)).await;
     }
+    warn!(
+        "[{}填表] 等待编辑区超时（{} 秒），继续尝试填表",
+        cfg.name, timeout_secs
+    );
+    format!("timeout({}s)", timeout_secs)
+}
 
+fn upload_surface_script(cfg: &PlatformPublishConfig) -> String {
     let selectors_array = js_array(cfg.surface_selectors);
     let text_markers_array = js_array(cfg.surface_text_markers);
-    let js = format!(
+    format!(
         r#"
         (function() {{
             const hasInput = document.querySelectorAll("input[type='file']").length > 0;
@@ -903,15 +2123,23 @@ async fn has_upload_surface(page: &Page, cfg: &PlatformPublishConfig) -> bool {
         }})()
     "#,
         selectors_array, text_markers_array
-    );
+    )
+}
 
-    page.evaluate(js.as_str())
+async fn has_upload_surface<P: PageOps>(page: &P, cfg: &PlatformPublishConfig) -> bool {
+    if cfg.id == "wechat" {
+        return wechat_upload_ready(&probe_upload_page(page, cfg).await);
+    }
+
+    let js = upload_surface_script(cfg);
+    page.evaluate_json(&js)
         .await
-        .map(|v| v.into_value().unwrap_or(false))
+        .ok()
+        .and_then(|v| v.as_bool())
         .unwrap_or(false)
 }
 
-async fn selector_match_count(page: &Page, selector: &str) -> i64 {
+async fn selector_match_count<P: PageOps>(page: &P, selector: &str) -> i64 {
     let js = format!(
         r#"
         (function() {{
@@ -925,29 +2153,28 @@ async fn selector_match_count(page: &Page, selector: &str) -> i64 {
         escape_js_single(selector)
     );
 
-    page.evaluate(js.as_str())
+    page.evaluate_json(&js)
         .await
-        .map(|v| v.into_value().unwrap_or(0))
+        .ok()
+        .and_then(|v| v.as_i64())
         .unwrap_or(0)
 }
 
-async fn current_url(page: &Page) -> String {
-    page.evaluate("window.location.href")
+async fn current_url<P: PageOps>(page: &P) -> String {
+    page.evaluate_json("window.location.href")
         .await
-        .map(|v| v.into_value().unwrap_or_else(|_| String::new()))
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
         .unwrap_or_default()
 }
 
-async fn probe_upload_page(
-    page: &Page,
-    cfg: &PlatformPublishConfig,
-) -> UploadPageProbe {
+fn upload_page_probe_script(cfg: &PlatformPublishConfig) -> String {
     let blocked_markers = js_array(cfg.blocked_text_markers);
     let init_markers = js_array(cfg.init_text_markers);
-    let login_markers = js_array(cfg.login_text_markers);
+    let login_markers = js_array(cfg.login_guard.text_markers);
     let surface_markers = js_array(cfg.surface_text_markers);
     let surface_selectors = js_array(cfg.surface_selectors);
-    let js = if cfg.id == "wechat" {
+    if cfg.id == "wechat" {
         r#"
         (function(surfaceSelectors, surfaceMarkers, blockedMarkers, initMarkers, loginMarkers) {
             const normalize = (value) => (value || '').replace(/\s+/g, ' ').trim();
@@ -1318,13 +2545,45 @@ async fn probe_upload_page(
         .replace("__LOGIN_MARKERS__", format!("[{}]", login_markers).as_str())
         .replace("__SURFACE_MARKERS__", format!("[{}]", surface_markers).as_str())
         .replace("__SURFACE_SELECTORS__", format!("[{}]", surface_selectors).as_str())
-    };
+    }
+}
+
+/// 自动化在页面上执行的检测脚本（上传页守卫、风控、维护公告、编辑区就绪、
+/// 强制弹窗、发布按钮），按用途命名。配置冒烟测试逐个编译，配置里的文案或
+/// 选择器弄坏脚本语法时能在发布前发现。
+pub fn marker_scripts(cfg: &PlatformPublishConfig) -> Vec<(&'static str, String)> {
+    let mut scripts = vec![
+        ("upload_surface", upload_surface_script(cfg)),
+        ("upload_page_probe", upload_page_probe_script(cfg)),
+        ("submit_button", submit_button_script(&cfg.submit)),
+    ];
+    let text_markers = [
+        ("login_guard", cfg.login_guard.text_markers),
+        ("risk_control", cfg.risk_control_text_markers),
+        ("maintenance", cfg.maintenance_text_markers),
+        ("submit_success", cfg.submit.success_text_markers),
+    ];
+    for (name, markers) in text_markers {
+        scripts.push((name, text_marker_script(markers)));
+    }
+    if let Some(editor) = &cfg.editor_ready {
+        scripts.push(("editor_ready", editor_ready_script(editor)));
+    }
+    if let Some(dialog) = &cfg.blocking_dialog {
+        scripts.push(("blocking_dialog", blocking_dialog_script(dialog)));
+    }
+    scripts
+}
 
-    let raw: String = page
-        .evaluate(js.as_str())
+async fn probe_upload_page<P: PageOps>(page: &P, cfg: &PlatformPublishConfig) -> UploadPageProbe {
+    let js = upload_page_probe_script(cfg);
+
+    let raw = page
+        .evaluate_json(&js)
         .await
-        .map(|v| v.into_value().unwrap_or_else(|_| "{}".to_string()))
-        .unwrap_or_else(|_| "{}".to_string());
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_else(|| "{}".to_string());
     let parsed: serde_json::Value = serde_json::from_str(&raw).unwrap_or_else(|_| serde_json::json!({}));
 
     UploadPageProbe {
@@ -1515,28 +2774,27 @@ fn compute_weak_ready(
     (false, "none".to_string())
 }
 
-async fn self_heal_weak_ready_page(page: &Page, cfg: &PlatformPublishConfig) -> bool {
+async fn self_heal_weak_ready_page<P: PageOps>(
+    page: &P,
+    cfg: &PlatformPublishConfig,
+    upload_url: &str,
+    profile: &AutomationProfile,
+) -> bool {
     let replace_js = format!(
         "(function() {{ try {{ window.location.replace('{}'); return 'ok'; }} catch (_) {{ return 'error'; }} }})()",
-        escape_js_single(cfg.upload_url)
+        escape_js_single(upload_url)
     );
-    let _replace_result: String = page
-        .evaluate(replace_js.as_str())
-        .await
-        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
-        .unwrap_or_else(|_| "error".to_string());
+    let _ = page.evaluate_json(&replace_js).await;
 
     tokio::time::sleep(Duration::from_millis(WEAK_READY_RELOAD_WAIT_MS)).await;
 
-    let _reload_result: String = page
-        .evaluate(
+    let _ = page
+        .evaluate_json(
             "(function() { try { window.location.reload(); return 'ok'; } catch (_) { return 'error'; } })()",
         )
-        .await
-        .map(|v| v.into_value().unwrap_or_else(|_| "error".to_string()))
-        .unwrap_or_else(|_| "error".to_string());
+        .await;
 
-    let timeout = Duration::from_secs(WEAK_READY_SELF_HEAL_TIMEOUT_SECS);
+    let timeout = profile.scaled(WEAK_READY_SELF_HEAL_TIMEOUT_SECS);
     let start = std::time::Instant::now();
     while start.elapsed() <= timeout {
         tokio::time::sleep(Duration::from_millis(FAST_POLL_INTERVAL_MS)).await;
@@ -1562,7 +2820,7 @@ async fn self_heal_weak_ready_page(page: &Page, cfg: &PlatformPublishConfig) ->
     false
 }
 
-fn is_target_url(url: &str, cfg: &PlatformPublishConfig) -> bool {
+pub fn is_target_url(url: &str, cfg: &PlatformPublishConfig) -> bool {
     url.contains(cfg.target_host) && path_allowed(url, cfg.allowed_paths)
 }
 
@@ -1573,11 +2831,22 @@ fn path_allowed(url: &str, allowed_paths: &[&str]) -> bool {
     allowed_paths.iter().any(|path| url.contains(path))
 }
 
-fn is_wechat_login_url(url: &str) -> bool {
-    if !url.contains("channels.weixin.qq.com") {
-        return false;
-    }
-    url.contains("/login") || url.contains("login.weixin.qq.com") || url.contains("scanlogin")
+/// 当前 URL 是否停在该平台的登录页
+pub fn is_login_url(url: &str, guard: &LoginGuardConfig) -> bool {
+    let lower = url.to_lowercase();
+    guard
+        .url_markers
+        .iter()
+        .any(|marker| lower.contains(&marker.to_lowercase()))
+}
+
+/// 登录窗口中的 URL 是否表明登录已完成：到达了登录后页面且不在登录页
+pub fn is_login_success_url(url: &str, guard: &LoginGuardConfig) -> bool {
+    !is_login_url(url, guard)
+        && guard
+            .success_url_markers
+            .iter()
+            .any(|marker| url.contains(marker))
 }
 
 fn marker_status(marker: &str) -> &'static str {
@@ -1591,7 +2860,58 @@ fn marker_status(marker: &str) -> &'static str {
 }
 
 fn is_fill_success(marker: &str) -> bool {
-    marker.starts_with("input:") || marker.starts_with("editable")
+    marker.starts_with("input:")
+        || marker.starts_with("editable")
+        || marker.starts_with("label_proximity:")
+}
+
+/// 只有一段说明文字的平台：标题作首行，接描述，末尾追加描述里还没有的
+/// #hashtag（最多 `max_hashtags` 个），超出 `max_chars` 的部分截掉
+pub(super) fn merge_into_caption(
+    title: &str,
+    description: &str,
+    tags: &[String],
+    max_hashtags: usize,
+    max_chars: usize,
+) -> String {
+    let mut parts: Vec<String> = [title.trim(), description.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect();
+    if parts.len() == 2 && parts[1].starts_with(parts[0].as_str()) {
+        parts.remove(0);
+    }
+    let mut caption = parts.join("\n\n");
+
+    let existing = caption.to_lowercase();
+    let mut hashtags: Vec<String> = Vec::new();
+    for tag in tags {
+        let hashtag: String = tag
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+        if hashtag.is_empty() {
+            continue;
+        }
+        let rendered = format!("#{}", hashtag);
+        let lower = rendered.to_lowercase();
+        if existing.contains(&lower) || hashtags.iter().any(|h| h.to_lowercase() == lower) {
+            continue;
+        }
+        hashtags.push(rendered);
+        if hashtags.len() >= max_hashtags {
+            break;
+        }
+    }
+    if !hashtags.is_empty() {
+        if !caption.is_empty() {
+            caption.push_str("\n\n");
+        }
+        caption.push_str(&hashtags.join(" "));
+    }
+
+    caption.chars().take(max_chars).collect()
 }
 
 fn js_array(values: &[&str]) -> String {
@@ -1605,3 +2925,149 @@ fn js_array(values: &[&str]) -> String {
 fn escape_js_single(input: &str) -> String {
     input.replace('\\', "\\\\").replace('\'', "\\'")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::browser::page_ops::mock::ScriptedPage;
+    use serde_json::json;
+
+    fn douyin() -> &'static PlatformPublishConfig {
+        crate::platforms::publish_config("douyin").expect("douyin config")
+    }
+
+    #[tokio::test]
+    async fn current_url_reads_location() {
+        let page = ScriptedPage::new().on("window.location.href", vec![json!("https://a.test/b")]);
+        assert_eq!(current_url(&page).await, "https://a.test/b");
+        assert_eq!(current_url(&ScriptedPage::new()).await, "");
+    }
+
+    #[tokio::test]
+    async fn risk_control_url_marker_skips_page_scan() {
+        let page = ScriptedPage::new();
+        let hit = detect_risk_control(
+            &page,
+            douyin(),
+            "https://www.douyin.com/verifycenter/captcha",
+        )
+        .await;
+        assert_eq!(hit.as_deref(), Some("url:verifycenter"));
+        assert_eq!(page.script_count(""), 0);
+    }
+
+    #[tokio::test]
+    async fn risk_control_text_marker_is_reported() {
+        let url = "https://creator.douyin.com/creator-micro/content/upload";
+        let page = ScriptedPage::new().on("markers.find", vec![json!("安全验证")]);
+        assert_eq!(
+            detect_risk_control(&page, douyin(), url).await.as_deref(),
+            Some("text:安全验证")
+        );
+        let page = ScriptedPage::new().on("markers.find", vec![json!("")]);
+        assert_eq!(detect_risk_control(&page, douyin(), url).await, None);
+    }
+
+    #[tokio::test]
+    async fn editor_ready_polls_until_hit() {
+        let editor = EditorReadyConfig {
+            selectors: &["input[placeholder*='标题']"],
+            text_markers: &[],
+            advance_text_markers: &[],
+            timeout_secs: 5,
+        };
+        let page = ScriptedPage::new().on(
+            "'selector:'",
+            vec![json!(""), json!("selector:input[placeholder*='标题']")],
+        );
+        let hit =
+            wait_for_editor_ready(&page, douyin(), &editor, &AutomationProfile::default()).await;
+        assert_eq!(hit, "selector:input[placeholder*='标题']");
+        assert_eq!(page.script_count("'selector:'"), 2);
+    }
+
+    #[tokio::test]
+    async fn editor_ready_times_out_without_failing() {
+        let editor = EditorReadyConfig {
+            selectors: &["[contenteditable='true']"],
+            text_markers: &["作品描述"],
+            advance_text_markers: &[],
+            timeout_secs: 1,
+        };
+        let page = ScriptedPage::new().on("'selector:'", vec![json!("")]);
+        let hit =
+            wait_for_editor_ready(&page, douyin(), &editor, &AutomationProfile::default()).await;
+        assert_eq!(hit, "timeout(1s)");
+    }
+
+    #[tokio::test]
+    async fn direct_file_set_dispatches_events_on_matched_input() {
+        let cfg = douyin();
+        let selector = cfg.file_input_selectors[0];
+        let page = ScriptedPage::new()
+            .on(
+                &format!("querySelectorAll('{}').length", escape_js_single(selector)),
+                vec![json!(1)],
+            )
+            .on("dispatched:files=", vec![json!("dispatched:files=1")])
+            .with_node(selector);
+        let profile = AutomationProfile {
+            fast_signal_timeout_secs: 0,
+            ..AutomationProfile::default()
+        };
+        let mut diagnostics = Vec::new();
+        let (performed, signal) =
+            upload_via_direct_file_set(&page, cfg, &profile, "/tmp/a.mp4", &mut diagnostics).await;
+        assert!(performed);
+        assert_eq!(signal, None);
+        assert!(diagnostics.contains(&format!("B:{} dispatch=dispatched:files=1", selector)));
+        let file_sets = page
+            .file_sets
+            .lock()
+            .map(|sets| sets.clone())
+            .unwrap_or_default();
+        assert_eq!(file_sets.len(), 1);
+        assert_eq!(file_sets[0].1, vec!["/tmp/a.mp4".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn direct_file_set_skips_selectors_without_match() {
+        let page = ScriptedPage::new();
+        let mut diagnostics = Vec::new();
+        let (performed, signal) = upload_via_direct_file_set(
+            &page,
+            douyin(),
+            &AutomationProfile::default(),
+            "/tmp/a.mp4",
+            &mut diagnostics,
+        )
+        .await;
+        assert!(!performed);
+        assert_eq!(signal, None);
+        assert!(diagnostics.iter().all(|line| line.ends_with("count=0")));
+        assert_eq!(page.script_count("dispatched:files="), 0);
+    }
+
+    #[tokio::test]
+    async fn upload_guard_passes_on_ready_upload_page() {
+        let cfg = douyin();
+        let page = ScriptedPage::new()
+            .on("window.location.href", vec![json!(cfg.upload_url)])
+            .on("const hasInput", vec![json!(true)]);
+        check_upload_guard(&page, cfg).await.expect("guard");
+        assert_eq!(page.script_count("window.location.href = '"), 0);
+    }
+
+    #[tokio::test]
+    async fn upload_guard_stops_at_login_without_trying_variants() {
+        let page = ScriptedPage::new().on(
+            "window.location.href",
+            vec![json!("https://sso.douyin.com/login")],
+        );
+        let err = check_upload_guard(&page, douyin())
+            .await
+            .expect_err("login");
+        assert!(err.to_string().starts_with("LOGIN_REQUIRED:"));
+        assert_eq!(page.script_count("window.location.href = '"), 1);
+    }
+}
@@ -1,12 +1,61 @@
-use super::common::{self, PlatformPublishConfig};
-use super::traits::PlatformInfo;
-use anyhow::Result;
+use super::capabilities::{CustomFieldSpec, DurationLimits, FieldKind};
+use super::common::{
+    self, EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SelectionCheck, SubmitConfig,
+    UploadOutcome, SCHEDULE_MODE_APP, SCHEDULE_MODE_PLATFORM,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{anyhow, bail, Result};
 use chromiumoxide::page::Page;
+use chrono::{DateTime, Local};
+use futures::future::BoxFuture;
+use log::{info, warn};
+use std::time::Duration;
+
+/// 抖音定时发布要求发布时间至少在 2 小时之后、14 天之内
+const SCHEDULE_MIN_LEAD_MINUTES: i64 = 120;
+const SCHEDULE_MAX_LEAD_DAYS: i64 = 14;
+const SCHEDULE_PICKER_WAIT_MS: u64 = 500;
+const SCHEDULE_TOGGLE_TEXT_MARKERS: &[&str] = &["定时发布"];
+const SCHEDULE_INPUT_SELECTORS: &[&str] = &[
+    "input[placeholder*='日期和时间']",
+    "input[placeholder*='发布时间']",
+    "input[placeholder*='日期']",
+    ".semi-datepicker input",
+    "[class*='date-picker'] input",
+];
+
+/// “谁可以看”单选项，按 visibility 字段取值
+const VISIBILITY_OPTIONS: &[(&str, &str)] = &[
+    ("public", "公开"),
+    ("friends", "好友可见"),
+    ("private", "仅自己可见"),
+];
+
+const COVER_OPEN_TEXT_MARKERS: &[&str] = &["选择封面", "设置封面", "编辑封面"];
+const COVER_UPLOAD_TAB_MARKERS: &[&str] = &["上传封面", "本地上传"];
+const COVER_CONFIRM_TEXT_MARKERS: &[&str] = &["完成", "确定", "保存"];
+const COVER_CANCEL_TEXT_MARKERS: &[&str] = &["取消", "关闭"];
+const COVER_MODAL_SELECTOR: &str =
+    ".semi-modal-content, [class*='cover'][class*='modal'], [role='dialog']";
+const COVER_FILE_INPUT_SELECTORS: &[&str] = &[
+    ".semi-modal-content input[type='file'][accept*='image']",
+    "[role='dialog'] input[type='file'][accept*='image']",
+    "[class*='cover'] input[type='file']",
+    "input[type='file'][accept*='image']",
+];
+const COVER_STEP_WAIT_MS: u64 = 800;
+/// 封面裁剪确认最多点几轮（选帧/上传后可能还有一个裁剪弹窗）
+const COVER_CONFIRM_ROUNDS: usize = 2;
 
-const DOUYIN_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+pub(super) const DOUYIN_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     id: "douyin",
     name: "抖音",
     upload_url: "https://creator.douyin.com/creator-micro/content/upload",
+    upload_url_variants: &["https://creator.douyin.com/creator-micro/content/post/video"],
     target_host: "creator.douyin.com",
     allowed_paths: &[
         "/creator-micro/content/upload",
@@ -49,7 +98,56 @@ const DOUYIN_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     weak_ready_min_body_text_len: 0,
     blocked_text_markers: &[],
     init_text_markers: &[],
-    login_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["sso.douyin.com", "passport.douyin.com"],
+        // 创作者中心登录态失效时停在首页并弹出登录框，URL 不变
+        text_markers: &["扫码登录", "验证码登录"],
+        success_url_markers: &["creator.douyin.com/creator-micro"],
+        avatar_selectors: &[
+            "#header-avatar",
+            ".semi-avatar img",
+            "[class*='avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[
+        "请完成下列验证后继续",
+        "安全验证",
+        "验证码",
+        "访问频繁",
+        "当前地区暂不支持",
+        "所在地区无法使用",
+    ],
+    risk_control_url_markers: &["verifycenter", "/captcha"],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停上传", "上传功能维护"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::UrlContains {
+                label: "post_video",
+                patterns: &["/creator-micro/content/post/video"],
+            },
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='progress']",
+                    ".progress-div",
+                    "[class*='upload-progress']",
+                    "[class*='percent']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "处理中", "转码中", "校验中"],
+            },
+            SignalMatcher::TextIncludes {
+                label: "replace-video",
+                markers: &["重新上传", "更换视频"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
     title_selectors: &[
         "input[placeholder*='标题']",
         "input[placeholder*='title']",
@@ -69,6 +167,26 @@ const DOUYIN_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
         "[class*='tag'] input",
         "[class*='topic'] input",
     ],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "input[placeholder*='标题']",
+            ".editor-kit-container [contenteditable='true']",
+            "[class*='editor'] [contenteditable='true']",
+        ],
+        text_markers: &["作品描述", "发布设置"],
+        // 旧流程最长等待 300 秒；大文件上传时编辑区要等服务端接收后才渲染
+        advance_text_markers: &[],
+        timeout_secs: 300,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发布"],
+        success_url_markers: &["creator-micro/content/manage"],
+        success_text_markers: &["发布成功"],
+    },
 };
 
 pub fn info() -> PlatformInfo {
@@ -79,15 +197,312 @@ pub fn info() -> PlatformInfo {
         login_url: "https://creator.douyin.com".into(),
         upload_url: DOUYIN_CONFIG.upload_url.into(),
         color: "#fe2c55".into(),
+        experimental: false,
+    }
+}
+
+const CUSTOM_FIELDS: &[CustomFieldSpec] = &[
+    CustomFieldSpec {
+        key: "visibility",
+        label: "谁可以看",
+        kind: FieldKind::Choice {
+            options: &["public", "friends", "private"],
+        },
+    },
+    CustomFieldSpec {
+        key: "activity",
+        label: "活动",
+        kind: FieldKind::Text { max_len: 50 },
+    },
+    CustomFieldSpec {
+        key: "cover_frame_secs",
+        label: "封面取帧时间（秒）",
+        kind: FieldKind::Number {
+            min: 0.0,
+            max: 3600.0,
+        },
+    },
+];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &DOUYIN_CONFIG,
+    automation: &Douyin,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(60.0 * 60.0),
+    },
+    custom_fields: CUSTOM_FIELDS,
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: true,
+};
+
+pub struct Douyin;
+
+impl PlatformAutomation for Douyin {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+
+    fn verify_before_submit<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        let checks = ctx
+            .payload
+            .custom_fields
+            .text("visibility")
+            .and_then(|visibility| {
+                VISIBILITY_OPTIONS
+                    .iter()
+                    .find(|(value, _)| *value == visibility)
+            })
+            .map(|(_, label)| SelectionCheck::CheckedLabel {
+                field: "visibility",
+                label: label.to_string(),
+            })
+            .into_iter()
+            .collect();
+        Box::pin(common::verify_before_submit(ctx, checks))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    let mut signal = common::fill_metadata(ctx, upload).await?;
+    let page = &ctx.handle.ensure_attached().await?;
+
+    // 封面失败不阻断发布：抖音会回退到默认封面，用户也可以在页面上手动再选
+    let frame_secs = payload.custom_fields.number("cover_frame_secs");
+    if payload.cover_path.is_some() || frame_secs.is_some() {
+        let cover = match set_cover(page, payload.cover_path.as_deref(), frame_secs).await {
+            Ok(mode) => mode,
+            Err(e) => {
+                warn!("[抖音封面] 设置封面失败：{}", e);
+                "failed"
+            }
+        };
+        signal = format!("{};cover={}", signal, cover);
+    }
+
+    if let Some(visibility) = payload.custom_fields.text("visibility") {
+        let marker = set_visibility(page, visibility).await?;
+        info!("[抖音可见范围] 已选择：{} ({})", visibility, marker);
+        signal = format!("{};visibility={}", signal, visibility);
+    }
+
+    // platform_scheduled_at 只允许平台定时，设置失败直接报错，避免点发布后立即公开
+    if let Some(scheduled_at) = payload.platform_scheduled_at {
+        let marker = set_scheduled_publish(page, scheduled_at)
+            .await
+            .map_err(|e| anyhow!("[抖音定时] 平台定时发布设置失败：{}", e))?;
+        info!(
+            "[抖音定时] 已设置定时发布：{} ({})",
+            scheduled_at.format("%Y-%m-%d %H:%M"),
+            marker
+        );
+        return Ok(format!("{};schedule={}", signal, SCHEDULE_MODE_PLATFORM));
+    }
+
+    let Some(publish_at) = payload.publish_at else {
+        return Ok(signal);
+    };
+
+    // 优先使用平台原生定时发布；失败时标记 schedule=app，本次不点发布，
+    // 由应用在计划时间重新发布
+    match set_scheduled_publish(page, publish_at).await {
+        Ok(marker) => {
+            info!(
+                "[抖音定时] 已设置定时发布：{} ({})",
+                publish_at.format("%Y-%m-%d %H:%M"),
+                marker
+            );
+            Ok(format!("{};schedule={}", signal, SCHEDULE_MODE_PLATFORM))
+        }
+        Err(e) => {
+            warn!(
+                "[抖音定时] 平台定时发布设置失败，改由应用在计划时间发布：{}",
+                e
+            );
+            Ok(format!("{};schedule={}", signal, SCHEDULE_MODE_APP))
+        }
     }
 }
 
-pub async fn auto_publish(
+async fn set_scheduled_publish(page: &Page, publish_at: DateTime<Local>) -> Result<String> {
+    let lead = publish_at - Local::now();
+    if lead < chrono::Duration::minutes(SCHEDULE_MIN_LEAD_MINUTES) {
+        bail!(
+            "发布时间距现在不足 {} 分钟，抖音不支持该定时",
+            SCHEDULE_MIN_LEAD_MINUTES
+        );
+    }
+    if lead > chrono::Duration::days(SCHEDULE_MAX_LEAD_DAYS) {
+        bail!(
+            "发布时间超过 {} 天，抖音不支持该定时",
+            SCHEDULE_MAX_LEAD_DAYS
+        );
+    }
+
+    automation::click_by_text(page, SCHEDULE_TOGGLE_TEXT_MARKERS, None).await?;
+    tokio::time::sleep(Duration::from_millis(SCHEDULE_PICKER_WAIT_MS)).await;
+
+    let value = publish_at.format("%Y-%m-%d %H:%M").to_string();
+    automation::type_into_input(page, SCHEDULE_INPUT_SELECTORS, &value, true).await
+}
+
+/// 在“谁可以看”中点选对应单选项；选不中时报错，避免以错误的可见范围发布
+async fn set_visibility(page: &Page, visibility: &str) -> Result<String> {
+    let Some((_, label)) = VISIBILITY_OPTIONS
+        .iter()
+        .find(|(value, _)| *value == visibility)
+    else {
+        bail!("[抖音可见范围] 不支持的可见范围：{}", visibility);
+    };
+    automation::click_by_text(page, &[label], None)
+        .await
+        .map_err(|e| anyhow!("[抖音可见范围] 未找到“{}”选项：{}", label, e))
+}
+
+/// 打开封面编辑器，上传封面图或按时间点选帧，然后确认裁剪弹窗。
+/// 失败时关掉弹窗，否则它会挡住后面的可见范围、定时和发布按钮
+async fn set_cover(
+    page: &Page,
+    cover_path: Option<&str>,
+    frame_secs: Option<f64>,
+) -> Result<&'static str> {
+    automation::click_by_text(page, COVER_OPEN_TEXT_MARKERS, None).await?;
+    let result = apply_cover(page, cover_path, frame_secs).await;
+    if result.is_err() {
+        dismiss_cover_modal(page).await;
+    }
+    result
+}
+
+async fn apply_cover(
     page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<String> {
-    common::auto_publish_with_config(page, video_path, title, description, tags, &DOUYIN_CONFIG).await
+    cover_path: Option<&str>,
+    frame_secs: Option<f64>,
+) -> Result<&'static str> {
+    tokio::time::sleep(Duration::from_millis(COVER_STEP_WAIT_MS)).await;
+
+    let mode = match cover_path {
+        Some(path) => {
+            // 上传入口通常在单独的 tab 下；找不到 tab 时直接尝试弹窗里的文件输入框
+            let _ = automation::click_by_text(
+                page,
+                COVER_UPLOAD_TAB_MARKERS,
+                Some(COVER_MODAL_SELECTOR),
+            )
+            .await;
+            tokio::time::sleep(Duration::from_millis(COVER_STEP_WAIT_MS)).await;
+            let mut uploaded = false;
+            for selector in COVER_FILE_INPUT_SELECTORS {
+                match automation::set_file_input(page, selector, &[path]).await {
+                    Ok(()) => {
+                        uploaded = true;
+                        break;
+                    }
+                    Err(e) => info!("[抖音封面] 封面输入框不可用（{}）：{}", selector, e),
+                }
+            }
+            if !uploaded {
+                bail!("未找到封面上传入口");
+            }
+            info!("[抖音封面] 已上传封面：{}", path);
+            "uploaded"
+        }
+        None => {
+            let secs = frame_secs.unwrap_or_default();
+            seek_cover_frame(page, secs).await?;
+            info!("[抖音封面] 已选取 {:.1}s 处的画面", secs);
+            "frame"
+        }
+    };
+
+    for _ in 0..COVER_CONFIRM_ROUNDS {
+        tokio::time::sleep(Duration::from_millis(COVER_STEP_WAIT_MS)).await;
+        if automation::click_by_text(page, COVER_CONFIRM_TEXT_MARKERS, Some(COVER_MODAL_SELECTOR))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+    Ok(mode)
+}
+
+/// 取消封面弹窗；找不到取消按钮时点右上角的关闭图标，再不行就发 Escape
+async fn dismiss_cover_modal(page: &Page) {
+    if automation::click_by_text(page, COVER_CANCEL_TEXT_MARKERS, Some(COVER_MODAL_SELECTOR))
+        .await
+        .is_ok()
+    {
+        info!("[抖音封面] 已取消封面弹窗");
+        tokio::time::sleep(Duration::from_millis(COVER_STEP_WAIT_MS)).await;
+        return;
+    }
+    match close_cover_modal(page).await {
+        Ok(outcome) => info!("[抖音封面] 关闭封面弹窗：{}", outcome),
+        Err(e) => warn!("[抖音封面] 关闭封面弹窗失败：{}", e),
+    }
+    tokio::time::sleep(Duration::from_millis(COVER_STEP_WAIT_MS)).await;
+}
+
+async fn close_cover_modal(page: &Page) -> Result<String> {
+    let script = format!(
+        r#"
+        (function() {{
+            const modal = document.querySelector({});
+            if (!modal) return 'no_modal';
+            const close = modal.querySelector('.semi-modal-close, [aria-label="close"], [class*="close"]');
+            if (close) {{
+                close.click();
+                return 'closed';
+            }}
+            document.dispatchEvent(new KeyboardEvent('keydown', {{ key: 'Escape', bubbles: true }}));
+            return 'escape';
+        }})()
+        "#,
+        serde_json::to_string(COVER_MODAL_SELECTOR)?
+    );
+    Ok(page.evaluate(script).await?.into_value()?)
+}
+
+/// 把封面编辑器里的视频和帧滑块拨到指定时间点
+async fn seek_cover_frame(page: &Page, secs: f64) -> Result<()> {
+    let script = format!(
+        r#"
+        (function() {{
+            const secs = {};
+            const scope = document.querySelector({}) || document;
+            const video = scope.querySelector('video');
+            if (!video) return 'no_video';
+            const duration = video.duration || 0;
+            const target = duration > 0 ? Math.min(secs, duration) : secs;
+            video.currentTime = target;
+            video.dispatchEvent(new Event('seeked', {{ bubbles: true }}));
+            const slider = scope.querySelector('input[type="range"]');
+            if (slider && duration > 0) {{
+                const min = Number(slider.min || 0);
+                const max = Number(slider.max || 100);
+                slider.value = String(min + (max - min) * (target / duration));
+                slider.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                slider.dispatchEvent(new Event('change', {{ bubbles: true }}));
+            }}
+            return 'ok';
+        }})()
+        "#,
+        secs,
+        serde_json::to_string(COVER_MODAL_SELECTOR)?
+    );
+    let result: String = page.evaluate(script).await?.into_value()?;
+    if result != "ok" {
+        bail!("封面编辑器里没有找到视频帧（{}）", result);
+    }
+    Ok(())
 }
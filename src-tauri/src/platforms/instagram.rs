@@ -0,0 +1,234 @@
+use super::capabilities::DurationLimits;
+use super::common::{
+    self, EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig, UploadOutcome,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order::UploadStrategy;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::page_handle::PageHandle;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::Result;
+use futures::future::BoxFuture;
+use log::{info, warn};
+use std::time::Duration;
+
+/// Instagram 说明文字上限与 hashtag 个数上限
+const MAX_CAPTION_CHARS: usize = 2200;
+const MAX_HASHTAGS: usize = 30;
+const CREATE_MENU_WAIT_MS: u64 = 800;
+/// 侧边栏“创建”入口
+const CREATE_SELECTORS: &[&str] = &[
+    "a:has(svg[aria-label='New post'])",
+    "[role='link']:has(svg[aria-label='New post'])",
+    "a:has(svg[aria-label='Create'])",
+];
+/// 新版“创建”会先弹出子菜单（帖子 / 直播 / AI），选“帖子”才打开上传对话框
+const CREATE_MENU_POST_MARKERS: &[&str] = &["Post"];
+
+pub(super) const INSTAGRAM_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+    id: "instagram",
+    name: "Instagram",
+    upload_url: "https://www.instagram.com/",
+    upload_url_variants: &[],
+    target_host: "www.instagram.com",
+    allowed_paths: &[],
+    surface_selectors: &[
+        "[role='dialog'] input[type='file']",
+        "svg[aria-label='New post']",
+        "svg[aria-label='Create']",
+    ],
+    surface_text_markers: &["Drag photos and videos here", "Select from computer"],
+    file_input_selectors: &[
+        "[role='dialog'] input[type='file']",
+        "form input[type='file'][accept*='video']",
+        "input[type='file']",
+    ],
+    drop_zone_selectors: &["[role='dialog'] [role='presentation']", "[role='dialog']"],
+    pre_click_selectors: &[],
+    click_selectors: &["[role='dialog'] button"],
+    click_text_markers: &["Select from computer", "Select From Computer"],
+    require_surface_ready: true,
+    fill_failure_is_error: true,
+    weak_ready_self_heal: false,
+    weak_ready_min_body_text_len: 0,
+    blocked_text_markers: &[],
+    init_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &[
+            "instagram.com/accounts/login",
+            "instagram.com/accounts/emailsignup",
+        ],
+        text_markers: &["Don't have an account?", "Forgot password?"],
+        success_url_markers: &["www.instagram.com"],
+        avatar_selectors: &[
+            "img[alt*='profile picture']",
+            "a[href*='/accounts/edit'] img",
+        ],
+    },
+    risk_control_text_markers: &[
+        "Help us confirm it's you",
+        "Confirm it's you",
+        "We suspect automated behavior",
+        "Try again later",
+    ],
+    risk_control_url_markers: &["/challenge/", "/accounts/suspended"],
+    maintenance_text_markers: &["Sorry, something went wrong", "temporarily unavailable"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "preview",
+                selectors: &["[role='dialog'] video", "[role='dialog'] canvas"],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "crop",
+                markers: &["Crop", "Video posts are now shared as reels"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
+    // Instagram 只有说明文字，标题并入说明，见 fill_metadata
+    title_selectors: &[],
+    title_editable_selector: None,
+    description_selectors: &[
+        "[role='dialog'] div[aria-label='Write a caption...'][contenteditable='true']",
+        "div[aria-label*='caption'][contenteditable='true']",
+        "[role='dialog'] [contenteditable='true']",
+    ],
+    description_editable_selector: Some("[role='dialog'] [contenteditable='true']"),
+    // 话题以 #hashtag 写进说明文字
+    tag_selectors: &[],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "div[aria-label='Write a caption...'][contenteditable='true']",
+            "div[aria-label*='caption'][contenteditable='true']",
+        ],
+        text_markers: &[],
+        // 裁剪、编辑两屏各点一次“Next”，首次发视频时还有一个“OK”提示
+        advance_text_markers: &["OK", "Next"],
+        timeout_secs: 120,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["Share"],
+        success_url_markers: &[],
+        success_text_markers: &[
+            "Your reel has been shared",
+            "Your post has been shared",
+            "Reel shared",
+        ],
+    },
+};
+
+pub fn info() -> PlatformInfo {
+    PlatformInfo {
+        id: "instagram".into(),
+        name: "Instagram".into(),
+        name_en: "Instagram".into(),
+        login_url: "https://www.instagram.com/accounts/login/".into(),
+        upload_url: INSTAGRAM_CONFIG.upload_url.into(),
+        color: "#e1306c".into(),
+        // Selectors not yet verified against enough live uploads
+        experimental: true,
+    }
+}
+
+/// The file input only exists once the create dialog is open, so the click
+/// through "Select from computer" goes first
+const UPLOAD_STRATEGIES: &[UploadStrategy] = &[
+    UploadStrategy::ClickUpload,
+    UploadStrategy::Chooser,
+    UploadStrategy::DirectSet,
+    UploadStrategy::DragDrop,
+];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &INSTAGRAM_CONFIG,
+    automation: &Instagram,
+    duration_limits: DurationLimits {
+        min_secs: Some(3.0),
+        max_secs: Some(15.0 * 60.0),
+    },
+    custom_fields: &[],
+    upload_strategies: UPLOAD_STRATEGIES,
+    native_schedule: false,
+};
+
+pub struct Instagram;
+
+impl PlatformAutomation for Instagram {
+    fn prepare_page<'a>(&'a self, ctx: &'a AutomationContext<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            open_create_dialog(ctx.handle).await;
+            common::prepare_page(ctx).await
+        })
+    }
+
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    // 标题、描述和标签合成一段说明文字
+    let mut caption_payload = payload.clone();
+    caption_payload.description = common::merge_into_caption(
+        &payload.title,
+        &payload.description,
+        &payload.tags,
+        MAX_HASHTAGS,
+        MAX_CAPTION_CHARS,
+    );
+    caption_payload.title.clear();
+    caption_payload.tags.clear();
+    common::fill_metadata(
+        &AutomationContext {
+            payload: &caption_payload,
+            ..*ctx
+        },
+        upload,
+    )
+    .await
+}
+
+/// 点击“创建”打开上传对话框，之后由点击上传策略点“Select from computer”
+/// 并接管文件选择器。打不开时只记警告，交给通用流程的各上传策略兜底。
+async fn open_create_dialog(handle: &PageHandle) {
+    let page = match handle.ensure_attached().await {
+        Ok(page) => page,
+        Err(e) => {
+            warn!("[Instagram上传] 页面未就绪，跳过打开创建对话框：{}", e);
+            return;
+        }
+    };
+    match automation::click_first_visible(&page, CREATE_SELECTORS).await {
+        Ok(marker) => info!("[Instagram上传] 已点击创建入口：{}", marker),
+        Err(e) => {
+            warn!("[Instagram上传] 未找到创建入口：{}", e);
+            return;
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(CREATE_MENU_WAIT_MS)).await;
+    if common::find_text_marker(&page, INSTAGRAM_CONFIG.surface_text_markers)
+        .await
+        .is_some()
+    {
+        return;
+    }
+    match automation::click_by_text(&page, CREATE_MENU_POST_MARKERS, None).await {
+        Ok(marker) => info!("[Instagram上传] 已在创建菜单中选择：{}", marker),
+        Err(e) => warn!("[Instagram上传] 创建菜单中未找到帖子入口：{}", e),
+    }
+}
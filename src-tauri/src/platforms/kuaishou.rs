@@ -0,0 +1,131 @@
+use super::capabilities::DurationLimits;
+use super::common::{EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{ConfigDriven, PlatformInfo};
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+
+pub(super) const KUAISHOU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+    id: "kuaishou",
+    name: "快手",
+    upload_url: "https://cp.kuaishou.com/article/publish/video",
+    upload_url_variants: &[],
+    target_host: "cp.kuaishou.com",
+    allowed_paths: &["/article/publish/video", "/article/publish"],
+    surface_selectors: &["[class*='upload']", "[class*='drag']", "[class*='drop']"],
+    surface_text_markers: &["上传视频", "拖拽视频到此处", "点击上传"],
+    file_input_selectors: &[
+        "input[type='file'][accept*='video']",
+        "[class*='upload'] input[type='file']",
+        "input[type='file']",
+    ],
+    drop_zone_selectors: &["[class*='upload']", "[class*='drag']", "[class*='drop']"],
+    pre_click_selectors: &[],
+    click_selectors: &[
+        "button[class*='upload']",
+        "[class*='upload-btn']",
+        "[class*='upload'] button",
+        "[role='button']",
+    ],
+    click_text_markers: &["上传视频", "点击上传", "选择文件"],
+    require_surface_ready: true,
+    fill_failure_is_error: true,
+    weak_ready_self_heal: false,
+    weak_ready_min_body_text_len: 0,
+    blocked_text_markers: &[],
+    init_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["passport.kuaishou.com", "cp.kuaishou.com/login"],
+        text_markers: &["扫码登录", "手机号登录"],
+        success_url_markers: &["cp.kuaishou.com/article", "cp.kuaishou.com/profile"],
+        avatar_selectors: &[
+            "[class*='header'] [class*='avatar'] img",
+            "[class*='user-info'] img",
+            "[class*='avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[
+        "请完成安全验证",
+        "安全验证",
+        "滑块验证",
+        "操作过于频繁",
+        "账号存在异常",
+    ],
+    risk_control_url_markers: &["/captcha", "verify"],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停发布", "暂停上传"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='progress']",
+                    "[class*='percent']",
+                    "[class*='uploading']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "正在上传", "处理中", "上传成功"],
+            },
+            SignalMatcher::TextIncludes {
+                label: "replace-video",
+                markers: &["重新上传", "更换视频"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
+    // 快手作品只有描述，多数情况下没有单独的标题栏，标题选择器未命中属正常
+    title_selectors: &["input[placeholder*='标题']", "[class*='title'] input"],
+    title_editable_selector: None,
+    description_selectors: &[
+        "#work-description-edit",
+        "[class*='description'] [contenteditable='true']",
+        "textarea[placeholder*='描述']",
+    ],
+    description_editable_selector: Some("[contenteditable='true']"),
+    tag_selectors: &["input[placeholder*='话题']", "[class*='topic'] input"],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &["#work-description-edit", "[contenteditable='true']"],
+        text_markers: &["作品描述", "添加作品描述"],
+        advance_text_markers: &[],
+        timeout_secs: 60,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发布"],
+        success_url_markers: &["/article/manage/video"],
+        success_text_markers: &["发布成功"],
+    },
+};
+
+pub fn info() -> PlatformInfo {
+    PlatformInfo {
+        id: "kuaishou".into(),
+        name: "快手".into(),
+        name_en: "Kuaishou".into(),
+        login_url: "https://cp.kuaishou.com".into(),
+        upload_url: KUAISHOU_CONFIG.upload_url.into(),
+        color: "#ff4906".into(),
+        // Selectors not yet verified against enough live uploads
+        experimental: true,
+    }
+}
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &KUAISHOU_CONFIG,
+    automation: &ConfigDriven,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(15.0 * 60.0),
+    },
+    custom_fields: &[],
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
@@ -1,32 +1,95 @@
 mod common;
 pub mod bilibili;
+pub mod capabilities;
 pub mod douyin;
+pub mod instagram;
+pub mod kuaishou;
+pub mod profile;
+pub mod registry;
+pub mod selector_overrides;
+pub mod simulated;
+pub mod strategy_order;
+pub mod tiktok;
 pub mod traits;
+pub mod user_scripts;
 pub mod wechat;
+pub mod weibo;
 pub mod xiaohongshu;
 pub mod youtube;
+pub mod zhihu;
 
-pub use traits::PlatformInfo;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub use capabilities::{CustomFields, PostType};
+pub use common::{
+    capture_preview, check_upload_guard, is_login_success_url, is_login_url, is_target_url,
+    marker_scripts, PlatformPublishConfig, PublishPayload, FAST_SIGNAL_TIMEOUT_SECS,
+    SCHEDULE_MODE_APP, SCHEDULE_MODE_PLATFORM, SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS,
+    SUBMIT_READY_TIMEOUT_SECS, SUBMIT_VERIFY_TIMEOUT_SECS,
+};
+pub use profile::{AutomationProfile, AutomationProfileKind};
+pub use traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+
+/// Setting that lists experimental platforms in `all_platforms()`, `true` to enable
+pub const SETTING_EXPERIMENTAL_PLATFORMS: &str = "experimental_platforms_enabled";
+
+static EXPERIMENTAL_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Apply the stored value of `SETTING_EXPERIMENTAL_PLATFORMS`
+pub fn set_experimental_enabled(raw: Option<&str>) {
+    let enabled = raw.is_some_and(|raw| matches!(raw.trim(), "1" | "true" | "yes" | "on"));
+    EXPERIMENTAL_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn experimental_enabled() -> bool {
+    EXPERIMENTAL_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Language of platform names and publish results: `zh` (default) or `en`
+pub const SETTING_LOCALE: &str = "locale";
+
+static ENGLISH: AtomicBool = AtomicBool::new(false);
+
+/// Apply the stored value of `SETTING_LOCALE`; anything starting with `en` means English
+pub fn set_locale(raw: Option<&str>) {
+    let english = raw.is_some_and(|raw| raw.trim().to_ascii_lowercase().starts_with("en"));
+    ENGLISH.store(english, Ordering::Relaxed);
+}
+
+pub fn english() -> bool {
+    ENGLISH.load(Ordering::Relaxed)
+}
+
+/// Platform name in the user's language
+pub fn display_name(platform: &str) -> String {
+    match get_platform_info(platform) {
+        Some(info) => info.localized_name(english()).to_string(),
+        None if english() => "Platform".to_string(),
+        None => "平台".to_string(),
+    }
+}
 
 /// Get platform info by platform ID
 pub fn get_platform_info(platform: &str) -> Option<PlatformInfo> {
-    match platform {
-        "bilibili" => Some(bilibili::info()),
-        "douyin" => Some(douyin::info()),
-        "xiaohongshu" => Some(xiaohongshu::info()),
-        "wechat" => Some(wechat::info()),
-        "youtube" => Some(youtube::info()),
-        _ => None,
-    }
+    registry::find(platform).map(|entry| (entry.info)())
+}
+
+/// Get the automation config (upload URL, selectors) for a platform
+pub fn publish_config(platform: &str) -> Option<&'static PlatformPublishConfig> {
+    registry::find(platform).map(|entry| entry.config)
+}
+
+/// Platform info for new accounts; experimental platforms only when enabled.
+/// Existing accounts keep resolving through `get_platform_info`.
+pub fn available_platform_info(platform: &str) -> Option<PlatformInfo> {
+    get_platform_info(platform).filter(|info| !info.experimental || experimental_enabled())
 }
 
-/// Get all supported platforms
+/// Get all supported platforms; experimental ones only when enabled
 pub fn all_platforms() -> Vec<PlatformInfo> {
-    vec![
-        douyin::info(),
-        xiaohongshu::info(),
-        bilibili::info(),
-        wechat::info(),
-        youtube::info(),
-    ]
+    registry::entries()
+        .iter()
+        .map(|entry| (entry.info)())
+        .filter(|info| !info.experimental || experimental_enabled())
+        .collect()
 }
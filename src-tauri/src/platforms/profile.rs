@@ -0,0 +1,147 @@
+use super::common::{
+    FAST_POLL_INTERVAL_MS, FAST_SIGNAL_TIMEOUT_SECS, QUICK_SURFACE_WAIT_SECS,
+    SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS, WECHAT_CLICK_RETRY_ROUNDS,
+};
+use super::strategy_order::UploadStrategy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default profile for publish requests that don't pick one
+pub const SETTING_AUTOMATION_PROFILE: &str = "automation_profile";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutomationProfileKind {
+    /// Current behaviour: short waits, give up early and hand over to the user
+    #[default]
+    Fast,
+    /// Longer waits, more retries and a screenshot after every step
+    Thorough,
+    /// Thorough plus per-step tracing into the phase log
+    Diagnostic,
+}
+
+impl AutomationProfileKind {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "fast" => Some(Self::Fast),
+            "thorough" => Some(Self::Thorough),
+            "diagnostic" => Some(Self::Diagnostic),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fast => "fast",
+            Self::Thorough => "thorough",
+            Self::Diagnostic => "diagnostic",
+        }
+    }
+}
+
+/// Poll intervals, timeouts and strategy order used by one automation run
+#[derive(Debug, Clone)]
+pub struct AutomationProfile {
+    pub kind: AutomationProfileKind,
+    pub quick_surface_wait_secs: u64,
+    pub fast_signal_timeout_secs: u64,
+    pub slow_fallback_signal_timeout_secs: u64,
+    pub poll_interval_ms: u64,
+    pub wechat_click_retry_rounds: usize,
+    /// Upper bound for the whole per-account automation
+    pub automation_timeout_secs: u64,
+    /// Try setFileInputFiles (strategy B) before file chooser interception (strategy A)
+    pub direct_file_set_first: bool,
+    /// Learned upload strategy order for this account; empty uses the built-in order
+    pub upload_strategy_order: Vec<UploadStrategy>,
+    /// Where step screenshots go; None disables them
+    pub screenshot_dir: Option<PathBuf>,
+    /// Where evidence screenshots (risk-control pages) go when step
+    /// screenshots are off; None disables them
+    pub evidence_dir: Option<PathBuf>,
+    /// Where files the upload page downloads go; None leaves Chrome's own
+    /// download settings alone
+    pub download_dir: Option<PathBuf>,
+    pub trace: bool,
+    /// Slow-network mode: timeouts are scaled by `timeout_factor` and network
+    /// requests count as upload evidence
+    pub slow_network: bool,
+    pub timeout_factor: u64,
+}
+
+impl AutomationProfile {
+    pub fn new(kind: AutomationProfileKind) -> Self {
+        let fast = Self {
+            kind,
+            quick_surface_wait_secs: QUICK_SURFACE_WAIT_SECS,
+            fast_signal_timeout_secs: FAST_SIGNAL_TIMEOUT_SECS,
+            slow_fallback_signal_timeout_secs: SLOW_FALLBACK_SIGNAL_TIMEOUT_SECS,
+            poll_interval_ms: FAST_POLL_INTERVAL_MS,
+            wechat_click_retry_rounds: WECHAT_CLICK_RETRY_ROUNDS,
+            automation_timeout_secs: 45,
+            direct_file_set_first: false,
+            upload_strategy_order: Vec::new(),
+            screenshot_dir: None,
+            evidence_dir: None,
+            download_dir: None,
+            trace: false,
+            slow_network: false,
+            timeout_factor: 1,
+        };
+        match kind {
+            AutomationProfileKind::Fast => fast,
+            AutomationProfileKind::Thorough | AutomationProfileKind::Diagnostic => Self {
+                quick_surface_wait_secs: fast.quick_surface_wait_secs * 4,
+                fast_signal_timeout_secs: fast.fast_signal_timeout_secs * 3,
+                slow_fallback_signal_timeout_secs: fast.slow_fallback_signal_timeout_secs * 4,
+                poll_interval_ms: fast.poll_interval_ms * 2,
+                wechat_click_retry_rounds: fast.wechat_click_retry_rounds * 2,
+                automation_timeout_secs: 180,
+                // The chooser interception is the flakiest path when pages load slowly
+                direct_file_set_first: true,
+                trace: kind == AutomationProfileKind::Diagnostic,
+                ..fast
+            },
+        }
+    }
+
+    /// Scale every signal and guard timeout by `factor` for slow uplinks
+    pub fn with_slow_network(self, factor: u64) -> Self {
+        let factor = factor.max(1);
+        Self {
+            quick_surface_wait_secs: self.quick_surface_wait_secs * factor,
+            fast_signal_timeout_secs: self.fast_signal_timeout_secs * factor,
+            slow_fallback_signal_timeout_secs: self.slow_fallback_signal_timeout_secs * factor,
+            automation_timeout_secs: self.automation_timeout_secs * factor,
+            slow_network: true,
+            timeout_factor: factor,
+            ..self
+        }
+    }
+
+    /// A fixed guard timeout, scaled in slow-network mode
+    pub fn scaled(&self, secs: u64) -> Duration {
+        Duration::from_secs(secs * self.timeout_factor)
+    }
+
+    pub fn wants_screenshots(&self) -> bool {
+        self.kind != AutomationProfileKind::Fast
+    }
+
+    /// Order in which to try the upload strategies on `platform`
+    pub fn strategy_order(&self, platform: &str) -> Vec<UploadStrategy> {
+        if self.upload_strategy_order.is_empty() {
+            UploadStrategy::default_order(platform, self.direct_file_set_first)
+        } else {
+            self.upload_strategy_order.clone()
+        }
+    }
+}
+
+impl Default for AutomationProfile {
+    fn default() -> Self {
+        Self::new(AutomationProfileKind::Fast)
+    }
+}
@@ -0,0 +1,60 @@
+use super::capabilities::{CustomFieldSpec, DurationLimits};
+use super::common::PlatformPublishConfig;
+use super::selector_overrides;
+use super::strategy_order::UploadStrategy;
+use super::traits::{PlatformAutomation, PlatformInfo};
+use super::{
+    bilibili, douyin, instagram, kuaishou, tiktok, wechat, weibo, xiaohongshu, youtube, zhihu,
+};
+
+/// Everything the app needs about one platform. Each platform module defines
+/// its entry once and it is listed in `PLATFORMS`.
+pub struct PlatformEntry {
+    pub info: fn() -> PlatformInfo,
+    /// Built-in config; publishing goes through `effective_config`
+    pub config: &'static PlatformPublishConfig,
+    /// Publish steps; `ConfigDriven` when the config covers all of them
+    pub automation: &'static dyn PlatformAutomation,
+    /// Video durations the web upload page accepts
+    pub duration_limits: DurationLimits,
+    /// Extra fields beyond title/description/tags, validated on publish
+    pub custom_fields: &'static [CustomFieldSpec],
+    /// Built-in upload strategy order, with `Chooser` listed before `DirectSet`
+    pub upload_strategies: &'static [UploadStrategy],
+    /// The upload page can schedule the release itself. Without it a
+    /// `publish_at` account runs from the scheduler queue at that time.
+    pub native_schedule: bool,
+}
+
+impl PlatformEntry {
+    pub fn id(&self) -> &'static str {
+        self.config.id
+    }
+
+    /// `config` with the loaded selector overrides merged in
+    pub fn effective_config(&self) -> &'static PlatformPublishConfig {
+        selector_overrides::effective(self.config)
+    }
+}
+
+/// Supported platforms in the order they are listed to the user
+static PLATFORMS: &[PlatformEntry] = &[
+    douyin::PLATFORM,
+    xiaohongshu::PLATFORM,
+    bilibili::PLATFORM,
+    wechat::PLATFORM,
+    youtube::PLATFORM,
+    kuaishou::PLATFORM,
+    zhihu::PLATFORM,
+    tiktok::PLATFORM,
+    weibo::PLATFORM,
+    instagram::PLATFORM,
+];
+
+pub fn find(platform: &str) -> Option<&'static PlatformEntry> {
+    PLATFORMS.iter().find(|entry| entry.id() == platform)
+}
+
+pub fn entries() -> &'static [PlatformEntry] {
+    PLATFORMS
+}
@@ -0,0 +1,338 @@
+use super::{registry, PlatformPublishConfig};
+use crate::content::bundle;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{LazyLock, RwLock};
+
+/// Config fields a `selectors.d/<platform>.json` file may override. Each maps
+/// to a list of CSS selectors, e.g. `{"file_input_selectors": ["input.upload"]}`.
+pub const OVERRIDABLE_FIELDS: &[&str] = &[
+    "surface_selectors",
+    "file_input_selectors",
+    "drop_zone_selectors",
+    "pre_click_selectors",
+    "click_selectors",
+    "title_selectors",
+    "description_selectors",
+    "tag_selectors",
+    "subtitle_input_selectors",
+];
+
+/// A user-provided selector rejected at load time
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidSelector {
+    pub platform: String,
+    pub field: String,
+    pub selector: String,
+    pub reason: String,
+}
+
+/// Valid override selectors of one platform keyed by field, plus everything
+/// that was rejected. Rejected selectors never reach the upload strategies.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SelectorOverrides {
+    pub platform: String,
+    pub fields: BTreeMap<String, Vec<String>>,
+    pub invalid: Vec<InvalidSelector>,
+}
+
+/// One field of the config publishing currently uses
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSelectors {
+    pub field: String,
+    /// Override selectors first, then the built-in ones they did not repeat
+    pub selectors: Vec<String>,
+    pub overridden: bool,
+}
+
+/// Configs with overrides merged in, for platforms that have any
+static EFFECTIVE: LazyLock<RwLock<HashMap<&'static str, &'static PlatformPublishConfig>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Built-in selectors of a platform config, keyed like `OVERRIDABLE_FIELDS`
+pub fn builtin_selectors(
+    cfg: &PlatformPublishConfig,
+) -> Vec<(&'static str, &'static [&'static str])> {
+    vec![
+        ("surface_selectors", cfg.surface_selectors),
+        ("file_input_selectors", cfg.file_input_selectors),
+        ("drop_zone_selectors", cfg.drop_zone_selectors),
+        ("pre_click_selectors", cfg.pre_click_selectors),
+        ("click_selectors", cfg.click_selectors),
+        ("title_selectors", cfg.title_selectors),
+        ("description_selectors", cfg.description_selectors),
+        ("tag_selectors", cfg.tag_selectors),
+        ("subtitle_input_selectors", cfg.subtitle_input_selectors),
+    ]
+}
+
+fn field_mut<'a>(
+    cfg: &'a mut PlatformPublishConfig,
+    field: &str,
+) -> Option<&'a mut &'static [&'static str]> {
+    match field {
+        "surface_selectors" => Some(&mut cfg.surface_selectors),
+        "file_input_selectors" => Some(&mut cfg.file_input_selectors),
+        "drop_zone_selectors" => Some(&mut cfg.drop_zone_selectors),
+        "pre_click_selectors" => Some(&mut cfg.pre_click_selectors),
+        "click_selectors" => Some(&mut cfg.click_selectors),
+        "title_selectors" => Some(&mut cfg.title_selectors),
+        "description_selectors" => Some(&mut cfg.description_selectors),
+        "tag_selectors" => Some(&mut cfg.tag_selectors),
+        "subtitle_input_selectors" => Some(&mut cfg.subtitle_input_selectors),
+        _ => None,
+    }
+}
+
+/// Override selectors go first so they are tried before the built-in ones,
+/// which stay as fallbacks. The config holds `'static` slices, so merged
+/// lists are leaked; that happens once per reload, which the user triggers.
+fn merge(cfg: &PlatformPublishConfig, overrides: &SelectorOverrides) -> PlatformPublishConfig {
+    let mut merged = *cfg;
+    for (field, selectors) in &overrides.fields {
+        let Some(slot) = field_mut(&mut merged, field) else {
+            continue;
+        };
+        if selectors.is_empty() {
+            continue;
+        }
+        let mut list: Vec<&'static str> = selectors
+            .iter()
+            .map(|selector| &*Box::leak(selector.clone().into_boxed_str()))
+            .collect();
+        list.extend(
+            slot.iter()
+                .copied()
+                .filter(|builtin| !selectors.iter().any(|selector| selector == builtin)),
+        );
+        *slot = Box::leak(list.into_boxed_slice());
+    }
+    merged
+}
+
+/// Load every platform's override file and make the merged configs the ones
+/// publishing uses from now on. Returns what was loaded, rejected entries included.
+pub fn reload(app_data_dir: &Path) -> Vec<SelectorOverrides> {
+    let mut effective = HashMap::new();
+    let mut reports = Vec::new();
+    for entry in registry::entries() {
+        let overrides = load(app_data_dir, entry.id());
+        for invalid in &overrides.invalid {
+            warn!(
+                "[选择器覆盖] platform={} field={} 已忽略 {}：{}",
+                invalid.platform, invalid.field, invalid.selector, invalid.reason
+            );
+        }
+        if overrides
+            .fields
+            .values()
+            .any(|selectors| !selectors.is_empty())
+        {
+            let merged: &'static PlatformPublishConfig =
+                Box::leak(Box::new(merge(entry.config, &overrides)));
+            effective.insert(entry.id(), merged);
+        }
+        reports.push(overrides);
+    }
+    info!("[选择器覆盖] 已加载 {} 个平台的覆盖", effective.len());
+    match EFFECTIVE.write() {
+        Ok(mut current) => *current = effective,
+        Err(e) => warn!("[选择器覆盖] 无法更新生效配置：{}", e),
+    }
+    reports
+}
+
+/// The config to publish with: the built-in one with loaded overrides merged in
+pub fn effective(cfg: &'static PlatformPublishConfig) -> &'static PlatformPublishConfig {
+    EFFECTIVE
+        .read()
+        .ok()
+        .and_then(|effective| effective.get(cfg.id).copied())
+        .unwrap_or(cfg)
+}
+
+/// Selectors of every overridable field as publishing uses them now
+pub fn effective_selectors(cfg: &'static PlatformPublishConfig) -> Vec<EffectiveSelectors> {
+    let builtin = builtin_selectors(cfg);
+    builtin_selectors(effective(cfg))
+        .into_iter()
+        .zip(builtin)
+        .map(|((field, selectors), (_, builtin))| EffectiveSelectors {
+            field: field.to_string(),
+            selectors: selectors.iter().map(|s| s.to_string()).collect(),
+            overridden: selectors != builtin,
+        })
+        .collect()
+}
+
+/// Load and validate `selectors.d/<platform>.json`. A missing file yields empty
+/// overrides; an unreadable file is reported as a single invalid entry.
+pub fn load(app_data_dir: &Path, platform: &str) -> SelectorOverrides {
+    let file = bundle::selector_overrides_dir(app_data_dir).join(format!("{}.json", platform));
+    let Ok(body) = std::fs::read(&file) else {
+        return SelectorOverrides {
+            platform: platform.to_string(),
+            ..Default::default()
+        };
+    };
+    match serde_json::from_slice::<serde_json::Value>(&body) {
+        Ok(value) => parse(platform, &value),
+        Err(e) => SelectorOverrides {
+            platform: platform.to_string(),
+            fields: BTreeMap::new(),
+            invalid: vec![InvalidSelector {
+                platform: platform.to_string(),
+                field: String::new(),
+                selector: String::new(),
+                reason: format!("{} 无法解析：{}", file.display(), e),
+            }],
+        },
+    }
+}
+
+/// Validate an override document, keeping the usable selectors
+pub fn parse(platform: &str, value: &serde_json::Value) -> SelectorOverrides {
+    let mut overrides = SelectorOverrides {
+        platform: platform.to_string(),
+        ..Default::default()
+    };
+    let reject = |field: &str, selector: &str, reason: String| InvalidSelector {
+        platform: platform.to_string(),
+        field: field.to_string(),
+        selector: selector.to_string(),
+        reason,
+    };
+    let Some(object) = value.as_object() else {
+        overrides
+            .invalid
+            .push(reject("", "", "覆盖文件必须是 JSON 对象".to_string()));
+        return overrides;
+    };
+
+    for (field, selectors) in object {
+        if !OVERRIDABLE_FIELDS.contains(&field.as_str()) {
+            overrides
+                .invalid
+                .push(reject(field, "", format!("未知字段 {}", field)));
+            continue;
+        }
+        let Some(list) = selectors.as_array() else {
+            overrides
+                .invalid
+                .push(reject(field, "", "字段值必须是字符串数组".to_string()));
+            continue;
+        };
+        let mut valid = Vec::new();
+        for item in list {
+            let Some(selector) = item.as_str() else {
+                overrides.invalid.push(reject(
+                    field,
+                    &item.to_string(),
+                    "选择器必须是字符串".to_string(),
+                ));
+                continue;
+            };
+            match check_syntax(selector) {
+                Ok(()) => valid.push(selector.trim().to_string()),
+                Err(reason) => overrides.invalid.push(reject(field, selector, reason)),
+            }
+        }
+        overrides.fields.insert(field.clone(), valid);
+    }
+    overrides
+}
+
+/// Static CSS selector check done without a browser. It catches what would make
+/// `querySelector` throw in practice (unbalanced brackets or quotes, dangling
+/// combinators, empty list items) plus characters that could break out of the
+/// JS string the selector is embedded in. The healthcheck still re-checks every
+/// selector in a real page.
+pub fn check_syntax(selector: &str) -> Result<(), String> {
+    let selector = selector.trim();
+    if selector.is_empty() {
+        return Err("选择器为空".to_string());
+    }
+    if selector.contains(['{', '}', ';', '\n', '\r']) {
+        return Err("选择器包含非法字符 { } ; 或换行".to_string());
+    }
+
+    let mut brackets = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for ch in selector.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        if ch == '\\' {
+            escaped = true;
+            continue;
+        }
+        if let Some(open) = quote {
+            if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '"' | '\'' => quote = Some(ch),
+            '(' | '[' => brackets.push(ch),
+            ')' | ']' => {
+                let expected = if ch == ')' { '(' } else { '[' };
+                if brackets.pop() != Some(expected) {
+                    return Err(format!("括号不匹配：多余的 {}", ch));
+                }
+            }
+            _ => {}
+        }
+    }
+    if quote.is_some() {
+        return Err("引号未闭合".to_string());
+    }
+    if let Some(open) = brackets.last() {
+        return Err(format!("括号未闭合：{}", open));
+    }
+
+    for part in split_top_level(selector) {
+        let part = part.trim();
+        if part.is_empty() {
+            return Err("选择器列表中存在空项".to_string());
+        }
+        if part.starts_with(['>', '+', '~']) || part.ends_with(['>', '+', '~']) {
+            return Err(format!("组合符两侧缺少选择器：{}", part));
+        }
+    }
+    Ok(())
+}
+
+/// Split a selector list on commas outside brackets and quotes
+fn split_top_level(selector: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (idx, ch) in selector.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, ch) {
+            (_, '\\') => escaped = true,
+            (Some(open), _) if ch == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                parts.push(&selector[start..idx]);
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&selector[start..]);
+    parts
+}
@@ -0,0 +1,171 @@
+use super::common::{self, LoginGuardConfig, PlatformPublishConfig, SubmitConfig, UploadOutcome};
+use super::traits::{AutomationContext, PlatformAutomation};
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use log::{info, warn};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::OnceCell;
+
+/// 模拟模式使用的本地上传页，所有平台共用，平台名通过 `?platform=` 显示
+const UPLOAD_PAGE: &str = include_str!("../../fixtures/simulated_upload.html");
+const UPLOAD_PATH: &str = "/upload";
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+static UPLOAD_URL: OnceCell<&'static str> = OnceCell::const_new();
+
+/// 本地模拟上传页的地址；首次调用时启动本地 HTTP 服务，之后一直复用
+pub async fn upload_url() -> Result<&'static str> {
+    UPLOAD_URL
+        .get_or_try_init(|| async {
+            let listener = TcpListener::bind("127.0.0.1:0")
+                .await
+                .context("启动模拟上传页服务失败")?;
+            let addr = listener.local_addr()?;
+            crate::tasks::global().spawn("simulate:fixture_server", serve(listener));
+            let url = format!("http://{}{}", addr, UPLOAD_PATH);
+            info!("[模拟模式] 本地上传页已就绪：{}", url);
+            // 配置里的地址都是 'static，服务只启动一次，这里泄漏一次地址即可
+            Ok(&*Box::leak(url.into_boxed_str()))
+        })
+        .await
+        .copied()
+}
+
+/// 某个平台在模拟模式下打开的页面
+pub async fn upload_url_for(platform: &str) -> Result<String> {
+    Ok(format!("{}?platform={}", upload_url().await?, platform))
+}
+
+async fn serve(listener: TcpListener) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(async move {
+                    if let Err(e) = respond(stream).await {
+                        warn!("[模拟模式] 处理请求失败：{}", e);
+                    }
+                });
+            }
+            Err(e) => warn!("[模拟模式] 接受连接失败：{}", e),
+        }
+    }
+}
+
+/// 只处理 `GET /upload`，其余一律 404
+async fn respond(mut stream: TcpStream) -> Result<()> {
+    let mut buffer = vec![0u8; MAX_REQUEST_BYTES];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .unwrap_or("/");
+    let route = path.split('?').next().unwrap_or(path);
+    let (status, body) = if route == UPLOAD_PATH {
+        ("200 OK", UPLOAD_PAGE)
+    } else {
+        ("404 Not Found", "not found")
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// 本地上传页的自动化配置；选择器与 `fixtures/simulated_upload.html` 对应
+fn config(upload_url: &'static str) -> PlatformPublishConfig {
+    PlatformPublishConfig {
+        id: "simulated",
+        name: "模拟平台",
+        upload_url,
+        upload_url_variants: &[],
+        target_host: "127.0.0.1",
+        allowed_paths: &[UPLOAD_PATH],
+        surface_selectors: &[".upload-area"],
+        surface_text_markers: &["选择视频"],
+        file_input_selectors: &["#video-input", "input[type='file']"],
+        drop_zone_selectors: &[".upload-area"],
+        pre_click_selectors: &[],
+        click_selectors: &[".upload-area"],
+        click_text_markers: &["选择视频"],
+        require_surface_ready: true,
+        fill_failure_is_error: true,
+        weak_ready_self_heal: false,
+        weak_ready_min_body_text_len: 0,
+        blocked_text_markers: &[],
+        init_text_markers: &[],
+        login_guard: LoginGuardConfig {
+            url_markers: &[],
+            text_markers: &[],
+            success_url_markers: &[],
+            avatar_selectors: &[],
+        },
+        risk_control_text_markers: &[],
+        risk_control_url_markers: &[],
+        maintenance_text_markers: &[],
+        upload_signals: UploadSignals {
+            matchers: &[
+                SignalMatcher::FileInputNonempty,
+                SignalMatcher::SelectorExists {
+                    label: "progress",
+                    selectors: &[".upload-progress .bar"],
+                    text_markers: &[],
+                },
+                SignalMatcher::TextIncludes {
+                    label: "uploading",
+                    markers: &["上传中", "上传完成"],
+                },
+            ],
+            veto_text_markers: &[],
+            deep_scan: false,
+        },
+        title_selectors: &["input[name='title']"],
+        title_editable_selector: None,
+        description_selectors: &["textarea[name='description']"],
+        description_editable_selector: None,
+        tag_selectors: &["input[name='tag']"],
+        subtitle_input_selectors: &[],
+        subtitle_open_text_markers: &[],
+        blocking_dialog: None,
+        editor_ready: None,
+        submit: SubmitConfig {
+            selectors: &[".publish-btn"],
+            text_markers: &["发布"],
+            success_url_markers: &[],
+            success_text_markers: &["发布成功"],
+        },
+    }
+}
+
+/// 本地上传页的配置，供自动提交使用
+pub async fn publish_config() -> Result<PlatformPublishConfig> {
+    Ok(config(upload_url().await?))
+}
+
+/// 在本地上传页上跑完整的通用上传与填表流程，不接触任何真实平台和账号
+pub struct Simulated;
+
+impl PlatformAutomation for Simulated {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let signal = common::fill_metadata(ctx, upload).await?;
+    ctx.handle.ensure_attached().await?;
+    info!("[模拟模式] 上传与填表完成：{}", signal);
+    Ok(format!("{},simulated", signal))
+}
@@ -0,0 +1,188 @@
+use super::registry;
+use crate::database::queries::{self, StrategyOutcome};
+use anyhow::Result;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Try the upload strategies in their learned order, `false` to always use the
+/// built-in order
+pub const SETTING_ADAPTIVE_STRATEGY_ORDER: &str = "adaptive_strategy_order";
+
+/// Attempts an account needs before its own record replaces the platform's
+const MIN_ACCOUNT_ATTEMPTS: i64 = 3;
+
+/// Built-in order of most platforms: the file input strategies first, then
+/// drag and drop before clicking the upload entry
+pub const DEFAULT_ORDER: &[UploadStrategy] = &[
+    UploadStrategy::Chooser,
+    UploadStrategy::DirectSet,
+    UploadStrategy::DragDrop,
+    UploadStrategy::ClickUpload,
+];
+
+/// The four ways a video file is handed to an upload page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadStrategy {
+    /// Strategy A: intercept the file chooser
+    Chooser,
+    /// Strategy B: setFileInputFiles plus dispatched events
+    DirectSet,
+    /// Strategy C: synthetic drag and drop onto the drop zone
+    DragDrop,
+    /// Strategy D: click the upload entry and answer the file chooser
+    ClickUpload,
+}
+
+impl UploadStrategy {
+    pub const ALL: [Self; 4] = [
+        Self::Chooser,
+        Self::DirectSet,
+        Self::DragDrop,
+        Self::ClickUpload,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Chooser => "A:file_chooser",
+            Self::DirectSet => "B:direct_set",
+            Self::DragDrop => "C:drag_drop",
+            Self::ClickUpload => "D:click_upload",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.label() == label)
+    }
+
+    /// The platform's built-in order, with `Chooser` and `DirectSet` swapped
+    /// when the profile sets the file input directly first
+    pub fn default_order(platform: &str, direct_file_set_first: bool) -> Vec<Self> {
+        let mut order = registry::find(platform)
+            .map(|entry| entry.upload_strategies)
+            .unwrap_or(DEFAULT_ORDER)
+            .to_vec();
+        if direct_file_set_first {
+            for strategy in &mut order {
+                *strategy = match *strategy {
+                    Self::Chooser => Self::DirectSet,
+                    Self::DirectSet => Self::Chooser,
+                    other => other,
+                };
+            }
+        }
+        order
+    }
+}
+
+/// Success rate with one success and one failure assumed up front, so a single
+/// lucky run does not outrank a long record and untried strategies sit at 0.5
+fn score(successes: i64, failures: i64) -> f64 {
+    (successes + 1) as f64 / (successes + failures + 2) as f64
+}
+
+/// Reorder `default` by how often each strategy started the upload. The
+/// account's own record is used once it has enough attempts, otherwise that
+/// of every account on the platform. Ties keep the built-in order.
+pub fn learned_order(
+    conn: &Connection,
+    platform: &str,
+    account_id: i64,
+    default: &[UploadStrategy],
+) -> Result<Vec<UploadStrategy>> {
+    if !queries::get_setting_bool(conn, SETTING_ADAPTIVE_STRATEGY_ORDER, true)? {
+        return Ok(default.to_vec());
+    }
+    let outcomes = queries::get_strategy_outcomes(conn, Some(platform))?;
+    Ok(order_from(&outcomes, account_id, default))
+}
+
+fn order_from(
+    outcomes: &[StrategyOutcome],
+    account_id: i64,
+    default: &[UploadStrategy],
+) -> Vec<UploadStrategy> {
+    let own = outcomes
+        .iter()
+        .filter(|o| o.account_id == account_id)
+        .collect::<Vec<_>>();
+    let own_attempts: i64 = own.iter().map(|o| o.successes + o.failures).sum();
+    let basis = if own_attempts >= MIN_ACCOUNT_ATTEMPTS {
+        own
+    } else {
+        outcomes.iter().collect()
+    };
+
+    let mut totals = BTreeMap::<&str, (i64, i64)>::new();
+    for outcome in basis {
+        let total = totals.entry(outcome.strategy.as_str()).or_default();
+        total.0 += outcome.successes;
+        total.1 += outcome.failures;
+    }
+    let mut order = default.to_vec();
+    order.sort_by(|a, b| {
+        let (sa, fa) = totals.get(a.label()).copied().unwrap_or_default();
+        let (sb, fb) = totals.get(b.label()).copied().unwrap_or_default();
+        score(sb, fb).total_cmp(&score(sa, fa))
+    });
+    order
+}
+
+/// Count each strategy one run tried; labels outside A–D are ignored
+pub fn record(
+    conn: &Connection,
+    platform: &str,
+    account_id: i64,
+    attempts: &[(String, bool)],
+) -> Result<()> {
+    for (label, succeeded) in attempts {
+        if UploadStrategy::from_label(label).is_some() {
+            queries::record_strategy_outcome(conn, platform, account_id, label, *succeeded)?;
+        }
+    }
+    Ok(())
+}
+
+/// The order one account would use with the fast profile, and the counts behind it
+#[derive(Debug, Clone, Serialize)]
+pub struct LearnedOrder {
+    pub platform: String,
+    pub account_id: i64,
+    pub order: Vec<&'static str>,
+    pub outcomes: Vec<StrategyOutcome>,
+}
+
+pub fn overview(conn: &Connection) -> Result<Vec<LearnedOrder>> {
+    let adaptive = queries::get_setting_bool(conn, SETTING_ADAPTIVE_STRATEGY_ORDER, true)?;
+    let outcomes = queries::get_strategy_outcomes(conn, None)?;
+    let mut keys = outcomes
+        .iter()
+        .map(|o| (o.platform.clone(), o.account_id))
+        .collect::<Vec<_>>();
+    keys.dedup();
+    Ok(keys
+        .into_iter()
+        .map(|(platform, account_id)| {
+            let on_platform = outcomes
+                .iter()
+                .filter(|o| o.platform == platform)
+                .cloned()
+                .collect::<Vec<_>>();
+            let default = UploadStrategy::default_order(&platform, false);
+            let order = if adaptive {
+                order_from(&on_platform, account_id, &default)
+            } else {
+                default
+            };
+            LearnedOrder {
+                order: order.into_iter().map(UploadStrategy::label).collect(),
+                outcomes: on_platform
+                    .into_iter()
+                    .filter(|o| o.account_id == account_id)
+                    .collect(),
+                platform,
+                account_id,
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,201 @@
+use super::capabilities::DurationLimits;
+use super::common::{
+    self, EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig, UploadOutcome,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+/// TikTok Studio 的说明文字（caption）上限
+const MAX_CAPTION_CHARS: usize = 4000;
+
+pub(super) const TIKTOK_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+    id: "tiktok",
+    name: "TikTok",
+    upload_url: "https://www.tiktok.com/tiktokstudio/upload",
+    upload_url_variants: &["https://www.tiktok.com/creator-center/upload"],
+    target_host: "www.tiktok.com",
+    allowed_paths: &["/tiktokstudio", "/creator-center/upload"],
+    surface_selectors: &[
+        "[data-e2e='select_video_container']",
+        "[class*='upload-card']",
+        "[class*='upload-container']",
+        "input[type='file']",
+    ],
+    surface_text_markers: &[
+        "Select video to upload",
+        "Select video",
+        "Or drag and drop a file",
+        "Drag and drop files here",
+    ],
+    file_input_selectors: &["input[type='file'][accept*='video']", "input[type='file']"],
+    drop_zone_selectors: &[
+        "[data-e2e='select_video_container']",
+        "[class*='upload-card']",
+        "[class*='upload-container']",
+    ],
+    pre_click_selectors: &[],
+    click_selectors: &[
+        "[data-e2e='select_video_button']",
+        "[data-e2e='select_video_container'] button",
+        "[class*='upload-card'] button",
+    ],
+    click_text_markers: &["Select video", "Select file"],
+    require_surface_ready: true,
+    fill_failure_is_error: true,
+    weak_ready_self_heal: false,
+    weak_ready_min_body_text_len: 0,
+    blocked_text_markers: &[],
+    init_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["www.tiktok.com/login", "www.tiktok.com/signup"],
+        text_markers: &[
+            "Log in to TikTok",
+            "Use QR code",
+            "Use phone / email / username",
+        ],
+        success_url_markers: &[
+            "www.tiktok.com/tiktokstudio",
+            "www.tiktok.com/creator-center",
+        ],
+        avatar_selectors: &[
+            "[data-e2e='profile-icon']",
+            "[class*='avatar'] img",
+            "img[class*='Avatar']",
+        ],
+    },
+    risk_control_text_markers: &[
+        "Verify to continue",
+        "Drag the slider to fit the puzzle",
+        "Select 2 objects that are the same shape",
+        "Too many attempts",
+    ],
+    risk_control_url_markers: &["/captcha"],
+    maintenance_text_markers: &[
+        "Uploading is temporarily unavailable",
+        "Service is temporarily unavailable",
+        "undergoing maintenance",
+    ],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[data-e2e='upload_progress']",
+                    "[class*='progress-bar']",
+                    "[class*='info-progress']",
+                    "[class*='uploading']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::SelectorExists {
+                label: "caption",
+                selectors: &[
+                    "[data-e2e='caption_container'] [contenteditable='true']",
+                    ".public-DraftEditor-content",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["Uploading", "Uploaded", "Processing", "Replace"],
+            },
+        ],
+        veto_text_markers: &["Log in to TikTok"],
+        deep_scan: false,
+    },
+    // TikTok 只有说明文字，标题并入说明，见 fill_metadata
+    title_selectors: &[],
+    title_editable_selector: None,
+    description_selectors: &[
+        "[data-e2e='caption_container'] .public-DraftEditor-content",
+        "[data-e2e='caption_container'] [contenteditable='true']",
+        ".public-DraftEditor-content",
+    ],
+    description_editable_selector: Some(".public-DraftEditor-content, [contenteditable='true']"),
+    // 话题以 #hashtag 写进说明文字
+    tag_selectors: &[],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "[data-e2e='caption_container']",
+            ".public-DraftEditor-content",
+        ],
+        text_markers: &["Description", "Caption"],
+        advance_text_markers: &[],
+        timeout_secs: 120,
+    }),
+    submit: SubmitConfig {
+        selectors: &["[data-e2e='post_video_button']"],
+        text_markers: &["Post"],
+        success_url_markers: &["/tiktokstudio/content"],
+        success_text_markers: &["Your video has been uploaded", "Video published"],
+    },
+};
+
+pub fn info() -> PlatformInfo {
+    PlatformInfo {
+        id: "tiktok".into(),
+        name: "TikTok".into(),
+        name_en: "TikTok".into(),
+        login_url: "https://www.tiktok.com/login".into(),
+        upload_url: TIKTOK_CONFIG.upload_url.into(),
+        color: "#010101".into(),
+        // Selectors not yet verified against enough live uploads
+        experimental: true,
+    }
+}
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &TIKTOK_CONFIG,
+    automation: &Tiktok,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(60.0 * 60.0),
+    },
+    custom_fields: &[],
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
+
+pub struct Tiktok;
+
+impl PlatformAutomation for Tiktok {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    // 标题、描述和标签合成一段说明文字
+    let mut caption_payload = payload.clone();
+    caption_payload.description = common::merge_into_caption(
+        &payload.title,
+        &payload.description,
+        &payload.tags,
+        usize::MAX,
+        MAX_CAPTION_CHARS,
+    );
+    caption_payload.title.clear();
+    caption_payload.tags.clear();
+    common::fill_metadata(
+        &AutomationContext {
+            payload: &caption_payload,
+            ..*ctx
+        },
+        upload,
+    )
+    .await
+}
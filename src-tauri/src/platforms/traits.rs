@@ -1,3 +1,7 @@
+use super::common::{self, PlatformPublishConfig, PublishPayload, UploadOutcome};
+use crate::browser::page_handle::PageHandle;
+use anyhow::Result;
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,4 +12,86 @@ pub struct PlatformInfo {
     pub login_url: String,
     pub upload_url: String,
     pub color: String,
+    /// Hidden from `all_platforms()` unless experimental platforms are enabled
+    #[serde(default)]
+    pub experimental: bool,
 }
+
+impl PlatformInfo {
+    pub fn localized_name(&self, english: bool) -> &str {
+        if english {
+            &self.name_en
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// Page, content and config of one account's publish run
+#[derive(Clone, Copy)]
+pub struct AutomationContext<'a> {
+    pub handle: &'a PageHandle,
+    pub payload: &'a PublishPayload,
+    /// Effective config: built-in selectors with the user's overrides merged in
+    pub cfg: &'a PlatformPublishConfig,
+}
+
+/// Steps of publishing to one platform, driven in order by the publish
+/// command. Every step defaults to the config-driven flow in `common`; a
+/// platform overrides the steps its quirks live in and usually calls the
+/// default from there.
+pub trait PlatformAutomation: Send + Sync {
+    /// Open the upload page and pass its login and risk-control guards
+    fn prepare_page<'a>(&'a self, ctx: &'a AutomationContext<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(common::prepare_page(ctx))
+    }
+
+    /// Upload the video until the platform shows the upload started
+    fn upload<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<UploadOutcome>> {
+        Box::pin(common::upload(ctx))
+    }
+
+    /// Fill title, description, tags and platform extras; returns the upload
+    /// signal with what was filled appended
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(common::fill_metadata(ctx, upload))
+    }
+
+    /// Last check before an automatic submit that the page still holds what
+    /// the request asked for; a mismatch fails with SUBMIT_BLOCKED_VERIFICATION
+    fn verify_before_submit<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(common::verify_before_submit(ctx, Vec::new()))
+    }
+
+    /// Click the final publish button once it is enabled; returns what was clicked
+    fn confirm_publish<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(common::confirm_publish(ctx))
+    }
+
+    /// Wait for the platform to confirm the post went out
+    fn verify_success<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        clicked: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(common::verify_success(ctx, clicked))
+    }
+}
+
+/// Platforms whose config covers every step
+pub struct ConfigDriven;
+
+impl PlatformAutomation for ConfigDriven {}
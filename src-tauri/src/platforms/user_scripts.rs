@@ -0,0 +1,142 @@
+use crate::browser::page_ops::PageOps;
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Directory under the app data dir holding one `<platform>.json` per platform
+pub const USER_SCRIPTS_DIR: &str = "scripts.d";
+
+pub const DEFAULT_SCRIPT_TIMEOUT_SECS: u64 = 5;
+const MAX_SCRIPT_TIMEOUT_SECS: u64 = 30;
+/// Longest script return value kept in the report
+const MAX_RESULT_CHARS: usize = 200;
+
+/// Snippets run in the upload page right before and right after the form is
+/// filled, e.g. to dismiss an A/B-test banner. Each runs as the body of an
+/// async function; its return value is captured into the automation report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserScripts {
+    #[serde(default)]
+    pub pre_fill_js: Option<String>,
+    #[serde(default)]
+    pub post_fill_js: Option<String>,
+    /// Per-snippet limit; 0 uses the default
+    #[serde(default)]
+    pub timeout_secs: u64,
+}
+
+impl UserScripts {
+    pub fn is_empty(&self) -> bool {
+        [&self.pre_fill_js, &self.post_fill_js]
+            .iter()
+            .all(|script| script.as_deref().is_none_or(|s| s.trim().is_empty()))
+    }
+
+    pub fn timeout(&self) -> Duration {
+        let secs = match self.timeout_secs {
+            0 => DEFAULT_SCRIPT_TIMEOUT_SECS,
+            secs => secs.min(MAX_SCRIPT_TIMEOUT_SECS),
+        };
+        Duration::from_secs(secs)
+    }
+}
+
+pub fn user_scripts_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(USER_SCRIPTS_DIR)
+}
+
+/// Load `scripts.d/<platform>.json`; a missing file means no snippets
+pub fn load(app_data_dir: &Path, platform: &str) -> Result<UserScripts> {
+    let file = user_scripts_dir(app_data_dir).join(format!("{}.json", platform));
+    let Ok(body) = std::fs::read(&file) else {
+        return Ok(UserScripts::default());
+    };
+    serde_json::from_slice(&body).with_context(|| format!("{} 无法解析", file.display()))
+}
+
+/// Write (or with empty snippets, remove) the platform's snippets file
+pub fn save(app_data_dir: &Path, platform: &str, scripts: &UserScripts) -> Result<()> {
+    if super::get_platform_info(platform).is_none() {
+        bail!("Unknown platform: {}", platform);
+    }
+    let dir = user_scripts_dir(app_data_dir);
+    let file = dir.join(format!("{}.json", platform));
+    if scripts.is_empty() {
+        if file.exists() {
+            std::fs::remove_file(&file)?;
+        }
+        return Ok(());
+    }
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(&file, serde_json::to_vec_pretty(scripts)?)?;
+    Ok(())
+}
+
+/// What one snippet did; `status` is `ok`, `error` or `timeout`
+#[derive(Debug, Clone)]
+pub struct ScriptOutcome {
+    pub stage: &'static str,
+    pub status: &'static str,
+    /// Return value or error message, truncated
+    pub output: String,
+}
+
+impl ScriptOutcome {
+    /// Result marker, e.g. `pre_fill_js=ok`
+    pub fn marker(&self) -> String {
+        format!("{}={}", self.stage, self.status)
+    }
+}
+
+/// Run one snippet with a timeout. Failures are reported, never propagated:
+/// a broken snippet must not abort the publish.
+pub async fn run(
+    page: &impl PageOps,
+    platform_name: &str,
+    stage: &'static str,
+    script: &str,
+    timeout: Duration,
+) -> ScriptOutcome {
+    let wrapped = format!(
+        r#"(async () => {{
+            try {{
+                const value = await (async () => {{
+{}
+                }})();
+                return {{ ok: true, value: value === undefined ? null : String(value) }};
+            }} catch (e) {{
+                return {{ ok: false, value: String(e && e.message ? e.message : e) }};
+            }}
+        }})()"#,
+        script
+    );
+    let (status, output) = match tokio::time::timeout(timeout, page.evaluate_json(&wrapped)).await {
+        Ok(Ok(value)) => {
+            let ok = value.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
+            let output = value
+                .get("value")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string();
+            (if ok { "ok" } else { "error" }, output)
+        }
+        Ok(Err(e)) => ("error", e.to_string()),
+        Err(_) => ("timeout", format!("超过 {} 秒未返回", timeout.as_secs())),
+    };
+    let output = output.chars().take(MAX_RESULT_CHARS).collect::<String>();
+    if status == "ok" {
+        info!("[{}用户脚本] {} 完成：{}", platform_name, stage, output);
+    } else {
+        warn!(
+            "[{}用户脚本] {} {}，已跳过：{}",
+            platform_name, stage, status, output
+        );
+    }
+    ScriptOutcome {
+        stage,
+        status,
+        output,
+    }
+}
@@ -1,12 +1,68 @@
-use super::common::{self, PlatformPublishConfig};
-use super::traits::PlatformInfo;
-use anyhow::Result;
+use super::capabilities::{CustomFieldSpec, DurationLimits, FieldKind};
+use super::common::{
+    self, BlockingDialogConfig, LoginGuardConfig, PlatformPublishConfig, PublishPayload,
+    SubmitConfig, UploadOutcome,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order::UploadStrategy;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{bail, Result};
 use chromiumoxide::page::Page;
+use futures::future::BoxFuture;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 合集选择器入口与下拉列表
+const COLLECTION_OPEN_TEXT_MARKERS: &[&str] = &["选择合集", "添加到合集", "合集"];
+const COLLECTION_LIST_SELECTOR: &str =
+    "[class*='collection'], [class*='album'], .weui-desktop-dropdown__list, [role='listbox']";
+const COLLECTION_STEP_WAIT_MS: u64 = 800;
+
+/// 一条动态最多附带的图片/视频数（不含主视频）
+pub const MAX_EXTRA_MEDIA: usize = 8;
+const EXTRA_MEDIA_IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif"];
+const EXTRA_MEDIA_VIDEO_EXTENSIONS: &[&str] = &["mp4", "mov", "m4v"];
+/// 主视频上传后，动态编辑区里继续添加图片/视频的文件输入框
+const EXTRA_MEDIA_INPUT_SELECTORS: &[&str] = &[
+    "[class*='media-list'] input[type='file']",
+    "[class*='add-media'] input[type='file']",
+    "[class*='upload-more'] input[type='file']",
+    "[class*='image-upload'] input[type='file']",
+];
+/// 已加入动态的媒体缩略图；每多出一项说明一个文件已被页面接收
+const EXTRA_MEDIA_ITEM_SELECTOR: &str =
+    "[class*='media-item'], [class*='image-item'], [class*='media-list'] [class*='item']";
+/// 页面拒收文件时的提示
+const EXTRA_MEDIA_REJECT_TEXT_MARKERS: &[&str] =
+    &["格式不支持", "文件过大", "最多上传", "上传失败"];
+const EXTRA_MEDIA_SIGNAL_TIMEOUT_SECS: u64 = 30;
+const EXTRA_MEDIA_POLL_MS: u64 = 500;
 
-const WECHAT_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+/// 页面上表示视频正在上传/处理的文案
+const WECHAT_UPLOADING_TEXT_MARKERS: &[&str] = &[
+    "上传中",
+    "处理中",
+    "校验中",
+    "转码中",
+    "发布中",
+    "正在上传",
+    "重新上传",
+    "更换视频",
+];
+
+/// 内容管理页：作品列表及审核状态
+pub const CONTENT_MANAGER_URL: &str = "https://channels.weixin.qq.com/platform/post/list";
+/// 作品列表里表示仍在审核的状态文案
+const IN_REVIEW_TEXT_MARKERS: &[&str] = &["审核中", "待审核"];
+
+pub(super) const WECHAT_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     id: "wechat",
     name: "微信视频号",
     upload_url: "https://channels.weixin.qq.com/platform/post/create",
+    upload_url_variants: &[],
     target_host: "channels.weixin.qq.com",
     allowed_paths: &["/platform/post/create", "/platform/post"],
     surface_selectors: &[
@@ -76,12 +132,59 @@ const WECHAT_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     weak_ready_min_body_text_len: 0,
     blocked_text_markers: &["暂时无法使用该功能了", "页面加载失败", "请稍后再试", "网络异常"],
     init_text_markers: &["页面初始化中", "初始化中", "正在初始化"],
-    login_text_markers: &[
-        "扫码登录",
-        "微信扫码",
-        "请使用微信扫码登录",
-        "请在手机上确认登录",
-    ],
+    login_guard: LoginGuardConfig {
+        url_markers: &[
+            "channels.weixin.qq.com/login",
+            "login.weixin.qq.com",
+            "scanlogin",
+        ],
+        text_markers: &[
+            "扫码登录",
+            "微信扫码",
+            "请使用微信扫码登录",
+            "请在手机上确认登录",
+        ],
+        success_url_markers: &["channels.weixin.qq.com/platform"],
+        avatar_selectors: &[
+            ".finder-info-container img",
+            "img.avatar",
+            "[class*='avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[],
+    risk_control_url_markers: &[],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停发表", "暂停上传"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='upload-progress']",
+                    "[class*='uploader']",
+                    "[class*='uploading']",
+                    "[class*='progress']",
+                    "[class*='percent']",
+                    "[class*='loading']",
+                ],
+                text_markers: WECHAT_UPLOADING_TEXT_MARKERS,
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: WECHAT_UPLOADING_TEXT_MARKERS,
+            },
+        ],
+        veto_text_markers: &[
+            "页面初始化中",
+            "初始化中",
+            "正在初始化",
+            "扫码登录",
+            "微信扫码",
+            "请使用微信扫码登录",
+            "请在手机上确认登录",
+        ],
+        deep_scan: true,
+    },
     title_selectors: &[
         "input[placeholder*='标题']",
         "input[placeholder*='描述']",
@@ -102,6 +205,23 @@ const WECHAT_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
         "[class*='tag'] input",
         "[class*='topic'] input",
     ],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: Some(BlockingDialogConfig {
+        name: "配乐",
+        dialog_selector: ".weui-desktop-dialog, [class*='dialog'], [role='dialog']",
+        detect_text_markers: &["选择音乐", "添加音乐", "背景音乐", "选择配乐"],
+        choice_text_markers: &["使用原声", "原声", "不使用音乐", "无音乐"],
+        confirm_text_markers: &["确定", "完成"],
+        resolved_signal: "music=original",
+    }),
+    editor_ready: None,
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发表"],
+        success_url_markers: &["/platform/post/list"],
+        success_text_markers: &["发表成功"],
+    },
 };
 
 pub fn info() -> PlatformInfo {
@@ -112,16 +232,321 @@ pub fn info() -> PlatformInfo {
         login_url: "https://channels.weixin.qq.com".into(),
         upload_url: WECHAT_CONFIG.upload_url.into(),
         color: "#07c160".into(),
+        experimental: false,
+    }
+}
+
+/// The upload entry is clicked before trying drag and drop
+const UPLOAD_STRATEGIES: &[UploadStrategy] = &[
+    UploadStrategy::Chooser,
+    UploadStrategy::DirectSet,
+    UploadStrategy::ClickUpload,
+    UploadStrategy::DragDrop,
+];
+
+const CUSTOM_FIELDS: &[CustomFieldSpec] = &[
+    CustomFieldSpec {
+        key: "collection",
+        label: "合集",
+        kind: FieldKind::Text { max_len: 50 },
+    },
+    CustomFieldSpec {
+        key: "activity",
+        label: "活动",
+        kind: FieldKind::Text { max_len: 50 },
+    },
+];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &WECHAT_CONFIG,
+    automation: &Wechat,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(8.0 * 60.0 * 60.0),
+    },
+    custom_fields: CUSTOM_FIELDS,
+    upload_strategies: UPLOAD_STRATEGIES,
+    native_schedule: false,
+};
+
+pub struct Wechat;
+
+impl PlatformAutomation for Wechat {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    let mut signal = common::fill_metadata(ctx, upload).await?;
+    let page = &ctx.handle.ensure_attached().await?;
+
+    if !payload.extra_media.is_empty() {
+        let attached = attach_extra_media(page, payload).await?;
+        signal = format!("{};media={}", signal, attached);
+    }
+
+    // 合集失败不阻断发布，用户可以在页面上手动选择
+    let Some(collection) = payload
+        .custom_fields
+        .text("collection")
+        .filter(|name| !name.trim().is_empty())
+    else {
+        return Ok(signal);
+    };
+    let status = match assign_collection(page, collection.trim()).await {
+        Ok(name) => {
+            info!("[视频号合集] 已加入合集：{}", name);
+            "assigned"
+        }
+        Err(e) => {
+            warn!("[视频号合集] 加入合集失败：{}", e);
+            if e.to_string().starts_with("COLLECTION_NOT_FOUND") {
+                "not_found"
+            } else {
+                "failed"
+            }
+        }
+    };
+    Ok(format!("{};collection={}", signal, status))
+}
+
+/// 检查附加媒体：数量不超过上限，文件存在且是视频号动态接受的图片或视频格式
+pub fn validate_extra_media(paths: &[String]) -> Result<()> {
+    if paths.len() > MAX_EXTRA_MEDIA {
+        bail!(
+            "最多附加 {} 个图片或视频，当前 {} 个",
+            MAX_EXTRA_MEDIA,
+            paths.len()
+        );
     }
+    for path in paths {
+        let file = std::path::Path::new(path);
+        if !file.is_file() {
+            bail!("附加媒体文件不存在：{}", path);
+        }
+        let ext = file
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let supported = EXTRA_MEDIA_IMAGE_EXTENSIONS.contains(&ext.as_str())
+            || EXTRA_MEDIA_VIDEO_EXTENSIONS.contains(&ext.as_str());
+        if !supported {
+            bail!("附加媒体格式不支持：{}", path);
+        }
+    }
+    Ok(())
+}
+
+/// 主视频上传并填表后，逐个把附加图片/视频加入同一条动态。每个文件都要等到
+/// 页面多出一个缩略图才算成功；有文件未被接收时返回 WECHAT_MEDIA_INCOMPLETE，
+/// 页面保持现状由用户补充。
+async fn attach_extra_media(page: &Page, payload: &PublishPayload) -> Result<String> {
+    let timeout = payload.profile.scaled(EXTRA_MEDIA_SIGNAL_TIMEOUT_SECS);
+    let total = payload.extra_media.len();
+    let mut outcomes = Vec::new();
+    for (index, path) in payload.extra_media.iter().enumerate() {
+        payload.ensure_active()?;
+        let before = count_media_items(page).await;
+        let mut set = false;
+        for selector in EXTRA_MEDIA_INPUT_SELECTORS {
+            match automation::set_file_input(page, selector, &[path]).await {
+                Ok(()) => {
+                    set = true;
+                    break;
+                }
+                Err(e) => info!("[视频号附加媒体] 输入框不可用（{}）：{}", selector, e),
+            }
+        }
+        let outcome = if set {
+            wait_for_media_item(page, before, timeout).await
+        } else {
+            "no_input"
+        };
+        info!(
+            "[视频号附加媒体] {}/{} {} -> {}",
+            index + 1,
+            total,
+            path,
+            outcome
+        );
+        outcomes.push((path.as_str(), outcome));
+    }
+
+    let failed = outcomes
+        .iter()
+        .filter(|(_, outcome)| *outcome != "ok")
+        .map(|(path, outcome)| format!("{}={}", path, outcome))
+        .collect::<Vec<_>>();
+    if !failed.is_empty() {
+        bail!(
+            "WECHAT_MEDIA_INCOMPLETE: 附加媒体 {}/{} 未加入动态：{}",
+            failed.len(),
+            total,
+            failed.join(" | ")
+        );
+    }
+    Ok(format!("{}/{}", total, total))
 }
 
-pub async fn auto_publish(
-    page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<String> {
-    common::auto_publish_with_config(page, video_path, title, description, tags, &WECHAT_CONFIG)
+/// 等待缩略图数量超过 `before`；`ok`、`rejected`（页面提示拒收）或 `timeout`
+async fn wait_for_media_item(page: &Page, before: usize, timeout: Duration) -> &'static str {
+    let started = std::time::Instant::now();
+    while started.elapsed() < timeout {
+        tokio::time::sleep(Duration::from_millis(EXTRA_MEDIA_POLL_MS)).await;
+        if count_media_items(page).await > before {
+            return "ok";
+        }
+        if common::find_text_marker(page, EXTRA_MEDIA_REJECT_TEXT_MARKERS)
+            .await
+            .is_some()
+        {
+            return "rejected";
+        }
+    }
+    "timeout"
+}
+
+async fn count_media_items(page: &Page) -> usize {
+    let script = format!(
+        "document.querySelectorAll({}).length",
+        serde_json::to_string(EXTRA_MEDIA_ITEM_SELECTOR).unwrap_or_default()
+    );
+    page.evaluate(script)
         .await
+        .ok()
+        .and_then(|v| v.into_value::<usize>().ok())
+        .unwrap_or(0)
+}
+
+/// 统计内容管理页上状态为审核中的作品数；调用方负责页面已停留在内容管理页
+pub async fn count_in_review(page: &Page) -> Result<usize> {
+    let markers = serde_json::to_string(IN_REVIEW_TEXT_MARKERS)?;
+    let script = format!(
+        r#"(() => {{
+            const markers = {};
+            let count = 0;
+            for (const el of document.querySelectorAll('body *')) {{
+                if (el.children.length > 0) continue;
+                const text = (el.textContent || '').trim();
+                if (markers.includes(text)) count += 1;
+            }}
+            return count;
+        }})()"#,
+        markers
+    );
+    let count: usize = page.evaluate(script).await?.into_value()?;
+    Ok(count)
+}
+
+/// 视频号合集选择器里的一项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WechatCollection {
+    pub name: String,
+    /// 页面上的 data-id / data-value，没有时为空
+    #[serde(default)]
+    pub id: String,
+}
+
+/// 打开合集选择器并读取可选合集；调用方负责页面已停留在发表页
+pub async fn list_collections(page: &Page) -> Result<Vec<WechatCollection>> {
+    open_collection_picker(page).await?;
+    let raw: String = page
+        .evaluate(collection_script("null")?)
+        .await?
+        .into_value()?;
+    Ok(serde_json::from_str(&raw)?)
+}
+
+/// 按合集名称或 id 选择合集，返回实际点中的合集名称
+async fn assign_collection(page: &Page, target: &str) -> Result<String> {
+    open_collection_picker(page).await?;
+    let raw: String = page
+        .evaluate(collection_script(&serde_json::to_string(target)?)?)
+        .await?
+        .into_value()?;
+    let result: serde_json::Value = serde_json::from_str(&raw)?;
+    match result.get("clicked").and_then(|v| v.as_str()) {
+        Some(name) => Ok(name.to_string()),
+        None => {
+            let available = result
+                .get("options")
+                .and_then(|v| v.as_array())
+                .map(|options| {
+                    options
+                        .iter()
+                        .filter_map(|o| o.get("name").and_then(|n| n.as_str()))
+                        .collect::<Vec<_>>()
+                        .join("、")
+                })
+                .unwrap_or_default();
+            bail!(
+                "COLLECTION_NOT_FOUND: 未找到合集「{}」，可选合集：{}",
+                target,
+                if available.is_empty() {
+                    "无"
+                } else {
+                    &available
+                }
+            );
+        }
+    }
+}
+
+async fn open_collection_picker(page: &Page) -> Result<()> {
+    automation::click_by_text(page, COLLECTION_OPEN_TEXT_MARKERS, None).await?;
+    tokio::time::sleep(Duration::from_millis(COLLECTION_STEP_WAIT_MS)).await;
+    Ok(())
+}
+
+/// 读取合集列表；`target_json` 不为 null 时点击名称或 id 匹配的一项
+fn collection_script(target_json: &str) -> Result<String> {
+    Ok(format!(
+        r#"
+        (function() {{
+            const target = {};
+            const listSelector = {};
+            const normalize = (v) => (v || '').replace(/\s+/g, '');
+            const visible = (el) => {{
+                const rect = el.getBoundingClientRect();
+                const style = window.getComputedStyle(el);
+                return rect.width > 4 && rect.height > 4
+                    && style.visibility !== 'hidden' && style.display !== 'none';
+            }};
+            const lists = Array.from(document.querySelectorAll(listSelector)).filter(visible);
+            const options = [];
+            const seen = new Set();
+            for (const list of lists) {{
+                const items = list.querySelectorAll('li, [role="option"], [class*="item"], [class*="option"]');
+                for (const el of items) {{
+                    if (!visible(el)) continue;
+                    const name = (el.innerText || el.textContent || '').trim().split('\n')[0].trim();
+                    if (!name || name.length > 50 || seen.has(name)) continue;
+                    seen.add(name);
+                    const id = el.getAttribute('data-id') || el.getAttribute('data-value') || '';
+                    options.push({{ el, name, id }});
+                }}
+            }}
+            const list = options.map((o) => ({{ name: o.name, id: o.id }}));
+            if (target === null) return JSON.stringify(list);
+            const wanted = normalize(target);
+            const hit = options.find((o) => o.id && o.id === target)
+                || options.find((o) => normalize(o.name) === wanted)
+                || options.find((o) => normalize(o.name).includes(wanted));
+            if (!hit) return JSON.stringify({{ clicked: null, options: list }});
+            hit.el.scrollIntoView({{ block: 'center' }});
+            hit.el.click();
+            return JSON.stringify({{ clicked: hit.name, options: list }});
+        }})()
+        "#,
+        target_json,
+        serde_json::to_string(COLLECTION_LIST_SELECTOR)?
+    ))
 }
@@ -0,0 +1,156 @@
+use super::capabilities::DurationLimits;
+use super::common::{EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{ConfigDriven, PlatformInfo};
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+
+pub(super) const WEIBO_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+    id: "weibo",
+    name: "微博",
+    upload_url: "https://weibo.com/upload/channel",
+    upload_url_variants: &["https://weibo.com/tv/upload"],
+    target_host: "weibo.com",
+    allowed_paths: &["/upload/channel", "/tv/upload"],
+    surface_selectors: &[
+        "[class*='VideoUpload']",
+        "[class*='upload']",
+        "[class*='Upload']",
+        "input[type='file']",
+    ],
+    surface_text_markers: &["上传视频", "点击上传", "拖拽视频到此处"],
+    file_input_selectors: &[
+        "input[type='file'][accept*='video']",
+        "[class*='upload'] input[type='file']",
+        "input[type='file']",
+    ],
+    // 文件输入框失效时走拖拽上传
+    drop_zone_selectors: &[
+        "[class*='VideoUpload']",
+        "[class*='dragArea']",
+        "[class*='upload']",
+        "[class*='Upload']",
+    ],
+    pre_click_selectors: &[],
+    click_selectors: &[
+        "[class*='VideoUpload'] button",
+        "[class*='upload'] button",
+        "button[class*='upload']",
+    ],
+    click_text_markers: &["上传视频", "点击上传", "选择视频"],
+    require_surface_ready: true,
+    fill_failure_is_error: true,
+    weak_ready_self_heal: false,
+    weak_ready_min_body_text_len: 0,
+    blocked_text_markers: &[],
+    init_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &[
+            "passport.weibo.com",
+            "weibo.com/login",
+            "weibo.com/newlogin",
+        ],
+        text_markers: &["扫码登录", "短信登录", "账号登录"],
+        success_url_markers: &["weibo.com/upload", "weibo.com/tv"],
+        avatar_selectors: &[
+            "[class*='Avatar'] img",
+            "img[class*='avatar']",
+            "[class*='woo-avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[
+        "请完成安全验证",
+        "安全验证",
+        "操作过于频繁",
+        "您的账号存在异常",
+    ],
+    risk_control_url_markers: &["/security/verify", "/captcha"],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停发布", "暂停上传"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='Progress']",
+                    "[class*='progress']",
+                    "[class*='percent']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "正在上传", "视频处理中", "上传成功", "上传完成"],
+            },
+            SignalMatcher::TextIncludes {
+                label: "replace-video",
+                markers: &["重新上传", "更换视频"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
+    title_selectors: &[
+        "input[placeholder*='标题']",
+        "[class*='title'] input",
+        "textarea[placeholder*='标题']",
+    ],
+    title_editable_selector: None,
+    description_selectors: &[
+        "textarea[placeholder*='简介']",
+        "textarea[placeholder*='分享新鲜事']",
+        "[class*='Form_input'] textarea",
+        "textarea",
+    ],
+    description_editable_selector: Some("[contenteditable='true']"),
+    tag_selectors: &[
+        "input[placeholder*='话题']",
+        "input[placeholder*='标签']",
+        "[class*='topic'] input",
+        "[class*='Tag'] input",
+    ],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "input[placeholder*='标题']",
+            "textarea[placeholder*='简介']",
+        ],
+        text_markers: &["填写标题", "视频简介", "添加话题"],
+        advance_text_markers: &[],
+        timeout_secs: 60,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发布", "立即发布"],
+        success_url_markers: &["/u/", "/tv/show"],
+        success_text_markers: &["发布成功"],
+    },
+};
+
+pub fn info() -> PlatformInfo {
+    PlatformInfo {
+        id: "weibo".into(),
+        name: "微博".into(),
+        name_en: "Weibo".into(),
+        login_url: "https://passport.weibo.com/sso/signin".into(),
+        upload_url: WEIBO_CONFIG.upload_url.into(),
+        color: "#e6162d".into(),
+        // Selectors not yet verified against enough live uploads
+        experimental: true,
+    }
+}
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &WEIBO_CONFIG,
+    automation: &ConfigDriven,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(60.0 * 60.0),
+    },
+    custom_fields: &[],
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
@@ -1,12 +1,41 @@
-use super::common::{self, PlatformPublishConfig};
-use super::traits::PlatformInfo;
-use anyhow::Result;
+use super::capabilities::{CustomFieldSpec, DurationLimits, FieldKind, PostType};
+use super::common::{
+    self, EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig, UploadOutcome,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{anyhow, bail, Result};
 use chromiumoxide::page::Page;
+use futures::future::BoxFuture;
+use log::info;
+use std::path::Path;
+use std::time::Duration;
+
+/// 小红书图文笔记最多 18 张图
+const MAX_IMAGES: usize = 18;
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp"];
+/// 上传页顶部切换到图文笔记的标签
+const IMAGE_TAB_TEXT_MARKERS: &[&str] = &["上传图文"];
+const IMAGE_TAB_WAIT_MS: u64 = 800;
+const IMAGE_INPUT_SELECTORS: &[&str] = &[
+    "input[type='file'][accept*='image'][multiple]",
+    "input.upload-input",
+    "input[type='file'][accept*='image']",
+];
+/// 编辑页里每张已接收图片一个缩略图
+const IMAGE_ITEM_SELECTOR: &str =
+    "[class*='img-container'] img, [class*='image-item'] img, [class*='img-list'] img";
+const IMAGE_UPLOAD_TIMEOUT_SECS: u64 = 60;
+const IMAGE_POLL_MS: u64 = 500;
 
-const XIAOHONGSHU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+pub(super) const XIAOHONGSHU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     id: "xiaohongshu",
     name: "小红书",
     upload_url: "https://creator.xiaohongshu.com/publish/publish",
+    upload_url_variants: &[],
     target_host: "creator.xiaohongshu.com",
     allowed_paths: &["/publish/publish", "/publish"],
     surface_selectors: &[
@@ -42,7 +71,59 @@ const XIAOHONGSHU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     weak_ready_min_body_text_len: 0,
     blocked_text_markers: &[],
     init_text_markers: &[],
-    login_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["creator.xiaohongshu.com/login"],
+        text_markers: &[],
+        success_url_markers: &[
+            "creator.xiaohongshu.com/new",
+            "creator.xiaohongshu.com/publish",
+        ],
+        avatar_selectors: &[
+            ".user-info img",
+            ".reds-avatar img",
+            "[class*='avatar'] img",
+        ],
+    },
+    risk_control_text_markers: &[
+        "请完成安全验证",
+        "安全验证",
+        "滑块验证",
+        "访问过于频繁",
+        "账号存在异常",
+        "当前地区不支持",
+        "IP存在风险",
+    ],
+    risk_control_url_markers: &["website-login/captcha", "/captcha", "verifyType"],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停发布", "暂停上传"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::UrlContains {
+                label: "post",
+                patterns: &["/publish/post", "/publish/edit", "/publish/success"],
+            },
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='progress']",
+                    "[class*='upload-progress']",
+                    "[class*='percent']",
+                    "[class*='loading']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "处理中", "发布中", "正在上传"],
+            },
+            SignalMatcher::TextIncludes {
+                label: "replace-video",
+                markers: &["重新上传", "更换视频"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
     title_selectors: &[
         "input[placeholder*='标题']",
         "input[placeholder*='添加标题']",
@@ -63,6 +144,25 @@ const XIAOHONGSHU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
         "[class*='tag'] input",
         "[class*='topic'] input",
     ],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "input[placeholder*='标题']",
+            "[class*='title'] input",
+            "[contenteditable='true']",
+        ],
+        text_markers: &["填写标题", "添加正文"],
+        advance_text_markers: &[],
+        timeout_secs: 60,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发布"],
+        success_url_markers: &["/publish/success"],
+        success_text_markers: &["发布成功"],
+    },
 };
 
 pub fn info() -> PlatformInfo {
@@ -73,23 +173,153 @@ pub fn info() -> PlatformInfo {
         login_url: "https://creator.xiaohongshu.com".into(),
         upload_url: XIAOHONGSHU_CONFIG.upload_url.into(),
         color: "#ff2442".into(),
+        experimental: false,
+    }
+}
+
+const CUSTOM_FIELDS: &[CustomFieldSpec] = &[CustomFieldSpec {
+    key: "visibility",
+    label: "可见范围",
+    kind: FieldKind::Choice {
+        options: &["public", "private"],
+    },
+}];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &XIAOHONGSHU_CONFIG,
+    automation: &Xiaohongshu,
+    duration_limits: DurationLimits {
+        min_secs: Some(5.0),
+        max_secs: Some(15.0 * 60.0),
+    },
+    custom_fields: CUSTOM_FIELDS,
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
+
+/// 图片路径的数量、存在性和格式，发布前校验
+pub fn validate_images(paths: &[String]) -> Result<()> {
+    if paths.is_empty() {
+        bail!("图文笔记至少需要 1 张图片");
+    }
+    if paths.len() > MAX_IMAGES {
+        bail!(
+            "图文笔记最多 {} 张图片，当前 {} 张",
+            MAX_IMAGES,
+            paths.len()
+        );
+    }
+    for path in paths {
+        let file = Path::new(path);
+        if !file.is_file() {
+            bail!("图片文件不存在：{}", path);
+        }
+        let ext = file
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            bail!("图片格式不支持：{}", path);
+        }
+    }
+    Ok(())
+}
+
+pub struct Xiaohongshu;
+
+impl PlatformAutomation for Xiaohongshu {
+    fn prepare_page<'a>(&'a self, ctx: &'a AutomationContext<'a>) -> BoxFuture<'a, Result<()>> {
+        Box::pin(prepare_page(ctx))
+    }
+
+    fn upload<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<UploadOutcome>> {
+        Box::pin(upload(ctx))
+    }
+}
+
+/// 图文笔记先切到“上传图文”标签，之后的页面守卫与视频共用
+async fn prepare_page(ctx: &AutomationContext<'_>) -> Result<()> {
+    common::prepare_page(ctx).await?;
+    if ctx.payload.post_type != PostType::Images {
+        return Ok(());
+    }
+    let page = ctx.handle.ensure_attached().await?;
+    let marker = automation::click_by_text(&page, IMAGE_TAB_TEXT_MARKERS, None)
+        .await
+        .map_err(|e| anyhow!("[小红书图文] 未找到“上传图文”入口：{}", e))?;
+    info!("[小红书图文] 已切换到图文笔记：{}", marker);
+    tokio::time::sleep(Duration::from_millis(IMAGE_TAB_WAIT_MS)).await;
+    Ok(())
+}
+
+/// 一次把所有图片写入多选文件框，等编辑页出现同样数量的缩略图
+async fn upload(ctx: &AutomationContext<'_>) -> Result<UploadOutcome> {
+    let payload = ctx.payload;
+    if payload.post_type != PostType::Images {
+        return common::upload(ctx).await;
+    }
+    let page = &ctx.handle.ensure_attached().await?;
+    let paths = payload
+        .images
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let mut diagnostics = Vec::new();
+    let mut input = None;
+    for selector in IMAGE_INPUT_SELECTORS {
+        match automation::set_file_input(page, selector, &paths).await {
+            Ok(()) => {
+                input = Some(*selector);
+                break;
+            }
+            Err(e) => diagnostics.push(format!("{} failed={}", selector, e)),
+        }
+    }
+    let Some(selector) = input else {
+        bail!(
+            "[小红书图文] 未找到图片上传输入框：{}",
+            diagnostics.join(" | ")
+        );
+    };
+    diagnostics.push(format!("image_input={}", selector));
+
+    let timeout = payload.profile.scaled(IMAGE_UPLOAD_TIMEOUT_SECS);
+    let started = std::time::Instant::now();
+    let total = paths.len();
+    let mut received = 0;
+    while started.elapsed() < timeout {
+        payload.ensure_active()?;
+        received = count_images(page).await;
+        if received >= total {
+            info!("[小红书图文] {} 张图片已上传", total);
+            return Ok(UploadOutcome {
+                signal: format!("images:{}/{}", received, total),
+                dialog_signal: None,
+                diagnostics,
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(IMAGE_POLL_MS)).await;
     }
+    bail!(
+        "[小红书图文] 等待图片上传超时：{}/{} 张已出现",
+        received,
+        total
+    );
 }
 
-pub async fn auto_publish(
-    page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<String> {
-    common::auto_publish_with_config(
-        page,
-        video_path,
-        title,
-        description,
-        tags,
-        &XIAOHONGSHU_CONFIG,
-    )
-    .await
+async fn count_images(page: &Page) -> usize {
+    let script = format!(
+        "document.querySelectorAll({}).length",
+        serde_json::to_string(IMAGE_ITEM_SELECTOR).unwrap_or_default()
+    );
+    page.evaluate(script)
+        .await
+        .ok()
+        .and_then(|v| v.into_value::<usize>().ok())
+        .unwrap_or(0)
 }
@@ -1,12 +1,65 @@
-use super::common::{self, PlatformPublishConfig};
-use super::traits::PlatformInfo;
-use anyhow::Result;
+use super::capabilities::{CustomFieldSpec, DurationLimits, FieldKind};
+use super::common::{
+    self, LoginGuardConfig, PlatformPublishConfig, SelectionCheck, SubmitConfig, UploadOutcome,
+};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{AutomationContext, PlatformAutomation, PlatformInfo};
+use crate::browser::automation;
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+use anyhow::{anyhow, bail, Result};
 use chromiumoxide::page::Page;
+use futures::future::BoxFuture;
+use log::{info, warn};
+use std::time::Duration;
+
+/// YouTube 描述中超过 15 个 hashtag 时会全部失效
+const MAX_HASHTAGS: usize = 15;
+/// 标签字段总长度上限（含逗号）
+const MAX_TAGS_TOTAL_CHARS: usize = 500;
+const SHOW_MORE_WAIT_MS: u64 = 500;
+const SHOW_MORE_SELECTORS: &[&str] = &[
+    "ytcp-video-metadata-editor #toggle-button",
+    "#toggle-button",
+];
+const SHOW_MORE_TEXT_MARKERS: &[&str] = &["Show more", "SHOW MORE", "显示更多", "展开"];
+const WIZARD_STEP_WAIT_MS: u64 = 800;
+/// “详细信息”之后依次是“视频元素”“检查”“公开范围”三步
+const WIZARD_NEXT_STEPS: usize = 3;
+const NEXT_SELECTORS: &[&str] = &["ytcp-uploads-dialog #next-button", "#next-button"];
+const NEXT_TEXT_MARKERS: &[&str] = &["Next", "下一步"];
+const NOT_MADE_FOR_KIDS_SELECTORS: &[&str] = &[
+    "tp-yt-paper-radio-button[name='VIDEO_MADE_FOR_KIDS_NOT_MFK']",
+    "[name='VIDEO_MADE_FOR_KIDS_NOT_MFK']",
+];
+const MADE_FOR_KIDS_SELECTORS: &[&str] = &[
+    "tp-yt-paper-radio-button[name='VIDEO_MADE_FOR_KIDS_MFK']",
+    "[name='VIDEO_MADE_FOR_KIDS_MFK']",
+];
+const PLAYLIST_TRIGGER_SELECTORS: &[&str] = &[
+    "ytcp-video-metadata-playlists ytcp-dropdown-trigger",
+    "ytcp-video-metadata-playlists [role='button']",
+];
+const PLAYLIST_DIALOG_SELECTOR: &str = "ytcp-playlist-dialog";
+const PLAYLIST_DONE_SELECTORS: &[&str] = &[
+    "ytcp-playlist-dialog .done-button",
+    "ytcp-playlist-dialog #save-button",
+];
+const PLAYLIST_DONE_TEXT_MARKERS: &[&str] = &["Done", "完成"];
+const CATEGORY_TRIGGER_SELECTORS: &[&str] = &[
+    "ytcp-form-select#category ytcp-dropdown-trigger",
+    "#category ytcp-dropdown-trigger",
+];
+const CATEGORY_OPTION_SELECTORS: &[&str] = &[
+    "tp-yt-paper-listbox tp-yt-paper-item",
+    "ytcp-text-menu tp-yt-paper-item",
+];
 
-const YOUTUBE_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+pub(super) const YOUTUBE_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     id: "youtube",
     name: "YouTube",
     upload_url: "https://studio.youtube.com",
+    upload_url_variants: &[],
     target_host: "studio.youtube.com",
     allowed_paths: &[],
     surface_selectors: &[
@@ -46,7 +99,54 @@ const YOUTUBE_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     weak_ready_min_body_text_len: 0,
     blocked_text_markers: &[],
     init_text_markers: &[],
-    login_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["accounts.google.com", "servicelogin"],
+        text_markers: &[],
+        success_url_markers: &["studio.youtube.com", "www.youtube.com"],
+        avatar_selectors: &["#avatar-btn", "ytcp-entity-avatar", "img#img[alt]"],
+    },
+    risk_control_text_markers: &[],
+    risk_control_url_markers: &[],
+    maintenance_text_markers: &[
+        "YouTube Studio is temporarily unavailable",
+        "Uploading is temporarily unavailable",
+        "undergoing maintenance",
+    ],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::UrlContains {
+                label: "upload",
+                patterns: &["/upload", "uploading", "video_id="],
+            },
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "ytcp-video-upload-progress",
+                    "ytcp-upload-progress",
+                    "ytcp-uploads-dialog",
+                    "[id*='progress']",
+                    "[class*='progress']",
+                    "[class*='upload-progress']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &[
+                    "Uploading",
+                    "Processing",
+                    "Checking",
+                    "Checks complete",
+                    "Upload complete",
+                    "上传中",
+                    "处理中",
+                ],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
     title_selectors: &[
         "#title-textarea #textbox",
         "#title-textarea [contenteditable='true']",
@@ -66,11 +166,25 @@ const YOUTUBE_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
     ],
     description_editable_selector: Some("#description-textarea #textbox, [contenteditable='true']"),
     tag_selectors: &[
+        "#tags-container input",
         "input[aria-label*='Tags']",
         "input[aria-label*='标签']",
         "#text-input input",
         "[class*='tags'] input",
     ],
+    subtitle_input_selectors: &[
+        "ytcp-subtitles-upload-dialog input[type='file']",
+        "input[type='file'][accept*='.srt']",
+    ],
+    subtitle_open_text_markers: &["Add subtitles", "添加字幕", "Upload file", "上传文件"],
+    blocking_dialog: None,
+    editor_ready: None,
+    submit: SubmitConfig {
+        selectors: &["#done-button"],
+        text_markers: &["Publish", "发布", "Save", "保存"],
+        success_url_markers: &[],
+        success_text_markers: &["Video published", "视频已发布"],
+    },
 };
 
 pub fn info() -> PlatformInfo {
@@ -81,16 +195,300 @@ pub fn info() -> PlatformInfo {
         login_url: "https://accounts.google.com".into(),
         upload_url: YOUTUBE_CONFIG.upload_url.into(),
         color: "#ff0000".into(),
+        experimental: false,
+    }
+}
+
+const CUSTOM_FIELDS: &[CustomFieldSpec] = &[
+    CustomFieldSpec {
+        key: "visibility",
+        label: "Visibility",
+        kind: FieldKind::Choice {
+            options: &["public", "unlisted", "private"],
+        },
+    },
+    CustomFieldSpec {
+        key: "made_for_kids",
+        label: "Made for kids",
+        kind: FieldKind::Bool,
+    },
+    CustomFieldSpec {
+        key: "playlists",
+        label: "Playlists",
+        kind: FieldKind::List { max_items: 10 },
+    },
+    CustomFieldSpec {
+        key: "category",
+        label: "Category",
+        kind: FieldKind::Text { max_len: 50 },
+    },
+];
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &YOUTUBE_CONFIG,
+    automation: &Youtube,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(12.0 * 60.0 * 60.0),
+    },
+    custom_fields: CUSTOM_FIELDS,
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
+
+pub struct Youtube;
+
+impl PlatformAutomation for Youtube {
+    fn fill_metadata<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+        upload: &'a UploadOutcome,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(fill_metadata(ctx, upload))
+    }
+
+    fn verify_before_submit<'a>(
+        &'a self,
+        ctx: &'a AutomationContext<'a>,
+    ) -> BoxFuture<'a, Result<String>> {
+        let name = visibility_radio_name(ctx.payload.custom_fields.text("visibility"));
+        let checks = vec![SelectionCheck::Selector {
+            field: "visibility",
+            selector: format!(
+                "tp-yt-paper-radio-button[name='{0}'][aria-checked='true'], #privacy-radios [name='{0}'][checked]",
+                name
+            ),
+        }];
+        Box::pin(common::verify_before_submit(ctx, checks))
+    }
+}
+
+async fn fill_metadata(ctx: &AutomationContext<'_>, upload: &UploadOutcome) -> Result<String> {
+    let payload = ctx.payload;
+    // 通用标签在 YouTube 上拆成两部分：描述末尾的 #hashtag 和"显示更多"里的标签字段
+    let mut base_payload = payload.clone();
+    base_payload.description = append_hashtags(&payload.description, &payload.tags);
+    base_payload.tags.clear();
+    let mut signal = common::fill_metadata(
+        &AutomationContext {
+            payload: &base_payload,
+            ..*ctx
+        },
+        upload,
+    )
+    .await?;
+    let page = &ctx.handle.ensure_attached().await?;
+    let fields = &payload.custom_fields;
+
+    let tags_value = join_tags(&payload.tags);
+    let category = fields.text("category");
+    if !tags_value.is_empty() || category.is_some() {
+        if let Err(e) = show_more(page).await {
+            warn!("[YouTube设置] 展开“显示更多”失败：{}", e);
+        }
+    }
+    if !tags_value.is_empty() {
+        match automation::type_into_input(page, ctx.cfg.tag_selectors, &tags_value, true).await {
+            Ok(marker) => {
+                info!("[YouTube标签] 标签已填写：{} ({})", tags_value, marker);
+                signal.push_str(";yt_tags=ok");
+            }
+            Err(e) => {
+                warn!("[YouTube标签] 标签填写失败：{}", e);
+                signal.push_str(";yt_tags=failed");
+            }
+        }
+    }
+    if let Some(category) = category {
+        let status = match set_category(page, category).await {
+            Ok(()) => "ok",
+            Err(e) => {
+                warn!("[YouTube设置] 类别设置失败：{}", e);
+                "failed"
+            }
+        };
+        signal.push_str(&format!(";yt_category={}", status));
+    }
+
+    // 必须回答“是否为儿童打造”，否则无法进入下一步
+    let made_for_kids = fields.flag("made_for_kids").unwrap_or(false);
+    let audience = if made_for_kids {
+        MADE_FOR_KIDS_SELECTORS
+    } else {
+        NOT_MADE_FOR_KIDS_SELECTORS
+    };
+    match automation::click_first_visible(page, audience).await {
+        Ok(marker) => info!(
+            "[YouTube设置] 受众已设置：made_for_kids={} ({})",
+            made_for_kids, marker
+        ),
+        Err(e) => bail!("设置“是否为儿童打造”失败：{}", e),
+    }
+
+    let playlists = fields.list("playlists");
+    if !playlists.is_empty() {
+        let status = match add_to_playlists(page, &playlists).await {
+            Ok(()) => "ok",
+            Err(e) => {
+                warn!("[YouTube设置] 加入播放列表失败：{}", e);
+                "failed"
+            }
+        };
+        signal.push_str(&format!(";yt_playlists={}", status));
+    }
+
+    let visibility = fields.text("visibility").unwrap_or("public");
+    advance_to_visibility(page).await?;
+    set_visibility(page, visibility).await?;
+    Ok(format!("{};yt_visibility={}", signal, visibility))
+}
+
+async fn show_more(page: &Page) -> Result<()> {
+    if automation::click_first_visible(page, SHOW_MORE_SELECTORS)
+        .await
+        .is_err()
+    {
+        automation::click_by_text(page, SHOW_MORE_TEXT_MARKERS, None).await?;
+    }
+    tokio::time::sleep(Duration::from_millis(SHOW_MORE_WAIT_MS)).await;
+    Ok(())
+}
+
+/// 类别下拉框在“显示更多”里，选项文字与 Studio 界面语言一致
+async fn set_category(page: &Page, category: &str) -> Result<()> {
+    automation::click_first_visible(page, CATEGORY_TRIGGER_SELECTORS).await?;
+    tokio::time::sleep(Duration::from_millis(WIZARD_STEP_WAIT_MS)).await;
+    match automation::click_matching_option(page, CATEGORY_OPTION_SELECTORS, category, &[]).await? {
+        Some(marker) => {
+            info!("[YouTube设置] 类别已选择：{} ({})", category, marker);
+            Ok(())
+        }
+        None => bail!("下拉列表中没有类别“{}”", category),
     }
 }
 
-pub async fn auto_publish(
-    page: &Page,
-    video_path: &str,
-    title: &str,
-    description: &str,
-    tags: &[String],
-) -> Result<String> {
-    common::auto_publish_with_config(page, video_path, title, description, tags, &YOUTUBE_CONFIG)
+/// 在播放列表对话框中勾选每个列表，然后点“完成”；列表需事先在频道中创建
+async fn add_to_playlists(page: &Page, playlists: &[String]) -> Result<()> {
+    automation::click_first_visible(page, PLAYLIST_TRIGGER_SELECTORS).await?;
+    tokio::time::sleep(Duration::from_millis(WIZARD_STEP_WAIT_MS)).await;
+    let mut missing = Vec::new();
+    for playlist in playlists {
+        if let Err(e) =
+            automation::click_by_text(page, &[playlist.as_str()], Some(PLAYLIST_DIALOG_SELECTOR))
+                .await
+        {
+            warn!("[YouTube设置] 未找到播放列表“{}”：{}", playlist, e);
+            missing.push(playlist.as_str());
+        }
+    }
+    if automation::click_first_visible(page, PLAYLIST_DONE_SELECTORS)
         .await
+        .is_err()
+    {
+        automation::click_by_text(
+            page,
+            PLAYLIST_DONE_TEXT_MARKERS,
+            Some(PLAYLIST_DIALOG_SELECTOR),
+        )
+        .await?;
+    }
+    tokio::time::sleep(Duration::from_millis(WIZARD_STEP_WAIT_MS)).await;
+    if !missing.is_empty() {
+        bail!("未找到播放列表：{}", missing.join("、"));
+    }
+    Ok(())
+}
+
+/// 逐步点“下一步”，停在公开范围这一页
+async fn advance_to_visibility(page: &Page) -> Result<()> {
+    for step in 1..=WIZARD_NEXT_STEPS {
+        if let Err(e) = automation::click_first_visible(page, NEXT_SELECTORS).await {
+            automation::click_by_text(page, NEXT_TEXT_MARKERS, None)
+                .await
+                .map_err(|_| anyhow!("第 {} 步未找到“下一步”按钮：{}", step, e))?;
+        }
+        tokio::time::sleep(Duration::from_millis(WIZARD_STEP_WAIT_MS)).await;
+    }
+    Ok(())
+}
+
+/// Radio button name of a visibility value; unset means public
+fn visibility_radio_name(visibility: Option<&str>) -> &'static str {
+    match visibility {
+        Some("unlisted") => "UNLISTED",
+        Some("private") => "PRIVATE",
+        _ => "PUBLIC",
+    }
+}
+
+async fn set_visibility(page: &Page, visibility: &str) -> Result<()> {
+    let name = visibility_radio_name(Some(visibility));
+    let selectors = [
+        format!("tp-yt-paper-radio-button[name='{}']", name),
+        format!("#privacy-radios [name='{}']", name),
+    ];
+    let selectors = selectors.iter().map(String::as_str).collect::<Vec<_>>();
+    let marker = automation::click_first_visible(page, &selectors)
+        .await
+        .map_err(|e| anyhow!("设置公开范围 {} 失败：{}", visibility, e))?;
+    info!("[YouTube设置] 公开范围已设置：{} ({})", visibility, marker);
+    Ok(())
+}
+
+fn to_hashtag(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .collect()
+}
+
+/// 把标签转成 #hashtag 追加到描述末尾，已出现在描述里的不重复添加。
+fn append_hashtags(description: &str, tags: &[String]) -> String {
+    let existing = description.to_lowercase();
+    let mut hashtags: Vec<String> = Vec::new();
+    for tag in tags {
+        let hashtag = to_hashtag(tag);
+        if hashtag.is_empty() {
+            continue;
+        }
+        let rendered = format!("#{}", hashtag);
+        let lower = rendered.to_lowercase();
+        if existing.contains(&lower) || hashtags.iter().any(|h| h.to_lowercase() == lower) {
+            continue;
+        }
+        hashtags.push(rendered);
+        if hashtags.len() >= MAX_HASHTAGS {
+            break;
+        }
+    }
+
+    if hashtags.is_empty() {
+        return description.to_string();
+    }
+    let trimmed = description.trim_end();
+    if trimmed.is_empty() {
+        hashtags.join(" ")
+    } else {
+        format!("{}\n\n{}", trimmed, hashtags.join(" "))
+    }
+}
+
+/// YouTube 标签字段以逗号分隔，总长度超过上限的标签会被丢弃。
+fn join_tags(tags: &[String]) -> String {
+    let mut joined = String::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').replace(',', " ");
+        if tag.is_empty() {
+            continue;
+        }
+        let extra = if joined.is_empty() { 0 } else { 1 };
+        if joined.chars().count() + extra + tag.chars().count() > MAX_TAGS_TOTAL_CHARS {
+            break;
+        }
+        if !joined.is_empty() {
+            joined.push(',');
+        }
+        joined.push_str(&tag);
+    }
+    joined
 }
@@ -0,0 +1,144 @@
+use super::capabilities::DurationLimits;
+use super::common::{EditorReadyConfig, LoginGuardConfig, PlatformPublishConfig, SubmitConfig};
+use super::registry::PlatformEntry;
+use super::strategy_order;
+use super::traits::{ConfigDriven, PlatformInfo};
+use crate::browser::signals::{SignalMatcher, UploadSignals};
+
+pub(super) const ZHIHU_CONFIG: PlatformPublishConfig = PlatformPublishConfig {
+    id: "zhihu",
+    name: "知乎",
+    upload_url: "https://www.zhihu.com/zvideo/upload-video",
+    upload_url_variants: &["https://www.zhihu.com/creator/zvideo/upload"],
+    target_host: "www.zhihu.com",
+    allowed_paths: &["/zvideo/upload-video", "/creator/zvideo"],
+    surface_selectors: &[
+        "[class*='Upload']",
+        "[class*='upload']",
+        "[class*='Dropzone']",
+    ],
+    surface_text_markers: &["上传视频", "点击上传", "拖拽视频到此处"],
+    file_input_selectors: &[
+        "input[type='file'][accept*='video']",
+        "[class*='Upload'] input[type='file']",
+        "input[type='file']",
+    ],
+    drop_zone_selectors: &[
+        "[class*='Dropzone']",
+        "[class*='Upload']",
+        "[class*='upload']",
+    ],
+    pre_click_selectors: &[],
+    click_selectors: &[
+        "button[class*='Upload']",
+        "[class*='Upload'] button",
+        "[class*='upload'] button",
+        "[role='button']",
+    ],
+    click_text_markers: &["上传视频", "点击上传", "选择文件"],
+    require_surface_ready: true,
+    fill_failure_is_error: true,
+    weak_ready_self_heal: false,
+    weak_ready_min_body_text_len: 0,
+    blocked_text_markers: &[],
+    init_text_markers: &[],
+    login_guard: LoginGuardConfig {
+        url_markers: &["www.zhihu.com/signin", "www.zhihu.com/signup"],
+        text_markers: &["扫码登录", "验证码登录", "密码登录"],
+        success_url_markers: &["www.zhihu.com/creator", "www.zhihu.com/zvideo"],
+        avatar_selectors: &[
+            ".AppHeader-profileEntry img",
+            "[class*='AppHeader'] [class*='Avatar']",
+            "img.Avatar",
+        ],
+    },
+    risk_control_text_markers: &[
+        "请完成安全验证",
+        "安全验证",
+        "系统监测到您的网络环境存在异常",
+        "操作过于频繁",
+    ],
+    risk_control_url_markers: &["/account/unhuman", "/captcha"],
+    maintenance_text_markers: &["系统维护中", "系统升级中", "暂停发布", "暂停上传"],
+    upload_signals: UploadSignals {
+        matchers: &[
+            SignalMatcher::FileInputNonempty,
+            SignalMatcher::SelectorExists {
+                label: "progress",
+                selectors: &[
+                    "[class*='Progress']",
+                    "[class*='progress']",
+                    "[class*='percent']",
+                ],
+                text_markers: &[],
+            },
+            SignalMatcher::TextIncludes {
+                label: "uploading",
+                markers: &["上传中", "正在上传", "视频处理中", "上传完成"],
+            },
+            SignalMatcher::TextIncludes {
+                label: "replace-video",
+                markers: &["重新上传", "更换视频"],
+            },
+        ],
+        veto_text_markers: &[],
+        deep_scan: false,
+    },
+    title_selectors: &[
+        "input[placeholder*='标题']",
+        "[class*='Title'] input",
+        "textarea[placeholder*='标题']",
+    ],
+    title_editable_selector: None,
+    description_selectors: &[
+        "textarea[placeholder*='简介']",
+        "textarea[placeholder*='描述']",
+        "[class*='Description'] textarea",
+    ],
+    description_editable_selector: Some("[contenteditable='true']"),
+    tag_selectors: &["input[placeholder*='话题']", "[class*='Topic'] input"],
+    subtitle_input_selectors: &[],
+    subtitle_open_text_markers: &[],
+    blocking_dialog: None,
+    editor_ready: Some(EditorReadyConfig {
+        selectors: &[
+            "input[placeholder*='标题']",
+            "textarea[placeholder*='简介']",
+        ],
+        text_markers: &["填写标题", "视频简介"],
+        advance_text_markers: &[],
+        timeout_secs: 60,
+    }),
+    submit: SubmitConfig {
+        selectors: &[],
+        text_markers: &["发布视频", "发布"],
+        success_url_markers: &["/zvideo/"],
+        success_text_markers: &["发布成功"],
+    },
+};
+
+pub fn info() -> PlatformInfo {
+    PlatformInfo {
+        id: "zhihu".into(),
+        name: "知乎".into(),
+        name_en: "Zhihu".into(),
+        login_url: "https://www.zhihu.com/signin".into(),
+        upload_url: ZHIHU_CONFIG.upload_url.into(),
+        color: "#1772f6".into(),
+        // Selectors not yet verified against enough live uploads
+        experimental: true,
+    }
+}
+
+pub(super) const PLATFORM: PlatformEntry = PlatformEntry {
+    info,
+    config: &ZHIHU_CONFIG,
+    automation: &ConfigDriven,
+    duration_limits: DurationLimits {
+        min_secs: None,
+        max_secs: Some(60.0 * 60.0),
+    },
+    custom_fields: &[],
+    upload_strategies: strategy_order::DEFAULT_ORDER,
+    native_schedule: false,
+};
@@ -0,0 +1,195 @@
+use crate::browser::{chrome, profile_lock};
+use crate::database::{queries, Database};
+use crate::diagnostics::progress;
+use crate::startup;
+use anyhow::{anyhow, bail, Context, Result};
+use log::{info, warn};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Files of the open database; the database itself is copied with VACUUM INTO
+const DB_FILES: &[&str] = &[
+    "multipublisher.db",
+    "multipublisher.db-wal",
+    "multipublisher.db-shm",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageLocations {
+    pub data_dir: String,
+    pub data_dir_overridden: bool,
+    pub profiles_dir: String,
+    pub profiles_dir_overridden: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelocationSummary {
+    /// Account profiles moved to the new profiles directory
+    pub profiles_moved: usize,
+    /// Profiles outside the old base directory, left where they are
+    pub profiles_left: Vec<String>,
+    /// The data directory was copied; it is used after a restart
+    pub restart_required: bool,
+    /// Old data directory, kept as a backup until the user removes it
+    pub previous_data_dir: Option<String>,
+}
+
+pub fn locations(data_dir: &Path) -> Result<StorageLocations> {
+    Ok(StorageLocations {
+        data_dir: data_dir.display().to_string(),
+        data_dir_overridden: startup::data_dir_override().is_some(),
+        profiles_dir: chrome::get_profiles_base_dir()?.display().to_string(),
+        profiles_dir_overridden: startup::profiles_dir_override().is_some(),
+    })
+}
+
+/// Move the Chrome profiles and/or the data directory. Profiles are moved
+/// first and their stored paths updated, so the copied database already points
+/// at the new locations. Refused while a publish is running.
+pub fn relocate(
+    db: &Database,
+    current_data_dir: &Path,
+    new_data_dir: Option<&Path>,
+    new_profiles_dir: Option<&Path>,
+) -> Result<RelocationSummary> {
+    if !progress::running_task_ids().is_empty() {
+        bail!("Wait for running publish tasks to finish before moving data");
+    }
+    let mut summary = RelocationSummary::default();
+    if let Some(target) = new_profiles_dir {
+        relocate_profiles(db, target, &mut summary)?;
+    }
+    if let Some(target) = new_data_dir {
+        relocate_data_dir(db, current_data_dir, target)?;
+        summary.restart_required = true;
+        summary.previous_data_dir = Some(current_data_dir.display().to_string());
+    }
+    Ok(summary)
+}
+
+fn relocate_profiles(db: &Database, target: &Path, summary: &mut RelocationSummary) -> Result<()> {
+    let current = chrome::get_profiles_base_dir()?;
+    check_target(&current, target)?;
+    let accounts = {
+        let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+        queries::get_all_accounts(&conn)?
+    };
+
+    let mut moves = Vec::new();
+    for account in &accounts {
+        let profile_dir = PathBuf::from(&account.chrome_profile_dir);
+        let Ok(relative) = profile_dir.strip_prefix(&current) else {
+            summary
+                .profiles_left
+                .push(account.chrome_profile_dir.clone());
+            continue;
+        };
+        let in_use = chrome::is_profile_busy(&profile_dir)
+            || profile_lock::inspect(&profile_dir).is_some_and(|lock| !lock.stale);
+        if in_use {
+            bail!(
+                "Close the Chrome window of {} before moving profiles",
+                account.display_name
+            );
+        }
+        let destination = target.join(relative);
+        if destination.exists() {
+            bail!("{} already exists", destination.display());
+        }
+        moves.push((account.id, profile_dir, destination));
+    }
+
+    // Each row is updated right after its move, so a failure part-way leaves
+    // every account pointing at where its profile actually is
+    for (account_id, from, to) in moves {
+        if from.exists() {
+            move_dir(&from, &to)?;
+        }
+        let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+        queries::update_account_profile_dir(&conn, account_id, &to.to_string_lossy())?;
+        summary.profiles_moved += 1;
+    }
+    startup::set_profiles_dir_override(Some(target))?;
+    info!(
+        "Moved {} Chrome profile(s) from {} to {}",
+        summary.profiles_moved,
+        current.display(),
+        target.display()
+    );
+    Ok(())
+}
+
+/// Copy the data directory to `target` and switch to it on the next start.
+/// The database is snapshotted with VACUUM INTO while holding the connection,
+/// so nothing written in between is lost.
+fn relocate_data_dir(db: &Database, current: &Path, target: &Path) -> Result<()> {
+    check_target(current, target)?;
+    let not_empty = std::fs::read_dir(target).is_ok_and(|mut entries| entries.next().is_some());
+    if not_empty {
+        bail!("{} is not empty", target.display());
+    }
+
+    for entry in std::fs::read_dir(current)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if DB_FILES.contains(&name.as_str()) {
+            continue;
+        }
+        copy_recursive(&entry.path(), &target.join(&name))?;
+    }
+    let conn = db.conn.lock().map_err(|e| anyhow!(e.to_string()))?;
+    conn.execute(
+        "VACUUM INTO ?1",
+        [target.join(DB_FILES[0]).to_string_lossy().to_string()],
+    )
+    .context("Copying the database failed")?;
+    startup::set_data_dir_override(Some(target))?;
+    info!(
+        "Copied data directory {} to {}; used after restart",
+        current.display(),
+        target.display()
+    );
+    Ok(())
+}
+
+fn check_target(current: &Path, target: &Path) -> Result<()> {
+    if !target.is_absolute() {
+        bail!("{} is not an absolute path", target.display());
+    }
+    if target.starts_with(current) || current.starts_with(target) {
+        bail!(
+            "{} and {} must not contain each other",
+            target.display(),
+            current.display()
+        );
+    }
+    startup::ensure_writable(target)
+}
+
+/// Rename when possible; across file systems copy and then remove the source
+fn move_dir(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to).with_context(|| format!("Copying {} failed", from.display()))?;
+    if let Err(e) = std::fs::remove_dir_all(from) {
+        warn!("Copied {} but could not remove it: {}", from.display(), e);
+    }
+    Ok(())
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<()> {
+    let meta = std::fs::symlink_metadata(from)?;
+    if meta.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)?.flatten() {
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else if meta.is_file() {
+        std::fs::copy(from, to)?;
+    }
+    // Chrome's Singleton* symlinks are per-session and not worth carrying over
+    Ok(())
+}
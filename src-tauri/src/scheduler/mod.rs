@@ -0,0 +1,3 @@
+pub mod payload;
+pub mod queue;
+pub mod window;
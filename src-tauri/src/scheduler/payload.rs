@@ -0,0 +1,106 @@
+use crate::commands::publish::PublishRequest;
+use crate::database::queries;
+use anyhow::{anyhow, bail, Context, Result};
+use log::{info, warn};
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Payload version written by this build. When a change to `PublishRequest`
+/// would stop older payloads from deserializing, bump it and append the
+/// converter from the previous version to `UPGRADES`.
+pub const CURRENT_VERSION: u64 = 2;
+
+/// Converters from version N to N + 1, at index N - 1
+const UPGRADES: &[fn(Value) -> Result<Value>] = &[v1_to_v2];
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    version: u64,
+    request: &'a PublishRequest,
+}
+
+/// Stored form of a queued request: `{"version": N, "request": {...}}`
+pub fn encode(request: &PublishRequest) -> Result<String> {
+    Ok(serde_json::to_string(&Envelope {
+        version: CURRENT_VERSION,
+        request,
+    })?)
+}
+
+/// Version 1 payloads predate the envelope and are a bare request object
+fn version_of(value: &Value) -> u64 {
+    value.get("version").and_then(Value::as_u64).unwrap_or(1)
+}
+
+/// True when the payload was written by a newer build than this one, e.g.
+/// after rolling an update back; such jobs are left pending, not failed
+pub fn is_from_newer_version(raw: &str) -> bool {
+    serde_json::from_str::<Value>(raw).is_ok_and(|value| version_of(&value) > CURRENT_VERSION)
+}
+
+/// Read a stored payload of any known version, upgrading it step by step
+pub fn decode(raw: &str) -> Result<PublishRequest> {
+    let mut value: Value = serde_json::from_str(raw).context("payload is not valid JSON")?;
+    let mut version = version_of(&value);
+    if version == 0 || version > CURRENT_VERSION {
+        bail!(
+            "payload version {} is not supported (this build reads up to {})",
+            version,
+            CURRENT_VERSION
+        );
+    }
+    while version < CURRENT_VERSION {
+        value = UPGRADES[version as usize - 1](value)
+            .with_context(|| format!("upgrading payload from version {}", version))?;
+        version += 1;
+    }
+    let request = value
+        .get_mut("request")
+        .map(Value::take)
+        .ok_or_else(|| anyhow!("payload has no request"))?;
+    Ok(serde_json::from_value(request)?)
+}
+
+fn v1_to_v2(value: Value) -> Result<Value> {
+    if !value.is_object() {
+        bail!("version 1 payload is not an object");
+    }
+    Ok(json!({ "version": 2, "request": value }))
+}
+
+/// Rewrite pending payloads from older builds in the current format, so a
+/// job queued overnight before an update runs with what this build reads.
+/// Payloads that cannot be upgraded are left untouched and fail when due.
+pub fn upgrade_pending(conn: &Connection) -> Result<usize> {
+    let mut upgraded = 0;
+    for (id, raw) in queries::get_pending_scheduled_payloads(conn)? {
+        if serde_json::from_str::<Value>(&raw).is_ok_and(|v| version_of(&v) == CURRENT_VERSION) {
+            continue;
+        }
+        if is_from_newer_version(&raw) {
+            warn!(
+                "Scheduled task {} was queued by a newer app version; leaving it pending",
+                id
+            );
+            continue;
+        }
+        match decode(&raw).and_then(|request| encode(&request)) {
+            Ok(payload) => {
+                queries::update_scheduled_task_payload(conn, id, &payload)?;
+                upgraded += 1;
+            }
+            Err(e) => warn!(
+                "Scheduled task {} payload could not be upgraded: {:#}",
+                id, e
+            ),
+        }
+    }
+    if upgraded > 0 {
+        info!(
+            "Upgraded {} scheduled task payload(s) to version {}",
+            upgraded, CURRENT_VERSION
+        );
+    }
+    Ok(upgraded)
+}
@@ -0,0 +1,123 @@
+use super::payload;
+use crate::commands::publish;
+use crate::database::{queries, Database};
+use crate::tasks::TaskRegistry;
+use log::{info, warn};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How often due scheduled tasks are looked for
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// `true` while due jobs are held back; set from the tray menu or the settings
+pub const SETTING_PAUSED: &str = "scheduler_paused";
+
+/// `run_at` is stored in this format so due jobs can be found by comparing strings
+pub fn format_run_at(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.format("%Y-%m-%dT%H:%M:%SZ").to_string()
+}
+
+/// Start scheduled tasks once their time has come. Jobs run one after another,
+/// each through the same path as a publish started from the UI.
+pub fn spawn_worker(app: AppHandle) {
+    if let Ok(conn) = app.state::<Database>().conn.lock() {
+        match queries::mark_orphaned_scheduled_tasks_interrupted(&conn) {
+            Ok(0) => {}
+            Ok(count) => warn!(
+                "Marked {} scheduled task(s) from an unfinished session as interrupted",
+                count
+            ),
+            Err(e) => warn!("Failed to reconcile unfinished scheduled tasks: {}", e),
+        }
+        if let Err(e) = payload::upgrade_pending(&conn) {
+            warn!("Failed to upgrade scheduled task payloads: {}", e);
+        }
+    }
+
+    let registry = app.state::<TaskRegistry>().inner().clone();
+    registry.spawn("scheduler:worker", async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            run_due(&app).await;
+        }
+    });
+}
+
+async fn run_due(app: &AppHandle) {
+    let due = {
+        let db = app.state::<Database>();
+        let Ok(conn) = db.conn.lock() else {
+            return;
+        };
+        match queries::get_due_scheduled_tasks(&conn, &format_run_at(chrono::Utc::now())) {
+            Ok(due) => due,
+            Err(e) => {
+                warn!("Failed to load due scheduled tasks: {}", e);
+                return;
+            }
+        }
+    };
+    for (id, payload) in due {
+        // Checked per job so pausing during a batch holds back the rest
+        if is_paused(app) {
+            break;
+        }
+        run_job(app, id, &payload).await;
+    }
+}
+
+pub fn is_paused(app: &AppHandle) -> bool {
+    let db = app.state::<Database>();
+    let Ok(conn) = db.conn.lock() else {
+        return false;
+    };
+    queries::get_setting_bool(&conn, SETTING_PAUSED, false).unwrap_or(false)
+}
+
+async fn run_job(app: &AppHandle, id: i64, raw: &str) {
+    if payload::is_from_newer_version(raw) {
+        warn!(
+            "Scheduled task {} was queued by a newer app version; leaving it pending",
+            id
+        );
+        return;
+    }
+    let db = app.state::<Database>();
+    let claimed = match db.conn.lock() {
+        Ok(conn) => queries::claim_scheduled_task(&conn, id).unwrap_or(false),
+        Err(_) => false,
+    };
+    if !claimed {
+        return;
+    }
+
+    info!("Starting scheduled task {}", id);
+    let outcome = match payload::decode(raw) {
+        Ok(request) => {
+            publish::create_publish_task(app.clone(), app.state(), app.state(), request).await
+        }
+        Err(e) => Err(format!("Stored request could not be read: {:#}", e)),
+    };
+    let (status, task_id, error) = match outcome {
+        Ok(result) => {
+            info!("Scheduled task {} ran as task {}", id, result.task_id);
+            ("done", Some(result.task_id), None)
+        }
+        Err(e) => {
+            warn!("Scheduled task {} failed: {}", id, e);
+            ("failed", None, Some(e))
+        }
+    };
+    let finished = match db.conn.lock() {
+        Ok(conn) => queries::finish_scheduled_task(&conn, id, status, task_id, error.as_deref()),
+        Err(e) => Err(anyhow::anyhow!(e.to_string())),
+    };
+    if let Err(e) = finished {
+        warn!(
+            "Failed to record the outcome of scheduled task {}: {}",
+            id, e
+        );
+    }
+}
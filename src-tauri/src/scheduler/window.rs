@@ -0,0 +1,105 @@
+use crate::database::queries;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Local, NaiveTime};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// One daily span in local time, `HH:MM`. An end before the start wraps past
+/// midnight, e.g. 22:00–02:00.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PublishWindow {
+    pub start: String,
+    pub end: String,
+}
+
+/// Times of day an account may publish at. No windows means any time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PublishWindows {
+    #[serde(default)]
+    pub windows: Vec<PublishWindow>,
+}
+
+fn parse_time(raw: &str) -> Result<NaiveTime> {
+    match NaiveTime::parse_from_str(raw.trim(), "%H:%M") {
+        Ok(time) => Ok(time),
+        Err(_) => bail!("Invalid time {:?}, expected HH:MM", raw),
+    }
+}
+
+impl PublishWindow {
+    fn bounds(&self) -> Result<(NaiveTime, NaiveTime)> {
+        let (start, end) = (parse_time(&self.start)?, parse_time(&self.end)?);
+        if start == end {
+            bail!("Publish window {}–{} is empty", self.start, self.end);
+        }
+        Ok((start, end))
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        match self.bounds() {
+            Ok((start, end)) if start < end => time >= start && time < end,
+            Ok((start, end)) => time >= start || time < end,
+            Err(_) => false,
+        }
+    }
+}
+
+impl PublishWindows {
+    /// The account's windows; unset or unreadable means any time
+    pub fn load(conn: &Connection, account_id: i64) -> Result<Self> {
+        Ok(Self::parse(
+            queries::get_account_publish_windows(conn, account_id)?.as_deref(),
+        ))
+    }
+
+    /// Windows from their stored JSON; unset or unreadable means any time
+    pub fn parse(raw: Option<&str>) -> Self {
+        raw.and_then(|raw| serde_json::from_str(raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        for window in &self.windows {
+            window.bounds()?;
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.windows.is_empty()
+    }
+
+    pub fn allows(&self, at: DateTime<Local>) -> bool {
+        self.is_empty() || self.windows.iter().any(|w| w.contains(at.time()))
+    }
+
+    /// `at` itself when allowed, otherwise the next window opening after it
+    pub fn next_allowed(&self, at: DateTime<Local>) -> DateTime<Local> {
+        if self.allows(at) {
+            return at;
+        }
+        self.windows
+            .iter()
+            .filter_map(|w| w.bounds().ok())
+            .filter_map(|(start, _)| {
+                let today = at.date_naive().and_time(start);
+                let opening = if today > at.naive_local() {
+                    today
+                } else {
+                    today + Duration::days(1)
+                };
+                opening.and_local_timezone(Local).earliest()
+            })
+            .min()
+            .unwrap_or(at)
+    }
+
+    /// `18:00–22:00、07:00–09:00`
+    pub fn describe(&self) -> String {
+        self.windows
+            .iter()
+            .map(|w| format!("{}–{}", w.start.trim(), w.end.trim()))
+            .collect::<Vec<_>>()
+            .join("、")
+    }
+}
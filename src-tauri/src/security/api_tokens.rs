@@ -0,0 +1,111 @@
+use crate::database::queries::{self, ApiToken};
+use anyhow::{anyhow, bail, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL;
+use base64::Engine;
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const TOKEN_PREFIX: &str = "mpp_";
+const TOKEN_BYTES: usize = 32;
+/// Characters of the token kept in clear for display
+const DISPLAY_PREFIX_LEN: usize = 12;
+const MAX_NAME_LEN: usize = 64;
+
+/// What a token may do on the local API/CLI. Scopes are ordered: each one
+/// includes everything the scopes before it allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Read tasks, results and statistics
+    Stats,
+    /// Also create and cancel publish tasks
+    Publish,
+    /// Also manage accounts, settings, exports and tokens
+    Admin,
+}
+
+impl ApiScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stats => "stats",
+            Self::Publish => "publish",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "stats" => Some(Self::Stats),
+            "publish" => Some(Self::Publish),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn allows(self, required: ApiScope) -> bool {
+        self >= required
+    }
+}
+
+/// A freshly created token. `token` is shown once and never stored.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub info: ApiToken,
+}
+
+fn hash_token(token: &str) -> String {
+    digest(&SHA256, token.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+pub fn create(conn: &Connection, name: &str, scope: ApiScope) -> Result<CreatedApiToken> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        bail!("Token name must be 1-{} characters", MAX_NAME_LEN);
+    }
+    let mut secret = [0u8; TOKEN_BYTES];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| anyhow!("failed to generate token"))?;
+    let token = format!("{}{}", TOKEN_PREFIX, BASE64_URL.encode(secret));
+    let display_prefix = token.chars().take(DISPLAY_PREFIX_LEN).collect::<String>();
+    let id = queries::insert_api_token(
+        conn,
+        name,
+        scope.as_str(),
+        &hash_token(&token),
+        &display_prefix,
+    )?;
+    let info = queries::get_api_tokens(conn)?
+        .into_iter()
+        .find(|token| token.id == id)
+        .ok_or_else(|| anyhow!("API token {} not found after insert", id))?;
+    Ok(CreatedApiToken { token, info })
+}
+
+/// Per-request check for the local API/CLI: the token must exist, not be
+/// revoked and carry a scope that covers `required`. Records the use.
+pub fn authorize(conn: &Connection, token: &str, required: ApiScope) -> Result<ApiToken> {
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+    let Some(info) = queries::find_api_token_by_hash(conn, &hash_token(token))? else {
+        bail!("Invalid or revoked API token");
+    };
+    let scope = ApiScope::parse(&info.scope)
+        .ok_or_else(|| anyhow!("API token {} has an unknown scope", info.id))?;
+    if !scope.allows(required) {
+        bail!(
+            "API token scope {} does not allow {} requests",
+            scope.as_str(),
+            required.as_str()
+        );
+    }
+    queries::touch_api_token(conn, info.id)?;
+    Ok(info)
+}
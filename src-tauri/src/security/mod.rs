@@ -0,0 +1,2 @@
+pub mod api_tokens;
+pub mod vault;
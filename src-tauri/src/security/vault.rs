@@ -0,0 +1,257 @@
+use crate::database::queries;
+use anyhow::{anyhow, bail, Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroU32;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Random salt for the passphrase key derivation, base64. Kept in
+/// `vault_config`, away from the settings commands.
+const CONFIG_SALT: &str = "vault_salt";
+/// A known value sealed with the vault key, used to verify the passphrase
+const CONFIG_CHECK: &str = "vault_check";
+
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const MIN_PASSPHRASE_LEN: usize = 8;
+const CHECK_PLAINTEXT: &[u8] = b"multipublisher-vault-v1";
+/// The vault locks itself again after this long without being unlocked anew
+const AUTO_LOCK_AFTER: Duration = Duration::from_secs(10 * 60);
+
+pub const AUDIT_SETUP: &str = "setup";
+pub const AUDIT_UNLOCK: &str = "unlock";
+pub const AUDIT_UNLOCK_FAILED: &str = "unlock_failed";
+pub const AUDIT_LOCK: &str = "lock";
+pub const AUDIT_READ: &str = "read";
+pub const AUDIT_WRITE: &str = "write";
+pub const AUDIT_DELETE: &str = "delete";
+
+/// Sensitive per-account details agencies keep next to the app
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountNotes {
+    #[serde(default)]
+    pub phone: String,
+    #[serde(default)]
+    pub owner: String,
+    #[serde(default)]
+    pub recovery_email: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl AccountNotes {
+    fn is_empty(&self) -> bool {
+        [&self.phone, &self.owner, &self.recovery_email, &self.notes]
+            .iter()
+            .all(|field| field.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    /// A passphrase has been set up
+    pub configured: bool,
+    pub unlocked: bool,
+}
+
+struct UnlockedKey {
+    bytes: [u8; KEY_LEN],
+    unlocked_at: Instant,
+}
+
+impl Drop for UnlockedKey {
+    fn drop(&mut self) {
+        self.bytes.fill(0);
+    }
+}
+
+/// Passphrase-protected store for account notes. Notes are sealed with
+/// AES-256-GCM under a key derived from the passphrase; the key only lives in
+/// memory while the vault is unlocked. Every access is written to the audit log.
+#[derive(Default)]
+pub struct Vault {
+    key: Mutex<Option<UnlockedKey>>,
+}
+
+impl Vault {
+    pub fn status(&self, conn: &Connection) -> Result<VaultStatus> {
+        Ok(VaultStatus {
+            configured: queries::get_vault_config(conn, CONFIG_CHECK)?.is_some(),
+            unlocked: self.current_key().is_ok(),
+        })
+    }
+
+    /// Set the passphrase the first time; the vault is unlocked afterwards
+    pub fn setup(&self, conn: &Connection, passphrase: &str) -> Result<()> {
+        if queries::get_vault_config(conn, CONFIG_CHECK)?.is_some() {
+            bail!("Vault passphrase is already set");
+        }
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            bail!(
+                "Passphrase must be at least {} characters",
+                MIN_PASSPHRASE_LEN
+            );
+        }
+        let mut salt = [0u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| anyhow!("failed to generate salt"))?;
+        let key = derive_key(passphrase, &salt);
+        queries::set_vault_config(conn, CONFIG_SALT, &BASE64.encode(salt))?;
+        queries::set_vault_config(conn, CONFIG_CHECK, &seal(&key, CHECK_PLAINTEXT, &[])?)?;
+        queries::insert_vault_audit(conn, None, AUDIT_SETUP)?;
+        self.store_key(key);
+        Ok(())
+    }
+
+    pub fn unlock(&self, conn: &Connection, passphrase: &str) -> Result<()> {
+        let (salt, check) = match (
+            queries::get_vault_config(conn, CONFIG_SALT)?,
+            queries::get_vault_config(conn, CONFIG_CHECK)?,
+        ) {
+            (Some(salt), Some(check)) => (salt, check),
+            _ => bail!("Vault is not set up yet"),
+        };
+        let salt = BASE64.decode(salt).context("vault salt is corrupted")?;
+        let key = derive_key(passphrase, &salt);
+        if open(&key, &check, &[]).ok().as_deref() != Some(CHECK_PLAINTEXT) {
+            queries::insert_vault_audit(conn, None, AUDIT_UNLOCK_FAILED)?;
+            bail!("Wrong passphrase");
+        }
+        queries::insert_vault_audit(conn, None, AUDIT_UNLOCK)?;
+        self.store_key(key);
+        Ok(())
+    }
+
+    pub fn lock(&self, conn: &Connection) -> Result<()> {
+        if let Ok(mut key) = self.key.lock() {
+            *key = None;
+        }
+        queries::insert_vault_audit(conn, None, AUDIT_LOCK)
+    }
+
+    pub fn read_notes(&self, conn: &Connection, account_id: i64) -> Result<Option<AccountNotes>> {
+        let key = self.current_key()?;
+        let Some(sealed) = queries::get_account_notes(conn, account_id)? else {
+            return Ok(None);
+        };
+        let aad = notes_aad(account_id);
+        let plain = match open(&key, &sealed, &aad) {
+            Ok(plain) => plain,
+            // Notes sealed before they were bound to their account; seal them
+            // again with the binding now that they have been read
+            Err(_) => {
+                let plain =
+                    open(&key, &sealed, &[]).context("account notes could not be decrypted")?;
+                queries::upsert_account_notes(conn, account_id, &seal(&key, &plain, &aad)?)?;
+                plain
+            }
+        };
+        queries::insert_vault_audit(conn, Some(account_id), AUDIT_READ)?;
+        Ok(Some(serde_json::from_slice(&plain)?))
+    }
+
+    /// Replace an account's notes; all-empty notes delete the entry
+    pub fn write_notes(
+        &self,
+        conn: &Connection,
+        account_id: i64,
+        notes: &AccountNotes,
+    ) -> Result<()> {
+        let key = self.current_key()?;
+        if notes.is_empty() {
+            queries::delete_account_notes(conn, account_id)?;
+            return queries::insert_vault_audit(conn, Some(account_id), AUDIT_DELETE);
+        }
+        let sealed = seal(&key, &serde_json::to_vec(notes)?, &notes_aad(account_id))?;
+        queries::upsert_account_notes(conn, account_id, &sealed)?;
+        queries::insert_vault_audit(conn, Some(account_id), AUDIT_WRITE)
+    }
+
+    fn store_key(&self, bytes: [u8; KEY_LEN]) {
+        if let Ok(mut key) = self.key.lock() {
+            *key = Some(UnlockedKey {
+                bytes,
+                unlocked_at: Instant::now(),
+            });
+        }
+    }
+
+    fn current_key(&self) -> Result<[u8; KEY_LEN]> {
+        let mut key = self.key.lock().map_err(|e| anyhow!(e.to_string()))?;
+        match key.as_ref() {
+            Some(unlocked) if unlocked.unlocked_at.elapsed() < AUTO_LOCK_AFTER => {
+                Ok(unlocked.bytes)
+            }
+            Some(_) => {
+                *key = None;
+                bail!("Vault is locked");
+            }
+            None => bail!("Vault is locked"),
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).expect("non-zero iterations");
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    key
+}
+
+/// Associated data binding sealed notes to their account, so notes copied
+/// onto another account's row fail to open
+fn notes_aad(account_id: i64) -> Vec<u8> {
+    format!("account_notes:{}", account_id).into_bytes()
+}
+
+/// base64(nonce || ciphertext || tag); `aad` must be given again to open it
+fn seal(key: &[u8; KEY_LEN], plaintext: &[u8], aad: &[u8]) -> Result<String> {
+    let key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow!("invalid vault key"))?,
+    );
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate nonce"))?;
+    let mut buffer = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(aad),
+        &mut buffer,
+    )
+    .map_err(|_| anyhow!("encryption failed"))?;
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&buffer);
+    Ok(BASE64.encode(sealed))
+}
+
+fn open(key: &[u8; KEY_LEN], sealed: &str, aad: &[u8]) -> Result<Vec<u8>> {
+    let raw = BASE64.decode(sealed)?;
+    if raw.len() < NONCE_LEN {
+        bail!("sealed value is too short");
+    }
+    let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+    let key = LessSafeKey::new(
+        UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow!("invalid vault key"))?,
+    );
+    let mut buffer = ciphertext.to_vec();
+    let plain = key
+        .open_in_place(nonce, Aad::from(aad), &mut buffer)
+        .map_err(|_| anyhow!("decryption failed"))?;
+    Ok(plain.to_vec())
+}
@@ -0,0 +1,262 @@
+use crate::browser::chrome;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{Manager, Runtime};
+use thiserror::Error;
+
+/// Names a data directory picked by the user. Lives next to the Chrome
+/// profiles rather than in the data dir, so it can be read when the default
+/// data dir is the thing that is broken.
+const DATA_DIR_OVERRIDE_FILE: &str = "data_dir";
+/// Same for the Chrome profiles base directory
+const PROFILES_DIR_OVERRIDE_FILE: &str = "profiles_dir";
+
+/// Held in the data directory by the running instance
+const INSTANCE_LOCK_FILE: &str = "instance.lock";
+/// Set to `1` to run several instances against the same data directory, e.g.
+/// separate workspaces that never publish with the same accounts
+pub const ENV_ALLOW_MULTIPLE_INSTANCES: &str = "MULTIPUBLISHER_ALLOW_MULTIPLE_INSTANCES";
+
+#[derive(Debug, Error)]
+pub enum SetupError {
+    #[error("app data directory unavailable: {0}")]
+    DataDir(String),
+    #[error("database could not be opened: {0}")]
+    Database(String),
+    #[error("another instance (pid {pid}) is already using this data directory")]
+    AlreadyRunning { pid: u32 },
+}
+
+impl SetupError {
+    fn stage(&self) -> &'static str {
+        match self {
+            Self::DataDir(_) => "data_dir",
+            Self::Database(_) => "database",
+            Self::AlreadyRunning { .. } => "instance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupIssue {
+    /// `data_dir`, `database` or `instance`
+    pub stage: &'static str,
+    pub message: String,
+}
+
+/// How setup went. In degraded mode nothing but the diagnostics and data
+/// directory commands is available; the UI should offer to pick another
+/// data directory.
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupStatus {
+    pub degraded: bool,
+    pub data_dir: Option<String>,
+    /// The data directory comes from the user's override, not the default
+    pub data_dir_overridden: bool,
+    /// Single-instance enforcement was turned off through the environment
+    pub multiple_instances_allowed: bool,
+    pub issues: Vec<StartupIssue>,
+}
+
+impl StartupStatus {
+    pub fn ready(data_dir: &Path, overridden: bool) -> Self {
+        Self {
+            degraded: false,
+            data_dir: Some(data_dir.display().to_string()),
+            data_dir_overridden: overridden,
+            multiple_instances_allowed: multiple_instances_allowed(),
+            issues: Vec::new(),
+        }
+    }
+
+    pub fn degraded(error: &SetupError) -> Self {
+        Self {
+            degraded: true,
+            data_dir: None,
+            data_dir_overridden: data_dir_override().is_some(),
+            multiple_instances_allowed: multiple_instances_allowed(),
+            issues: vec![StartupIssue {
+                stage: error.stage(),
+                message: error.to_string(),
+            }],
+        }
+    }
+}
+
+fn override_file(name: &str) -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".multi-publisher").join(name))
+}
+
+fn read_override(name: &str) -> Option<PathBuf> {
+    let raw = std::fs::read_to_string(override_file(name)?).ok()?;
+    let path = raw.trim();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Point `name` at `dir`, or remove the override with `None`. The directory
+/// must be creatable and writable.
+fn write_override(name: &str, dir: Option<&Path>) -> Result<()> {
+    let file = override_file(name).context("Cannot find home directory")?;
+    let Some(dir) = dir else {
+        if file.exists() {
+            std::fs::remove_file(&file)?;
+        }
+        return Ok(());
+    };
+    ensure_writable(dir)?;
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&file, dir.display().to_string())?;
+    Ok(())
+}
+
+pub fn ensure_writable(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    let probe = dir.join(".write-test");
+    std::fs::write(&probe, b"ok").with_context(|| format!("{} is not writable", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// The user-chosen data directory, if one is set
+pub fn data_dir_override() -> Option<PathBuf> {
+    read_override(DATA_DIR_OVERRIDE_FILE)
+}
+
+/// The user-chosen Chrome profiles base directory, if one is set
+pub fn profiles_dir_override() -> Option<PathBuf> {
+    read_override(PROFILES_DIR_OVERRIDE_FILE)
+}
+
+/// Directory holding the database, artifacts and per-platform config files:
+/// the override when set, otherwise Tauri's app data dir
+pub fn data_dir<R: Runtime>(app: &impl Manager<R>) -> Result<PathBuf> {
+    match data_dir_override() {
+        Some(dir) => Ok(dir),
+        None => Ok(app.path().app_data_dir()?),
+    }
+}
+
+/// Like [`data_dir`], creating the directory; the flag tells whether it is
+/// the override
+pub fn resolve_data_dir<R: Runtime>(app: &impl Manager<R>) -> Result<(PathBuf, bool), SetupError> {
+    let dir = data_dir(app).map_err(|e| SetupError::DataDir(e.to_string()))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| SetupError::DataDir(format!("{}: {}", dir.display(), e)))?;
+    Ok((dir, data_dir_override().is_some()))
+}
+
+/// Use `dir` as the data directory from the next start on; `None` goes back
+/// to the default
+pub fn set_data_dir_override(dir: Option<&Path>) -> Result<()> {
+    write_override(DATA_DIR_OVERRIDE_FILE, dir)
+}
+
+/// Create new Chrome profiles under `dir`; `None` goes back to the default
+pub fn set_profiles_dir_override(dir: Option<&Path>) -> Result<()> {
+    write_override(PROFILES_DIR_OVERRIDE_FILE, dir)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct InstanceLockInfo {
+    pid: u32,
+    started_at: String,
+}
+
+/// The data directory lock held by this process; released on exit, or when
+/// dropped because startup failed after claiming it
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    pub fn release(&self) {
+        let ours = read_lock_holder(&self.path).is_some_and(|info| info.pid == std::process::id());
+        if ours {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+fn read_lock_holder(path: &Path) -> Option<InstanceLockInfo> {
+    std::fs::read(path)
+        .ok()
+        .and_then(|body| serde_json::from_slice::<InstanceLockInfo>(&body).ok())
+}
+
+/// A lock file too young to have its body written yet belongs to an instance
+/// that is still starting
+fn lock_file_is_fresh(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < std::time::Duration::from_secs(5))
+}
+
+pub fn multiple_instances_allowed() -> bool {
+    std::env::var(ENV_ALLOW_MULTIPLE_INSTANCES).is_ok_and(|v| v.trim() == "1")
+}
+
+/// Claim the data directory for this process. A lock left by a process that
+/// no longer runs is taken over. Returns `None` when enforcement is off.
+pub fn acquire_instance_lock(data_dir: &Path) -> Result<Option<InstanceLock>, SetupError> {
+    if multiple_instances_allowed() {
+        log::warn!(
+            "{} is set; not guarding against other instances",
+            ENV_ALLOW_MULTIPLE_INSTANCES
+        );
+        return Ok(None);
+    }
+    let path = data_dir.join(INSTANCE_LOCK_FILE);
+    let info = InstanceLockInfo {
+        pid: std::process::id(),
+        started_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let body = serde_json::to_vec(&info).map_err(|e| SetupError::DataDir(e.to_string()))?;
+    // Created exclusively so two instances starting together cannot both win
+    let lock_error = |e: std::io::Error| SetupError::DataDir(format!("{}: {}", path.display(), e));
+    match create_lock_file(&path, &body) {
+        Ok(()) => return Ok(Some(InstanceLock { path })),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(lock_error(e)),
+    }
+    match read_lock_holder(&path) {
+        Some(holder) if holder.pid != info.pid && chrome::is_pid_running(holder.pid) => {
+            return Err(SetupError::AlreadyRunning { pid: holder.pid });
+        }
+        Some(holder) => log::info!("Taking over instance lock left by pid {}", holder.pid),
+        None if lock_file_is_fresh(&path) => {
+            return Err(SetupError::AlreadyRunning { pid: 0 });
+        }
+        None => log::info!("Clearing unreadable instance lock {}", path.display()),
+    }
+    match std::fs::remove_file(&path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(lock_error(e)),
+        _ => {}
+    }
+    // Another instance clearing the same stale lock may have claimed it first
+    create_lock_file(&path, &body).map_err(lock_error)?;
+    Ok(Some(InstanceLock { path }))
+}
+
+fn create_lock_file(path: &Path, body: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    if let Err(e) = file.write_all(body).and_then(|_| file.sync_all()) {
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use log::info;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use tauri::async_runtime::{self, JoinHandle};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// Tracks background tasks (periodic pollers, CDP handler loops, one-off jobs)
+/// so they can be listed for debugging and aborted together on shutdown.
+/// Clones share the same registry.
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: AtomicU64,
+    entries: Mutex<Vec<Entry>>,
+    /// Cancellation tokens of running publish tasks, keyed by task id
+    publish_tokens: Mutex<HashMap<i64, CancellationToken>>,
+    /// Account runs in flight, keyed by (task id, account id); the flag turns
+    /// true once the run ends
+    account_runs: Mutex<HashMap<(i64, i64), watch::Receiver<bool>>>,
+}
+
+struct Entry {
+    id: u64,
+    label: String,
+    started_at: String,
+    handle: JoinHandle<()>,
+}
+
+/// Result of `claim_account_run`
+pub enum RunClaim {
+    /// No run of this account was in flight; the caller runs it and holds the
+    /// guard until the result is recorded
+    Owner(AccountRunGuard),
+    /// Another trigger of the same task is running this account; resolves
+    /// once that run ends
+    InFlight(watch::Receiver<bool>),
+    /// Another task is running this account, on the same Chrome profile
+    Busy { task_id: i64 },
+}
+
+/// Releases an account run claim on drop
+pub struct AccountRunGuard {
+    registry: TaskRegistry,
+    key: (i64, i64),
+    done: watch::Sender<bool>,
+}
+
+impl Drop for AccountRunGuard {
+    fn drop(&mut self) {
+        if let Ok(mut runs) = self.registry.inner.account_runs.lock() {
+            runs.remove(&self.key);
+        }
+        let _ = self.done.send(true);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackgroundTaskInfo {
+    pub id: u64,
+    pub label: String,
+    pub started_at: String,
+    pub finished: bool,
+}
+
+static GLOBAL: LazyLock<TaskRegistry> = LazyLock::new(TaskRegistry::default);
+
+/// The app-wide registry. Also managed as Tauri state; both are the same
+/// instance, this accessor is for code that has no `AppHandle`.
+pub fn global() -> &'static TaskRegistry {
+    &GLOBAL
+}
+
+impl TaskRegistry {
+    /// Spawn `task` on the Tauri runtime under `label`. Entries of tasks that
+    /// already finished are dropped here, so the list does not grow unbounded.
+    pub fn spawn<F>(&self, label: impl Into<String>, task: F) -> u64
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let handle = async_runtime::spawn(task);
+        if let Ok(mut entries) = self.inner.entries.lock() {
+            entries.retain(|entry| !entry.handle.inner().is_finished());
+            entries.push(Entry {
+                id,
+                label: label.into(),
+                started_at: chrono::Utc::now().to_rfc3339(),
+                handle,
+            });
+        }
+        id
+    }
+
+    pub fn list(&self) -> Vec<BackgroundTaskInfo> {
+        let Ok(entries) = self.inner.entries.lock() else {
+            return Vec::new();
+        };
+        entries
+            .iter()
+            .map(|entry| BackgroundTaskInfo {
+                id: entry.id,
+                label: entry.label.clone(),
+                started_at: entry.started_at.clone(),
+                finished: entry.handle.inner().is_finished(),
+            })
+            .collect()
+    }
+
+    /// Token a running publish task checks between automation steps. Dropped
+    /// with `finish_publish` when the task ends.
+    pub fn start_publish(&self, task_id: i64) -> CancellationToken {
+        let token = CancellationToken::new();
+        if let Ok(mut tokens) = self.inner.publish_tokens.lock() {
+            tokens.insert(task_id, token.clone());
+        }
+        token
+    }
+
+    pub fn finish_publish(&self, task_id: i64) {
+        if let Ok(mut tokens) = self.inner.publish_tokens.lock() {
+            tokens.remove(&task_id);
+        }
+    }
+
+    /// Cancel a running publish task; false when it is not running
+    pub fn cancel_publish(&self, task_id: i64) -> bool {
+        let Ok(tokens) = self.inner.publish_tokens.lock() else {
+            return false;
+        };
+        match tokens.get(&task_id) {
+            Some(token) => {
+                token.cancel();
+                info!(
+                    "[Tasks] cancellation requested for publish task {}",
+                    task_id
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Claim the run of one account in a publish task. A second trigger for the
+    /// same task and account gets the in-flight run to wait on, and a run of
+    /// the account in another task is reported, so a second Chrome session
+    /// never opens on the same profile.
+    pub fn claim_account_run(&self, task_id: i64, account_id: i64) -> RunClaim {
+        let key = (task_id, account_id);
+        let (done, watcher) = watch::channel(false);
+        if let Ok(mut runs) = self.inner.account_runs.lock() {
+            if let Some(running) = runs.get(&key) {
+                return RunClaim::InFlight(running.clone());
+            }
+            if let Some(&(other_task, _)) = runs.keys().find(|(_, id)| *id == account_id) {
+                return RunClaim::Busy {
+                    task_id: other_task,
+                };
+            }
+            runs.insert(key, watcher);
+        }
+        RunClaim::Owner(AccountRunGuard {
+            registry: self.clone(),
+            key,
+            done,
+        })
+    }
+
+    /// Abort every tracked task; used on app exit
+    pub fn abort_all(&self) {
+        let Ok(mut entries) = self.inner.entries.lock() else {
+            return;
+        };
+        let running = entries
+            .iter()
+            .filter(|entry| !entry.handle.inner().is_finished())
+            .count();
+        for entry in entries.drain(..) {
+            entry.handle.abort();
+        }
+        info!("[Tasks] aborted {} background task(s) on shutdown", running);
+    }
+}
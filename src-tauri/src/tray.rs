@@ -0,0 +1,215 @@
+use crate::database::{queries, Database};
+use crate::diagnostics::progress;
+use crate::platforms;
+use crate::scheduler::queue;
+use crate::tasks::TaskRegistry;
+use log::{info, warn};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Manager, Wry};
+
+const TRAY_ID: &str = "main";
+const MAIN_WINDOW: &str = "main";
+
+const MENU_OPEN: &str = "tray:open";
+const MENU_PAUSE: &str = "tray:pause";
+const MENU_CANCEL: &str = "tray:cancel";
+
+/// How often the tooltip follows the queue and running tasks
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// What the tray shows; the icon is only touched when this changes
+#[derive(Debug, Clone, PartialEq)]
+struct TrayState {
+    english: bool,
+    paused: bool,
+    queued: i64,
+    /// Platforms of the accounts being automated right now
+    publishing: Vec<String>,
+}
+
+/// Menu items whose labels depend on the state, managed so the menu handler
+/// can refresh them right after an action
+struct TrayMenu {
+    open: MenuItem<Wry>,
+    pause: MenuItem<Wry>,
+    cancel: MenuItem<Wry>,
+    shown: Mutex<Option<TrayState>>,
+}
+
+/// Tray icon with the queue length and the platform being published to in its
+/// tooltip, and menu items to open the app, pause the scheduled queue and
+/// cancel the running publish task. Meant for long unattended batches.
+pub fn init(app: &tauri::App) -> tauri::Result<()> {
+    let handle = app.handle();
+    let open = MenuItem::with_id(handle, MENU_OPEN, "", true, None::<&str>)?;
+    let pause = MenuItem::with_id(handle, MENU_PAUSE, "", true, None::<&str>)?;
+    let cancel = MenuItem::with_id(handle, MENU_CANCEL, "", false, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(handle)?;
+    let menu = Menu::with_items(handle, &[&open, &separator, &pause, &cancel])?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .on_menu_event(on_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+
+    app.manage(TrayMenu {
+        open,
+        pause,
+        cancel,
+        shown: Mutex::new(None),
+    });
+    refresh(handle);
+
+    let app = handle.clone();
+    let registry = app.state::<TaskRegistry>().inner().clone();
+    registry.spawn("tray:refresh", async move {
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            refresh(&app);
+        }
+    });
+    Ok(())
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        MENU_OPEN => {
+            if let Some(window) = app.get_webview_window(MAIN_WINDOW) {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        MENU_PAUSE => {
+            let paused = !queue::is_paused(app);
+            let db = app.state::<Database>();
+            let saved = match db.conn.lock() {
+                Ok(conn) => queries::set_setting(
+                    &conn,
+                    queue::SETTING_PAUSED,
+                    if paused { "true" } else { "false" },
+                ),
+                Err(e) => Err(anyhow::anyhow!(e.to_string())),
+            };
+            match saved {
+                Ok(()) if paused => info!("[Tray] scheduled queue paused"),
+                Ok(()) => info!("[Tray] scheduled queue resumed"),
+                Err(e) => warn!("[Tray] failed to toggle the scheduled queue: {}", e),
+            }
+        }
+        MENU_CANCEL => {
+            let registry = app.state::<TaskRegistry>();
+            for task_id in progress::running_task_ids() {
+                registry.cancel_publish(task_id);
+            }
+        }
+        _ => return,
+    }
+    refresh(app);
+}
+
+fn current_state(app: &AppHandle) -> TrayState {
+    let db = app.state::<Database>();
+    let (paused, queued) = db
+        .conn
+        .lock()
+        .map(|conn| {
+            (
+                queries::get_setting_bool(&conn, queue::SETTING_PAUSED, false).unwrap_or(false),
+                queries::count_pending_scheduled_tasks(&conn).unwrap_or(0),
+            )
+        })
+        .unwrap_or((false, 0));
+    let mut publishing = Vec::new();
+    for task in progress::running_task_ids()
+        .into_iter()
+        .filter_map(progress::snapshot)
+    {
+        for account in task.accounts {
+            if account.status == progress::STATUS_RUNNING && !publishing.contains(&account.platform)
+            {
+                publishing.push(account.platform);
+            }
+        }
+    }
+    TrayState {
+        english: platforms::english(),
+        paused,
+        queued,
+        publishing,
+    }
+}
+
+fn tooltip(state: &TrayState) -> String {
+    let names = state
+        .publishing
+        .iter()
+        .map(|platform| platforms::display_name(platform))
+        .collect::<Vec<_>>();
+    if state.english {
+        let paused = if state.paused { " (paused)" } else { "" };
+        let activity = if names.is_empty() {
+            "Idle".to_string()
+        } else {
+            format!("Publishing: {}", names.join(", "))
+        };
+        format!(
+            "MultiPublisher\nQueue: {} pending{}\n{}",
+            state.queued, paused, activity
+        )
+    } else {
+        let paused = if state.paused { "（已暂停）" } else { "" };
+        let activity = if names.is_empty() {
+            "空闲".to_string()
+        } else {
+            format!("正在发布：{}", names.join("、"))
+        };
+        format!(
+            "MultiPublisher\n队列：{} 个待发布{}\n{}",
+            state.queued, paused, activity
+        )
+    }
+}
+
+/// Bring the tooltip and menu labels in line with the queue and running tasks
+fn refresh(app: &AppHandle) {
+    let Some(menu) = app.try_state::<TrayMenu>() else {
+        return;
+    };
+    let state = current_state(app);
+    let Ok(mut shown) = menu.shown.lock() else {
+        return;
+    };
+    if shown.as_ref() == Some(&state) {
+        return;
+    }
+
+    let (open, pause, cancel) = match (state.english, state.paused) {
+        (true, true) => ("Open MultiPublisher", "Resume queue", "Cancel current task"),
+        (true, false) => ("Open MultiPublisher", "Pause queue", "Cancel current task"),
+        (false, true) => ("打开 MultiPublisher", "恢复队列", "取消当前任务"),
+        (false, false) => ("打开 MultiPublisher", "暂停队列", "取消当前任务"),
+    };
+    let applied = menu
+        .open
+        .set_text(open)
+        .and_then(|_| menu.pause.set_text(pause))
+        .and_then(|_| menu.cancel.set_text(cancel))
+        .and_then(|_| menu.cancel.set_enabled(!state.publishing.is_empty()))
+        .and_then(|_| match app.tray_by_id(TRAY_ID) {
+            Some(tray) => tray.set_tooltip(Some(tooltip(&state))),
+            None => Ok(()),
+        });
+    match applied {
+        Ok(()) => *shown = Some(state),
+        Err(e) => warn!("[Tray] failed to update the tray icon: {}", e),
+    }
+}
@@ -23,6 +23,11 @@ export function PlatformIcon({ platform, size = "md", className }: PlatformIconP
     bilibili: "B",
     wechat: "微",
     youtube: "YT",
+    kuaishou: "快",
+    zhihu: "知",
+    tiktok: "T",
+    weibo: "微",
+    instagram: "IG",
   };
 
   return (
@@ -24,6 +24,11 @@ const platformFilters: { id: PlatformType | "all"; label: string }[] = [
   { id: "wechat", label: "微信视频号" },
   { id: "bilibili", label: "哔哩哔哩" },
   { id: "youtube", label: "YouTube" },
+  { id: "kuaishou", label: "快手" },
+  { id: "zhihu", label: "知乎" },
+  { id: "tiktok", label: "TikTok" },
+  { id: "weibo", label: "微博" },
+  { id: "instagram", label: "Instagram" },
 ];
 
 export function Accounts() {
@@ -245,6 +250,11 @@ function AddAccountDialog({
     "bilibili",
     "wechat",
     "youtube",
+    "kuaishou",
+    "zhihu",
+    "tiktok",
+    "weibo",
+    "instagram",
   ];
 
   return (
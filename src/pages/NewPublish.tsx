@@ -1,4 +1,4 @@
-import { useState, useEffect, type KeyboardEvent } from "react";
+import { useState, useEffect, useRef, type KeyboardEvent } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { open as openDialog } from "@tauri-apps/plugin-dialog";
 import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
@@ -96,6 +96,19 @@ export function NewPublish() {
     };
   }, []);
 
+  // Warm up Chrome for newly selected accounts while the form is being filled in
+  const prewarmedIds = useRef(new Set<number>());
+  useEffect(() => {
+    if (!videoPath) return;
+    const pending = selectedAccountIds.filter((id) => !prewarmedIds.current.has(id));
+    if (pending.length === 0) return;
+    const timer = setTimeout(() => {
+      pending.forEach((id) => prewarmedIds.current.add(id));
+      invoke("prewarm_publish", { accountIds: pending }).catch(() => {});
+    }, 1500);
+    return () => clearTimeout(timer);
+  }, [videoPath, selectedAccountIds]);
+
   const handleClickUpload = async () => {
     const selected = await openDialog({
       multiple: false,
@@ -477,6 +490,10 @@ function platformStatusLabel(status: string): string {
   switch (status) {
     case "automated":
       return "自动化已完成";
+    case "published":
+      return "已自动发布";
+    case "cancelled":
+      return "已取消";
     case "launched":
       return "已打开 Chrome，需手动继续";
     case "failed":
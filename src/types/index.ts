@@ -1,5 +1,15 @@
 // Platform types
-export type PlatformType = "douyin" | "xiaohongshu" | "bilibili" | "wechat" | "youtube";
+export type PlatformType =
+  | "douyin"
+  | "xiaohongshu"
+  | "bilibili"
+  | "wechat"
+  | "youtube"
+  | "kuaishou"
+  | "zhihu"
+  | "tiktok"
+  | "weibo"
+  | "instagram";
 
 export interface PlatformInfo {
   id: PlatformType;
@@ -57,6 +67,51 @@ export const PLATFORMS: Record<PlatformType, PlatformInfo> = {
     creatorUrl: "https://studio.youtube.com",
     icon: "youtube",
   },
+  kuaishou: {
+    id: "kuaishou",
+    name: "快手",
+    nameEn: "Kuaishou",
+    color: "#ff4906",
+    bgColor: "#ff4906",
+    creatorUrl: "https://cp.kuaishou.com",
+    icon: "kuaishou",
+  },
+  zhihu: {
+    id: "zhihu",
+    name: "知乎",
+    nameEn: "Zhihu",
+    color: "#1772f6",
+    bgColor: "#1772f6",
+    creatorUrl: "https://www.zhihu.com/creator",
+    icon: "zhihu",
+  },
+  tiktok: {
+    id: "tiktok",
+    name: "TikTok",
+    nameEn: "TikTok",
+    color: "#010101",
+    bgColor: "#010101",
+    creatorUrl: "https://www.tiktok.com/tiktokstudio",
+    icon: "tiktok",
+  },
+  weibo: {
+    id: "weibo",
+    name: "微博",
+    nameEn: "Weibo",
+    color: "#e6162d",
+    bgColor: "#e6162d",
+    creatorUrl: "https://weibo.com/upload/channel",
+    icon: "weibo",
+  },
+  instagram: {
+    id: "instagram",
+    name: "Instagram",
+    nameEn: "Instagram",
+    color: "#e1306c",
+    bgColor: "#e1306c",
+    creatorUrl: "https://www.instagram.com/",
+    icon: "instagram",
+  },
 };
 
 // Account types
@@ -72,7 +127,7 @@ export interface Account {
 }
 
 // Publish task types
-export type TaskStatus = "pending" | "publishing" | "completed" | "partial" | "failed";
+export type TaskStatus = "pending" | "publishing" | "completed" | "partial" | "failed" | "cancelled";
 export type PlatformTaskStatus = "pending" | "uploading" | "filling" | "waiting_confirm" | "published" | "failed";
 
 export interface PublishTask {